@@ -1,7 +1,8 @@
 use anchor_lang::prelude::*;
-use crate::adapters::{dex_adapter::DexAdapter, raydium::RaydiumAdapter, whirlpool::WhirlpoolAdapter, meteora::MeteoraAdapter};
+use anchor_spl::token_interface::TokenAccount;
+use crate::adapters::{dex_adapter::DexAdapter, raydium, raydium::RaydiumAdapter, whirlpool, whirlpool::WhirlpoolAdapter, whirlpool_two_hop, whirlpool_two_hop::WhirlpoolTwoHopAdapter, meteora, meteora::MeteoraAdapter, wsol, wsol::WsolAdapter};
 use crate::errors::ErrorCode;
-use crate::state::{Swap, AdapterRegistry};
+use crate::state::{Swap, AdapterRegistry, AdapterStatus};
 
 // Context struct for passing account information to adapters
 #[derive(Clone)]
@@ -14,13 +15,38 @@ pub struct AdapterContext<'info> {
     pub program_id: Pubkey //for PDA calculation
 }
 
+impl<'info> AdapterContext<'info> {
+    // Checks input_account is a token account owned by the authority that signs the swap
+    // (a program vault or an order vault). Adapters move funds out of input_account with that
+    // signature, so a route plan must not be able to point it at anyone else's account.
+    // The program's lamport escrow, which WrapSol steps spend from, is accepted as well.
+    // # Returns
+    // * `Result<()>` - InvalidVaultOwner for a foreign or non-token input account
+    pub fn verify_input_owner(&self) -> Result<()> {
+        if wsol::is_sol_escrow(&self.input_account) {
+            return Ok(());
+        }
+        let data = self.input_account.try_borrow_data()?;
+        let input_token = TokenAccount::try_deserialize(&mut data.as_ref()).map_err(|_| ErrorCode::InvalidVaultOwner)?;
+        if input_token.owner != self.authority.key() {
+            msg!("Swap input {} is not owned by the vault authority", self.input_account.key());
+            return Err(ErrorCode::InvalidVaultOwner.into());
+        }
+        Ok(())
+    }
+}
+
 // Retrieves the appropriate adapter implementation based on the swap type
+// Paused adapters are rejected here so route, limit order and shared paths all skip them
 // # Arguments
 // * `swap` - The swap type (e.g., Raydium, Whirlpool)
 // * `registry` - The adapter registry account containing supported adapters
 // # Returns
-// * `Result<Box<dyn DexAdapter>>` - A boxed adapter implementing the DexAdapter trait
+// * `Result<Box<dyn DexAdapter>>` - A boxed adapter implementing the DexAdapter trait, or
+//   AdapterNotImplemented, AdapterNotConfigured or AdapterDisabled (see adapter_status)
 pub fn get_adapter(swap: &Swap, registry: &Account<AdapterRegistry>) -> Result<Box<dyn DexAdapter>> {
+    adapter_status(swap, registry).require_enabled(swap)?;
+
     match swap {
         Swap::Raydium => {
             // Initialize Raydium adapter with program ID
@@ -36,19 +62,89 @@ pub fn get_adapter(swap: &Swap, registry: &Account<AdapterRegistry>) -> Result<B
             let adapter = WhirlpoolAdapter {
                 program_id: registry.get_adapter_program_id(swap)?,
                 a_to_b: *a_to_b,
+                memo_program_id: registry.memo_program(),
             };
             // Validate CPI interface for security
             adapter.validate_cpi(&adapter.program_id)?;
             Ok(Box::new(adapter))
         }
+        Swap::WhirlpoolTwoHop { a_to_b_one, a_to_b_two } => {
+            // Initialize Whirlpool two-hop adapter with program ID and both directions
+            let adapter = WhirlpoolTwoHopAdapter {
+                program_id: registry.get_adapter_program_id(swap)?,
+                a_to_b_one: *a_to_b_one,
+                a_to_b_two: *a_to_b_two,
+                memo_program_id: registry.memo_program(),
+            };
+            adapter.validate_cpi(&adapter.program_id)?;
+            Ok(Box::new(adapter))
+        }
         Swap::Meteora => {
             let program_id = registry.get_adapter_program_id(swap)?;
             let adapter = MeteoraAdapter {
                 program_id,
+                memo_program_id: registry.memo_program(),
             };
             adapter.validate_cpi(&adapter.program_id)?;
             Ok(Box::new(adapter))
         }
-        _ => Err(ErrorCode::SwapNotSupported.into()), // Return error for unsupported swap types
+        Swap::WrapSol | Swap::UnwrapSol => {
+            // No external program: the registry entry must name the system program
+            let adapter = WsolAdapter {
+                program_id: anchor_lang::system_program::ID,
+                wrap: *swap == Swap::WrapSol,
+            };
+            adapter.validate_cpi(&registry.get_adapter_program_id(swap)?)?;
+            Ok(Box::new(adapter))
+        }
+        _ => Err(ErrorCode::AdapterNotImplemented.into()), // Return error for unsupported swap types
     }
-}
\ No newline at end of file
+}
+
+// Reports whether a swap type can be routed through, and if not, which part is missing:
+// the program has no adapter for it, the registry has no AdapterInfo, or it is paused
+// # Arguments
+// * `swap` - The swap type to describe
+// * `registry` - The adapter registry account containing supported adapters
+// # Returns
+// * `AdapterStatus` - NotImplemented, or the registry's AdapterRegistry::describe_adapter
+pub fn adapter_status(swap: &Swap, registry: &AdapterRegistry) -> AdapterStatus {
+    if adapter_min_accounts(swap).is_err() {
+        return AdapterStatus::NotImplemented;
+    }
+    registry.describe_adapter(swap)
+}
+
+// Returns the minimum number of adapter accounts (pool_info through the last fixed account)
+// required by the adapter for the given swap type
+// # Arguments
+// * `swap` - The swap type (e.g., Raydium, Whirlpool)
+// # Returns
+// * `Result<usize>` - Minimum adapter account count, or AdapterNotImplemented
+pub fn adapter_min_accounts(swap: &Swap) -> Result<usize> {
+    match swap {
+        Swap::Raydium => Ok(raydium::MIN_ACCOUNTS),
+        Swap::Whirlpool { .. } => Ok(whirlpool::MIN_ACCOUNTS),
+        Swap::WhirlpoolTwoHop { .. } => Ok(whirlpool_two_hop::MIN_ACCOUNTS),
+        Swap::Meteora => Ok(meteora::MIN_ACCOUNTS),
+        Swap::WrapSol | Swap::UnwrapSol => Ok(wsol::MIN_ACCOUNTS),
+        _ => Err(ErrorCode::AdapterNotImplemented.into()),
+    }
+}
+
+// Reads the two reserve token accounts a pool account names, in the pool's own order
+// # Arguments
+// * `swap` - The swap type (e.g., Raydium, Whirlpool)
+// * `pool_data` - Data of the pool account PoolInfo.pool_address names
+// # Returns
+// * `Result<(Pubkey, Pubkey)>` - The reserve accounts, InvalidPoolAddress for data the
+//   adapter can't read, or SwapNotSupported for swap types without pool reserves
+pub fn pool_reserve_accounts(swap: &Swap, pool_data: &[u8]) -> Result<(Pubkey, Pubkey)> {
+    let reserves = match swap {
+        Swap::Raydium => raydium::pool_state_vaults(pool_data),
+        Swap::Whirlpool { .. } | Swap::WhirlpoolTwoHop { .. } => whirlpool::whirlpool_token_vaults(pool_data),
+        Swap::Meteora => meteora::lb_pair_reserves(pool_data),
+        _ => return Err(ErrorCode::SwapNotSupported.into()),
+    };
+    reserves.ok_or_else(|| ErrorCode::InvalidPoolAddress.into())
+}
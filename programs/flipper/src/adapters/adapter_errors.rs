@@ -0,0 +1,130 @@
+use anchor_lang::prelude::*;
+use anchor_lang::solana_program::instruction::Instruction;
+use anchor_lang::solana_program::program::{get_return_data, invoke_signed, set_return_data};
+use crate::errors::ErrorCode;
+
+/// Downstream custom error code and the flipper error it is reported as
+pub type AdapterErrorTable = &'static [(u32, ErrorCode)];
+
+/// SPL Token InsufficientFunds, raised by the token program inside any DEX swap
+pub const TOKEN_INSUFFICIENT_FUNDS: u32 = 1;
+
+/// Raydium CPMM codes (mock_raydium ErrorCode order)
+pub const RAYDIUM_ERRORS: AdapterErrorTable = &[
+    (6000, ErrorCode::StepAmountTooSmall),        // ZeroAmount
+    (6001, ErrorCode::SlippageToleranceExceeded), // InsufficientOutputAmount
+    (6002, ErrorCode::InvalidCalculation),        // ArithmeticOverflow
+    (6003, ErrorCode::InvalidTokenProgram),       // InvalidTokenProgram
+];
+
+/// Whirlpool codes (mock_whirlpools ErrorCode order)
+pub const WHIRLPOOL_ERRORS: AdapterErrorTable = &[
+    (6000, ErrorCode::StepAmountTooSmall),        // ZeroAmount
+    (6001, ErrorCode::SlippageToleranceExceeded), // AmountOutBelowMinimum
+    (6002, ErrorCode::SlippageToleranceExceeded), // AmountInAboveMaximum
+    (6003, ErrorCode::InvalidCalculation),        // ArithmeticOverflow
+    (6004, ErrorCode::InvalidTokenProgram),       // InvalidTokenProgram
+];
+
+/// Meteora DLMM codes (mock_meteora ErrorCode order)
+pub const METEORA_ERRORS: AdapterErrorTable = &[
+    (6000, ErrorCode::StepAmountTooSmall),        // ZeroAmount
+    (6001, ErrorCode::SlippageToleranceExceeded), // InsufficientOutputAmount
+    (6002, ErrorCode::InvalidCalculation),        // ArithmeticOverflow
+    (6003, ErrorCode::InvalidPoolReserve),        // InvalidReserveX
+    (6004, ErrorCode::InvalidPoolReserve),        // InvalidReserveY
+    (6005, ErrorCode::InvalidMint),               // InvalidMint
+    (6007, ErrorCode::InvalidTokenProgram),       // InvalidTokenProgram
+    (6008, ErrorCode::SlippageToleranceExceeded), // AmountInAboveMaximum
+];
+
+/// Looks up a downstream custom code in an adapter's table
+///
+/// # Returns
+/// * `Option<ErrorCode>` - The mapped error; token InsufficientFunds maps for every adapter
+pub fn lookup_adapter_error(table: AdapterErrorTable, code: u32) -> Option<ErrorCode> {
+    if code == TOKEN_INSUFFICIENT_FUNDS {
+        return Some(ErrorCode::InsufficientFunds);
+    }
+    table
+        .iter()
+        .find(|(downstream, _)| *downstream == code)
+        .map(|(_, mapped)| *mapped)
+}
+
+/// Prefix of a failure report a DEX hands back as return data instead of failing outright
+///
+/// The runtime aborts the whole transaction when the invoked program itself fails, so its
+/// error code never reaches the adapter. A DEX may instead leave the swap undone and return
+/// this prefix followed by the little-endian u32 error code; the mock DEXes do so in their
+/// fail mode (`set_fail_mode`).
+pub const ADAPTER_FAILURE_TAG: [u8; 8] = *b"dexfail\0";
+
+/// Reads the error code of a failure report `program_id` returned, see ADAPTER_FAILURE_TAG
+///
+/// # Arguments
+/// * `return_data` - Return data left by the last CPI, as get_return_data reads it
+/// * `program_id` - Program the adapter invoked; reports of other programs are ignored
+pub fn reported_failure(return_data: Option<(Pubkey, Vec<u8>)>, program_id: &Pubkey) -> Option<u32> {
+    let (returned_by, data) = return_data?;
+    if returned_by != *program_id || data.len() != ADAPTER_FAILURE_TAG.len() + 4 || data[..8] != ADAPTER_FAILURE_TAG {
+        return None;
+    }
+    Some(u32::from_le_bytes(data[8..].try_into().ok()?))
+}
+
+/// Invokes an adapter's DEX instruction, mapping both the error invoke_signed returns and a
+/// failure report the DEX returns (see ADAPTER_FAILURE_TAG) through the adapter's table
+///
+/// # Arguments
+/// * `adapter` - Adapter name used in the log line
+/// * `table` - Known downstream codes for this adapter
+/// * `instruction` - The DEX instruction
+/// * `account_infos` - Accounts of the instruction
+/// * `signer_seeds` - Vault authority seeds
+pub fn invoke_adapter_cpi(
+    adapter: &str,
+    table: AdapterErrorTable,
+    instruction: &Instruction,
+    account_infos: &[AccountInfo],
+    signer_seeds: &[&[&[u8]]],
+) -> Result<()> {
+    // Clear what an earlier CPI returned so it is not read as this DEX's report
+    set_return_data(&[]);
+    invoke_signed(instruction, account_infos, signer_seeds).map_err(|err| map_adapter_error(adapter, table, err))?;
+    match reported_failure(get_return_data(), &instruction.program_id) {
+        Some(code) => Err(map_adapter_error(adapter, table, ProgramError::Custom(code))),
+        None => Ok(()),
+    }
+}
+
+/// Translates the error of an adapter's DEX CPI into a flipper error, logging the raw code
+///
+/// Only errors that come back to the adapter reach this: those `invoke_signed` returns
+/// (account and privilege checks) and failure reports (see ADAPTER_FAILURE_TAG). A DEX that
+/// fails outright aborts the transaction with its own error instead.
+///
+/// # Arguments
+/// * `adapter` - Adapter name used in the log line
+/// * `table` - Known downstream codes for this adapter
+/// * `err` - Error returned by invoke_signed, or the reported code as ProgramError::Custom
+///
+/// # Returns
+/// * `Error` - The mapped ErrorCode, or AdapterCpiFailed for unknown errors
+pub fn map_adapter_error(adapter: &str, table: AdapterErrorTable, err: ProgramError) -> Error {
+    let mapped = match err {
+        ProgramError::Custom(code) => lookup_adapter_error(table, code),
+        _ => None,
+    };
+
+    match mapped {
+        Some(code) => {
+            msg!("{} CPI failed: {:?}, reported as {:?}", adapter, err, code);
+            code.into()
+        }
+        None => {
+            msg!("{} CPI failed: {:?}", adapter, err);
+            ErrorCode::AdapterCpiFailed.into()
+        }
+    }
+}
@@ -0,0 +1,114 @@
+#[cfg(test)]
+mod tests {
+    use super::super::adapter_errors::*;
+    use crate::errors::ErrorCode;
+    use anchor_lang::prelude::*;
+
+    const TABLES: [(&str, AdapterErrorTable); 3] = [
+        ("Raydium", RAYDIUM_ERRORS),
+        ("Whirlpool", WHIRLPOOL_ERRORS),
+        ("Meteora", METEORA_ERRORS),
+    ];
+
+    #[test]
+    fn test_every_table_entry_maps_to_its_error() {
+        for (adapter, table) in TABLES {
+            for (code, expected) in table.iter() {
+                let err = map_adapter_error(adapter, table, ProgramError::Custom(*code));
+                assert_eq!(err, (*expected).into(), "{} code {}", adapter, code);
+            }
+        }
+    }
+
+    #[test]
+    fn test_slippage_codes_map_to_slippage_error() {
+        let slippage: Error = ErrorCode::SlippageToleranceExceeded.into();
+        assert_eq!(map_adapter_error("Raydium", RAYDIUM_ERRORS, ProgramError::Custom(6001)), slippage);
+        assert_eq!(map_adapter_error("Whirlpool", WHIRLPOOL_ERRORS, ProgramError::Custom(6001)), slippage);
+        assert_eq!(map_adapter_error("Whirlpool", WHIRLPOOL_ERRORS, ProgramError::Custom(6002)), slippage);
+        assert_eq!(map_adapter_error("Meteora", METEORA_ERRORS, ProgramError::Custom(6001)), slippage);
+        assert_eq!(map_adapter_error("Meteora", METEORA_ERRORS, ProgramError::Custom(6008)), slippage);
+    }
+
+    #[test]
+    fn test_meteora_reserve_errors_map_to_invalid_pool_reserve() {
+        for code in [6003, 6004] {
+            let err = map_adapter_error("Meteora", METEORA_ERRORS, ProgramError::Custom(code));
+            assert_eq!(err, ErrorCode::InvalidPoolReserve.into());
+        }
+    }
+
+    #[test]
+    fn test_token_insufficient_funds_maps_for_every_adapter() {
+        for (adapter, table) in TABLES {
+            let err = map_adapter_error(adapter, table, ProgramError::Custom(TOKEN_INSUFFICIENT_FUNDS));
+            assert_eq!(err, ErrorCode::InsufficientFunds.into());
+        }
+    }
+
+    #[test]
+    fn test_unknown_errors_fall_back_to_adapter_cpi_failed() {
+        for (adapter, table) in TABLES {
+            for err in [
+                ProgramError::Custom(6999),
+                ProgramError::Custom(0),
+                ProgramError::MissingRequiredSignature,
+                ProgramError::InvalidAccountData,
+            ] {
+                let mapped = map_adapter_error(adapter, table, err);
+                assert_eq!(mapped, ErrorCode::AdapterCpiFailed.into());
+            }
+        }
+    }
+
+    #[test]
+    fn test_codes_are_adapter_specific() {
+        // Meteora 6003 is a reserve error; Raydium 6003 is a token program error
+        assert_eq!(lookup_adapter_error(METEORA_ERRORS, 6003).map(|c| c as u32), Some(ErrorCode::InvalidPoolReserve as u32));
+        assert_eq!(lookup_adapter_error(RAYDIUM_ERRORS, 6003).map(|c| c as u32), Some(ErrorCode::InvalidTokenProgram as u32));
+        assert!(lookup_adapter_error(RAYDIUM_ERRORS, 6004).is_none());
+    }
+
+    #[test]
+    fn test_tables_have_unique_codes() {
+        for (adapter, table) in TABLES {
+            for (idx, (code, _)) in table.iter().enumerate() {
+                assert!(
+                    table[idx + 1..].iter().all(|(other, _)| other != code),
+                    "{} maps code {} twice",
+                    adapter,
+                    code
+                );
+                assert_ne!(*code, TOKEN_INSUFFICIENT_FUNDS);
+            }
+        }
+    }
+
+    fn failure_report(code: u32) -> Vec<u8> {
+        let mut report = ADAPTER_FAILURE_TAG.to_vec();
+        report.extend_from_slice(&code.to_le_bytes());
+        report
+    }
+
+    #[test]
+    fn test_reported_failure_reads_the_invoked_programs_code() {
+        let dex = Pubkey::new_unique();
+        assert_eq!(reported_failure(Some((dex, failure_report(6001))), &dex), Some(6001));
+        assert_eq!(reported_failure(Some((dex, failure_report(TOKEN_INSUFFICIENT_FUNDS))), &dex), Some(1));
+    }
+
+    #[test]
+    fn test_reported_failure_ignores_other_return_data() {
+        let dex = Pubkey::new_unique();
+        // No return data, another program's report, or data that is not a report
+        assert_eq!(reported_failure(None, &dex), None);
+        assert_eq!(reported_failure(Some((Pubkey::new_unique(), failure_report(6001))), &dex), None);
+        assert_eq!(reported_failure(Some((dex, 6001u64.to_le_bytes().to_vec())), &dex), None);
+        let mut wrong_tag = failure_report(6001);
+        wrong_tag[0] ^= 1;
+        assert_eq!(reported_failure(Some((dex, wrong_tag)), &dex), None);
+        let mut trailing = failure_report(6001);
+        trailing.push(0);
+        assert_eq!(reported_failure(Some((dex, trailing)), &dex), None);
+    }
+}
@@ -1,5 +1,7 @@
 use anchor_lang::prelude::*;
+use anchor_spl::token_interface::TokenAccount;
 use crate::adapters::adapter_connector_module::AdapterContext;
+use crate::errors::ErrorCode;
 use crate::state::SwapResult;
 
 // Trait defining the interface for decentralized exchange (DEX) adapters
@@ -41,4 +43,48 @@ pub trait DexAdapter {
     // # Returns
     // * `Result<()>` - Ok if valid, error if mismatched
     fn validate_cpi(&self, program_id: &Pubkey) -> Result<()>;
+
+    // Reads the pool's reserve of the token being swapped in, used to estimate price impact
+    // # Arguments
+    // * `ctx` - Context containing account references for the swap
+    // * `remaining_accounts_start_index` - Index for accessing remaining accounts
+    // * `remaining_accounts_count` - Number of accounts available for this adapter
+    // # Returns
+    // * `Result<u64>` - Balance of the pool vault holding the input token
+    fn input_reserve<'info>(
+        &self,
+        ctx: AdapterContext<'info>,
+        remaining_accounts_start_index: usize,
+        remaining_accounts_count: usize,
+    ) -> Result<u64>;
+
+    // Estimates the output of swapping `amount` from the pool's current vault balances,
+    // as for a constant-product pool (see math::constant_product_out); fees are ignored.
+    // Concentrated liquidity pools (Whirlpool, Meteora DLMM) price from their active tick or
+    // bin, not their vault balances, so they return None and the route is not quoted
+    // # Arguments
+    // * `ctx` - Context containing account references for the swap
+    // * `amount` - Input token amount to quote
+    // * `remaining_accounts_start_index` - Index for accessing remaining accounts
+    // * `remaining_accounts_count` - Number of accounts available for this adapter
+    // # Returns
+    // * `Result<Option<u64>>` - Estimated output amount, None if the pool cannot be quoted
+    fn quote<'info>(
+        &self,
+        ctx: AdapterContext<'info>,
+        amount: u64,
+        remaining_accounts_start_index: usize,
+        remaining_accounts_count: usize,
+    ) -> Result<Option<u64>>;
+}
+
+// Reads the balance of a pool vault
+// # Arguments
+// * `vault` - Token account owned by the pool
+// # Returns
+// * `Result<u64>` - Token amount held by the vault
+pub fn pool_vault_amount(vault: &AccountInfo) -> Result<u64> {
+    let vault_data = TokenAccount::try_deserialize(&mut vault.data.borrow().as_ref())
+        .map_err(|_| ErrorCode::InvalidAccount)?;
+    Ok(vault_data.amount)
 }
\ No newline at end of file
@@ -127,4 +127,144 @@ mod tests {
         let result = SwapResult { output_amount: large_amount };
         assert_eq!(result.output_amount, u64::MAX - 1);
     }
+
+    #[test]
+    fn test_raydium_validate_authority_accepts_pda() {
+        use crate::adapters::raydium::RaydiumAdapter;
+        use anchor_lang::prelude::Pubkey;
+
+        let adapter = RaydiumAdapter { program_id: Pubkey::new_unique() };
+        let (authority, _) = Pubkey::find_program_address(
+            &[b"vault_and_lp_mint_auth_seed"],
+            &adapter.program_id,
+        );
+
+        assert!(adapter.validate_authority(&authority).is_ok());
+    }
+
+    #[test]
+    fn test_raydium_validate_authority_rejects_forged_authority() {
+        use crate::adapters::raydium::RaydiumAdapter;
+        use crate::errors::ErrorCode;
+        use anchor_lang::prelude::Pubkey;
+
+        let adapter = RaydiumAdapter { program_id: Pubkey::new_unique() };
+
+        // Same seed under another program, and an arbitrary key
+        let (foreign_pda, _) = Pubkey::find_program_address(
+            &[b"vault_and_lp_mint_auth_seed"],
+            &Pubkey::new_unique(),
+        );
+        for forged in [foreign_pda, Pubkey::new_unique()] {
+            let err = adapter.validate_authority(&forged).unwrap_err();
+            assert_eq!(err, ErrorCode::InvalidCpiInterface.into());
+        }
+    }
+
+    #[test]
+    fn test_raydium_validate_amm_config_owner() {
+        use crate::adapters::raydium::RaydiumAdapter;
+        use crate::errors::ErrorCode;
+        use anchor_lang::prelude::Pubkey;
+
+        let adapter = RaydiumAdapter { program_id: Pubkey::new_unique() };
+
+        assert!(adapter.validate_amm_config_owner(&adapter.program_id).is_ok());
+
+        let err = adapter.validate_amm_config_owner(&Pubkey::default()).unwrap_err();
+        assert_eq!(err, ErrorCode::InvalidCpiInterface.into());
+    }
+
+    #[test]
+    fn test_whirlpool_validate_memo_program() {
+        use crate::adapters::whirlpool::WhirlpoolAdapter;
+        use crate::errors::ErrorCode;
+        use crate::state::SPL_MEMO_PROGRAM_ID;
+        use anchor_lang::prelude::Pubkey;
+
+        let adapter = WhirlpoolAdapter {
+            program_id: Pubkey::new_unique(),
+            a_to_b: true,
+            memo_program_id: SPL_MEMO_PROGRAM_ID,
+        };
+
+        assert!(adapter.validate_memo_program(&SPL_MEMO_PROGRAM_ID).is_ok());
+
+        // Default pubkey and arbitrary programs are rejected
+        for wrong in [Pubkey::default(), Pubkey::new_unique(), adapter.program_id] {
+            let err = adapter.validate_memo_program(&wrong).unwrap_err();
+            assert_eq!(err, ErrorCode::InvalidCpiInterface.into());
+        }
+    }
+
+    #[test]
+    fn test_meteora_validate_memo_program_uses_configured_id() {
+        use crate::adapters::meteora::MeteoraAdapter;
+        use crate::errors::ErrorCode;
+        use crate::state::SPL_MEMO_PROGRAM_ID;
+        use anchor_lang::prelude::Pubkey;
+
+        // Fork with its own memo program configured on the registry
+        let fork_memo = Pubkey::new_unique();
+        let adapter = MeteoraAdapter {
+            program_id: Pubkey::new_unique(),
+            memo_program_id: fork_memo,
+        };
+
+        assert!(adapter.validate_memo_program(&fork_memo).is_ok());
+
+        let err = adapter.validate_memo_program(&SPL_MEMO_PROGRAM_ID).unwrap_err();
+        assert_eq!(err, ErrorCode::InvalidCpiInterface.into());
+    }
+
+    #[test]
+    fn test_meteora_validate_event_authority() {
+        use crate::adapters::meteora::MeteoraAdapter;
+        use crate::errors::ErrorCode;
+        use crate::state::SPL_MEMO_PROGRAM_ID;
+        use anchor_lang::prelude::Pubkey;
+
+        let adapter = MeteoraAdapter {
+            program_id: Pubkey::new_unique(),
+            memo_program_id: SPL_MEMO_PROGRAM_ID,
+        };
+        let (event_authority, _) = Pubkey::find_program_address(
+            &[b"__event_authority"],
+            &adapter.program_id,
+        );
+
+        assert!(adapter.validate_event_authority(&event_authority).is_ok());
+
+        // Same seed under another program, and an arbitrary key
+        let (foreign_pda, _) = Pubkey::find_program_address(
+            &[b"__event_authority"],
+            &Pubkey::new_unique(),
+        );
+        for wrong in [foreign_pda, Pubkey::new_unique()] {
+            let err = adapter.validate_event_authority(&wrong).unwrap_err();
+            assert_eq!(err, ErrorCode::InvalidCpiInterface.into());
+        }
+    }
+
+    #[test]
+    fn test_verify_input_owner_requires_vault_authority() {
+        use crate::errors::ErrorCode;
+        use crate::test_utils::*;
+        use anchor_lang::prelude::Pubkey;
+
+        let mint = Pubkey::new_unique();
+        let (vault_authority, _) = Pubkey::find_program_address(&[b"vault_authority"], &crate::ID);
+        let output = || token_account(Pubkey::new_unique(), vault_authority, 0, anchor_spl::token::ID);
+
+        let ctx = adapter_context(token_account(mint, vault_authority, 1_000, anchor_spl::token::ID), output(), vec![]);
+        assert!(ctx.verify_input_owner().is_ok());
+
+        // A user's token account named as the step input
+        let ctx = adapter_context(token_account(mint, Pubkey::new_unique(), 1_000, anchor_spl::token::ID), output(), vec![]);
+        assert_eq!(ctx.verify_input_owner().unwrap_err(), ErrorCode::InvalidVaultOwner.into());
+
+        // Not a token account at all
+        let ctx = adapter_context(empty_account(), output(), vec![]);
+        assert_eq!(ctx.verify_input_owner().unwrap_err(), ErrorCode::InvalidVaultOwner.into());
+    }
 }
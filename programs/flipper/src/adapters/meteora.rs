@@ -1,15 +1,16 @@
 use anchor_lang::prelude::*;
 use anchor_lang::solana_program::instruction::Instruction;
-use anchor_lang::solana_program::program::invoke_signed;
 use anchor_spl::token_interface::TokenAccount;
-use crate::adapters::dex_adapter::DexAdapter;
+use crate::adapters::dex_adapter::{DexAdapter, pool_vault_amount};
 use crate::adapters::adapter_connector_module::{AdapterContext};
+use crate::adapters::adapter_errors::{self, METEORA_ERRORS};
 use crate::errors::ErrorCode;
 use crate::state::{Swap, SwapEvent, SwapResult, PoolInfo};
 
 /// Adapter for interacting with the Meteora DLMM protocol
 pub struct MeteoraAdapter {
     pub program_id: Pubkey, // Meteora program ID for CPI calls
+    pub memo_program_id: Pubkey, // Memo program expected by swap2, from the registry
 }
 
 // Program IDs from anchor-spl
@@ -50,6 +51,133 @@ pub enum AccountsType {
     TransferHookMultiReward(u8),
 }
 
+/// Minimum adapter accounts for a Meteora swap2 (pool_info through program, with memo_program)
+pub const MIN_ACCOUNTS: usize = 16;
+
+/// Index of the lb_pair in the adapter accounts
+pub const POOL_ACCOUNT_INDEX: usize = 1;
+
+/// Maximum bin arrays forwarded to swap2
+pub const MAX_BIN_ARRAYS: usize = 5;
+
+/// Index of the first bin array in the adapter accounts (after the program at index 15)
+pub const BIN_ARRAYS_START: usize = 16;
+
+/// Index of the optional host_fee_in account in the adapter accounts
+/// Passing the Meteora program id in this slot means no host fee (Anchor's convention for a missing optional account)
+pub const HOST_FEE_IN_INDEX: usize = 10;
+
+impl MeteoraAdapter {
+    /// Validates the memo_program account so memo-required Token-2022 transfers don't fail inside the DEX
+    pub fn validate_memo_program(&self, memo_program: &Pubkey) -> Result<()> {
+        if *memo_program != self.memo_program_id {
+            return Err(ErrorCode::InvalidCpiInterface.into());
+        }
+        Ok(())
+    }
+
+    /// Validates the event authority is the Anchor event CPI PDA ["__event_authority"] of the Meteora program
+    pub fn validate_event_authority(&self, event_authority: &Pubkey) -> Result<()> {
+        let expected_event_authority = Pubkey::find_program_address(
+            &[b"__event_authority"],
+            &self.program_id,
+        ).0;
+
+        if *event_authority != expected_event_authority {
+            return Err(ErrorCode::InvalidCpiInterface.into());
+        }
+        Ok(())
+    }
+
+    /// Validates the oracle is the ["oracle", lb_pair] PDA of the Meteora program,
+    /// so a mismatched oracle fails here instead of inside swap2 after the route has spent CU
+    pub fn validate_oracle(&self, oracle: &Pubkey, lb_pair: &Pubkey) -> Result<()> {
+        let expected_oracle = Pubkey::find_program_address(
+            &[b"oracle", lb_pair.as_ref()],
+            &self.program_id,
+        ).0;
+
+        if *oracle != expected_oracle {
+            return Err(ErrorCode::InvalidOracleAccount.into());
+        }
+        Ok(())
+    }
+
+    /// Counts the bin arrays passed after the fixed accounts (at most MAX_BIN_ARRAYS)
+    /// adapter_accounts[0] = Pool Info, [1-15] = swap2 accounts ending with the program,
+    /// then bin arrays, optionally closed by a second program_id marker
+    pub fn bin_arrays_count(&self, adapter_accounts: &[AccountInfo]) -> u8 {
+        let trailing = adapter_accounts.get(BIN_ARRAYS_START..).unwrap_or(&[]);
+        // Bin arrays end at the second program_id; without it every trailing account is a bin array
+        let count = trailing
+            .iter()
+            .position(|account| account.key() == self.program_id)
+            .unwrap_or(trailing.len());
+        count.min(MAX_BIN_ARRAYS) as u8
+    }
+
+    /// Returns the host fee account, or None if the host_fee_in slot holds the Meteora program id
+    pub fn host_fee_account<'a, 'info>(&self, adapter_accounts: &'a [AccountInfo<'info>]) -> Option<&'a AccountInfo<'info>> {
+        let host_fee_in = &adapter_accounts[HOST_FEE_IN_INDEX];
+        if host_fee_in.key() == self.program_id {
+            None
+        } else {
+            Some(host_fee_in)
+        }
+    }
+
+    /// Validates the host fee account is a token account of the input mint owned by the vault authority,
+    /// so the host share of LP fees accrues to the protocol
+    pub fn validate_host_fee_account(
+        &self,
+        host_fee_in: &AccountInfo,
+        input_mint: &Pubkey,
+        vault_authority: &Pubkey,
+    ) -> Result<()> {
+        if ![TOKEN_PROGRAM_ID, TOKEN_2022_PROGRAM_ID].contains(host_fee_in.owner) {
+            return Err(ErrorCode::InvalidAccount.into());
+        }
+        let host_fee_data = TokenAccount::try_deserialize(&mut host_fee_in.data.borrow().as_ref())
+            .map_err(|_| ErrorCode::InvalidAccount)?;
+        if host_fee_data.mint != *input_mint {
+            return Err(ErrorCode::InvalidMint.into());
+        }
+        if host_fee_data.owner != *vault_authority {
+            return Err(ErrorCode::InvalidVaultOwner.into());
+        }
+        Ok(())
+    }
+}
+
+/// Discriminator of Meteora LbPair accounts (sha256("account:LbPair")[..8])
+pub const LB_PAIR_DISCRIMINATOR: [u8; 8] = [33, 11, 49, 98, 181, 101, 177, 13];
+
+/// Size of an LbPair account on the Meteora DLMM program
+pub const LB_PAIR_LEN: usize = 904;
+
+/// Offsets of reserve_x and reserve_y in an LbPair account on the Meteora DLMM program
+pub const LB_PAIR_RESERVE_X_OFFSET: usize = 152;
+pub const LB_PAIR_RESERVE_Y_OFFSET: usize = 184;
+
+/// Reads (reserve_x, reserve_y) from an lb_pair account
+///
+/// Meteora LbPair accounts store the reserves after the pair parameters and mints.
+/// Smaller LbPair accounts (the local mock program) store them first.
+/// Returns None for data that is not an LbPair.
+pub fn lb_pair_reserves(data: &[u8]) -> Option<(Pubkey, Pubkey)> {
+    if data.get(..8)? != LB_PAIR_DISCRIMINATOR {
+        return None;
+    }
+    let (offset_x, offset_y) = if data.len() >= LB_PAIR_LEN {
+        (LB_PAIR_RESERVE_X_OFFSET, LB_PAIR_RESERVE_Y_OFFSET)
+    } else {
+        (8, 8 + 32)
+    };
+    let reserve_x: [u8; 32] = data.get(offset_x..offset_x + 32)?.try_into().ok()?;
+    let reserve_y: [u8; 32] = data.get(offset_y..offset_y + 32)?.try_into().ok()?;
+    Some((Pubkey::new_from_array(reserve_x), Pubkey::new_from_array(reserve_y)))
+}
+
 impl DexAdapter for MeteoraAdapter {
     /// Execute a swap through Meteora DLMM protocol using swap2 instruction
     fn execute_swap(
@@ -63,7 +191,6 @@ impl DexAdapter for MeteoraAdapter {
         msg!("Meteora adapter: start_index={}, count={}, total_remaining={}", 
              remaining_accounts_start_index, remaining_accounts_count, ctx.remaining_accounts.len());
 
-        const MIN_ACCOUNTS: usize = 16; // swap2 имеет 16 основных аккаунтов (с memo_program)
         // Ensure minimum required accounts are present
         if remaining_accounts_count < MIN_ACCOUNTS {
             msg!("Error: Not enough accounts. Required: {}, Got: {}", MIN_ACCOUNTS, remaining_accounts_count);
@@ -81,41 +208,12 @@ impl DexAdapter for MeteoraAdapter {
         msg!("Meteora adapter: adapter_accounts length={}", adapter_accounts.len());
 
         // Validate pool is enabled
-        let pool_info = Account::<PoolInfo>::try_from(&adapter_accounts[0])?;
+        let pool_info = PoolInfo::load_checked(&adapter_accounts[0])?;
         if !pool_info.enabled {
             return Err(ErrorCode::PoolDisabled.into());
         }
 
-        // Calculate number of bin arrays available (maximum 5 for Meteora, optional)
-        // adapter_accounts[0] = Pool Info (not used in instruction)
-        // adapter_accounts[1-15] = Basic Meteora accounts (15 accounts, but we use ctx.input_account and ctx.output_account)
-        // In instruction: 16 basic accounts (lb_pair through program)
-        // adapter_accounts[15] = Program (first program_id)
-        // adapter_accounts[16-N] = Bin arrays (between two program_id)
-        // adapter_accounts[N+1] = Program ID (second program_id) - marks end of bin arrays
-        // adapter_accounts[N+2] = Output Vault (not included in adapter_accounts)
-        const MAX_BIN_ARRAYS: usize = 5;
-        const BIN_ARRAYS_START: usize = 16; // Bin arrays start at index 16 in adapter_accounts (after first program at index 15)
-        const PROGRAM_INDEX: usize = 15; // First program_id is at index 15
-        
-        // Find the second program_id to determine where bin arrays end
-        // Bin arrays are between two program_id accounts
-        let mut bin_arrays_count = 0;
-        if remaining_accounts_count > BIN_ARRAYS_START {
-            // Look for second program_id starting from BIN_ARRAYS_START
-            for i in BIN_ARRAYS_START..adapter_accounts.len() {
-                if adapter_accounts[i].key() == self.program_id {
-                    // Found second program_id, bin arrays are between first and second program_id
-                    bin_arrays_count = (i - BIN_ARRAYS_START).min(MAX_BIN_ARRAYS) as u8;
-                    break;
-                }
-            }
-            // If no second program_id found, assume all accounts after first program are bin arrays
-            // (but limit to MAX_BIN_ARRAYS)
-            if bin_arrays_count == 0 && remaining_accounts_count > BIN_ARRAYS_START {
-                bin_arrays_count = ((remaining_accounts_count - BIN_ARRAYS_START).min(MAX_BIN_ARRAYS)) as u8;
-            }
-        }
+        let bin_arrays_count = self.bin_arrays_count(adapter_accounts);
         
         msg!("Meteora adapter: bin_arrays_count={}, BIN_ARRAYS_START={}, adapter_accounts.len()={}", 
              bin_arrays_count, BIN_ARRAYS_START, adapter_accounts.len());
@@ -124,6 +222,18 @@ impl DexAdapter for MeteoraAdapter {
         let output_vault_data = TokenAccount::try_deserialize(&mut ctx.output_account.data.borrow().as_ref())?;
         let initial_output_amount = output_vault_data.amount;
 
+        // Record initial host fee balance to report the host fee earned by this swap
+        let host_fee_in = self.host_fee_account(adapter_accounts);
+        let initial_host_fee_amount = match host_fee_in {
+            Some(account) => TokenAccount::try_deserialize(&mut account.data.borrow().as_ref())?.amount,
+            None => 0,
+        };
+        // A missing host fee account is passed as the readonly program id
+        let host_fee_in_meta = match host_fee_in {
+            Some(account) => AccountMeta::new(account.key(), false),
+            None => AccountMeta::new_readonly(self.program_id, false),
+        };
+
         // Build remaining_accounts_info for bin arrays
         // For swap2, bin arrays are passed through remaining_accounts_info
         let remaining_accounts_info = RemainingAccountsInfo {
@@ -171,7 +281,7 @@ impl DexAdapter for MeteoraAdapter {
             AccountMeta::new_readonly(adapter_accounts[7].key(), false), // token_x_mint
             AccountMeta::new_readonly(adapter_accounts[8].key(), false), // token_y_mint
             AccountMeta::new(adapter_accounts[9].key(), false),       // oracle
-            AccountMeta::new(adapter_accounts[10].key(), false),       // host_fee_in
+            host_fee_in_meta,                                          // host_fee_in (optional)
             AccountMeta::new_readonly(ctx.authority.key(), true),      // user (signer)
             AccountMeta::new_readonly(adapter_accounts[11].key(), false), // token_x_program
             AccountMeta::new_readonly(adapter_accounts[12].key(), false), // token_y_program
@@ -261,7 +371,7 @@ impl DexAdapter for MeteoraAdapter {
         let authority_seeds: &[&[u8]] = &[b"vault_authority", &[vault_authority_bump]];
         let signer_seeds: &[&[&[u8]]] = &[authority_seeds];
 
-        invoke_signed(&instruction, &account_infos, signer_seeds)?;
+        adapter_errors::invoke_adapter_cpi("Meteora", METEORA_ERRORS, &instruction, &account_infos, signer_seeds)?;
 
         // Calculate output amount by checking balance difference
         let output_vault_data = TokenAccount::try_deserialize(&mut ctx.output_account.data.borrow().as_ref())?;
@@ -269,9 +379,17 @@ impl DexAdapter for MeteoraAdapter {
             .checked_sub(initial_output_amount)
             .ok_or(ErrorCode::InvalidCalculation)?;
 
-        msg!("Meteora swap2 completed, output amount: {}", output_amount);
+        let host_fee_amount = match host_fee_in {
+            Some(account) => TokenAccount::try_deserialize(&mut account.data.borrow().as_ref())?
+                .amount
+                .checked_sub(initial_host_fee_amount)
+                .ok_or(ErrorCode::InvalidCalculation)?,
+            None => 0,
+        };
+
+        msg!("Meteora swap2 completed, output amount: {}, host fee: {}", output_amount, host_fee_amount);
 
-        Ok(SwapResult { output_amount })
+        Ok(SwapResult { output_amount, host_fee_amount })
     }
 
     /// Validate that all required accounts are provided and valid
@@ -282,8 +400,6 @@ impl DexAdapter for MeteoraAdapter {
         remaining_accounts_count: usize,
     ) -> Result<()> {
 
-        const MIN_ACCOUNTS: usize = 16; // swap2 имеет 16 основных аккаунтов (с memo_program)
-
         // Ensure minimum required accounts are present
         if remaining_accounts_count < MIN_ACCOUNTS {
             return Err(ErrorCode::NotEnoughAccountKeys.into());
@@ -298,12 +414,12 @@ impl DexAdapter for MeteoraAdapter {
         let adapter_accounts = &ctx.remaining_accounts[remaining_accounts_start_index..end_index];
 
         // Validate pool is enabled and matches expected address
-        let pool_info = Account::<PoolInfo>::try_from(&adapter_accounts[0])?;
+        let pool_info = PoolInfo::load_checked(&adapter_accounts[0])?;
         if !pool_info.enabled {
             return Err(ErrorCode::PoolDisabled.into());
         }
 
-        let lb_pair = &adapter_accounts[1];
+        let lb_pair = &adapter_accounts[POOL_ACCOUNT_INDEX];
         if pool_info.pool_address != lb_pair.key() {
             return Err(ErrorCode::InvalidPoolAddress.into());
         }
@@ -324,6 +440,20 @@ impl DexAdapter for MeteoraAdapter {
             return Err(ErrorCode::InvalidCpiInterface.into());
         }
 
+        // Validate the oracle (index 9) belongs to lb_pair
+        self.validate_oracle(&adapter_accounts[9].key(), &lb_pair.key())?;
+
+        // Validate memo program (index 13) and event authority (index 14) forwarded to swap2
+        self.validate_memo_program(&adapter_accounts[13].key())?;
+        self.validate_event_authority(&adapter_accounts[14].key())?;
+
+        // Validate the optional host fee account (index 10)
+        if let Some(host_fee_in) = self.host_fee_account(adapter_accounts) {
+            let input_vault = TokenAccount::try_deserialize(&mut ctx.input_account.data.borrow().as_ref())
+                .map_err(|_| ErrorCode::InvalidAccount)?;
+            self.validate_host_fee_account(host_fee_in, &input_vault.mint, &ctx.authority.key())?;
+        }
+
         // Ensure critical accounts are not default (empty) pubkeys
         if lb_pair.key() == Pubkey::default() {
             return Err(ErrorCode::InvalidAccount.into());
@@ -339,4 +469,43 @@ impl DexAdapter for MeteoraAdapter {
         }
         Ok(())
     }
-}
\ No newline at end of file
+
+    /// Reads the pool vault holding the input token (reserve_x at index 3, reserve_y at index 4)
+    fn input_reserve(
+        &self,
+        ctx: AdapterContext,
+        remaining_accounts_start_index: usize,
+        remaining_accounts_count: usize,
+    ) -> Result<u64> {
+        if remaining_accounts_count < MIN_ACCOUNTS {
+            return Err(ErrorCode::NotEnoughAccountKeys.into());
+        }
+
+        let end_index = remaining_accounts_start_index + remaining_accounts_count;
+        if ctx.remaining_accounts.len() < end_index {
+            return Err(ErrorCode::NotEnoughAccountKeys.into());
+        }
+
+        let adapter_accounts = &ctx.remaining_accounts[remaining_accounts_start_index..end_index];
+
+        // Swaps from X when the input vault holds token_x_mint (index 7)
+        let input_mint = TokenAccount::try_deserialize(&mut ctx.input_account.data.borrow().as_ref())?.mint;
+        if input_mint == adapter_accounts[7].key() {
+            pool_vault_amount(&adapter_accounts[3])
+        } else {
+            pool_vault_amount(&adapter_accounts[4])
+        }
+    }
+
+    /// Not quoted: a DLMM pair prices from its active bin, which its reserves do not reflect,
+    /// so routes through it skip the quote sanity check
+    fn quote(
+        &self,
+        _ctx: AdapterContext,
+        _amount: u64,
+        _remaining_accounts_start_index: usize,
+        _remaining_accounts_count: usize,
+    ) -> Result<Option<u64>> {
+        Ok(None)
+    }
+}
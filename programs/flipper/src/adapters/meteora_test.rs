@@ -0,0 +1,328 @@
+#[cfg(test)]
+mod tests {
+    use super::super::dex_adapter::DexAdapter;
+    use super::super::meteora::*;
+    use crate::errors::ErrorCode;
+    use crate::state::{PoolInfo, Swap, SPL_MEMO_PROGRAM_ID};
+    use crate::test_utils::*;
+    use anchor_lang::prelude::*;
+
+    const TOKEN_PROGRAM_ID: Pubkey = anchor_spl::token::ID;
+
+    fn create_test_adapter() -> MeteoraAdapter {
+        MeteoraAdapter {
+            program_id: Pubkey::new_unique(),
+            memo_program_id: SPL_MEMO_PROGRAM_ID,
+        }
+    }
+
+    // Adapter accounts for swap2 (pool_info through program)
+    fn create_meteora_accounts(adapter: &MeteoraAdapter) -> Vec<TestAccount> {
+        let lb_pair = Pubkey::new_unique();
+        let (event_authority, _) =
+            Pubkey::find_program_address(&[b"__event_authority"], &adapter.program_id);
+        let (oracle, _) =
+            Pubkey::find_program_address(&[b"oracle", lb_pair.as_ref()], &adapter.program_id);
+        let (mint_x, mint_y) = (Pubkey::new_unique(), Pubkey::new_unique());
+        vec![
+            pool_info_account(Swap::Meteora, lb_pair, true),
+            pool_state_account(adapter.program_id, 8).with_key(lb_pair),
+            empty_account(),                                             // bin_array_bitmap_extension
+            token_account(mint_x, lb_pair, 1_000_000, TOKEN_PROGRAM_ID), // reserve_x
+            token_account(mint_y, lb_pair, 1_000_000, TOKEN_PROGRAM_ID), // reserve_y
+            empty_account(),                                             // user_token_in (from ctx)
+            empty_account(),                                             // user_token_out (from ctx)
+            mint_account(6, TOKEN_PROGRAM_ID).with_key(mint_x),
+            mint_account(6, TOKEN_PROGRAM_ID).with_key(mint_y),
+            empty_account().with_key(oracle),
+            program_account(adapter.program_id),                         // host_fee_in (none)
+            program_account(TOKEN_PROGRAM_ID),                           // token_x_program
+            program_account(TOKEN_PROGRAM_ID),                           // token_y_program
+            program_account(SPL_MEMO_PROGRAM_ID),                        // memo_program
+            empty_account().with_key(event_authority),
+            program_account(adapter.program_id),
+        ]
+    }
+
+    fn with_bin_arrays(
+        adapter: &MeteoraAdapter,
+        bin_arrays: usize,
+        closing_program_id: bool,
+    ) -> Vec<TestAccount> {
+        let mut accounts = create_meteora_accounts(adapter);
+        accounts.extend((0..bin_arrays).map(|_| pool_state_account(adapter.program_id, 8)));
+        if closing_program_id {
+            accounts.push(program_account(adapter.program_id));
+        }
+        accounts
+    }
+
+    fn bin_arrays_count(adapter: &MeteoraAdapter, accounts: Vec<TestAccount>) -> u8 {
+        adapter.bin_arrays_count(leak_account_infos(accounts))
+    }
+
+    fn vault_authority() -> Pubkey {
+        Pubkey::find_program_address(&[b"vault_authority"], &crate::ID).0
+    }
+
+    // Validates a token_x -> token_y swap from vaults owned by the vault authority
+    fn validate(adapter: &MeteoraAdapter, accounts: Vec<TestAccount>) -> Result<()> {
+        let count = accounts.len();
+        let (mint_x, mint_y) = (accounts[7].key, accounts[8].key);
+        let ctx = adapter_context(
+            token_account(mint_x, vault_authority(), 1_000, TOKEN_PROGRAM_ID),
+            token_account(mint_y, vault_authority(), 0, TOKEN_PROGRAM_ID),
+            accounts,
+        );
+        adapter.validate_accounts(ctx, 0, count)
+    }
+
+    fn with_host_fee_account(adapter: &MeteoraAdapter, mint: Option<Pubkey>, owner: Pubkey) -> Vec<TestAccount> {
+        let mut accounts = create_meteora_accounts(adapter);
+        let mint = mint.unwrap_or(accounts[7].key);
+        accounts[HOST_FEE_IN_INDEX] = token_account(mint, owner, 0, TOKEN_PROGRAM_ID);
+        accounts
+    }
+
+    #[test]
+    fn test_lb_pair_reserves_layouts() {
+        let (reserve_x, reserve_y) = (Pubkey::new_unique(), Pubkey::new_unique());
+        // Mock program layout: discriminator, reserve_x, reserve_y, vault amounts
+        let mut mock = vec![0u8; 8 + 32 * 2 + 8 * 2];
+        mock[..8].copy_from_slice(&LB_PAIR_DISCRIMINATOR);
+        mock[8..40].copy_from_slice(reserve_x.as_ref());
+        mock[40..72].copy_from_slice(reserve_y.as_ref());
+        assert_eq!(lb_pair_reserves(&mock), Some((reserve_x, reserve_y)));
+
+        let mut dlmm = vec![0u8; LB_PAIR_LEN];
+        dlmm[..8].copy_from_slice(&LB_PAIR_DISCRIMINATOR);
+        dlmm[LB_PAIR_RESERVE_X_OFFSET..LB_PAIR_RESERVE_X_OFFSET + 32].copy_from_slice(reserve_x.as_ref());
+        dlmm[LB_PAIR_RESERVE_Y_OFFSET..LB_PAIR_RESERVE_Y_OFFSET + 32].copy_from_slice(reserve_y.as_ref());
+        assert_eq!(lb_pair_reserves(&dlmm), Some((reserve_x, reserve_y)));
+
+        // Other accounts, e.g. bin arrays, are not lb pairs
+        mock[..8].copy_from_slice(&[0u8; 8]);
+        assert_eq!(lb_pair_reserves(&mock), None);
+        assert_eq!(lb_pair_reserves(&[]), None);
+    }
+
+    #[test]
+    fn test_bin_arrays_count_without_bin_arrays() {
+        let adapter = create_test_adapter();
+        assert_eq!(bin_arrays_count(&adapter, with_bin_arrays(&adapter, 0, false)), 0);
+    }
+
+    #[test]
+    fn test_bin_arrays_count_only_closing_program_id() {
+        // The closing program id itself must not be counted as a bin array
+        let adapter = create_test_adapter();
+        assert_eq!(bin_arrays_count(&adapter, with_bin_arrays(&adapter, 0, true)), 0);
+    }
+
+    #[test]
+    fn test_bin_arrays_count_between_program_ids() {
+        let adapter = create_test_adapter();
+        let mut accounts = with_bin_arrays(&adapter, 3, true);
+        // Accounts after the closing program id (e.g. the next hop) are not bin arrays
+        accounts.push(empty_account());
+        accounts.push(empty_account());
+
+        assert_eq!(bin_arrays_count(&adapter, accounts), 3);
+    }
+
+    #[test]
+    fn test_bin_arrays_count_without_closing_program_id() {
+        let adapter = create_test_adapter();
+        assert_eq!(bin_arrays_count(&adapter, with_bin_arrays(&adapter, 2, false)), 2);
+    }
+
+    #[test]
+    fn test_bin_arrays_count_capped() {
+        let adapter = create_test_adapter();
+        assert_eq!(
+            bin_arrays_count(&adapter, with_bin_arrays(&adapter, MAX_BIN_ARRAYS + 2, true)),
+            MAX_BIN_ARRAYS as u8
+        );
+        assert_eq!(
+            bin_arrays_count(&adapter, with_bin_arrays(&adapter, MAX_BIN_ARRAYS + 2, false)),
+            MAX_BIN_ARRAYS as u8
+        );
+    }
+
+    #[test]
+    fn test_validate_accounts_valid() {
+        let adapter = create_test_adapter();
+        assert!(validate(&adapter, create_meteora_accounts(&adapter)).is_ok());
+        assert!(validate(&adapter, with_bin_arrays(&adapter, 3, true)).is_ok());
+    }
+
+    #[test]
+    fn test_validate_accounts_disabled_pool() {
+        let adapter = create_test_adapter();
+        let mut accounts = create_meteora_accounts(&adapter);
+        let lb_pair = accounts[1].key;
+        accounts[0] = pool_info_account(Swap::Meteora, lb_pair, false);
+
+        let err = validate(&adapter, accounts).unwrap_err();
+        assert_eq!(err, ErrorCode::PoolDisabled.into());
+    }
+
+    #[test]
+    fn test_validate_accounts_pool_info_for_other_pool() {
+        let adapter = create_test_adapter();
+        let mut accounts = create_meteora_accounts(&adapter);
+        accounts[0] = pool_info_account(Swap::Meteora, Pubkey::new_unique(), true);
+
+        let err = validate(&adapter, accounts).unwrap_err();
+        assert_eq!(err, ErrorCode::InvalidPoolAddress.into());
+    }
+
+    #[test]
+    fn test_validate_accounts_truncated_pool_info() {
+        let adapter = create_test_adapter();
+        let mut accounts = create_meteora_accounts(&adapter);
+        accounts[0].data.truncate(PoolInfo::MIN_DATA_LEN - 1);
+
+        let err = validate(&adapter, accounts).unwrap_err();
+        assert_eq!(err, ErrorCode::InvalidPoolInfoAccount.into());
+    }
+
+    #[test]
+    fn test_validate_accounts_pool_info_without_discriminator() {
+        let adapter = create_test_adapter();
+        let mut accounts = create_meteora_accounts(&adapter);
+        accounts[0].data[..8].fill(0);
+
+        let err = validate(&adapter, accounts).unwrap_err();
+        assert_eq!(err, ErrorCode::InvalidPoolInfoAccount.into());
+    }
+
+    #[test]
+    fn test_validate_accounts_wrong_program() {
+        let adapter = create_test_adapter();
+        let mut accounts = create_meteora_accounts(&adapter);
+        accounts[15] = program_account(Pubkey::new_unique());
+
+        let err = validate(&adapter, accounts).unwrap_err();
+        assert_eq!(err, ErrorCode::InvalidCpiInterface.into());
+    }
+
+    #[test]
+    fn test_validate_accounts_wrong_event_authority() {
+        let adapter = create_test_adapter();
+        let mut accounts = create_meteora_accounts(&adapter);
+        accounts[14] = empty_account();
+
+        let err = validate(&adapter, accounts).unwrap_err();
+        assert_eq!(err, ErrorCode::InvalidCpiInterface.into());
+    }
+
+    #[test]
+    fn test_validate_accounts_wrong_oracle() {
+        let adapter = create_test_adapter();
+        let mut accounts = create_meteora_accounts(&adapter);
+        // The oracle of another pool is rejected as well as an arbitrary account
+        let other_pool_oracle =
+            Pubkey::find_program_address(&[b"oracle", Pubkey::new_unique().as_ref()], &adapter.program_id).0;
+        accounts[9] = empty_account().with_key(other_pool_oracle);
+
+        let err = validate(&adapter, accounts).unwrap_err();
+        assert_eq!(err, ErrorCode::InvalidOracleAccount.into());
+
+        let mut accounts = create_meteora_accounts(&adapter);
+        accounts[9] = empty_account();
+        let err = validate(&adapter, accounts).unwrap_err();
+        assert_eq!(err, ErrorCode::InvalidOracleAccount.into());
+    }
+
+    #[test]
+    fn test_validate_accounts_wrong_memo_program() {
+        let adapter = create_test_adapter();
+        let mut accounts = create_meteora_accounts(&adapter);
+        accounts[13] = program_account(Pubkey::new_unique());
+
+        let err = validate(&adapter, accounts).unwrap_err();
+        assert_eq!(err, ErrorCode::InvalidCpiInterface.into());
+    }
+
+    #[test]
+    fn test_validate_accounts_not_enough_accounts() {
+        let adapter = create_test_adapter();
+        let mut accounts = create_meteora_accounts(&adapter);
+        accounts.pop();
+
+        let err = validate(&adapter, accounts).unwrap_err();
+        assert_eq!(err, ErrorCode::NotEnoughAccountKeys.into());
+    }
+
+    #[test]
+    fn test_host_fee_account_none_when_program_id_passed() {
+        let adapter = create_test_adapter();
+        let accounts = leak_account_infos(create_meteora_accounts(&adapter));
+        assert!(adapter.host_fee_account(accounts).is_none());
+
+        let accounts = leak_account_infos(with_host_fee_account(&adapter, None, vault_authority()));
+        assert_eq!(
+            adapter.host_fee_account(accounts).map(|account| account.key()),
+            Some(accounts[HOST_FEE_IN_INDEX].key())
+        );
+    }
+
+    #[test]
+    fn test_validate_accounts_host_fee_account_owned_by_vault_authority() {
+        let adapter = create_test_adapter();
+        let accounts = with_host_fee_account(&adapter, None, vault_authority());
+
+        assert!(validate(&adapter, accounts).is_ok());
+    }
+
+    #[test]
+    fn test_validate_accounts_host_fee_account_wrong_mint() {
+        let adapter = create_test_adapter();
+        let accounts = with_host_fee_account(&adapter, Some(Pubkey::new_unique()), vault_authority());
+
+        let err = validate(&adapter, accounts).unwrap_err();
+        assert_eq!(err, ErrorCode::InvalidMint.into());
+    }
+
+    #[test]
+    fn test_validate_accounts_host_fee_account_not_owned_by_vault_authority() {
+        let adapter = create_test_adapter();
+        let accounts = with_host_fee_account(&adapter, None, Pubkey::new_unique());
+
+        let err = validate(&adapter, accounts).unwrap_err();
+        assert_eq!(err, ErrorCode::InvalidVaultOwner.into());
+    }
+
+    #[test]
+    fn test_validate_accounts_host_fee_account_not_a_token_account() {
+        let adapter = create_test_adapter();
+        let mut accounts = create_meteora_accounts(&adapter);
+        accounts[HOST_FEE_IN_INDEX] = empty_account();
+
+        let err = validate(&adapter, accounts).unwrap_err();
+        assert_eq!(err, ErrorCode::InvalidAccount.into());
+    }
+
+    #[test]
+    fn test_input_reserve_follows_input_mint() {
+        let adapter = create_test_adapter();
+        for swap_for_y in [true, false] {
+            let mut accounts = create_meteora_accounts(&adapter);
+            let (mint_x, mint_y) = (accounts[7].key, accounts[8].key);
+            let lb_pair = accounts[1].key;
+            accounts[3] = token_account(mint_x, lb_pair, 3_000, TOKEN_PROGRAM_ID);
+            accounts[4] = token_account(mint_y, lb_pair, 7_000, TOKEN_PROGRAM_ID);
+            let (input_mint, output_mint) = if swap_for_y { (mint_x, mint_y) } else { (mint_y, mint_x) };
+
+            let count = accounts.len();
+            let ctx = adapter_context(
+                token_account(input_mint, vault_authority(), 1_000, TOKEN_PROGRAM_ID),
+                token_account(output_mint, vault_authority(), 0, TOKEN_PROGRAM_ID),
+                accounts,
+            );
+            let expected = if swap_for_y { 3_000 } else { 7_000 };
+            assert_eq!(adapter.input_reserve(ctx, 0, count).unwrap(), expected);
+        }
+    }
+}
@@ -3,15 +3,30 @@ use anchor_lang::prelude::*;
 // Declare submodules for adapter implementations and trait
 pub mod raydium;
 pub mod whirlpool;
+pub mod whirlpool_two_hop;
 pub mod meteora;
+pub mod wsol;
 
 pub mod dex_adapter;
+pub mod adapter_errors;
 
 pub mod adapter_connector_module;
 
 // Test modules
 #[cfg(test)]
 mod dex_adapter_test;
+#[cfg(test)]
+mod raydium_test;
+#[cfg(test)]
+mod whirlpool_test;
+#[cfg(test)]
+mod whirlpool_two_hop_test;
+#[cfg(test)]
+mod meteora_test;
+#[cfg(test)]
+mod wsol_test;
+#[cfg(test)]
+mod adapter_errors_test;
 
 // Result struct for swap operations, holding the output amount
 #[derive(AnchorSerialize, AnchorDeserialize)]
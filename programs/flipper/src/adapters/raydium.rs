@@ -1,11 +1,12 @@
 use anchor_lang::prelude::*;
 use anchor_lang::solana_program::instruction::Instruction;
-use anchor_lang::solana_program::program::invoke_signed;
 use anchor_lang::solana_program::program::invoke;
 use anchor_spl::token_interface::TokenAccount;
 use crate::adapters::adapter_connector_module::{AdapterContext};
-use crate::adapters::dex_adapter::DexAdapter;
+use crate::adapters::adapter_errors::{self, RAYDIUM_ERRORS};
+use crate::adapters::dex_adapter::{DexAdapter, pool_vault_amount};
 use crate::errors::ErrorCode;
+use crate::math;
 use crate::state::{Swap, SwapEvent, SwapResult, PoolInfo};
 
 /// Adapter for interacting with the Raydium CPMM protocol
@@ -28,6 +29,64 @@ pub struct SwapBaseInputArgs {
     pub minimum_amount_out: u64,    // Minimum amount of output tokens expected
 }
 
+/// Minimum adapter accounts for a Raydium CPMM swap (pool_info through program id)
+pub const MIN_ACCOUNTS: usize = 11;
+
+/// Index of pool_state in the adapter accounts
+pub const POOL_ACCOUNT_INDEX: usize = 3;
+
+impl RaydiumAdapter {
+    /// Validates the vault authority is the Raydium PDA ["vault_and_lp_mint_auth_seed"]
+    pub fn validate_authority(&self, authority: &Pubkey) -> Result<()> {
+        let expected_authority = Pubkey::find_program_address(
+            &[b"vault_and_lp_mint_auth_seed"],
+            &self.program_id,
+        ).0;
+
+        if *authority != expected_authority {
+            return Err(ErrorCode::InvalidCpiInterface.into());
+        }
+        Ok(())
+    }
+
+    /// Validates amm_config is owned by the Raydium program (prevents forged zero-fee configs)
+    pub fn validate_amm_config_owner(&self, owner: &Pubkey) -> Result<()> {
+        if *owner != self.program_id {
+            return Err(ErrorCode::InvalidCpiInterface.into());
+        }
+        Ok(())
+    }
+}
+
+/// Discriminator of Raydium CPMM PoolState accounts (sha256("account:PoolState")[..8])
+pub const POOL_STATE_DISCRIMINATOR: [u8; 8] = [247, 237, 227, 245, 215, 195, 222, 70];
+
+/// Size of a PoolState account on the Raydium CPMM program
+pub const POOL_STATE_LEN: usize = 637;
+
+/// Offsets of token_0_vault and token_1_vault in a PoolState account on the Raydium CPMM program
+pub const POOL_STATE_TOKEN_0_VAULT_OFFSET: usize = 72;
+pub const POOL_STATE_TOKEN_1_VAULT_OFFSET: usize = 104;
+
+/// Reads (token_0_vault, token_1_vault) from a pool_state account
+///
+/// Raydium CPMM pool states store the vaults after amm_config and pool_creator.
+/// Smaller PoolState accounts (the local mock program) store them first.
+/// Returns None for data that is not a PoolState.
+pub fn pool_state_vaults(data: &[u8]) -> Option<(Pubkey, Pubkey)> {
+    if data.get(..8)? != POOL_STATE_DISCRIMINATOR {
+        return None;
+    }
+    let (offset_0, offset_1) = if data.len() >= POOL_STATE_LEN {
+        (POOL_STATE_TOKEN_0_VAULT_OFFSET, POOL_STATE_TOKEN_1_VAULT_OFFSET)
+    } else {
+        (8, 8 + 32)
+    };
+    let token_0_vault: [u8; 32] = data.get(offset_0..offset_0 + 32)?.try_into().ok()?;
+    let token_1_vault: [u8; 32] = data.get(offset_1..offset_1 + 32)?.try_into().ok()?;
+    Some((Pubkey::new_from_array(token_0_vault), Pubkey::new_from_array(token_1_vault)))
+}
+
 impl DexAdapter for RaydiumAdapter {
     /// Execute a swap through Raydium CPMM protocol
     fn execute_swap(
@@ -38,8 +97,6 @@ impl DexAdapter for RaydiumAdapter {
         remaining_accounts_count: usize,
     ) -> Result<SwapResult> {
 
-        const MIN_ACCOUNTS: usize = 11;
-
         if remaining_accounts_count < MIN_ACCOUNTS {
             return Err(ErrorCode::NotEnoughAccountKeys.into());
         }
@@ -53,7 +110,7 @@ impl DexAdapter for RaydiumAdapter {
         let adapter_accounts = &ctx.remaining_accounts[remaining_accounts_start_index..end_index];
 
         // Validate pool is enabled (pool_info is at index 0)
-        let pool_info = Account::<PoolInfo>::try_from(&adapter_accounts[0])?;
+        let pool_info = PoolInfo::load_checked(&adapter_accounts[0])?;
         if !pool_info.enabled {
             return Err(ErrorCode::PoolDisabled.into());
         }
@@ -129,7 +186,7 @@ impl DexAdapter for RaydiumAdapter {
         let authority_seeds: &[&[u8]] = &[b"vault_authority", &[vault_authority_bump]];
         let signer_seeds: &[&[&[u8]]] = &[authority_seeds];
 
-        invoke_signed(&instruction, &account_infos, signer_seeds)?;
+        adapter_errors::invoke_adapter_cpi("Raydium", RAYDIUM_ERRORS, &instruction, &account_infos, signer_seeds)?;
 
         // Calculate output amount by checking balance difference
         let output_vault_data = TokenAccount::try_deserialize(&mut ctx.output_account.data.borrow().as_ref())?;
@@ -139,7 +196,7 @@ impl DexAdapter for RaydiumAdapter {
 
         msg!("Raydium CPMM swap_base_input completed, output amount: {}", output_amount);
 
-        Ok(SwapResult { output_amount })
+        Ok(SwapResult { output_amount, host_fee_amount: 0 })
     }
 
     fn validate_accounts(
@@ -148,7 +205,6 @@ impl DexAdapter for RaydiumAdapter {
         remaining_accounts_start_index: usize,
         remaining_accounts_count: usize,
     ) -> Result<()> {
-        const MIN_ACCOUNTS: usize = 11;
 
         if remaining_accounts_count < MIN_ACCOUNTS {
             return Err(ErrorCode::NotEnoughAccountKeys.into());
@@ -163,26 +219,19 @@ impl DexAdapter for RaydiumAdapter {
         let adapter_accounts = &ctx.remaining_accounts[remaining_accounts_start_index..end_index];
 
         // Validate pool is enabled (pool_info is at index 0)
-        let pool_info = Account::<PoolInfo>::try_from(&adapter_accounts[0])?;
+        let pool_info = PoolInfo::load_checked(&adapter_accounts[0])?;
         if !pool_info.enabled {
             return Err(ErrorCode::PoolDisabled.into());
         }
 
-        let pool_state = &adapter_accounts[3]; // pool_state is at index 3 in adapter_accounts
+        let pool_state = &adapter_accounts[POOL_ACCOUNT_INDEX];
         if pool_info.pool_address != pool_state.key() {
             return Err(ErrorCode::InvalidPoolAddress.into());
         }
 
-        // Validate authority PDA
-        let authority = &adapter_accounts[1];
-        let expected_authority = Pubkey::find_program_address(
-            &[b"vault_and_lp_mint_auth_seed"],
-            &self.program_id,
-        ).0;
-
-        if authority.key() != expected_authority {
-            return Err(ErrorCode::InvalidCpiInterface.into());
-        }
+        // Validate authority PDA and amm_config so neither can be substituted in the CPI
+        self.validate_authority(&adapter_accounts[1].key())?;
+        self.validate_amm_config_owner(adapter_accounts[2].owner)?;
 
         // Validate token programs are correct
         let input_token_program = &adapter_accounts[6];
@@ -210,4 +259,49 @@ impl DexAdapter for RaydiumAdapter {
         }
         Ok(())
     }
-}
\ No newline at end of file
+
+    /// Reads the pool vault holding the input token (input_vault at index 4)
+    fn input_reserve(
+        &self,
+        ctx: AdapterContext,
+        remaining_accounts_start_index: usize,
+        remaining_accounts_count: usize,
+    ) -> Result<u64> {
+        if remaining_accounts_count < MIN_ACCOUNTS {
+            return Err(ErrorCode::NotEnoughAccountKeys.into());
+        }
+
+        let end_index = remaining_accounts_start_index + remaining_accounts_count;
+        if ctx.remaining_accounts.len() < end_index {
+            return Err(ErrorCode::NotEnoughAccountKeys.into());
+        }
+
+        let adapter_accounts = &ctx.remaining_accounts[remaining_accounts_start_index..end_index];
+
+        pool_vault_amount(&adapter_accounts[4])
+    }
+
+    /// Quotes from the pool vaults (input_vault at index 4, output_vault at index 5)
+    fn quote(
+        &self,
+        ctx: AdapterContext,
+        amount: u64,
+        remaining_accounts_start_index: usize,
+        remaining_accounts_count: usize,
+    ) -> Result<Option<u64>> {
+        if remaining_accounts_count < MIN_ACCOUNTS {
+            return Err(ErrorCode::NotEnoughAccountKeys.into());
+        }
+
+        let end_index = remaining_accounts_start_index + remaining_accounts_count;
+        if ctx.remaining_accounts.len() < end_index {
+            return Err(ErrorCode::NotEnoughAccountKeys.into());
+        }
+
+        let adapter_accounts = &ctx.remaining_accounts[remaining_accounts_start_index..end_index];
+
+        let reserve_in = pool_vault_amount(&adapter_accounts[4])?;
+        let reserve_out = pool_vault_amount(&adapter_accounts[5])?;
+        math::constant_product_out(amount, reserve_in, reserve_out).map(Some)
+    }
+}
@@ -0,0 +1,99 @@
+#[cfg(test)]
+mod tests {
+    use super::super::dex_adapter::DexAdapter;
+    use super::super::raydium::*;
+    use crate::errors::ErrorCode;
+    use crate::state::{PoolInfo, Swap};
+    use crate::test_utils::*;
+    use anchor_lang::prelude::*;
+
+    const TOKEN_PROGRAM_ID: Pubkey = anchor_spl::token::ID;
+
+    fn create_test_adapter() -> RaydiumAdapter {
+        RaydiumAdapter {
+            program_id: Pubkey::new_unique(),
+        }
+    }
+
+    // Adapter accounts for swap_base_input (pool_info through program)
+    fn create_raydium_accounts(adapter: &RaydiumAdapter) -> Vec<TestAccount> {
+        let pool_state = Pubkey::new_unique();
+        let (authority, _) =
+            Pubkey::find_program_address(&[b"vault_and_lp_mint_auth_seed"], &adapter.program_id);
+        let (input_mint, output_mint) = (Pubkey::new_unique(), Pubkey::new_unique());
+        vec![
+            pool_info_account(Swap::Raydium, pool_state, true),
+            empty_account().with_key(authority),
+            pool_state_account(adapter.program_id, 8),                        // amm_config
+            pool_state_account(adapter.program_id, 8).with_key(pool_state),
+            token_account(input_mint, authority, 1_000_000, TOKEN_PROGRAM_ID),  // input_vault
+            token_account(output_mint, authority, 1_000_000, TOKEN_PROGRAM_ID), // output_vault
+            program_account(TOKEN_PROGRAM_ID),                                 // input_token_program
+            program_account(TOKEN_PROGRAM_ID),                                 // output_token_program
+            mint_account(6, TOKEN_PROGRAM_ID).with_key(input_mint),
+            mint_account(6, TOKEN_PROGRAM_ID).with_key(output_mint),
+            pool_state_account(adapter.program_id, 8),                        // observation_state
+            program_account(adapter.program_id),
+        ]
+    }
+
+    fn validate(adapter: &RaydiumAdapter, accounts: Vec<TestAccount>) -> Result<()> {
+        let count = accounts.len();
+        let (input_mint, output_mint) = (accounts[8].key, accounts[9].key);
+        let vault_owner = Pubkey::new_unique();
+        let ctx = adapter_context(
+            token_account(input_mint, vault_owner, 1_000, TOKEN_PROGRAM_ID),
+            token_account(output_mint, vault_owner, 0, TOKEN_PROGRAM_ID),
+            accounts,
+        );
+        adapter.validate_accounts(ctx, 0, count)
+    }
+
+    #[test]
+    fn test_validate_accounts_valid() {
+        let adapter = create_test_adapter();
+        assert!(validate(&adapter, create_raydium_accounts(&adapter)).is_ok());
+    }
+
+    #[test]
+    fn test_validate_accounts_disabled_pool() {
+        let adapter = create_test_adapter();
+        let mut accounts = create_raydium_accounts(&adapter);
+        let pool_state = accounts[POOL_ACCOUNT_INDEX].key;
+        accounts[0] = pool_info_account(Swap::Raydium, pool_state, false);
+
+        let err = validate(&adapter, accounts).unwrap_err();
+        assert_eq!(err, ErrorCode::PoolDisabled.into());
+    }
+
+    #[test]
+    fn test_validate_accounts_truncated_pool_info() {
+        let adapter = create_test_adapter();
+        let mut accounts = create_raydium_accounts(&adapter);
+        accounts[0].data.truncate(PoolInfo::MIN_DATA_LEN - 1);
+
+        let err = validate(&adapter, accounts).unwrap_err();
+        assert_eq!(err, ErrorCode::InvalidPoolInfoAccount.into());
+    }
+
+    #[test]
+    fn test_validate_accounts_pool_info_without_discriminator() {
+        let adapter = create_test_adapter();
+        let mut accounts = create_raydium_accounts(&adapter);
+        accounts[0].data[..8].fill(0);
+
+        let err = validate(&adapter, accounts).unwrap_err();
+        assert_eq!(err, ErrorCode::InvalidPoolInfoAccount.into());
+    }
+
+    #[test]
+    fn test_validate_accounts_legacy_sized_pool_info() {
+        // A PoolInfo never grown by migrate_pool_info is too short to load
+        let adapter = create_test_adapter();
+        let mut accounts = create_raydium_accounts(&adapter);
+        accounts[0].data.truncate(PoolInfo::LEGACY_SPACE);
+
+        let err = validate(&adapter, accounts).unwrap_err();
+        assert_eq!(err, ErrorCode::InvalidPoolInfoAccount.into());
+    }
+}
@@ -1,15 +1,16 @@
 use anchor_lang::prelude::*;
 use anchor_lang::solana_program::instruction::Instruction;
-use anchor_lang::solana_program::program::invoke_signed;
 use anchor_spl::token_interface::TokenAccount;
 use crate::adapters::adapter_connector_module::{AdapterContext};
-use crate::adapters::dex_adapter::DexAdapter;
+use crate::adapters::adapter_errors::{self, WHIRLPOOL_ERRORS};
+use crate::adapters::dex_adapter::{DexAdapter, pool_vault_amount};
 use crate::errors::ErrorCode;
 use crate::state::{Swap, SwapEvent, SwapResult, PoolInfo};
 
 pub struct WhirlpoolAdapter {
     pub program_id: Pubkey,
-    pub a_to_b: bool
+    pub a_to_b: bool, // Client-provided direction; only logged, the swap follows the pool's mints
+    pub memo_program_id: Pubkey, // Memo program expected by swapV2, from the registry
 }
 
 const TOKEN_PROGRAM_ID: Pubkey = anchor_spl::token::ID;
@@ -51,6 +52,190 @@ pub enum AccountsType {
     SupplementalTickArraysTwo,
 }
 
+/// Minimum adapter accounts for a Whirlpool swapV2 (pool_info through oracle)
+pub const MIN_ACCOUNTS: usize = 15;
+
+/// Index of the whirlpool in the adapter accounts
+pub const POOL_ACCOUNT_INDEX: usize = 4;
+
+impl WhirlpoolAdapter {
+    /// Validates the memo_program account so memo-required Token-2022 transfers don't fail inside the DEX
+    pub fn validate_memo_program(&self, memo_program: &Pubkey) -> Result<()> {
+        if *memo_program != self.memo_program_id {
+            return Err(ErrorCode::InvalidCpiInterface.into());
+        }
+        Ok(())
+    }
+
+    /// Validates a supplemental tick array is owned by the Whirlpool program and belongs to `whirlpool`
+    pub fn validate_tick_array(&self, tick_array: &AccountInfo, whirlpool: &Pubkey) -> Result<()> {
+        if tick_array.owner != &self.program_id {
+            return Err(ErrorCode::InvalidCpiInterface.into());
+        }
+        let tick_array_pool = tick_array_whirlpool(&tick_array.try_borrow_data()?);
+        if tick_array_pool != Some(*whirlpool) {
+            return Err(ErrorCode::InvalidAccount.into());
+        }
+        Ok(())
+    }
+
+    /// Validates the oracle is the ["oracle", whirlpool] PDA of the Whirlpool program,
+    /// since swapV2 takes it writable and an arbitrary account must not be forwarded in its place
+    pub fn validate_oracle(&self, oracle: &Pubkey, whirlpool: &Pubkey) -> Result<()> {
+        let expected_oracle = Pubkey::find_program_address(
+            &[b"oracle", whirlpool.as_ref()],
+            &self.program_id,
+        ).0;
+
+        if *oracle != expected_oracle {
+            return Err(ErrorCode::InvalidOracleAccount.into());
+        }
+        Ok(())
+    }
+
+    /// Derives the swap direction from the whirlpool's token_mint_a/token_mint_b and the input mint
+    /// A client a_to_b flag that disagrees is logged and ignored
+    ///
+    /// # Returns
+    /// * `Result<bool>` - true when the input is token A; InvalidAccount if `whirlpool` is not a Whirlpool,
+    ///   InvalidMint if the input mint is neither of its tokens
+    pub fn swap_direction(&self, whirlpool: &AccountInfo, input_mint: &Pubkey) -> Result<bool> {
+        let (token_mint_a, token_mint_b) = whirlpool_token_mints(&whirlpool.try_borrow_data()?)
+            .ok_or(ErrorCode::InvalidAccount)?;
+        let a_to_b = if *input_mint == token_mint_a {
+            true
+        } else if *input_mint == token_mint_b {
+            false
+        } else {
+            return Err(ErrorCode::InvalidMint.into());
+        };
+        if a_to_b != self.a_to_b {
+            msg!("Whirlpool adapter: ignoring client a_to_b={}, pool mints give a_to_b={}", self.a_to_b, a_to_b);
+        }
+        Ok(a_to_b)
+    }
+}
+
+/// Maps a pair given in swap direction (from, to) to the pool's fixed token order (A, B)
+/// a_to_b = true keeps the pair, a_to_b = false swaps it
+pub fn to_pool_order<T>(a_to_b: bool, from: T, to: T) -> (T, T) {
+    if a_to_b {
+        (from, to)
+    } else {
+        (to, from)
+    }
+}
+
+/// Orders the two mint accounts passed at indices 5 and 6 as (token_mint_a, token_mint_b),
+/// whichever order the client passed them in
+///
+/// # Returns
+/// * `Result<(&AccountInfo, &AccountInfo)>` - InvalidMint unless the keys are exactly the pool's two mints
+pub fn mints_in_pool_order<'a, 'info>(
+    first: &'a AccountInfo<'info>,
+    second: &'a AccountInfo<'info>,
+    token_mint_a: &Pubkey,
+    token_mint_b: &Pubkey,
+) -> Result<(&'a AccountInfo<'info>, &'a AccountInfo<'info>)> {
+    if first.key == token_mint_a && second.key == token_mint_b {
+        Ok((first, second))
+    } else if first.key == token_mint_b && second.key == token_mint_a {
+        Ok((second, first))
+    } else {
+        Err(ErrorCode::InvalidMint.into())
+    }
+}
+
+/// Discriminator of Whirlpool accounts (sha256("account:Whirlpool")[..8])
+pub const WHIRLPOOL_DISCRIMINATOR: [u8; 8] = [63, 149, 209, 12, 225, 128, 99, 9];
+
+/// Size of a Whirlpool account on the Orca program
+pub const WHIRLPOOL_LEN: usize = 653;
+
+/// Offsets of token_mint_a and token_mint_b in a Whirlpool account on the Orca program
+pub const WHIRLPOOL_TOKEN_MINT_A_OFFSET: usize = 101;
+pub const WHIRLPOOL_TOKEN_MINT_B_OFFSET: usize = 181;
+
+/// Reads (token_mint_a, token_mint_b) from a whirlpool account
+///
+/// Orca Whirlpool accounts store the mints at fixed offsets after the price state.
+/// Smaller Whirlpool accounts (the local mock program) store them first.
+/// Returns None for data that is not a Whirlpool.
+pub fn whirlpool_token_mints(data: &[u8]) -> Option<(Pubkey, Pubkey)> {
+    if data.get(..8)? != WHIRLPOOL_DISCRIMINATOR {
+        return None;
+    }
+    let (offset_a, offset_b) = if data.len() >= WHIRLPOOL_LEN {
+        (WHIRLPOOL_TOKEN_MINT_A_OFFSET, WHIRLPOOL_TOKEN_MINT_B_OFFSET)
+    } else {
+        (8, 8 + 32)
+    };
+    let token_mint_a: [u8; 32] = data.get(offset_a..offset_a + 32)?.try_into().ok()?;
+    let token_mint_b: [u8; 32] = data.get(offset_b..offset_b + 32)?.try_into().ok()?;
+    Some((Pubkey::new_from_array(token_mint_a), Pubkey::new_from_array(token_mint_b)))
+}
+
+/// Offsets of token_vault_a and token_vault_b in a Whirlpool account on the Orca program
+pub const WHIRLPOOL_TOKEN_VAULT_A_OFFSET: usize = 133;
+pub const WHIRLPOOL_TOKEN_VAULT_B_OFFSET: usize = 213;
+
+/// Reads (token_vault_a, token_vault_b) from a whirlpool account
+///
+/// Each Orca vault follows its mint. Smaller Whirlpool accounts (the local mock program)
+/// store both vaults after both mints.
+/// Returns None for data that is not a Whirlpool.
+pub fn whirlpool_token_vaults(data: &[u8]) -> Option<(Pubkey, Pubkey)> {
+    if data.get(..8)? != WHIRLPOOL_DISCRIMINATOR {
+        return None;
+    }
+    let (offset_a, offset_b) = if data.len() >= WHIRLPOOL_LEN {
+        (WHIRLPOOL_TOKEN_VAULT_A_OFFSET, WHIRLPOOL_TOKEN_VAULT_B_OFFSET)
+    } else {
+        (8 + 64, 8 + 96)
+    };
+    let token_vault_a: [u8; 32] = data.get(offset_a..offset_a + 32)?.try_into().ok()?;
+    let token_vault_b: [u8; 32] = data.get(offset_b..offset_b + 32)?.try_into().ok()?;
+    Some((Pubkey::new_from_array(token_vault_a), Pubkey::new_from_array(token_vault_b)))
+}
+
+/// Number of supplemental tick arrays passed after the fixed accounts (at most 3)
+/// The last adapter account is the program id and is not a tick array
+pub fn supplemental_tick_arrays_count(remaining_accounts_count: usize) -> u8 {
+    remaining_accounts_count
+        .saturating_sub(MIN_ACCOUNTS)
+        .saturating_sub(1)
+        .min(3) as u8
+}
+
+/// Discriminator of Whirlpool TickArray accounts (sha256("account:TickArray")[..8])
+pub const TICK_ARRAY_DISCRIMINATOR: [u8; 8] = [69, 97, 189, 190, 110, 7, 66, 187];
+
+/// Discriminator of Whirlpool DynamicTickArray accounts (sha256("account:DynamicTickArray")[..8])
+pub const DYNAMIC_TICK_ARRAY_DISCRIMINATOR: [u8; 8] = [17, 216, 246, 142, 225, 199, 218, 56];
+
+/// Size of a fixed TickArray: discriminator + start_tick_index + 88 ticks of 113 bytes + whirlpool
+pub const FIXED_TICK_ARRAY_LEN: usize = 8 + 4 + 88 * 113 + 32;
+
+/// Reads the whirlpool a tick array account belongs to
+///
+/// Fixed tick arrays store the whirlpool after their ticks, dynamic ones right after
+/// start_tick_index. Smaller TickArray accounts (the local mock program) store it first.
+/// Returns None for data that is not a tick array.
+pub fn tick_array_whirlpool(data: &[u8]) -> Option<Pubkey> {
+    let discriminator = data.get(..8)?;
+    let offset = if discriminator == DYNAMIC_TICK_ARRAY_DISCRIMINATOR {
+        8 + 4
+    } else if discriminator != TICK_ARRAY_DISCRIMINATOR {
+        return None;
+    } else if data.len() >= FIXED_TICK_ARRAY_LEN {
+        FIXED_TICK_ARRAY_LEN - 32
+    } else {
+        8
+    };
+    let whirlpool: [u8; 32] = data.get(offset..offset + 32)?.try_into().ok()?;
+    Some(Pubkey::new_from_array(whirlpool))
+}
+
 impl DexAdapter for WhirlpoolAdapter {
     fn execute_swap(
         &self,
@@ -61,7 +246,6 @@ impl DexAdapter for WhirlpoolAdapter {
     ) -> Result<SwapResult> {
         msg!("Executing Whirlpool swapV2, amount: {}", amount);
 
-        const MIN_ACCOUNTS: usize = 15;
 
         if remaining_accounts_count < MIN_ACCOUNTS {
             return Err(ErrorCode::NotEnoughAccountKeys.into());
@@ -75,11 +259,15 @@ impl DexAdapter for WhirlpoolAdapter {
         let adapter_accounts = &ctx.remaining_accounts[remaining_accounts_start_index..end_index];
 
         // Validate pool_info (index 0) - check that pool is enabled
-        let pool_info = Account::<PoolInfo>::try_from(&adapter_accounts[0])?;
+        let pool_info = PoolInfo::load_checked(&adapter_accounts[0])?;
         if !pool_info.enabled {
             return Err(ErrorCode::PoolDisabled.into());
         }
 
+        // Derive the direction from the pool's mints rather than the client flag
+        let input_mint = TokenAccount::try_deserialize(&mut ctx.input_account.data.borrow().as_ref())?.mint;
+        let a_to_b = self.swap_direction(&adapter_accounts[4], &input_mint)?;
+
         // Record initial output token balance
         // Note: Works with Token2022 accounts with extensions (e.g., 179 bytes) because
         // token_interface::TokenAccount only reads base structure (165 bytes)
@@ -88,13 +276,7 @@ impl DexAdapter for WhirlpoolAdapter {
 
         // Calculate supplemental tick arrays (up to 3: -200, 200, 300)
         // MIN_ACCOUNTS (15) covers accounts 0-14, supplemental tick arrays start at index 15
-        // The last account in remaining_accounts is program id, which should not be counted as tick array
-        // Use saturating_sub to prevent underflow when remaining_accounts_count == MIN_ACCOUNTS
-        let supplemental_tick_arrays_count = remaining_accounts_count
-            .saturating_sub(MIN_ACCOUNTS)
-            .saturating_sub(1) // Exclude last account (program id)
-            .min(3) as u8;
-        
+        let supplemental_tick_arrays_count = supplemental_tick_arrays_count(remaining_accounts_count);
 
         // Create swap args
         let swap_args = SwapV2Args {
@@ -102,7 +284,7 @@ impl DexAdapter for WhirlpoolAdapter {
             other_amount_threshold: 0,
             sqrt_price_limit: 0,
             amount_specified_is_input: true,
-            a_to_b: self.a_to_b,
+            a_to_b,
             remaining_accounts_info: if supplemental_tick_arrays_count > 0 {
                 Some(RemainingAccountsInfo {
                     slices: vec![RemainingAccountsSlice {
@@ -119,8 +301,8 @@ impl DexAdapter for WhirlpoolAdapter {
         instruction_data.extend_from_slice(&SWAP_V2_DISCRIMINATOR);
         instruction_data.extend_from_slice(&swap_args.try_to_vec()?);
 
-        // Determine correct token_owner_accounts and token_mints based on swap direction (a_to_b)
-        // When swap direction changes, we need to swap both token_owner_accounts AND token_mints
+        // Determine correct token_owner_accounts and token_mints based on the derived swap direction (a_to_b)
+        // The owner accounts follow the direction; the mint accounts are matched to the pool's mints by key
         //
         // Whirlpool pool has fixed token order: token_mint_a and token_mint_b are fixed at pool creation
         // The swap direction (a_to_b) determines how tokens flow:
@@ -134,29 +316,12 @@ impl DexAdapter for WhirlpoolAdapter {
         // When a_to_b = false (swapping B -> A, e.g., SOL -> JUP):
         //   - token_owner_account_b: account to take FROM (contains token B = SOL = input_account)
         //   - token_owner_account_a: account to send TO (contains token A = JUP = output_account)
-        //   - token_mint_b: mint of token we're swapping FROM (input)
-        //   - token_mint_a: mint of token we're swapping TO (output)
-        let (token_owner_account_a, token_owner_account_b, token_mint_a, token_mint_b) = if self.a_to_b {
-            // a_to_b = true: swapping A -> B
-            // input_account contains token A, output_account contains token B
-            (
-                ctx.input_account.clone(),
-                ctx.output_account.clone(),
-                adapter_accounts[5].clone(),  // token_mint_a (from remaining_accounts)
-                adapter_accounts[6].clone(),  // token_mint_b (from remaining_accounts)
-            )
-        } else {
-            // a_to_b = false: swapping B -> A (e.g., SOL -> JUP)
-            // input_account contains token B (SOL), but Whirlpool expects it in token_owner_account_b
-            // output_account contains token A (JUP), but Whirlpool expects it in token_owner_account_a
-            // We also need to swap token_mints to match the swap direction
-            (
-                ctx.output_account.clone(),
-                ctx.input_account.clone(),
-                adapter_accounts[6].clone(),  // token_mint_b becomes token_mint_a (swapped)
-                adapter_accounts[5].clone(),  // token_mint_a becomes token_mint_b (swapped)
-            )
-        };
+        let (token_owner_account_a, token_owner_account_b) =
+            to_pool_order(a_to_b, ctx.input_account.clone(), ctx.output_account.clone());
+        let (pool_mint_a, pool_mint_b) = whirlpool_token_mints(&adapter_accounts[4].try_borrow_data()?)
+            .ok_or(ErrorCode::InvalidAccount)?;
+        let (token_mint_a, token_mint_b) =
+            mints_in_pool_order(&adapter_accounts[5], &adapter_accounts[6], &pool_mint_a, &pool_mint_b)?;
 
         // Build account metas in SwapV2 order
         let mut accounts = vec![
@@ -165,8 +330,8 @@ impl DexAdapter for WhirlpoolAdapter {
             AccountMeta::new_readonly(adapter_accounts[3].key(), false),    // memo_program
             AccountMeta::new_readonly(ctx.authority.key(), true),           // token_authority
             AccountMeta::new(adapter_accounts[4].key(), false),             // whirlpool
-            AccountMeta::new_readonly(token_mint_a.key(), false),            // token_mint_a (swapped if a_to_b = false)
-            AccountMeta::new_readonly(token_mint_b.key(), false),            // token_mint_b (swapped if a_to_b = false)
+            AccountMeta::new_readonly(token_mint_a.key(), false),            // token_mint_a
+            AccountMeta::new_readonly(token_mint_b.key(), false),            // token_mint_b
             AccountMeta::new(token_owner_account_a.key(), false),            // token_owner_account_a (receives token A)
             AccountMeta::new(adapter_accounts[8].key(), false),             // token_vault_a (contains token A)
             AccountMeta::new(token_owner_account_b.key(), false),           // token_owner_account_b (receives token B)
@@ -190,8 +355,8 @@ impl DexAdapter for WhirlpoolAdapter {
             adapter_accounts[3].clone(),     // memo_program
             ctx.authority.clone(),           // token_authority
             adapter_accounts[4].clone(),     // whirlpool
-            token_mint_a.clone(),            // token_mint_a (swapped if a_to_b = false)
-            token_mint_b.clone(),            // token_mint_b (swapped if a_to_b = false)
+            token_mint_a.clone(),            // token_mint_a
+            token_mint_b.clone(),            // token_mint_b
             token_owner_account_a.clone(),   // token_owner_account_a
             adapter_accounts[8].clone(),     // token_vault_a
             token_owner_account_b.clone(),   // token_owner_account_b
@@ -228,7 +393,7 @@ impl DexAdapter for WhirlpoolAdapter {
         let authority_seeds: &[&[u8]] = &[b"vault_authority", &[vault_authority_bump]];
         let signer_seeds: &[&[&[u8]]] = &[authority_seeds];
 
-        invoke_signed(&instruction, &account_infos, signer_seeds)?;
+        adapter_errors::invoke_adapter_cpi("Whirlpool", WHIRLPOOL_ERRORS, &instruction, &account_infos, signer_seeds)?;
 
         // Note: Works with Token2022 accounts with extensions (e.g., 179 bytes)
         let output_vault_data = TokenAccount::try_deserialize(&mut ctx.output_account.data.borrow().as_ref())?;
@@ -238,7 +403,7 @@ impl DexAdapter for WhirlpoolAdapter {
 
         msg!("Whirlpool swapV2 completed, output amount: {}", output_amount);
 
-        Ok(SwapResult { output_amount })
+        Ok(SwapResult { output_amount, host_fee_amount: 0 })
     }
 
     fn validate_accounts(
@@ -247,7 +412,6 @@ impl DexAdapter for WhirlpoolAdapter {
         remaining_accounts_start_index: usize,
         remaining_accounts_count: usize,
     ) -> Result<()> {
-        const MIN_ACCOUNTS: usize = 15;
 
         if remaining_accounts_count < MIN_ACCOUNTS {
             return Err(ErrorCode::NotEnoughAccountKeys.into());
@@ -261,16 +425,19 @@ impl DexAdapter for WhirlpoolAdapter {
         let adapter_accounts = &ctx.remaining_accounts[remaining_accounts_start_index..end_index];
 
         // Validate pool_info (index 0)
-        let pool_info = Account::<PoolInfo>::try_from(&adapter_accounts[0])?;
+        let pool_info = PoolInfo::load_checked(&adapter_accounts[0])?;
         if !pool_info.enabled {
             return Err(ErrorCode::PoolDisabled.into());
         }
 
-        let whirlpool = &adapter_accounts[4];
+        let whirlpool = &adapter_accounts[POOL_ACCOUNT_INDEX];
         if pool_info.pool_address != whirlpool.key() {
             return Err(ErrorCode::InvalidPoolAddress.into());
         }
 
+        // Validate memo program (index 3) forwarded to swapV2
+        self.validate_memo_program(&adapter_accounts[3].key())?;
+
         // Validate token programs
         let token_program_a = &adapter_accounts[1];
         let token_program_b = &adapter_accounts[2];
@@ -287,29 +454,27 @@ impl DexAdapter for WhirlpoolAdapter {
             return Err(ErrorCode::InvalidCpiInterface.into());
         }
 
+        // Read the pool's fixed token order and check the input vault holds one of its tokens
+        let (pool_mint_a, pool_mint_b) = whirlpool_token_mints(&whirlpool.try_borrow_data()?)
+            .ok_or(ErrorCode::InvalidAccount)?;
+        let input_vault = TokenAccount::try_deserialize(&mut ctx.input_account.data.borrow().as_ref())
+            .map_err(|_| ErrorCode::InvalidAccount)?;
+        self.swap_direction(whirlpool, &input_vault.mint)?;
+
+        // The mints at indices 5 and 6 may come in either order but must be the pool's two mints
+        let (token_mint_a, token_mint_b) =
+            mints_in_pool_order(&adapter_accounts[5], &adapter_accounts[6], &pool_mint_a, &pool_mint_b)?;
+
+        // Each token program must own its mint; a Token-2022 mint paired with the legacy
+        // program would otherwise only fail in transfer_checked inside swapV2
+        if token_mint_a.owner != token_program_a.key || token_mint_b.owner != token_program_b.key {
+            return Err(ErrorCode::InvalidTokenProgram.into());
+        }
+
         // Validate pool vault accounts (token_vault_a at index 8, token_vault_b at index 10)
-        // Note: token_mint_a and token_mint_b in remaining_accounts are passed in swap direction order,
-        // but vaults are always in pool's fixed order (token_vault_a contains pool's token A, token_vault_b contains pool's token B)
-        // We need to account for swap direction when validating mints against vaults
+        // Vaults are always in pool's fixed order (token_vault_a contains pool's token A, token_vault_b contains pool's token B)
         let token_vault_a = &adapter_accounts[8];
         let token_vault_b = &adapter_accounts[10];
-        let token_mint_from_remaining = &adapter_accounts[5];  // mint in swap direction (A if a_to_b=true, B if a_to_b=false)
-        let token_mint_to_remaining = &adapter_accounts[6];    // mint in swap direction (B if a_to_b=true, A if a_to_b=false)
-
-        // Determine which mint corresponds to which vault based on swap direction
-        // Vaults are always in pool's fixed order:
-        // - token_vault_a always contains pool's token A
-        // - token_vault_b always contains pool's token B
-        // But mints in remaining_accounts are in swap direction order
-        let (expected_mint_for_vault_a, expected_mint_for_vault_b) = if self.a_to_b {
-            // a_to_b = true: swapping A -> B
-            // token_mint_from_remaining is A (matches vault_a), token_mint_to_remaining is B (matches vault_b)
-            (token_mint_from_remaining, token_mint_to_remaining)
-        } else {
-            // a_to_b = false: swapping B -> A
-            // token_mint_from_remaining is B (matches vault_b), token_mint_to_remaining is A (matches vault_a)
-            (token_mint_to_remaining, token_mint_from_remaining)
-        };
 
         // Ensure vault accounts are not default pubkeys
         if token_vault_a.key() == Pubkey::default() || token_vault_b.key() == Pubkey::default() {
@@ -326,11 +491,11 @@ impl DexAdapter for WhirlpoolAdapter {
         let vault_b_data = TokenAccount::try_deserialize(&mut token_vault_b.data.borrow().as_ref())
             .map_err(|_| ErrorCode::InvalidAccount)?;
 
-        // Validate vault mints match expected token mints (accounting for swap direction)
-        if vault_a_data.mint != expected_mint_for_vault_a.key() {
+        // Validate vault mints match the pool's token mints
+        if vault_a_data.mint != pool_mint_a {
             return Err(ErrorCode::InvalidMint.into());
         }
-        if vault_b_data.mint != expected_mint_for_vault_b.key() {
+        if vault_b_data.mint != pool_mint_b {
             return Err(ErrorCode::InvalidMint.into());
         }
 
@@ -341,6 +506,16 @@ impl DexAdapter for WhirlpoolAdapter {
             }
         }
 
+        // Validate oracle (index 14), passed writable to swapV2
+        self.validate_oracle(&adapter_accounts[14].key(), &whirlpool.key())?;
+
+        // Supplemental tick arrays (indices 15+) are forwarded to swapV2 as is, so each must be
+        // a tick array of this whirlpool
+        let supplemental_tick_arrays_count = supplemental_tick_arrays_count(remaining_accounts_count) as usize;
+        for tick_array in &adapter_accounts[MIN_ACCOUNTS..MIN_ACCOUNTS + supplemental_tick_arrays_count] {
+            self.validate_tick_array(tick_array, &whirlpool.key())?;
+        }
+
         Ok(())
     }
 
@@ -350,4 +525,42 @@ impl DexAdapter for WhirlpoolAdapter {
         }
         Ok(())
     }
-}
\ No newline at end of file
+
+    /// Reads the pool vault holding the input token (token_vault_a at index 8, token_vault_b at index 10)
+    fn input_reserve(
+        &self,
+        ctx: AdapterContext,
+        remaining_accounts_start_index: usize,
+        remaining_accounts_count: usize,
+    ) -> Result<u64> {
+        if remaining_accounts_count < MIN_ACCOUNTS {
+            return Err(ErrorCode::NotEnoughAccountKeys.into());
+        }
+
+        let end_index = remaining_accounts_start_index + remaining_accounts_count;
+        if ctx.remaining_accounts.len() < end_index {
+            return Err(ErrorCode::NotEnoughAccountKeys.into());
+        }
+
+        let adapter_accounts = &ctx.remaining_accounts[remaining_accounts_start_index..end_index];
+
+        let input_mint = TokenAccount::try_deserialize(&mut ctx.input_account.data.borrow().as_ref())?.mint;
+        let a_to_b = self.swap_direction(&adapter_accounts[4], &input_mint)?;
+
+        // to_pool_order is its own inverse: maps the pool's (A, B) vaults back to swap direction
+        let (input_vault, _) = to_pool_order(a_to_b, &adapter_accounts[8], &adapter_accounts[10]);
+        pool_vault_amount(input_vault)
+    }
+
+    /// Not quoted: a Whirlpool prices from its sqrt_price and in-range liquidity, which its
+    /// vault balances do not reflect, so routes through it skip the quote sanity check
+    fn quote(
+        &self,
+        _ctx: AdapterContext,
+        _amount: u64,
+        _remaining_accounts_start_index: usize,
+        _remaining_accounts_count: usize,
+    ) -> Result<Option<u64>> {
+        Ok(None)
+    }
+}
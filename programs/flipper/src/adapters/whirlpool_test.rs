@@ -0,0 +1,498 @@
+#[cfg(test)]
+mod tests {
+    use super::super::dex_adapter::DexAdapter;
+    use super::super::whirlpool::*;
+    use crate::errors::ErrorCode;
+    use crate::state::{PoolInfo, Swap, SPL_MEMO_PROGRAM_ID};
+    use crate::test_utils::*;
+    use anchor_lang::prelude::*;
+
+    const TOKEN_PROGRAM_ID: Pubkey = anchor_spl::token::ID;
+    const TOKEN_2022_PROGRAM_ID: Pubkey = anchor_spl::token_2022::ID;
+
+    fn create_test_adapter(a_to_b: bool) -> WhirlpoolAdapter {
+        WhirlpoolAdapter {
+            program_id: Pubkey::new_unique(),
+            a_to_b,
+            memo_program_id: SPL_MEMO_PROGRAM_ID,
+        }
+    }
+
+    // Adapter accounts for swapV2, with pool vaults in the pool's fixed order (A, B)
+    // and `passed_mints` at indices 5 and 6
+    fn create_whirlpool_accounts(
+        adapter: &WhirlpoolAdapter,
+        mint_a: Pubkey,
+        mint_b: Pubkey,
+        passed_mints: (Pubkey, Pubkey),
+    ) -> Vec<TestAccount> {
+        let whirlpool = Pubkey::new_unique();
+        vec![
+            pool_info_account(Swap::Whirlpool { a_to_b: adapter.a_to_b }, whirlpool, true),
+            program_account(TOKEN_PROGRAM_ID),                       // token_program_a
+            program_account(TOKEN_PROGRAM_ID),                       // token_program_b
+            program_account(SPL_MEMO_PROGRAM_ID),                    // memo_program
+            TestAccount::new(adapter.program_id, mock_whirlpool_data(mint_a, mint_b)).with_key(whirlpool),
+            mint_account(6, TOKEN_PROGRAM_ID).with_key(passed_mints.0),
+            mint_account(6, TOKEN_PROGRAM_ID).with_key(passed_mints.1),
+            empty_account(),                                         // token_owner_account_a (from ctx)
+            token_account(mint_a, whirlpool, 1_000_000, TOKEN_PROGRAM_ID), // token_vault_a
+            empty_account(),                                         // token_owner_account_b (from ctx)
+            token_account(mint_b, whirlpool, 1_000_000, TOKEN_PROGRAM_ID), // token_vault_b
+            empty_account(),                                         // tick_array_0
+            empty_account(),                                         // tick_array_1
+            empty_account(),                                         // tick_array_2
+            empty_account().with_key(whirlpool_oracle_address(&adapter.program_id, &whirlpool)), // oracle
+        ]
+    }
+
+    // Moves `mint` (token A or B of the pool) to Token-2022, together with its token program
+    fn use_token_2022(accounts: &mut [TestAccount], mint: Pubkey, token_program_index: usize) {
+        let mint_index = if accounts[5].key == mint { 5 } else { 6 };
+        accounts[mint_index] = mint_account(6, TOKEN_2022_PROGRAM_ID).with_key(mint);
+        accounts[token_program_index] = program_account(TOKEN_2022_PROGRAM_ID);
+    }
+
+    // Validates a swap out of an input vault holding `input_mint`
+    fn validate(adapter: &WhirlpoolAdapter, accounts: Vec<TestAccount>, input_mint: Pubkey) -> Result<()> {
+        let count = accounts.len();
+        let vault_owner = Pubkey::new_unique();
+        let ctx = adapter_context(
+            token_account(input_mint, vault_owner, 1_000, TOKEN_PROGRAM_ID),
+            token_account(Pubkey::new_unique(), vault_owner, 0, TOKEN_PROGRAM_ID),
+            accounts,
+        );
+        adapter.validate_accounts(ctx, 0, count)
+    }
+
+    #[test]
+    fn test_to_pool_order_a_to_b_keeps_pair() {
+        assert_eq!(to_pool_order(true, "input", "output"), ("input", "output"));
+    }
+
+    #[test]
+    fn test_to_pool_order_b_to_a_swaps_pair() {
+        assert_eq!(to_pool_order(false, "input", "output"), ("output", "input"));
+    }
+
+    #[test]
+    fn test_whirlpool_token_mints_layouts() {
+        let (mint_a, mint_b) = (Pubkey::new_unique(), Pubkey::new_unique());
+        assert_eq!(whirlpool_token_mints(&mock_whirlpool_data(mint_a, mint_b)), Some((mint_a, mint_b)));
+
+        let mut orca = vec![0u8; WHIRLPOOL_LEN];
+        orca[..8].copy_from_slice(&WHIRLPOOL_DISCRIMINATOR);
+        orca[WHIRLPOOL_TOKEN_MINT_A_OFFSET..WHIRLPOOL_TOKEN_MINT_A_OFFSET + 32].copy_from_slice(mint_a.as_ref());
+        orca[WHIRLPOOL_TOKEN_MINT_B_OFFSET..WHIRLPOOL_TOKEN_MINT_B_OFFSET + 32].copy_from_slice(mint_b.as_ref());
+        assert_eq!(whirlpool_token_mints(&orca), Some((mint_a, mint_b)));
+
+        // Other accounts, e.g. tick arrays, are not whirlpools
+        let mut tick_array = mock_whirlpool_data(mint_a, mint_b);
+        tick_array[..8].copy_from_slice(&TICK_ARRAY_DISCRIMINATOR);
+        assert_eq!(whirlpool_token_mints(&tick_array), None);
+        assert_eq!(whirlpool_token_mints(&mock_whirlpool_data(mint_a, mint_b)[..50]), None);
+        assert_eq!(whirlpool_token_mints(&[]), None);
+    }
+
+    #[test]
+    fn test_whirlpool_token_vaults_layouts() {
+        let (vault_a, vault_b) = (Pubkey::new_unique(), Pubkey::new_unique());
+        let mut mock = mock_whirlpool_data(Pubkey::new_unique(), Pubkey::new_unique());
+        mock[72..104].copy_from_slice(vault_a.as_ref());
+        mock[104..136].copy_from_slice(vault_b.as_ref());
+        assert_eq!(whirlpool_token_vaults(&mock), Some((vault_a, vault_b)));
+
+        let mut orca = vec![0u8; WHIRLPOOL_LEN];
+        orca[..8].copy_from_slice(&WHIRLPOOL_DISCRIMINATOR);
+        orca[WHIRLPOOL_TOKEN_VAULT_A_OFFSET..WHIRLPOOL_TOKEN_VAULT_A_OFFSET + 32].copy_from_slice(vault_a.as_ref());
+        orca[WHIRLPOOL_TOKEN_VAULT_B_OFFSET..WHIRLPOOL_TOKEN_VAULT_B_OFFSET + 32].copy_from_slice(vault_b.as_ref());
+        assert_eq!(whirlpool_token_vaults(&orca), Some((vault_a, vault_b)));
+
+        mock[..8].copy_from_slice(&TICK_ARRAY_DISCRIMINATOR);
+        assert_eq!(whirlpool_token_vaults(&mock), None);
+        assert_eq!(whirlpool_token_vaults(&[]), None);
+    }
+
+    #[test]
+    fn test_swap_direction_follows_input_mint_not_client_flag() {
+        let (mint_a, mint_b) = (Pubkey::new_unique(), Pubkey::new_unique());
+        for client_a_to_b in [true, false] {
+            let adapter = create_test_adapter(client_a_to_b);
+            let whirlpool = leak_account_infos(vec![TestAccount::new(adapter.program_id, mock_whirlpool_data(mint_a, mint_b))]);
+
+            assert!(adapter.swap_direction(&whirlpool[0], &mint_a).unwrap());
+            assert!(!adapter.swap_direction(&whirlpool[0], &mint_b).unwrap());
+            let err = adapter.swap_direction(&whirlpool[0], &Pubkey::new_unique()).unwrap_err();
+            assert_eq!(err, ErrorCode::InvalidMint.into());
+        }
+    }
+
+    #[test]
+    fn test_mints_in_pool_order_accepts_either_order() {
+        let (mint_a, mint_b) = (Pubkey::new_unique(), Pubkey::new_unique());
+        let mints = leak_account_infos(vec![
+            mint_account(6, TOKEN_PROGRAM_ID).with_key(mint_a),
+            mint_account(6, TOKEN_PROGRAM_ID).with_key(mint_b),
+            mint_account(6, TOKEN_PROGRAM_ID),
+        ]);
+
+        let (first, second) = mints_in_pool_order(&mints[0], &mints[1], &mint_a, &mint_b).unwrap();
+        assert_eq!((*first.key, *second.key), (mint_a, mint_b));
+        let (first, second) = mints_in_pool_order(&mints[1], &mints[0], &mint_a, &mint_b).unwrap();
+        assert_eq!((*first.key, *second.key), (mint_a, mint_b));
+
+        let err = mints_in_pool_order(&mints[0], &mints[2], &mint_a, &mint_b).unwrap_err();
+        assert_eq!(err, ErrorCode::InvalidMint.into());
+    }
+
+    #[test]
+    fn test_supplemental_tick_arrays_count() {
+        assert_eq!(supplemental_tick_arrays_count(0), 0);
+        assert_eq!(supplemental_tick_arrays_count(MIN_ACCOUNTS), 0);
+        // Only the trailing program id
+        assert_eq!(supplemental_tick_arrays_count(MIN_ACCOUNTS + 1), 0);
+        assert_eq!(supplemental_tick_arrays_count(MIN_ACCOUNTS + 2), 1);
+        assert_eq!(supplemental_tick_arrays_count(MIN_ACCOUNTS + 4), 3);
+        // Capped at 3
+        assert_eq!(supplemental_tick_arrays_count(MIN_ACCOUNTS + 10), 3);
+    }
+
+    #[test]
+    fn test_validate_accounts_a_to_b() {
+        let adapter = create_test_adapter(true);
+        let (mint_a, mint_b) = (Pubkey::new_unique(), Pubkey::new_unique());
+        let accounts = create_whirlpool_accounts(&adapter, mint_a, mint_b, (mint_a, mint_b));
+
+        assert!(validate(&adapter, accounts, mint_a).is_ok());
+    }
+
+    #[test]
+    fn test_validate_accounts_b_to_a_mints_in_swap_direction() {
+        let adapter = create_test_adapter(false);
+        let (mint_a, mint_b) = (Pubkey::new_unique(), Pubkey::new_unique());
+        // Swapping B -> A: mints are passed as (from, to) = (B, A)
+        let accounts = create_whirlpool_accounts(&adapter, mint_a, mint_b, (mint_b, mint_a));
+
+        assert!(validate(&adapter, accounts, mint_b).is_ok());
+    }
+
+    #[test]
+    fn test_validate_accounts_ignores_wrong_client_flag() {
+        let (mint_a, mint_b) = (Pubkey::new_unique(), Pubkey::new_unique());
+        // The flag and the mint order disagree with the input vault; the pool's mints decide
+        for (client_a_to_b, input_mint) in [(true, mint_b), (false, mint_a)] {
+            let adapter = create_test_adapter(client_a_to_b);
+            for passed_mints in [(mint_a, mint_b), (mint_b, mint_a)] {
+                let accounts = create_whirlpool_accounts(&adapter, mint_a, mint_b, passed_mints);
+                assert!(validate(&adapter, accounts, input_mint).is_ok());
+            }
+        }
+    }
+
+    #[test]
+    fn test_validate_accounts_input_mint_not_in_pool() {
+        let adapter = create_test_adapter(true);
+        let (mint_a, mint_b) = (Pubkey::new_unique(), Pubkey::new_unique());
+        let accounts = create_whirlpool_accounts(&adapter, mint_a, mint_b, (mint_a, mint_b));
+
+        let err = validate(&adapter, accounts, Pubkey::new_unique()).unwrap_err();
+        assert_eq!(err, ErrorCode::InvalidMint.into());
+    }
+
+    #[test]
+    fn test_validate_accounts_rejects_mints_of_other_pool() {
+        let adapter = create_test_adapter(true);
+        let (mint_a, mint_b) = (Pubkey::new_unique(), Pubkey::new_unique());
+        let accounts = create_whirlpool_accounts(&adapter, mint_a, mint_b, (mint_a, Pubkey::new_unique()));
+
+        let err = validate(&adapter, accounts, mint_a).unwrap_err();
+        assert_eq!(err, ErrorCode::InvalidMint.into());
+    }
+
+    #[test]
+    fn test_validate_accounts_rejects_vaults_in_swap_direction() {
+        let adapter = create_test_adapter(false);
+        let (mint_a, mint_b) = (Pubkey::new_unique(), Pubkey::new_unique());
+        let mut accounts = create_whirlpool_accounts(&adapter, mint_a, mint_b, (mint_b, mint_a));
+        // Vaults must stay in the pool's order whatever the direction
+        accounts.swap(8, 10);
+
+        let err = validate(&adapter, accounts, mint_b).unwrap_err();
+        assert_eq!(err, ErrorCode::InvalidMint.into());
+    }
+
+    #[test]
+    fn test_validate_accounts_whirlpool_data_not_a_whirlpool() {
+        let adapter = create_test_adapter(true);
+        let (mint_a, mint_b) = (Pubkey::new_unique(), Pubkey::new_unique());
+        let mut accounts = create_whirlpool_accounts(&adapter, mint_a, mint_b, (mint_a, mint_b));
+        let whirlpool = accounts[4].key;
+        accounts[4] = pool_state_account(adapter.program_id, 8).with_key(whirlpool);
+
+        let err = validate(&adapter, accounts, mint_a).unwrap_err();
+        assert_eq!(err, ErrorCode::InvalidAccount.into());
+    }
+
+    #[test]
+    fn test_validate_accounts_disabled_pool() {
+        let adapter = create_test_adapter(true);
+        let (mint_a, mint_b) = (Pubkey::new_unique(), Pubkey::new_unique());
+        let mut accounts = create_whirlpool_accounts(&adapter, mint_a, mint_b, (mint_a, mint_b));
+        let whirlpool = accounts[4].key;
+        accounts[0] = pool_info_account(Swap::Whirlpool { a_to_b: true }, whirlpool, false);
+
+        let err = validate(&adapter, accounts, mint_a).unwrap_err();
+        assert_eq!(err, ErrorCode::PoolDisabled.into());
+    }
+
+    #[test]
+    fn test_validate_accounts_pool_info_for_other_pool() {
+        let adapter = create_test_adapter(true);
+        let (mint_a, mint_b) = (Pubkey::new_unique(), Pubkey::new_unique());
+        let mut accounts = create_whirlpool_accounts(&adapter, mint_a, mint_b, (mint_a, mint_b));
+        accounts[0] = pool_info_account(Swap::Whirlpool { a_to_b: true }, Pubkey::new_unique(), true);
+
+        let err = validate(&adapter, accounts, mint_a).unwrap_err();
+        assert_eq!(err, ErrorCode::InvalidPoolAddress.into());
+    }
+
+    #[test]
+    fn test_validate_accounts_truncated_pool_info() {
+        let adapter = create_test_adapter(true);
+        let (mint_a, mint_b) = (Pubkey::new_unique(), Pubkey::new_unique());
+        let mut accounts = create_whirlpool_accounts(&adapter, mint_a, mint_b, (mint_a, mint_b));
+        accounts[0].data.truncate(PoolInfo::MIN_DATA_LEN - 1);
+
+        let err = validate(&adapter, accounts, mint_a).unwrap_err();
+        assert_eq!(err, ErrorCode::InvalidPoolInfoAccount.into());
+    }
+
+    #[test]
+    fn test_validate_accounts_pool_info_without_discriminator() {
+        let adapter = create_test_adapter(true);
+        let (mint_a, mint_b) = (Pubkey::new_unique(), Pubkey::new_unique());
+        let mut accounts = create_whirlpool_accounts(&adapter, mint_a, mint_b, (mint_a, mint_b));
+        accounts[0].data[..8].fill(0);
+
+        let err = validate(&adapter, accounts, mint_a).unwrap_err();
+        assert_eq!(err, ErrorCode::InvalidPoolInfoAccount.into());
+    }
+
+    #[test]
+    fn test_validate_accounts_wrong_memo_program() {
+        let adapter = create_test_adapter(true);
+        let (mint_a, mint_b) = (Pubkey::new_unique(), Pubkey::new_unique());
+        let mut accounts = create_whirlpool_accounts(&adapter, mint_a, mint_b, (mint_a, mint_b));
+        accounts[3] = program_account(Pubkey::new_unique());
+
+        let err = validate(&adapter, accounts, mint_a).unwrap_err();
+        assert_eq!(err, ErrorCode::InvalidCpiInterface.into());
+    }
+
+    #[test]
+    fn test_validate_accounts_whirlpool_not_owned_by_program() {
+        let adapter = create_test_adapter(true);
+        let (mint_a, mint_b) = (Pubkey::new_unique(), Pubkey::new_unique());
+        let mut accounts = create_whirlpool_accounts(&adapter, mint_a, mint_b, (mint_a, mint_b));
+        accounts[4].owner = Pubkey::new_unique();
+
+        let err = validate(&adapter, accounts, mint_a).unwrap_err();
+        assert_eq!(err, ErrorCode::InvalidCpiInterface.into());
+    }
+
+    #[test]
+    fn test_validate_accounts_not_enough_accounts() {
+        let adapter = create_test_adapter(true);
+        let (mint_a, mint_b) = (Pubkey::new_unique(), Pubkey::new_unique());
+        let mut accounts = create_whirlpool_accounts(&adapter, mint_a, mint_b, (mint_a, mint_b));
+        accounts.pop();
+
+        let err = validate(&adapter, accounts, mint_a).unwrap_err();
+        assert_eq!(err, ErrorCode::NotEnoughAccountKeys.into());
+    }
+
+    #[test]
+    fn test_validate_accounts_token_2022_mint_on_either_side() {
+        let (mint_a, mint_b) = (Pubkey::new_unique(), Pubkey::new_unique());
+        // One Token-2022 mint per pool side, swapped in both directions with the mints in either order
+        for (token_2022_mint, token_program_index) in [(mint_a, 1), (mint_b, 2)] {
+            for (input_mint, passed_mints) in [(mint_a, (mint_a, mint_b)), (mint_b, (mint_b, mint_a))] {
+                let adapter = create_test_adapter(input_mint == mint_a);
+                let mut accounts = create_whirlpool_accounts(&adapter, mint_a, mint_b, passed_mints);
+                use_token_2022(&mut accounts, token_2022_mint, token_program_index);
+
+                assert!(validate(&adapter, accounts, input_mint).is_ok());
+            }
+        }
+    }
+
+    #[test]
+    fn test_validate_accounts_token_program_not_owning_mint() {
+        let adapter = create_test_adapter(true);
+        let (mint_a, mint_b) = (Pubkey::new_unique(), Pubkey::new_unique());
+
+        // Token-2022 mint A with the legacy token program
+        let mut accounts = create_whirlpool_accounts(&adapter, mint_a, mint_b, (mint_a, mint_b));
+        use_token_2022(&mut accounts, mint_a, 1);
+        accounts[1] = program_account(TOKEN_PROGRAM_ID);
+        let err = validate(&adapter, accounts, mint_a).unwrap_err();
+        assert_eq!(err, ErrorCode::InvalidTokenProgram.into());
+
+        // Token-2022 program for legacy mint B
+        let mut accounts = create_whirlpool_accounts(&adapter, mint_a, mint_b, (mint_a, mint_b));
+        accounts[2] = program_account(TOKEN_2022_PROGRAM_ID);
+        let err = validate(&adapter, accounts, mint_a).unwrap_err();
+        assert_eq!(err, ErrorCode::InvalidTokenProgram.into());
+    }
+
+    #[test]
+    fn test_validate_accounts_token_programs_follow_pool_order() {
+        let adapter = create_test_adapter(false);
+        let (mint_a, mint_b) = (Pubkey::new_unique(), Pubkey::new_unique());
+        // Mints passed as (B, A) for a B -> A swap; the token programs stay in pool order
+        let mut accounts = create_whirlpool_accounts(&adapter, mint_a, mint_b, (mint_b, mint_a));
+        use_token_2022(&mut accounts, mint_b, 1);
+
+        let err = validate(&adapter, accounts, mint_b).unwrap_err();
+        assert_eq!(err, ErrorCode::InvalidTokenProgram.into());
+    }
+
+    #[test]
+    fn test_validate_accounts_wrong_oracle() {
+        let adapter = create_test_adapter(true);
+        let (mint_a, mint_b) = (Pubkey::new_unique(), Pubkey::new_unique());
+        // The oracle of another pool is rejected as well as an arbitrary account
+        let mut accounts = create_whirlpool_accounts(&adapter, mint_a, mint_b, (mint_a, mint_b));
+        accounts[14] = empty_account().with_key(whirlpool_oracle_address(&adapter.program_id, &Pubkey::new_unique()));
+        let err = validate(&adapter, accounts, mint_a).unwrap_err();
+        assert_eq!(err, ErrorCode::InvalidOracleAccount.into());
+
+        let mut accounts = create_whirlpool_accounts(&adapter, mint_a, mint_b, (mint_a, mint_b));
+        accounts[14] = empty_account();
+        let err = validate(&adapter, accounts, mint_a).unwrap_err();
+        assert_eq!(err, ErrorCode::InvalidOracleAccount.into());
+    }
+
+    #[test]
+    fn test_input_reserve_follows_swap_direction() {
+        for (client_a_to_b, a_to_b) in [(true, true), (false, false), (true, false), (false, true)] {
+            let adapter = create_test_adapter(client_a_to_b);
+            let (mint_a, mint_b) = (Pubkey::new_unique(), Pubkey::new_unique());
+            let mut accounts = create_whirlpool_accounts(&adapter, mint_a, mint_b, to_pool_order(a_to_b, mint_a, mint_b));
+            accounts[8] = token_account(mint_a, Pubkey::new_unique(), 3_000, TOKEN_PROGRAM_ID);
+            accounts[10] = token_account(mint_b, Pubkey::new_unique(), 7_000, TOKEN_PROGRAM_ID);
+
+            let count = accounts.len();
+            let vault_owner = Pubkey::new_unique();
+            let (input_mint, output_mint) = to_pool_order(a_to_b, mint_a, mint_b);
+            let ctx = adapter_context(
+                token_account(input_mint, vault_owner, 1_000, TOKEN_PROGRAM_ID),
+                token_account(output_mint, vault_owner, 0, TOKEN_PROGRAM_ID),
+                accounts,
+            );
+            // a_to_b swaps into token_vault_a, b_to_a into token_vault_b, whatever the client flag says
+            let expected = if a_to_b { 3_000 } else { 7_000 };
+            assert_eq!(adapter.input_reserve(ctx, 0, count).unwrap(), expected);
+        }
+    }
+
+    // sha256("account:Position")[..8]
+    const POSITION_DISCRIMINATOR: [u8; 8] = [170, 188, 143, 228, 122, 64, 247, 208];
+
+    // Tick array data with `whirlpool` written at `offset`
+    fn tick_array_data(discriminator: [u8; 8], len: usize, offset: usize, whirlpool: Pubkey) -> Vec<u8> {
+        let mut data = vec![0u8; len];
+        data[..8].copy_from_slice(&discriminator);
+        data[offset..offset + 32].copy_from_slice(whirlpool.as_ref());
+        data
+    }
+
+    // Mock program layout: whirlpool then start_tick_index
+    fn mock_tick_array(adapter: &WhirlpoolAdapter, whirlpool: Pubkey) -> TestAccount {
+        TestAccount::new(adapter.program_id, tick_array_data(TICK_ARRAY_DISCRIMINATOR, 8 + 36, 8, whirlpool))
+    }
+
+    // Whirlpool accounts (mints passed as (A, B) at indices 5 and 6) followed by the supplemental
+    // tick arrays built for the pool's key and the closing program id
+    fn with_supplemental_tick_arrays(
+        adapter: &WhirlpoolAdapter,
+        tick_arrays: impl FnOnce(Pubkey) -> Vec<TestAccount>,
+    ) -> Vec<TestAccount> {
+        let (mint_a, mint_b) = (Pubkey::new_unique(), Pubkey::new_unique());
+        let mut accounts = create_whirlpool_accounts(adapter, mint_a, mint_b, (mint_a, mint_b));
+        let whirlpool = accounts[4].key;
+        accounts.extend(tick_arrays(whirlpool));
+        accounts.push(program_account(adapter.program_id));
+        accounts
+    }
+
+    #[test]
+    fn test_tick_array_whirlpool_layouts() {
+        let whirlpool = Pubkey::new_unique();
+
+        let mock = tick_array_data(TICK_ARRAY_DISCRIMINATOR, 8 + 36, 8, whirlpool);
+        assert_eq!(tick_array_whirlpool(&mock), Some(whirlpool));
+
+        let fixed = tick_array_data(TICK_ARRAY_DISCRIMINATOR, FIXED_TICK_ARRAY_LEN, FIXED_TICK_ARRAY_LEN - 32, whirlpool);
+        assert_eq!(tick_array_whirlpool(&fixed), Some(whirlpool));
+        // The start of a fixed tick array holds start_tick_index and ticks, not the whirlpool
+        assert_ne!(tick_array_whirlpool(&tick_array_data(TICK_ARRAY_DISCRIMINATOR, FIXED_TICK_ARRAY_LEN, 8, whirlpool)), Some(whirlpool));
+
+        let dynamic = tick_array_data(DYNAMIC_TICK_ARRAY_DISCRIMINATOR, 8 + 4 + 32 + 16, 12, whirlpool);
+        assert_eq!(tick_array_whirlpool(&dynamic), Some(whirlpool));
+    }
+
+    #[test]
+    fn test_tick_array_whirlpool_rejects_other_accounts() {
+        let whirlpool = Pubkey::new_unique();
+
+        // Other Whirlpool accounts (e.g. positions) also start with the whirlpool key
+        let position = tick_array_data(POSITION_DISCRIMINATOR, 8 + 208, 8, whirlpool);
+        assert_eq!(tick_array_whirlpool(&position), None);
+
+        let truncated = tick_array_data(TICK_ARRAY_DISCRIMINATOR, 8 + 36, 8, whirlpool);
+        assert_eq!(tick_array_whirlpool(&truncated[..20]), None);
+        assert_eq!(tick_array_whirlpool(&[]), None);
+    }
+
+    #[test]
+    fn test_validate_accounts_supplemental_tick_arrays_of_pool() {
+        let adapter = create_test_adapter(true);
+        let accounts = with_supplemental_tick_arrays(&adapter, |whirlpool| {
+            vec![
+                mock_tick_array(&adapter, whirlpool),
+                TestAccount::new(
+                    adapter.program_id,
+                    tick_array_data(DYNAMIC_TICK_ARRAY_DISCRIMINATOR, 8 + 4 + 32 + 16, 12, whirlpool),
+                ),
+            ]
+        });
+
+        let mint_a = accounts[5].key;
+        assert!(validate(&adapter, accounts, mint_a).is_ok());
+    }
+
+    #[test]
+    fn test_validate_accounts_supplemental_tick_array_of_other_pool() {
+        let adapter = create_test_adapter(true);
+        let accounts = with_supplemental_tick_arrays(&adapter, |_| vec![mock_tick_array(&adapter, Pubkey::new_unique())]);
+
+        let mint_a = accounts[5].key;
+        let err = validate(&adapter, accounts, mint_a).unwrap_err();
+        assert_eq!(err, ErrorCode::InvalidAccount.into());
+    }
+
+    #[test]
+    fn test_validate_accounts_supplemental_tick_array_not_owned_by_program() {
+        let adapter = create_test_adapter(true);
+        let accounts = with_supplemental_tick_arrays(&adapter, |whirlpool| {
+            let mut tick_array = mock_tick_array(&adapter, whirlpool);
+            tick_array.owner = Pubkey::new_unique();
+            vec![tick_array]
+        });
+
+        let mint_a = accounts[5].key;
+        let err = validate(&adapter, accounts, mint_a).unwrap_err();
+        assert_eq!(err, ErrorCode::InvalidCpiInterface.into());
+    }
+}
@@ -0,0 +1,370 @@
+use anchor_lang::prelude::*;
+use anchor_lang::solana_program::instruction::Instruction;
+use anchor_spl::token_interface::TokenAccount;
+use crate::adapters::adapter_connector_module::{AdapterContext};
+use crate::adapters::adapter_errors::{self, WHIRLPOOL_ERRORS};
+use crate::adapters::dex_adapter::{DexAdapter, pool_vault_amount};
+use crate::adapters::whirlpool::{whirlpool_token_mints, RemainingAccountsInfo, WhirlpoolAdapter};
+use crate::errors::ErrorCode;
+use crate::state::{Swap, SwapResult, PoolInfo};
+
+/// Executes two Whirlpool pools in one two_hop_swap_v2 call, so the intermediate token never
+/// lands in a router vault
+pub struct WhirlpoolTwoHopAdapter {
+    pub program_id: Pubkey,
+    pub a_to_b_one: bool, // Client-provided directions; only logged, the swap follows the pools' mints
+    pub a_to_b_two: bool,
+    pub memo_program_id: Pubkey, // Memo program expected by two_hop_swap_v2, from the registry
+}
+
+const TOKEN_PROGRAM_ID: Pubkey = anchor_spl::token::ID;
+const TOKEN_2022_PROGRAM_ID: Pubkey = anchor_spl::token_2022::ID;
+
+const TWO_HOP_SWAP_V2_DISCRIMINATOR: [u8; 8] = [186, 143, 209, 29, 254, 2, 194, 117];
+
+#[derive(Clone, AnchorSerialize, AnchorDeserialize)]
+pub struct TwoHopSwapV2Args {
+    pub amount: u64,
+    pub other_amount_threshold: u64,
+    pub amount_specified_is_input: bool,
+    pub a_to_b_one: bool,
+    pub a_to_b_two: bool,
+    pub sqrt_price_limit_one: u128,
+    pub sqrt_price_limit_two: u128,
+    pub remaining_accounts_info: Option<RemainingAccountsInfo>,
+}
+
+/// Minimum adapter accounts for a two_hop_swap_v2 (pool_info_one through oracle_two)
+///
+/// Layout: 0 pool_info_one, 1 pool_info_two, 2-4 token programs (input, intermediate, output),
+/// 5 memo program, 6 whirlpool_one, 7 whirlpool_two, 8-10 mints (input, intermediate, output),
+/// 11 vault_one_input, 12 vault_one_intermediate, 13 vault_two_intermediate, 14 vault_two_output,
+/// 15-17 tick arrays of pool one, 18-20 tick arrays of pool two, 21 oracle_one, 22 oracle_two
+pub const MIN_ACCOUNTS: usize = 23;
+
+/// Index of whirlpool_one (the pool pool_info_one names) in the adapter accounts
+pub const POOL_ACCOUNT_INDEX: usize = 6;
+
+/// Index of the second hop's PoolInfo in the adapter accounts
+pub const POOL_INFO_TWO_INDEX: usize = 1;
+
+/// Index of whirlpool_two in the adapter accounts
+pub const POOL_TWO_ACCOUNT_INDEX: usize = 7;
+
+impl WhirlpoolTwoHopAdapter {
+    /// The swap type both PoolInfo accounts of the step are registered under
+    pub fn swap(&self) -> Swap {
+        Swap::WhirlpoolTwoHop { a_to_b_one: self.a_to_b_one, a_to_b_two: self.a_to_b_two }
+    }
+
+    /// Single-pool view of one hop, to reuse the swapV2 adapter's pool checks
+    fn hop(&self, a_to_b: bool) -> WhirlpoolAdapter {
+        WhirlpoolAdapter {
+            program_id: self.program_id,
+            a_to_b,
+            memo_program_id: self.memo_program_id,
+        }
+    }
+
+    /// Derives both hop directions from the pools' mints and the input mint
+    ///
+    /// # Returns
+    /// * `Result<(bool, bool, Pubkey, Pubkey)>` - (a_to_b_one, a_to_b_two, intermediate mint, output mint);
+    ///   InvalidAccount if either pool is not a Whirlpool, InvalidMint if the pools don't chain from the input
+    pub fn hop_directions(
+        &self,
+        whirlpool_one: &AccountInfo,
+        whirlpool_two: &AccountInfo,
+        input_mint: &Pubkey,
+    ) -> Result<(bool, bool, Pubkey, Pubkey)> {
+        let a_to_b_one = self.hop(self.a_to_b_one).swap_direction(whirlpool_one, input_mint)?;
+        let (one_mint_a, one_mint_b) = whirlpool_token_mints(&whirlpool_one.try_borrow_data()?)
+            .ok_or(ErrorCode::InvalidAccount)?;
+        let intermediate_mint = if a_to_b_one { one_mint_b } else { one_mint_a };
+
+        let a_to_b_two = self.hop(self.a_to_b_two).swap_direction(whirlpool_two, &intermediate_mint)?;
+        let (two_mint_a, two_mint_b) = whirlpool_token_mints(&whirlpool_two.try_borrow_data()?)
+            .ok_or(ErrorCode::InvalidAccount)?;
+        let output_mint = if a_to_b_two { two_mint_b } else { two_mint_a };
+
+        Ok((a_to_b_one, a_to_b_two, intermediate_mint, output_mint))
+    }
+}
+
+impl DexAdapter for WhirlpoolTwoHopAdapter {
+    fn execute_swap(
+        &self,
+        ctx: AdapterContext,
+        amount: u64,
+        remaining_accounts_start_index: usize,
+        remaining_accounts_count: usize,
+    ) -> Result<SwapResult> {
+        msg!("Executing Whirlpool two_hop_swap_v2, amount: {}", amount);
+
+        if remaining_accounts_count < MIN_ACCOUNTS {
+            return Err(ErrorCode::NotEnoughAccountKeys.into());
+        }
+
+        let end_index = remaining_accounts_start_index + remaining_accounts_count;
+        if ctx.remaining_accounts.len() < end_index {
+            return Err(ErrorCode::NotEnoughAccountKeys.into());
+        }
+
+        let adapter_accounts = &ctx.remaining_accounts[remaining_accounts_start_index..end_index];
+
+        // Both pools must be enabled
+        for pool_info_account in &adapter_accounts[..=POOL_INFO_TWO_INDEX] {
+            if !PoolInfo::load_checked(pool_info_account)?.enabled {
+                return Err(ErrorCode::PoolDisabled.into());
+            }
+        }
+
+        // Derive the directions from the pools' mints rather than the client flags
+        let input_mint = TokenAccount::try_deserialize(&mut ctx.input_account.data.borrow().as_ref())?.mint;
+        let (a_to_b_one, a_to_b_two, _, _) = self.hop_directions(
+            &adapter_accounts[POOL_ACCOUNT_INDEX],
+            &adapter_accounts[POOL_TWO_ACCOUNT_INDEX],
+            &input_mint,
+        )?;
+
+        // Record initial output token balance
+        let output_vault_data = TokenAccount::try_deserialize(&mut ctx.output_account.data.borrow().as_ref())?;
+        let initial_output_amount = output_vault_data.amount;
+
+        let swap_args = TwoHopSwapV2Args {
+            amount,
+            other_amount_threshold: 0,
+            amount_specified_is_input: true,
+            a_to_b_one,
+            a_to_b_two,
+            sqrt_price_limit_one: 0,
+            sqrt_price_limit_two: 0,
+            remaining_accounts_info: None,
+        };
+
+        let mut instruction_data = Vec::new();
+        instruction_data.extend_from_slice(&TWO_HOP_SWAP_V2_DISCRIMINATOR);
+        instruction_data.extend_from_slice(&swap_args.try_to_vec()?);
+
+        // Unlike swapV2, two_hop_swap_v2 takes its mints, programs and vaults in swap direction
+        // (input, intermediate, output), so the adapter slice is forwarded without reordering
+        let accounts = vec![
+            AccountMeta::new(adapter_accounts[6].key(), false),             // whirlpool_one
+            AccountMeta::new(adapter_accounts[7].key(), false),             // whirlpool_two
+            AccountMeta::new_readonly(adapter_accounts[8].key(), false),    // token_mint_input
+            AccountMeta::new_readonly(adapter_accounts[9].key(), false),    // token_mint_intermediate
+            AccountMeta::new_readonly(adapter_accounts[10].key(), false),   // token_mint_output
+            AccountMeta::new_readonly(adapter_accounts[2].key(), false),    // token_program_input
+            AccountMeta::new_readonly(adapter_accounts[3].key(), false),    // token_program_intermediate
+            AccountMeta::new_readonly(adapter_accounts[4].key(), false),    // token_program_output
+            AccountMeta::new(ctx.input_account.key(), false),               // token_owner_account_input
+            AccountMeta::new(adapter_accounts[11].key(), false),            // token_vault_one_input
+            AccountMeta::new(adapter_accounts[12].key(), false),            // token_vault_one_intermediate
+            AccountMeta::new(adapter_accounts[13].key(), false),            // token_vault_two_intermediate
+            AccountMeta::new(adapter_accounts[14].key(), false),            // token_vault_two_output
+            AccountMeta::new(ctx.output_account.key(), false),              // token_owner_account_output
+            AccountMeta::new_readonly(ctx.authority.key(), true),           // token_authority
+            AccountMeta::new(adapter_accounts[15].key(), false),            // tick_array_one_0
+            AccountMeta::new(adapter_accounts[16].key(), false),            // tick_array_one_1
+            AccountMeta::new(adapter_accounts[17].key(), false),            // tick_array_one_2
+            AccountMeta::new(adapter_accounts[18].key(), false),            // tick_array_two_0
+            AccountMeta::new(adapter_accounts[19].key(), false),            // tick_array_two_1
+            AccountMeta::new(adapter_accounts[20].key(), false),            // tick_array_two_2
+            AccountMeta::new(adapter_accounts[21].key(), false),            // oracle_one (mut in TwoHopSwapV2)
+            AccountMeta::new(adapter_accounts[22].key(), false),            // oracle_two (mut in TwoHopSwapV2)
+            AccountMeta::new_readonly(adapter_accounts[5].key(), false),    // memo_program
+        ];
+
+        let account_infos = vec![
+            adapter_accounts[6].clone(),     // whirlpool_one
+            adapter_accounts[7].clone(),     // whirlpool_two
+            adapter_accounts[8].clone(),     // token_mint_input
+            adapter_accounts[9].clone(),     // token_mint_intermediate
+            adapter_accounts[10].clone(),    // token_mint_output
+            adapter_accounts[2].clone(),     // token_program_input
+            adapter_accounts[3].clone(),     // token_program_intermediate
+            adapter_accounts[4].clone(),     // token_program_output
+            ctx.input_account.clone(),       // token_owner_account_input
+            adapter_accounts[11].clone(),    // token_vault_one_input
+            adapter_accounts[12].clone(),    // token_vault_one_intermediate
+            adapter_accounts[13].clone(),    // token_vault_two_intermediate
+            adapter_accounts[14].clone(),    // token_vault_two_output
+            ctx.output_account.clone(),      // token_owner_account_output
+            ctx.authority.clone(),           // token_authority
+            adapter_accounts[15].clone(),    // tick_array_one_0
+            adapter_accounts[16].clone(),    // tick_array_one_1
+            adapter_accounts[17].clone(),    // tick_array_one_2
+            adapter_accounts[18].clone(),    // tick_array_two_0
+            adapter_accounts[19].clone(),    // tick_array_two_1
+            adapter_accounts[20].clone(),    // tick_array_two_2
+            adapter_accounts[21].clone(),    // oracle_one
+            adapter_accounts[22].clone(),    // oracle_two
+            adapter_accounts[5].clone(),     // memo_program
+        ];
+
+        let instruction = Instruction {
+            program_id: self.program_id,
+            accounts,
+            data: instruction_data,
+        };
+
+        // Find vault authority PDA and verify that ctx.authority matches it
+        let (vault_authority_pda, vault_authority_bump) = Pubkey::find_program_address(
+            &[b"vault_authority"],
+            &ctx.program_id,
+        );
+        if ctx.authority.key() != vault_authority_pda {
+            return Err(ErrorCode::InvalidAccount.into());
+        }
+
+        let authority_seeds: &[&[u8]] = &[b"vault_authority", &[vault_authority_bump]];
+        let signer_seeds: &[&[&[u8]]] = &[authority_seeds];
+
+        adapter_errors::invoke_adapter_cpi("Whirlpool two-hop", WHIRLPOOL_ERRORS, &instruction, &account_infos, signer_seeds)?;
+
+        let output_vault_data = TokenAccount::try_deserialize(&mut ctx.output_account.data.borrow().as_ref())?;
+        let output_amount = output_vault_data.amount
+            .checked_sub(initial_output_amount)
+            .ok_or(ErrorCode::InvalidCalculation)?;
+
+        msg!("Whirlpool two_hop_swap_v2 completed, output amount: {}", output_amount);
+
+        Ok(SwapResult { output_amount, host_fee_amount: 0 })
+    }
+
+    fn validate_accounts(
+        &self,
+        ctx: AdapterContext,
+        remaining_accounts_start_index: usize,
+        remaining_accounts_count: usize,
+    ) -> Result<()> {
+        if remaining_accounts_count < MIN_ACCOUNTS {
+            return Err(ErrorCode::NotEnoughAccountKeys.into());
+        }
+
+        let end_index = remaining_accounts_start_index + remaining_accounts_count;
+        if ctx.remaining_accounts.len() < end_index {
+            return Err(ErrorCode::NotEnoughAccountKeys.into());
+        }
+
+        let adapter_accounts = &ctx.remaining_accounts[remaining_accounts_start_index..end_index];
+        let whirlpool_one = &adapter_accounts[POOL_ACCOUNT_INDEX];
+        let whirlpool_two = &adapter_accounts[POOL_TWO_ACCOUNT_INDEX];
+
+        // Validate both PoolInfo accounts (indices 0 and 1). The route validator checks the first
+        // one's swap type like any step's; the second must be registered under the same swap type
+        for (pool_info_account, whirlpool) in [(&adapter_accounts[0], whirlpool_one), (&adapter_accounts[1], whirlpool_two)] {
+            let pool_info = PoolInfo::load_checked(pool_info_account)?;
+            if !pool_info.enabled {
+                return Err(ErrorCode::PoolDisabled.into());
+            }
+            if pool_info.adapter_swap_type != self.swap() || pool_info.pool_address != whirlpool.key() {
+                return Err(ErrorCode::InvalidPoolAddress.into());
+            }
+        }
+
+        // Orca rejects a two-hop swap through the same pool twice
+        if whirlpool_one.key() == whirlpool_two.key() {
+            return Err(ErrorCode::InvalidPoolAddress.into());
+        }
+
+        // Validate memo program (index 5) forwarded to two_hop_swap_v2
+        self.hop(self.a_to_b_one).validate_memo_program(&adapter_accounts[5].key())?;
+
+        // Validate token programs (input, intermediate, output)
+        let token_programs = &adapter_accounts[2..=4];
+        let valid_token_programs = [TOKEN_PROGRAM_ID, TOKEN_2022_PROGRAM_ID];
+        if token_programs.iter().any(|program| !valid_token_programs.contains(&program.key())) {
+            return Err(ErrorCode::InvalidCpiInterface.into());
+        }
+
+        // Validate whirlpool ownership
+        if whirlpool_one.owner != &self.program_id || whirlpool_two.owner != &self.program_id {
+            return Err(ErrorCode::InvalidCpiInterface.into());
+        }
+
+        // The pools must chain: input -> intermediate through pool one, intermediate -> output through pool two
+        let input_vault = TokenAccount::try_deserialize(&mut ctx.input_account.data.borrow().as_ref())
+            .map_err(|_| ErrorCode::InvalidAccount)?;
+        let output_vault = TokenAccount::try_deserialize(&mut ctx.output_account.data.borrow().as_ref())
+            .map_err(|_| ErrorCode::InvalidAccount)?;
+        let (_, _, intermediate_mint, output_mint) =
+            self.hop_directions(whirlpool_one, whirlpool_two, &input_vault.mint)?;
+        if output_vault.mint != output_mint {
+            return Err(ErrorCode::InvalidMint.into());
+        }
+
+        // Mints (indices 8-10) are passed in swap direction, each owned by its token program
+        let expected_mints = [input_vault.mint, intermediate_mint, output_mint];
+        for ((mint, expected_mint), token_program) in adapter_accounts[8..=10].iter().zip(expected_mints).zip(token_programs) {
+            if mint.key() != expected_mint {
+                return Err(ErrorCode::InvalidMint.into());
+            }
+            if mint.owner != token_program.key {
+                return Err(ErrorCode::InvalidTokenProgram.into());
+            }
+        }
+
+        // Validate pool vaults (indices 11-14): one_input, one_intermediate, two_intermediate, two_output
+        let expected_vault_mints = [input_vault.mint, intermediate_mint, intermediate_mint, output_mint];
+        for (vault, expected_mint) in adapter_accounts[11..=14].iter().zip(expected_vault_mints) {
+            if vault.key() == Pubkey::default() {
+                return Err(ErrorCode::InvalidAccount.into());
+            }
+            let vault_data = TokenAccount::try_deserialize(&mut vault.data.borrow().as_ref())
+                .map_err(|_| ErrorCode::InvalidAccount)?;
+            if vault_data.mint != expected_mint {
+                return Err(ErrorCode::InvalidMint.into());
+            }
+        }
+
+        // Validate tick arrays (indices 15-20 match execute_swap)
+        if adapter_accounts[15..=20].iter().any(|tick_array| tick_array.key() == Pubkey::default()) {
+            return Err(ErrorCode::InvalidAccount.into());
+        }
+
+        // Validate oracles (indices 21 and 22), passed writable to two_hop_swap_v2
+        self.hop(self.a_to_b_one).validate_oracle(&adapter_accounts[21].key(), &whirlpool_one.key())?;
+        self.hop(self.a_to_b_two).validate_oracle(&adapter_accounts[22].key(), &whirlpool_two.key())?;
+
+        Ok(())
+    }
+
+    fn validate_cpi(&self, program_id: &Pubkey) -> Result<()> {
+        if *program_id != self.program_id {
+            return Err(ErrorCode::InvalidCpiInterface.into());
+        }
+        Ok(())
+    }
+
+    /// Reads pool one's vault holding the input token (token_vault_one_input at index 11)
+    fn input_reserve(
+        &self,
+        ctx: AdapterContext,
+        remaining_accounts_start_index: usize,
+        remaining_accounts_count: usize,
+    ) -> Result<u64> {
+        if remaining_accounts_count < MIN_ACCOUNTS {
+            return Err(ErrorCode::NotEnoughAccountKeys.into());
+        }
+
+        let end_index = remaining_accounts_start_index + remaining_accounts_count;
+        if ctx.remaining_accounts.len() < end_index {
+            return Err(ErrorCode::NotEnoughAccountKeys.into());
+        }
+
+        pool_vault_amount(&ctx.remaining_accounts[remaining_accounts_start_index + 11])
+    }
+
+    /// Not quoted: both hops are Whirlpools, which price from sqrt_price and in-range
+    /// liquidity rather than vault balances (see WhirlpoolAdapter::quote)
+    fn quote(
+        &self,
+        _ctx: AdapterContext,
+        _amount: u64,
+        _remaining_accounts_start_index: usize,
+        _remaining_accounts_count: usize,
+    ) -> Result<Option<u64>> {
+        Ok(None)
+    }
+}
@@ -0,0 +1,217 @@
+#[cfg(test)]
+mod tests {
+    use super::super::adapter_connector_module::AdapterContext;
+    use super::super::dex_adapter::DexAdapter;
+    use super::super::whirlpool_two_hop::*;
+    use crate::errors::ErrorCode;
+    use crate::state::{Swap, SPL_MEMO_PROGRAM_ID};
+    use crate::test_utils::*;
+    use anchor_lang::prelude::*;
+
+    const TOKEN_PROGRAM_ID: Pubkey = anchor_spl::token::ID;
+
+    fn create_test_adapter(a_to_b_one: bool, a_to_b_two: bool) -> WhirlpoolTwoHopAdapter {
+        WhirlpoolTwoHopAdapter {
+            program_id: Pubkey::new_unique(),
+            a_to_b_one,
+            a_to_b_two,
+            memo_program_id: SPL_MEMO_PROGRAM_ID,
+        }
+    }
+
+    // Mints of a swap input -> intermediate -> output
+    struct Mints {
+        input: Pubkey,
+        intermediate: Pubkey,
+        output: Pubkey,
+    }
+
+    fn new_mints() -> Mints {
+        Mints { input: Pubkey::new_unique(), intermediate: Pubkey::new_unique(), output: Pubkey::new_unique() }
+    }
+
+    // Adapter accounts for two_hop_swap_v2, with each pool's mints stored in the order its
+    // hop direction implies (the hop's input is token A when a_to_b)
+    fn create_two_hop_accounts(adapter: &WhirlpoolTwoHopAdapter, mints: &Mints, a_to_b_one: bool, a_to_b_two: bool) -> Vec<TestAccount> {
+        let (whirlpool_one, whirlpool_two) = (Pubkey::new_unique(), Pubkey::new_unique());
+        let pool_one_mints = if a_to_b_one { (mints.input, mints.intermediate) } else { (mints.intermediate, mints.input) };
+        let pool_two_mints = if a_to_b_two { (mints.intermediate, mints.output) } else { (mints.output, mints.intermediate) };
+        vec![
+            pool_info_account(adapter.swap(), whirlpool_one, true),    // pool_info_one
+            pool_info_account(adapter.swap(), whirlpool_two, true),    // pool_info_two
+            program_account(TOKEN_PROGRAM_ID),                         // token_program_input
+            program_account(TOKEN_PROGRAM_ID),                         // token_program_intermediate
+            program_account(TOKEN_PROGRAM_ID),                         // token_program_output
+            program_account(SPL_MEMO_PROGRAM_ID),                      // memo_program
+            TestAccount::new(adapter.program_id, mock_whirlpool_data(pool_one_mints.0, pool_one_mints.1)).with_key(whirlpool_one),
+            TestAccount::new(adapter.program_id, mock_whirlpool_data(pool_two_mints.0, pool_two_mints.1)).with_key(whirlpool_two),
+            mint_account(6, TOKEN_PROGRAM_ID).with_key(mints.input),
+            mint_account(6, TOKEN_PROGRAM_ID).with_key(mints.intermediate),
+            mint_account(6, TOKEN_PROGRAM_ID).with_key(mints.output),
+            token_account(mints.input, whirlpool_one, 1_000_000, TOKEN_PROGRAM_ID),        // token_vault_one_input
+            token_account(mints.intermediate, whirlpool_one, 2_000_000, TOKEN_PROGRAM_ID), // token_vault_one_intermediate
+            token_account(mints.intermediate, whirlpool_two, 3_000_000, TOKEN_PROGRAM_ID), // token_vault_two_intermediate
+            token_account(mints.output, whirlpool_two, 4_000_000, TOKEN_PROGRAM_ID),       // token_vault_two_output
+            empty_account(),                                           // tick_array_one_0
+            empty_account(),                                           // tick_array_one_1
+            empty_account(),                                           // tick_array_one_2
+            empty_account(),                                           // tick_array_two_0
+            empty_account(),                                           // tick_array_two_1
+            empty_account(),                                           // tick_array_two_2
+            empty_account().with_key(whirlpool_oracle_address(&adapter.program_id, &whirlpool_one)), // oracle_one
+            empty_account().with_key(whirlpool_oracle_address(&adapter.program_id, &whirlpool_two)), // oracle_two
+        ]
+    }
+
+    fn context(mints: &Mints, accounts: Vec<TestAccount>) -> AdapterContext<'static> {
+        let vault_owner = Pubkey::new_unique();
+        adapter_context(
+            token_account(mints.input, vault_owner, 1_000, TOKEN_PROGRAM_ID),
+            token_account(mints.output, vault_owner, 0, TOKEN_PROGRAM_ID),
+            accounts,
+        )
+    }
+
+    fn validate(adapter: &WhirlpoolTwoHopAdapter, mints: &Mints, accounts: Vec<TestAccount>) -> Result<()> {
+        let count = accounts.len();
+        adapter.validate_accounts(context(mints, accounts), 0, count)
+    }
+
+    #[test]
+    fn test_validate_accounts_every_direction() {
+        for a_to_b_one in [true, false] {
+            for a_to_b_two in [true, false] {
+                let adapter = create_test_adapter(a_to_b_one, a_to_b_two);
+                let mints = new_mints();
+                let accounts = create_two_hop_accounts(&adapter, &mints, a_to_b_one, a_to_b_two);
+                assert!(validate(&adapter, &mints, accounts).is_ok());
+            }
+        }
+    }
+
+    #[test]
+    fn test_hop_directions_follow_pool_mints_not_client_flags() {
+        let adapter = create_test_adapter(true, true);
+        let mints = new_mints();
+        let accounts = leak_account_infos(create_two_hop_accounts(&adapter, &mints, false, true));
+
+        let directions = adapter
+            .hop_directions(&accounts[POOL_ACCOUNT_INDEX], &accounts[POOL_TWO_ACCOUNT_INDEX], &mints.input)
+            .unwrap();
+        assert_eq!(directions, (false, true, mints.intermediate, mints.output));
+    }
+
+    #[test]
+    fn test_validate_accounts_pools_must_chain() {
+        let adapter = create_test_adapter(true, true);
+        let mints = new_mints();
+        let mut accounts = create_two_hop_accounts(&adapter, &mints, true, true);
+        // Pool two does not hold the intermediate mint
+        let whirlpool_two = accounts[POOL_TWO_ACCOUNT_INDEX].key;
+        accounts[POOL_TWO_ACCOUNT_INDEX] =
+            TestAccount::new(adapter.program_id, mock_whirlpool_data(Pubkey::new_unique(), mints.output)).with_key(whirlpool_two);
+
+        let err = validate(&adapter, &mints, accounts).unwrap_err();
+        assert_eq!(err, ErrorCode::InvalidMint.into());
+    }
+
+    #[test]
+    fn test_validate_accounts_output_vault_must_hold_output_mint() {
+        let adapter = create_test_adapter(true, true);
+        let mints = new_mints();
+        let accounts = create_two_hop_accounts(&adapter, &mints, true, true);
+        let count = accounts.len();
+        let vault_owner = Pubkey::new_unique();
+        let ctx = adapter_context(
+            token_account(mints.input, vault_owner, 1_000, TOKEN_PROGRAM_ID),
+            token_account(mints.intermediate, vault_owner, 0, TOKEN_PROGRAM_ID),
+            accounts,
+        );
+
+        let err = adapter.validate_accounts(ctx, 0, count).unwrap_err();
+        assert_eq!(err, ErrorCode::InvalidMint.into());
+    }
+
+    #[test]
+    fn test_validate_accounts_rejects_same_pool_twice() {
+        let adapter = create_test_adapter(true, false);
+        let mints = new_mints();
+        let mut accounts = create_two_hop_accounts(&adapter, &mints, true, false);
+        let whirlpool_one = accounts[POOL_ACCOUNT_INDEX].key;
+        accounts[POOL_INFO_TWO_INDEX] = pool_info_account(adapter.swap(), whirlpool_one, true);
+        let pool_one_data = accounts[POOL_ACCOUNT_INDEX].data.clone();
+        accounts[POOL_TWO_ACCOUNT_INDEX] = TestAccount::new(adapter.program_id, pool_one_data).with_key(whirlpool_one);
+
+        let err = validate(&adapter, &mints, accounts).unwrap_err();
+        assert_eq!(err, ErrorCode::InvalidPoolAddress.into());
+    }
+
+    #[test]
+    fn test_validate_accounts_second_pool_info() {
+        let adapter = create_test_adapter(true, true);
+        let mints = new_mints();
+
+        let mut accounts = create_two_hop_accounts(&adapter, &mints, true, true);
+        let whirlpool_two = accounts[POOL_TWO_ACCOUNT_INDEX].key;
+        accounts[POOL_INFO_TWO_INDEX] = pool_info_account(adapter.swap(), whirlpool_two, false);
+        assert_eq!(validate(&adapter, &mints, accounts).unwrap_err(), ErrorCode::PoolDisabled.into());
+
+        // Registered under a single-pool swap type
+        let mut accounts = create_two_hop_accounts(&adapter, &mints, true, true);
+        accounts[POOL_INFO_TWO_INDEX] = pool_info_account(Swap::Whirlpool { a_to_b: true }, whirlpool_two, true);
+        assert_eq!(validate(&adapter, &mints, accounts).unwrap_err(), ErrorCode::InvalidPoolAddress.into());
+
+        // Naming another pool
+        let mut accounts = create_two_hop_accounts(&adapter, &mints, true, true);
+        accounts[POOL_INFO_TWO_INDEX] = pool_info_account(adapter.swap(), Pubkey::new_unique(), true);
+        assert_eq!(validate(&adapter, &mints, accounts).unwrap_err(), ErrorCode::InvalidPoolAddress.into());
+    }
+
+    #[test]
+    fn test_validate_accounts_intermediate_vaults() {
+        let adapter = create_test_adapter(true, true);
+        let mints = new_mints();
+        let mut accounts = create_two_hop_accounts(&adapter, &mints, true, true);
+        // Pool two's intermediate vault swapped for its output vault
+        accounts.swap(13, 14);
+
+        let err = validate(&adapter, &mints, accounts).unwrap_err();
+        assert_eq!(err, ErrorCode::InvalidMint.into());
+    }
+
+    #[test]
+    fn test_validate_accounts_oracle_of_each_pool() {
+        let adapter = create_test_adapter(true, true);
+        let mints = new_mints();
+        let mut accounts = create_two_hop_accounts(&adapter, &mints, true, true);
+        // Both oracles must be passed, each for its own pool
+        accounts.swap(21, 22);
+
+        let err = validate(&adapter, &mints, accounts).unwrap_err();
+        assert_eq!(err, ErrorCode::InvalidOracleAccount.into());
+    }
+
+    #[test]
+    fn test_validate_accounts_not_enough_accounts() {
+        let adapter = create_test_adapter(true, true);
+        let mints = new_mints();
+        let mut accounts = create_two_hop_accounts(&adapter, &mints, true, true);
+        accounts.pop();
+
+        let err = validate(&adapter, &mints, accounts).unwrap_err();
+        assert_eq!(err, ErrorCode::NotEnoughAccountKeys.into());
+    }
+
+    #[test]
+    fn test_quote_skips_concentrated_liquidity_pools() {
+        let adapter = create_test_adapter(true, true);
+        let mints = new_mints();
+        let count = MIN_ACCOUNTS;
+
+        // Whirlpools do not price from their vault balances, so the step is left unquoted
+        let ctx = context(&mints, create_two_hop_accounts(&adapter, &mints, true, true));
+        assert_eq!(adapter.quote(ctx, 10_000, 0, count).unwrap(), None);
+        let ctx = context(&mints, create_two_hop_accounts(&adapter, &mints, true, true));
+        assert_eq!(adapter.input_reserve(ctx, 0, count).unwrap(), 1_000_000);
+    }
+}
@@ -0,0 +1,383 @@
+use anchor_lang::prelude::*;
+use anchor_lang::solana_program::program_pack::Pack;
+use anchor_lang::system_program::{self, Allocate, Assign, Transfer};
+use anchor_spl::token::spl_token::{native_mint, state::Account as SplTokenAccount};
+use anchor_spl::token_interface::{
+    close_account, initialize_account3, sync_native, transfer_checked, CloseAccount, InitializeAccount3, SyncNative,
+    TokenAccount, TransferChecked,
+};
+use crate::adapters::dex_adapter::DexAdapter;
+use crate::adapters::adapter_connector_module::AdapterContext;
+use crate::errors::ErrorCode;
+use crate::sdk::{derive_sol_escrow, SOL_ESCROW_SEED, UNWRAP_ACCOUNT_SEED};
+use crate::state::SwapResult;
+
+/// Pseudo-adapter converting between lamports in the program's lamport escrow and WSOL in a
+/// native mint vault, so a route can unwrap and re-wrap SOL as ordinary steps.
+/// No external program is invoked: wrapping is a system transfer plus SyncNative, unwrapping
+/// moves the WSOL into a transient account and closes it into the escrow.
+pub struct WsolAdapter {
+    pub program_id: Pubkey, // Always the system program; the registry entry must name it too
+    pub wrap: bool,         // WrapSol: escrow -> WSOL vault, UnwrapSol: WSOL vault -> escrow
+}
+
+const TOKEN_PROGRAM_ID: Pubkey = anchor_spl::token::ID;
+
+/// Decimals of the native mint, passed to transfer_checked
+const NATIVE_MINT_DECIMALS: u8 = 9;
+
+/// Adapter accounts: sol_escrow (in the PoolInfo slot), unwrap_account, native mint,
+/// SPL Token program and the system program
+pub const MIN_ACCOUNTS: usize = 5;
+
+/// Index of the escrow, which stands in for both the PoolInfo and the pool
+pub const POOL_ACCOUNT_INDEX: usize = 0;
+
+/// Index of the transient ["unwrap_account", sol_escrow] WSOL account
+pub const UNWRAP_ACCOUNT_INDEX: usize = 1;
+
+/// Index of the native mint
+pub const NATIVE_MINT_INDEX: usize = 2;
+
+/// Index of the SPL Token program
+pub const TOKEN_PROGRAM_INDEX: usize = 3;
+
+/// Index of the system program, the adapter's "program"
+pub const SYSTEM_PROGRAM_INDEX: usize = 4;
+
+/// Whether `account` is the lamport escrow PDA ["sol_escrow"].
+/// The owner is checked first so token vaults never pay for the PDA derivation.
+pub fn is_sol_escrow(account: &AccountInfo) -> bool {
+    account.owner == &system_program::ID && account.key() == derive_sol_escrow().0
+}
+
+/// Lamports the escrow keeps out of routes: its own rent exemption plus the rent of the
+/// transient WSOL account an UnwrapSol step creates from it
+pub fn escrow_reserve(rent: &Rent) -> u64 {
+    rent.minimum_balance(0) + rent.minimum_balance(SplTokenAccount::LEN)
+}
+
+/// Lamports of the escrow available to routes, i.e. above its reserve
+pub fn escrow_route_balance(lamports: u64, reserve: u64) -> u64 {
+    lamports.saturating_sub(reserve)
+}
+
+/// Reads the escrow's route balance, as a native mint vault would report its amount
+pub fn escrow_balance(escrow: &AccountInfo) -> Result<u64> {
+    Ok(escrow_route_balance(escrow.lamports(), escrow_reserve(&Rent::get()?)))
+}
+
+/// Moves a route's native SOL input from the user into the escrow, where its opening
+/// WrapSol step spends it
+pub fn fund_escrow<'info>(
+    user: &AccountInfo<'info>,
+    escrow: &AccountInfo<'info>,
+    system_program_account: &AccountInfo<'info>,
+    amount: u64,
+) -> Result<()> {
+    if !is_sol_escrow(escrow) {
+        return Err(ErrorCode::InvalidSolEscrow.into());
+    }
+    system_program::transfer(
+        CpiContext::new(
+            system_program_account.clone(),
+            Transfer {
+                from: user.clone(),
+                to: escrow.clone(),
+            },
+        ),
+        amount,
+    )
+}
+
+/// Pays `amount` lamports a route's closing UnwrapSol step left in the escrow out to `recipient`
+pub fn pay_from_escrow<'info>(
+    escrow: &AccountInfo<'info>,
+    recipient: &AccountInfo<'info>,
+    system_program_account: &AccountInfo<'info>,
+    amount: u64,
+) -> Result<()> {
+    if amount > escrow_balance(escrow)? {
+        return Err(ErrorCode::SolEscrowUnderfunded.into());
+    }
+    let escrow_bump = derive_sol_escrow().1;
+    let escrow_seeds: &[&[u8]] = &[SOL_ESCROW_SEED, &[escrow_bump]];
+    system_program::transfer(
+        CpiContext::new_with_signer(
+            system_program_account.clone(),
+            Transfer {
+                from: escrow.clone(),
+                to: recipient.clone(),
+            },
+            &[escrow_seeds],
+        ),
+        amount,
+    )
+}
+
+impl WsolAdapter {
+    /// Validates the fixed adapter accounts shared by both directions
+    pub fn validate_fixed_accounts(&self, adapter_accounts: &[AccountInfo]) -> Result<()> {
+        if !is_sol_escrow(&adapter_accounts[POOL_ACCOUNT_INDEX]) {
+            return Err(ErrorCode::InvalidSolEscrow.into());
+        }
+
+        let expected_unwrap_account = Pubkey::find_program_address(
+            &[UNWRAP_ACCOUNT_SEED, adapter_accounts[POOL_ACCOUNT_INDEX].key.as_ref()],
+            &crate::ID,
+        ).0;
+        if adapter_accounts[UNWRAP_ACCOUNT_INDEX].key() != expected_unwrap_account {
+            return Err(ErrorCode::InvalidUnwrapAccount.into());
+        }
+
+        if adapter_accounts[NATIVE_MINT_INDEX].key() != native_mint::ID {
+            return Err(ErrorCode::InvalidMint.into());
+        }
+        if adapter_accounts[TOKEN_PROGRAM_INDEX].key() != TOKEN_PROGRAM_ID
+            || adapter_accounts[SYSTEM_PROGRAM_INDEX].key() != self.program_id
+        {
+            return Err(ErrorCode::InvalidCpiInterface.into());
+        }
+        Ok(())
+    }
+
+    /// Validates the WSOL side of the step is a native mint token account
+    pub fn validate_wsol_vault(&self, vault: &AccountInfo) -> Result<()> {
+        let vault_data = TokenAccount::try_deserialize(&mut vault.data.borrow().as_ref())
+            .map_err(|_| ErrorCode::InvalidAccount)?;
+        if vault_data.mint != native_mint::ID {
+            return Err(ErrorCode::InvalidMint.into());
+        }
+        Ok(())
+    }
+
+    /// Sends `amount` lamports from the escrow into the WSOL vault and syncs its token balance
+    fn wrap_lamports<'info>(&self, ctx: &AdapterContext<'info>, adapter_accounts: &[AccountInfo<'info>], amount: u64) -> Result<()> {
+        let escrow = &ctx.input_account;
+        if amount > escrow_balance(escrow)? {
+            msg!("Lamport escrow cannot wrap {} lamports above its reserve", amount);
+            return Err(ErrorCode::SolEscrowUnderfunded.into());
+        }
+
+        let escrow_bump = derive_sol_escrow().1;
+        let escrow_seeds: &[&[u8]] = &[SOL_ESCROW_SEED, &[escrow_bump]];
+        system_program::transfer(
+            CpiContext::new_with_signer(
+                adapter_accounts[SYSTEM_PROGRAM_INDEX].clone(),
+                Transfer {
+                    from: escrow.clone(),
+                    to: ctx.output_account.clone(),
+                },
+                &[escrow_seeds],
+            ),
+            amount,
+        )?;
+
+        sync_native(CpiContext::new(
+            adapter_accounts[TOKEN_PROGRAM_INDEX].clone(),
+            SyncNative {
+                account: ctx.output_account.clone(),
+            },
+        ))
+    }
+
+    /// Moves `amount` WSOL from the vault into the transient unwrap account and closes it into
+    /// the escrow, which gets back the account's rent along with the unwrapped lamports
+    fn unwrap_lamports<'info>(&self, ctx: &AdapterContext<'info>, adapter_accounts: &[AccountInfo<'info>], amount: u64) -> Result<()> {
+        let escrow = &ctx.output_account;
+        let unwrap_account = &adapter_accounts[UNWRAP_ACCOUNT_INDEX];
+        let system_program_account = &adapter_accounts[SYSTEM_PROGRAM_INDEX];
+        let token_program = &adapter_accounts[TOKEN_PROGRAM_INDEX];
+
+        let rent = Rent::get()?;
+        if escrow.lamports() < escrow_reserve(&rent) {
+            msg!("Lamport escrow {} is below its rent reserve; fund it before unwrapping", escrow.key());
+            return Err(ErrorCode::SolEscrowUnderfunded.into());
+        }
+
+        let escrow_bump = derive_sol_escrow().1;
+        let escrow_seeds: &[&[u8]] = &[SOL_ESCROW_SEED, &[escrow_bump]];
+        let unwrap_bump = Pubkey::find_program_address(&[UNWRAP_ACCOUNT_SEED, escrow.key.as_ref()], &ctx.program_id).1;
+        let unwrap_seeds: &[&[u8]] = &[UNWRAP_ACCOUNT_SEED, escrow.key.as_ref(), &[unwrap_bump]];
+        let vault_authority_bump = Pubkey::find_program_address(&[b"vault_authority"], &ctx.program_id).1;
+        let authority_seeds: &[&[u8]] = &[b"vault_authority", &[vault_authority_bump]];
+
+        // Fund, allocate and assign rather than create_account, which fails if anyone has
+        // sent lamports to the unwrap account's address
+        let rent_due = rent
+            .minimum_balance(SplTokenAccount::LEN)
+            .saturating_sub(unwrap_account.lamports());
+        if rent_due > 0 {
+            system_program::transfer(
+                CpiContext::new_with_signer(
+                    system_program_account.clone(),
+                    Transfer {
+                        from: escrow.clone(),
+                        to: unwrap_account.clone(),
+                    },
+                    &[escrow_seeds],
+                ),
+                rent_due,
+            )?;
+        }
+        system_program::allocate(
+            CpiContext::new_with_signer(
+                system_program_account.clone(),
+                Allocate {
+                    account_to_allocate: unwrap_account.clone(),
+                },
+                &[unwrap_seeds],
+            ),
+            SplTokenAccount::LEN as u64,
+        )?;
+        system_program::assign(
+            CpiContext::new_with_signer(
+                system_program_account.clone(),
+                Assign {
+                    account_to_assign: unwrap_account.clone(),
+                },
+                &[unwrap_seeds],
+            ),
+            &TOKEN_PROGRAM_ID,
+        )?;
+
+        initialize_account3(CpiContext::new(
+            token_program.clone(),
+            InitializeAccount3 {
+                account: unwrap_account.clone(),
+                mint: adapter_accounts[NATIVE_MINT_INDEX].clone(),
+                authority: ctx.authority.clone(),
+            },
+        ))?;
+
+        transfer_checked(
+            CpiContext::new_with_signer(
+                token_program.clone(),
+                TransferChecked {
+                    from: ctx.input_account.clone(),
+                    mint: adapter_accounts[NATIVE_MINT_INDEX].clone(),
+                    to: unwrap_account.clone(),
+                    authority: ctx.authority.clone(),
+                },
+                &[authority_seeds],
+            ),
+            amount,
+            NATIVE_MINT_DECIMALS,
+        )?;
+
+        close_account(CpiContext::new_with_signer(
+            token_program.clone(),
+            CloseAccount {
+                account: unwrap_account.clone(),
+                destination: escrow.clone(),
+                authority: ctx.authority.clone(),
+            },
+            &[authority_seeds],
+        ))
+    }
+}
+
+impl DexAdapter for WsolAdapter {
+    /// Wraps or unwraps `amount` lamports; the output is measured like a swap's
+    fn execute_swap(
+        &self,
+        ctx: AdapterContext,
+        amount: u64,
+        remaining_accounts_start_index: usize,
+        remaining_accounts_count: usize,
+    ) -> Result<SwapResult> {
+        msg!("Executing {} step, amount: {}", if self.wrap { "WrapSol" } else { "UnwrapSol" }, amount);
+
+        if remaining_accounts_count < MIN_ACCOUNTS {
+            return Err(ErrorCode::NotEnoughAccountKeys.into());
+        }
+
+        let end_index = remaining_accounts_start_index + remaining_accounts_count;
+        if ctx.remaining_accounts.len() < end_index {
+            return Err(ErrorCode::NotEnoughAccountKeys.into());
+        }
+
+        let adapter_accounts = &ctx.remaining_accounts[remaining_accounts_start_index..end_index];
+        self.validate_fixed_accounts(adapter_accounts)?;
+
+        let output_amount = if self.wrap {
+            let before = TokenAccount::try_deserialize(&mut ctx.output_account.data.borrow().as_ref())?.amount;
+            self.wrap_lamports(&ctx, adapter_accounts, amount)?;
+            TokenAccount::try_deserialize(&mut ctx.output_account.data.borrow().as_ref())?
+                .amount
+                .checked_sub(before)
+                .ok_or(ErrorCode::InvalidCalculation)?
+        } else {
+            let before = ctx.output_account.lamports();
+            self.unwrap_lamports(&ctx, adapter_accounts, amount)?;
+            ctx.output_account
+                .lamports()
+                .checked_sub(before)
+                .ok_or(ErrorCode::InvalidCalculation)?
+        };
+
+        msg!("WSOL step completed, output amount: {}", output_amount);
+
+        Ok(SwapResult { output_amount, host_fee_amount: 0 })
+    }
+
+    /// Validate the escrow, unwrap account, native mint and programs, and that the step
+    /// converts between the escrow and a WSOL vault in its direction
+    fn validate_accounts(
+        &self,
+        ctx: AdapterContext,
+        remaining_accounts_start_index: usize,
+        remaining_accounts_count: usize,
+    ) -> Result<()> {
+        if remaining_accounts_count < MIN_ACCOUNTS {
+            return Err(ErrorCode::NotEnoughAccountKeys.into());
+        }
+
+        let end_index = remaining_accounts_start_index + remaining_accounts_count;
+        if ctx.remaining_accounts.len() < end_index {
+            return Err(ErrorCode::NotEnoughAccountKeys.into());
+        }
+
+        let adapter_accounts = &ctx.remaining_accounts[remaining_accounts_start_index..end_index];
+        self.validate_fixed_accounts(adapter_accounts)?;
+
+        let (escrow_side, wsol_side) = if self.wrap {
+            (&ctx.input_account, &ctx.output_account)
+        } else {
+            (&ctx.output_account, &ctx.input_account)
+        };
+        if escrow_side.key() != adapter_accounts[POOL_ACCOUNT_INDEX].key() {
+            return Err(ErrorCode::InvalidSolEscrow.into());
+        }
+        self.validate_wsol_vault(wsol_side)
+    }
+
+    /// Validate CPI call is targeting the system program
+    fn validate_cpi(&self, program_id: &Pubkey) -> Result<()> {
+        if *program_id != self.program_id || *program_id != system_program::ID {
+            return Err(ErrorCode::InvalidCpiInterface.into());
+        }
+        Ok(())
+    }
+
+    /// A 1:1 conversion has no price impact, so the reserve is unbounded
+    fn input_reserve(
+        &self,
+        _ctx: AdapterContext,
+        _remaining_accounts_start_index: usize,
+        _remaining_accounts_count: usize,
+    ) -> Result<u64> {
+        Ok(u64::MAX)
+    }
+
+    /// Lamports and WSOL convert 1:1
+    fn quote(
+        &self,
+        _ctx: AdapterContext,
+        amount: u64,
+        _remaining_accounts_start_index: usize,
+        _remaining_accounts_count: usize,
+    ) -> Result<Option<u64>> {
+        Ok(Some(amount))
+    }
+}
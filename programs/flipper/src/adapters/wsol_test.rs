@@ -0,0 +1,175 @@
+#[cfg(test)]
+mod tests {
+    use super::super::dex_adapter::DexAdapter;
+    use super::super::wsol::*;
+    use crate::errors::ErrorCode;
+    use crate::sdk::{derive_sol_escrow, derive_sol_escrow_unwrap_account};
+    use crate::test_utils::*;
+    use anchor_lang::prelude::*;
+    use anchor_spl::token::spl_token::native_mint;
+
+    const TOKEN_PROGRAM_ID: Pubkey = anchor_spl::token::ID;
+    const SYSTEM_PROGRAM_ID: Pubkey = anchor_lang::system_program::ID;
+
+    fn create_test_adapter(wrap: bool) -> WsolAdapter {
+        WsolAdapter { program_id: SYSTEM_PROGRAM_ID, wrap }
+    }
+
+    fn vault_authority() -> Pubkey {
+        Pubkey::find_program_address(&[b"vault_authority"], &crate::ID).0
+    }
+
+    fn escrow_account() -> TestAccount {
+        empty_account().with_key(derive_sol_escrow().0)
+    }
+
+    fn wsol_vault() -> TestAccount {
+        token_account(native_mint::ID, vault_authority(), 1_000, TOKEN_PROGRAM_ID)
+    }
+
+    // Adapter accounts: escrow in the PoolInfo slot through the system program
+    fn create_wsol_accounts() -> Vec<TestAccount> {
+        vec![
+            escrow_account(),
+            empty_account().with_key(derive_sol_escrow_unwrap_account().0),
+            mint_account(9, TOKEN_PROGRAM_ID).with_key(native_mint::ID),
+            program_account(TOKEN_PROGRAM_ID),
+            program_account(SYSTEM_PROGRAM_ID),
+        ]
+    }
+
+    // Validates a step in the adapter's direction between the escrow and a WSOL vault
+    fn validate(adapter: &WsolAdapter, accounts: Vec<TestAccount>) -> Result<()> {
+        let count = accounts.len();
+        let ctx = if adapter.wrap {
+            adapter_context(escrow_account(), wsol_vault(), accounts)
+        } else {
+            adapter_context(wsol_vault(), escrow_account(), accounts)
+        };
+        adapter.validate_accounts(ctx, 0, count)
+    }
+
+    #[test]
+    fn test_validate_accounts_valid_in_both_directions() {
+        assert!(validate(&create_test_adapter(true), create_wsol_accounts()).is_ok());
+        assert!(validate(&create_test_adapter(false), create_wsol_accounts()).is_ok());
+    }
+
+    #[test]
+    fn test_validate_accounts_not_enough_accounts() {
+        for wrap in [true, false] {
+            let mut accounts = create_wsol_accounts();
+            accounts.pop();
+
+            let err = validate(&create_test_adapter(wrap), accounts).unwrap_err();
+            assert_eq!(err, ErrorCode::NotEnoughAccountKeys.into());
+        }
+    }
+
+    #[test]
+    fn test_validate_accounts_rejects_other_account_in_escrow_slot() {
+        for wrap in [true, false] {
+            let mut accounts = create_wsol_accounts();
+            accounts[POOL_ACCOUNT_INDEX] = empty_account();
+
+            let err = validate(&create_test_adapter(wrap), accounts).unwrap_err();
+            assert_eq!(err, ErrorCode::InvalidSolEscrow.into());
+        }
+    }
+
+    #[test]
+    fn test_validate_accounts_rejects_foreign_unwrap_account() {
+        let mut accounts = create_wsol_accounts();
+        accounts[UNWRAP_ACCOUNT_INDEX] = empty_account();
+
+        let err = validate(&create_test_adapter(false), accounts).unwrap_err();
+        assert_eq!(err, ErrorCode::InvalidUnwrapAccount.into());
+    }
+
+    #[test]
+    fn test_validate_accounts_rejects_wrong_mint_and_programs() {
+        let mut accounts = create_wsol_accounts();
+        accounts[NATIVE_MINT_INDEX] = mint_account(9, TOKEN_PROGRAM_ID);
+        let err = validate(&create_test_adapter(true), accounts).unwrap_err();
+        assert_eq!(err, ErrorCode::InvalidMint.into());
+
+        // Native SOL only exists under SPL Token, not Token-2022
+        let mut accounts = create_wsol_accounts();
+        accounts[TOKEN_PROGRAM_INDEX] = program_account(anchor_spl::token_2022::ID);
+        let err = validate(&create_test_adapter(true), accounts).unwrap_err();
+        assert_eq!(err, ErrorCode::InvalidCpiInterface.into());
+
+        let mut accounts = create_wsol_accounts();
+        accounts[SYSTEM_PROGRAM_INDEX] = program_account(Pubkey::new_unique());
+        let err = validate(&create_test_adapter(false), accounts).unwrap_err();
+        assert_eq!(err, ErrorCode::InvalidCpiInterface.into());
+    }
+
+    #[test]
+    fn test_wrap_requires_escrow_input_and_wsol_output() {
+        let adapter = create_test_adapter(true);
+
+        // Swapped direction: the escrow is on the output side of a WrapSol step
+        let ctx = adapter_context(wsol_vault(), escrow_account(), create_wsol_accounts());
+        let err = adapter.validate_accounts(ctx, 0, MIN_ACCOUNTS).unwrap_err();
+        assert_eq!(err, ErrorCode::InvalidSolEscrow.into());
+
+        let other_mint_vault = token_account(Pubkey::new_unique(), vault_authority(), 0, TOKEN_PROGRAM_ID);
+        let ctx = adapter_context(escrow_account(), other_mint_vault, create_wsol_accounts());
+        let err = adapter.validate_accounts(ctx, 0, MIN_ACCOUNTS).unwrap_err();
+        assert_eq!(err, ErrorCode::InvalidMint.into());
+    }
+
+    #[test]
+    fn test_unwrap_requires_wsol_input_and_escrow_output() {
+        let adapter = create_test_adapter(false);
+
+        let ctx = adapter_context(escrow_account(), wsol_vault(), create_wsol_accounts());
+        let err = adapter.validate_accounts(ctx, 0, MIN_ACCOUNTS).unwrap_err();
+        assert_eq!(err, ErrorCode::InvalidSolEscrow.into());
+
+        let other_mint_vault = token_account(Pubkey::new_unique(), vault_authority(), 1_000, TOKEN_PROGRAM_ID);
+        let ctx = adapter_context(other_mint_vault, escrow_account(), create_wsol_accounts());
+        let err = adapter.validate_accounts(ctx, 0, MIN_ACCOUNTS).unwrap_err();
+        assert_eq!(err, ErrorCode::InvalidMint.into());
+    }
+
+    #[test]
+    fn test_validate_cpi_only_accepts_system_program() {
+        let adapter = create_test_adapter(true);
+        assert!(adapter.validate_cpi(&SYSTEM_PROGRAM_ID).is_ok());
+
+        let err = adapter.validate_cpi(&Pubkey::new_unique()).unwrap_err();
+        assert_eq!(err, ErrorCode::InvalidCpiInterface.into());
+    }
+
+    #[test]
+    fn test_quote_is_one_to_one_without_impact() {
+        for wrap in [true, false] {
+            let adapter = create_test_adapter(wrap);
+            let ctx = adapter_context(escrow_account(), wsol_vault(), create_wsol_accounts());
+            assert_eq!(adapter.quote(ctx.clone(), 123_456, 0, MIN_ACCOUNTS).unwrap(), Some(123_456));
+            assert_eq!(adapter.input_reserve(ctx, 0, MIN_ACCOUNTS).unwrap(), u64::MAX);
+        }
+    }
+
+    #[test]
+    fn test_escrow_detection_and_route_balance() {
+        let accounts = leak_account_infos(vec![
+            escrow_account(),
+            // The escrow's key under another owner is not the escrow
+            TestAccount::new(TOKEN_PROGRAM_ID, vec![]).with_key(derive_sol_escrow().0),
+            empty_account(),
+        ]);
+        assert!(is_sol_escrow(&accounts[0]));
+        assert!(!is_sol_escrow(&accounts[1]));
+        assert!(!is_sol_escrow(&accounts[2]));
+
+        let rent = Rent::default();
+        let reserve = escrow_reserve(&rent);
+        assert_eq!(reserve, rent.minimum_balance(0) + rent.minimum_balance(165));
+        assert_eq!(escrow_route_balance(reserve + 5_000, reserve), 5_000);
+        // An underfunded escrow reads as empty rather than wrapping
+        assert_eq!(escrow_route_balance(reserve - 1, reserve), 0);
+    }
+}
@@ -143,4 +143,292 @@ pub enum ErrorCode {
 
     #[msg("Invalid Jupiter program ID")]
     InvalidJupiterProgram,
-}
\ No newline at end of file
+
+    #[msg("Not enough remaining accounts for the route plan")]
+    InsufficientRouteAccounts,
+
+    #[msg("Order receipt account does not match the order or create_receipt flag")]
+    InvalidOrderReceipt,
+
+    #[msg("Route plan count must be 1..=2 and match the quoted amounts")]
+    InvalidRoutePlanCount,
+
+    #[msg("Fallback route plans must use non-overlapping account indices")]
+    OverlappingRoutePlans,
+
+    #[msg("Route step amount rounds to zero; increase the input amount")]
+    StepAmountTooSmall,
+
+    #[msg("Quote signer is configured but no quote attestation was provided")]
+    MissingQuoteAttestation,
+
+    #[msg("Quote attestation is not signed by the quote signer or does not cover this order and quote")]
+    InvalidQuoteAttestation,
+
+    #[msg("Quote attestation slot window does not include the current slot")]
+    QuoteAttestationExpired,
+
+    #[msg("Output is below the user's minimum output amount")]
+    BelowUserMinimum,
+
+    #[msg("Staged route session has expired")]
+    RouteSessionExpired,
+
+    #[msg("Only the route owner can close a route session before it expires")]
+    RouteSessionNotExpired,
+
+    #[msg("Remaining accounts do not match the staged route commitment")]
+    RouteCommitmentMismatch,
+
+    #[msg("Route session must be valid for 1..=MAX_ROUTE_SESSION_SLOTS slots")]
+    InvalidRouteSessionWindow,
+
+    #[msg("Staged route plan does not fit in a route session")]
+    StagedRouteTooLong,
+
+    #[msg("Operator is already suspended")]
+    OperatorAlreadySuspended,
+
+    #[msg("Operator is not suspended")]
+    OperatorNotSuspended,
+
+    #[msg("Suspension reason code is reserved for suspend_self")]
+    InvalidSuspensionReason,
+
+    #[msg("Vault authority admin cannot be the default pubkey")]
+    InvalidVaultAdmin,
+
+    #[msg("Creator cannot pay the order creation fee")]
+    InsufficientCreationFee,
+
+    #[msg("Treasury account does not match the registry treasury")]
+    InvalidTreasury,
+
+    #[msg("Order destination is closed and no creator ATA was provided")]
+    MissingDestinationAccount,
+
+    #[msg("Destination is not the creator's associated token account for the output mint")]
+    InvalidDestinationAccount,
+
+    #[msg("Pool is not on the adapter's pool whitelist")]
+    PoolNotWhitelisted,
+
+    #[msg("Too many whitelisted pools for one adapter")]
+    TooManyAdapterPools,
+
+    #[msg("Operator heartbeat sent again before the heartbeat interval elapsed")]
+    HeartbeatTooFrequent,
+
+    #[msg("Platform fee account has a delegate or a close authority other than the vault authority")]
+    UnsafeFeeAccount,
+
+    #[msg("Caller is not a trusted router")]
+    UntrustedRouter,
+
+    #[msg("Trusted router already exists")]
+    TrustedRouterAlreadyExists,
+
+    #[msg("Trusted router not found")]
+    TrustedRouterNotFound,
+
+    #[msg("Too many trusted routers")]
+    TooManyTrustedRouters,
+
+    #[msg("Route left a vault with less balance or changed authorities")]
+    VaultInvariantViolated,
+
+    #[msg("Route step exceeds the adapter's price impact cap")]
+    ExcessivePriceImpact,
+
+    #[msg("Price impact cap must not exceed 10000 basis points")]
+    InvalidPriceImpactCap,
+
+    #[msg("Limit order price is outside the band around the attested reference price")]
+    ImplausibleOrderPrice,
+
+    #[msg("Order price band must not exceed 10000 basis points")]
+    InvalidOrderPriceBand,
+
+    #[msg("Jupiter instruction in_amount does not match the amount left after the input fee")]
+    JupiterInAmountMismatch,
+
+    #[msg("Order has not failed enough executions to be flagged for review")]
+    NotEnoughFailedAttempts,
+
+    #[msg("Order is not flagged for review")]
+    OrderNotUnderReview,
+
+    #[msg("Limit order cannot be executed before its activation time")]
+    OrderNotActiveYet,
+
+    #[msg("Activation time must be non-negative and before expiry")]
+    InvalidActivationTime,
+
+    #[msg("Oracle account is not the oracle PDA of the pool")]
+    InvalidOracleAccount,
+
+    #[msg("Fee schedule must have at most 4 tiers with strictly increasing max_input_amount")]
+    InvalidFeeSchedule,
+
+    #[msg("Adapter program id cannot change while it has enabled pools")]
+    AdapterHasActivePools,
+
+    #[msg("Quoted output is outside the band around the on-chain quote")]
+    ImplausibleQuote,
+
+    #[msg("Quote sanity band must not exceed 10000 basis points")]
+    InvalidQuoteSanityBand,
+
+    #[msg("Jupiter instruction data is shorter than its 8-byte discriminator")]
+    JupiterDataTooShort,
+
+    #[msg("Jupiter instruction data exceeds the configured maximum length")]
+    JupiterDataTooLong,
+
+    #[msg("Jupiter instruction data is not a shared_accounts_route instruction")]
+    InvalidJupiterDiscriminator,
+
+    #[msg("Jupiter data limit must be 0 or between 8 and 1232 bytes")]
+    InvalidJupiterDataLimit,
+
+    #[msg("DEX rejected the pool's reserve or bin accounts")]
+    InvalidPoolReserve,
+
+    #[msg("DEX CPI failed with an error the adapter does not map")]
+    AdapterCpiFailed,
+
+    #[msg("Tracked token balances changed outside the swap beyond transfer fees")]
+    BalanceConservationViolated,
+
+    #[msg("Adapter is in sandbox mode and the user is not on its sandbox list")]
+    AdapterInSandbox,
+
+    #[msg("Sandbox list exceeds the maximum number of users")]
+    TooManySandboxUsers,
+
+    #[msg("Adapter is not in sandbox mode")]
+    AdapterNotInSandbox,
+
+    #[msg("Unwrap account or lamport destination missing or not the order's")]
+    InvalidUnwrapAccount,
+
+    #[msg("Compact route plan is truncated")]
+    InvalidCompactRoutePlan,
+
+    #[msg("Compact route plan step has an unknown swap tag")]
+    UnknownCompactSwapTag,
+
+    #[msg("Route vault has a delegate set while delegated vaults are rejected")]
+    VaultHasDelegate,
+
+    #[msg("No platform fee vault is registered for the fee mint")]
+    FeeVaultNotRegistered,
+
+    #[msg("Fee vault registry has no room for another mint")]
+    FeeVaultRegistryFull,
+
+    #[msg("Registered fee vault missing from remaining accounts, or platform_fee_account passed as well")]
+    RegisteredFeeVaultNotProvided,
+
+    #[msg("Protocol slippage caps must not exceed 10000 basis points nor the StopLoss trigger cap 10000")]
+    InvalidProtocolLimits,
+
+    #[msg("Order expires sooner than the protocol minimum order duration")]
+    OrderDurationTooShort,
+
+    #[msg("Order expires later than the protocol maximum order duration")]
+    OrderDurationTooLong,
+
+    #[msg("Account is not the program's lamport escrow, or the escrow is used outside a WrapSol or UnwrapSol step")]
+    InvalidSolEscrow,
+
+    #[msg("Lamport escrow holds less than its rent reserve")]
+    SolEscrowUnderfunded,
+
+    #[msg("Lamport escrow spent before anything paid into it, or ends a route that cannot pay out lamports")]
+    SolEscrowAtRouteEndpoint,
+
+    #[msg("Token account is not owned by the SPL Token or Token-2022 program")]
+    InvalidAccountOwner,
+
+    #[msg("Limit order destination token account is frozen")]
+    DestinationFrozen,
+
+    #[msg("Reserve accounts do not match the vaults the pool names")]
+    InvalidPoolReserves,
+
+    #[msg("Pool liquidity has not been attested within the registry's maximum attestation age")]
+    PoolAttestationStale,
+
+    #[msg("A platform fee is charged but no platform fee account was provided")]
+    MissingFeeAccount,
+
+    #[msg("Rent payer does not match the payer recorded for the order vault")]
+    InvalidRentPayer,
+
+    #[msg("Account is not a PoolInfo: data is too short or lacks the PoolInfo discriminator")]
+    InvalidPoolInfoAccount,
+
+    #[msg("Order input and output mints must differ")]
+    IdenticalMints,
+
+    #[msg("Limit order creation is paused")]
+    OrderCreationPaused,
+
+    #[msg("Limit order execution is paused")]
+    OrderExecutionPaused,
+
+    #[msg("Route amount exceeds the source account's remaining delegation")]
+    InsufficientDelegation,
+
+    #[msg("Vault generation does not match the expected generation: the vault was closed since")]
+    VaultGenerationMismatch,
+
+    #[msg("This change must first be proposed with propose_governance_call")]
+    GovernanceProposalRequired,
+
+    #[msg("Arguments do not match the governance proposal")]
+    GovernanceProposalMismatch,
+
+    #[msg("The governance delay of the proposal has not elapsed")]
+    GovernanceDelayNotElapsed,
+
+    #[msg("The governance proposal has expired")]
+    GovernanceProposalExpired,
+
+    #[msg("Only the registry authority can cancel a governance proposal before it expires")]
+    GovernanceProposalNotExpired,
+
+    #[msg("Governance delay must be between MIN_GOVERNANCE_DELAY_SECS and MAX_GOVERNANCE_DELAY_SECS")]
+    InvalidGovernanceDelay,
+
+    #[msg("The program has no adapter for this swap type")]
+    AdapterNotImplemented,
+
+    #[msg("Adapter is not configured in the registry")]
+    AdapterNotConfigured,
+
+    #[msg("Adapter is disabled in the registry")]
+    AdapterDisabled,
+
+    #[msg("Volume fee tiers must be at most 4 with strictly increasing min_volume and multipliers up to 10000 bps")]
+    InvalidVolumeFeeTiers,
+
+    #[msg("The registry cannot remember any more removed adapters")]
+    TooManyRetiredAdapters,
+
+    #[msg("Operator still sponsors open order vaults; pass force to transfer them to the treasury")]
+    OperatorHasObligations,
+
+    #[msg("Route step would take the pool over its volume cap for the current window")]
+    PoolVolumeCapExceeded,
+
+    #[msg("Creator ATA rent is refunded from the order rent, so the order must close with this fill")]
+    DestinationRentNotRefunded,
+
+    #[msg("Adapter registry has no room to count the pools of another adapter")]
+    AdapterPoolCountsFull,
+
+    #[msg("An expected vault generation requires the VaultMeta of a route vault")]
+    VaultMetaNotProvided,
+}
@@ -1,6 +1,9 @@
 #[cfg(test)]
 mod tests {
+    use super::super::*;
+    use crate::errors::ErrorCode;
     use crate::state::*;
+    use crate::test_utils::*;
     use anchor_lang::prelude::*;
 
     #[test]
@@ -54,6 +57,24 @@ mod tests {
             operators: vec![operator],
             supported_adapters: vec![adapter],
             bump: 255,
+            memo_program_id: Pubkey::default(),
+            quote_signer: Pubkey::default(),
+            suspended_operators: vec![],
+            order_creation_fee_lamports: 0,
+            treasury: Pubkey::default(),
+            adapter_pools: vec![],
+            trusted_routers: vec![],
+            adapter_impact_caps: vec![],
+            order_price_band_bps: 0,
+            suppress_step_events: false,
+            enforce_quote_sanity: false,
+            quote_sanity_band_bps: 0,
+            reject_delegated_vaults: false,
+            pool_attestation_max_age_slots: 0,
+            paused_adapters: vec![],
+            sandboxed_adapters: vec![],
+            retired_adapters: vec![],
+            adapter_pool_counts: vec![],
         };
 
         assert_eq!(registry.authority, authority);
@@ -71,6 +92,24 @@ mod tests {
             operators: operators.clone(),
             supported_adapters: vec![],
             bump: 255,
+            memo_program_id: Pubkey::default(),
+            quote_signer: Pubkey::default(),
+            suspended_operators: vec![],
+            order_creation_fee_lamports: 0,
+            treasury: Pubkey::default(),
+            adapter_pools: vec![],
+            trusted_routers: vec![],
+            adapter_impact_caps: vec![],
+            order_price_band_bps: 0,
+            suppress_step_events: false,
+            enforce_quote_sanity: false,
+            quote_sanity_band_bps: 0,
+            reject_delegated_vaults: false,
+            pool_attestation_max_age_slots: 0,
+            paused_adapters: vec![],
+            sandboxed_adapters: vec![],
+            retired_adapters: vec![],
+            adapter_pool_counts: vec![],
         };
 
         assert_eq!(registry.operators.len(), 5);
@@ -104,6 +143,24 @@ mod tests {
             operators: vec![],
             supported_adapters: adapters.clone(),
             bump: 255,
+            memo_program_id: Pubkey::default(),
+            quote_signer: Pubkey::default(),
+            suspended_operators: vec![],
+            order_creation_fee_lamports: 0,
+            treasury: Pubkey::default(),
+            adapter_pools: vec![],
+            trusted_routers: vec![],
+            adapter_impact_caps: vec![],
+            order_price_band_bps: 0,
+            suppress_step_events: false,
+            enforce_quote_sanity: false,
+            quote_sanity_band_bps: 0,
+            reject_delegated_vaults: false,
+            pool_attestation_max_age_slots: 0,
+            paused_adapters: vec![],
+            sandboxed_adapters: vec![],
+            retired_adapters: vec![],
+            adapter_pool_counts: vec![],
         };
 
         assert_eq!(registry.supported_adapters.len(), 3);
@@ -119,6 +176,24 @@ mod tests {
             operators: vec![],
             supported_adapters: vec![],
             bump: 255,
+            memo_program_id: Pubkey::default(),
+            quote_signer: Pubkey::default(),
+            suspended_operators: vec![],
+            order_creation_fee_lamports: 0,
+            treasury: Pubkey::default(),
+            adapter_pools: vec![],
+            trusted_routers: vec![],
+            adapter_impact_caps: vec![],
+            order_price_band_bps: 0,
+            suppress_step_events: false,
+            enforce_quote_sanity: false,
+            quote_sanity_band_bps: 0,
+            reject_delegated_vaults: false,
+            pool_attestation_max_age_slots: 0,
+            paused_adapters: vec![],
+            sandboxed_adapters: vec![],
+            retired_adapters: vec![],
+            adapter_pool_counts: vec![],
         };
 
         assert_eq!(registry.operators.len(), 0);
@@ -147,6 +222,24 @@ mod tests {
             operators: vec![],
             supported_adapters: adapters,
             bump: 255,
+            memo_program_id: Pubkey::default(),
+            quote_signer: Pubkey::default(),
+            suspended_operators: vec![],
+            order_creation_fee_lamports: 0,
+            treasury: Pubkey::default(),
+            adapter_pools: vec![],
+            trusted_routers: vec![],
+            adapter_impact_caps: vec![],
+            order_price_band_bps: 0,
+            suppress_step_events: false,
+            enforce_quote_sanity: false,
+            quote_sanity_band_bps: 0,
+            reject_delegated_vaults: false,
+            pool_attestation_max_age_slots: 0,
+            paused_adapters: vec![],
+            sandboxed_adapters: vec![],
+            retired_adapters: vec![],
+            adapter_pool_counts: vec![],
         };
 
         let result = registry.get_adapter_program_id(&Swap::Raydium);
@@ -162,6 +255,24 @@ mod tests {
             operators: vec![],
             supported_adapters: vec![],
             bump: 255,
+            memo_program_id: Pubkey::default(),
+            quote_signer: Pubkey::default(),
+            suspended_operators: vec![],
+            order_creation_fee_lamports: 0,
+            treasury: Pubkey::default(),
+            adapter_pools: vec![],
+            trusted_routers: vec![],
+            adapter_impact_caps: vec![],
+            order_price_band_bps: 0,
+            suppress_step_events: false,
+            enforce_quote_sanity: false,
+            quote_sanity_band_bps: 0,
+            reject_delegated_vaults: false,
+            pool_attestation_max_age_slots: 0,
+            paused_adapters: vec![],
+            sandboxed_adapters: vec![],
+            retired_adapters: vec![],
+            adapter_pool_counts: vec![],
         };
 
         // Authority should be authorized even if not in operators list
@@ -188,6 +299,24 @@ mod tests {
             operators: vec![],
             supported_adapters: vec![],
             bump: 255,
+            memo_program_id: Pubkey::default(),
+            quote_signer: Pubkey::default(),
+            suspended_operators: vec![],
+            order_creation_fee_lamports: 0,
+            treasury: Pubkey::default(),
+            adapter_pools: vec![],
+            trusted_routers: vec![],
+            adapter_impact_caps: vec![],
+            order_price_band_bps: 0,
+            suppress_step_events: false,
+            enforce_quote_sanity: false,
+            quote_sanity_band_bps: 0,
+            reject_delegated_vaults: false,
+            pool_attestation_max_age_slots: 0,
+            paused_adapters: vec![],
+            sandboxed_adapters: vec![],
+            retired_adapters: vec![],
+            adapter_pool_counts: vec![],
         };
 
         let new_operator = Pubkey::new_unique();
@@ -208,6 +337,24 @@ mod tests {
             operators: vec![operator1, operator2, operator3],
             supported_adapters: vec![],
             bump: 255,
+            memo_program_id: Pubkey::default(),
+            quote_signer: Pubkey::default(),
+            suspended_operators: vec![],
+            order_creation_fee_lamports: 0,
+            treasury: Pubkey::default(),
+            adapter_pools: vec![],
+            trusted_routers: vec![],
+            adapter_impact_caps: vec![],
+            order_price_band_bps: 0,
+            suppress_step_events: false,
+            enforce_quote_sanity: false,
+            quote_sanity_band_bps: 0,
+            reject_delegated_vaults: false,
+            pool_attestation_max_age_slots: 0,
+            paused_adapters: vec![],
+            sandboxed_adapters: vec![],
+            retired_adapters: vec![],
+            adapter_pool_counts: vec![],
         };
 
         assert_eq!(registry.operators.len(), 3);
@@ -246,6 +393,24 @@ mod tests {
             operators: vec![],
             supported_adapters: adapters,
             bump: 255,
+            memo_program_id: Pubkey::default(),
+            quote_signer: Pubkey::default(),
+            suspended_operators: vec![],
+            order_creation_fee_lamports: 0,
+            treasury: Pubkey::default(),
+            adapter_pools: vec![],
+            trusted_routers: vec![],
+            adapter_impact_caps: vec![],
+            order_price_band_bps: 0,
+            suppress_step_events: false,
+            enforce_quote_sanity: false,
+            quote_sanity_band_bps: 0,
+            reject_delegated_vaults: false,
+            pool_attestation_max_age_slots: 0,
+            paused_adapters: vec![],
+            sandboxed_adapters: vec![],
+            retired_adapters: vec![],
+            adapter_pool_counts: vec![],
         };
 
         assert_eq!(registry.supported_adapters.len(), 3);
@@ -260,6 +425,24 @@ mod tests {
             operators: operators.clone(),
             supported_adapters: vec![],
             bump: 255,
+            memo_program_id: Pubkey::default(),
+            quote_signer: Pubkey::default(),
+            suspended_operators: vec![],
+            order_creation_fee_lamports: 0,
+            treasury: Pubkey::default(),
+            adapter_pools: vec![],
+            trusted_routers: vec![],
+            adapter_impact_caps: vec![],
+            order_price_band_bps: 0,
+            suppress_step_events: false,
+            enforce_quote_sanity: false,
+            quote_sanity_band_bps: 0,
+            reject_delegated_vaults: false,
+            pool_attestation_max_age_slots: 0,
+            paused_adapters: vec![],
+            sandboxed_adapters: vec![],
+            retired_adapters: vec![],
+            adapter_pool_counts: vec![],
         };
 
         assert_eq!(registry.operators.len(), 100);
@@ -269,4 +452,123 @@ mod tests {
             assert!(registry.is_authorized_operator(op));
         }
     }
+
+    // PoolInfo fixture at its PDA, as created by initialize_pool_info
+    fn pool_info_at_pda(swap_type: Swap, pool_address: Pubkey) -> TestAccount {
+        let (pool_info_key, _) = Pubkey::find_program_address(
+            &[b"pool_info", swap_type.to_bytes().as_ref(), pool_address.as_ref()],
+            &crate::ID,
+        );
+        pool_info_account(swap_type, pool_address, true).with_key(pool_info_key)
+    }
+
+    #[test]
+    fn test_validate_adapter_pools_accepts_pool_infos() {
+        let pools: Vec<Pubkey> = (0..3).map(|_| Pubkey::new_unique()).collect();
+        let pool_infos = leak_account_infos(pools.iter().map(|pool| pool_info_at_pda(Swap::Raydium, *pool)).collect());
+
+        assert!(validate_adapter_pools(&Swap::Raydium, &pools, pool_infos, &crate::ID).is_ok());
+        // Empty whitelist needs no accounts
+        assert!(validate_adapter_pools(&Swap::Raydium, &[], &[], &crate::ID).is_ok());
+    }
+
+    #[test]
+    fn test_validate_adapter_pools_requires_pool_info_pda() {
+        let pool = Pubkey::new_unique();
+
+        // PoolInfo of another adapter
+        let pool_infos = leak_account_infos(vec![pool_info_at_pda(Swap::Meteora, pool)]);
+        let err = validate_adapter_pools(&Swap::Raydium, &[pool], pool_infos, &crate::ID).unwrap_err();
+        assert_eq!(err, ErrorCode::InvalidPoolAddress.into());
+
+        // Right data at a non-PDA address
+        let pool_infos = leak_account_infos(vec![pool_info_account(Swap::Raydium, pool, true)]);
+        let err = validate_adapter_pools(&Swap::Raydium, &[pool], pool_infos, &crate::ID).unwrap_err();
+        assert_eq!(err, ErrorCode::InvalidPoolAddress.into());
+
+        // Missing PoolInfo account
+        let err = validate_adapter_pools(&Swap::Raydium, &[pool], &[], &crate::ID).unwrap_err();
+        assert_eq!(err, ErrorCode::NotEnoughAccountKeys.into());
+    }
+
+    #[test]
+    fn test_validate_adapter_pools_rejects_duplicates_and_overflow() {
+        let pool = Pubkey::new_unique();
+        let pool_infos = leak_account_infos(vec![
+            pool_info_at_pda(Swap::Raydium, pool),
+            pool_info_at_pda(Swap::Raydium, pool),
+        ]);
+        let err = validate_adapter_pools(&Swap::Raydium, &[pool, pool], pool_infos, &crate::ID).unwrap_err();
+        assert_eq!(err, ErrorCode::InvalidPoolAddress.into());
+
+        let pools: Vec<Pubkey> = (0..=AdapterRegistry::MAX_ADAPTER_POOLS).map(|_| Pubkey::new_unique()).collect();
+        let err = validate_adapter_pools(&Swap::Raydium, &pools, &[], &crate::ID).unwrap_err();
+        assert_eq!(err, ErrorCode::TooManyAdapterPools.into());
+    }
+
+    fn registry_with_pool_counts(adapter_pool_counts: Vec<AdapterPoolCount>) -> AdapterRegistry {
+        AdapterRegistry {
+            authority: Pubkey::new_unique(),
+            operators: vec![],
+            supported_adapters: vec![],
+            bump: 255,
+            memo_program_id: Pubkey::default(),
+            quote_signer: Pubkey::default(),
+            suspended_operators: vec![],
+            order_creation_fee_lamports: 0,
+            treasury: Pubkey::default(),
+            adapter_pools: vec![],
+            trusted_routers: vec![],
+            adapter_impact_caps: vec![],
+            order_price_band_bps: 0,
+            suppress_step_events: false,
+            enforce_quote_sanity: false,
+            quote_sanity_band_bps: 0,
+            reject_delegated_vaults: false,
+            pool_attestation_max_age_slots: 0,
+            paused_adapters: vec![],
+            sandboxed_adapters: vec![],
+            retired_adapters: vec![],
+            adapter_pool_counts,
+        }
+    }
+
+    #[test]
+    fn test_ensure_adapter_pools_drained_blocks_enabled_pools() {
+        let registry = registry_with_pool_counts(vec![AdapterPoolCount { swap_type: Swap::Raydium, enabled_pools: 1 }]);
+
+        let err = ensure_adapter_pools_drained(&registry, &Swap::Raydium).unwrap_err();
+        assert_eq!(err, ErrorCode::AdapterHasActivePools.into());
+
+        // Enabled pools of another adapter do not block the change
+        assert!(ensure_adapter_pools_drained(&registry, &Swap::Meteora).is_ok());
+    }
+
+    #[test]
+    fn test_ensure_adapter_pools_drained_follows_pool_count() {
+        let mut registry = registry_with_pool_counts(vec![]);
+        assert!(ensure_adapter_pools_drained(&registry, &Swap::Raydium).is_ok());
+
+        registry.add_enabled_pool(&Swap::Raydium).unwrap();
+        registry.add_enabled_pool(&Swap::Raydium).unwrap();
+        registry.remove_enabled_pool(&Swap::Raydium).unwrap();
+        let err = ensure_adapter_pools_drained(&registry, &Swap::Raydium).unwrap_err();
+        assert_eq!(err, ErrorCode::AdapterHasActivePools.into());
+
+        registry.remove_enabled_pool(&Swap::Raydium).unwrap();
+        assert!(ensure_adapter_pools_drained(&registry, &Swap::Raydium).is_ok());
+        assert!(registry.adapter_pool_counts.is_empty());
+    }
+
+    #[test]
+    fn test_validate_sandbox_users_caps_list() {
+        let users: Vec<Pubkey> = (0..AdapterRegistry::MAX_SANDBOX_USERS).map(|_| Pubkey::new_unique()).collect();
+        assert!(validate_sandbox_users(&[]).is_ok());
+        assert!(validate_sandbox_users(&users).is_ok());
+
+        let mut too_many = users;
+        too_many.push(Pubkey::new_unique());
+        let err = validate_sandbox_users(&too_many).unwrap_err();
+        assert_eq!(err, ErrorCode::TooManySandboxUsers.into());
+    }
 }
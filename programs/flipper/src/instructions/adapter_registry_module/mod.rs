@@ -1,6 +1,10 @@
 use anchor_lang::prelude::*;
-use crate::adapters::adapter_connector_module::{AdapterContext, get_adapter};
+use anchor_spl::token_interface::TokenAccount;
+use crate::adapters::adapter_connector_module::{AdapterContext, adapter_status, get_adapter, pool_reserve_accounts};
+use crate::adapters::dex_adapter::pool_vault_amount;
 use crate::errors::ErrorCode;
+use crate::instructions::route_validator_module;
+use crate::instructions::vault_manager_module::VaultAuthority;
 use crate::state::*;
 
 // Test modules
@@ -14,26 +18,31 @@ pub fn initialize_adapter_registry(ctx: Context<InitializeAdapterRegistry>, adap
     registry.authority = ctx.accounts.authority.key();
     registry.operators = operators;
     registry.bump = ctx.bumps.adapter_registry;
+    registry.memo_program_id = SPL_MEMO_PROGRAM_ID;
     Ok(())
 }
 
 /// Initializes a new pool info account for an adapter.
 pub fn initialize_pool_info(ctx: Context<InitializePoolInfo>, swap_type: Swap, pool_address: Pubkey) -> Result<()> {
-    let registry = &ctx.accounts.adapter_registry;
+    let registry = &mut ctx.accounts.adapter_registry;
     if !registry.is_supported_adapter(&swap_type) {
-        return Err(ErrorCode::SwapNotSupported.into());
+        return Err(ErrorCode::AdapterNotConfigured.into());
     }
+    registry.add_enabled_pool(&swap_type)?;
 
     let pool_info = &mut ctx.accounts.pool_info;
     pool_info.adapter_swap_type = swap_type.clone();
     pool_info.pool_address = pool_address;
     pool_info.enabled = true;
+    pool_info.counted = true;
 
     emit_cpi!(PoolInitialized {
         swap_type,
         pool_address,
     });
 
+    AdminLog::record(&ctx.accounts.admin_log, ctx.program_id, AdminAction::InitializePoolInfo, ctx.accounts.operator.key(), pool_address)?;
+
     Ok(())
 }
 
@@ -47,11 +56,15 @@ pub fn add_operator(ctx: Context<AddOperator>, operator: Pubkey) -> Result<()> {
 
     emit_cpi!(OperatorAdded { operator });
 
+    AdminLog::record(&ctx.accounts.admin_log, ctx.program_id, AdminAction::AddOperator, ctx.accounts.authority.key(), operator)?;
+
     Ok(())
 }
 
 /// Removes an operator from the adapter registry.
-pub fn remove_operator(ctx: Context<RemoveOperator>, operator: Pubkey) -> Result<()> {
+/// Fails with OperatorHasObligations while the operator sponsors order vaults that are not closed
+/// yet, unless `force` is set; their rent is then refunded to the registry treasury instead.
+pub fn remove_operator(ctx: Context<RemoveOperator>, operator: Pubkey, force: bool) -> Result<()> {
     let registry = &mut ctx.accounts.adapter_registry;
     let initial_len = registry.operators.len();
     registry.operators.retain(|op| *op != operator);
@@ -59,15 +72,175 @@ pub fn remove_operator(ctx: Context<RemoveOperator>, operator: Pubkey) -> Result
     if registry.operators.len() == initial_len {
         return Err(error!(ErrorCode::OperatorNotFound));
     }
+    registry.suspended_operators.retain(|op| *op != operator);
+
+    let operator_stats = ctx.accounts.operator_stats.to_account_info();
+    let mut transferred_obligations = 0;
+    if let Some(mut stats) = OperatorStats::load(&operator_stats, ctx.program_id)? {
+        if stats.outstanding_obligations > 0 {
+            if !force {
+                return Err(error!(ErrorCode::OperatorHasObligations));
+            }
+            if registry.treasury == Pubkey::default() {
+                return Err(error!(ErrorCode::InvalidTreasury));
+            }
+            stats.obligations_transferred_to = registry.treasury;
+            stats.store(&operator_stats)?;
+            transferred_obligations = stats.outstanding_obligations;
+        }
+    }
+
+    emit_cpi!(OperatorRemoved { operator, transferred_obligations });
+
+    AdminLog::record(&ctx.accounts.admin_log, ctx.program_id, AdminAction::RemoveOperator, ctx.accounts.authority.key(), operator)?;
+
+    Ok(())
+}
+
+/// Adds an integrator allowed to call route_prevalidated.
+pub fn add_trusted_router(ctx: Context<AddTrustedRouter>, router: Pubkey) -> Result<()> {
+    let registry = &mut ctx.accounts.adapter_registry;
+    if registry.is_trusted_router(&router) {
+        return Err(error!(ErrorCode::TrustedRouterAlreadyExists));
+    }
+    if registry.trusted_routers.len() >= AdapterRegistry::MAX_TRUSTED_ROUTERS {
+        return Err(error!(ErrorCode::TooManyTrustedRouters));
+    }
+    registry.trusted_routers.push(router);
+
+    emit_cpi!(TrustedRouterAdded { router });
+
+    AdminLog::record(&ctx.accounts.admin_log, ctx.program_id, AdminAction::AddTrustedRouter, ctx.accounts.authority.key(), router)?;
+
+    Ok(())
+}
+
+/// Removes an integrator from the trusted routers.
+pub fn remove_trusted_router(ctx: Context<RemoveTrustedRouter>, router: Pubkey) -> Result<()> {
+    let registry = &mut ctx.accounts.adapter_registry;
+    let initial_len = registry.trusted_routers.len();
+    registry.trusted_routers.retain(|key| *key != router);
+
+    if registry.trusted_routers.len() == initial_len {
+        return Err(error!(ErrorCode::TrustedRouterNotFound));
+    }
+
+    emit_cpi!(TrustedRouterRemoved { router });
+
+    AdminLog::record(&ctx.accounts.admin_log, ctx.program_id, AdminAction::RemoveTrustedRouter, ctx.accounts.authority.key(), router)?;
+
+    Ok(())
+}
+
+/// Reason code recorded when an operator suspends its own key.
+pub const SUSPEND_REASON_SELF: u16 = 0;
+
+/// Suspends the signing operator's own key, e.g. when it may have leaked.
+/// Suspended operators fail operator checks until the authority reinstates them.
+pub fn suspend_self(ctx: Context<SuspendSelf>) -> Result<()> {
+    let operator = ctx.accounts.operator.key();
+    let registry = &mut ctx.accounts.adapter_registry;
+    if registry.is_suspended_operator(&operator) {
+        return Err(error!(ErrorCode::OperatorAlreadySuspended));
+    }
+    registry.suspended_operators.push(operator);
+
+    emit_cpi!(OperatorSuspended {
+        operator,
+        suspended_by: operator,
+        reason_code: SUSPEND_REASON_SELF,
+    });
+
+    AdminLog::record(&ctx.accounts.admin_log, ctx.program_id, AdminAction::SuspendSelf, operator, operator)?;
+
+    Ok(())
+}
+
+/// Records that the signing operator is alive by writing the current time to its OperatorStats PDA.
+/// Creates the PDA on first use. Heartbeats closer than OperatorStats::HEARTBEAT_INTERVAL apart are rejected.
+///
+/// # Returns
+/// * `Result<i64>` - The recorded last_active timestamp
+pub fn operator_heartbeat(ctx: Context<OperatorHeartbeat>) -> Result<i64> {
+    let now = Clock::get()?.unix_timestamp;
+    let stats = &mut ctx.accounts.operator_stats;
+    stats.operator = ctx.accounts.operator.key();
+    stats.bump = ctx.bumps.operator_stats;
+    if !stats.record_activity(now) {
+        return Err(error!(ErrorCode::HeartbeatTooFrequent));
+    }
+
+    Ok(stats.last_active)
+}
+
+/// Suspends another operator's key without waiting for the registry authority.
+/// Any active operator (or the authority) can call it; `reason_code` must not be SUSPEND_REASON_SELF.
+pub fn suspend_operator(ctx: Context<SuspendOperator>, operator: Pubkey, reason_code: u16) -> Result<()> {
+    if reason_code == SUSPEND_REASON_SELF {
+        return Err(error!(ErrorCode::InvalidSuspensionReason));
+    }
+
+    let registry = &mut ctx.accounts.adapter_registry;
+    if !registry.operators.contains(&operator) {
+        return Err(error!(ErrorCode::OperatorNotFound));
+    }
+    if registry.is_suspended_operator(&operator) {
+        return Err(error!(ErrorCode::OperatorAlreadySuspended));
+    }
+    registry.suspended_operators.push(operator);
+
+    emit_cpi!(OperatorSuspended {
+        operator,
+        suspended_by: ctx.accounts.caller.key(),
+        reason_code,
+    });
+
+    AdminLog::record(&ctx.accounts.admin_log, ctx.program_id, AdminAction::SuspendOperator, ctx.accounts.caller.key(), operator)?;
+
+    Ok(())
+}
+
+/// Reinstates a suspended operator.
+pub fn reinstate_operator(ctx: Context<ReinstateOperator>, operator: Pubkey) -> Result<()> {
+    let registry = &mut ctx.accounts.adapter_registry;
+    if !registry.is_suspended_operator(&operator) {
+        return Err(error!(ErrorCode::OperatorNotSuspended));
+    }
+    registry.suspended_operators.retain(|op| *op != operator);
 
-    emit_cpi!(OperatorRemoved { operator });
+    emit_cpi!(OperatorReinstated { operator });
+
+    AdminLog::record(&ctx.accounts.admin_log, ctx.program_id, AdminAction::ReinstateOperator, ctx.accounts.authority.key(), operator)?;
 
     Ok(())
 }
 
 /// Configures an adapter in the registry by adding or updating it.
+/// Pointing a swap type at another program_id than it was last registered with, including
+/// re-adding it after disable_adapter, requires every PoolInfo of the swap type to be disabled,
+/// so no route built against the old program is still live.
+/// It is also a GovernanceCall: the matching proposal must be passed once its delay has passed.
 pub fn configure_adapter(ctx: Context<ConfigureAdapter>, adapter: AdapterInfo) -> Result<()> {
+    let repoints = ctx
+        .accounts
+        .adapter_registry
+        .last_program_id(&adapter.swap_type)
+        .map_or(false, |program_id| program_id != adapter.program_id);
+    if repoints {
+        let call = GovernanceCall::ConfigureAdapter { adapter: adapter.clone() };
+        let call_hash = consume_governance_proposal(
+            &ctx.accounts.governance_proposal,
+            &call,
+            Clock::get()?.unix_timestamp,
+            &ctx.accounts.operator.to_account_info(),
+        )?;
+        emit_cpi!(GovernanceCallExecuted { call_hash, executed_by: ctx.accounts.operator.key() });
+        ensure_adapter_pools_drained(&ctx.accounts.adapter_registry, &adapter.swap_type)?;
+    }
+
     let registry = &mut ctx.accounts.adapter_registry;
+    registry.retired_adapters.retain(|retired| retired.swap_type != adapter.swap_type);
+
     if let Some(existing) = registry.supported_adapters.iter_mut().find(|a| a.swap_type == adapter.swap_type) {
         *existing = adapter.clone();
     } else {
@@ -79,21 +252,96 @@ pub fn configure_adapter(ctx: Context<ConfigureAdapter>, adapter: AdapterInfo) -
         swap_type: adapter.swap_type.clone(),
     });
 
+    AdminLog::record(&ctx.accounts.admin_log, ctx.program_id, AdminAction::ConfigureAdapter, ctx.accounts.operator.key(), adapter.program_id)?;
+
     Ok(())
 }
 
 /// Disables an adapter in the registry by removing it.
+/// Its program_id is kept in retired_adapters, so configure_adapter can only re-add the swap
+/// type under another program through a GovernanceCall.
 pub fn disable_adapter(ctx: Context<DisableAdapter>, swap_type: Swap) -> Result<()> {
     let registry = &mut ctx.accounts.adapter_registry;
-    let initial_len = registry.supported_adapters.len();
-    registry.supported_adapters.retain(|adapter| adapter.swap_type != swap_type);
-
-    if registry.supported_adapters.len() == initial_len {
-        return Err(error!(ErrorCode::SwapNotSupported));
+    let index = registry
+        .supported_adapters
+        .iter()
+        .position(|adapter| adapter.swap_type == swap_type)
+        .ok_or(error!(ErrorCode::AdapterNotConfigured))?;
+    if registry.retired_adapters.len() >= AdapterRegistry::MAX_RETIRED_ADAPTERS {
+        return Err(error!(ErrorCode::TooManyRetiredAdapters));
     }
+    let retired = registry.supported_adapters.remove(index);
+    let program_id = retired.program_id;
+    registry.retired_adapters.push(retired);
+    registry.adapter_pools.retain(|entry| entry.swap_type != swap_type);
+    registry.paused_adapters.retain(|paused| *paused != swap_type);
+    registry.sandboxed_adapters.retain(|entry| entry.swap_type != swap_type);
 
     emit_cpi!(AdapterDisabled { swap_type });
 
+    AdminLog::record(&ctx.accounts.admin_log, ctx.program_id, AdminAction::DisableAdapter, ctx.accounts.operator.key(), program_id)?;
+
+    Ok(())
+}
+
+/// Pauses or resumes an adapter while keeping its registry entry.
+/// Paused adapters are rejected by get_adapter, so every swap path stops using them.
+pub fn set_adapter_enabled(ctx: Context<SetAdapterEnabled>, swap_type: Swap, enabled: bool) -> Result<()> {
+    let registry = &mut ctx.accounts.adapter_registry;
+    let program_id = registry.get_adapter_program_id(&swap_type)?;
+    registry.paused_adapters.retain(|paused| *paused != swap_type);
+    if !enabled {
+        registry.paused_adapters.push(swap_type.clone());
+    }
+
+    emit_cpi!(AdapterStatusChanged { swap_type, enabled });
+
+    AdminLog::record(&ctx.accounts.admin_log, ctx.program_id, AdminAction::SetAdapterEnabled, ctx.accounts.operator.key(), program_id)?;
+
+    Ok(())
+}
+
+/// Checks a sandbox list before it is stored: at most MAX_SANDBOX_USERS wallets
+pub fn validate_sandbox_users(sandbox_users: &[Pubkey]) -> Result<()> {
+    if sandbox_users.len() > AdapterRegistry::MAX_SANDBOX_USERS {
+        return Err(error!(ErrorCode::TooManySandboxUsers));
+    }
+    Ok(())
+}
+
+/// Puts an adapter in sandbox mode, or replaces the sandbox list of one already in it.
+/// Only the listed wallets may route through a sandboxed adapter, as user_transfer_authority
+/// or as the creator of the limit order being executed.
+pub fn set_adapter_sandbox(ctx: Context<SetAdapterSandbox>, swap_type: Swap, sandbox_users: Vec<Pubkey>) -> Result<()> {
+    validate_sandbox_users(&sandbox_users)?;
+    let registry = &mut ctx.accounts.adapter_registry;
+    let program_id = registry.get_adapter_program_id(&swap_type)?;
+    registry.sandboxed_adapters.retain(|entry| entry.swap_type != swap_type);
+    registry.sandboxed_adapters.push(AdapterSandbox {
+        swap_type: swap_type.clone(),
+        sandbox_users: sandbox_users.clone(),
+    });
+
+    emit_cpi!(AdapterSandboxUpdated { swap_type, sandbox_users });
+
+    AdminLog::record(&ctx.accounts.admin_log, ctx.program_id, AdminAction::SetAdapterSandbox, ctx.accounts.operator.key(), program_id)?;
+
+    Ok(())
+}
+
+/// Takes an adapter out of sandbox mode and clears its sandbox list, opening it to all users.
+pub fn graduate_adapter(ctx: Context<GraduateAdapter>, swap_type: Swap) -> Result<()> {
+    let registry = &mut ctx.accounts.adapter_registry;
+    let program_id = registry.get_adapter_program_id(&swap_type)?;
+    if !registry.sandboxed_adapters.iter().any(|entry| entry.swap_type == swap_type) {
+        return Err(error!(ErrorCode::AdapterNotInSandbox));
+    }
+    registry.sandboxed_adapters.retain(|entry| entry.swap_type != swap_type);
+
+    emit_cpi!(AdapterGraduated { swap_type });
+
+    AdminLog::record(&ctx.accounts.admin_log, ctx.program_id, AdminAction::GraduateAdapter, ctx.accounts.operator.key(), program_id)?;
+
     Ok(())
 }
 
@@ -108,216 +356,1685 @@ pub fn disable_pool(ctx: Context<DisablePool>, swap_type: Swap, pool_address: Pu
     }
 
     pool_info.enabled = false;
+    if pool_info.counted {
+        ctx.accounts.adapter_registry.remove_enabled_pool(&swap_type)?;
+        pool_info.counted = false;
+    }
+
+    emit_cpi!(PoolDisabled {
+        swap_type,
+        pool_address,
+    });
+
+    AdminLog::record(&ctx.accounts.admin_log, ctx.program_id, AdminAction::DisablePool, ctx.accounts.operator.key(), pool_address)?;
+
+    Ok(())
+}
+
+/// Reads a pool's reserves and stamps its PoolInfo with the current slot if both exceed
+/// the pool's min_liquidity_threshold. Anyone may call it: the reserves must be the vaults
+/// the pool account itself names, and the pool must be owned by the adapter's program.
+/// A pool at or below the threshold has its stamp cleared, so a drained pool stops passing
+/// the freshness check right away instead of when its last attestation ages out.
+pub fn attest_pool_liquidity(ctx: Context<AttestPoolLiquidity>) -> Result<()> {
+    let pool_info = &ctx.accounts.pool_info;
+    let pool = &ctx.accounts.pool;
+    let program_id = ctx.accounts.adapter_registry.get_adapter_program_id(&pool_info.adapter_swap_type)?;
+    if *pool.owner != program_id {
+        return Err(error!(ErrorCode::InvalidPoolAddress));
+    }
+
+    let (expected_reserve_a, expected_reserve_b) =
+        pool_reserve_accounts(&pool_info.adapter_swap_type, &pool.try_borrow_data()?)?;
+    if ctx.accounts.reserve_a.key() != expected_reserve_a || ctx.accounts.reserve_b.key() != expected_reserve_b {
+        return Err(error!(ErrorCode::InvalidPoolReserves));
+    }
+
+    let reserve_a = pool_vault_amount(&ctx.accounts.reserve_a)?;
+    let reserve_b = pool_vault_amount(&ctx.accounts.reserve_b)?;
+    let slot = Clock::get()?.slot;
+    let attested = reserve_a > pool_info.min_liquidity_threshold && reserve_b > pool_info.min_liquidity_threshold;
+
+    let pool_info = &mut ctx.accounts.pool_info;
+    pool_info.last_verified_slot = if attested { slot } else { 0 };
+
+    emit_cpi!(PoolLiquidityAttested {
+        pool_address: pool_info.pool_address,
+        reserve_a,
+        reserve_b,
+        slot,
+        attested,
+    });
+
+    Ok(())
+}
+
+/// Sets the amount each of a pool's reserves must exceed for attest_pool_liquidity to stamp it.
+/// Clears the pool's current stamp, so routes that require attestations skip the pool until
+/// someone attests it against the new threshold.
+pub fn set_pool_liquidity_threshold(
+    ctx: Context<SetPoolLiquidityThreshold>,
+    swap_type: Swap,
+    pool_address: Pubkey,
+    min_liquidity_threshold: u64,
+) -> Result<()> {
+    let pool_info = &mut ctx.accounts.pool_info;
+    if pool_info.adapter_swap_type != swap_type || pool_info.pool_address != pool_address {
+        return Err(error!(ErrorCode::InvalidPoolAddress));
+    }
+
+    pool_info.min_liquidity_threshold = min_liquidity_threshold;
+    pool_info.last_verified_slot = 0;
+
+    emit_cpi!(PoolLiquidityThresholdUpdated {
+        swap_type,
+        pool_address,
+        min_liquidity_threshold,
+    });
+
+    AdminLog::record(&ctx.accounts.admin_log, ctx.program_id, AdminAction::SetPoolLiquidityThreshold, ctx.accounts.operator.key(), pool_address)?;
+
+    Ok(())
+}
+
+/// Caps how much step input routes may send through a pool per POOL_VOLUME_WINDOW_SECS.
+/// Steps that would take the window over the cap fail with PoolVolumeCapExceeded. 0 removes the
+/// cap. The current window's volume is kept, so a lowered cap applies to what was already routed.
+pub fn set_pool_volume_cap(
+    ctx: Context<SetPoolVolumeCap>,
+    swap_type: Swap,
+    pool_address: Pubkey,
+    max_volume_per_window: u64,
+) -> Result<()> {
+    let pool_info = &mut ctx.accounts.pool_info;
+    if pool_info.adapter_swap_type != swap_type || pool_info.pool_address != pool_address {
+        return Err(error!(ErrorCode::InvalidPoolAddress));
+    }
+
+    pool_info.max_volume_per_window = max_volume_per_window;
+
+    emit_cpi!(PoolVolumeCapUpdated {
+        swap_type,
+        pool_address,
+        max_volume_per_window,
+    });
+
+    AdminLog::record(&ctx.accounts.admin_log, ctx.program_id, AdminAction::SetPoolVolumeCap, ctx.accounts.operator.key(), pool_address)?;
+
+    Ok(())
+}
+
+/// Sets how many slots a pool liquidity attestation stays fresh. While it is non-zero, routes
+/// reject any step whose pool was not attested within that many slots. 0 turns the check off.
+pub fn set_pool_attestation_max_age(ctx: Context<SetPoolAttestationMaxAge>, max_age_slots: u64) -> Result<()> {
+    ctx.accounts.adapter_registry.pool_attestation_max_age_slots = max_age_slots;
+
+    emit_cpi!(PoolAttestationMaxAgeUpdated { max_age_slots });
+
+    AdminLog::record(&ctx.accounts.admin_log, ctx.program_id, AdminAction::SetPoolAttestationMaxAge, ctx.accounts.operator.key(), ctx.accounts.adapter_registry.key())?;
+
+    Ok(())
+}
+
+/// Grows a PoolInfo created before last_verified_slot and min_liquidity_threshold existed,
+/// before the volume cap fields existed, or before counted existed. Added fields start zeroed:
+/// no threshold, never attested and uncapped. An enabled pool is added to its adapter's enabled
+/// pool count. Anyone may pay for the migration.
+pub fn migrate_pool_info(ctx: Context<MigratePoolInfo>) -> Result<()> {
+    let pool_info = &ctx.accounts.pool_info;
+    require!(pool_info.owner == ctx.program_id, ErrorCode::InvalidAccount);
+    {
+        let data = pool_info.try_borrow_data()?;
+        require!(
+            [PoolInfo::LEGACY_SPACE, PoolInfo::PRE_VOLUME_CAP_SPACE, PoolInfo::PRE_POOL_COUNT_SPACE].contains(&data.len()),
+            ErrorCode::InvalidAccount
+        );
+        require!(&data[..8] == PoolInfo::DISCRIMINATOR, ErrorCode::InvalidAccount);
+    }
+
+    let lamports_diff = Rent::get()?.minimum_balance(PoolInfo::SPACE).saturating_sub(pool_info.lamports());
+    if lamports_diff > 0 {
+        anchor_lang::system_program::transfer(
+            CpiContext::new(
+                ctx.accounts.system_program.to_account_info(),
+                anchor_lang::system_program::Transfer {
+                    from: ctx.accounts.payer.to_account_info(),
+                    to: pool_info.to_account_info(),
+                },
+            ),
+            lamports_diff,
+        )?;
+    }
+
+    pool_info.resize(PoolInfo::SPACE)?;
+
+    let mut migrated = PoolInfo::try_deserialize(&mut &pool_info.try_borrow_data()?[..])?;
+    if migrated.enabled {
+        ctx.accounts.adapter_registry.add_enabled_pool(&migrated.adapter_swap_type)?;
+        migrated.counted = true;
+        migrated.try_serialize(&mut &mut pool_info.try_borrow_mut_data()?[..])?;
+    }
+
+    Ok(())
+}
+
+/// Changes the authority of the adapter registry.
+pub fn change_authority(ctx: Context<ChangeAuthority>) -> Result<()> {
+    let registry = &mut ctx.accounts.adapter_registry;
+    let old_authority = registry.authority;
+    registry.authority = ctx.accounts.new_authority.key();
+
+    emit_cpi!(AuthorityChanged {
+        old_authority,
+        new_authority: registry.authority,
+    });
+
+    AdminLog::record(&ctx.accounts.admin_log, ctx.program_id, AdminAction::ChangeAuthority, old_authority, ctx.accounts.new_authority.key())?;
+
+    Ok(())
+}
+
+/// Resets the adapter registry with new adapters and operators.
+/// A GovernanceCall: runs only with the arguments proposed with propose_governance_call, once
+/// the proposal's delay has passed, and closes the proposal to the authority. The proposed
+/// adapter list is a clean slate, so retired adapters are forgotten too. PoolInfos outlive the
+/// reset, so the adapter pool counts are kept.
+pub fn reset_adapter_registry(ctx: Context<ResetAdapterRegistry>, adapters: Vec<AdapterInfo>, operators: Vec<Pubkey>) -> Result<()> {
+    let call = GovernanceCall::ResetAdapterRegistry { adapters: adapters.clone(), operators: operators.clone() };
+    ctx.accounts.governance_proposal.require_executable(&call, Clock::get()?.unix_timestamp)?;
+    emit_cpi!(GovernanceCallExecuted {
+        call_hash: ctx.accounts.governance_proposal.call_hash,
+        executed_by: ctx.accounts.authority.key(),
+    });
+
+    let registry = &mut ctx.accounts.adapter_registry;
+    registry.supported_adapters = adapters;
+    registry.operators = operators;
+    registry.suspended_operators = vec![];
+    registry.adapter_pools = vec![];
+    registry.trusted_routers = vec![];
+    registry.adapter_impact_caps = vec![];
+    registry.paused_adapters = vec![];
+    registry.sandboxed_adapters = vec![];
+    registry.retired_adapters = vec![];
+
+    emit_cpi!(RegistryReset {
+        authority: ctx.accounts.authority.key(),
+    });
+
+    AdminLog::record(&ctx.accounts.admin_log, ctx.program_id, AdminAction::ResetAdapterRegistry, ctx.accounts.authority.key(), ctx.accounts.adapter_registry.key())?;
+
+    Ok(())
+}
+
+/// Sets the memo program that Whirlpool and Meteora swaps must receive.
+/// Forks deploying a different memo program point adapters at it here;
+/// Pubkey::default() restores the SPL Memo program.
+pub fn set_memo_program(ctx: Context<SetMemoProgram>, memo_program_id: Pubkey) -> Result<()> {
+    let registry = &mut ctx.accounts.adapter_registry;
+    let old_memo_program_id = registry.memo_program();
+    registry.memo_program_id = memo_program_id;
+
+    emit_cpi!(MemoProgramUpdated {
+        old_memo_program_id,
+        new_memo_program_id: registry.memo_program(),
+    });
+
+    AdminLog::record(&ctx.accounts.admin_log, ctx.program_id, AdminAction::SetMemoProgram, ctx.accounts.authority.key(), memo_program_id)?;
+
+    Ok(())
+}
+
+/// Sets the key that must attest quotes for limit order executions.
+/// Pubkey::default() turns quote attestations off.
+pub fn set_quote_signer(ctx: Context<SetQuoteSigner>, quote_signer: Pubkey) -> Result<()> {
+    let registry = &mut ctx.accounts.adapter_registry;
+    let old_quote_signer = registry.quote_signer;
+    registry.quote_signer = quote_signer;
+
+    emit_cpi!(QuoteSignerUpdated {
+        old_quote_signer,
+        new_quote_signer: quote_signer,
+    });
+
+    AdminLog::record(&ctx.accounts.admin_log, ctx.program_id, AdminAction::SetQuoteSigner, ctx.accounts.authority.key(), quote_signer)?;
+
+    Ok(())
+}
+
+/// Sets the flat fee, in lamports, charged to creators of limit orders and the treasury receiving it.
+/// A fee of 0 disables it; a non-zero fee requires a treasury.
+pub fn set_order_creation_fee(ctx: Context<SetOrderCreationFee>, fee_lamports: u64, treasury: Pubkey) -> Result<()> {
+    if fee_lamports > 0 && treasury == Pubkey::default() {
+        return Err(error!(ErrorCode::InvalidTreasury));
+    }
+
+    let registry = &mut ctx.accounts.adapter_registry;
+    let old_fee_lamports = registry.order_creation_fee_lamports;
+    registry.order_creation_fee_lamports = fee_lamports;
+    registry.treasury = treasury;
+
+    emit_cpi!(OrderCreationFeeUpdated {
+        old_fee_lamports,
+        new_fee_lamports: fee_lamports,
+        treasury,
+    });
+
+    AdminLog::record(&ctx.accounts.admin_log, ctx.program_id, AdminAction::SetOrderCreationFee, ctx.accounts.authority.key(), treasury)?;
+
+    Ok(())
+}
+
+/// Sets the band, in basis points around the attested reference price, that limit orders
+/// created with strict_price_check must fall within. A band of 0 restores the default.
+pub fn set_order_price_band(ctx: Context<SetOrderPriceBand>, band_bps: u16) -> Result<()> {
+    if band_bps > 10_000 {
+        return Err(error!(ErrorCode::InvalidOrderPriceBand));
+    }
+
+    let registry = &mut ctx.accounts.adapter_registry;
+    let old_band_bps = registry.order_price_band_bps;
+    registry.order_price_band_bps = band_bps;
+
+    emit_cpi!(OrderPriceBandUpdated {
+        old_band_bps,
+        new_band_bps: band_bps,
+    });
+
+    AdminLog::record(&ctx.accounts.admin_log, ctx.program_id, AdminAction::SetOrderPriceBand, ctx.accounts.authority.key(), ctx.accounts.adapter_registry.key())?;
+
+    Ok(())
+}
+
+/// Chooses whether single-hop routes emit their per-step SwapEvent next to the aggregated
+/// route event. Multi-hop routes always emit step events.
+pub fn set_emit_step_events(ctx: Context<SetEmitStepEvents>, emit_step_events: bool) -> Result<()> {
+    ctx.accounts.adapter_registry.suppress_step_events = !emit_step_events;
+
+    emit_cpi!(StepEventsUpdated { emit_step_events });
+
+    AdminLog::record(&ctx.accounts.admin_log, ctx.program_id, AdminAction::SetEmitStepEvents, ctx.accounts.authority.key(), ctx.accounts.adapter_registry.key())?;
+
+    Ok(())
+}
+
+/// Turns the route quote sanity check on or off. When on, routes reject a quoted_out_amount
+/// further than band_bps from the adapters' on-chain quote. A band of 0 restores the default.
+pub fn set_quote_sanity(ctx: Context<SetQuoteSanity>, enforce_quote_sanity: bool, band_bps: u16) -> Result<()> {
+    if band_bps > 10_000 {
+        return Err(error!(ErrorCode::InvalidQuoteSanityBand));
+    }
+
+    let registry = &mut ctx.accounts.adapter_registry;
+    registry.enforce_quote_sanity = enforce_quote_sanity;
+    registry.quote_sanity_band_bps = band_bps;
+
+    emit_cpi!(QuoteSanityUpdated {
+        enforce_quote_sanity,
+        band_bps,
+    });
+
+    AdminLog::record(&ctx.accounts.admin_log, ctx.program_id, AdminAction::SetQuoteSanity, ctx.accounts.operator.key(), ctx.accounts.adapter_registry.key())?;
+
+    Ok(())
+}
+
+/// Chooses whether routes refuse vaults that have a delegate set. Operators clear stale
+/// approvals with revoke_vault_delegations first, then turn this on to keep them out.
+pub fn set_reject_delegated_vaults(ctx: Context<SetRejectDelegatedVaults>, reject_delegated_vaults: bool) -> Result<()> {
+    ctx.accounts.adapter_registry.reject_delegated_vaults = reject_delegated_vaults;
+
+    emit_cpi!(DelegatedVaultPolicyUpdated { reject_delegated_vaults });
+
+    AdminLog::record(&ctx.accounts.admin_log, ctx.program_id, AdminAction::SetRejectDelegatedVaults, ctx.accounts.operator.key(), ctx.accounts.adapter_registry.key())?;
+
+    Ok(())
+}
+
+/// Sets the execution fee tiers, by order input size, for limit orders selling `mint`.
+/// Creates the ["fee_schedule", mint] PDA on first use; empty tiers disable the schedule.
+pub fn set_fee_schedule(ctx: Context<SetFeeSchedule>, mint: Pubkey, tiers: Vec<FeeTier>) -> Result<()> {
+    FeeSchedule::validate_tiers(&tiers)?;
+
+    let fee_schedule = &mut ctx.accounts.fee_schedule;
+    fee_schedule.mint = mint;
+    fee_schedule.tiers = [FeeTier::default(); MAX_FEE_TIERS];
+    fee_schedule.tiers[..tiers.len()].copy_from_slice(&tiers);
+    fee_schedule.tier_count = tiers.len() as u8;
+    fee_schedule.bump = ctx.bumps.fee_schedule;
+
+    emit_cpi!(FeeScheduleUpdated { mint, tiers });
+
+    AdminLog::record(&ctx.accounts.admin_log, ctx.program_id, AdminAction::SetFeeSchedule, ctx.accounts.authority.key(), mint)?;
+
+    Ok(())
+}
+
+/// Registers fee_vault as the platform fee vault of its mint, replacing any vault registered
+/// for that mint. Creates the ["fee_vault_registry"] PDA on first use.
+pub fn set_fee_vault(ctx: Context<SetFeeVault>) -> Result<()> {
+    let fee_vault = &ctx.accounts.fee_vault;
+    let mint = fee_vault.mint;
+    route_validator_module::validate_platform_fee_vault(fee_vault, &mint, &ctx.accounts.vault_authority.key())?;
+
+    let fee_vault_registry = &mut ctx.accounts.fee_vault_registry;
+    let replaced_fee_vault = fee_vault_registry.register(mint, fee_vault.key())?;
+    fee_vault_registry.bump = ctx.bumps.fee_vault_registry;
+
+    emit_cpi!(FeeVaultRegistered {
+        mint,
+        fee_vault: fee_vault.key(),
+        replaced_fee_vault,
+    });
+
+    AdminLog::record(&ctx.accounts.admin_log, ctx.program_id, AdminAction::SetFeeVault, ctx.accounts.authority.key(), mint)?;
+
+    Ok(())
+}
+
+/// Unregisters the platform fee vault of `mint`. Fees already in the vault stay withdrawable
+/// through withdraw_platform_fees.
+pub fn remove_fee_vault(ctx: Context<RemoveFeeVault>, mint: Pubkey) -> Result<()> {
+    let fee_vault = ctx.accounts.fee_vault_registry.unregister(&mint)?;
+
+    emit_cpi!(FeeVaultUnregistered { mint, fee_vault });
+
+    AdminLog::record(&ctx.accounts.admin_log, ctx.program_id, AdminAction::RemoveFeeVault, ctx.accounts.authority.key(), mint)?;
+
+    Ok(())
+}
+
+/// Creates the ["protocol_config"] PDA holding DEFAULT_PROTOCOL_LIMITS, the limits instructions
+/// apply while it does not exist, so creating it changes no behavior by itself.
+pub fn create_protocol_config(ctx: Context<CreateProtocolConfig>) -> Result<()> {
+    let protocol_config = &mut ctx.accounts.protocol_config;
+    protocol_config.limits = DEFAULT_PROTOCOL_LIMITS;
+    protocol_config.bump = ctx.bumps.protocol_config;
+    protocol_config.governance_delay_secs = MIN_GOVERNANCE_DELAY_SECS;
+
+    emit_cpi!(ProtocolLimitsUpdated { limits: DEFAULT_PROTOCOL_LIMITS });
+
+    AdminLog::record(&ctx.accounts.admin_log, ctx.program_id, AdminAction::CreateProtocolConfig, ctx.accounts.authority.key(), protocol_config.key())?;
+
+    Ok(())
+}
+
+/// Replaces the protocol-wide slippage and trigger caps. Orders already open keep the slippage
+/// and trigger they were created with.
+pub fn set_protocol_limits(ctx: Context<SetProtocolLimits>, limits: ProtocolLimits) -> Result<()> {
+    limits.validate()?;
+
+    ctx.accounts.protocol_config.limits = limits;
+
+    emit_cpi!(ProtocolLimitsUpdated { limits });
+
+    AdminLog::record(&ctx.accounts.admin_log, ctx.program_id, AdminAction::SetProtocolLimits, ctx.accounts.authority.key(), ctx.accounts.protocol_config.key())?;
+
+    Ok(())
+}
+
+/// Sets the route platform fee volume tiers: traders whose TraderVolume window reaches a tier's
+/// min_volume, counted in `volume_mint`, pay its multiplier of platform_fee_bps on route and
+/// shared_route. Empty tiers or a default `volume_mint` turn the rebates off.
+pub fn set_volume_fee_tiers(ctx: Context<SetVolumeFeeTiers>, volume_mint: Pubkey, tiers: Vec<VolumeFeeTier>) -> Result<()> {
+    ProtocolConfig::validate_volume_tiers(&tiers)?;
+
+    let protocol_config = &mut ctx.accounts.protocol_config;
+    protocol_config.volume_mint = volume_mint;
+    protocol_config.volume_fee_tiers = [VolumeFeeTier::default(); MAX_VOLUME_FEE_TIERS];
+    protocol_config.volume_fee_tiers[..tiers.len()].copy_from_slice(&tiers);
+    protocol_config.volume_tier_count = tiers.len() as u8;
+
+    emit_cpi!(VolumeFeeTiersUpdated { volume_mint, tiers });
+
+    AdminLog::record(&ctx.accounts.admin_log, ctx.program_id, AdminAction::SetVolumeFeeTiers, ctx.accounts.authority.key(), ctx.accounts.protocol_config.key())?;
+
+    Ok(())
+}
+
+/// Sets the percent of a pool's volume cap above which a route step emits PoolVolumeWarning,
+/// from 1 to 100. 0 restores DEFAULT_POOL_VOLUME_WARNING_PCT.
+pub fn set_pool_volume_warning_pct(ctx: Context<SetProtocolLimits>, warning_pct: u8) -> Result<()> {
+    require!(warning_pct <= 100, ErrorCode::InvalidProtocolLimits);
+
+    let protocol_config = &mut ctx.accounts.protocol_config;
+    protocol_config.pool_volume_warning_pct = warning_pct;
+
+    emit_cpi!(PoolVolumeWarningPctUpdated { warning_pct: protocol_config.pool_volume_warning_pct() });
+
+    AdminLog::record(&ctx.accounts.admin_log, ctx.program_id, AdminAction::SetPoolVolumeWarningPct, ctx.accounts.authority.key(), ctx.accounts.protocol_config.key())?;
+
+    Ok(())
+}
+
+/// Pauses or resumes new limit orders (create_limit_order, route_and_create_order and
+/// shared_route_and_create_order). Cancels and withdrawals are never paused.
+pub fn set_order_creation_paused(ctx: Context<SetOrderPause>, paused: bool) -> Result<()> {
+    ctx.accounts.protocol_config.creation_paused = paused;
+
+    emit_cpi!(OrderCreationPauseUpdated { paused });
+
+    AdminLog::record(&ctx.accounts.admin_log, ctx.program_id, AdminAction::SetOrderCreationPaused, ctx.accounts.operator.key(), ctx.accounts.protocol_config.key())?;
+
+    Ok(())
+}
+
+/// Pauses or resumes limit order execution (execute_limit_order and shared_execute_limit_order).
+/// Cancels and withdrawals are never paused.
+pub fn set_order_execution_paused(ctx: Context<SetOrderPause>, paused: bool) -> Result<()> {
+    ctx.accounts.protocol_config.execution_paused = paused;
+
+    emit_cpi!(OrderExecutionPauseUpdated { paused });
+
+    AdminLog::record(&ctx.accounts.admin_log, ctx.program_id, AdminAction::SetOrderExecutionPaused, ctx.accounts.operator.key(), ctx.accounts.protocol_config.key())?;
+
+    Ok(())
+}
+
+/// Grows a ProtocolConfig created before creation_paused and execution_paused, before
+/// governance_delay_secs, before the volume fee tiers or before pool_volume_warning_pct existed.
+/// Added pauses start unpaused, the delay at 0 (waited out as MIN_GOVERNANCE_DELAY_SECS), the
+/// volume tiers empty and the warning threshold unset. Anyone may pay for the migration.
+pub fn migrate_protocol_config(ctx: Context<MigrateProtocolConfig>) -> Result<()> {
+    let protocol_config = &ctx.accounts.protocol_config;
+    require!(protocol_config.owner == ctx.program_id, ErrorCode::InvalidAccount);
+    {
+        let data = protocol_config.try_borrow_data()?;
+        require!(
+            [
+                ProtocolConfig::LEGACY_SPACE,
+                ProtocolConfig::PRE_GOVERNANCE_SPACE,
+                ProtocolConfig::PRE_VOLUME_TIERS_SPACE,
+                ProtocolConfig::PRE_POOL_VOLUME_WARNING_SPACE,
+            ]
+            .contains(&data.len()),
+            ErrorCode::InvalidAccount
+        );
+        require!(&data[..8] == ProtocolConfig::DISCRIMINATOR, ErrorCode::InvalidAccount);
+    }
+
+    let lamports_diff = Rent::get()?.minimum_balance(ProtocolConfig::SPACE).saturating_sub(protocol_config.lamports());
+    if lamports_diff > 0 {
+        anchor_lang::system_program::transfer(
+            CpiContext::new(
+                ctx.accounts.system_program.to_account_info(),
+                anchor_lang::system_program::Transfer {
+                    from: ctx.accounts.payer.to_account_info(),
+                    to: protocol_config.to_account_info(),
+                },
+            ),
+            lamports_diff,
+        )?;
+    }
+
+    protocol_config.resize(ProtocolConfig::SPACE)?;
+
+    Ok(())
+}
+
+/// Proposes a GovernanceCall. The instruction it names accepts exactly these arguments from
+/// the governance delay in the ProtocolConfig after now until GOVERNANCE_PROPOSAL_TTL_SECS
+/// later. One proposal can be pending at a time; cancel_governance_proposal withdraws it.
+pub fn propose_governance_call(ctx: Context<ProposeGovernanceCall>, call: GovernanceCall) -> Result<()> {
+    let delay_secs = ProtocolConfig::load_config(&ctx.accounts.protocol_config, ctx.program_id)?.governance_delay();
+    let now = Clock::get()?.unix_timestamp;
+    let executable_at = now.checked_add(delay_secs).ok_or(ErrorCode::InvalidCalculation)?;
+    let expires_at = executable_at
+        .checked_add(GOVERNANCE_PROPOSAL_TTL_SECS)
+        .ok_or(ErrorCode::InvalidCalculation)?;
+
+    let proposal = &mut ctx.accounts.governance_proposal;
+    proposal.call_hash = call.hash()?;
+    proposal.proposer = ctx.accounts.authority.key();
+    proposal.proposed_at = now;
+    proposal.executable_at = executable_at;
+    proposal.expires_at = expires_at;
+    proposal.bump = ctx.bumps.governance_proposal;
+
+    emit_cpi!(GovernanceCallProposed {
+        call_hash: proposal.call_hash,
+        proposer: proposal.proposer,
+        executable_at,
+        expires_at,
+    });
+
+    AdminLog::record(&ctx.accounts.admin_log, ctx.program_id, AdminAction::ProposeGovernanceCall, ctx.accounts.authority.key(), ctx.accounts.governance_proposal.key())?;
+
+    Ok(())
+}
+
+/// Withdraws the pending proposal and refunds its rent to the proposer. The registry authority
+/// may cancel at any time; once the proposal has expired unexecuted, anyone may.
+pub fn cancel_governance_proposal(ctx: Context<CancelGovernanceProposal>) -> Result<()> {
+    let proposal = &ctx.accounts.governance_proposal;
+    let canceller = ctx.accounts.canceller.key();
+    let expired = proposal.is_expired(Clock::get()?.unix_timestamp);
+    require!(
+        expired || canceller == ctx.accounts.adapter_registry.authority,
+        ErrorCode::GovernanceProposalNotExpired
+    );
+
+    emit_cpi!(GovernanceProposalCancelled {
+        call_hash: proposal.call_hash,
+        cancelled_by: canceller,
+        expired,
+    });
+
+    AdminLog::record(&ctx.accounts.admin_log, ctx.program_id, AdminAction::CancelGovernanceProposal, canceller, proposal.key())?;
+
+    Ok(())
+}
+
+/// Sets the delay between proposing and executing a GovernanceCall, from
+/// MIN_GOVERNANCE_DELAY_SECS to MAX_GOVERNANCE_DELAY_SECS. Raising it applies at once; lowering it is itself a GovernanceCall,
+/// so the current delay still protects the change. Proposals already pending keep their times.
+pub fn set_governance_delay(ctx: Context<SetGovernanceDelay>, delay_secs: i64) -> Result<()> {
+    require!(
+        (MIN_GOVERNANCE_DELAY_SECS..=MAX_GOVERNANCE_DELAY_SECS).contains(&delay_secs),
+        ErrorCode::InvalidGovernanceDelay
+    );
+    let old_delay_secs = ctx.accounts.protocol_config.governance_delay();
+    if delay_secs < old_delay_secs {
+        let call = GovernanceCall::SetGovernanceDelay { delay_secs };
+        let call_hash = consume_governance_proposal(
+            &ctx.accounts.governance_proposal,
+            &call,
+            Clock::get()?.unix_timestamp,
+            &ctx.accounts.authority.to_account_info(),
+        )?;
+        emit_cpi!(GovernanceCallExecuted { call_hash, executed_by: ctx.accounts.authority.key() });
+    }
+
+    ctx.accounts.protocol_config.governance_delay_secs = delay_secs;
+
+    emit_cpi!(GovernanceDelayUpdated { old_delay_secs, new_delay_secs: delay_secs });
+
+    AdminLog::record(&ctx.accounts.admin_log, ctx.program_id, AdminAction::SetGovernanceDelay, ctx.accounts.authority.key(), ctx.accounts.protocol_config.key())?;
+
+    Ok(())
+}
+
+/// Checks `call` against the optional pending proposal and closes the proposal to
+/// `rent_destination`. Returns the executed call's hash.
+fn consume_governance_proposal<'info>(
+    governance_proposal: &Option<Account<'info, GovernanceProposal>>,
+    call: &GovernanceCall,
+    now: i64,
+    rent_destination: &AccountInfo<'info>,
+) -> Result<[u8; 32]> {
+    let proposal = governance_proposal.as_ref().ok_or(ErrorCode::GovernanceProposalRequired)?;
+    proposal.require_executable(call, now)?;
+    proposal.close(rent_destination.clone())?;
+    Ok(proposal.call_hash)
+}
+
+/// Checks a pool whitelist before it is stored: within MAX_ADAPTER_POOLS, no duplicates,
+/// and every pool has its PoolInfo PDA passed in the same order in `pool_infos`.
+pub fn validate_adapter_pools(
+    swap_type: &Swap,
+    pool_addresses: &[Pubkey],
+    pool_infos: &[AccountInfo],
+    program_id: &Pubkey,
+) -> Result<()> {
+    if pool_addresses.len() > AdapterRegistry::MAX_ADAPTER_POOLS {
+        return Err(error!(ErrorCode::TooManyAdapterPools));
+    }
+    if pool_infos.len() != pool_addresses.len() {
+        return Err(error!(ErrorCode::NotEnoughAccountKeys));
+    }
+
+    for (i, (pool_address, pool_info_account)) in pool_addresses.iter().zip(pool_infos).enumerate() {
+        if pool_addresses[..i].contains(pool_address) {
+            return Err(error!(ErrorCode::InvalidPoolAddress));
+        }
+
+        let (expected_pool_info, _) = Pubkey::find_program_address(
+            &[b"pool_info", swap_type.to_bytes().as_ref(), pool_address.as_ref()],
+            program_id,
+        );
+        if pool_info_account.key() != expected_pool_info || pool_info_account.owner != program_id {
+            return Err(error!(ErrorCode::InvalidPoolAddress));
+        }
+        let pool_info = PoolInfo::try_deserialize(&mut &pool_info_account.try_borrow_data()?[..])?;
+        if pool_info.adapter_swap_type != *swap_type || pool_info.pool_address != *pool_address {
+            return Err(error!(ErrorCode::InvalidPoolAddress));
+        }
+    }
+
+    Ok(())
+}
+
+/// Checks that an adapter has no live pools before its program_id changes: the registry's
+/// count of the adapter's enabled PoolInfos, kept by initialize_pool_info, disable_pool and
+/// migrate_pool_info, must be zero.
+pub fn ensure_adapter_pools_drained(registry: &AdapterRegistry, swap_type: &Swap) -> Result<()> {
+    let enabled_pools = registry.enabled_pool_count(swap_type);
+    if enabled_pools > 0 {
+        msg!("Adapter {:?} still has {} enabled pools", swap_type, enabled_pools);
+        return Err(error!(ErrorCode::AdapterHasActivePools));
+    }
+    Ok(())
+}
+
+/// Restricts an adapter to a whitelist of pools; route steps through any other pool are rejected.
+/// An empty list removes the whitelist, so any enabled PoolInfo of the adapter is accepted again.
+pub fn set_adapter_pools<'info>(
+    ctx: Context<'_, '_, 'info, 'info, SetAdapterPools<'info>>,
+    swap_type: Swap,
+    pool_addresses: Vec<Pubkey>,
+) -> Result<()> {
+    let registry = &mut ctx.accounts.adapter_registry;
+    let program_id = registry.get_adapter_program_id(&swap_type)?;
+    validate_adapter_pools(&swap_type, &pool_addresses, ctx.remaining_accounts, ctx.program_id)?;
+
+    registry.adapter_pools.retain(|entry| entry.swap_type != swap_type);
+    if !pool_addresses.is_empty() {
+        registry.adapter_pools.push(AdapterPools {
+            swap_type: swap_type.clone(),
+            pool_addresses: pool_addresses.clone(),
+        });
+    }
+
+    emit_cpi!(AdapterPoolsUpdated {
+        swap_type,
+        pool_addresses,
+    });
+
+    AdminLog::record(&ctx.accounts.admin_log, ctx.program_id, AdminAction::SetAdapterPools, ctx.accounts.operator.key(), program_id)?;
+
+    Ok(())
+}
+
+/// Caps the estimated price impact of each route step through an adapter, regardless of slippage.
+/// A cap of 0 removes it.
+pub fn set_max_step_impact(
+    ctx: Context<SetMaxStepImpact>,
+    swap_type: Swap,
+    max_step_impact_bps: u16,
+) -> Result<()> {
+    let registry = &mut ctx.accounts.adapter_registry;
+    let program_id = registry.get_adapter_program_id(&swap_type)?;
+    if max_step_impact_bps > 10_000 {
+        return Err(error!(ErrorCode::InvalidPriceImpactCap));
+    }
+
+    registry.adapter_impact_caps.retain(|entry| entry.swap_type != swap_type);
+    if max_step_impact_bps > 0 {
+        registry.adapter_impact_caps.push(AdapterImpactCap {
+            swap_type: swap_type.clone(),
+            max_step_impact_bps,
+        });
+    }
+
+    emit_cpi!(AdapterImpactCapUpdated {
+        swap_type,
+        max_step_impact_bps,
+    });
+
+    AdminLog::record(&ctx.accounts.admin_log, ctx.program_id, AdminAction::SetMaxStepImpact, ctx.accounts.operator.key(), program_id)?;
+
+    Ok(())
+}
+
+/// Migrates the adapter registry to write the PDA bump seed into the account data.
+/// This is needed because the bump field was added after the account was originally created on-chain.
+/// The account is reallocated to AdapterRegistry::SPACE, which also makes room for memo_program_id, quote_signer,
+/// suspended_operators, the order creation fee, pool whitelists, trusted routers, impact caps, the order price band
+/// the paused and sandboxed adapter lists, the retired adapters and the adapter pool counts.
+pub fn migrate_adapter_registry(ctx: Context<MigrateAdapterRegistry>) -> Result<()> {
+    let registry = &mut ctx.accounts.adapter_registry;
+    registry.bump = ctx.bumps.adapter_registry;
+    Ok(())
+}
+
+/// Creates the AdminLog that admin instructions record into.
+/// Until it exists those instructions still run; they just leave no entry.
+pub fn initialize_admin_log(ctx: Context<InitializeAdminLog>) -> Result<()> {
+    let admin_log = &mut ctx.accounts.admin_log;
+    admin_log.total_actions = 0;
+    admin_log.bump = ctx.bumps.admin_log;
+    Ok(())
+}
+
+/// Returns a page of the most recent admin actions, newest first.
+/// `skip` is the number of newer entries to pass over; a page holds at most ADMIN_LOG_PAGE_SIZE entries.
+pub fn get_admin_log(ctx: Context<GetAdminLog>, skip: u8) -> Result<Vec<AdminLogEntry>> {
+    Ok(ctx.accounts.admin_log.recent(skip as usize, ADMIN_LOG_PAGE_SIZE))
+}
+
+/// Reports the configuration state of each swap type, in order, so a client can tell before
+/// quoting or validating a route whether a step would fail with AdapterNotImplemented,
+/// AdapterNotConfigured or AdapterDisabled.
+pub fn describe_adapters(ctx: Context<DescribeAdapters>, swap_types: Vec<Swap>) -> Result<Vec<AdapterStatus>> {
+    Ok(swap_types.iter().map(|swap| adapter_status(swap, &ctx.accounts.adapter_registry)).collect())
+}
+
+/// Accounts for initializing the adapter registry.
+#[derive(Accounts)]
+pub struct InitializeAdapterRegistry<'info> {
+    #[account(
+        init,
+        payer = payer,
+        space = AdapterRegistry::SPACE,
+        seeds = [b"adapter_registry"],
+        bump
+    )]
+    pub adapter_registry: Account<'info, AdapterRegistry>,
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    /// CHECK: Only recorded as the registry authority, attested by the payer. It need not sign,
+    /// so a multisig or governance PDA can be set at genesis; every later has_one = authority
+    /// check still requires its signature.
+    pub authority: UncheckedAccount<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+/// Accounts for initializing a pool info account.
+#[event_cpi]
+#[derive(Accounts)]
+#[instruction(swap_type: Swap, pool_address: Pubkey)]
+pub struct InitializePoolInfo<'info> {
+    #[account(
+        init,
+        payer = payer,
+        space = PoolInfo::SPACE,
+        seeds = [b"pool_info", swap_type.to_bytes().as_ref(), pool_address.as_ref()],
+        bump
+    )]
+    pub pool_info: Account<'info, PoolInfo>,
+    #[account(
+        mut,
+        seeds = [b"adapter_registry"],
+        bump,
+        constraint = adapter_registry.is_authorized_operator(&operator.key()) @ ErrorCode::InvalidOperator
+    )]
+    pub adapter_registry: Account<'info, AdapterRegistry>,
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    #[account(signer)]
+    pub operator: Signer<'info>,
+    pub system_program: Program<'info, System>,
+    /// CHECK: AdminLog PDA, recorded to once initialize_admin_log has created it
+    #[account(mut, seeds = [b"admin_log"], bump)]
+    pub admin_log: UncheckedAccount<'info>,
+}
+
+/// Accounts for adding an operator to the registry.
+#[event_cpi]
+#[derive(Accounts)]
+pub struct AddOperator<'info> {
+    #[account(
+        mut,
+        seeds = [b"adapter_registry"],
+        bump,
+        has_one = authority @ ErrorCode::InvalidAuthority
+    )]
+    pub adapter_registry: Account<'info, AdapterRegistry>,
+    #[account(signer)]
+    pub authority: Signer<'info>,
+    /// CHECK: AdminLog PDA, recorded to once initialize_admin_log has created it
+    #[account(mut, seeds = [b"admin_log"], bump)]
+    pub admin_log: UncheckedAccount<'info>,
+}
+
+/// Accounts for removing an operator from the registry.
+#[event_cpi]
+#[derive(Accounts)]
+#[instruction(operator: Pubkey)]
+pub struct RemoveOperator<'info> {
+    #[account(
+        mut,
+        seeds = [b"adapter_registry"],
+        bump,
+        has_one = authority @ ErrorCode::InvalidAuthority
+    )]
+    pub adapter_registry: Account<'info, AdapterRegistry>,
+    /// CHECK: The operator's OperatorStats PDA, read for outstanding obligations once created
+    #[account(mut, seeds = [b"operator_stats", operator.as_ref()], bump)]
+    pub operator_stats: UncheckedAccount<'info>,
+    #[account(signer)]
+    pub authority: Signer<'info>,
+    /// CHECK: AdminLog PDA, recorded to once initialize_admin_log has created it
+    #[account(mut, seeds = [b"admin_log"], bump)]
+    pub admin_log: UncheckedAccount<'info>,
+}
+
+/// Accounts for adding a trusted router to the registry.
+#[event_cpi]
+#[derive(Accounts)]
+pub struct AddTrustedRouter<'info> {
+    #[account(
+        mut,
+        seeds = [b"adapter_registry"],
+        bump,
+        has_one = authority @ ErrorCode::InvalidAuthority
+    )]
+    pub adapter_registry: Account<'info, AdapterRegistry>,
+    #[account(signer)]
+    pub authority: Signer<'info>,
+    /// CHECK: AdminLog PDA, recorded to once initialize_admin_log has created it
+    #[account(mut, seeds = [b"admin_log"], bump)]
+    pub admin_log: UncheckedAccount<'info>,
+}
+
+/// Accounts for removing a trusted router from the registry.
+#[event_cpi]
+#[derive(Accounts)]
+pub struct RemoveTrustedRouter<'info> {
+    #[account(
+        mut,
+        seeds = [b"adapter_registry"],
+        bump,
+        has_one = authority @ ErrorCode::InvalidAuthority
+    )]
+    pub adapter_registry: Account<'info, AdapterRegistry>,
+    #[account(signer)]
+    pub authority: Signer<'info>,
+    /// CHECK: AdminLog PDA, recorded to once initialize_admin_log has created it
+    #[account(mut, seeds = [b"admin_log"], bump)]
+    pub admin_log: UncheckedAccount<'info>,
+}
+
+/// Accounts for an operator suspending its own key.
+#[event_cpi]
+#[derive(Accounts)]
+pub struct SuspendSelf<'info> {
+    #[account(
+        mut,
+        seeds = [b"adapter_registry"],
+        bump,
+        constraint = adapter_registry.operators.contains(&operator.key()) @ ErrorCode::InvalidOperator
+    )]
+    pub adapter_registry: Account<'info, AdapterRegistry>,
+    #[account(signer)]
+    pub operator: Signer<'info>,
+    /// CHECK: AdminLog PDA, recorded to once initialize_admin_log has created it
+    #[account(mut, seeds = [b"admin_log"], bump)]
+    pub admin_log: UncheckedAccount<'info>,
+}
+
+/// Accounts for an operator heartbeat.
+#[derive(Accounts)]
+pub struct OperatorHeartbeat<'info> {
+    #[account(
+        seeds = [b"adapter_registry"],
+        bump,
+        constraint = adapter_registry.is_active_operator(&operator.key()) @ ErrorCode::InvalidOperator
+    )]
+    pub adapter_registry: Account<'info, AdapterRegistry>,
+    #[account(
+        init_if_needed,
+        payer = operator,
+        space = OperatorStats::SPACE,
+        seeds = [b"operator_stats", operator.key().as_ref()],
+        bump
+    )]
+    pub operator_stats: Account<'info, OperatorStats>,
+    #[account(mut, signer)]
+    pub operator: Signer<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+/// Accounts for an operator suspending another operator.
+#[event_cpi]
+#[derive(Accounts)]
+pub struct SuspendOperator<'info> {
+    #[account(
+        mut,
+        seeds = [b"adapter_registry"],
+        bump,
+        constraint = adapter_registry.is_authorized_operator(&caller.key()) @ ErrorCode::InvalidOperator
+    )]
+    pub adapter_registry: Account<'info, AdapterRegistry>,
+    #[account(signer)]
+    pub caller: Signer<'info>,
+    /// CHECK: AdminLog PDA, recorded to once initialize_admin_log has created it
+    #[account(mut, seeds = [b"admin_log"], bump)]
+    pub admin_log: UncheckedAccount<'info>,
+}
+
+/// Accounts for reinstating a suspended operator.
+#[event_cpi]
+#[derive(Accounts)]
+pub struct ReinstateOperator<'info> {
+    #[account(
+        mut,
+        seeds = [b"adapter_registry"],
+        bump,
+        has_one = authority @ ErrorCode::InvalidAuthority
+    )]
+    pub adapter_registry: Account<'info, AdapterRegistry>,
+    #[account(signer)]
+    pub authority: Signer<'info>,
+    /// CHECK: AdminLog PDA, recorded to once initialize_admin_log has created it
+    #[account(mut, seeds = [b"admin_log"], bump)]
+    pub admin_log: UncheckedAccount<'info>,
+}
+
+/// Accounts for configuring an adapter in the registry.
+#[event_cpi]
+#[derive(Accounts)]
+pub struct ConfigureAdapter<'info> {
+    #[account(
+        mut,
+        seeds = [b"adapter_registry"],
+        bump,
+        constraint = adapter_registry.is_authorized_operator(&operator.key()) @ ErrorCode::InvalidOperator
+    )]
+    pub adapter_registry: Account<'info, AdapterRegistry>,
+    /// Receives the rent of the governance proposal a program_id change consumes
+    #[account(mut, signer)]
+    pub operator: Signer<'info>,
+    /// Proposal of the program_id change; required only when the change re-points an adapter
+    #[account(mut, seeds = [b"governance_proposal"], bump = governance_proposal.bump)]
+    pub governance_proposal: Option<Account<'info, GovernanceProposal>>,
+    /// CHECK: AdminLog PDA, recorded to once initialize_admin_log has created it
+    #[account(mut, seeds = [b"admin_log"], bump)]
+    pub admin_log: UncheckedAccount<'info>,
+}
+
+/// Accounts for disabling an adapter in the registry.
+#[event_cpi]
+#[derive(Accounts)]
+pub struct DisableAdapter<'info> {
+    #[account(
+        mut,
+        seeds = [b"adapter_registry"],
+        bump,
+        constraint = adapter_registry.is_authorized_operator(&operator.key()) @ ErrorCode::InvalidOperator
+    )]
+    pub adapter_registry: Account<'info, AdapterRegistry>,
+    #[account(signer)]
+    pub operator: Signer<'info>,
+    /// CHECK: AdminLog PDA, recorded to once initialize_admin_log has created it
+    #[account(mut, seeds = [b"admin_log"], bump)]
+    pub admin_log: UncheckedAccount<'info>,
+}
+
+/// Accounts for pausing or resuming an adapter in the registry.
+#[event_cpi]
+#[derive(Accounts)]
+pub struct SetAdapterEnabled<'info> {
+    #[account(
+        mut,
+        seeds = [b"adapter_registry"],
+        bump,
+        constraint = adapter_registry.is_authorized_operator(&operator.key()) @ ErrorCode::InvalidOperator
+    )]
+    pub adapter_registry: Account<'info, AdapterRegistry>,
+    #[account(signer)]
+    pub operator: Signer<'info>,
+    /// CHECK: AdminLog PDA, recorded to once initialize_admin_log has created it
+    #[account(mut, seeds = [b"admin_log"], bump)]
+    pub admin_log: UncheckedAccount<'info>,
+}
+
+/// Accounts for setting an adapter's sandbox list.
+#[event_cpi]
+#[derive(Accounts)]
+pub struct SetAdapterSandbox<'info> {
+    #[account(
+        mut,
+        seeds = [b"adapter_registry"],
+        bump,
+        constraint = adapter_registry.is_authorized_operator(&operator.key()) @ ErrorCode::InvalidOperator
+    )]
+    pub adapter_registry: Account<'info, AdapterRegistry>,
+    #[account(signer)]
+    pub operator: Signer<'info>,
+    /// CHECK: AdminLog PDA, recorded to once initialize_admin_log has created it
+    #[account(mut, seeds = [b"admin_log"], bump)]
+    pub admin_log: UncheckedAccount<'info>,
+}
+
+/// Accounts for graduating an adapter out of sandbox mode.
+#[event_cpi]
+#[derive(Accounts)]
+pub struct GraduateAdapter<'info> {
+    #[account(
+        mut,
+        seeds = [b"adapter_registry"],
+        bump,
+        constraint = adapter_registry.is_authorized_operator(&operator.key()) @ ErrorCode::InvalidOperator
+    )]
+    pub adapter_registry: Account<'info, AdapterRegistry>,
+    #[account(signer)]
+    pub operator: Signer<'info>,
+    /// CHECK: AdminLog PDA, recorded to once initialize_admin_log has created it
+    #[account(mut, seeds = [b"admin_log"], bump)]
+    pub admin_log: UncheckedAccount<'info>,
+}
+
+/// Accounts for disabling a pool in an adapter.
+#[event_cpi]
+#[derive(Accounts)]
+#[instruction(swap_type: Swap, pool_address: Pubkey)]
+pub struct DisablePool<'info> {
+    #[account(
+        mut,
+        seeds = [b"pool_info", swap_type.to_bytes().as_ref(), pool_address.as_ref()],
+        bump
+    )]
+    pub pool_info: Account<'info, PoolInfo>,
+    #[account(
+        mut,
+        seeds = [b"adapter_registry"],
+        bump,
+        constraint = adapter_registry.is_authorized_operator(&operator.key()) @ ErrorCode::InvalidOperator
+    )]
+    pub adapter_registry: Account<'info, AdapterRegistry>,
+    #[account(signer)]
+    pub operator: Signer<'info>,
+    /// CHECK: AdminLog PDA, recorded to once initialize_admin_log has created it
+    #[account(mut, seeds = [b"admin_log"], bump)]
+    pub admin_log: UncheckedAccount<'info>,
+}
+
+/// Accounts for attesting a pool's liquidity.
+#[event_cpi]
+#[derive(Accounts)]
+pub struct AttestPoolLiquidity<'info> {
+    #[account(mut)]
+    pub pool_info: Account<'info, PoolInfo>,
+    /// CHECK: The pool PoolInfo names; its owner is checked against the adapter's program in the handler
+    #[account(constraint = pool.key() == pool_info.pool_address @ ErrorCode::InvalidPoolAddress)]
+    pub pool: UncheckedAccount<'info>,
+    /// CHECK: Must be the first reserve vault the pool names
+    pub reserve_a: UncheckedAccount<'info>,
+    /// CHECK: Must be the second reserve vault the pool names
+    pub reserve_b: UncheckedAccount<'info>,
+    #[account(
+        seeds = [b"adapter_registry"],
+        bump
+    )]
+    pub adapter_registry: Account<'info, AdapterRegistry>,
+}
+
+/// Accounts for setting a pool's minimum liquidity threshold.
+#[event_cpi]
+#[derive(Accounts)]
+#[instruction(swap_type: Swap, pool_address: Pubkey)]
+pub struct SetPoolLiquidityThreshold<'info> {
+    #[account(
+        mut,
+        seeds = [b"pool_info", swap_type.to_bytes().as_ref(), pool_address.as_ref()],
+        bump
+    )]
+    pub pool_info: Account<'info, PoolInfo>,
+    #[account(
+        seeds = [b"adapter_registry"],
+        bump,
+        constraint = adapter_registry.is_authorized_operator(&operator.key()) @ ErrorCode::InvalidOperator
+    )]
+    pub adapter_registry: Account<'info, AdapterRegistry>,
+    #[account(signer)]
+    pub operator: Signer<'info>,
+    /// CHECK: AdminLog PDA, recorded to once initialize_admin_log has created it
+    #[account(mut, seeds = [b"admin_log"], bump)]
+    pub admin_log: UncheckedAccount<'info>,
+}
+
+/// Accounts for setting a pool's volume cap.
+#[event_cpi]
+#[derive(Accounts)]
+#[instruction(swap_type: Swap, pool_address: Pubkey)]
+pub struct SetPoolVolumeCap<'info> {
+    #[account(
+        mut,
+        seeds = [b"pool_info", swap_type.to_bytes().as_ref(), pool_address.as_ref()],
+        bump
+    )]
+    pub pool_info: Account<'info, PoolInfo>,
+    #[account(
+        seeds = [b"adapter_registry"],
+        bump,
+        constraint = adapter_registry.is_authorized_operator(&operator.key()) @ ErrorCode::InvalidOperator
+    )]
+    pub adapter_registry: Account<'info, AdapterRegistry>,
+    #[account(signer)]
+    pub operator: Signer<'info>,
+    /// CHECK: AdminLog PDA, recorded to once initialize_admin_log has created it
+    #[account(mut, seeds = [b"admin_log"], bump)]
+    pub admin_log: UncheckedAccount<'info>,
+}
+
+/// Accounts for setting the maximum pool attestation age of routes.
+#[event_cpi]
+#[derive(Accounts)]
+pub struct SetPoolAttestationMaxAge<'info> {
+    #[account(
+        mut,
+        seeds = [b"adapter_registry"],
+        bump,
+        constraint = adapter_registry.is_authorized_operator(&operator.key()) @ ErrorCode::InvalidOperator
+    )]
+    pub adapter_registry: Account<'info, AdapterRegistry>,
+    #[account(signer)]
+    pub operator: Signer<'info>,
+    /// CHECK: AdminLog PDA, recorded to once initialize_admin_log has created it
+    #[account(mut, seeds = [b"admin_log"], bump)]
+    pub admin_log: UncheckedAccount<'info>,
+}
+
+/// Accounts for growing a PoolInfo created before the liquidity or volume cap fields existed.
+#[derive(Accounts)]
+pub struct MigratePoolInfo<'info> {
+    /// CHECK: Legacy PoolInfo; owner, size and discriminator are checked in the handler
+    #[account(mut)]
+    pub pool_info: UncheckedAccount<'info>,
+    /// Counts the pool among its adapter's enabled pools if it is enabled
+    #[account(mut, seeds = [b"adapter_registry"], bump = adapter_registry.bump)]
+    pub adapter_registry: Account<'info, AdapterRegistry>,
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+/// Accounts for changing the authority of the adapter registry.
+#[event_cpi]
+#[derive(Accounts)]
+pub struct ChangeAuthority<'info> {
+    #[account(
+        mut,
+        seeds = [b"adapter_registry"],
+        bump,
+        has_one = authority @ ErrorCode::InvalidAuthority
+    )]
+    pub adapter_registry: Account<'info, AdapterRegistry>,
+    #[account(signer)]
+    pub authority: Signer<'info>,
+    /// CHECK: The new authority is not validated here, as it is just a Pubkey being set.
+    pub new_authority: UncheckedAccount<'info>,
+    /// CHECK: AdminLog PDA, recorded to once initialize_admin_log has created it
+    #[account(mut, seeds = [b"admin_log"], bump)]
+    pub admin_log: UncheckedAccount<'info>,
+}
+
+/// Accounts for resetting the adapter registry.
+#[event_cpi]
+#[derive(Accounts)]
+pub struct ResetAdapterRegistry<'info> {
+    #[account(
+        mut,
+        seeds = [b"adapter_registry"],
+        bump,
+        has_one = authority @ ErrorCode::InvalidAuthority
+    )]
+    pub adapter_registry: Account<'info, AdapterRegistry>,
+    #[account(mut, signer)]
+    pub authority: Signer<'info>,
+    /// Proposal of this reset, closed to the authority once executed
+    #[account(
+        mut,
+        seeds = [b"governance_proposal"],
+        bump = governance_proposal.bump,
+        close = authority
+    )]
+    pub governance_proposal: Account<'info, GovernanceProposal>,
+    /// CHECK: AdminLog PDA, recorded to once initialize_admin_log has created it
+    #[account(mut, seeds = [b"admin_log"], bump)]
+    pub admin_log: UncheckedAccount<'info>,
+}
+
+/// Accounts for setting the memo program expected by adapters.
+#[event_cpi]
+#[derive(Accounts)]
+pub struct SetMemoProgram<'info> {
+    #[account(
+        mut,
+        seeds = [b"adapter_registry"],
+        bump,
+        has_one = authority @ ErrorCode::InvalidAuthority
+    )]
+    pub adapter_registry: Account<'info, AdapterRegistry>,
+    #[account(signer)]
+    pub authority: Signer<'info>,
+    /// CHECK: AdminLog PDA, recorded to once initialize_admin_log has created it
+    #[account(mut, seeds = [b"admin_log"], bump)]
+    pub admin_log: UncheckedAccount<'info>,
+}
 
-    emit_cpi!(PoolDisabled {
-        swap_type,
-        pool_address,
-    });
+/// Accounts for setting the limit order quote signer.
+#[event_cpi]
+#[derive(Accounts)]
+pub struct SetQuoteSigner<'info> {
+    #[account(
+        mut,
+        seeds = [b"adapter_registry"],
+        bump,
+        has_one = authority @ ErrorCode::InvalidAuthority
+    )]
+    pub adapter_registry: Account<'info, AdapterRegistry>,
+    #[account(signer)]
+    pub authority: Signer<'info>,
+    /// CHECK: AdminLog PDA, recorded to once initialize_admin_log has created it
+    #[account(mut, seeds = [b"admin_log"], bump)]
+    pub admin_log: UncheckedAccount<'info>,
+}
 
-    Ok(())
+/// Accounts for setting the order creation fee.
+#[event_cpi]
+#[derive(Accounts)]
+pub struct SetOrderCreationFee<'info> {
+    #[account(
+        mut,
+        seeds = [b"adapter_registry"],
+        bump,
+        has_one = authority @ ErrorCode::InvalidAuthority
+    )]
+    pub adapter_registry: Account<'info, AdapterRegistry>,
+    #[account(signer)]
+    pub authority: Signer<'info>,
+    /// CHECK: AdminLog PDA, recorded to once initialize_admin_log has created it
+    #[account(mut, seeds = [b"admin_log"], bump)]
+    pub admin_log: UncheckedAccount<'info>,
 }
 
-/// Changes the authority of the adapter registry.
-pub fn change_authority(ctx: Context<ChangeAuthority>) -> Result<()> {
-    let registry = &mut ctx.accounts.adapter_registry;
-    let old_authority = registry.authority;
-    registry.authority = ctx.accounts.new_authority.key();
+/// Accounts for setting the strict limit order price band.
+#[event_cpi]
+#[derive(Accounts)]
+pub struct SetOrderPriceBand<'info> {
+    #[account(
+        mut,
+        seeds = [b"adapter_registry"],
+        bump,
+        has_one = authority @ ErrorCode::InvalidAuthority
+    )]
+    pub adapter_registry: Account<'info, AdapterRegistry>,
+    #[account(signer)]
+    pub authority: Signer<'info>,
+    /// CHECK: AdminLog PDA, recorded to once initialize_admin_log has created it
+    #[account(mut, seeds = [b"admin_log"], bump)]
+    pub admin_log: UncheckedAccount<'info>,
+}
 
-    emit_cpi!(AuthorityChanged {
-        old_authority,
-        new_authority: registry.authority,
-    });
+/// Accounts for setting whether single-hop routes emit step events.
+#[event_cpi]
+#[derive(Accounts)]
+pub struct SetEmitStepEvents<'info> {
+    #[account(
+        mut,
+        seeds = [b"adapter_registry"],
+        bump,
+        has_one = authority @ ErrorCode::InvalidAuthority
+    )]
+    pub adapter_registry: Account<'info, AdapterRegistry>,
+    #[account(signer)]
+    pub authority: Signer<'info>,
+    /// CHECK: AdminLog PDA, recorded to once initialize_admin_log has created it
+    #[account(mut, seeds = [b"admin_log"], bump)]
+    pub admin_log: UncheckedAccount<'info>,
+}
 
-    Ok(())
+/// Accounts for configuring the route quote sanity check.
+#[event_cpi]
+#[derive(Accounts)]
+pub struct SetQuoteSanity<'info> {
+    #[account(
+        mut,
+        seeds = [b"adapter_registry"],
+        bump,
+        constraint = adapter_registry.is_authorized_operator(&operator.key()) @ ErrorCode::InvalidOperator
+    )]
+    pub adapter_registry: Account<'info, AdapterRegistry>,
+    #[account(signer)]
+    pub operator: Signer<'info>,
+    /// CHECK: AdminLog PDA, recorded to once initialize_admin_log has created it
+    #[account(mut, seeds = [b"admin_log"], bump)]
+    pub admin_log: UncheckedAccount<'info>,
 }
 
-/// Resets the adapter registry with new adapters and operators.
-pub fn reset_adapter_registry(ctx: Context<ResetAdapterRegistry>, adapters: Vec<AdapterInfo>, operators: Vec<Pubkey>) -> Result<()> {
-    let registry = &mut ctx.accounts.adapter_registry;
-    registry.supported_adapters = adapters;
-    registry.operators = operators;
+/// Accounts for setting the delegated vault policy of routes.
+#[event_cpi]
+#[derive(Accounts)]
+pub struct SetRejectDelegatedVaults<'info> {
+    #[account(
+        mut,
+        seeds = [b"adapter_registry"],
+        bump,
+        constraint = adapter_registry.is_authorized_operator(&operator.key()) @ ErrorCode::InvalidOperator
+    )]
+    pub adapter_registry: Account<'info, AdapterRegistry>,
+    #[account(signer)]
+    pub operator: Signer<'info>,
+    /// CHECK: AdminLog PDA, recorded to once initialize_admin_log has created it
+    #[account(mut, seeds = [b"admin_log"], bump)]
+    pub admin_log: UncheckedAccount<'info>,
+}
 
-    emit_cpi!(RegistryReset {
-        authority: ctx.accounts.authority.key(),
-    });
+/// Accounts for setting an adapter's pool whitelist.
+/// The whitelisted pools' PoolInfo accounts are passed in remaining_accounts.
+#[event_cpi]
+#[derive(Accounts)]
+pub struct SetAdapterPools<'info> {
+    #[account(
+        mut,
+        seeds = [b"adapter_registry"],
+        bump,
+        constraint = adapter_registry.is_authorized_operator(&operator.key()) @ ErrorCode::InvalidOperator
+    )]
+    pub adapter_registry: Account<'info, AdapterRegistry>,
+    #[account(signer)]
+    pub operator: Signer<'info>,
+    /// CHECK: AdminLog PDA, recorded to once initialize_admin_log has created it
+    #[account(mut, seeds = [b"admin_log"], bump)]
+    pub admin_log: UncheckedAccount<'info>,
+}
 
-    Ok(())
+/// Accounts for setting an adapter's price impact cap.
+#[event_cpi]
+#[derive(Accounts)]
+pub struct SetMaxStepImpact<'info> {
+    #[account(
+        mut,
+        seeds = [b"adapter_registry"],
+        bump,
+        constraint = adapter_registry.is_authorized_operator(&operator.key()) @ ErrorCode::InvalidOperator
+    )]
+    pub adapter_registry: Account<'info, AdapterRegistry>,
+    #[account(signer)]
+    pub operator: Signer<'info>,
+    /// CHECK: AdminLog PDA, recorded to once initialize_admin_log has created it
+    #[account(mut, seeds = [b"admin_log"], bump)]
+    pub admin_log: UncheckedAccount<'info>,
 }
 
-/// Migrates the adapter registry to write the PDA bump seed into the account data.
-/// This is needed because the bump field was added after the account was originally created on-chain.
-/// The account is reallocated to accommodate the extra byte if necessary.
-pub fn migrate_adapter_registry(ctx: Context<MigrateAdapterRegistry>) -> Result<()> {
-    let registry = &mut ctx.accounts.adapter_registry;
-    registry.bump = ctx.bumps.adapter_registry;
-    Ok(())
+/// Accounts for migrating the adapter registry (writing bump to existing account).
+/// Uses realloc to expand the account by 1 byte and re-derives the bump from seeds.
+#[derive(Accounts)]
+pub struct MigrateAdapterRegistry<'info> {
+    #[account(
+        mut,
+        realloc = AdapterRegistry::SPACE,
+        realloc::payer = payer,
+        realloc::zero = false,
+        seeds = [b"adapter_registry"],
+        bump,
+        has_one = authority @ ErrorCode::InvalidAuthority
+    )]
+    pub adapter_registry: Account<'info, AdapterRegistry>,
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    pub authority: Signer<'info>,
+    pub system_program: Program<'info, System>,
 }
 
-/// Accounts for initializing the adapter registry.
+/// Accounts for setting the execution fee schedule of a mint.
+#[event_cpi]
 #[derive(Accounts)]
-pub struct InitializeAdapterRegistry<'info> {
+#[instruction(mint: Pubkey)]
+pub struct SetFeeSchedule<'info> {
     #[account(
-        init,
+        init_if_needed,
         payer = payer,
-        space = 8 + 32 + 4 + 10 * (4 + 32 + 32) + 4 + 10 * 32 + 1,
-        seeds = [b"adapter_registry"],
+        space = FeeSchedule::SPACE,
+        seeds = [b"fee_schedule", mint.as_ref()],
         bump
     )]
+    pub fee_schedule: Account<'info, FeeSchedule>,
+    #[account(
+        seeds = [b"adapter_registry"],
+        bump,
+        has_one = authority @ ErrorCode::InvalidAuthority
+    )]
     pub adapter_registry: Account<'info, AdapterRegistry>,
     #[account(mut)]
     pub payer: Signer<'info>,
     pub authority: Signer<'info>,
     pub system_program: Program<'info, System>,
+    /// CHECK: AdminLog PDA, recorded to once initialize_admin_log has created it
+    #[account(mut, seeds = [b"admin_log"], bump)]
+    pub admin_log: UncheckedAccount<'info>,
 }
 
-/// Accounts for initializing a pool info account.
+/// Accounts for registering a mint's platform fee vault.
 #[event_cpi]
 #[derive(Accounts)]
-#[instruction(swap_type: Swap, pool_address: Pubkey)]
-pub struct InitializePoolInfo<'info> {
+pub struct SetFeeVault<'info> {
     #[account(
-        init,
+        init_if_needed,
         payer = payer,
-        space = 8 + 4 + 32 + 1,
-        seeds = [b"pool_info", swap_type.to_bytes().as_ref(), pool_address.as_ref()],
+        space = FeeVaultRegistry::SPACE,
+        seeds = [b"fee_vault_registry"],
         bump
     )]
-    pub pool_info: Account<'info, PoolInfo>,
+    pub fee_vault_registry: Account<'info, FeeVaultRegistry>,
     #[account(
         seeds = [b"adapter_registry"],
         bump,
-        constraint = adapter_registry.authority == operator.key() || adapter_registry.operators.contains(&operator.key()) @ ErrorCode::InvalidOperator
+        has_one = authority @ ErrorCode::InvalidAuthority
     )]
     pub adapter_registry: Account<'info, AdapterRegistry>,
+    #[account(seeds = [b"vault_authority"], bump)]
+    pub vault_authority: Account<'info, VaultAuthority>,
+    /// Token account to collect the platform fee of its mint; must be owned by the vault authority
+    pub fee_vault: InterfaceAccount<'info, TokenAccount>,
     #[account(mut)]
     pub payer: Signer<'info>,
-    #[account(signer)]
-    pub operator: Signer<'info>,
+    pub authority: Signer<'info>,
     pub system_program: Program<'info, System>,
+    /// CHECK: AdminLog PDA, recorded to once initialize_admin_log has created it
+    #[account(mut, seeds = [b"admin_log"], bump)]
+    pub admin_log: UncheckedAccount<'info>,
 }
 
-/// Accounts for adding an operator to the registry.
+/// Accounts for unregistering a mint's platform fee vault.
 #[event_cpi]
 #[derive(Accounts)]
-pub struct AddOperator<'info> {
+pub struct RemoveFeeVault<'info> {
     #[account(
         mut,
+        seeds = [b"fee_vault_registry"],
+        bump = fee_vault_registry.bump
+    )]
+    pub fee_vault_registry: Account<'info, FeeVaultRegistry>,
+    #[account(
         seeds = [b"adapter_registry"],
         bump,
         has_one = authority @ ErrorCode::InvalidAuthority
     )]
     pub adapter_registry: Account<'info, AdapterRegistry>,
-    #[account(signer)]
     pub authority: Signer<'info>,
+    /// CHECK: AdminLog PDA, recorded to once initialize_admin_log has created it
+    #[account(mut, seeds = [b"admin_log"], bump)]
+    pub admin_log: UncheckedAccount<'info>,
 }
 
-/// Accounts for removing an operator from the registry.
+/// Accounts for creating the ProtocolConfig.
 #[event_cpi]
 #[derive(Accounts)]
-pub struct RemoveOperator<'info> {
+pub struct CreateProtocolConfig<'info> {
+    #[account(
+        init,
+        payer = payer,
+        space = ProtocolConfig::SPACE,
+        seeds = [b"protocol_config"],
+        bump
+    )]
+    pub protocol_config: Account<'info, ProtocolConfig>,
     #[account(
-        mut,
         seeds = [b"adapter_registry"],
         bump,
         has_one = authority @ ErrorCode::InvalidAuthority
     )]
     pub adapter_registry: Account<'info, AdapterRegistry>,
-    #[account(signer)]
+    #[account(mut)]
+    pub payer: Signer<'info>,
     pub authority: Signer<'info>,
+    pub system_program: Program<'info, System>,
+    /// CHECK: AdminLog PDA, recorded to once initialize_admin_log has created it
+    #[account(mut, seeds = [b"admin_log"], bump)]
+    pub admin_log: UncheckedAccount<'info>,
 }
 
-/// Accounts for configuring an adapter in the registry.
+/// Accounts for changing the protocol-wide limits.
 #[event_cpi]
 #[derive(Accounts)]
-pub struct ConfigureAdapter<'info> {
+pub struct SetProtocolLimits<'info> {
     #[account(
         mut,
+        seeds = [b"protocol_config"],
+        bump = protocol_config.bump
+    )]
+    pub protocol_config: Account<'info, ProtocolConfig>,
+    #[account(
         seeds = [b"adapter_registry"],
         bump,
-        constraint = adapter_registry.authority == operator.key() || adapter_registry.operators.contains(&operator.key()) @ ErrorCode::InvalidOperator
+        has_one = authority @ ErrorCode::InvalidAuthority
     )]
     pub adapter_registry: Account<'info, AdapterRegistry>,
-    #[account(signer)]
-    pub operator: Signer<'info>,
+    pub authority: Signer<'info>,
+    /// CHECK: AdminLog PDA, recorded to once initialize_admin_log has created it
+    #[account(mut, seeds = [b"admin_log"], bump)]
+    pub admin_log: UncheckedAccount<'info>,
 }
 
-/// Accounts for disabling an adapter in the registry.
+/// Accounts for setting the route platform fee volume tiers.
 #[event_cpi]
 #[derive(Accounts)]
-pub struct DisableAdapter<'info> {
+pub struct SetVolumeFeeTiers<'info> {
     #[account(
         mut,
+        seeds = [b"protocol_config"],
+        bump = protocol_config.bump
+    )]
+    pub protocol_config: Account<'info, ProtocolConfig>,
+    #[account(
         seeds = [b"adapter_registry"],
         bump,
-        constraint = adapter_registry.authority == operator.key() || adapter_registry.operators.contains(&operator.key()) @ ErrorCode::InvalidOperator
+        has_one = authority @ ErrorCode::InvalidAuthority
     )]
     pub adapter_registry: Account<'info, AdapterRegistry>,
-    #[account(signer)]
-    pub operator: Signer<'info>,
+    pub authority: Signer<'info>,
+    /// CHECK: AdminLog PDA, recorded to once initialize_admin_log has created it
+    #[account(mut, seeds = [b"admin_log"], bump)]
+    pub admin_log: UncheckedAccount<'info>,
 }
 
-/// Accounts for disabling a pool in an adapter.
+/// Accounts for pausing or resuming limit order creation or execution.
 #[event_cpi]
 #[derive(Accounts)]
-#[instruction(swap_type: Swap, pool_address: Pubkey)]
-pub struct DisablePool<'info> {
+pub struct SetOrderPause<'info> {
     #[account(
         mut,
-        seeds = [b"pool_info", swap_type.to_bytes().as_ref(), pool_address.as_ref()],
-        bump
+        seeds = [b"protocol_config"],
+        bump = protocol_config.bump
     )]
-    pub pool_info: Account<'info, PoolInfo>,
+    pub protocol_config: Account<'info, ProtocolConfig>,
     #[account(
         seeds = [b"adapter_registry"],
         bump,
-        constraint = adapter_registry.authority == operator.key() || adapter_registry.operators.contains(&operator.key()) @ ErrorCode::InvalidOperator
+        constraint = adapter_registry.is_authorized_operator(&operator.key()) @ ErrorCode::InvalidOperator
     )]
     pub adapter_registry: Account<'info, AdapterRegistry>,
-    #[account(signer)]
     pub operator: Signer<'info>,
+    /// CHECK: AdminLog PDA, recorded to once initialize_admin_log has created it
+    #[account(mut, seeds = [b"admin_log"], bump)]
+    pub admin_log: UncheckedAccount<'info>,
 }
 
-/// Accounts for changing the authority of the adapter registry.
+/// Accounts for growing a ProtocolConfig created before the order pause flags existed.
+#[derive(Accounts)]
+pub struct MigrateProtocolConfig<'info> {
+    /// CHECK: Legacy ProtocolConfig; owner, size and discriminator are checked in the handler
+    #[account(mut, seeds = [b"protocol_config"], bump)]
+    pub protocol_config: UncheckedAccount<'info>,
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+/// Accounts for proposing a GovernanceCall.
 #[event_cpi]
 #[derive(Accounts)]
-pub struct ChangeAuthority<'info> {
+pub struct ProposeGovernanceCall<'info> {
+    #[account(
+        init,
+        payer = authority,
+        space = GovernanceProposal::SPACE,
+        seeds = [b"governance_proposal"],
+        bump
+    )]
+    pub governance_proposal: Account<'info, GovernanceProposal>,
     #[account(
-        mut,
         seeds = [b"adapter_registry"],
         bump,
         has_one = authority @ ErrorCode::InvalidAuthority
     )]
     pub adapter_registry: Account<'info, AdapterRegistry>,
-    #[account(signer)]
+    /// CHECK: ProtocolConfig PDA, read with load_config; without it the delay is 0
+    #[account(seeds = [b"protocol_config"], bump)]
+    pub protocol_config: UncheckedAccount<'info>,
+    #[account(mut)]
     pub authority: Signer<'info>,
-    /// CHECK: The new authority is not validated here, as it is just a Pubkey being set.
-    pub new_authority: UncheckedAccount<'info>,
+    pub system_program: Program<'info, System>,
+    /// CHECK: AdminLog PDA, recorded to once initialize_admin_log has created it
+    #[account(mut, seeds = [b"admin_log"], bump)]
+    pub admin_log: UncheckedAccount<'info>,
 }
 
-/// Accounts for resetting the adapter registry.
+/// Accounts for cancelling the pending governance proposal.
 #[event_cpi]
 #[derive(Accounts)]
-pub struct ResetAdapterRegistry<'info> {
+pub struct CancelGovernanceProposal<'info> {
     #[account(
         mut,
+        seeds = [b"governance_proposal"],
+        bump = governance_proposal.bump,
+        has_one = proposer @ ErrorCode::InvalidAccount,
+        close = proposer
+    )]
+    pub governance_proposal: Account<'info, GovernanceProposal>,
+    #[account(seeds = [b"adapter_registry"], bump)]
+    pub adapter_registry: Account<'info, AdapterRegistry>,
+    /// CHECK: Proposer recorded on the proposal, refunded its rent
+    #[account(mut)]
+    pub proposer: UncheckedAccount<'info>,
+    pub canceller: Signer<'info>,
+    /// CHECK: AdminLog PDA, recorded to once initialize_admin_log has created it
+    #[account(mut, seeds = [b"admin_log"], bump)]
+    pub admin_log: UncheckedAccount<'info>,
+}
+
+/// Accounts for changing the governance delay.
+#[event_cpi]
+#[derive(Accounts)]
+pub struct SetGovernanceDelay<'info> {
+    #[account(
+        mut,
+        seeds = [b"protocol_config"],
+        bump = protocol_config.bump
+    )]
+    pub protocol_config: Account<'info, ProtocolConfig>,
+    #[account(
         seeds = [b"adapter_registry"],
         bump,
         has_one = authority @ ErrorCode::InvalidAuthority
     )]
     pub adapter_registry: Account<'info, AdapterRegistry>,
-    #[account(signer)]
+    /// Receives the rent of the governance proposal a lower delay consumes
+    #[account(mut)]
     pub authority: Signer<'info>,
+    /// Proposal of a lower delay; not needed to raise it
+    #[account(mut, seeds = [b"governance_proposal"], bump = governance_proposal.bump)]
+    pub governance_proposal: Option<Account<'info, GovernanceProposal>>,
+    /// CHECK: AdminLog PDA, recorded to once initialize_admin_log has created it
+    #[account(mut, seeds = [b"admin_log"], bump)]
+    pub admin_log: UncheckedAccount<'info>,
 }
 
-/// Accounts for migrating the adapter registry (writing bump to existing account).
-/// Uses realloc to expand the account by 1 byte and re-derives the bump from seeds.
+/// Accounts for creating the AdminLog.
 #[derive(Accounts)]
-pub struct MigrateAdapterRegistry<'info> {
+pub struct InitializeAdminLog<'info> {
+    #[account(
+        init,
+        payer = payer,
+        space = AdminLog::SPACE,
+        seeds = [b"admin_log"],
+        bump
+    )]
+    pub admin_log: Box<Account<'info, AdminLog>>,
     #[account(
-        mut,
-        realloc = 8 + 32 + 4 + 10 * (4 + 32 + 32) + 4 + 10 * 32 + 1,
-        realloc::payer = payer,
-        realloc::zero = false,
         seeds = [b"adapter_registry"],
         bump,
         has_one = authority @ ErrorCode::InvalidAuthority
@@ -327,4 +2044,18 @@ pub struct MigrateAdapterRegistry<'info> {
     pub payer: Signer<'info>,
     pub authority: Signer<'info>,
     pub system_program: Program<'info, System>,
-}
\ No newline at end of file
+}
+
+/// Accounts for reading the AdminLog.
+#[derive(Accounts)]
+pub struct GetAdminLog<'info> {
+    #[account(seeds = [b"admin_log"], bump = admin_log.bump)]
+    pub admin_log: Box<Account<'info, AdminLog>>,
+}
+
+/// Accounts for describing adapters.
+#[derive(Accounts)]
+pub struct DescribeAdapters<'info> {
+    #[account(seeds = [b"adapter_registry"], bump)]
+    pub adapter_registry: Account<'info, AdapterRegistry>,
+}
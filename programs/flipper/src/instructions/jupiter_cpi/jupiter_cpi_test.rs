@@ -0,0 +1,120 @@
+#[cfg(test)]
+mod tests {
+    use super::super::*;
+    use crate::test_utils::*;
+
+    // Thirteen Jupiter accounts; index 9 signed the outer transaction
+    fn jupiter_accounts() -> &'static [AccountInfo<'static>] {
+        let mut accounts: Vec<TestAccount> = (0..JUPITER_MIN_ACCOUNTS).map(|_| empty_account()).collect();
+        accounts[9].is_signer = true;
+        accounts[0].is_writable = false;
+        leak_account_infos(accounts)
+    }
+
+    #[test]
+    fn test_build_account_metas_forces_only_transfer_authority() {
+        let accounts = jupiter_accounts();
+        let metas = build_account_metas(accounts, false);
+
+        assert_eq!(metas.len(), accounts.len());
+        for (idx, meta) in metas.iter().enumerate() {
+            assert_eq!(meta.pubkey, *accounts[idx].key);
+            assert_eq!(meta.is_signer, idx == JUPITER_USER_TRANSFER_AUTHORITY_INDEX);
+            assert_eq!(meta.is_writable, accounts[idx].is_writable);
+        }
+    }
+
+    #[test]
+    fn test_build_account_metas_preserves_outer_signers() {
+        let accounts = jupiter_accounts();
+        let metas = build_account_metas(accounts, true);
+
+        for (idx, meta) in metas.iter().enumerate() {
+            assert_eq!(meta.is_signer, idx == JUPITER_USER_TRANSFER_AUTHORITY_INDEX || idx == 9);
+        }
+        assert!(!metas[0].is_writable);
+    }
+
+    #[test]
+    fn test_validate_fixed_positions() {
+        let accounts = jupiter_accounts();
+        let authority = *accounts[JUPITER_USER_TRANSFER_AUTHORITY_INDEX].key;
+        let source = *accounts[JUPITER_USER_SOURCE_INDEX].key;
+        let destination = *accounts[JUPITER_USER_DESTINATION_INDEX].key;
+
+        assert!(validate_fixed_positions(accounts, &authority, &source, &destination).is_ok());
+
+        let other = Pubkey::new_unique();
+        let err = validate_fixed_positions(accounts, &other, &source, &destination).unwrap_err();
+        assert_eq!(err, ErrorCode::JupiterProgramAuthorityMismatch.into());
+        let err = validate_fixed_positions(accounts, &authority, &other, &destination).unwrap_err();
+        assert_eq!(err, ErrorCode::JupiterProgramSourceMismatch.into());
+        let err = validate_fixed_positions(accounts, &authority, &source, &other).unwrap_err();
+        assert_eq!(err, ErrorCode::JupiterProgramDestinationMismatch.into());
+    }
+
+    #[test]
+    fn test_validate_fixed_positions_requires_all_accounts() {
+        let accounts = jupiter_accounts();
+        let authority = *accounts[JUPITER_USER_TRANSFER_AUTHORITY_INDEX].key;
+        let source = *accounts[JUPITER_USER_SOURCE_INDEX].key;
+        let destination = *accounts[JUPITER_USER_DESTINATION_INDEX].key;
+
+        let err = validate_fixed_positions(
+            &accounts[..JUPITER_MIN_ACCOUNTS - 1],
+            &authority,
+            &source,
+            &destination,
+        ).unwrap_err();
+        assert_eq!(err, ErrorCode::NotEnoughJupiterAccounts.into());
+    }
+
+    fn route_data(len: usize) -> Vec<u8> {
+        let mut data = JUPITER_SHARED_ACCOUNTS_ROUTE_DISCRIMINATOR.to_vec();
+        data.resize(len, 0);
+        data
+    }
+
+    #[test]
+    fn test_validate_jupiter_data_minimum_length() {
+        let limit = DEFAULT_MAX_JUPITER_DATA_LEN as usize;
+        assert!(validate_jupiter_data(&route_data(JUPITER_MIN_DATA_LEN), limit).is_ok());
+
+        for len in [0, 1, JUPITER_MIN_DATA_LEN - 1] {
+            let err = validate_jupiter_data(&route_data(8)[..len], limit).unwrap_err();
+            assert_eq!(err, ErrorCode::JupiterDataTooShort.into());
+        }
+    }
+
+    #[test]
+    fn test_validate_jupiter_data_maximum_length() {
+        let limit = DEFAULT_MAX_JUPITER_DATA_LEN as usize;
+        assert!(validate_jupiter_data(&route_data(limit), limit).is_ok());
+
+        let err = validate_jupiter_data(&route_data(limit + 1), limit).unwrap_err();
+        assert_eq!(err, ErrorCode::JupiterDataTooLong.into());
+
+        // A raised limit admits up to a full packet
+        let hard_cap = JUPITER_DATA_HARD_CAP as usize;
+        assert!(validate_jupiter_data(&route_data(hard_cap), hard_cap).is_ok());
+        let err = validate_jupiter_data(&route_data(10_240), hard_cap).unwrap_err();
+        assert_eq!(err, ErrorCode::JupiterDataTooLong.into());
+    }
+
+    #[test]
+    fn test_validate_jupiter_data_rejects_wrong_discriminator() {
+        let limit = DEFAULT_MAX_JUPITER_DATA_LEN as usize;
+        for byte in 0..8 {
+            let mut data = route_data(64);
+            data[byte] ^= 0xff;
+            let err = validate_jupiter_data(&data, limit).unwrap_err();
+            assert_eq!(err, ErrorCode::InvalidJupiterDiscriminator.into());
+        }
+
+        // Length is checked before the discriminator
+        let err = validate_jupiter_data(&[0u8; 4], limit).unwrap_err();
+        assert_eq!(err, ErrorCode::JupiterDataTooShort.into());
+        let err = validate_jupiter_data(&vec![0u8; limit + 1], limit).unwrap_err();
+        assert_eq!(err, ErrorCode::JupiterDataTooLong.into());
+    }
+}
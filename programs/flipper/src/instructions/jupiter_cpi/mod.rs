@@ -0,0 +1,106 @@
+use anchor_lang::prelude::*;
+use anchor_lang::solana_program::instruction::AccountMeta;
+use crate::errors::ErrorCode;
+
+/// Jupiter shared_accounts_route fixed account indices (must match Jupiter IDL order).
+/// 0: token_program
+/// 1: program_authority (Jupiter PDA - not ours!)
+/// 2: user_transfer_authority  <-- Must match our vault_authority (we sign for this)
+/// 3: user_source_token_account  <-- Must match our source vault
+/// 4: program_source_token_account (Jupiter's intermediate account)
+/// 5: program_destination_token_account (Jupiter's intermediate account)
+/// 6: user_destination_token_account  <-- Must match our destination vault
+/// 7-12: source_mint, destination_mint, platform_fee, token2022, event_authority, program
+pub const JUPITER_USER_TRANSFER_AUTHORITY_INDEX: usize = 2;
+pub const JUPITER_USER_SOURCE_INDEX: usize = 3;
+pub const JUPITER_USER_DESTINATION_INDEX: usize = 6;
+pub const JUPITER_MIN_ACCOUNTS: usize = 13; // 0..=12: token_program, program_authority, ..., program
+
+/// Anchor discriminator of Jupiter shared_accounts_route (sha256("global:shared_accounts_route")[..8])
+pub const JUPITER_SHARED_ACCOUNTS_ROUTE_DISCRIMINATOR: [u8; 8] = [193, 32, 155, 51, 65, 214, 156, 129];
+/// Shortest accepted Jupiter instruction data: the discriminator alone
+pub const JUPITER_MIN_DATA_LEN: usize = 8;
+/// Largest configurable data limit: a whole Solana packet (PACKET_DATA_SIZE)
+pub const JUPITER_DATA_HARD_CAP: u16 = 1232;
+/// Data limit used while VaultAuthority.max_jupiter_data_len is 0: the packet minus one signature,
+/// the message header, the blockhash and room for the outer instruction's accounts and args
+pub const DEFAULT_MAX_JUPITER_DATA_LEN: u16 = 1000;
+
+// Test modules
+#[cfg(test)]
+mod jupiter_cpi_test;
+
+/// Builds the Jupiter CPI account metas from remaining_accounts
+/// user_transfer_authority (index 2) is always a signer, since vault_authority signs via invoke_signed
+///
+/// # Arguments
+/// * `remaining_accounts` - Jupiter accounts in shared_accounts_route order
+/// * `preserve_signers` - Also keep accounts that signed the outer transaction as signers
+///   (e.g. a pool authority in test routes); otherwise only index 2 is a signer
+///
+/// # Returns
+/// * `Vec<AccountMeta>` - One meta per remaining account, writability unchanged
+pub fn build_account_metas(remaining_accounts: &[AccountInfo], preserve_signers: bool) -> Vec<AccountMeta> {
+    remaining_accounts
+        .iter()
+        .enumerate()
+        .map(|(idx, acc)| AccountMeta {
+            pubkey: *acc.key,
+            is_signer: idx == JUPITER_USER_TRANSFER_AUTHORITY_INDEX || (preserve_signers && acc.is_signer),
+            is_writable: acc.is_writable,
+        })
+        .collect()
+}
+
+/// Checks the size and discriminator of client-supplied Jupiter instruction data before the CPI
+///
+/// # Arguments
+/// * `data` - Serialized shared_accounts_route instruction data
+/// * `max_len` - Largest accepted length, from VaultAuthority::jupiter_data_limit
+///
+/// # Returns
+/// * `Result<()>` - JupiterDataTooShort, JupiterDataTooLong or InvalidJupiterDiscriminator
+pub fn validate_jupiter_data(data: &[u8], max_len: usize) -> Result<()> {
+    require!(data.len() >= JUPITER_MIN_DATA_LEN, ErrorCode::JupiterDataTooShort);
+    require!(data.len() <= max_len, ErrorCode::JupiterDataTooLong);
+    require!(
+        data[..8] == JUPITER_SHARED_ACCOUNTS_ROUTE_DISCRIMINATOR,
+        ErrorCode::InvalidJupiterDiscriminator
+    );
+    Ok(())
+}
+
+/// Checks that the fixed shared_accounts_route positions hold our accounts
+///
+/// # Arguments
+/// * `remaining_accounts` - Jupiter accounts in shared_accounts_route order
+/// * `vault_authority` - Expected user_transfer_authority (index 2)
+/// * `user_source` - Expected user_source_token_account (index 3)
+/// * `user_destination` - Expected user_destination_token_account (index 6)
+///
+/// # Returns
+/// * `Result<()>` - NotEnoughJupiterAccounts or the mismatch error of the first wrong position
+pub fn validate_fixed_positions(
+    remaining_accounts: &[AccountInfo],
+    vault_authority: &Pubkey,
+    user_source: &Pubkey,
+    user_destination: &Pubkey,
+) -> Result<()> {
+    require!(
+        remaining_accounts.len() >= JUPITER_MIN_ACCOUNTS,
+        ErrorCode::NotEnoughJupiterAccounts
+    );
+    require!(
+        remaining_accounts[JUPITER_USER_TRANSFER_AUTHORITY_INDEX].key == vault_authority,
+        ErrorCode::JupiterProgramAuthorityMismatch
+    );
+    require!(
+        remaining_accounts[JUPITER_USER_SOURCE_INDEX].key == user_source,
+        ErrorCode::JupiterProgramSourceMismatch
+    );
+    require!(
+        remaining_accounts[JUPITER_USER_DESTINATION_INDEX].key == user_destination,
+        ErrorCode::JupiterProgramDestinationMismatch
+    );
+    Ok(())
+}
@@ -1,6 +1,8 @@
 #[cfg(test)]
 mod tests {
     use super::super::*;
+    use crate::errors::ErrorCode;
+    use crate::test_utils::*;
     use anchor_lang::prelude::*;
 
     fn create_test_limit_order(
@@ -22,6 +24,14 @@ mod tests {
             status: OrderStatus::Open,
             slippage_bps: 50,
             bump: 0,
+            input_token_program: Pubkey::new_unique(),
+            output_token_program: Pubkey::new_unique(),
+            failed_attempts: 0,
+            activate_at: 0,
+            execution_fee_bps: None,
+            unwrap_output: false,
+            user_lamport_destination: Pubkey::default(),
+            generation: 0,
         }
     }
 
@@ -206,6 +216,7 @@ mod tests {
         assert_eq!(OrderStatus::Filled as u8, 1);
         assert_eq!(OrderStatus::Cancelled as u8, 2);
         assert_eq!(OrderStatus::Init as u8, 3);
+        assert_eq!(OrderStatus::NeedsReview as u8, 4);
     }
 
     #[test]
@@ -228,7 +239,19 @@ mod tests {
     fn test_limit_order_space_constant() {
         // Verify the SPACE constant matches the actual struct size
         // This is important for account initialization
-        assert_eq!(LimitOrder::SPACE, 8 + 193);
+        assert_eq!(LimitOrder::SPACE, 8 + 305);
+        // Orders created before the token programs were stored
+        assert_eq!(LimitOrder::LEGACY_SPACE, 8 + 193);
+        // Orders created before failed_attempts was stored
+        assert_eq!(LimitOrder::PRE_FAILED_ATTEMPTS_SPACE, 8 + 257);
+        // Orders created before activate_at was stored
+        assert_eq!(LimitOrder::PRE_ACTIVATE_AT_SPACE, 8 + 258);
+        // Orders created before execution_fee_bps was stored
+        assert_eq!(LimitOrder::PRE_EXECUTION_FEE_SPACE, 8 + 266);
+        // Orders created before unwrap_output was stored
+        assert_eq!(LimitOrder::PRE_UNWRAP_OUTPUT_SPACE, 8 + 268);
+        // Orders created before generation was stored
+        assert_eq!(LimitOrder::PRE_GENERATION_SPACE, 8 + 301);
     }
 
     #[test]
@@ -321,4 +344,1193 @@ mod tests {
         assert!(result.is_ok());
         assert!(!result.unwrap());
     }
+
+    #[test]
+    fn test_limit_order_calculate_effective_price() {
+        let order = create_test_limit_order(TriggerType::TakeProfit, 1000, 100_000);
+
+        // 1,000,000 in -> 2,500,000 out = 2.5 output per input
+        let price = order.calculate_effective_price(2_500_000).unwrap();
+        assert_eq!(price, 2_500_000_000);
+
+        // 1,000,000 in -> 500 out = 0.0005 output per input
+        let price = order.calculate_effective_price(500).unwrap();
+        assert_eq!(price, 500_000);
+    }
+
+    #[test]
+    fn test_limit_order_calculate_effective_price_zero_input() {
+        let mut order = create_test_limit_order(TriggerType::TakeProfit, 1000, 100_000);
+        order.input_amount = 0;
+
+        assert!(order.calculate_effective_price(1_000).is_err());
+    }
+
+    #[test]
+    fn test_limit_order_calculate_effective_price_overflow() {
+        let mut order = create_test_limit_order(TriggerType::TakeProfit, 1000, 100_000);
+        order.input_amount = 1;
+
+        // u64::MAX * 1e9 does not fit in u64; the price saturates and the fill goes through
+        assert_eq!(order.calculate_effective_price(u64::MAX).unwrap(), u64::MAX);
+    }
+
+    #[test]
+    fn test_limit_order_trigger_threshold() {
+        // TakeProfit: 100,000 * 1.10
+        let order = create_test_limit_order(TriggerType::TakeProfit, 1_000, 100_000);
+        assert_eq!(order.calculate_trigger_threshold().unwrap(), 110_000);
+
+        // TakeProfit above 100%: 100,000 * 11.0
+        let order = create_test_limit_order(TriggerType::TakeProfit, 100_000, 100_000);
+        assert_eq!(order.calculate_trigger_threshold().unwrap(), 1_100_000);
+
+        // StopLoss: 100,000 * 0.90
+        let order = create_test_limit_order(TriggerType::StopLoss, 1_000, 100_000);
+        assert_eq!(order.calculate_trigger_threshold().unwrap(), 90_000);
+
+        // StopLoss at 100% has a zero threshold
+        let order = create_test_limit_order(TriggerType::StopLoss, 10_000, 100_000);
+        assert_eq!(order.calculate_trigger_threshold().unwrap(), 0);
+    }
+
+    #[test]
+    fn test_limit_order_execution_premium_take_profit() {
+        let order = create_test_limit_order(TriggerType::TakeProfit, 1_000, 100_000);
+
+        // 0.8% better than the 110,000 threshold
+        assert_eq!(order.calculate_execution_premium_bps(110_880).unwrap(), 80);
+        assert_eq!(order.calculate_execution_premium_bps(110_000).unwrap(), 0);
+        // Fees can push the delivered output below the threshold
+        assert_eq!(order.calculate_execution_premium_bps(109_890).unwrap(), -10);
+    }
+
+    #[test]
+    fn test_limit_order_execution_premium_stop_loss_is_negative() {
+        let order = create_test_limit_order(TriggerType::StopLoss, 1_000, 100_000);
+
+        // StopLoss fires at or below the 90,000 threshold
+        assert_eq!(order.calculate_execution_premium_bps(90_000).unwrap(), 0);
+        assert_eq!(order.calculate_execution_premium_bps(85_500).unwrap(), -500);
+        // Rounded towards zero
+        assert_eq!(order.calculate_execution_premium_bps(89_999).unwrap(), 0);
+        assert_eq!(order.calculate_execution_premium_bps(0).unwrap(), -10_000);
+        // Slippage in the user's favour still reports a premium
+        assert_eq!(order.calculate_execution_premium_bps(90_900).unwrap(), 100);
+
+        // A zero threshold has no meaningful premium
+        let order = create_test_limit_order(TriggerType::StopLoss, 10_000, 100_000);
+        assert_eq!(order.calculate_execution_premium_bps(5_000).unwrap(), 0);
+    }
+
+    #[test]
+    fn test_limit_order_trigger_threshold_rejects_invalid_stop_loss() {
+        // Rejected at creation; never wraps when computing the threshold
+        let order = create_test_limit_order(TriggerType::StopLoss, 10_001, 100_000);
+        assert!(order.calculate_trigger_threshold().is_err());
+        let order = create_test_limit_order(TriggerType::StopLoss, 70_000, 100_000);
+        assert!(order.calculate_trigger_threshold().is_err());
+    }
+
+    fn create_test_receipt() -> OrderReceipt {
+        OrderReceipt {
+            order: Pubkey::new_unique(),
+            creator: Pubkey::new_unique(),
+            status: OrderStatus::Open,
+            output_amount: 0,
+            fee_amount: 0,
+            executor: Pubkey::default(),
+            timestamp: 0,
+            bump: 255,
+            vault_close_pending: false,
+        }
+    }
+
+    #[test]
+    fn test_order_receipt_space_matches_serialized_size() {
+        let receipt = create_test_receipt();
+        let data = receipt.try_to_vec().unwrap();
+        assert_eq!(OrderReceipt::SPACE, 8 + data.len());
+    }
+
+    #[test]
+    fn test_order_receipt_record_filled() {
+        let mut receipt = create_test_receipt();
+        let operator = Pubkey::new_unique();
+        assert!(!receipt.is_final());
+
+        receipt.record(OrderStatus::Filled, 1_500_000, 1_500, operator, 1_700_000_000);
+
+        assert!(receipt.is_final());
+        assert_eq!(receipt.status, OrderStatus::Filled);
+        assert_eq!(receipt.output_amount, 1_500_000);
+        assert_eq!(receipt.fee_amount, 1_500);
+        assert_eq!(receipt.executor, operator);
+        assert_eq!(receipt.timestamp, 1_700_000_000);
+    }
+
+    #[test]
+    fn test_order_receipt_record_cancelled() {
+        let mut receipt = create_test_receipt();
+        let creator = receipt.creator;
+
+        receipt.record(OrderStatus::Cancelled, 0, 0, creator, 1_700_000_000);
+
+        assert!(receipt.is_final());
+        assert_eq!(receipt.status, OrderStatus::Cancelled);
+        assert_eq!(receipt.output_amount, 0);
+        assert_eq!(receipt.executor, creator);
+    }
+
+    #[test]
+    fn test_order_receipt_init_is_not_final() {
+        let mut receipt = create_test_receipt();
+        receipt.status = OrderStatus::Init;
+        assert!(!receipt.is_final());
+    }
+
+    #[test]
+    fn test_limit_order_space_matches_serialized_size() {
+        // Adding a field without updating SPACE must fail here, not at init time
+        let mut order = create_test_limit_order(TriggerType::StopLoss, 1000, 100_000);
+        // SPACE reserves room for Some(execution_fee_bps), the largest serialized form
+        order.execution_fee_bps = Some(25);
+        let data = order.try_to_vec().unwrap();
+        assert_eq!(LimitOrder::SPACE, 8 + data.len());
+    }
+
+    // Single-signature ed25519 program instruction data with all offsets inside the instruction
+    fn create_test_ed25519_data(public_key: &Pubkey, signature: &[u8; 64], message: &[u8]) -> Vec<u8> {
+        let public_key_offset: u16 = 16;
+        let signature_offset: u16 = public_key_offset + 32;
+        let message_offset: u16 = signature_offset + 64;
+
+        let mut data = vec![1u8, 0u8];
+        for value in [
+            signature_offset,
+            u16::MAX,
+            public_key_offset,
+            u16::MAX,
+            message_offset,
+            message.len() as u16,
+            u16::MAX,
+        ] {
+            data.extend_from_slice(&value.to_le_bytes());
+        }
+        data.extend_from_slice(public_key.as_ref());
+        data.extend_from_slice(signature);
+        data.extend_from_slice(message);
+        data
+    }
+
+    #[test]
+    fn test_parse_ed25519_instruction_roundtrip() {
+        let signer = Pubkey::new_unique();
+        let signature = [7u8; 64];
+        let message = quote_attestation_message(&Pubkey::new_unique(), 1_000, 10, 20);
+        let data = create_test_ed25519_data(&signer, &signature, &message);
+
+        let (parsed_signer, parsed_signature, parsed_message) = parse_ed25519_instruction(&data).unwrap();
+        assert_eq!(parsed_signer, signer);
+        assert_eq!(parsed_signature, signature);
+        assert_eq!(parsed_message, message.as_slice());
+    }
+
+    #[test]
+    fn test_parse_ed25519_instruction_rejects_forged_offsets() {
+        let signer = Pubkey::new_unique();
+        let message = quote_attestation_message(&Pubkey::new_unique(), 1_000, 10, 20);
+        let valid = create_test_ed25519_data(&signer, &[7u8; 64], &message);
+
+        // Public key / message read from another instruction: the ed25519 program verified different bytes
+        for index_field in [4usize, 8, 14] {
+            let mut forged = valid.clone();
+            forged[index_field..index_field + 2].copy_from_slice(&0u16.to_le_bytes());
+            let err = parse_ed25519_instruction(&forged).unwrap_err();
+            assert_eq!(err, ErrorCode::InvalidQuoteAttestation.into());
+        }
+
+        // More than one signature
+        let mut multi = valid.clone();
+        multi[0] = 2;
+        assert!(parse_ed25519_instruction(&multi).is_err());
+
+        // Message offset past the end of the data
+        let mut out_of_bounds = valid.clone();
+        out_of_bounds[10..12].copy_from_slice(&u16::MAX.to_le_bytes());
+        assert!(parse_ed25519_instruction(&out_of_bounds).is_err());
+
+        // Truncated data
+        assert!(parse_ed25519_instruction(&valid[..10]).is_err());
+    }
+
+    #[test]
+    fn test_validate_quote_attestation_message() {
+        let order = Pubkey::new_unique();
+        let message = quote_attestation_message(&order, 1_000, 100, 200);
+
+        assert!(validate_quote_attestation_message(&message, &order, 1_000, 100).is_ok());
+        assert!(validate_quote_attestation_message(&message, &order, 1_000, 200).is_ok());
+
+        // Attestation for another order or another quote
+        let err = validate_quote_attestation_message(&message, &Pubkey::new_unique(), 1_000, 150).unwrap_err();
+        assert_eq!(err, ErrorCode::InvalidQuoteAttestation.into());
+        let err = validate_quote_attestation_message(&message, &order, 1_001, 150).unwrap_err();
+        assert_eq!(err, ErrorCode::InvalidQuoteAttestation.into());
+
+        // Truncated message
+        let err = validate_quote_attestation_message(&message[..48], &order, 1_000, 150).unwrap_err();
+        assert_eq!(err, ErrorCode::InvalidQuoteAttestation.into());
+    }
+
+    #[test]
+    fn test_validate_quote_attestation_message_expired() {
+        let order = Pubkey::new_unique();
+        let message = quote_attestation_message(&order, 1_000, 100, 200);
+
+        let err = validate_quote_attestation_message(&message, &order, 1_000, 201).unwrap_err();
+        assert_eq!(err, ErrorCode::QuoteAttestationExpired.into());
+        let err = validate_quote_attestation_message(&message, &order, 1_000, 99).unwrap_err();
+        assert_eq!(err, ErrorCode::QuoteAttestationExpired.into());
+    }
+
+    #[test]
+    fn test_validate_quote_attestation_message_window_limits() {
+        let order = Pubkey::new_unique();
+
+        let widest = quote_attestation_message(&order, 1_000, 100, 100 + MAX_QUOTE_ATTESTATION_SLOTS);
+        assert!(validate_quote_attestation_message(&widest, &order, 1_000, 150).is_ok());
+
+        let too_wide = quote_attestation_message(&order, 1_000, 100, 101 + MAX_QUOTE_ATTESTATION_SLOTS);
+        let err = validate_quote_attestation_message(&too_wide, &order, 1_000, 150).unwrap_err();
+        assert_eq!(err, ErrorCode::InvalidQuoteAttestation.into());
+
+        let inverted = quote_attestation_message(&order, 1_000, 200, 100);
+        let err = validate_quote_attestation_message(&inverted, &order, 1_000, 150).unwrap_err();
+        assert_eq!(err, ErrorCode::InvalidQuoteAttestation.into());
+    }
+
+    #[test]
+    fn test_verify_quote_attestation_not_required_without_signer() {
+        let result = verify_quote_attestation(
+            &Pubkey::default(),
+            None,
+            None,
+            None,
+            &Pubkey::new_unique(),
+            1_000,
+            100,
+        );
+        assert!(result.is_ok());
+    }
+
+    #[test]
+    fn test_verify_quote_attestation_missing_or_forged_publisher() {
+        let quote_signer = Pubkey::new_unique();
+        let order = Pubkey::new_unique();
+
+        // No attestation at all
+        let err = verify_quote_attestation(&quote_signer, None, None, None, &order, 1_000, 100).unwrap_err();
+        assert_eq!(err, ErrorCode::MissingQuoteAttestation.into());
+
+        // Signature without publisher
+        let err = verify_quote_attestation(&quote_signer, None, Some([1u8; 64]), None, &order, 1_000, 100).unwrap_err();
+        assert_eq!(err, ErrorCode::MissingQuoteAttestation.into());
+
+        // Attestation signed by someone other than the configured quote signer
+        let err = verify_quote_attestation(
+            &quote_signer,
+            None,
+            Some([1u8; 64]),
+            Some(Pubkey::new_unique()),
+            &order,
+            1_000,
+            100,
+        ).unwrap_err();
+        assert_eq!(err, ErrorCode::InvalidQuoteAttestation.into());
+
+        // Correct publisher but no instructions sysvar to introspect
+        let err = verify_quote_attestation(
+            &quote_signer,
+            None,
+            Some([1u8; 64]),
+            Some(quote_signer),
+            &order,
+            1_000,
+            100,
+        ).unwrap_err();
+        assert_eq!(err, ErrorCode::MissingQuoteAttestation.into());
+    }
+
+    #[test]
+    fn test_validate_price_attestation_message() {
+        let input_mint = Pubkey::new_unique();
+        let output_mint = Pubkey::new_unique();
+        let message = price_attestation_message(&input_mint, &output_mint, 150_000_000_000, 100, 200);
+
+        let reference_price = validate_price_attestation_message(&message, &input_mint, &output_mint, 150).unwrap();
+        assert_eq!(reference_price, 150_000_000_000);
+
+        // Attestation for the reverse pair
+        let err = validate_price_attestation_message(&message, &output_mint, &input_mint, 150).unwrap_err();
+        assert_eq!(err, ErrorCode::InvalidQuoteAttestation.into());
+
+        // An order quote attestation is never accepted as a price attestation
+        let quote = quote_attestation_message(&input_mint, 150_000_000_000, 100, 200);
+        let err = validate_price_attestation_message(&quote, &input_mint, &output_mint, 150).unwrap_err();
+        assert_eq!(err, ErrorCode::InvalidQuoteAttestation.into());
+
+        let zero_price = price_attestation_message(&input_mint, &output_mint, 0, 100, 200);
+        let err = validate_price_attestation_message(&zero_price, &input_mint, &output_mint, 150).unwrap_err();
+        assert_eq!(err, ErrorCode::InvalidQuoteAttestation.into());
+
+        let err = validate_price_attestation_message(&message, &input_mint, &output_mint, 201).unwrap_err();
+        assert_eq!(err, ErrorCode::QuoteAttestationExpired.into());
+
+        let too_wide = price_attestation_message(&input_mint, &output_mint, 1, 100, 101 + MAX_QUOTE_ATTESTATION_SLOTS);
+        let err = validate_price_attestation_message(&too_wide, &input_mint, &output_mint, 150).unwrap_err();
+        assert_eq!(err, ErrorCode::InvalidQuoteAttestation.into());
+    }
+
+    #[test]
+    fn test_implied_order_price_adjusts_for_decimals() {
+        // 1 SOL (9 decimals) for 150 USDC (6 decimals)
+        assert_eq!(implied_order_price(1_000_000_000, 150_000_000, 9, 6), Some(150_000_000_000));
+        // 150 USDC for 1 SOL: 1/150 SOL per USDC
+        assert_eq!(implied_order_price(150_000_000, 1_000_000_000, 6, 9), Some(6_666_666));
+        // Same decimals
+        assert_eq!(implied_order_price(2_000, 1_000, 6, 6), Some(ORDER_PRICE_SCALE / 2));
+        // Too large to represent
+        assert_eq!(implied_order_price(1, u64::MAX, 38, 0), None);
+    }
+
+    #[test]
+    fn test_check_order_price_band_edges() {
+        // 150 USDC per SOL with a 10% band: 135 to 165 USDC per SOL inclusive
+        let reference_price = 150_000_000_000;
+        let price = |min_output_amount: u64| implied_order_price(1_000_000_000, min_output_amount, 9, 6).unwrap();
+
+        assert!(check_order_price_band(price(150_000_000), reference_price, 1_000).is_ok());
+        assert!(check_order_price_band(price(135_000_000), reference_price, 1_000).is_ok());
+        assert!(check_order_price_band(price(165_000_000), reference_price, 1_000).is_ok());
+
+        let err = check_order_price_band(price(134_999_999), reference_price, 1_000).unwrap_err();
+        assert_eq!(err, ErrorCode::ImplausibleOrderPrice.into());
+        let err = check_order_price_band(price(165_000_001), reference_price, 1_000).unwrap_err();
+        assert_eq!(err, ErrorCode::ImplausibleOrderPrice.into());
+    }
+
+    #[test]
+    fn test_check_order_price_band_rejects_unit_confusion() {
+        let reference_price = 150_000_000_000;
+        let price = |min_output_amount: u64| implied_order_price(1_000_000_000, min_output_amount, 9, 6).unwrap();
+
+        // 150 given as a raw amount instead of 150 USDC, and 150 USDC scaled up by 10^6 twice
+        let err = check_order_price_band(price(150), reference_price, DEFAULT_ORDER_PRICE_BAND_BPS).unwrap_err();
+        assert_eq!(err, ErrorCode::ImplausibleOrderPrice.into());
+        let err = check_order_price_band(price(150_000_000_000_000), reference_price, DEFAULT_ORDER_PRICE_BAND_BPS).unwrap_err();
+        assert_eq!(err, ErrorCode::ImplausibleOrderPrice.into());
+
+        // The widest band still has a finite upper edge
+        assert!(check_order_price_band(price(1), reference_price, 10_000).is_ok());
+        assert!(check_order_price_band(price(300_000_000), reference_price, 10_000).is_ok());
+        let err = check_order_price_band(price(300_000_001), reference_price, 10_000).unwrap_err();
+        assert_eq!(err, ErrorCode::ImplausibleOrderPrice.into());
+    }
+
+    #[test]
+    fn test_verify_order_price_requires_quote_signer() {
+        let registry = registry_with_fee(0, Pubkey::default());
+        let accounts = leak_account_infos(vec![
+            mint_account(9, anchor_spl::token::ID),
+            mint_account(6, anchor_spl::token::ID),
+        ]);
+        let input_mint = InterfaceAccount::<Mint>::try_from(&accounts[0]).unwrap();
+        let output_mint = InterfaceAccount::<Mint>::try_from(&accounts[1]).unwrap();
+
+        let err = verify_order_price(
+            &registry,
+            None,
+            Some([1u8; 64]),
+            Some(Pubkey::new_unique()),
+            &input_mint,
+            &output_mint,
+            1_000_000_000,
+            150_000_000,
+            100,
+        ).unwrap_err();
+        assert_eq!(err, ErrorCode::MissingQuoteAttestation.into());
+
+        // With a quote signer, the attestation must be signed by it
+        let mut registry = registry;
+        registry.quote_signer = Pubkey::new_unique();
+        let err = verify_order_price(
+            &registry,
+            None,
+            Some([1u8; 64]),
+            Some(Pubkey::new_unique()),
+            &input_mint,
+            &output_mint,
+            1_000_000_000,
+            150_000_000,
+            100,
+        ).unwrap_err();
+        assert_eq!(err, ErrorCode::InvalidQuoteAttestation.into());
+    }
+
+    fn create_legacy_limit_order_data(input_vault: Pubkey, output_mint: Pubkey) -> Vec<u8> {
+        let mut data = vec![0u8; LimitOrder::LEGACY_SPACE];
+        data[..8].copy_from_slice(LimitOrder::DISCRIMINATOR);
+        data[72..104].copy_from_slice(output_mint.as_ref());
+        data[104..136].copy_from_slice(input_vault.as_ref());
+        data
+    }
+
+    #[test]
+    fn test_legacy_limit_order_keys() {
+        let input_vault = Pubkey::new_unique();
+        let output_mint = Pubkey::new_unique();
+        let data = create_legacy_limit_order_data(input_vault, output_mint);
+
+        let keys = legacy_limit_order_keys(&data).unwrap();
+        assert_eq!(keys, (input_vault, output_mint));
+    }
+
+    #[test]
+    fn test_legacy_limit_order_keys_rejects_migrated_order() {
+        let mut data = create_legacy_limit_order_data(Pubkey::new_unique(), Pubkey::new_unique());
+        data.resize(LimitOrder::SPACE, 0);
+
+        let err = legacy_limit_order_keys(&data).unwrap_err();
+        assert_eq!(err, ErrorCode::InvalidAccount.into());
+    }
+
+    #[test]
+    fn test_legacy_limit_order_keys_rejects_pre_failed_attempts_order() {
+        let mut data = create_legacy_limit_order_data(Pubkey::new_unique(), Pubkey::new_unique());
+        data.resize(LimitOrder::PRE_FAILED_ATTEMPTS_SPACE, 0);
+
+        let err = legacy_limit_order_keys(&data).unwrap_err();
+        assert_eq!(err, ErrorCode::InvalidAccount.into());
+    }
+
+    #[test]
+    fn test_failed_attempts_survive_serialization() {
+        let mut order = create_test_limit_order(TriggerType::TakeProfit, 1000, 100_000);
+        order.failed_attempts = MAX_FAILED_EXECUTION_ATTEMPTS;
+        order.status = OrderStatus::NeedsReview;
+
+        let data = order.try_to_vec().unwrap();
+        assert_eq!(data[LimitOrder::PRE_FAILED_ATTEMPTS_SPACE - 8], MAX_FAILED_EXECUTION_ATTEMPTS);
+
+        let decoded = LimitOrder::try_from_slice(&data).unwrap();
+        assert_eq!(decoded.failed_attempts, MAX_FAILED_EXECUTION_ATTEMPTS);
+        assert_eq!(decoded.status, OrderStatus::NeedsReview);
+    }
+
+    #[test]
+    fn test_limit_order_is_active() {
+        let mut order = create_test_limit_order(TriggerType::TakeProfit, 1000, 100_000);
+        // activate_at = 0 means executable immediately
+        assert!(order.is_active(0));
+        assert!(order.is_active(1_700_000_000));
+
+        order.activate_at = 1_700_000_000;
+        assert!(!order.is_active(1_699_999_999));
+        assert!(order.is_active(1_700_000_000));
+        assert!(order.is_active(1_700_000_001));
+    }
+
+    #[test]
+    fn test_activate_at_precedes_execution_fee() {
+        let mut order = create_test_limit_order(TriggerType::TakeProfit, 1000, 100_000);
+        order.activate_at = 1_700_000_000;
+
+        let data = order.try_to_vec().unwrap();
+        let start = LimitOrder::PRE_ACTIVATE_AT_SPACE - 8;
+        assert_eq!(&data[start..start + 8], &1_700_000_000i64.to_le_bytes());
+        // execution_fee_bps = None is a single tag byte, so zero-filled migrated orders read as None
+        assert_eq!(data[start + 8], 0);
+
+        // Some(bps) fills both reserved bytes, ending where unwrap_output was appended
+        order.execution_fee_bps = Some(25);
+        let data = order.try_to_vec().unwrap();
+        assert_eq!(&data[start + 8..start + 10], &[1, 25]);
+        assert_eq!(start + 10, LimitOrder::PRE_UNWRAP_OUTPUT_SPACE - 8);
+    }
+
+    #[test]
+    fn test_zero_filled_unwrap_fields_read_as_token_delivery() {
+        let mut order = create_test_limit_order(TriggerType::TakeProfit, 1000, 100_000);
+        order.execution_fee_bps = Some(25);
+        let mut data = order.try_to_vec().unwrap();
+        data.truncate(LimitOrder::PRE_UNWRAP_OUTPUT_SPACE - 8);
+        data.resize(LimitOrder::SPACE - 8, 0);
+
+        let decoded = LimitOrder::try_from_slice(&data).unwrap();
+        assert!(!decoded.unwrap_output);
+        assert_eq!(decoded.user_lamport_destination, Pubkey::default());
+    }
+
+    #[test]
+    fn test_generation_is_last_and_zero_filled_for_migrated_orders() {
+        let mut order = create_test_limit_order(TriggerType::TakeProfit, 1000, 100_000);
+        order.execution_fee_bps = Some(25);
+        order.generation = 7;
+        let mut data = order.try_to_vec().unwrap();
+        assert_eq!(&data[LimitOrder::PRE_GENERATION_SPACE - 8..], &7u32.to_le_bytes());
+
+        data.truncate(LimitOrder::PRE_GENERATION_SPACE - 8);
+        data.resize(LimitOrder::SPACE - 8, 0);
+        assert_eq!(LimitOrder::try_from_slice(&data).unwrap().generation, 0);
+    }
+
+    #[test]
+    fn test_creator_order_counter_stamps_increasing_generations() {
+        let mut counter = CreatorOrderCounter { creator: Pubkey::new_unique(), orders_created: 0, bump: 0 };
+        assert_eq!(counter.next_generation().unwrap(), 0);
+        assert_eq!(counter.next_generation().unwrap(), 1);
+        assert_eq!(counter.orders_created, 2);
+        assert_eq!(counter.try_to_vec().unwrap().len() + 8, CreatorOrderCounter::SPACE);
+
+        counter.orders_created = u32::MAX;
+        assert_eq!(counter.next_generation().unwrap_err(), ErrorCode::InvalidCalculation.into());
+        assert_eq!(counter.orders_created, u32::MAX);
+    }
+
+    fn vault_rent_payer_account(payer: Pubkey) -> TestAccount {
+        sponsored_vault_rent_payer_account(payer, false)
+    }
+
+    fn sponsored_vault_rent_payer_account(payer: Pubkey, operator_sponsored: bool) -> TestAccount {
+        let vault = Pubkey::new_unique();
+        let mut data = Vec::new();
+        VaultRentPayer { vault, payer, bump: 0, operator_sponsored }.try_serialize(&mut data).unwrap();
+        assert_eq!(data.len(), VaultRentPayer::SPACE);
+        TestAccount::new(crate::ID, data)
+    }
+
+    fn operator_stats_account(operator: Pubkey, obligations_transferred_to: Pubkey) -> TestAccount {
+        let mut data = Vec::new();
+        OperatorStats {
+            operator,
+            last_active: 0,
+            bump: 0,
+            outstanding_obligations: 1,
+            obligations_transferred_to,
+        }
+        .try_serialize(&mut data)
+        .unwrap();
+        TestAccount::new(crate::ID, data)
+    }
+
+    #[test]
+    fn test_vault_rent_destination_without_record_pays_default() {
+        let mut record = empty_account();
+        let mut creator = empty_account();
+        let mut stranger = empty_account();
+        let creator_key = creator.key;
+        let record = record.to_account_info();
+        let creator = creator.to_account_info();
+        let stranger = stranger.to_account_info();
+
+        for rent_payer in [None, Some(&stranger)] {
+            let destination = vault_rent_destination(&record, rent_payer, None, &creator, &crate::ID).unwrap();
+            assert_eq!(destination.key(), creator_key);
+        }
+    }
+
+    #[test]
+    fn test_vault_rent_destination_requires_recorded_payer() {
+        let mut sponsor = empty_account();
+        let mut stranger = empty_account();
+        let mut creator = empty_account();
+        let mut record = vault_rent_payer_account(sponsor.key);
+        let sponsor_key = sponsor.key;
+        let record = record.to_account_info();
+        let sponsor = sponsor.to_account_info();
+        let stranger = stranger.to_account_info();
+        let creator = creator.to_account_info();
+
+        for rent_payer in [None, Some(&stranger), Some(&creator)] {
+            assert_eq!(
+                vault_rent_destination(&record, rent_payer, None, &creator, &crate::ID).unwrap_err(),
+                ErrorCode::InvalidRentPayer.into()
+            );
+        }
+        let destination = vault_rent_destination(&record, Some(&sponsor), None, &creator, &crate::ID).unwrap();
+        assert_eq!(destination.key(), sponsor_key);
+    }
+
+    #[test]
+    fn test_vault_rent_destination_requires_sponsoring_operator_stats() {
+        let mut operator = empty_account();
+        let mut creator = empty_account();
+        let mut record = sponsored_vault_rent_payer_account(operator.key, true);
+        let mut own_stats = operator_stats_account(operator.key, Pubkey::default());
+        let mut other_stats = operator_stats_account(Pubkey::new_unique(), Pubkey::default());
+        let operator_key = operator.key;
+        let record = record.to_account_info();
+        let operator = operator.to_account_info();
+        let creator = creator.to_account_info();
+        let own_stats = own_stats.to_account_info();
+        let other_stats = other_stats.to_account_info();
+
+        for rent_payer_stats in [None, Some(&other_stats), Some(&creator)] {
+            assert_eq!(
+                vault_rent_destination(&record, Some(&operator), rent_payer_stats, &creator, &crate::ID).unwrap_err(),
+                ErrorCode::InvalidRentPayer.into()
+            );
+        }
+        let destination = vault_rent_destination(&record, Some(&operator), Some(&own_stats), &creator, &crate::ID).unwrap();
+        assert_eq!(destination.key(), operator_key);
+    }
+
+    #[test]
+    fn test_vault_rent_destination_pays_treasury_after_forced_removal() {
+        let mut operator = empty_account();
+        let mut treasury = empty_account();
+        let mut creator = empty_account();
+        let mut record = sponsored_vault_rent_payer_account(operator.key, true);
+        let mut stats = operator_stats_account(operator.key, treasury.key);
+        let treasury_key = treasury.key;
+        let record = record.to_account_info();
+        let operator = operator.to_account_info();
+        let treasury = treasury.to_account_info();
+        let creator = creator.to_account_info();
+        let stats = stats.to_account_info();
+
+        assert_eq!(
+            vault_rent_destination(&record, Some(&operator), Some(&stats), &creator, &crate::ID).unwrap_err(),
+            ErrorCode::InvalidRentPayer.into()
+        );
+        let destination = vault_rent_destination(&record, Some(&treasury), Some(&stats), &creator, &crate::ID).unwrap();
+        assert_eq!(destination.key(), treasury_key);
+    }
+
+    fn unwrap_order() -> (LimitOrder, Pubkey, Pubkey) {
+        let mut order = create_test_limit_order(TriggerType::TakeProfit, 1000, 100_000);
+        order.output_mint = anchor_spl::token::spl_token::native_mint::ID;
+        order.unwrap_output = true;
+        order.user_lamport_destination = order.creator;
+        let order_key = Pubkey::new_unique();
+        let (unwrap_account, _) = Pubkey::find_program_address(&[b"unwrap_account", order_key.as_ref()], &crate::ID);
+        (order, order_key, unwrap_account)
+    }
+
+    #[test]
+    fn test_check_unwrap_accounts_accepts_order_pda_and_creator() {
+        let (order, order_key, unwrap_account) = unwrap_order();
+        let (_, bump) = Pubkey::find_program_address(&[b"unwrap_account", order_key.as_ref()], &crate::ID);
+
+        let result = check_unwrap_accounts(&order, &order_key, Some(&unwrap_account), Some(&order.creator), &crate::ID);
+        assert_eq!(result.unwrap(), bump);
+    }
+
+    #[test]
+    fn test_check_unwrap_accounts_rejects_missing_or_foreign_accounts() {
+        let (order, order_key, unwrap_account) = unwrap_order();
+        let stranger = Pubkey::new_unique();
+
+        for (unwrap, destination) in [
+            (None, Some(&order.creator)),
+            (Some(&unwrap_account), None),
+            (Some(&stranger), Some(&order.creator)),
+            (Some(&unwrap_account), Some(&stranger)),
+        ] {
+            let err = check_unwrap_accounts(&order, &order_key, unwrap, destination, &crate::ID).unwrap_err();
+            assert_eq!(err, ErrorCode::InvalidUnwrapAccount.into());
+        }
+
+        // Another order's unwrap account
+        let (other_unwrap_account, _) =
+            Pubkey::find_program_address(&[b"unwrap_account", Pubkey::new_unique().as_ref()], &crate::ID);
+        let err = check_unwrap_accounts(&order, &order_key, Some(&other_unwrap_account), Some(&order.creator), &crate::ID)
+            .unwrap_err();
+        assert_eq!(err, ErrorCode::InvalidUnwrapAccount.into());
+    }
+
+    #[test]
+    fn test_check_unwrap_accounts_requires_native_output() {
+        let (mut order, order_key, unwrap_account) = unwrap_order();
+        order.output_mint = Pubkey::new_unique();
+
+        let err = check_unwrap_accounts(&order, &order_key, Some(&unwrap_account), Some(&order.creator), &crate::ID)
+            .unwrap_err();
+        assert_eq!(err, ErrorCode::InvalidMint.into());
+    }
+
+    #[test]
+    fn test_execution_fee_bps_precedence() {
+        let mut order = create_test_limit_order(TriggerType::TakeProfit, 1000, 100_000);
+        let mut tiers = [FeeTier::default(); MAX_FEE_TIERS];
+        tiers[0] = FeeTier { max_input_amount: u64::MAX, fee_bps: 20 };
+        let schedule = FeeSchedule { mint: order.input_mint, tiers, tier_count: 1, bump: 0 };
+
+        // Operator value applies when there is no schedule or override
+        assert_eq!(order.execution_fee_bps(None, 50), 50);
+        // Schedule tier replaces the operator value
+        assert_eq!(order.execution_fee_bps(Some(&schedule), 50), 20);
+        // Creator override wins over both, including an explicit zero fee
+        order.execution_fee_bps = Some(5);
+        assert_eq!(order.execution_fee_bps(Some(&schedule), 50), 5);
+        order.execution_fee_bps = Some(0);
+        assert_eq!(order.execution_fee_bps(Some(&schedule), 50), 0);
+    }
+
+    #[test]
+    fn test_legacy_limit_order_keys_rejects_wrong_discriminator() {
+        let mut data = create_legacy_limit_order_data(Pubkey::new_unique(), Pubkey::new_unique());
+        data[0] ^= 0xff;
+
+        let err = legacy_limit_order_keys(&data).unwrap_err();
+        assert_eq!(err, ErrorCode::InvalidAccount.into());
+    }
+
+    fn registry_with_fee(order_creation_fee_lamports: u64, treasury: Pubkey) -> AdapterRegistry {
+        AdapterRegistry {
+            authority: Pubkey::new_unique(),
+            operators: vec![],
+            supported_adapters: vec![],
+            bump: 255,
+            memo_program_id: Pubkey::default(),
+            quote_signer: Pubkey::default(),
+            suspended_operators: vec![],
+            order_creation_fee_lamports,
+            treasury,
+            adapter_pools: vec![],
+            trusted_routers: vec![],
+            adapter_impact_caps: vec![],
+            order_price_band_bps: 0,
+            suppress_step_events: false,
+            enforce_quote_sanity: false,
+            quote_sanity_band_bps: 0,
+            reject_delegated_vaults: false,
+            pool_attestation_max_age_slots: 0,
+            paused_adapters: vec![],
+            sandboxed_adapters: vec![],
+            retired_adapters: vec![],
+            adapter_pool_counts: vec![],
+        }
+    }
+
+    // (creator, treasury, system_program); the creator holds the fixture balance
+    fn fee_accounts(treasury: Pubkey) -> &'static [AccountInfo<'static>] {
+        leak_account_infos(vec![
+            empty_account(),
+            empty_account().with_key(treasury),
+            program_account(System::id()),
+        ])
+    }
+
+    #[test]
+    fn test_collect_order_creation_fee_disabled() {
+        // Fee of 0 charges nothing and needs no treasury
+        let registry = registry_with_fee(0, Pubkey::default());
+        let accounts = fee_accounts(Pubkey::new_unique());
+
+        let fee = collect_order_creation_fee(&registry, &accounts[0], None, &accounts[2]).unwrap();
+        assert_eq!(fee, 0);
+        assert_eq!(accounts[0].lamports(), FIXTURE_LAMPORTS);
+    }
+
+    #[test]
+    fn test_collect_order_creation_fee_requires_treasury() {
+        let registry = registry_with_fee(1_000_000, Pubkey::new_unique());
+        let accounts = fee_accounts(registry.treasury);
+
+        let err = collect_order_creation_fee(&registry, &accounts[0], None, &accounts[2]).unwrap_err();
+        assert_eq!(err, ErrorCode::InvalidTreasury.into());
+    }
+
+    #[test]
+    fn test_collect_order_creation_fee_rejects_other_treasury() {
+        let registry = registry_with_fee(1_000_000, Pubkey::new_unique());
+        let accounts = fee_accounts(Pubkey::new_unique());
+
+        let err = collect_order_creation_fee(&registry, &accounts[0], Some(&accounts[1]), &accounts[2]).unwrap_err();
+        assert_eq!(err, ErrorCode::InvalidTreasury.into());
+    }
+
+    #[test]
+    fn test_collect_order_creation_fee_insufficient_lamports() {
+        let registry = registry_with_fee(FIXTURE_LAMPORTS + 1, Pubkey::new_unique());
+        let accounts = fee_accounts(registry.treasury);
+
+        let err = collect_order_creation_fee(&registry, &accounts[0], Some(&accounts[1]), &accounts[2]).unwrap_err();
+        assert_eq!(err, ErrorCode::InsufficientCreationFee.into());
+    }
+
+    // (recorded destination, creator ATA, creator, associated token program, operator,
+    // output mint, output token program, system program)
+    fn destination_accounts(
+        order: &LimitOrder,
+        recorded_destination: TestAccount,
+        creator_ata: Pubkey,
+    ) -> &'static [AccountInfo<'static>] {
+        let token_program = anchor_spl::token::ID;
+        leak_account_infos(vec![
+            recorded_destination,
+            empty_account().with_key(creator_ata),
+            empty_account().with_key(order.creator),
+            program_account(anchor_spl::associated_token::ID),
+            empty_account(),
+            mint_account(6, token_program).with_key(order.output_mint),
+            program_account(token_program),
+            program_account(System::id()),
+        ])
+    }
+
+    fn resolve(accounts: &'static [AccountInfo<'static>], order: &LimitOrder, with_ata: bool) -> Result<(AccountInfo<'static>, u64)> {
+        let fallback = |index: usize| if with_ata { Some(&accounts[index]) } else { None };
+        resolve_user_destination(
+            &accounts[0],
+            order,
+            fallback(1),
+            fallback(2),
+            fallback(3),
+            &accounts[4],
+            &accounts[5],
+            &accounts[6],
+            &accounts[7],
+        )
+    }
+
+    #[test]
+    fn test_resolve_user_destination_uses_recorded_account() {
+        let order = create_test_limit_order(TriggerType::TakeProfit, 1000, 1000);
+        let recorded = token_account(order.output_mint, order.creator, 0, anchor_spl::token::ID);
+        let accounts = destination_accounts(&order, recorded, Pubkey::new_unique());
+
+        let (destination, created_rent) = resolve(accounts, &order, false).unwrap();
+        assert_eq!(destination.key(), accounts[0].key());
+        assert_eq!(created_rent, 0);
+    }
+
+    #[test]
+    fn test_resolve_user_destination_rejects_foreign_recorded_account() {
+        let order = create_test_limit_order(TriggerType::TakeProfit, 1000, 1000);
+        let recorded = token_account(order.output_mint, Pubkey::new_unique(), 0, anchor_spl::token::ID);
+        let accounts = destination_accounts(&order, recorded, Pubkey::new_unique());
+
+        let err = resolve(accounts, &order, false).unwrap_err();
+        assert_eq!(err, ErrorCode::InvalidAccount.into());
+
+        let recorded = token_account(Pubkey::new_unique(), order.creator, 0, anchor_spl::token::ID);
+        let accounts = destination_accounts(&order, recorded, Pubkey::new_unique());
+
+        let err = resolve(accounts, &order, false).unwrap_err();
+        assert_eq!(err, ErrorCode::InvalidMint.into());
+    }
+
+    #[test]
+    fn test_resolve_user_destination_closed_requires_creator_ata() {
+        let order = create_test_limit_order(TriggerType::TakeProfit, 1000, 1000);
+        let accounts = destination_accounts(&order, empty_account(), Pubkey::new_unique());
+
+        let err = resolve(accounts, &order, false).unwrap_err();
+        assert_eq!(err, ErrorCode::MissingDestinationAccount.into());
+    }
+
+    #[test]
+    fn test_resolve_user_destination_closed_rejects_non_ata() {
+        // Only the creator's canonical ATA for the output mint is accepted as a fallback
+        let order = create_test_limit_order(TriggerType::TakeProfit, 1000, 1000);
+        let accounts = destination_accounts(&order, empty_account(), Pubkey::new_unique());
+
+        let err = resolve(accounts, &order, true).unwrap_err();
+        assert_eq!(err, ErrorCode::InvalidDestinationAccount.into());
+    }
+
+    #[test]
+    fn test_refund_destination_rent_moves_lamports_from_order_to_operator() {
+        let accounts = leak_account_infos(vec![empty_account(), empty_account()]);
+        let (limit_order, operator) = (&accounts[0], &accounts[1]);
+
+        refund_destination_rent(limit_order, operator, 2_039_280).unwrap();
+        assert_eq!(limit_order.lamports(), FIXTURE_LAMPORTS - 2_039_280);
+        assert_eq!(operator.lamports(), FIXTURE_LAMPORTS + 2_039_280);
+
+        // Nothing was created, nothing moves
+        refund_destination_rent(limit_order, operator, 0).unwrap();
+        assert_eq!(operator.lamports(), FIXTURE_LAMPORTS + 2_039_280);
+
+        // The order cannot refund more than it holds
+        assert!(refund_destination_rent(limit_order, operator, FIXTURE_LAMPORTS).is_err());
+    }
+
+    // Token account of the order's output mint and creator, frozen by the mint's freeze authority
+    fn frozen_destination(order: &LimitOrder) -> TestAccount {
+        use anchor_lang::solana_program::program_pack::Pack;
+        use anchor_spl::token::spl_token::state::{Account, AccountState};
+
+        let mut account = token_account(order.output_mint, order.creator, 0, anchor_spl::token::ID);
+        let mut state = Account::unpack(&account.data).unwrap();
+        state.state = AccountState::Frozen;
+        Account::pack(state, &mut account.data).unwrap();
+        account
+    }
+
+    #[test]
+    fn test_require_destination_not_frozen_checks_recorded_account() {
+        let mut order = create_test_limit_order(TriggerType::TakeProfit, 1000, 1000);
+        let accounts = leak_account_infos(vec![
+            token_account(order.output_mint, order.creator, 0, anchor_spl::token::ID),
+            frozen_destination(&order),
+        ]);
+
+        assert!(require_destination_not_frozen(&order, &accounts[0], None).is_ok());
+        let err = require_destination_not_frozen(&order, &accounts[1], None).unwrap_err();
+        assert_eq!(err, ErrorCode::DestinationFrozen.into());
+        // The recorded destination is what receives the output, whatever the creator ATA holds
+        assert!(require_destination_not_frozen(&order, &accounts[0], Some(&accounts[1])).is_ok());
+
+        // Unwrapped output is paid in lamports and never touches the token account
+        order.unwrap_output = true;
+        assert!(require_destination_not_frozen(&order, &accounts[1], None).is_ok());
+    }
+
+    #[test]
+    fn test_require_destination_not_frozen_checks_creator_ata_once_recorded_is_closed() {
+        let order = create_test_limit_order(TriggerType::TakeProfit, 1000, 1000);
+        let accounts = leak_account_infos(vec![
+            empty_account(),
+            frozen_destination(&order),
+            empty_account(),
+        ]);
+
+        let err = require_destination_not_frozen(&order, &accounts[0], Some(&accounts[1])).unwrap_err();
+        assert_eq!(err, ErrorCode::DestinationFrozen.into());
+        // A creator ATA the fill still has to create cannot be frozen
+        assert!(require_destination_not_frozen(&order, &accounts[0], Some(&accounts[2])).is_ok());
+        assert!(require_destination_not_frozen(&order, &accounts[0], None).is_ok());
+    }
+
+    // Token-2022 account with a TransferFeeAmount extension holding `withheld` fees
+    fn token_2022_vault_data(amount: u64, withheld: u64) -> Vec<u8> {
+        use anchor_spl::token_2022::spl_token_2022::extension::{BaseStateWithExtensionsMut, ExtensionType, StateWithExtensionsMut};
+        use anchor_spl::token_2022::spl_token_2022::state::AccountState;
+
+        let space = ExtensionType::try_calculate_account_len::<Token2022Account>(&[ExtensionType::TransferFeeAmount]).unwrap();
+        let mut data = vec![0u8; space];
+        let mut vault = StateWithExtensionsMut::<Token2022Account>::unpack_uninitialized(&mut data).unwrap();
+        vault.base = Token2022Account {
+            mint: Pubkey::new_unique(),
+            owner: Pubkey::new_unique(),
+            amount,
+            state: AccountState::Initialized,
+            ..Default::default()
+        };
+        vault.pack_base();
+        vault.init_account_type().unwrap();
+        vault.init_extension::<TransferFeeAmount>(true).unwrap().withheld_amount = withheld.into();
+        data
+    }
+
+    #[test]
+    fn test_input_vault_closable_when_empty() {
+        let vault = token_account(Pubkey::new_unique(), Pubkey::new_unique(), 0, anchor_spl::token::ID);
+        assert!(input_vault_closable(&vault.data).unwrap());
+        assert!(input_vault_closable(&token_2022_vault_data(0, 0)).unwrap());
+    }
+
+    #[test]
+    fn test_input_vault_not_closable_with_dust() {
+        let vault = token_account(Pubkey::new_unique(), Pubkey::new_unique(), 1, anchor_spl::token::ID);
+        assert!(!input_vault_closable(&vault.data).unwrap());
+        assert!(!input_vault_closable(&token_2022_vault_data(1, 0)).unwrap());
+    }
+
+    #[test]
+    fn test_input_vault_not_closable_with_withheld_fees() {
+        // The deposit's transfer fee is withheld in the order vault itself
+        assert!(!input_vault_closable(&token_2022_vault_data(0, 5)).unwrap());
+    }
+
+    // Runs validate_execution_prelude for an order against the given route accounts, at `now`
+    fn run_execution_prelude(
+        order: LimitOrder,
+        route_plan: &[RoutePlanStep],
+        remaining_accounts: &'static [AccountInfo<'static>],
+        quoted_out_amount: u64,
+        now: i64,
+    ) -> Result<Pubkey> {
+        let mut order_data = Vec::new();
+        order.try_serialize(&mut order_data).unwrap();
+        let mut registry_data = Vec::new();
+        registry_with_fee(0, Pubkey::default()).try_serialize(&mut registry_data).unwrap();
+        let accounts = leak_account_infos(vec![
+            TestAccount::new(crate::ID, order_data),
+            TestAccount::new(crate::ID, registry_data),
+            empty_account(),
+            program_account(anchor_spl::token::ID),
+            mint_account(6, anchor_spl::token::ID),
+            mint_account(6, anchor_spl::token::ID),
+        ]);
+        let limit_order = Account::<LimitOrder>::try_from(&accounts[0]).unwrap();
+        let registry = Account::<AdapterRegistry>::try_from(&accounts[1]).unwrap();
+
+        validate_execution_prelude(
+            &limit_order,
+            &registry,
+            &accounts[2],
+            &limit_order.input_vault,
+            &accounts[3],
+            &accounts[3],
+            &accounts[4],
+            &accounts[5],
+            route_plan,
+            remaining_accounts,
+            &crate::ID,
+            quoted_out_amount,
+            now,
+        )
+        .map(|destination_vault| destination_vault.key())
+    }
+
+    fn open_order(input_vault: Pubkey) -> LimitOrder {
+        // Take profit at +5% over 1_000_000: quotes of 1_050_000 and up trigger
+        let mut order = create_test_limit_order(TriggerType::TakeProfit, 500, 1_000_000);
+        order.input_vault = input_vault;
+        order.expiry = 2_000;
+        order.activate_at = 1_000;
+        order
+    }
+
+    fn raydium_step(input_index: u8) -> RoutePlanStep {
+        RoutePlanStep { swap: Swap::Raydium, percent: 100, input_index, output_index: input_index + 13 }
+    }
+
+    #[test]
+    fn test_execution_prelude_checks_timing_and_trigger() {
+        let input_vault = Pubkey::new_unique();
+        let remaining = leak_account_infos(vec![empty_account().with_key(input_vault)]);
+        let plan = [raydium_step(0)];
+
+        let err = run_execution_prelude(open_order(input_vault), &plan, remaining, 1_050_000, 2_000).unwrap_err();
+        assert_eq!(err, ErrorCode::OrderExpired.into());
+
+        let err = run_execution_prelude(open_order(input_vault), &plan, remaining, 1_050_000, 999).unwrap_err();
+        assert_eq!(err, ErrorCode::OrderNotActiveYet.into());
+
+        let err = run_execution_prelude(open_order(input_vault), &plan, remaining, 1_049_999, 1_500).unwrap_err();
+        assert_eq!(err, ErrorCode::TriggerPriceNotMet.into());
+    }
+
+    #[test]
+    fn test_execution_prelude_requires_order_vault_as_route_input() {
+        let input_vault = Pubkey::new_unique();
+        let remaining = leak_account_infos(vec![
+            empty_account().with_key(input_vault),
+            empty_account(), // another order's vault
+        ]);
+
+        let err = run_execution_prelude(open_order(input_vault), &[], remaining, 1_050_000, 1_500).unwrap_err();
+        assert_eq!(err, ErrorCode::EmptyRoute.into());
+
+        let err = run_execution_prelude(open_order(input_vault), &[raydium_step(2)], remaining, 1_050_000, 1_500).unwrap_err();
+        assert_eq!(err, ErrorCode::InvalidAccountIndex.into());
+
+        let err = run_execution_prelude(open_order(input_vault), &[raydium_step(1)], remaining, 1_050_000, 1_500).unwrap_err();
+        assert_eq!(err, ErrorCode::InvalidVaultAddress.into());
+    }
+
+    #[test]
+    fn test_order_mints_must_differ() {
+        let (input_mint, output_mint) = (Pubkey::new_unique(), Pubkey::new_unique());
+        assert!(require_distinct_order_mints(&input_mint, &output_mint).is_ok());
+
+        // A parking order (same mint on both sides) has no route to execute it
+        let err = require_distinct_order_mints(&input_mint, &input_mint).unwrap_err();
+        assert_eq!(err, ErrorCode::IdenticalMints.into());
+    }
+
+    #[test]
+    fn test_trigger_price_caps_follow_trigger_type() {
+        let limits = DEFAULT_PROTOCOL_LIMITS;
+        for trigger_type in [TriggerType::StopLoss, TriggerType::TakeProfit] {
+            let err = validate_trigger_price(&limits, trigger_type, 0).unwrap_err();
+            assert_eq!(err, ErrorCode::InvalidTriggerPrice.into());
+        }
+
+        assert!(validate_trigger_price(&limits, TriggerType::StopLoss, 10_000).is_ok());
+        let err = validate_trigger_price(&limits, TriggerType::StopLoss, 10_001).unwrap_err();
+        assert_eq!(err, ErrorCode::InvalidTriggerPrice.into());
+
+        assert!(validate_trigger_price(&limits, TriggerType::TakeProfit, 100_000).is_ok());
+        let err = validate_trigger_price(&limits, TriggerType::TakeProfit, 100_001).unwrap_err();
+        assert_eq!(err, ErrorCode::InvalidTriggerPrice.into());
+
+        // A tightened config applies to new orders of either type
+        let limits = ProtocolLimits {
+            max_stop_loss_trigger_bps: 2_000,
+            max_take_profit_trigger_bps: 5_000,
+            ..DEFAULT_PROTOCOL_LIMITS
+        };
+        assert!(validate_trigger_price(&limits, TriggerType::StopLoss, 2_001).is_err());
+        assert!(validate_trigger_price(&limits, TriggerType::TakeProfit, 5_001).is_err());
+    }
+
+    #[test]
+    fn test_open_interest_changes_cancel_out_per_order() {
+        let mut order = create_test_limit_order(TriggerType::TakeProfit, 1000, 1000);
+        order.input_amount = 1_500_000;
+
+        let opened = order.open_interest_change(true);
+        let released = order.open_interest_change(false);
+        assert_eq!((opened.input_mint, opened.output_mint), (order.input_mint, order.output_mint));
+        assert_eq!((opened.delta_input_amount, opened.open_orders_delta), (1_500_000, 1));
+        assert_eq!((released.delta_input_amount, released.open_orders_delta), (-1_500_000, -1));
+        assert_eq!(opened.delta_input_amount + released.delta_input_amount, 0);
+
+        // Amounts up to u64::MAX stay exact in the signed i128 delta
+        order.input_amount = u64::MAX;
+        assert_eq!(order.open_interest_change(false).delta_input_amount, -(u64::MAX as i128));
+    }
+
+    #[test]
+    fn test_open_interest_changed_encoding_fixture() {
+        let mut order = create_test_limit_order(TriggerType::TakeProfit, 1000, 1000);
+        order.input_amount = 1_500_000;
+        let event = order.open_interest_change(false);
+
+        // Event data: discriminator | input_mint | output_mint | i128 LE delta | i32 LE count
+        let data = anchor_lang::Event::data(&event);
+        assert_eq!(data.len(), 8 + 32 + 32 + 16 + 4);
+        assert_eq!(&data[..8], OpenInterestChanged::DISCRIMINATOR);
+        assert_eq!(&data[8..40], order.input_mint.as_ref());
+        assert_eq!(&data[40..72], order.output_mint.as_ref());
+        assert_eq!(
+            &data[72..88],
+            &[0xa0, 0x1c, 0xe9, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff]
+        );
+        assert_eq!(&data[88..], &[0xff, 0xff, 0xff, 0xff]);
+
+        let decoded = OpenInterestChanged::try_from_slice(&data[8..]).unwrap();
+        assert_eq!(decoded.delta_input_amount, -1_500_000);
+        assert_eq!(decoded.open_orders_delta, -1);
+    }
+
+    #[test]
+    fn test_replacement_amounts_increase_tops_up_from_creator() {
+        // Everything in the old vault moves over, the creator adds the rest
+        assert_eq!(replacement_amounts(1_000_000, 1_500_000), (1_000_000, 500_000, 0));
+    }
+
+    #[test]
+    fn test_replacement_amounts_decrease_refunds_difference() {
+        assert_eq!(replacement_amounts(1_500_000, 1_000_000), (1_000_000, 0, 500_000));
+        // Same size only moves tokens between the vaults
+        assert_eq!(replacement_amounts(1_000_000, 1_000_000), (1_000_000, 0, 0));
+    }
+
+    #[test]
+    fn test_replacement_amounts_conserve_both_sides() {
+        for (old_amount, new_amount) in [(1, u64::MAX), (u64::MAX, 1), (7, 3), (3, 7)] {
+            let (carried, top_up, refund) = replacement_amounts(old_amount, new_amount);
+            assert_eq!(carried + refund, old_amount);
+            assert_eq!(carried + top_up, new_amount);
+            assert!(top_up == 0 || refund == 0);
+        }
+    }
 }
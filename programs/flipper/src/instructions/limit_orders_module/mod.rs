@@ -6,10 +6,30 @@ use anchor_spl::token_interface::{
     initialize_account3, InitializeAccount3
 };
 use anchor_spl::token_2022::ID as TOKEN_2022_PROGRAM_ID;
+use anchor_spl::token_2022::spl_token_2022::{
+    extension::{
+        confidential_transfer::ConfidentialTransferAccount, transfer_fee::TransferFeeAmount,
+        BaseStateWithExtensions, StateWithExtensions,
+    },
+    state::Account as Token2022Account,
+};
+use anchor_spl::associated_token::{
+    AssociatedToken, Create, create_idempotent, get_associated_token_address_with_program_id
+};
+use anchor_lang::solana_program::ed25519_program;
+use anchor_lang::solana_program::sysvar::instructions::{load_current_index_checked, load_instruction_at_checked};
 use crate::adapters::adapter_connector_module::AdapterContext;
 use crate::errors::ErrorCode;
+#[cfg(feature = "invariant-checks")]
+use crate::invariants;
+#[cfg(feature = "telemetry")]
+use crate::telemetry;
+#[cfg(feature = "test-clock")]
+use crate::instructions::test_clock_module;
+use crate::math;
 use crate::state::*;
 use crate::instructions::route_validator_module;
+use crate::instructions::swap_processor_module::resolve_in_amount;
 use crate::instructions::route_executor_module;
 use crate::instructions::vault_manager_module::{VaultAuthority, get_vault_authority_address};
 
@@ -28,7 +48,7 @@ pub enum TriggerType {
 }
 
 /// Order execution status
-/// Order: Open = 0, Filled = 1, Cancelled = 2, Init = 3
+/// Order: Open = 0, Filled = 1, Cancelled = 2, Init = 3, NeedsReview = 4
 /// This order is maintained for backward compatibility with existing orders
 #[repr(u8)]
 #[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug, PartialEq, Eq)]
@@ -41,6 +61,9 @@ pub enum OrderStatus {
     Cancelled = 2,
     /// Order is being initialized (before create_limit_order)
     Init = 3,
+    /// Flagged by an operator after repeated zero-output executions;
+    /// paused until the creator reopens or cancels it
+    NeedsReview = 4,
 }
 
 /// Limit order with trigger price mechanism
@@ -61,7 +84,7 @@ pub struct LimitOrder {
     /// Minimum output amount (baseline for trigger calculation)
     pub min_output_amount: u64,
     /// Trigger price deviation from min_output_amount in basis points (1000 = 10%)
-    /// Supports up to 1000% (100,000 bps) for TakeProfit orders
+    /// Capped per trigger type by the ProtocolConfig (by default 100% for StopLoss, 1000% for TakeProfit)
     pub trigger_price_bps: u32,
     /// Type of trigger (TakeProfit or StopLoss)
     pub trigger_type: TriggerType,
@@ -73,11 +96,128 @@ pub struct LimitOrder {
     pub slippage_bps: u16,
     /// PDA bump seed
     pub bump: u8,
+    /// Token program of the input mint, snapshotted at creation
+    pub input_token_program: Pubkey,
+    /// Token program of the output mint, snapshotted at creation (default until create_limit_order)
+    pub output_token_program: Pubkey,
+    /// Executions whose route delivered no output without spending the input; reset when reopened
+    pub failed_attempts: u8,
+    /// Earliest execution timestamp (0 = executable immediately)
+    pub activate_at: i64,
+    /// Creator override of the execution fee in bps; None follows the input mint's FeeSchedule
+    pub execution_fee_bps: Option<u8>,
+    /// Deliver a WSOL output as native SOL to user_lamport_destination instead of the token account
+    pub unwrap_output: bool,
+    /// Creator's system account receiving the unwrapped output (default unless unwrap_output)
+    pub user_lamport_destination: Pubkey,
+    /// Creator's CreatorOrderCounter value at init_limit_order (0 for migrated orders)
+    pub generation: u32,
+}
+
+/// Current unix timestamp for the order time checks (expiry, activation, receipts)
+///
+/// Builds with the "test-clock" feature read a MockClock passed in remaining_accounts
+/// instead of the Clock sysvar, so tests can move order time without warping the validator.
+#[cfg(not(feature = "test-clock"))]
+pub fn current_unix_timestamp(_remaining_accounts: &[AccountInfo]) -> Result<i64> {
+    Ok(Clock::get()?.unix_timestamp)
+}
+
+#[cfg(feature = "test-clock")]
+pub fn current_unix_timestamp(remaining_accounts: &[AccountInfo]) -> Result<i64> {
+    match test_clock_module::mock_unix_timestamp(remaining_accounts)? {
+        Some(unix_timestamp) => Ok(unix_timestamp),
+        None => Ok(Clock::get()?.unix_timestamp),
+    }
+}
+
+/// Failed executions after which the registry authority may flag an order NeedsReview
+pub const MAX_FAILED_EXECUTION_ATTEMPTS: u8 = 3;
+
+/// Checks a trigger deviation against the protocol cap of its trigger type
+///
+/// StopLoss caps never exceed MAX_STOP_LOSS_TRIGGER_BPS, so 10_000 - trigger_price_bps cannot
+/// underflow in should_execute.
+pub fn validate_trigger_price(limits: &ProtocolLimits, trigger_type: TriggerType, trigger_price_bps: u32) -> Result<()> {
+    let max_trigger_bps = match trigger_type {
+        TriggerType::StopLoss => limits.max_stop_loss_trigger_bps,
+        TriggerType::TakeProfit => limits.max_take_profit_trigger_bps,
+    };
+    require!(
+        trigger_price_bps > 0 && trigger_price_bps <= max_trigger_bps,
+        ErrorCode::InvalidTriggerPrice
+    );
+    Ok(())
+}
+
+/// Rejects orders whose input and output mints are the same ("parking" orders).
+///
+/// No route swaps a mint into itself, and the destination vault scan would find the order's own
+/// input vault, so such an order could only sit until it expires or is cancelled.
+pub fn require_distinct_order_mints(input_mint: &Pubkey, output_mint: &Pubkey) -> Result<()> {
+    require_keys_neq!(*input_mint, *output_mint, ErrorCode::IdenticalMints);
+    Ok(())
 }
 
 impl LimitOrder {
 
-    pub const SPACE: usize = 8 + 193; // Updated: trigger_price_bps changed from u16 (2 bytes) to u32 (4 bytes) = +2 bytes
+    /// Account size, summed field by field (8 + 305); covered by a serialization size test
+    pub const SPACE: usize = 8 // discriminator
+        + 32 * 5 // creator, input_mint, output_mint, input_vault, user_destination_account
+        + 8 // input_amount
+        + 8 // min_output_amount
+        + 4 // trigger_price_bps
+        + 1 // trigger_type
+        + 8 // expiry
+        + 1 // status
+        + 2 // slippage_bps
+        + 1 // bump
+        + 32 * 2 // input_token_program, output_token_program
+        + 1 // failed_attempts
+        + 8 // activate_at
+        + 1 + 1 // execution_fee_bps
+        + 1 // unwrap_output
+        + 32 // user_lamport_destination
+        + 4; // generation
+
+    /// Size of orders created before the token programs were stored; migrate_limit_order grows them to SPACE
+    pub const LEGACY_SPACE: usize = Self::SPACE - 32 * 2 - 1 - 8 - 2 - 33 - 4;
+
+    /// Size of orders created before failed_attempts was stored; migrate_limit_order grows them to SPACE
+    pub const PRE_FAILED_ATTEMPTS_SPACE: usize = Self::SPACE - 1 - 8 - 2 - 33 - 4;
+
+    /// Size of orders created before activate_at was stored; migrate_limit_order grows them to SPACE
+    pub const PRE_ACTIVATE_AT_SPACE: usize = Self::SPACE - 8 - 2 - 33 - 4;
+
+    /// Size of orders created before execution_fee_bps was stored; migrate_limit_order grows them to SPACE
+    pub const PRE_EXECUTION_FEE_SPACE: usize = Self::SPACE - 2 - 33 - 4;
+
+    /// Size of orders created before unwrap_output was stored; migrate_limit_order grows them to SPACE
+    pub const PRE_UNWRAP_OUTPUT_SPACE: usize = Self::SPACE - 33 - 4;
+
+    /// Size of orders created before generation was stored; migrate_limit_order grows them to SPACE
+    pub const PRE_GENERATION_SPACE: usize = Self::SPACE - 4;
+
+    /// Resolves the execution fee: the creator's override, else the fee schedule tier
+    /// covering input_amount, else the operator-supplied fee
+    ///
+    /// # Arguments
+    /// * `fee_schedule` - The input mint's FeeSchedule, if one has been set
+    /// * `operator_fee_bps` - platform_fee_bps passed to the execute instruction
+    pub fn execution_fee_bps(&self, fee_schedule: Option<&FeeSchedule>, operator_fee_bps: u8) -> u8 {
+        self.execution_fee_bps
+            .or_else(|| fee_schedule.and_then(|schedule| schedule.fee_bps_for(self.input_amount)))
+            .unwrap_or(operator_fee_bps)
+    }
+
+    /// Checks if the order's execution window has opened
+    ///
+    /// # Arguments
+    /// * `now` - Current unix timestamp
+    pub fn is_active(&self, now: i64) -> bool {
+        now >= self.activate_at
+    }
+
     /// Checks if order should be executed based on current price
     ///
     /// # Arguments
@@ -117,17 +257,276 @@ impl LimitOrder {
     }
 
     pub fn calculate_min_acceptable_output(&self, quoted_amount: u64) -> Result<u64> {
-        let min_output = (quoted_amount as u128)
-            .checked_mul(
-                (10_000u128)
-                    .checked_sub(self.slippage_bps as u128)
-                    .ok_or(ErrorCode::InvalidCalculation)?
-            )
-            .ok_or(ErrorCode::InvalidCalculation)?
-            .checked_div(10_000)
-            .ok_or(ErrorCode::InvalidCalculation)? as u64;
-        Ok(min_output)
+        math::apply_bps_discount(quoted_amount, self.slippage_bps)
+    }
+
+    /// Calculates the output at which the trigger fires:
+    /// min_output_amount * (1 + trigger_price_bps/10000) for TakeProfit,
+    /// min_output_amount * (1 - trigger_price_bps/10000) for StopLoss
+    pub fn calculate_trigger_threshold(&self) -> Result<u64> {
+        match self.trigger_type {
+            TriggerType::TakeProfit => math::apply_bps_premium(self.min_output_amount, self.trigger_price_bps),
+            TriggerType::StopLoss => {
+                let trigger_price_bps = u16::try_from(self.trigger_price_bps)
+                    .map_err(|_| ErrorCode::InvalidCalculation)?;
+                math::apply_bps_discount(self.min_output_amount, trigger_price_bps)
+            }
+        }
+    }
+
+    /// Calculates how much better (positive) or worse (negative) the realized output is than
+    /// the trigger threshold, in basis points. StopLoss orders fire at or below their threshold,
+    /// so their premium is usually negative; a zero threshold reports 0.
+    ///
+    /// # Arguments
+    /// * `output_amount` - Output amount delivered to the creator (after fees)
+    pub fn calculate_execution_premium_bps(&self, output_amount: u64) -> Result<i64> {
+        Ok(math::signed_bps_difference(output_amount, self.calculate_trigger_threshold()?))
+    }
+
+    /// Calculates the realized execution price as output per input unit,
+    /// scaled by EFFECTIVE_PRICE_SCALE
+    ///
+    /// The price is only reported in the fill event, so a price too large for u64 (a tiny
+    /// input filled for a huge output) saturates at u64::MAX rather than failing the fill.
+    ///
+    /// # Arguments
+    /// * `output_amount` - Output amount delivered to the creator (after fees)
+    pub fn calculate_effective_price(&self, output_amount: u64) -> Result<u64> {
+        // u64 * 1e9 always fits in u128
+        let price = (output_amount as u128 * EFFECTIVE_PRICE_SCALE as u128)
+            .checked_div(self.input_amount as u128)
+            .ok_or(ErrorCode::InvalidCalculation)?;
+        Ok(u64::try_from(price).unwrap_or(u64::MAX))
+    }
+
+    /// Open interest change of this order being funded (`opened`) or released, from its
+    /// stored input_amount
+    pub fn open_interest_change(&self, opened: bool) -> OpenInterestChanged {
+        let (delta_input_amount, open_orders_delta) = if opened {
+            (self.input_amount as i128, 1)
+        } else {
+            (-(self.input_amount as i128), -1)
+        };
+        OpenInterestChanged {
+            input_mint: self.input_mint,
+            output_mint: self.output_mint,
+            delta_input_amount,
+            open_orders_delta,
+        }
+    }
+}
+
+/// Optional on-chain record of a limit order's outcome
+/// PDA: ["receipt", limit_order]. Paid for by the creator at init_limit_order and
+/// outlives the order account, so wallets without CPI event log access can still
+/// see what happened. Only the creator can close it and reclaim the rent.
+#[account]
+pub struct OrderReceipt {
+    /// Limit order this receipt belongs to
+    pub order: Pubkey,
+    /// Order creator (receives rent when the receipt is closed)
+    pub creator: Pubkey,
+    /// Final order status (Init/Open until the order is executed or cancelled)
+    pub status: OrderStatus,
+    /// Output amount delivered to the creator (0 unless Filled)
+    pub output_amount: u64,
+    /// Platform fee collected on execution (0 unless Filled)
+    pub fee_amount: u64,
+    /// Operator who executed or expired the order, or creator for self-cancel
+    pub executor: Pubkey,
+    /// Unix timestamp of the final status change (0 until then)
+    pub timestamp: i64,
+    /// PDA bump seed
+    pub bump: u8,
+    /// Order was filled but its input vault could not be closed yet;
+    /// close_limit_order_by_operator finishes the cleanup
+    pub vault_close_pending: bool,
+}
+
+impl OrderReceipt {
+    pub const SPACE: usize = 8 + 32 + 32 + 1 + 8 + 8 + 32 + 8 + 1 + 1;
+
+    /// Records the final outcome of the order
+    ///
+    /// # Arguments
+    /// * `status` - Final order status (Filled or Cancelled)
+    /// * `output_amount` - Output delivered to the creator
+    /// * `fee_amount` - Platform fee collected
+    /// * `executor` - Account that finalized the order
+    /// * `timestamp` - Unix timestamp of finalization
+    pub fn record(
+        &mut self,
+        status: OrderStatus,
+        output_amount: u64,
+        fee_amount: u64,
+        executor: Pubkey,
+        timestamp: i64,
+    ) {
+        self.status = status;
+        self.output_amount = output_amount;
+        self.fee_amount = fee_amount;
+        self.executor = executor;
+        self.timestamp = timestamp;
+    }
+
+    /// Returns true once the order reached a final status
+    pub fn is_final(&self) -> bool {
+        self.status == OrderStatus::Filled || self.status == OrderStatus::Cancelled
+    }
+
+    /// Reads the receipt passed as the ["receipt", limit_order] PDA, if the order has one
+    pub fn load(order_receipt: &AccountInfo, program_id: &Pubkey) -> Result<Option<OrderReceipt>> {
+        if order_receipt.owner != program_id || order_receipt.data_is_empty() {
+            return Ok(None);
+        }
+        let data = order_receipt.try_borrow_data()?;
+        Ok(Some(OrderReceipt::try_deserialize(&mut &data[..])?))
+    }
+
+    /// Applies `update` to the order's receipt, if it has one, and writes it back
+    ///
+    /// Terminal paths always pass the receipt PDA, so an order created with a receipt
+    /// cannot be finalized without it being recorded.
+    pub fn update(
+        order_receipt: &AccountInfo,
+        program_id: &Pubkey,
+        update: impl FnOnce(&mut OrderReceipt),
+    ) -> Result<()> {
+        let Some(mut receipt) = Self::load(order_receipt, program_id)? else {
+            return Ok(());
+        };
+        update(&mut receipt);
+        receipt.try_serialize(&mut &mut order_receipt.try_borrow_mut_data()?[..])?;
+        Ok(())
+    }
+}
+
+/// Per-creator count of orders ever initialized, PDA ["order_counter", creator]
+///
+/// Order addresses only depend on (creator, nonce), so a nonce reused after its order was
+/// closed recreates the same address. Each order is stamped with the counter value at
+/// init_limit_order, which tells the two lives of such an address apart.
+#[account]
+pub struct CreatorOrderCounter {
+    /// Creator the counter belongs to
+    pub creator: Pubkey,
+    /// Orders initialized so far; the generation of the next order
+    pub orders_created: u32,
+    /// PDA bump seed
+    pub bump: u8,
+}
+
+impl CreatorOrderCounter {
+    pub const SPACE: usize = 8 + 32 + 4 + 1;
+
+    /// Returns the generation for a new order and advances the counter
+    pub fn next_generation(&mut self) -> Result<u32> {
+        let generation = self.orders_created;
+        self.orders_created = generation.checked_add(1).ok_or(ErrorCode::InvalidCalculation)?;
+        Ok(generation)
+    }
+}
+
+/// Payer of an order vault funded by someone other than the order creator,
+/// PDA ["vault_rent_payer", input_vault]
+///
+/// Created by init_limit_order when the payer is not the creator. Whichever path closes the
+/// vault refunds its rent, and the rent of this record, to the recorded payer instead of the
+/// creator or operator it would otherwise pay. A registered operator's sponsorship is counted
+/// in its OperatorStats until then, see remove_operator.
+#[account]
+pub struct VaultRentPayer {
+    /// Order vault the record belongs to
+    pub vault: Pubkey,
+    /// Account that funded the vault rent
+    pub payer: Pubkey,
+    /// PDA bump seed
+    pub bump: u8,
+    /// The payer was a registered operator, whose OperatorStats counts the vault
+    pub operator_sponsored: bool,
+}
+
+impl VaultRentPayer {
+    pub const SPACE: usize = 8 + 32 + 32 + 1 + 1;
+
+    /// Reads the record passed as the ["vault_rent_payer", input_vault] PDA, if the vault has one
+    pub fn load(vault_rent_payer: &AccountInfo, program_id: &Pubkey) -> Result<Option<VaultRentPayer>> {
+        if vault_rent_payer.owner != program_id || vault_rent_payer.data_is_empty() {
+            return Ok(None);
+        }
+        let data = vault_rent_payer.try_borrow_data()?;
+        Ok(Some(VaultRentPayer::try_deserialize(&mut &data[..])?))
+    }
+}
+
+/// Returns the account an order vault's rent is refunded to on close
+///
+/// # Arguments
+/// * `vault_rent_payer` - The vault's ["vault_rent_payer", input_vault] PDA
+/// * `rent_payer` - Recorded payer, required when the record exists
+/// * `rent_payer_stats` - Recorded payer's OperatorStats, required when an operator sponsored the vault
+/// * `default_destination` - Account the close path pays when the creator funded the vault
+/// * `program_id` - This program's ID
+///
+/// # Returns
+/// * `Result<AccountInfo>` - The recorded payer (or the treasury an operator's obligations were
+///   transferred to), or `default_destination` without a record
+pub fn vault_rent_destination<'info>(
+    vault_rent_payer: &AccountInfo<'info>,
+    rent_payer: Option<&AccountInfo<'info>>,
+    rent_payer_stats: Option<&AccountInfo<'info>>,
+    default_destination: &AccountInfo<'info>,
+    program_id: &Pubkey,
+) -> Result<AccountInfo<'info>> {
+    let Some(record) = VaultRentPayer::load(vault_rent_payer, program_id)? else {
+        return Ok(default_destination.clone());
+    };
+    let refund_to = match sponsor_stats(&record, rent_payer_stats, program_id)? {
+        Some(stats) => stats.refund_destination(),
+        None => record.payer,
+    };
+    let rent_payer = rent_payer.ok_or(ErrorCode::InvalidRentPayer)?;
+    require_keys_eq!(rent_payer.key(), refund_to, ErrorCode::InvalidRentPayer);
+    Ok(rent_payer.clone())
+}
+
+/// Loads the OperatorStats of the operator that sponsored a vault, None for other sponsors
+fn sponsor_stats(
+    record: &VaultRentPayer,
+    rent_payer_stats: Option<&AccountInfo>,
+    program_id: &Pubkey,
+) -> Result<Option<OperatorStats>> {
+    if !record.operator_sponsored {
+        return Ok(None);
+    }
+    let rent_payer_stats = rent_payer_stats.ok_or(ErrorCode::InvalidRentPayer)?;
+    let stats = OperatorStats::load(rent_payer_stats, program_id)?.ok_or(ErrorCode::InvalidRentPayer)?;
+    require_keys_eq!(stats.operator, record.payer, ErrorCode::InvalidRentPayer);
+    Ok(Some(stats))
+}
+
+/// Closes a closed vault's rent payer record, if it has one, into `destination` and settles
+/// the sponsoring operator's obligation
+pub fn close_vault_rent_payer<'info>(
+    vault_rent_payer: &AccountInfo<'info>,
+    destination: &AccountInfo<'info>,
+    rent_payer_stats: Option<&AccountInfo<'info>>,
+    program_id: &Pubkey,
+) -> Result<()> {
+    let Some(record) = VaultRentPayer::load(vault_rent_payer, program_id)? else {
+        return Ok(());
+    };
+    if let (Some(mut stats), Some(rent_payer_stats)) = (sponsor_stats(&record, rent_payer_stats, program_id)?, rent_payer_stats) {
+        stats.settle_obligation();
+        stats.store(rent_payer_stats)?;
     }
+    let lamports = vault_rent_payer.lamports();
+    vault_rent_payer.sub_lamports(lamports)?;
+    destination.add_lamports(lamports)?;
+    vault_rent_payer.assign(&System::id());
+    vault_rent_payer.resize(0)?;
+    Ok(())
 }
 
 /// Initializes a limit order account and its associated vault with support for Token 2022 extensions
@@ -136,7 +535,7 @@ impl LimitOrder {
 /// For standard tokens: account_space = 0 (uses default 165 bytes)
 /// For xstocks tokens with confidential transfer: account_space = 14 (179 bytes total)
 #[derive(Accounts)]
-#[instruction(nonce: u64, account_space: u16)]
+#[instruction(nonce: u64, account_space: u16, create_receipt: bool)]
 pub struct InitLimitOrder<'info> {
     /// Vault authority PDA controlling all vaults
     #[account(
@@ -148,26 +547,75 @@ pub struct InitLimitOrder<'info> {
     /// Limit order account to be created
     #[account(
         init,
-        payer = creator,
+        payer = payer,
         space = LimitOrder::SPACE,
         seeds = [b"limit_order", creator.key().as_ref(), nonce.to_le_bytes().as_ref()],
         bump
     )]
     pub limit_order: Account<'info, LimitOrder>,
 
+    /// Creator's order counter, created with the creator's first order; stamps the order's generation
+    #[account(
+        init_if_needed,
+        payer = payer,
+        space = CreatorOrderCounter::SPACE,
+        seeds = [b"order_counter", creator.key().as_ref()],
+        bump
+    )]
+    pub order_counter: Account<'info, CreatorOrderCounter>,
+
+    /// Optional receipt recording the order outcome (required when create_receipt = true)
+    /// Rent is paid by the payer and reclaimed by the creator via close_order_receipt
+    #[account(
+        init,
+        payer = payer,
+        space = OrderReceipt::SPACE,
+        seeds = [b"receipt", limit_order.key().as_ref()],
+        bump
+    )]
+    pub order_receipt: Option<Account<'info, OrderReceipt>>,
+
     /// Vault to hold input tokens until order execution
     /// CHECK: The vault account will be initialized by this instruction
     #[account(mut)]
     pub input_vault: AccountInfo<'info>,
 
+    /// Record of the vault rent payer, created only when the payer is not the creator
+    /// CHECK: ["vault_rent_payer", input_vault] PDA, created by this instruction
+    #[account(
+        mut,
+        seeds = [b"vault_rent_payer", input_vault.key().as_ref()],
+        bump
+    )]
+    pub vault_rent_payer: UncheckedAccount<'info>,
+
+    /// Registry, read to tell whether the payer is an operator
+    #[account(
+        seeds = [b"adapter_registry"],
+        bump
+    )]
+    pub adapter_registry: Account<'info, AdapterRegistry>,
+
+    /// Payer's OperatorStats, counting the vault when an operator sponsors it (created if needed)
+    /// CHECK: ["operator_stats", payer] PDA, only written when the payer is a registered operator
+    #[account(
+        mut,
+        seeds = [b"operator_stats", payer.key().as_ref()],
+        bump
+    )]
+    pub payer_stats: UncheckedAccount<'info>,
+
     #[account(
         constraint = input_mint.to_account_info().owner == &input_token_program.key() @ ErrorCode::InvalidCpiInterface
     )]
     pub input_mint: InterfaceAccount<'info, Mint>,
     pub input_token_program: Interface<'info, TokenInterface>,
     
-    #[account(mut)]
+    /// Order creator and authority over the order; may be a program PDA signing via CPI
     pub creator: Signer<'info>,
+    /// Pays rent for the order, receipt and vault (may be the creator)
+    #[account(mut)]
+    pub payer: Signer<'info>,
     pub system_program: Program<'info, System>,
     pub rent: Sysvar<'info, Rent>,
 }
@@ -176,7 +624,14 @@ pub fn init_limit_order(
     ctx: Context<InitLimitOrder>,
     nonce: u64,
     account_space: u16,
+    create_receipt: bool,
 ) -> Result<()> {
+    // Receipt account must be passed exactly when requested
+    require!(
+        create_receipt == ctx.accounts.order_receipt.is_some(),
+        ErrorCode::InvalidOrderReceipt
+    );
+
     // Derive vault PDA using limit_order.key() as seed
     let limit_order_key = ctx.accounts.limit_order.key();
     let (vault_pda, vault_bump) = Pubkey::find_program_address(
@@ -213,6 +668,12 @@ pub fn init_limit_order(
         );
     }
 
+    // Stamp the order so a recreated (creator, nonce) address is distinguishable from its predecessor
+    let order_counter = &mut ctx.accounts.order_counter;
+    order_counter.creator = ctx.accounts.creator.key();
+    order_counter.bump = ctx.bumps.order_counter;
+    let generation = order_counter.next_generation()?;
+
     // Initialize limit_order with default values (will be filled in create_limit_order)
     let order = &mut ctx.accounts.limit_order;
     order.creator = ctx.accounts.creator.key();
@@ -227,7 +688,27 @@ pub fn init_limit_order(
     order.expiry = 0; // Will be set in create_limit_order
     order.status = OrderStatus::Init;
     order.slippage_bps = 0; // Will be set in create_limit_order
+    order.failed_attempts = 0;
+    order.activate_at = 0; // Will be set in create_limit_order
+    order.execution_fee_bps = None;
+    order.unwrap_output = false;
+    order.user_lamport_destination = Pubkey::default();
+    order.generation = generation;
     order.bump = ctx.bumps.limit_order;
+    order.input_token_program = ctx.accounts.input_token_program.key();
+    order.output_token_program = Pubkey::default(); // Will be set in create_limit_order
+
+    if let Some(receipt) = ctx.accounts.order_receipt.as_mut() {
+        receipt.order = limit_order_key;
+        receipt.creator = ctx.accounts.creator.key();
+        receipt.status = OrderStatus::Init;
+        receipt.output_amount = 0;
+        receipt.fee_amount = 0;
+        receipt.executor = Pubkey::default();
+        receipt.timestamp = 0;
+        receipt.bump = ctx.bumps.order_receipt.ok_or(ErrorCode::InvalidOrderReceipt)?;
+        receipt.vault_close_pending = false;
+    }
 
     // Create the vault account manually to support extensions
     let vault_authority_bump = ctx.accounts.vault_authority.bump;
@@ -239,14 +720,14 @@ pub fn init_limit_order(
 
     anchor_lang::solana_program::program::invoke_signed(
         &anchor_lang::solana_program::system_instruction::create_account(
-            &ctx.accounts.creator.key(),
+            &ctx.accounts.payer.key(),
             &ctx.accounts.input_vault.key(),
             ctx.accounts.rent.minimum_balance(total_size),
             total_size as u64,
             &ctx.accounts.input_token_program.key(),
         ),
         &[
-            ctx.accounts.creator.to_account_info(),
+            ctx.accounts.payer.to_account_info(),
             ctx.accounts.input_vault.to_account_info(),
             ctx.accounts.system_program.to_account_info(),
         ],
@@ -285,6 +766,59 @@ pub fn init_limit_order(
     // 2. input_token_program is TOKEN_2022_PROGRAM_ID when account_space > 0
     // 3. The mint actually has the C extension enabled
 
+    // A sponsor's vault rent goes back to the sponsor, whichever path later closes the vault
+    if ctx.accounts.payer.key() != ctx.accounts.creator.key() {
+        let input_vault_key = ctx.accounts.input_vault.key();
+        let record_bump = ctx.bumps.vault_rent_payer;
+        let record_seeds: &[&[u8]] = &[b"vault_rent_payer".as_ref(), input_vault_key.as_ref(), &[record_bump]];
+        create_pda_account(
+            &ctx.accounts.payer.to_account_info(),
+            &ctx.accounts.vault_rent_payer.to_account_info(),
+            &ctx.accounts.system_program.to_account_info(),
+            VaultRentPayer::SPACE,
+            ctx.program_id,
+            record_seeds,
+        )?;
+        let operator_sponsored = ctx.accounts.adapter_registry.operators.contains(&ctx.accounts.payer.key());
+        let record = VaultRentPayer {
+            vault: input_vault_key,
+            payer: ctx.accounts.payer.key(),
+            bump: record_bump,
+            operator_sponsored,
+        };
+        record.try_serialize(&mut &mut ctx.accounts.vault_rent_payer.try_borrow_mut_data()?[..])?;
+
+        // An operator cannot be removed while it sponsors open vaults without handing them over
+        if operator_sponsored {
+            let payer_stats = ctx.accounts.payer_stats.to_account_info();
+            let mut stats = match OperatorStats::load(&payer_stats, ctx.program_id)? {
+                Some(stats) => stats,
+                None => {
+                    let stats_bump = ctx.bumps.payer_stats;
+                    let payer_key = ctx.accounts.payer.key();
+                    let stats_seeds: &[&[u8]] = &[b"operator_stats".as_ref(), payer_key.as_ref(), &[stats_bump]];
+                    create_pda_account(
+                        &ctx.accounts.payer.to_account_info(),
+                        &payer_stats,
+                        &ctx.accounts.system_program.to_account_info(),
+                        OperatorStats::SPACE,
+                        ctx.program_id,
+                        stats_seeds,
+                    )?;
+                    OperatorStats {
+                        operator: payer_key,
+                        last_active: 0,
+                        bump: stats_bump,
+                        outstanding_obligations: 0,
+                        obligations_transferred_to: Pubkey::default(),
+                    }
+                }
+            };
+            stats.add_obligation()?;
+            stats.store(&payer_stats)?;
+        }
+    }
+
     msg!(
         "Successfully initialized limit order: {} and vault: {} for mint: {} (space: {} bytes)",
         ctx.accounts.limit_order.key(),
@@ -295,6 +829,54 @@ pub fn init_limit_order(
     Ok(())
 }
 
+/// Transfers the registry's order creation fee from the payer to the treasury
+///
+/// # Arguments
+/// * `adapter_registry` - Registry holding the fee and treasury
+/// * `payer` - System account paying the fee (must sign the instruction)
+/// * `treasury` - Treasury account, required when the fee is non-zero
+/// * `system_program` - System program for the transfer
+///
+/// # Returns
+/// * `Result<u64>` - Fee charged in lamports (0 when disabled)
+pub fn collect_order_creation_fee<'info>(
+    adapter_registry: &AdapterRegistry,
+    payer: &AccountInfo<'info>,
+    treasury: Option<&AccountInfo<'info>>,
+    system_program: &AccountInfo<'info>,
+) -> Result<u64> {
+    let fee_lamports = adapter_registry.order_creation_fee_lamports;
+    if fee_lamports == 0 {
+        return Ok(0);
+    }
+
+    let treasury = treasury.ok_or(ErrorCode::InvalidTreasury)?;
+    require!(
+        treasury.key() == adapter_registry.treasury,
+        ErrorCode::InvalidTreasury
+    );
+    require!(
+        payer.lamports() >= fee_lamports,
+        ErrorCode::InsufficientCreationFee
+    );
+
+    anchor_lang::solana_program::program::invoke(
+        &anchor_lang::solana_program::system_instruction::transfer(
+            &payer.key(),
+            &treasury.key(),
+            fee_lamports,
+        ),
+        &[
+            payer.clone(),
+            treasury.clone(),
+            system_program.clone(),
+        ],
+    )?;
+
+    msg!("Charged order creation fee of {} lamports to {}", fee_lamports, payer.key());
+    Ok(fee_lamports)
+}
+
 /// Create limit order instruction accounts
 #[event_cpi]
 #[derive(Accounts)]
@@ -307,6 +889,13 @@ pub struct CreateLimitOrder<'info> {
     )]
     pub vault_authority: Account<'info, VaultAuthority>,
 
+    /// Adapter registry holding the order creation fee
+    #[account(
+        seeds = [b"adapter_registry"],
+        bump
+    )]
+    pub adapter_registry: Box<Account<'info, AdapterRegistry>>,
+
     /// Limit order account (must be initialized separately using init_limit_order)
     #[account(
         mut,
@@ -342,17 +931,39 @@ pub struct CreateLimitOrder<'info> {
     pub user_destination_token_account: Box<InterfaceAccount<'info, TokenAccount>>,
 
     /// Input token mint
+    #[account(
+        constraint = input_mint.to_account_info().owner == &input_token_program.key() @ ErrorCode::InvalidTokenProgram
+    )]
     pub input_mint: Box<InterfaceAccount<'info, Mint>>,
     /// Output token mint
+    #[account(
+        constraint = output_mint.to_account_info().owner == &output_token_program.key() @ ErrorCode::InvalidTokenProgram
+    )]
     pub output_mint: Box<InterfaceAccount<'info, Mint>>,
-    /// Token program for input tokens
+    /// Token program for input tokens (snapshotted on the order)
     pub input_token_program: Interface<'info, TokenInterface>,
-    /// Token program for output tokens
+    /// Token program for output tokens (snapshotted on the order)
     pub output_token_program: Interface<'info, TokenInterface>,
 
-    /// Order creator (must sign)
-    #[account(mut, signer)]
+    /// Treasury receiving the order creation fee (required when the fee is non-zero)
+    /// CHECK: Must match adapter_registry.treasury, checked in collect_order_creation_fee
+    #[account(mut)]
+    pub treasury: Option<UncheckedAccount<'info>>,
+
+    /// Instructions sysvar for the reference price attestation (required for strict price checks)
+    /// CHECK: address is constrained to the instructions sysvar
+    #[account(address = anchor_lang::solana_program::sysvar::instructions::ID)]
+    pub instructions_sysvar: Option<UncheckedAccount<'info>>,
+
+    /// CHECK: ProtocolConfig PDA; default limits apply until create_protocol_config has created it
+    #[account(seeds = [b"protocol_config"], bump)]
+    pub protocol_config: UncheckedAccount<'info>,
+
+    /// Order creator (must sign; may be a program PDA signing via CPI), owner of the source tokens
     pub creator: Signer<'info>,
+    /// Pays the order creation fee (may be the creator)
+    #[account(mut)]
+    pub payer: Signer<'info>,
     /// System program for account creation
     pub system_program: Program<'info, System>,
 }
@@ -361,11 +972,18 @@ pub struct CreateLimitOrder<'info> {
 ///
 /// # Arguments
 /// * `nonce` - Unique identifier for order creation
-/// * `input_amount` - Amount of input tokens to swap
+/// * `input_amount` - Amount of input tokens to swap, or SWEEP_SOURCE_BALANCE for the whole
+///   user_input_token_account balance
 /// * `min_output_amount` - Minimum output amount (baseline for trigger)
 /// * `trigger_price_bps` - Trigger deviation percentage in basis points
 /// * `trigger_type` - Type of trigger (TakeProfit or StopLoss)
 /// * `expiry` - Order expiration timestamp
+/// * `strict_price_check` - Reject the order if min_output_amount / input_amount is outside the
+///   registry price band around an attested reference price
+/// * `price_signature` - Ed25519 signature of the reference price attestation (strict checks only)
+/// * `price_publisher` - Key that signed the reference price attestation (must be the registry quote signer)
+/// * `activate_at` - Earliest execution timestamp (0 = immediately); must be before expiry
+#[allow(clippy::too_many_arguments)]
 pub fn create_limit_order(
     ctx: Context<CreateLimitOrder>,
     nonce: u64,
@@ -374,8 +992,15 @@ pub fn create_limit_order(
     trigger_price_bps: u32,
     trigger_type: TriggerType,
     expiry: i64,
-    slippage_bps: u16
+    slippage_bps: u16,
+    strict_price_check: bool,
+    price_signature: Option<[u8; 64]>,
+    price_publisher: Option<Pubkey>,
+    activate_at: i64,
 ) -> Result<()> {
+    // Resolve a sweep before the vault transfer so the order records what was actually moved
+    let input_amount = resolve_in_amount(input_amount, ctx.accounts.user_input_token_account.amount)?;
+
     // Validate input parameters
     if input_amount == 0 {
         return Err(ErrorCode::InvalidAmount.into());
@@ -383,43 +1008,55 @@ pub fn create_limit_order(
     if min_output_amount == 0 {
         return Err(ErrorCode::InvalidAmount.into());
     }
-    if trigger_price_bps == 0 {
-        return Err(ErrorCode::InvalidTriggerPrice.into());
-    }
-    // Validate upper bound based on trigger type
-    // For StopLoss orders: trigger_price_bps must not exceed 10,000 (100%)
-    // because 10_000 - trigger_price_bps would underflow in should_execute
-    // For TakeProfit orders: allow up to 100,000 (1000%)
-    // 10_000 + 100_000 = 110_000 fits in u64, so no overflow risk
-    match trigger_type {
-        TriggerType::StopLoss => {
-            if trigger_price_bps > 10_000 {
-                return Err(ErrorCode::InvalidTriggerPrice.into());
-            }
-        },
-        TriggerType::TakeProfit => {
-            // Allow up to 100,000 (1000%) for TakeProfit orders
-            if trigger_price_bps > 100_000 {
-                return Err(ErrorCode::InvalidTriggerPrice.into());
-            }
-        }
-    }
-    if expiry <= Clock::get()?.unix_timestamp {
+    require_distinct_order_mints(&ctx.accounts.input_mint.key(), &ctx.accounts.output_mint.key())?;
+    // Trigger and slippage caps come from the ProtocolConfig (defaults until it exists)
+    let protocol_config = ProtocolConfig::load_config(&ctx.accounts.protocol_config, ctx.program_id)?;
+    protocol_config.require_creation_open()?;
+    let limits = protocol_config.limits;
+    validate_trigger_price(&limits, trigger_type, trigger_price_bps)?;
+    let now = current_unix_timestamp(ctx.remaining_accounts)?;
+    if expiry <= now {
         return Err(ErrorCode::InvalidExpiry.into());
     }
-
+    limits.check_order_duration(expiry, now)?;
     require!(
-        slippage_bps <= 1000,
-        ErrorCode::InvalidSlippage
+        activate_at >= 0 && activate_at < expiry,
+        ErrorCode::InvalidActivationTime
     );
 
+    limits.check_order_slippage(slippage_bps)?;
+
+    // Catch min_output_amount given in UI units instead of raw amounts (or vice versa)
+    if strict_price_check {
+        let instructions_sysvar = ctx.accounts.instructions_sysvar.as_ref().map(|sysvar| sysvar.to_account_info());
+        verify_order_price(
+            &ctx.accounts.adapter_registry,
+            instructions_sysvar.as_ref(),
+            price_signature,
+            price_publisher,
+            &ctx.accounts.input_mint,
+            &ctx.accounts.output_mint,
+            input_amount,
+            min_output_amount,
+            Clock::get()?.slot,
+        )?;
+    }
+
     // Validate that limit_order was initialized and matches the creator
     require!(
         ctx.accounts.limit_order.creator == ctx.accounts.creator.key(),
         ErrorCode::UnauthorizedAdmin
     );
 
+    collect_order_creation_fee(
+        &ctx.accounts.adapter_registry,
+        &ctx.accounts.payer.to_account_info(),
+        ctx.accounts.treasury.as_deref(),
+        &ctx.accounts.system_program.to_account_info(),
+    )?;
+
     // Transfer input tokens to order vault
+    let vault_balance_before = ctx.accounts.input_vault.amount;
     transfer_checked(
         CpiContext::new(
             ctx.accounts.input_token_program.to_account_info(),
@@ -434,6 +1071,13 @@ pub fn create_limit_order(
         ctx.accounts.input_mint.decimals,
     )?;
 
+    // Record what arrived in the vault: a Token-2022 transfer fee is withheld from the gross amount
+    ctx.accounts.input_vault.reload()?;
+    let input_amount = ctx.accounts.input_vault.amount
+        .checked_sub(vault_balance_before)
+        .ok_or(ErrorCode::InvalidCalculation)?;
+    require!(input_amount > 0, ErrorCode::InvalidAmount);
+
     // Update order account with order parameters
     let order = &mut ctx.accounts.limit_order;
     order.input_mint = ctx.accounts.input_mint.key();
@@ -446,6 +1090,13 @@ pub fn create_limit_order(
     order.expiry = expiry;
     order.status = OrderStatus::Open;
     order.slippage_bps = slippage_bps;
+    order.failed_attempts = 0;
+    order.activate_at = activate_at;
+    order.execution_fee_bps = None;
+    order.unwrap_output = false;
+    order.user_lamport_destination = Pubkey::default();
+    order.input_token_program = ctx.accounts.input_token_program.key();
+    order.output_token_program = ctx.accounts.output_token_program.key();
 
     // Emit order creation event
     emit_cpi!(LimitOrderCreated {
@@ -458,143 +1109,1830 @@ pub fn create_limit_order(
         trigger_price_bps: order.trigger_price_bps,
         trigger_type: order.trigger_type as u8,
         expiry: order.expiry,
+        activate_at: order.activate_at,
+        generation: order.generation,
+        ui_amount_multiplier: route_validator_module::ui_amount_multiplier(&ctx.accounts.output_mint.to_account_info())?,
+        replaces: None,
     });
+    emit_cpi!(order.open_interest_change(true));
 
     Ok(())
 }
 
-/// Execute limit order instruction accounts
-#[event_cpi]
-#[derive(Accounts)]
-#[instruction(route_plan: Vec<RoutePlanStep>)]
-pub struct ExecuteLimitOrder<'info> {
-    /// Adapter registry for routing validation
-    #[account(
-        seeds = [b"adapter_registry"],
-        bump
-    )]
-    pub adapter_registry: Account<'info, AdapterRegistry>,
+/// Quote attestation message signed by the registry quote signer:
+/// order (32) | quoted_out_amount (u64 LE) | valid_from_slot (u64 LE) | valid_until_slot (u64 LE)
+pub const QUOTE_ATTESTATION_MESSAGE_LEN: usize = 32 + 8 + 8 + 8;
+/// Longest slot window a single quote attestation may cover (~1 minute)
+pub const MAX_QUOTE_ATTESTATION_SLOTS: u64 = 150;
+
+/// Ed25519 program instruction data: [num_signatures: u8, padding: u8] followed by
+/// one 14-byte offsets entry per signature (7 little-endian u16 values)
+const ED25519_OFFSETS_START: usize = 2;
+const ED25519_OFFSETS_SIZE: usize = 14;
+/// Offsets instruction index meaning "data is inside the ed25519 instruction itself"
+const ED25519_CURRENT_INSTRUCTION: u16 = u16::MAX;
+
+/// Builds the message the quote signer signs for an order execution
+pub fn quote_attestation_message(
+    order: &Pubkey,
+    quoted_out_amount: u64,
+    valid_from_slot: u64,
+    valid_until_slot: u64,
+) -> [u8; QUOTE_ATTESTATION_MESSAGE_LEN] {
+    let mut message = [0u8; QUOTE_ATTESTATION_MESSAGE_LEN];
+    message[0..32].copy_from_slice(order.as_ref());
+    message[32..40].copy_from_slice(&quoted_out_amount.to_le_bytes());
+    message[40..48].copy_from_slice(&valid_from_slot.to_le_bytes());
+    message[48..56].copy_from_slice(&valid_until_slot.to_le_bytes());
+    message
+}
 
-    /// Vault authority controlling token transfers
-    #[account(
-        seeds = [b"vault_authority"],
-        bump
-    )]
-    pub vault_authority: Account<'info, VaultAuthority>,
+/// Extracts (public key, signature, message) from a single-signature ed25519 program instruction
+/// All offsets must point into the ed25519 instruction itself, otherwise the bytes the
+/// ed25519 program verified are not the bytes we read here
+pub fn parse_ed25519_instruction(data: &[u8]) -> Result<(Pubkey, [u8; 64], &[u8])> {
+    if data.len() < ED25519_OFFSETS_START + ED25519_OFFSETS_SIZE || data[0] != 1 {
+        return Err(ErrorCode::InvalidQuoteAttestation.into());
+    }
 
-    /// Limit order to execute (will be closed, rent goes to operator)
-    #[account(
-        mut,
-        close = operator,
-        constraint = limit_order.status == OrderStatus::Open @ ErrorCode::InvalidOrderStatus,
-        constraint = limit_order.input_vault == input_vault.key() @ ErrorCode::InvalidVaultAddress
-    )]
-    pub limit_order: Account<'info, LimitOrder>,
+    let read_u16 = |at: usize| u16::from_le_bytes([data[at], data[at + 1]]);
+    let signature_offset = read_u16(ED25519_OFFSETS_START) as usize;
+    let signature_instruction_index = read_u16(ED25519_OFFSETS_START + 2);
+    let public_key_offset = read_u16(ED25519_OFFSETS_START + 4) as usize;
+    let public_key_instruction_index = read_u16(ED25519_OFFSETS_START + 6);
+    let message_offset = read_u16(ED25519_OFFSETS_START + 8) as usize;
+    let message_size = read_u16(ED25519_OFFSETS_START + 10) as usize;
+    let message_instruction_index = read_u16(ED25519_OFFSETS_START + 12);
+
+    if signature_instruction_index != ED25519_CURRENT_INSTRUCTION
+        || public_key_instruction_index != ED25519_CURRENT_INSTRUCTION
+        || message_instruction_index != ED25519_CURRENT_INSTRUCTION
+    {
+        return Err(ErrorCode::InvalidQuoteAttestation.into());
+    }
 
-    /// Vault holding input tokens
-    #[account(
-        mut,
-        constraint = input_vault.mint == limit_order.input_mint @ ErrorCode::InvalidMint
-    )]
-    pub input_vault: Box<InterfaceAccount<'info, TokenAccount>>,
+    let signature: [u8; 64] = data
+        .get(signature_offset..signature_offset + 64)
+        .and_then(|bytes| bytes.try_into().ok())
+        .ok_or(ErrorCode::InvalidQuoteAttestation)?;
+    let public_key: [u8; 32] = data
+        .get(public_key_offset..public_key_offset + 32)
+        .and_then(|bytes| bytes.try_into().ok())
+        .ok_or(ErrorCode::InvalidQuoteAttestation)?;
+    let message = data
+        .get(message_offset..message_offset + message_size)
+        .ok_or(ErrorCode::InvalidQuoteAttestation)?;
+
+    Ok((Pubkey::new_from_array(public_key), signature, message))
+}
 
-    /// Token program for input tokens
-    pub input_token_program: Interface<'info, TokenInterface>,
-    /// Token program for output tokens
-    pub output_token_program: Interface<'info, TokenInterface>,
+/// Checks an attested message covers this order, this quote and the current slot
+pub fn validate_quote_attestation_message(
+    message: &[u8],
+    order: &Pubkey,
+    quoted_out_amount: u64,
+    current_slot: u64,
+) -> Result<()> {
+    if message.len() != QUOTE_ATTESTATION_MESSAGE_LEN {
+        return Err(ErrorCode::InvalidQuoteAttestation.into());
+    }
 
-    /// User's destination account for output tokens
-    #[account(
-        mut,
-        constraint = user_destination_token_account.key() == limit_order.user_destination_account,
-        constraint = user_destination_token_account.mint == limit_order.output_mint,
-        constraint = user_destination_token_account.owner == limit_order.creator
-    )]
-    pub user_destination_token_account: Box<InterfaceAccount<'info, TokenAccount>>,
+    let read_u64 = |at: usize| {
+        let mut bytes = [0u8; 8];
+        bytes.copy_from_slice(&message[at..at + 8]);
+        u64::from_le_bytes(bytes)
+    };
+    let attested_out_amount = read_u64(32);
+    let valid_from_slot = read_u64(40);
+    let valid_until_slot = read_u64(48);
+
+    if &message[0..32] != order.as_ref() || attested_out_amount != quoted_out_amount {
+        return Err(ErrorCode::InvalidQuoteAttestation.into());
+    }
 
-    /// Input token mint (must match limit_order.input_mint)
-    #[account(
-        constraint = input_mint.key() == limit_order.input_mint @ ErrorCode::InvalidMint
-    )]
-    pub input_mint: InterfaceAccount<'info, Mint>,
-    /// Output token mint (must match limit_order.output_mint)
-    #[account(
-        constraint = output_mint.key() == limit_order.output_mint @ ErrorCode::InvalidMint
-    )]
-    pub output_mint: InterfaceAccount<'info, Mint>,
+    validate_attestation_slot_window(valid_from_slot, valid_until_slot, current_slot)
+}
 
-    /// Optional platform fee collection account
-    #[account(mut)]
-    pub platform_fee_account: Option<InterfaceAccount<'info, TokenAccount>>,
+/// Checks an attestation slot window is at most MAX_QUOTE_ATTESTATION_SLOTS wide and
+/// includes the current slot
+fn validate_attestation_slot_window(
+    valid_from_slot: u64,
+    valid_until_slot: u64,
+    current_slot: u64,
+) -> Result<()> {
+    if valid_until_slot < valid_from_slot
+        || valid_until_slot - valid_from_slot > MAX_QUOTE_ATTESTATION_SLOTS
+    {
+        return Err(ErrorCode::InvalidQuoteAttestation.into());
+    }
+    if current_slot < valid_from_slot || current_slot > valid_until_slot {
+        return Err(ErrorCode::QuoteAttestationExpired.into());
+    }
+
+    Ok(())
+}
+
+/// Enforces the registry quote signer, if one is configured, on a limit order execution
+/// The operator must place an ed25519 program instruction earlier in the same transaction
+/// that signs quote_attestation_message(order, quoted_out_amount, slot window) with the
+/// quote signer key; quote_signature selects which ed25519 signature is the attestation
+pub fn verify_quote_attestation(
+    quote_signer: &Pubkey,
+    instructions_sysvar: Option<&AccountInfo>,
+    quote_signature: Option<[u8; 64]>,
+    quote_publisher: Option<Pubkey>,
+    order: &Pubkey,
+    quoted_out_amount: u64,
+    current_slot: u64,
+) -> Result<()> {
+    if *quote_signer == Pubkey::default() {
+        return Ok(());
+    }
+
+    let message = load_attested_message(quote_signer, instructions_sysvar, quote_signature, quote_publisher)?;
+    validate_quote_attestation_message(&message, order, quoted_out_amount, current_slot)
+}
+
+/// Returns the message the quote signer signed in an earlier ed25519 program instruction
+/// of this transaction; quote_signature selects which ed25519 signature to use
+fn load_attested_message(
+    quote_signer: &Pubkey,
+    instructions_sysvar: Option<&AccountInfo>,
+    quote_signature: Option<[u8; 64]>,
+    quote_publisher: Option<Pubkey>,
+) -> Result<Vec<u8>> {
+    let (Some(quote_signature), Some(quote_publisher)) = (quote_signature, quote_publisher) else {
+        return Err(ErrorCode::MissingQuoteAttestation.into());
+    };
+    if quote_publisher != *quote_signer {
+        return Err(ErrorCode::InvalidQuoteAttestation.into());
+    }
+    let instructions_sysvar = instructions_sysvar.ok_or(ErrorCode::MissingQuoteAttestation)?;
+
+    // Only earlier instructions count: the runtime has already verified their signatures
+    let current_index = load_current_index_checked(instructions_sysvar)?;
+    for index in 0..current_index {
+        let instruction = load_instruction_at_checked(index as usize, instructions_sysvar)?;
+        if instruction.program_id != ed25519_program::ID {
+            continue;
+        }
+        let Ok((public_key, signature, message)) = parse_ed25519_instruction(&instruction.data) else {
+            continue;
+        };
+        if public_key == quote_publisher && signature == quote_signature {
+            return Ok(message.to_vec());
+        }
+    }
+
+    Err(ErrorCode::InvalidQuoteAttestation.into())
+}
+
+/// Reference price attestation message signed by the registry quote signer:
+/// input_mint (32) | output_mint (32) | reference_price (u64 LE) | valid_from_slot (u64 LE) | valid_until_slot (u64 LE)
+pub const PRICE_ATTESTATION_MESSAGE_LEN: usize = 32 + 32 + 8 + 8 + 8;
+/// Scale of order prices: whole output tokens per whole input token, times ORDER_PRICE_SCALE
+pub const ORDER_PRICE_SCALE: u128 = 1_000_000_000;
+
+/// Builds the message the quote signer signs to attest a reference price for a mint pair
+pub fn price_attestation_message(
+    input_mint: &Pubkey,
+    output_mint: &Pubkey,
+    reference_price: u64,
+    valid_from_slot: u64,
+    valid_until_slot: u64,
+) -> [u8; PRICE_ATTESTATION_MESSAGE_LEN] {
+    let mut message = [0u8; PRICE_ATTESTATION_MESSAGE_LEN];
+    message[0..32].copy_from_slice(input_mint.as_ref());
+    message[32..64].copy_from_slice(output_mint.as_ref());
+    message[64..72].copy_from_slice(&reference_price.to_le_bytes());
+    message[72..80].copy_from_slice(&valid_from_slot.to_le_bytes());
+    message[80..88].copy_from_slice(&valid_until_slot.to_le_bytes());
+    message
+}
+
+/// Checks an attested message covers this mint pair and the current slot
+///
+/// # Returns
+/// * `Result<u64>` - The attested reference price (scaled by ORDER_PRICE_SCALE)
+pub fn validate_price_attestation_message(
+    message: &[u8],
+    input_mint: &Pubkey,
+    output_mint: &Pubkey,
+    current_slot: u64,
+) -> Result<u64> {
+    if message.len() != PRICE_ATTESTATION_MESSAGE_LEN {
+        return Err(ErrorCode::InvalidQuoteAttestation.into());
+    }
+
+    let read_u64 = |at: usize| {
+        let mut bytes = [0u8; 8];
+        bytes.copy_from_slice(&message[at..at + 8]);
+        u64::from_le_bytes(bytes)
+    };
+    let reference_price = read_u64(64);
+
+    if &message[0..32] != input_mint.as_ref()
+        || &message[32..64] != output_mint.as_ref()
+        || reference_price == 0
+    {
+        return Err(ErrorCode::InvalidQuoteAttestation.into());
+    }
+    validate_attestation_slot_window(read_u64(72), read_u64(80), current_slot)?;
+
+    Ok(reference_price)
+}
+
+/// Price implied by an order's min_output_amount / input_amount, in whole output tokens per
+/// whole input token scaled by ORDER_PRICE_SCALE
+///
+/// # Returns
+/// * `Option<u128>` - The implied price, or None if it does not fit in a u128
+pub fn implied_order_price(
+    input_amount: u64,
+    min_output_amount: u64,
+    input_decimals: u8,
+    output_decimals: u8,
+) -> Option<u128> {
+    let scaled_output = (min_output_amount as u128).checked_mul(ORDER_PRICE_SCALE)?;
+    if input_decimals >= output_decimals {
+        let factor = 10u128.checked_pow((input_decimals - output_decimals) as u32)?;
+        scaled_output.checked_mul(factor)?.checked_div(input_amount as u128)
+    } else {
+        let factor = 10u128.checked_pow((output_decimals - input_decimals) as u32)?;
+        scaled_output.checked_div((input_amount as u128).checked_mul(factor)?)
+    }
+}
+
+/// Checks an implied order price lies within band_bps of the reference price, edges included
+pub fn check_order_price_band(implied_price: u128, reference_price: u64, band_bps: u16) -> Result<()> {
+    let band_bps = band_bps as u128;
+    let reference_price = reference_price as u128;
+    // Compare implied * 10000 against reference * (10000 ± band) to avoid rounding the band edges
+    let scaled_implied = implied_price
+        .checked_mul(10_000)
+        .ok_or(ErrorCode::ImplausibleOrderPrice)?;
+    let lower = reference_price * (10_000 - band_bps);
+    let upper = reference_price * (10_000 + band_bps);
+    if scaled_implied < lower || scaled_implied > upper {
+        return Err(ErrorCode::ImplausibleOrderPrice.into());
+    }
+    Ok(())
+}
+
+/// Enforces the strict price check on a new limit order: the price implied by
+/// min_output_amount must lie within the registry price band around a reference price the
+/// quote signer attested for this mint pair in an earlier ed25519 instruction
+#[allow(clippy::too_many_arguments)]
+pub fn verify_order_price(
+    adapter_registry: &AdapterRegistry,
+    instructions_sysvar: Option<&AccountInfo>,
+    price_signature: Option<[u8; 64]>,
+    price_publisher: Option<Pubkey>,
+    input_mint: &InterfaceAccount<Mint>,
+    output_mint: &InterfaceAccount<Mint>,
+    input_amount: u64,
+    min_output_amount: u64,
+    current_slot: u64,
+) -> Result<()> {
+    // Without a quote signer there is nobody to attest the reference price
+    if adapter_registry.quote_signer == Pubkey::default() {
+        return Err(ErrorCode::MissingQuoteAttestation.into());
+    }
+
+    let message = load_attested_message(
+        &adapter_registry.quote_signer,
+        instructions_sysvar,
+        price_signature,
+        price_publisher,
+    )?;
+    let reference_price = validate_price_attestation_message(
+        &message,
+        &input_mint.key(),
+        &output_mint.key(),
+        current_slot,
+    )?;
+
+    // A price too large for u128 is far outside any band around a u64 reference price
+    let implied_price = implied_order_price(
+        input_amount,
+        min_output_amount,
+        input_mint.decimals,
+        output_mint.decimals,
+    )
+    .ok_or(ErrorCode::ImplausibleOrderPrice)?;
+
+    check_order_price_band(implied_price, reference_price, adapter_registry.price_band_bps())
+}
+
+/// Returns true if an order input vault can be closed: no token balance, no withheld
+/// Token-2022 transfer fees and no confidential transfer balances
+///
+/// # Arguments
+/// * `vault_data` - Raw token account data (SPL Token or Token-2022)
+pub fn input_vault_closable(vault_data: &[u8]) -> Result<bool> {
+    let vault = StateWithExtensions::<Token2022Account>::unpack(vault_data)?;
+    if vault.base.amount != 0 {
+        return Ok(false);
+    }
+    if let Ok(fee_amount) = vault.get_extension::<TransferFeeAmount>() {
+        if u64::from(fee_amount.withheld_amount) != 0 {
+            return Ok(false);
+        }
+    }
+    if let Ok(confidential) = vault.get_extension::<ConfidentialTransferAccount>() {
+        if confidential.closable().is_err() {
+            return Ok(false);
+        }
+    }
+    Ok(true)
+}
+
+/// Moves any input left in an order vault to the protocol vault for the input mint
+///
+/// # Returns
+/// * `Result<u64>` - Amount moved (0 if the vault was empty)
+pub fn sweep_input_dust<'info>(
+    input_vault: &AccountInfo<'info>,
+    dust_vault: &AccountInfo<'info>,
+    input_mint: &InterfaceAccount<'info, Mint>,
+    input_token_program: &AccountInfo<'info>,
+    vault_authority: &AccountInfo<'info>,
+    signer_seeds: &[&[&[u8]]],
+) -> Result<u64> {
+    let dust = TokenAccount::try_deserialize(&mut &input_vault.try_borrow_data()?[..])?.amount;
+    if dust == 0 {
+        return Ok(0);
+    }
+
+    transfer_checked(
+        CpiContext::new_with_signer(
+            input_token_program.clone(),
+            TransferChecked {
+                from: input_vault.clone(),
+                to: dust_vault.clone(),
+                authority: vault_authority.clone(),
+                mint: input_mint.to_account_info(),
+            },
+            signer_seeds,
+        ),
+        dust,
+        input_mint.decimals,
+    )?;
+
+    msg!("Swept {} input dust from {} to {}", dust, input_vault.key(), dust_vault.key());
+    Ok(dust)
+}
+
+/// Closes a filled order's input vault and order account, sending their rent to the operator
+/// (the vault rent to its recorded payer instead, see VaultRentPayer)
+///
+/// Input dust is first swept to the protocol vault when `dust_vault` is passed. If the vault
+/// still cannot be closed, both accounts stay open with the order Filled (so it cannot be
+/// filled again) and the receipt flagged, and close_limit_order_by_operator finishes later.
+/// The fill stands either way, unless `destination_rent` was refunded out of the order rent:
+/// the order can then not stay open and the fill fails with DestinationRentNotRefunded.
+///
+/// # Returns
+/// * `Result<bool>` - Whether the vault and order were closed
+#[allow(clippy::too_many_arguments)]
+pub fn close_filled_order<'info>(
+    limit_order: &Account<'info, LimitOrder>,
+    input_vault: &AccountInfo<'info>,
+    dust_vault: Option<&AccountInfo<'info>>,
+    order_receipt: &AccountInfo<'info>,
+    input_mint: &InterfaceAccount<'info, Mint>,
+    input_token_program: &AccountInfo<'info>,
+    vault_authority: &AccountInfo<'info>,
+    operator: &AccountInfo<'info>,
+    vault_rent_payer: &AccountInfo<'info>,
+    rent_payer: Option<&AccountInfo<'info>>,
+    rent_payer_stats: Option<&AccountInfo<'info>>,
+    destination_rent: u64,
+    program_id: &Pubkey,
+    signer_seeds: &[&[&[u8]]],
+) -> Result<bool> {
+    if let Some(dust_vault) = dust_vault {
+        sweep_input_dust(input_vault, dust_vault, input_mint, input_token_program, vault_authority, signer_seeds)?;
+    }
+
+    if !input_vault_closable(&input_vault.try_borrow_data()?)? {
+        require!(destination_rent == 0, ErrorCode::DestinationRentNotRefunded);
+        OrderReceipt::update(order_receipt, program_id, |receipt| receipt.vault_close_pending = true)?;
+        msg!("Input vault {} cannot be closed yet, leaving order {} for operator cleanup",
+             input_vault.key(),
+             limit_order.key());
+        return Ok(false);
+    }
+
+    let rent_destination = vault_rent_destination(vault_rent_payer, rent_payer, rent_payer_stats, operator, program_id)?;
+    close_account(
+        CpiContext::new_with_signer(
+            input_token_program.clone(),
+            CloseAccount {
+                account: input_vault.clone(),
+                destination: rent_destination.clone(),
+                authority: vault_authority.clone(),
+            },
+            signer_seeds
+        )
+    )?;
+    close_vault_rent_payer(vault_rent_payer, &rent_destination, rent_payer_stats, program_id)?;
+    limit_order.close(operator.clone())?;
+    Ok(true)
+}
+
+/// Returns the account receiving a limit order's output
+///
+/// The destination recorded on the order is used while it exists. If it has been closed,
+/// the creator's canonical output-mint ATA is used instead and created if needed. The operator
+/// pays its rent, which the caller refunds out of the order rent (see refund_destination_rent).
+///
+/// # Arguments
+/// * `recorded_destination` - Account matching `limit_order.user_destination_account`
+/// * `limit_order` - Order being executed
+/// * `creator_destination_ata` - Creator's ATA for the output mint (required if the recorded destination is closed)
+/// * `creator` - Order creator, owner of the ATA (required with the ATA)
+/// * `associated_token_program` - Associated token program (required with the ATA)
+/// * `operator` - Operator paying the ATA rent
+/// * `output_mint` - Output token mint
+/// * `output_token_program` - Token program of the output mint
+/// * `system_program` - System program for the ATA creation
+///
+/// # Returns
+/// * `Result<(AccountInfo, u64)>` - Token account the output is transferred to, and the
+///   lamports the operator paid to create it (0 unless the creator ATA was created)
+#[allow(clippy::too_many_arguments)]
+pub fn resolve_user_destination<'info>(
+    recorded_destination: &AccountInfo<'info>,
+    limit_order: &LimitOrder,
+    creator_destination_ata: Option<&AccountInfo<'info>>,
+    creator: Option<&AccountInfo<'info>>,
+    associated_token_program: Option<&AccountInfo<'info>>,
+    operator: &AccountInfo<'info>,
+    output_mint: &AccountInfo<'info>,
+    output_token_program: &AccountInfo<'info>,
+    system_program: &AccountInfo<'info>,
+) -> Result<(AccountInfo<'info>, u64)> {
+    if !recorded_destination.data_is_empty() {
+        require!(
+            recorded_destination.owner == output_token_program.key,
+            ErrorCode::InvalidAccount
+        );
+        let destination = TokenAccount::try_deserialize(&mut &recorded_destination.try_borrow_data()?[..])
+            .map_err(|_| ErrorCode::InvalidAccount)?;
+        require!(destination.mint == limit_order.output_mint, ErrorCode::InvalidMint);
+        require!(destination.owner == limit_order.creator, ErrorCode::InvalidAccount);
+        return Ok((recorded_destination.clone(), 0));
+    }
+
+    // Recorded destination was closed: fall back to the creator's canonical ATA
+    let (ata, creator, associated_token_program) = match (creator_destination_ata, creator, associated_token_program) {
+        (Some(ata), Some(creator), Some(associated_token_program)) => (ata, creator, associated_token_program),
+        _ => return Err(ErrorCode::MissingDestinationAccount.into()),
+    };
+    let expected_ata = get_associated_token_address_with_program_id(
+        &limit_order.creator,
+        &limit_order.output_mint,
+        output_token_program.key,
+    );
+    require!(ata.key() == expected_ata, ErrorCode::InvalidDestinationAccount);
+    require!(creator.key() == limit_order.creator, ErrorCode::InvalidDestinationAccount);
+
+    let operator_lamports = operator.lamports();
+    create_idempotent(CpiContext::new(
+        associated_token_program.clone(),
+        Create {
+            payer: operator.clone(),
+            associated_token: ata.clone(),
+            authority: creator.clone(),
+            mint: output_mint.clone(),
+            system_program: system_program.clone(),
+            token_program: output_token_program.clone(),
+        },
+    ))?;
+
+    msg!("Recorded destination {} is closed, delivering to creator ATA {}", recorded_destination.key(), ata.key());
+    Ok((ata.clone(), operator_lamports.saturating_sub(operator.lamports())))
+}
+
+/// Refunds the operator the creator ATA rent it paid in resolve_user_destination out of the
+/// order account's rent. The order is left short of rent exemption, so close_filled_order
+/// must close it in the same fill (DestinationRentNotRefunded otherwise).
+///
+/// # Arguments
+/// * `limit_order` - Order being executed, paying the refund
+/// * `operator` - Operator that paid the ATA rent
+/// * `destination_rent` - Lamports the operator paid, as resolve_user_destination returned
+pub fn refund_destination_rent<'info>(
+    limit_order: &AccountInfo<'info>,
+    operator: &AccountInfo<'info>,
+    destination_rent: u64,
+) -> Result<()> {
+    if destination_rent == 0 {
+        return Ok(());
+    }
+    limit_order.sub_lamports(destination_rent)?;
+    operator.add_lamports(destination_rent)?;
+    msg!("Refunded {} lamports of creator ATA rent to operator {} from order {}", destination_rent, operator.key(), limit_order.key());
+    Ok(())
+}
+
+/// Fails before any swap work if the account resolve_user_destination would deliver to is frozen,
+/// so keepers stop retrying a fill whose final transfer can only revert. The creator's ATA is
+/// checked only once the recorded destination has been closed; unwrapped orders pay out lamports
+/// and are never blocked by a frozen token account.
+///
+/// # Arguments
+/// * `limit_order` - Order being executed
+/// * `recorded_destination` - Account matching `limit_order.user_destination_account`
+/// * `creator_destination_ata` - Creator's ATA for the output mint, if passed
+pub fn require_destination_not_frozen<'a, 'info>(
+    limit_order: &LimitOrder,
+    recorded_destination: &'a AccountInfo<'info>,
+    creator_destination_ata: Option<&'a AccountInfo<'info>>,
+) -> Result<()> {
+    if limit_order.unwrap_output {
+        return Ok(());
+    }
+    let destination = if !recorded_destination.data_is_empty() {
+        recorded_destination
+    } else {
+        match creator_destination_ata {
+            Some(ata) if !ata.data_is_empty() => ata,
+            // Created by the fill itself, so it cannot be frozen yet
+            _ => return Ok(()),
+        }
+    };
+
+    // Anything that does not deserialize is left for resolve_user_destination to reject
+    let data = destination.try_borrow_data()?;
+    if let Ok(token_account) = TokenAccount::try_deserialize(&mut &data[..]) {
+        require!(!token_account.is_frozen(), ErrorCode::DestinationFrozen);
+    }
+    Ok(())
+}
+
+/// Checks the accounts a native SOL delivery needs: the order's transient
+/// ["unwrap_account", limit_order] token account and its recorded user_lamport_destination
+///
+/// # Arguments
+/// * `limit_order` - Order being executed (must have a WSOL output)
+/// * `limit_order_key` - Address of the order
+/// * `unwrap_account` - Transient token account passed by the operator
+/// * `lamport_destination` - System account passed by the operator
+/// * `program_id` - This program, owner of the unwrap account PDA
+///
+/// # Returns
+/// * `Result<u8>` - Bump of the unwrap account PDA
+pub fn check_unwrap_accounts(
+    limit_order: &LimitOrder,
+    limit_order_key: &Pubkey,
+    unwrap_account: Option<&Pubkey>,
+    lamport_destination: Option<&Pubkey>,
+    program_id: &Pubkey,
+) -> Result<u8> {
+    require!(limit_order.output_mint == anchor_spl::token::spl_token::native_mint::ID, ErrorCode::InvalidMint);
+    let (unwrap_account, lamport_destination) = match (unwrap_account, lamport_destination) {
+        (Some(unwrap_account), Some(lamport_destination)) => (unwrap_account, lamport_destination),
+        _ => return Err(ErrorCode::InvalidUnwrapAccount.into()),
+    };
+    let (expected_unwrap_account, bump) =
+        Pubkey::find_program_address(&[b"unwrap_account", limit_order_key.as_ref()], program_id);
+    require!(*unwrap_account == expected_unwrap_account, ErrorCode::InvalidUnwrapAccount);
+    require!(*lamport_destination == limit_order.user_lamport_destination, ErrorCode::InvalidUnwrapAccount);
+    Ok(bump)
+}
+
+/// Creates a PDA owned by `owner` with `space` bytes, funding it from `payer` up to rent exemption
+/// Funds, allocates and assigns rather than using create_account, which fails if anyone has sent
+/// lamports to the address first; lamports already there count toward the rent
+///
+/// # Arguments
+/// * `payer` - Signer funding the missing rent
+/// * `account` - The PDA to create
+/// * `system_program` - System program
+/// * `space` - Account data length
+/// * `owner` - Program the account is assigned to
+/// * `seeds` - Signer seeds of the PDA, bump included
+pub fn create_pda_account<'info>(
+    payer: &AccountInfo<'info>,
+    account: &AccountInfo<'info>,
+    system_program: &AccountInfo<'info>,
+    space: usize,
+    owner: &Pubkey,
+    seeds: &[&[u8]],
+) -> Result<()> {
+    let rent_due = Rent::get()?.minimum_balance(space).saturating_sub(account.lamports());
+    if rent_due > 0 {
+        anchor_lang::system_program::transfer(
+            CpiContext::new(
+                system_program.clone(),
+                anchor_lang::system_program::Transfer {
+                    from: payer.clone(),
+                    to: account.clone(),
+                },
+            ),
+            rent_due,
+        )?;
+    }
+    anchor_lang::system_program::allocate(
+        CpiContext::new_with_signer(
+            system_program.clone(),
+            anchor_lang::system_program::Allocate { account_to_allocate: account.clone() },
+            &[seeds],
+        ),
+        space as u64,
+    )?;
+    anchor_lang::system_program::assign(
+        CpiContext::new_with_signer(
+            system_program.clone(),
+            anchor_lang::system_program::Assign { account_to_assign: account.clone() },
+            &[seeds],
+        ),
+        owner,
+    )?;
+    Ok(())
+}
+
+/// Delivers an order's WSOL output as native SOL
+/// A native token account only releases its lamports when closed, so the output moves from the
+/// destination vault into the order's transient unwrap account, which is closed to the operator;
+/// the operator then forwards `amount` lamports to user_lamport_destination. The operator fronts
+/// the unwrap account's rent and gets it back in the same instruction.
+///
+/// # Arguments
+/// * `limit_order` - Order being executed
+/// * `destination_vault` - Vault holding the order's output
+/// * `unwrap_account` - Transient ["unwrap_account", limit_order] token account
+/// * `lamport_destination` - Creator's system account recorded on the order
+/// * `output_mint` - Native mint
+/// * `output_token_program` - SPL Token program
+/// * `vault_authority` - Authority of the vaults
+/// * `operator` - Executing operator, paying and recovering the unwrap account rent
+/// * `system_program` - System program for the account creation and lamport transfer
+/// * `program_id` - This program
+/// * `amount` - Output to deliver, after fees
+/// * `signer_seeds` - Vault authority signer seeds
+///
+/// # Returns
+/// * `Result<AccountInfo>` - The lamport destination, reported as the fill's recipient
+#[allow(clippy::too_many_arguments)]
+pub fn deliver_unwrapped_output<'info>(
+    limit_order: &Account<'info, LimitOrder>,
+    destination_vault: &AccountInfo<'info>,
+    unwrap_account: Option<&AccountInfo<'info>>,
+    lamport_destination: Option<&AccountInfo<'info>>,
+    output_mint: &InterfaceAccount<'info, Mint>,
+    output_token_program: &AccountInfo<'info>,
+    vault_authority: &AccountInfo<'info>,
+    operator: &AccountInfo<'info>,
+    system_program: &AccountInfo<'info>,
+    program_id: &Pubkey,
+    amount: u64,
+    signer_seeds: &[&[&[u8]]],
+) -> Result<AccountInfo<'info>> {
+    let limit_order_key = limit_order.key();
+    let unwrap_bump = check_unwrap_accounts(
+        limit_order,
+        &limit_order_key,
+        unwrap_account.map(|account| account.key),
+        lamport_destination.map(|account| account.key),
+        program_id,
+    )?;
+    let (unwrap_account, lamport_destination) = match (unwrap_account, lamport_destination) {
+        (Some(unwrap_account), Some(lamport_destination)) => (unwrap_account, lamport_destination),
+        _ => return Err(ErrorCode::InvalidUnwrapAccount.into()),
+    };
+    require!(*output_token_program.key == anchor_spl::token::ID, ErrorCode::InvalidTokenProgram);
+
+    // Base token account size is 165 bytes; the native mint has no extensions
+    let unwrap_space: usize = 165;
+    let unwrap_seeds: &[&[u8]] = &[b"unwrap_account", limit_order_key.as_ref(), &[unwrap_bump]];
+    create_pda_account(operator, unwrap_account, system_program, unwrap_space, output_token_program.key, unwrap_seeds)?;
+    initialize_account3(CpiContext::new(
+        output_token_program.clone(),
+        InitializeAccount3 {
+            account: unwrap_account.clone(),
+            mint: output_mint.to_account_info(),
+            authority: vault_authority.clone(),
+        },
+    ))?;
+
+    transfer_checked(
+        CpiContext::new_with_signer(
+            output_token_program.clone(),
+            TransferChecked {
+                from: destination_vault.clone(),
+                to: unwrap_account.clone(),
+                authority: vault_authority.clone(),
+                mint: output_mint.to_account_info(),
+            },
+            signer_seeds,
+        ),
+        amount,
+        output_mint.decimals,
+    )?;
+
+    // Closing releases the amount plus the rent the operator put up; the operator keeps the rent
+    close_account(CpiContext::new_with_signer(
+        output_token_program.clone(),
+        CloseAccount {
+            account: unwrap_account.clone(),
+            destination: operator.clone(),
+            authority: vault_authority.clone(),
+        },
+        signer_seeds,
+    ))?;
+
+    anchor_lang::system_program::transfer(
+        CpiContext::new(
+            system_program.clone(),
+            anchor_lang::system_program::Transfer {
+                from: operator.clone(),
+                to: lamport_destination.clone(),
+            },
+        ),
+        amount,
+    )?;
+
+    msg!("Delivered {} lamports of unwrapped output to {}", amount, lamport_destination.key());
+    Ok(lamport_destination.clone())
+}
+
+/// Checks execute_limit_order runs before any transfer or CPI, shared with
+/// validate_order_execution so the dry-run cannot drift from the real execution
+///
+/// Covers expiry and activation, the trigger against the quote, the route's input vault
+/// (route_plan[0] must read from the order vault, so an operator cannot drain another
+/// order's vault), route validation and the destination vault lookup.
+///
+/// # Returns
+/// * `Result<&AccountInfo>` - The destination vault: the last remaining account holding the output mint
+#[allow(clippy::too_many_arguments)]
+pub fn validate_execution_prelude<'info>(
+    limit_order: &Account<'info, LimitOrder>,
+    adapter_registry: &Account<'info, AdapterRegistry>,
+    vault_authority: &AccountInfo<'info>,
+    input_vault: &Pubkey,
+    input_token_program: &AccountInfo<'info>,
+    output_token_program: &AccountInfo<'info>,
+    input_mint: &AccountInfo<'info>,
+    output_mint: &AccountInfo<'info>,
+    route_plan: &[RoutePlanStep],
+    remaining_accounts: &'info [AccountInfo<'info>],
+    program_id: &Pubkey,
+    quoted_out_amount: u64,
+    now: i64,
+) -> Result<&'info AccountInfo<'info>> {
+    if now >= limit_order.expiry {
+        return Err(ErrorCode::OrderExpired.into());
+    }
+    require!(limit_order.is_active(now), ErrorCode::OrderNotActiveYet);
+
+    // IMPORTANT: Check trigger price relative to min_output_amount
+    if !limit_order.should_execute(quoted_out_amount)? {
+        return Err(ErrorCode::TriggerPriceNotMet.into());
+    }
+
+    // SECURITY: route_plan[0].input_index must point at this order's input vault
+    if route_plan.is_empty() {
+        return Err(ErrorCode::EmptyRoute.into());
+    }
+    let route_input_vault = remaining_accounts
+        .get(route_plan[0].input_index as usize)
+        .ok_or(ErrorCode::InvalidAccountIndex)?;
+    if route_input_vault.key() != *input_vault {
+        return Err(ErrorCode::InvalidVaultAddress.into());
+    }
+    // Nor may any later step of the input mint read another order's vault
+    let steps = route_validator_module::resolve_route_steps(route_plan, remaining_accounts)?;
+    route_validator_module::validate_order_step_inputs(&steps, input_vault, &input_mint.key())?;
+
+    route_validator_module::validate_route(
+        adapter_registry,
+        input_token_program,
+        output_token_program,
+        vault_authority,
+        input_mint,
+        output_mint,
+        route_plan,
+        remaining_accounts,
+        program_id,
+        limit_order.input_amount,
+        &limit_order.creator,
+        false,
+    )?;
+
+    // Find destination vault for output tokens
+    let destination_vault = remaining_accounts
+        .iter()
+        .rev()
+        .find(|acc| {
+            route_validator_module::scan_token_account(acc)
+                .is_some_and(|token_account| token_account.mint == output_mint.key())
+        })
+        .ok_or(ErrorCode::VaultNotFound)?;
+    Ok(destination_vault)
+}
+
+/// Result of validate_order_execution, written to return data
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Debug, PartialEq, Eq)]
+pub struct OrderExecutionCheck {
+    /// Order that passed every pre-execution check
+    pub order: Pubkey,
+    /// Amount the route would swap (the order's input_amount)
+    pub input_amount: u64,
+    /// Remaining account the route would pay the output into
+    pub destination_vault: Pubkey,
+    /// Output the route must reach for the trigger to hold after the swap
+    pub trigger_threshold_amount: u64,
+    /// Least net output the slippage check accepts for this quote
+    pub min_acceptable_output: u64,
+}
+
+/// Accounts for validate_order_execution: the subset of ExecuteLimitOrder its checks read
+#[derive(Accounts)]
+pub struct ValidateOrderExecution<'info> {
+    #[account(
+        seeds = [b"adapter_registry"],
+        bump
+    )]
+    pub adapter_registry: Account<'info, AdapterRegistry>,
+
+    #[account(
+        seeds = [b"vault_authority"],
+        bump
+    )]
+    pub vault_authority: Account<'info, VaultAuthority>,
+
+    #[account(
+        constraint = limit_order.status == OrderStatus::Open @ ErrorCode::InvalidOrderStatus,
+        constraint = limit_order.input_vault == input_vault.key() @ ErrorCode::InvalidVaultAddress,
+        constraint = limit_order.input_token_program == input_token_program.key() @ ErrorCode::InvalidTokenProgram,
+        constraint = limit_order.output_token_program == output_token_program.key() @ ErrorCode::InvalidTokenProgram
+    )]
+    pub limit_order: Account<'info, LimitOrder>,
+
+    #[account(
+        constraint = input_vault.mint == limit_order.input_mint @ ErrorCode::InvalidMint
+    )]
+    pub input_vault: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    pub input_token_program: Interface<'info, TokenInterface>,
+    pub output_token_program: Interface<'info, TokenInterface>,
+
+    #[account(
+        constraint = input_mint.key() == limit_order.input_mint @ ErrorCode::InvalidMint
+    )]
+    pub input_mint: InterfaceAccount<'info, Mint>,
+    #[account(
+        constraint = output_mint.key() == limit_order.output_mint @ ErrorCode::InvalidMint
+    )]
+    pub output_mint: InterfaceAccount<'info, Mint>,
+}
+
+/// Dry-runs the checks of execute_limit_order without moving funds
+///
+/// Read-only, so keepers can simulate it for many orders at once to catch a wrong
+/// remaining_accounts layout before sending the real execution. Operator checks, the quote
+/// attestation and the post-swap trigger and slippage checks need the live swap and are not run.
+///
+/// # Arguments
+/// * `route_plan` - Route the operator intends to execute, with the same remaining_accounts
+/// * `quoted_out_amount` - Quote the execution would be sent with
+///
+/// # Returns
+/// * `Result<OrderExecutionCheck>` - The resolved destination vault and the output bounds
+pub fn validate_order_execution<'info>(
+    ctx: Context<'_, '_, 'info, 'info, ValidateOrderExecution<'info>>,
+    route_plan: Vec<RoutePlanStep>,
+    quoted_out_amount: u64,
+) -> Result<OrderExecutionCheck> {
+    let limit_order = &ctx.accounts.limit_order;
+    let destination_vault = validate_execution_prelude(
+        limit_order,
+        &ctx.accounts.adapter_registry,
+        &ctx.accounts.vault_authority.to_account_info(),
+        &ctx.accounts.input_vault.key(),
+        &ctx.accounts.input_token_program.to_account_info(),
+        &ctx.accounts.output_token_program.to_account_info(),
+        &ctx.accounts.input_mint.to_account_info(),
+        &ctx.accounts.output_mint.to_account_info(),
+        &route_plan,
+        ctx.remaining_accounts,
+        ctx.program_id,
+        quoted_out_amount,
+        current_unix_timestamp(ctx.remaining_accounts)?,
+    )?;
+
+    Ok(OrderExecutionCheck {
+        order: limit_order.key(),
+        input_amount: limit_order.input_amount,
+        destination_vault: destination_vault.key(),
+        trigger_threshold_amount: limit_order.calculate_trigger_threshold()?,
+        min_acceptable_output: limit_order.calculate_min_acceptable_output(quoted_out_amount)?,
+    })
+}
+
+/// Execute limit order instruction accounts
+#[event_cpi]
+#[derive(Accounts)]
+#[instruction(route_plan: Vec<RoutePlanStep>)]
+pub struct ExecuteLimitOrder<'info> {
+    /// Adapter registry for routing validation
+    #[account(
+        seeds = [b"adapter_registry"],
+        bump
+    )]
+    pub adapter_registry: Account<'info, AdapterRegistry>,
+
+    /// Vault authority controlling token transfers
+    #[account(
+        seeds = [b"vault_authority"],
+        bump
+    )]
+    pub vault_authority: Account<'info, VaultAuthority>,
+
+    /// Limit order to execute (closed with rent to the operator once its input vault closes)
+    #[account(
+        mut,
+        constraint = limit_order.status == OrderStatus::Open @ ErrorCode::InvalidOrderStatus,
+        constraint = limit_order.input_vault == input_vault.key() @ ErrorCode::InvalidVaultAddress,
+        constraint = limit_order.input_token_program == input_token_program.key() @ ErrorCode::InvalidTokenProgram,
+        constraint = limit_order.output_token_program == output_token_program.key() @ ErrorCode::InvalidTokenProgram
+    )]
+    pub limit_order: Account<'info, LimitOrder>,
+
+    /// Vault holding input tokens
+    #[account(
+        mut,
+        constraint = input_vault.mint == limit_order.input_mint @ ErrorCode::InvalidMint
+    )]
+    pub input_vault: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    /// Token program for input tokens
+    pub input_token_program: Interface<'info, TokenInterface>,
+    /// Token program for output tokens
+    pub output_token_program: Interface<'info, TokenInterface>,
+
+    /// User's destination account for output tokens, as recorded on the order
+    /// CHECK: May have been closed since creation; validated in resolve_user_destination
+    #[account(
+        mut,
+        constraint = user_destination_token_account.key() == limit_order.user_destination_account
+    )]
+    pub user_destination_token_account: UncheckedAccount<'info>,
+
+    /// Input token mint (must match limit_order.input_mint)
+    #[account(
+        constraint = input_mint.key() == limit_order.input_mint @ ErrorCode::InvalidMint
+    )]
+    pub input_mint: InterfaceAccount<'info, Mint>,
+    /// Output token mint (must match limit_order.output_mint)
+    #[account(
+        constraint = output_mint.key() == limit_order.output_mint @ ErrorCode::InvalidMint
+    )]
+    pub output_mint: InterfaceAccount<'info, Mint>,
+
+    /// Optional platform fee collection account
+    #[account(mut)]
+    pub platform_fee_account: Option<InterfaceAccount<'info, TokenAccount>>,
+
+    /// CHECK: FeeSchedule PDA of the input mint, applied once set_fee_schedule has created it
+    #[account(seeds = [b"fee_schedule", limit_order.input_mint.as_ref()], bump)]
+    pub fee_schedule: UncheckedAccount<'info>,
+
+    /// CHECK: ProtocolConfig PDA; nothing is paused until create_protocol_config has created it
+    #[account(seeds = [b"protocol_config"], bump)]
+    pub protocol_config: UncheckedAccount<'info>,
+
+    /// Protocol vault for the input mint, receiving input left in the order vault
+    #[account(
+        mut,
+        seeds = [b"vault", limit_order.input_mint.as_ref()],
+        bump
+    )]
+    pub dust_vault: Option<Box<InterfaceAccount<'info, TokenAccount>>>,
+
+    /// CHECK: ["receipt", limit_order] PDA, recorded into when the order was created with a receipt
+    #[account(
+        mut,
+        seeds = [b"receipt", limit_order.key().as_ref()],
+        bump
+    )]
+    pub order_receipt: UncheckedAccount<'info>,
+
+    /// CHECK: ["vault_rent_payer", input_vault] PDA, read when a sponsor funded the vault
+    #[account(
+        mut,
+        seeds = [b"vault_rent_payer", input_vault.key().as_ref()],
+        bump
+    )]
+    pub vault_rent_payer: UncheckedAccount<'info>,
+
+    /// Sponsor recorded in vault_rent_payer, receiving the vault rent (required when recorded)
+    /// CHECK: Must match the recorded payer, or the treasury its obligations were transferred to,
+    /// checked in vault_rent_destination
+    #[account(mut)]
+    pub rent_payer: Option<UncheckedAccount<'info>>,
+
+    /// Sponsoring operator's OperatorStats (required when an operator sponsored the vault)
+    /// CHECK: Must belong to the recorded payer, checked in vault_rent_destination
+    #[account(mut)]
+    pub rent_payer_stats: Option<UncheckedAccount<'info>>,
+
+    /// Instructions sysvar for quote attestation (required when the registry has a quote signer)
+    /// CHECK: address is constrained to the instructions sysvar
+    #[account(address = anchor_lang::solana_program::sysvar::instructions::ID)]
+    pub instructions_sysvar: Option<UncheckedAccount<'info>>,
+
+    /// Creator's output-mint ATA, created and used if the recorded destination was closed
+    /// CHECK: Must be the creator's ATA for the output mint, checked in resolve_user_destination
+    #[account(mut)]
+    pub creator_destination_ata: Option<UncheckedAccount<'info>>,
+
+    /// Order creator, owner of creator_destination_ata
+    /// CHECK: Must be the order creator, checked in resolve_user_destination
+    pub creator: Option<UncheckedAccount<'info>>,
+
+    /// Associated token program for creating creator_destination_ata
+    pub associated_token_program: Option<Program<'info, AssociatedToken>>,
+
+    /// Transient WSOL account ["unwrap_account", limit_order] (required when the order has unwrap_output)
+    /// CHECK: Created, filled and closed in deliver_unwrapped_output, which checks the address
+    #[account(mut)]
+    pub unwrap_account: Option<UncheckedAccount<'info>>,
+
+    /// Creator's system account receiving unwrapped output (required when the order has unwrap_output)
+    /// CHECK: Must be limit_order.user_lamport_destination, checked in deliver_unwrapped_output
+    #[account(mut)]
+    pub user_lamport_destination: Option<UncheckedAccount<'info>>,
+
+    /// Executing operator's liveness record, refreshed when passed
+    #[account(
+        mut,
+        seeds = [b"operator_stats", operator.key().as_ref()],
+        bump = operator_stats.bump
+    )]
+    pub operator_stats: Option<Account<'info, OperatorStats>>,
 
     /// Operator executing the order (must be registered, receives rent from closed order)
     #[account(
         mut,
-        signer,
-        constraint = adapter_registry.operators.contains(&operator.key()) @ ErrorCode::InvalidOperator
+        signer,
+        constraint = adapter_registry.is_active_operator(&operator.key()) @ ErrorCode::InvalidOperator
+    )]
+    pub operator: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+/// Executes a limit order when trigger conditions are met
+///
+/// # Arguments
+/// * `route_plan` - Swap route to execute
+/// * `quoted_out_amount` - Expected output amount from quote
+/// * `platform_fee_bps` - Platform fee in basis points
+/// * `quote_signature` - Ed25519 signature of the quote attestation (when a quote signer is configured)
+/// * `quote_publisher` - Key that signed the quote attestation (must be the registry quote signer)
+/// * `emit_step_events` - Per-step SwapEvents for a single-hop route (None = registry setting)
+///
+/// # Returns
+/// * `Result<u64>` - Final output amount after fees
+pub fn execute_limit_order<'info>(
+    ctx: Context<'_, '_, 'info, 'info, ExecuteLimitOrder<'info>>,
+    route_plan: Vec<RoutePlanStep>,
+    quoted_out_amount: u64,
+    platform_fee_bps: u8,
+    quote_signature: Option<[u8; 64]>,
+    quote_publisher: Option<Pubkey>,
+    emit_step_events: Option<bool>,
+) -> Result<u64> {
+    #[cfg(feature = "telemetry")]
+    let compute_meter = telemetry::ComputeMeter::start(telemetry::EXECUTE_LIMIT_ORDER_TELEMETRY_TAG);
+
+    let protocol_config = ProtocolConfig::load_config(&ctx.accounts.protocol_config, ctx.program_id)?;
+    protocol_config.require_execution_open()?;
+
+    let clock = Clock::get()?;
+    let now = current_unix_timestamp(ctx.remaining_accounts)?;
+
+    // A frozen destination would revert the final transfer after the whole route has run,
+    // so it is rejected before any route validation or swap work
+    require_destination_not_frozen(
+        &ctx.accounts.limit_order,
+        &ctx.accounts.user_destination_token_account,
+        ctx.accounts.creator_destination_ata.as_deref(),
+    )?;
+
+    // Same checks validate_order_execution runs; also resolves the destination vault
+    let destination_vault = validate_execution_prelude(
+        &ctx.accounts.limit_order,
+        &ctx.accounts.adapter_registry,
+        &ctx.accounts.vault_authority.to_account_info(),
+        &ctx.accounts.input_vault.key(),
+        &ctx.accounts.input_token_program.to_account_info(),
+        &ctx.accounts.output_token_program.to_account_info(),
+        &ctx.accounts.input_mint.to_account_info(),
+        &ctx.accounts.output_mint.to_account_info(),
+        &route_plan,
+        ctx.remaining_accounts,
+        ctx.program_id,
+        quoted_out_amount,
+        now,
+    )?;
+
+    // Require a quote attestation covering this order and quote if the registry has a quote signer
+    let instructions_sysvar = ctx.accounts.instructions_sysvar.as_ref().map(|sysvar| sysvar.to_account_info());
+    verify_quote_attestation(
+        &ctx.accounts.adapter_registry.quote_signer,
+        instructions_sysvar.as_ref(),
+        quote_signature,
+        quote_publisher,
+        &ctx.accounts.limit_order.key(),
+        quoted_out_amount,
+        clock.slot,
+    )?;
+
+    let in_amount = ctx.accounts.limit_order.input_amount;
+
+    // Prepare PDA signer seeds
+    let vault_authority_bump = ctx.bumps.vault_authority;
+    let authority_seeds: &[&[u8]] = &[
+        b"vault_authority".as_ref(),
+        &[vault_authority_bump],
+    ];
+    let signer_seeds: &[&[&[u8]]] = &[authority_seeds];
+
+    // Track the recorded destination and the creator ATA fallback, whichever receives the output
+    #[cfg(feature = "invariant-checks")]
+    let mut conservation = {
+        let mut tracked = vec![
+            ctx.accounts.input_vault.to_account_info(),
+            destination_vault.clone(),
+            ctx.accounts.user_destination_token_account.to_account_info(),
+        ];
+        if let Some(creator_destination_ata) = &ctx.accounts.creator_destination_ata {
+            tracked.push(creator_destination_ata.to_account_info());
+        }
+        if let Some(platform_fee_account) = &ctx.accounts.platform_fee_account {
+            tracked.push(platform_fee_account.to_account_info());
+        }
+        let mut check = invariants::ConservationCheck::start(tracked);
+        check.begin_swap();
+        check
+    };
+
+    emit_cpi!(RoutePlanEvent::from_plan(&route_plan, ctx.remaining_accounts)?);
+
+    // Execute swap route
+    let (mut output_amount, event_data, volume_warnings) = route_executor_module::execute_route(
+        &ctx.accounts.adapter_registry,
+        &ctx.accounts.input_token_program.to_account_info(),
+        &ctx.accounts.vault_authority.to_account_info(),
+        &ctx.accounts.input_mint.to_account_info(),
+        destination_vault,
+        &route_plan,
+        ctx.remaining_accounts,
+        ctx.program_id,
+        in_amount,
+        false,
+        protocol_config.pool_volume_warning_pct(),
+    )?;
+
+    #[cfg(feature = "invariant-checks")]
+    conservation.end_swap();
+
+    for warning in volume_warnings {
+        emit_cpi!(warning);
+    }
+
+    let venues = route_executor_module::route_venues(&event_data);
+
+    // Emit swap events; a single-hop SwapEvent may be skipped since LimitOrderSwapEvent repeats it
+    if ctx.accounts.adapter_registry.should_emit_step_events(event_data.len(), emit_step_events) {
+        for event in event_data {
+            emit_cpi!(SwapEvent {
+                amm: event.amm,
+                input_mint: event.input_mint,
+                input_amount: event.input_amount,
+                output_mint: event.output_mint,
+                output_amount: event.output_amount,
+                host_fee_amount: event.host_fee_amount,
+            });
+        }
+    }
+
+    // Verify actual output meets trigger condition
+    if !ctx.accounts.limit_order.should_execute(output_amount)? {
+        return Err(ErrorCode::TriggerPriceNotMet.into());
+    }
+
+    // Collect the platform fee; the fee account is only read when there is a fee to collect
+    let mut fee_amount = 0u64;
+    let mut fee_account: Option<Pubkey> = None;
+    let fee_schedule = FeeSchedule::load(&ctx.accounts.fee_schedule, ctx.program_id)?;
+    let fee_bps = ctx.accounts.limit_order.execution_fee_bps(fee_schedule.as_ref(), platform_fee_bps);
+    if let Some(platform_fee_account) = route_validator_module::charged_fee_account(fee_bps, ctx.accounts.platform_fee_account.as_ref())? {
+        if platform_fee_account.mint != ctx.accounts.output_mint.key() {
+            return Err(ErrorCode::InvalidPlatformFeeMint.into());
+        }
+        route_validator_module::validate_fee_account_authorities(
+            platform_fee_account,
+            &ctx.accounts.vault_authority.key(),
+        )?;
+
+        fee_amount = math::apply_bps_fee(output_amount, fee_bps.into())?;
+        if fee_amount > 0 {
+            transfer_checked(
+                CpiContext::new_with_signer(
+                    ctx.accounts.output_token_program.to_account_info(),
+                    TransferChecked {
+                        from: destination_vault.clone(),
+                        to: platform_fee_account.to_account_info(),
+                        authority: ctx.accounts.vault_authority.to_account_info(),
+                        mint: ctx.accounts.output_mint.to_account_info(),
+                    },
+                    signer_seeds
+                ),
+                fee_amount,
+                ctx.accounts.output_mint.decimals,
+            )?;
+
+            emit_cpi!(FeeEvent {
+                account: platform_fee_account.key(),
+                mint: ctx.accounts.output_mint.key(),
+                amount: fee_amount,
+                net_amount: route_validator_module::net_transfer_amount(&ctx.accounts.output_mint.to_account_info(), fee_amount)?,
+                fee_bps,
+                source: FeeSource::LimitOrder,
+            });
+
+            fee_account = Some(platform_fee_account.key());
+            output_amount = output_amount.checked_sub(fee_amount).ok_or(ErrorCode::InvalidCalculation)?;
+        }
+    }
+
+    // Check slippage tolerance AFTER fees are deducted (consistent with swap_processor_module::route)
+    let min_acceptable = ctx.accounts.limit_order
+        .calculate_min_acceptable_output(quoted_out_amount)?;
+
+    // Compare what actually lands in the user account (net of any Token-2022 transfer fee)
+    let net_output_amount = route_validator_module::net_transfer_amount(
+        &ctx.accounts.output_mint.to_account_info(),
+        output_amount,
+    )?;
+    if net_output_amount < min_acceptable {
+        return Err(ErrorCode::SlippageToleranceExceeded.into());
+    }
+
+    let (user_destination, destination_rent) = if ctx.accounts.limit_order.unwrap_output {
+        // Deliver as native SOL to the creator's system account
+        let lamport_destination = deliver_unwrapped_output(
+            &ctx.accounts.limit_order,
+            destination_vault,
+            ctx.accounts.unwrap_account.as_deref(),
+            ctx.accounts.user_lamport_destination.as_deref(),
+            &ctx.accounts.output_mint,
+            &ctx.accounts.output_token_program.to_account_info(),
+            &ctx.accounts.vault_authority.to_account_info(),
+            &ctx.accounts.operator.to_account_info(),
+            &ctx.accounts.system_program.to_account_info(),
+            ctx.program_id,
+            output_amount,
+            signer_seeds,
+        )?;
+        (lamport_destination, 0)
+    } else {
+        // Deliver to the recorded destination, or the creator's ATA if it was closed
+        let (user_destination, created_rent) = resolve_user_destination(
+            &ctx.accounts.user_destination_token_account,
+            &ctx.accounts.limit_order,
+            ctx.accounts.creator_destination_ata.as_deref(),
+            ctx.accounts.creator.as_deref(),
+            ctx.accounts.associated_token_program.as_deref(),
+            &ctx.accounts.operator.to_account_info(),
+            &ctx.accounts.output_mint.to_account_info(),
+            &ctx.accounts.output_token_program.to_account_info(),
+            &ctx.accounts.system_program.to_account_info(),
+        )?;
+        refund_destination_rent(
+            &ctx.accounts.limit_order.to_account_info(),
+            &ctx.accounts.operator.to_account_info(),
+            created_rent,
+        )?;
+
+        // Transfer output tokens to user's destination account
+        transfer_checked(
+            CpiContext::new_with_signer(
+                ctx.accounts.output_token_program.to_account_info(),
+                TransferChecked {
+                    from: destination_vault.clone(),
+                    to: user_destination.clone(),
+                    authority: ctx.accounts.vault_authority.to_account_info(),
+                    mint: ctx.accounts.output_mint.to_account_info(),
+                },
+                signer_seeds
+            ),
+            output_amount,
+            ctx.accounts.output_mint.decimals,
+        )?;
+        (user_destination, created_rent)
+    };
+
+    // Unwrapped output leaves the tracked token accounts as lamports
+    #[cfg(feature = "invariant-checks")]
+    {
+        let output_mint = ctx.accounts.output_mint.to_account_info();
+        let mut allowances = vec![invariants::transfer_fee_allowance(&output_mint, &[fee_amount, output_amount])?];
+        if ctx.accounts.limit_order.unwrap_output {
+            allowances.push((output_mint.key(), output_amount));
+        }
+        conservation.finish(&allowances)?;
+    }
+
+    // Update order status to filled
+    ctx.accounts.limit_order.status = OrderStatus::Filled;
+
+    OrderReceipt::update(&ctx.accounts.order_receipt.to_account_info(), ctx.program_id, |receipt| {
+        receipt.record(OrderStatus::Filled, output_amount, fee_amount, ctx.accounts.operator.key(), now)
+    })?;
+
+    let effective_price = ctx.accounts.limit_order.calculate_effective_price(output_amount)?;
+    let trigger_threshold_amount = ctx.accounts.limit_order.calculate_trigger_threshold()?;
+    let execution_premium_bps = ctx.accounts.limit_order.calculate_execution_premium_bps(output_amount)?;
+
+    // Emit order execution event (full price report, emitted before the order account closes)
+    emit_cpi!(LimitOrderExecuted {
+        order: ctx.accounts.limit_order.key(),
+        executor: ctx.accounts.operator.key(),
+        input_amount: in_amount,
+        output_amount,
+        fee_amount,
+        trigger_type: ctx.accounts.limit_order.trigger_type as u8,
+        min_output_amount: ctx.accounts.limit_order.min_output_amount,
+        venues,
+        effective_price,
+        fee_account,
+        quoted_out_amount,
+        trigger_threshold_amount,
+        execution_premium_bps,
+    });
+    emit_cpi!(ctx.accounts.limit_order.open_interest_change(false));
+
+    // Emit global limit order swap event
+    emit_cpi!(LimitOrderSwapEvent {
+        order: ctx.accounts.limit_order.key(),
+        sender: ctx.accounts.limit_order.creator,
+        recipient: user_destination.key(),
+        executor: ctx.accounts.operator.key(),
+        input_mint: ctx.accounts.input_mint.key(),
+        output_mint: ctx.accounts.output_mint.key(),
+        input_amount: in_amount,
+        output_amount,
+        fee_amount,
+        fee_account,
+        trigger_type: ctx.accounts.limit_order.trigger_type as u8,
+        net_output_amount,
+    });
+
+    // Close input_vault and the order, returning rent to operator. Best effort: the fill
+    // above is final even if the vault has to wait for close_limit_order_by_operator
+    close_filled_order(
+        &ctx.accounts.limit_order,
+        &ctx.accounts.input_vault.to_account_info(),
+        ctx.accounts.dust_vault.as_ref().map(|vault| vault.to_account_info()).as_ref(),
+        &ctx.accounts.order_receipt.to_account_info(),
+        &ctx.accounts.input_mint,
+        &ctx.accounts.input_token_program.to_account_info(),
+        &ctx.accounts.vault_authority.to_account_info(),
+        &ctx.accounts.operator.to_account_info(),
+        &ctx.accounts.vault_rent_payer.to_account_info(),
+        ctx.accounts.rent_payer.as_ref().map(|payer| payer.to_account_info()).as_ref(),
+        ctx.accounts.rent_payer_stats.as_ref().map(|stats| stats.to_account_info()).as_ref(),
+        destination_rent,
+        ctx.program_id,
+        signer_seeds,
+    )?;
+
+    // Execution counts as operator activity; only standalone heartbeats are throttled.
+    // An operator filling an order it sponsored has just had that obligation settled in its
+    // own stats, so they are reloaded first and the exit does not write back a stale copy
+    if let Some(operator_stats) = ctx.accounts.operator_stats.as_mut() {
+        operator_stats.reload()?;
+        operator_stats.last_active = now;
+    }
+
+    #[cfg(feature = "telemetry")]
+    emit_cpi!(compute_meter.finish(route_plan.len()));
+
+    Ok(output_amount)
+}
+
+/// Cancel limit order instruction accounts
+/// Supports cancellation of orders in Init, Open or NeedsReview status
+#[event_cpi]
+#[derive(Accounts)]
+pub struct CancelLimitOrder<'info> {
+    /// Vault authority controlling token transfers
+    #[account(
+        seeds = [b"vault_authority"],
+        bump
+    )]
+    pub vault_authority: Account<'info, VaultAuthority>,
+
+    /// Limit order to cancel (will be closed, rent goes to creator)
+    /// Can be cancelled in Init status (no tokens transferred yet) or Open/NeedsReview status (tokens in vault)
+    #[account(
+        mut,
+        close = creator,
+        constraint = matches!(limit_order.status, OrderStatus::Init | OrderStatus::Open | OrderStatus::NeedsReview) @ ErrorCode::InvalidOrderStatus,
+        constraint = limit_order.creator == creator.key() @ ErrorCode::UnauthorizedAdmin,
+        constraint = limit_order.input_token_program == input_token_program.key() @ ErrorCode::InvalidTokenProgram
+    )]
+    pub limit_order: Account<'info, LimitOrder>,
+
+    /// Vault holding input tokens
+    /// For Init status, vault exists but is empty (no tokens transferred yet)
+    /// For Open status, vault contains input tokens
+    #[account(
+        mut,
+        constraint = input_vault.key() == limit_order.input_vault @ ErrorCode::InvalidVaultAddress
+    )]
+    pub input_vault: InterfaceAccount<'info, TokenAccount>,
+
+    /// Account to receive refunded tokens, chosen by the creator
+    /// Any token account of the order's input mint is accepted (e.g. a multisig-owned account
+    /// after the creator closed their ATA); only the creator's signature can pick it
+    /// Only used for Open status when refunding tokens
+    /// For Init status, this is still required but won't be used
+    #[account(
+        mut,
+        constraint = user_input_token_account.mint == limit_order.input_mint @ ErrorCode::InvalidMint
+    )]
+    pub user_input_token_account: InterfaceAccount<'info, TokenAccount>,
+
+    /// Input token mint
+    /// Only used for Open status when refunding tokens
+    /// For Init status, this is still required but won't be used
+    pub input_mint: InterfaceAccount<'info, Mint>,
+    /// Token program for input tokens
+    pub input_token_program: Interface<'info, TokenInterface>,
+
+    /// CHECK: ["receipt", limit_order] PDA, recorded into when the order was created with a receipt
+    #[account(
+        mut,
+        seeds = [b"receipt", limit_order.key().as_ref()],
+        bump
+    )]
+    pub order_receipt: UncheckedAccount<'info>,
+
+    /// CHECK: ["vault_rent_payer", input_vault] PDA, read when a sponsor funded the vault
+    #[account(
+        mut,
+        seeds = [b"vault_rent_payer", input_vault.key().as_ref()],
+        bump
+    )]
+    pub vault_rent_payer: UncheckedAccount<'info>,
+
+    /// Sponsor recorded in vault_rent_payer, receiving the vault rent (required when recorded)
+    /// CHECK: Must match the recorded payer, or the treasury its obligations were transferred to,
+    /// checked in vault_rent_destination
+    #[account(mut)]
+    pub rent_payer: Option<UncheckedAccount<'info>>,
+
+    /// Sponsoring operator's OperatorStats (required when an operator sponsored the vault)
+    /// CHECK: Must belong to the recorded payer, checked in vault_rent_destination
+    #[account(mut)]
+    pub rent_payer_stats: Option<UncheckedAccount<'info>>,
+
+    /// Order creator (must sign, receives rent from closed input_vault)
+    #[account(mut, signer)]
+    pub creator: Signer<'info>,
+}
+
+/// Cancels a limit order in Init, Open or NeedsReview status and refunds tokens to creator
+pub fn cancel_limit_order(ctx: Context<CancelLimitOrder>) -> Result<()> {
+    // Prepare PDA signer seeds
+    let vault_authority_bump = ctx.bumps.vault_authority;
+    let authority_seeds: &[&[u8]] = &[
+        b"vault_authority".as_ref(),
+        &[vault_authority_bump],
+    ];
+    let signer_seeds: &[&[&[u8]]] = &[authority_seeds];
+
+    // Vault rent goes to the creator unless a sponsor funded the vault
+    let rent_destination = vault_rent_destination(
+        &ctx.accounts.vault_rent_payer.to_account_info(),
+        ctx.accounts.rent_payer.as_ref().map(|payer| payer.to_account_info()).as_ref(),
+        ctx.accounts.rent_payer_stats.as_ref().map(|stats| stats.to_account_info()).as_ref(),
+        &ctx.accounts.creator.to_account_info(),
+        ctx.program_id,
+    )?;
+
+    // Handle cancellation based on order status; Open orders report where tokens were refunded
+    let refund_account = match ctx.accounts.limit_order.status {
+        OrderStatus::Init => {
+            // For Init status: input_amount is 0, no tokens were transferred
+            // Vault exists but is empty, so we can close it to recover rent
+            // Always close vault for Init status (it's always empty)
+            // Verify vault is empty before closing
+            require!(
+                ctx.accounts.input_vault.amount == 0,
+                ErrorCode::InvalidAmount
+            );
+            
+            close_account(
+                CpiContext::new_with_signer(
+                    ctx.accounts.input_token_program.to_account_info(),
+                    CloseAccount {
+                        account: ctx.accounts.input_vault.to_account_info(),
+                        destination: rent_destination.clone(),
+                        authority: ctx.accounts.vault_authority.to_account_info(),
+                    },
+                    signer_seeds
+                )
+            )?;
+
+            None
+        },
+        OrderStatus::Open | OrderStatus::NeedsReview => {
+            // For Open/NeedsReview status: input_amount > 0, refund tokens to creator
+            require!(
+                ctx.accounts.input_vault.mint == ctx.accounts.limit_order.input_mint,
+                ErrorCode::InvalidMint
+            );
+            require!(
+                ctx.accounts.input_mint.key() == ctx.accounts.limit_order.input_mint,
+                ErrorCode::InvalidMint
+            );
+            require!(
+                ctx.accounts.limit_order.input_amount > 0,
+                ErrorCode::InvalidAmount
+            );
+
+            // Refund input tokens to the creator's chosen destination
+            transfer_checked(
+                CpiContext::new_with_signer(
+                    ctx.accounts.input_token_program.to_account_info(),
+                    TransferChecked {
+                        from: ctx.accounts.input_vault.to_account_info(),
+                        to: ctx.accounts.user_input_token_account.to_account_info(),
+                        authority: ctx.accounts.vault_authority.to_account_info(),
+                        mint: ctx.accounts.input_mint.to_account_info(),
+                    },
+                    signer_seeds
+                ),
+                ctx.accounts.limit_order.input_amount,
+                ctx.accounts.input_mint.decimals,
+            )?;
+
+            // Close input_vault and return rent to creator (or the vault's sponsor)
+            // All tokens have been refunded to creator, so vault is empty
+            close_account(
+                CpiContext::new_with_signer(
+                    ctx.accounts.input_token_program.to_account_info(),
+                    CloseAccount {
+                        account: ctx.accounts.input_vault.to_account_info(),
+                        destination: rent_destination.clone(),
+                        authority: ctx.accounts.vault_authority.to_account_info(),
+                    },
+                    signer_seeds
+                )
+            )?;
+
+            Some(ctx.accounts.user_input_token_account.key())
+        },
+        _ => {
+            // Should not reach here due to constraint check
+            return Err(ErrorCode::InvalidOrderStatus.into());
+        }
+    };
+    close_vault_rent_payer(
+        &ctx.accounts.vault_rent_payer.to_account_info(),
+        &rent_destination,
+        ctx.accounts.rent_payer_stats.as_ref().map(|stats| stats.to_account_info()).as_ref(),
+        ctx.program_id,
+    )?;
+
+    let finalized_at = current_unix_timestamp(ctx.remaining_accounts)?;
+    OrderReceipt::update(&ctx.accounts.order_receipt.to_account_info(), ctx.program_id, |receipt| {
+        receipt.record(OrderStatus::Cancelled, 0, 0, ctx.accounts.creator.key(), finalized_at)
+    })?;
+
+    // Emit cancellation event before account is closed
+    emit_cpi!(LimitOrderCancelled {
+        order: ctx.accounts.limit_order.key(),
+        creator: ctx.accounts.creator.key(),
+        refund_account,
+    });
+    // Only a funded order (refunded above) held open interest
+    if refund_account.is_some() {
+        emit_cpi!(ctx.accounts.limit_order.open_interest_change(false));
+    }
+
+    // Account will be closed automatically and rent transferred to creator due to `close = creator`
+    // Note: Operator can also close cancelled orders if they weren't closed by creator
+    Ok(())
+}
+
+/// Splits a replacement of an order holding `old_amount` by one of `new_amount` into
+/// (carried vault to vault, topped up from the creator, refunded to the creator)
+pub fn replacement_amounts(old_amount: u64, new_amount: u64) -> (u64, u64, u64) {
+    let carried = old_amount.min(new_amount);
+    (carried, new_amount - carried, old_amount - carried)
+}
+
+/// Replace limit order instruction accounts
+/// The old order must be Open; the new one must have been initialized with init_limit_order
+#[event_cpi]
+#[derive(Accounts)]
+#[instruction(old_nonce: u64, new_nonce: u64)]
+pub struct ReplaceLimitOrder<'info> {
+    /// Vault authority controlling both order vaults
+    #[account(
+        seeds = [b"vault_authority"],
+        bump
+    )]
+    pub vault_authority: Account<'info, VaultAuthority>,
+
+    /// Adapter registry holding the order creation fee
+    #[account(
+        seeds = [b"adapter_registry"],
+        bump
+    )]
+    pub adapter_registry: Box<Account<'info, AdapterRegistry>>,
+
+    /// Order being replaced (will be closed, rent goes to creator)
+    #[account(
+        mut,
+        close = creator,
+        seeds = [b"limit_order", creator.key().as_ref(), old_nonce.to_le_bytes().as_ref()],
+        bump = old_order.bump,
+        constraint = old_order.status == OrderStatus::Open @ ErrorCode::InvalidOrderStatus,
+        constraint = old_order.creator == creator.key() @ ErrorCode::UnauthorizedAdmin,
+        constraint = old_order.input_mint == input_mint.key() @ ErrorCode::InvalidMint,
+        constraint = old_order.input_token_program == input_token_program.key() @ ErrorCode::InvalidTokenProgram
+    )]
+    pub old_order: Box<Account<'info, LimitOrder>>,
+
+    /// Vault of the old order (emptied and closed, rent goes to creator)
+    #[account(
+        mut,
+        constraint = old_input_vault.key() == old_order.input_vault @ ErrorCode::InvalidVaultAddress
+    )]
+    pub old_input_vault: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    /// Replacement order (must be initialized separately using init_limit_order)
+    #[account(
+        mut,
+        seeds = [b"limit_order", creator.key().as_ref(), new_nonce.to_le_bytes().as_ref()],
+        bump = new_order.bump,
+        constraint = new_order.status == OrderStatus::Init @ ErrorCode::InvalidOrderStatus,
+        constraint = new_order.creator == creator.key() @ ErrorCode::UnauthorizedAdmin
+    )]
+    pub new_order: Box<Account<'info, LimitOrder>>,
+
+    /// Vault of the replacement order
+    #[account(
+        mut,
+        constraint = new_input_vault.mint == input_mint.key() @ ErrorCode::InvalidMint,
+        constraint = new_input_vault.owner == vault_authority.key() @ ErrorCode::InvalidVaultOwner,
+        constraint = new_input_vault.key() == new_order.input_vault @ ErrorCode::InvalidVaultAddress
+    )]
+    pub new_input_vault: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    /// Creator's input token account: source of a size increase, destination of a decrease refund
+    #[account(
+        mut,
+        constraint = user_input_token_account.mint == input_mint.key() @ ErrorCode::InvalidMint,
+        constraint = user_input_token_account.owner == creator.key()
+    )]
+    pub user_input_token_account: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    /// Creator's destination account for the replacement order's output tokens
+    #[account(
+        constraint = user_destination_token_account.mint == output_mint.key(),
+        constraint = user_destination_token_account.owner == creator.key()
+    )]
+    pub user_destination_token_account: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    /// Input token mint (shared by both orders)
+    #[account(
+        constraint = input_mint.to_account_info().owner == &input_token_program.key() @ ErrorCode::InvalidTokenProgram
+    )]
+    pub input_mint: Box<InterfaceAccount<'info, Mint>>,
+    /// Output token mint of the replacement order
+    #[account(
+        constraint = output_mint.to_account_info().owner == &output_token_program.key() @ ErrorCode::InvalidTokenProgram
+    )]
+    pub output_mint: Box<InterfaceAccount<'info, Mint>>,
+    /// Token program for input tokens
+    pub input_token_program: Interface<'info, TokenInterface>,
+    /// Token program for output tokens (snapshotted on the replacement order)
+    pub output_token_program: Interface<'info, TokenInterface>,
+
+    /// CHECK: ["receipt", old_order] PDA, recorded into when the old order was created with a receipt
+    #[account(
+        mut,
+        seeds = [b"receipt", old_order.key().as_ref()],
+        bump
+    )]
+    pub old_order_receipt: UncheckedAccount<'info>,
+
+    /// CHECK: ["vault_rent_payer", old_input_vault] PDA, read when a sponsor funded the vault
+    #[account(
+        mut,
+        seeds = [b"vault_rent_payer", old_input_vault.key().as_ref()],
+        bump
     )]
-    pub operator: Signer<'info>,
+    pub vault_rent_payer: UncheckedAccount<'info>,
+
+    /// Sponsor recorded in vault_rent_payer, receiving the vault rent (required when recorded)
+    /// CHECK: Must match the recorded payer, or the treasury its obligations were transferred to,
+    /// checked in vault_rent_destination
+    #[account(mut)]
+    pub rent_payer: Option<UncheckedAccount<'info>>,
+
+    /// Sponsoring operator's OperatorStats (required when an operator sponsored the vault)
+    /// CHECK: Must belong to the recorded payer, checked in vault_rent_destination
+    #[account(mut)]
+    pub rent_payer_stats: Option<UncheckedAccount<'info>>,
+
+    /// Treasury receiving the order creation fee (required when the fee is non-zero)
+    /// CHECK: Must match adapter_registry.treasury, checked in collect_order_creation_fee
+    #[account(mut)]
+    pub treasury: Option<UncheckedAccount<'info>>,
+
+    /// CHECK: ProtocolConfig PDA; default limits apply until create_protocol_config has created it
+    #[account(seeds = [b"protocol_config"], bump)]
+    pub protocol_config: UncheckedAccount<'info>,
 
+    /// Order creator (must sign, receives rent from the closed old order and vault)
+    #[account(mut)]
+    pub creator: Signer<'info>,
+    /// Pays the order creation fee (may be the creator)
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    /// System program for the fee transfer
     pub system_program: Program<'info, System>,
 }
 
-/// Executes a limit order when trigger conditions are met
+/// Replaces an open limit order with a new one in a single instruction
 ///
-/// # Arguments
-/// * `route_plan` - Swap route to execute
-/// * `quoted_out_amount` - Expected output amount from quote
-/// * `platform_fee_bps` - Platform fee in basis points
+/// Tokens move from the old vault to the new vault without passing through the creator's
+/// account; a larger order is topped up from user_input_token_account and a smaller one
+/// refunds the difference to it. The old order and vault are closed to the creator.
 ///
-/// # Returns
-/// * `Result<u64>` - Final output amount after fees
-pub fn execute_limit_order<'info>(
-    ctx: Context<'_, '_, 'info, 'info, ExecuteLimitOrder<'info>>,
-    route_plan: Vec<RoutePlanStep>,
-    quoted_out_amount: u64,
-    platform_fee_bps: u8,
-) -> Result<u64> {
-    // Check if order has expired
-    let now = Clock::get()?.unix_timestamp;
-    if now >= ctx.accounts.limit_order.expiry {
-        return Err(ErrorCode::OrderExpired.into());
+/// # Arguments
+/// * `old_nonce` - Nonce of the Open order being replaced
+/// * `new_nonce` - Nonce of the replacement order (in Init status)
+/// * `input_amount` - Input amount of the replacement order
+/// * remaining arguments - As for create_limit_order
+#[allow(clippy::too_many_arguments)]
+pub fn replace_limit_order(
+    ctx: Context<ReplaceLimitOrder>,
+    _old_nonce: u64,
+    _new_nonce: u64,
+    input_amount: u64,
+    min_output_amount: u64,
+    trigger_price_bps: u32,
+    trigger_type: TriggerType,
+    expiry: i64,
+    slippage_bps: u16,
+    activate_at: i64,
+) -> Result<()> {
+    // Validate the replacement order's parameters as create_limit_order does
+    if input_amount == 0 || min_output_amount == 0 {
+        return Err(ErrorCode::InvalidAmount.into());
     }
-
-    // IMPORTANT: Check trigger price relative to min_output_amount
-    if !ctx.accounts.limit_order.should_execute(quoted_out_amount)? {
-        return Err(ErrorCode::TriggerPriceNotMet.into());
+    require_distinct_order_mints(&ctx.accounts.input_mint.key(), &ctx.accounts.output_mint.key())?;
+    // Opening the replacement is an order creation, so the creation pause applies
+    let protocol_config = ProtocolConfig::load_config(&ctx.accounts.protocol_config, ctx.program_id)?;
+    protocol_config.require_creation_open()?;
+    let limits = protocol_config.limits;
+    validate_trigger_price(&limits, trigger_type, trigger_price_bps)?;
+    let now = current_unix_timestamp(ctx.remaining_accounts)?;
+    if expiry <= now {
+        return Err(ErrorCode::InvalidExpiry.into());
     }
+    limits.check_order_duration(expiry, now)?;
+    require!(
+        activate_at >= 0 && activate_at < expiry,
+        ErrorCode::InvalidActivationTime
+    );
+    limits.check_order_slippage(slippage_bps)?;
 
-    let in_amount = ctx.accounts.limit_order.input_amount;
-
-    // SECURITY: Validate that route_plan[0].input_index points to the correct input vault
-    // This prevents operators from draining other orders' vaults by pointing to a different vault
-    if route_plan.is_empty() {
-        return Err(ErrorCode::EmptyRoute.into());
-    }
-    let route_input_index = route_plan[0].input_index as usize;
-    if route_input_index >= ctx.remaining_accounts.len() {
-        return Err(ErrorCode::InvalidAccountIndex.into());
-    }
-    let route_input_vault = &ctx.remaining_accounts[route_input_index];
-    if route_input_vault.key() != ctx.accounts.input_vault.key() {
-        return Err(ErrorCode::InvalidVaultAddress.into());
-    }
+    let old_amount = ctx.accounts.old_order.input_amount;
+    require!(old_amount > 0, ErrorCode::InvalidAmount);
 
-    // Validate swap route
-    route_validator_module::validate_route(
+    collect_order_creation_fee(
         &ctx.accounts.adapter_registry,
-        &ctx.accounts.input_token_program.to_account_info(),
-        &ctx.accounts.output_token_program.to_account_info(),
-        &ctx.accounts.vault_authority.to_account_info(),
-        &ctx.accounts.input_mint.to_account_info(),
-        &ctx.accounts.output_mint.to_account_info(),
-        &route_plan,
-        ctx.remaining_accounts,
-        ctx.program_id,
-        in_amount,
+        &ctx.accounts.payer.to_account_info(),
+        ctx.accounts.treasury.as_deref(),
+        &ctx.accounts.system_program.to_account_info(),
     )?;
 
-    // Prepare PDA signer seeds
     let vault_authority_bump = ctx.bumps.vault_authority;
     let authority_seeds: &[&[u8]] = &[
         b"vault_authority".as_ref(),
@@ -602,307 +2940,306 @@ pub fn execute_limit_order<'info>(
     ];
     let signer_seeds: &[&[&[u8]]] = &[authority_seeds];
 
-    // Find destination vault for output tokens
-    let destination_vault = ctx.remaining_accounts
-        .iter()
-        .rev()
-        .find(|acc| {
-            if let Ok(account_data) = acc.try_borrow_data() {
-                if let Ok(token_account) = TokenAccount::try_deserialize(&mut account_data.as_ref()) {
-                    token_account.mint == ctx.accounts.output_mint.key()
-                } else {
-                    false
-                }
-            } else {
-                false
-            }
-        })
-        .ok_or(ErrorCode::VaultNotFound)?;
-
-    // Execute swap route
-    let (mut output_amount, event_data) = route_executor_module::execute_route(
-        &ctx.accounts.adapter_registry,
-        &ctx.accounts.input_token_program.to_account_info(),
-        &ctx.accounts.vault_authority.to_account_info(),
-        &ctx.accounts.input_mint.to_account_info(),
-        destination_vault,
-        &route_plan,
-        ctx.remaining_accounts,
-        ctx.program_id,
-        in_amount,
-    )?;
-
-    // Emit swap events
-    for event in event_data {
-        emit_cpi!(SwapEvent {
-            amm: event.amm,
-            input_mint: event.input_mint,
-            input_amount: event.input_amount,
-            output_mint: event.output_mint,
-            output_amount: event.output_amount,
-        });
-    }
-
-    // Verify actual output meets trigger condition
-    if !ctx.accounts.limit_order.should_execute(output_amount)? {
-        return Err(ErrorCode::TriggerPriceNotMet.into());
-    }
-
-    // Collect platform fee if specified
-    let mut fee_amount = 0u64;
-    let mut fee_account: Option<Pubkey> = None;
-    if let Some(platform_fee_account) = &ctx.accounts.platform_fee_account {
-        if platform_fee_account.mint != ctx.accounts.output_mint.key() {
-            return Err(ErrorCode::InvalidPlatformFeeMint.into());
-        }
-
-        fee_amount = (output_amount as u128 * platform_fee_bps as u128 / 10_000) as u64;
-        if fee_amount > 0 {
-            transfer_checked(
-                CpiContext::new_with_signer(
-                    ctx.accounts.output_token_program.to_account_info(),
-                    TransferChecked {
-                        from: destination_vault.clone(),
-                        to: platform_fee_account.to_account_info(),
-                        authority: ctx.accounts.vault_authority.to_account_info(),
-                        mint: ctx.accounts.output_mint.to_account_info(),
-                    },
-                    signer_seeds
-                ),
-                fee_amount,
-                ctx.accounts.output_mint.decimals,
-            )?;
-
-            emit_cpi!(FeeEvent {
-                account: platform_fee_account.key(),
-                mint: ctx.accounts.output_mint.key(),
-                amount: fee_amount,
-            });
-
-            fee_account = Some(platform_fee_account.key());
-            output_amount = output_amount.checked_sub(fee_amount).ok_or(ErrorCode::InvalidCalculation)?;
-        }
-    }
-
-    // Check slippage tolerance AFTER fees are deducted (consistent with swap_processor_module::route)
-    let min_acceptable = ctx.accounts.limit_order
-        .calculate_min_acceptable_output(quoted_out_amount)?;
-
-    if output_amount < min_acceptable {
-        return Err(ErrorCode::SlippageToleranceExceeded.into());
-    }
+    let (carried, top_up, refund) = replacement_amounts(old_amount, input_amount);
+    let vault_balance_before = ctx.accounts.new_input_vault.amount;
 
-    // Transfer output tokens to user's destination account
+    // Move the tokens both orders share directly between the vaults
     transfer_checked(
         CpiContext::new_with_signer(
-            ctx.accounts.output_token_program.to_account_info(),
+            ctx.accounts.input_token_program.to_account_info(),
             TransferChecked {
-                from: destination_vault.clone(),
-                to: ctx.accounts.user_destination_token_account.to_account_info(),
+                from: ctx.accounts.old_input_vault.to_account_info(),
+                to: ctx.accounts.new_input_vault.to_account_info(),
                 authority: ctx.accounts.vault_authority.to_account_info(),
-                mint: ctx.accounts.output_mint.to_account_info(),
+                mint: ctx.accounts.input_mint.to_account_info(),
             },
             signer_seeds
         ),
-        output_amount,
-        ctx.accounts.output_mint.decimals,
+        carried,
+        ctx.accounts.input_mint.decimals,
     )?;
 
-    // Update order status to filled
-    ctx.accounts.limit_order.status = OrderStatus::Filled;
-
-    // Emit order execution event
-    emit_cpi!(LimitOrderExecuted {
-        order: ctx.accounts.limit_order.key(),
-        executor: ctx.accounts.operator.key(),
-        input_amount: in_amount,
-        output_amount,
-        fee_amount,
-        trigger_type: ctx.accounts.limit_order.trigger_type as u8,
-        min_output_amount: ctx.accounts.limit_order.min_output_amount,
-    });
+    if top_up > 0 {
+        transfer_checked(
+            CpiContext::new(
+                ctx.accounts.input_token_program.to_account_info(),
+                TransferChecked {
+                    from: ctx.accounts.user_input_token_account.to_account_info(),
+                    to: ctx.accounts.new_input_vault.to_account_info(),
+                    authority: ctx.accounts.creator.to_account_info(),
+                    mint: ctx.accounts.input_mint.to_account_info(),
+                },
+            ),
+            top_up,
+            ctx.accounts.input_mint.decimals,
+        )?;
+    }
 
-    // Emit global limit order swap event
-    emit_cpi!(LimitOrderSwapEvent {
-        order: ctx.accounts.limit_order.key(),
-        sender: ctx.accounts.limit_order.creator,
-        recipient: ctx.accounts.user_destination_token_account.key(),
-        executor: ctx.accounts.operator.key(),
-        input_mint: ctx.accounts.input_mint.key(),
-        output_mint: ctx.accounts.output_mint.key(),
-        input_amount: in_amount,
-        output_amount,
-        fee_amount,
-        fee_account,
-        trigger_type: ctx.accounts.limit_order.trigger_type as u8,
-    });
+    if refund > 0 {
+        transfer_checked(
+            CpiContext::new_with_signer(
+                ctx.accounts.input_token_program.to_account_info(),
+                TransferChecked {
+                    from: ctx.accounts.old_input_vault.to_account_info(),
+                    to: ctx.accounts.user_input_token_account.to_account_info(),
+                    authority: ctx.accounts.vault_authority.to_account_info(),
+                    mint: ctx.accounts.input_mint.to_account_info(),
+                },
+                signer_seeds
+            ),
+            refund,
+            ctx.accounts.input_mint.decimals,
+        )?;
+    }
 
-    // Close input_vault and return rent to operator
-    // All tokens have been transferred out via swap, so vault is empty
+    // The old vault is empty now; return its rent to the creator, or to the sponsor that funded it
+    let rent_destination = vault_rent_destination(
+        &ctx.accounts.vault_rent_payer.to_account_info(),
+        ctx.accounts.rent_payer.as_ref().map(|payer| payer.to_account_info()).as_ref(),
+        ctx.accounts.rent_payer_stats.as_ref().map(|stats| stats.to_account_info()).as_ref(),
+        &ctx.accounts.creator.to_account_info(),
+        ctx.program_id,
+    )?;
     close_account(
         CpiContext::new_with_signer(
             ctx.accounts.input_token_program.to_account_info(),
             CloseAccount {
-                account: ctx.accounts.input_vault.to_account_info(),
-                destination: ctx.accounts.operator.to_account_info(),
+                account: ctx.accounts.old_input_vault.to_account_info(),
+                destination: rent_destination.clone(),
                 authority: ctx.accounts.vault_authority.to_account_info(),
             },
             signer_seeds
         )
     )?;
+    close_vault_rent_payer(
+        &ctx.accounts.vault_rent_payer.to_account_info(),
+        &rent_destination,
+        ctx.accounts.rent_payer_stats.as_ref().map(|stats| stats.to_account_info()).as_ref(),
+        ctx.program_id,
+    )?;
 
-    Ok(output_amount)
+    // Record what arrived in the new vault: a Token-2022 transfer fee is withheld from each leg
+    ctx.accounts.new_input_vault.reload()?;
+    let input_amount = ctx.accounts.new_input_vault.amount
+        .checked_sub(vault_balance_before)
+        .ok_or(ErrorCode::InvalidCalculation)?;
+    require!(input_amount > 0, ErrorCode::InvalidAmount);
+
+    let order = &mut ctx.accounts.new_order;
+    order.input_mint = ctx.accounts.input_mint.key();
+    order.output_mint = ctx.accounts.output_mint.key();
+    order.user_destination_account = ctx.accounts.user_destination_token_account.key();
+    order.input_amount = input_amount;
+    order.min_output_amount = min_output_amount;
+    order.trigger_price_bps = trigger_price_bps;
+    order.trigger_type = trigger_type;
+    order.expiry = expiry;
+    order.status = OrderStatus::Open;
+    order.slippage_bps = slippage_bps;
+    order.failed_attempts = 0;
+    order.activate_at = activate_at;
+    order.execution_fee_bps = None;
+    order.unwrap_output = false;
+    order.user_lamport_destination = Pubkey::default();
+    order.input_token_program = ctx.accounts.input_token_program.key();
+    order.output_token_program = ctx.accounts.output_token_program.key();
+
+    let finalized_at = current_unix_timestamp(ctx.remaining_accounts)?;
+    OrderReceipt::update(&ctx.accounts.old_order_receipt.to_account_info(), ctx.program_id, |receipt| {
+        receipt.record(OrderStatus::Cancelled, 0, 0, ctx.accounts.creator.key(), finalized_at)
+    })?;
+
+    // Cancelled, then Created with `replaces` pointing back at the cancelled order
+    let old_order_key = ctx.accounts.old_order.key();
+    emit_cpi!(LimitOrderCancelled {
+        order: old_order_key,
+        creator: ctx.accounts.creator.key(),
+        refund_account: (refund > 0).then(|| ctx.accounts.user_input_token_account.key()),
+    });
+    emit_cpi!(ctx.accounts.old_order.open_interest_change(false));
+
+    let order = &ctx.accounts.new_order;
+    emit_cpi!(LimitOrderCreated {
+        order: order.key(),
+        creator: order.creator,
+        input_mint: order.input_mint,
+        output_mint: order.output_mint,
+        input_amount: order.input_amount,
+        min_output_amount: order.min_output_amount,
+        trigger_price_bps: order.trigger_price_bps,
+        trigger_type: order.trigger_type as u8,
+        expiry: order.expiry,
+        activate_at: order.activate_at,
+        generation: order.generation,
+        ui_amount_multiplier: route_validator_module::ui_amount_multiplier(&ctx.accounts.output_mint.to_account_info())?,
+        replaces: Some(old_order_key),
+    });
+    emit_cpi!(order.open_interest_change(true));
+
+    Ok(())
 }
 
-/// Cancel limit order instruction accounts
-/// Supports cancellation of orders in Init or Open status
+/// Flag order for review instruction accounts
 #[event_cpi]
 #[derive(Accounts)]
-pub struct CancelLimitOrder<'info> {
-    /// Vault authority controlling token transfers
+pub struct FlagOrderForReview<'info> {
+    /// Adapter registry whose authority confirms the flag
     #[account(
-        seeds = [b"vault_authority"],
-        bump
+        seeds = [b"adapter_registry"],
+        bump,
+        has_one = authority @ ErrorCode::InvalidAuthority
     )]
-    pub vault_authority: Account<'info, VaultAuthority>,
+    pub adapter_registry: Account<'info, AdapterRegistry>,
 
-    /// Limit order to cancel (will be closed, rent goes to creator)
-    /// Can be cancelled in Init status (no tokens transferred yet) or Open status (tokens in vault)
+    /// Limit order to pause (must be open)
     #[account(
         mut,
-        close = creator,
-        constraint = (limit_order.status == OrderStatus::Init || limit_order.status == OrderStatus::Open) @ ErrorCode::InvalidOrderStatus,
-        constraint = limit_order.creator == creator.key() @ ErrorCode::UnauthorizedAdmin
+        constraint = limit_order.status == OrderStatus::Open @ ErrorCode::InvalidOrderStatus
     )]
     pub limit_order: Account<'info, LimitOrder>,
 
-    /// Vault holding input tokens
-    /// For Init status, vault exists but is empty (no tokens transferred yet)
-    /// For Open status, vault contains input tokens
+    /// Registry authority confirming the flag. Operators choose the route, so they could produce
+    /// the zero-output failures themselves and cannot flag orders on their own.
+    pub authority: Signer<'info>,
+
+    /// CHECK: AdminLog PDA, recorded to once initialize_admin_log has created it
+    #[account(mut, seeds = [b"admin_log"], bump)]
+    pub admin_log: UncheckedAccount<'info>,
+}
+
+/// Moves an open order to NeedsReview once it has failed MAX_FAILED_EXECUTION_ATTEMPTS executions
+/// and the registry authority confirms it. Executors skip the order until the creator reopens or
+/// cancels it
+pub fn flag_order_for_review(ctx: Context<FlagOrderForReview>) -> Result<()> {
+    let order = &mut ctx.accounts.limit_order;
+    require!(
+        order.failed_attempts >= MAX_FAILED_EXECUTION_ATTEMPTS,
+        ErrorCode::NotEnoughFailedAttempts
+    );
+
+    order.status = OrderStatus::NeedsReview;
+
+    emit_cpi!(LimitOrderFlaggedForReview {
+        order: order.key(),
+        authority: ctx.accounts.authority.key(),
+        failed_attempts: order.failed_attempts,
+    });
+
+    AdminLog::record(&ctx.accounts.admin_log, ctx.program_id, AdminAction::FlagOrderForReview, ctx.accounts.authority.key(), order.key())?;
+
+    Ok(())
+}
+
+/// Reopen limit order instruction accounts
+#[event_cpi]
+#[derive(Accounts)]
+pub struct ReopenLimitOrder<'info> {
+    /// Limit order flagged for review
     #[account(
         mut,
-        constraint = input_vault.key() == limit_order.input_vault @ ErrorCode::InvalidVaultAddress
+        constraint = limit_order.status == OrderStatus::NeedsReview @ ErrorCode::OrderNotUnderReview,
+        constraint = limit_order.creator == creator.key() @ ErrorCode::UnauthorizedAdmin
     )]
-    pub input_vault: InterfaceAccount<'info, TokenAccount>,
+    pub limit_order: Account<'info, LimitOrder>,
 
-    /// User's account to receive refunded tokens
-    /// Only used for Open status when refunding tokens
-    /// For Init status, this is still required but won't be used
+    /// CHECK: ProtocolConfig PDA; default limits apply until create_protocol_config has created it
+    #[account(seeds = [b"protocol_config"], bump)]
+    pub protocol_config: UncheckedAccount<'info>,
+
+    /// Order creator (must sign)
+    pub creator: Signer<'info>,
+}
+
+/// Reopens an order flagged for review with fresh execution bounds and a cleared failure counter
+pub fn reopen_limit_order(
+    ctx: Context<ReopenLimitOrder>,
+    min_output_amount: u64,
+    slippage_bps: u16,
+) -> Result<()> {
+    require!(min_output_amount > 0, ErrorCode::InvalidAmount);
+    ProtocolConfig::load(&ctx.accounts.protocol_config, ctx.program_id)?.check_order_slippage(slippage_bps)?;
+
+    let order = &mut ctx.accounts.limit_order;
+    order.min_output_amount = min_output_amount;
+    order.slippage_bps = slippage_bps;
+    order.failed_attempts = 0;
+    order.status = OrderStatus::Open;
+
+    emit_cpi!(LimitOrderReopened {
+        order: order.key(),
+        creator: order.creator,
+        min_output_amount,
+        slippage_bps,
+    });
+
+    Ok(())
+}
+
+/// Set order execution fee instruction accounts
+#[event_cpi]
+#[derive(Accounts)]
+pub struct SetOrderExecutionFee<'info> {
+    /// Limit order awaiting execution
     #[account(
         mut,
-        constraint = user_input_token_account.owner == creator.key()
+        constraint = matches!(limit_order.status, OrderStatus::Open | OrderStatus::NeedsReview) @ ErrorCode::InvalidOrderStatus,
+        constraint = limit_order.creator == creator.key() @ ErrorCode::UnauthorizedAdmin
     )]
-    pub user_input_token_account: InterfaceAccount<'info, TokenAccount>,
-
-    /// Input token mint
-    /// Only used for Open status when refunding tokens
-    /// For Init status, this is still required but won't be used
-    pub input_mint: InterfaceAccount<'info, Mint>,
-    /// Token program for input tokens
-    pub input_token_program: Interface<'info, TokenInterface>,
+    pub limit_order: Account<'info, LimitOrder>,
 
-    /// Order creator (must sign, receives rent from closed input_vault)
-    #[account(mut, signer)]
+    /// Order creator (must sign)
     pub creator: Signer<'info>,
 }
 
-/// Cancels a limit order in Init or Open status and refunds tokens to creator
-pub fn cancel_limit_order(ctx: Context<CancelLimitOrder>) -> Result<()> {
-    // Prepare PDA signer seeds
-    let vault_authority_bump = ctx.bumps.vault_authority;
-    let authority_seeds: &[&[u8]] = &[
-        b"vault_authority".as_ref(),
-        &[vault_authority_bump],
-    ];
-    let signer_seeds: &[&[&[u8]]] = &[authority_seeds];
+/// Overrides the execution fee of an order, or clears the override with None so the
+/// input mint's FeeSchedule applies again
+pub fn set_order_execution_fee(ctx: Context<SetOrderExecutionFee>, fee_bps: Option<u8>) -> Result<()> {
+    let order = &mut ctx.accounts.limit_order;
+    order.execution_fee_bps = fee_bps;
 
-    // Handle cancellation based on order status
-    match ctx.accounts.limit_order.status {
-        OrderStatus::Init => {
-            // For Init status: input_amount is 0, no tokens were transferred
-            // Vault exists but is empty, so we can close it to recover rent
-            // Always close vault for Init status (it's always empty)
-            // Verify vault is empty before closing
-            require!(
-                ctx.accounts.input_vault.amount == 0,
-                ErrorCode::InvalidAmount
-            );
-            
-            close_account(
-                CpiContext::new_with_signer(
-                    ctx.accounts.input_token_program.to_account_info(),
-                    CloseAccount {
-                        account: ctx.accounts.input_vault.to_account_info(),
-                        destination: ctx.accounts.creator.to_account_info(),
-                        authority: ctx.accounts.vault_authority.to_account_info(),
-                    },
-                    signer_seeds
-                )
-            )?;
-        },
-        OrderStatus::Open => {
-            // For Open status: input_amount > 0, refund tokens to creator
-            require!(
-                ctx.accounts.input_vault.mint == ctx.accounts.limit_order.input_mint,
-                ErrorCode::InvalidMint
-            );
-            require!(
-                ctx.accounts.user_input_token_account.mint == ctx.accounts.limit_order.input_mint,
-                ErrorCode::InvalidMint
-            );
-            require!(
-                ctx.accounts.input_mint.key() == ctx.accounts.limit_order.input_mint,
-                ErrorCode::InvalidMint
-            );
-            require!(
-                ctx.accounts.limit_order.input_amount > 0,
-                ErrorCode::InvalidAmount
-            );
+    emit_cpi!(OrderExecutionFeeSet {
+        order: order.key(),
+        creator: order.creator,
+        fee_bps,
+    });
 
-            // Refund input tokens to creator
-            transfer_checked(
-                CpiContext::new_with_signer(
-                    ctx.accounts.input_token_program.to_account_info(),
-                    TransferChecked {
-                        from: ctx.accounts.input_vault.to_account_info(),
-                        to: ctx.accounts.user_input_token_account.to_account_info(),
-                        authority: ctx.accounts.vault_authority.to_account_info(),
-                        mint: ctx.accounts.input_mint.to_account_info(),
-                    },
-                    signer_seeds
-                ),
-                ctx.accounts.limit_order.input_amount,
-                ctx.accounts.input_mint.decimals,
-            )?;
+    Ok(())
+}
 
-            // Close input_vault and return rent to creator
-            // All tokens have been refunded to creator, so vault is empty
-            close_account(
-                CpiContext::new_with_signer(
-                    ctx.accounts.input_token_program.to_account_info(),
-                    CloseAccount {
-                        account: ctx.accounts.input_vault.to_account_info(),
-                        destination: ctx.accounts.creator.to_account_info(),
-                        authority: ctx.accounts.vault_authority.to_account_info(),
-                    },
-                    signer_seeds
-                )
-            )?;
-        },
-        _ => {
-            // Should not reach here due to constraint check
-            return Err(ErrorCode::InvalidOrderStatus.into());
-        }
+/// Set order unwrap output instruction accounts
+#[event_cpi]
+#[derive(Accounts)]
+pub struct SetOrderUnwrapOutput<'info> {
+    /// Limit order awaiting execution
+    #[account(
+        mut,
+        constraint = matches!(limit_order.status, OrderStatus::Open | OrderStatus::NeedsReview) @ ErrorCode::InvalidOrderStatus,
+        constraint = limit_order.creator == creator.key() @ ErrorCode::UnauthorizedAdmin
+    )]
+    pub limit_order: Account<'info, LimitOrder>,
+
+    /// Order creator (must sign); its system account receives the unwrapped output
+    pub creator: Signer<'info>,
+}
+
+/// Chooses whether a WSOL-output order is filled as native SOL to the creator's system account.
+/// Turning it off delivers to user_destination_account again.
+pub fn set_order_unwrap_output(ctx: Context<SetOrderUnwrapOutput>, unwrap_output: bool) -> Result<()> {
+    let order = &mut ctx.accounts.limit_order;
+    if unwrap_output {
+        require!(order.output_mint == anchor_spl::token::spl_token::native_mint::ID, ErrorCode::InvalidMint);
+        require!(order.output_token_program == anchor_spl::token::ID, ErrorCode::InvalidTokenProgram);
     }
+    order.unwrap_output = unwrap_output;
+    order.user_lamport_destination = if unwrap_output { order.creator } else { Pubkey::default() };
 
-    // Emit cancellation event before account is closed
-    emit_cpi!(LimitOrderCancelled {
-        order: ctx.accounts.limit_order.key(),
-        creator: ctx.accounts.creator.key(),
+    emit_cpi!(OrderUnwrapOutputSet {
+        order: order.key(),
+        creator: order.creator,
+        unwrap_output,
+        user_lamport_destination: order.user_lamport_destination,
     });
 
-    // Account will be closed automatically and rent transferred to creator due to `close = creator`
-    // Note: Operator can also close cancelled orders if they weren't closed by creator
     Ok(())
 }
 
@@ -924,11 +3261,12 @@ pub struct CancelExpiredLimitOrderByOperator<'info> {
     )]
     pub vault_authority: Account<'info, VaultAuthority>,
 
-    /// Limit order to cancel (must be open and expired, rent goes to operator)
+    /// Limit order to cancel (must be open or under review and expired, rent goes to operator)
     #[account(
         mut,
         close = operator,
-        constraint = limit_order.status == OrderStatus::Open @ ErrorCode::InvalidOrderStatus
+        constraint = matches!(limit_order.status, OrderStatus::Open | OrderStatus::NeedsReview) @ ErrorCode::InvalidOrderStatus,
+        constraint = limit_order.input_token_program == input_token_program.key() @ ErrorCode::InvalidTokenProgram
     )]
     pub limit_order: Account<'info, LimitOrder>,
 
@@ -956,22 +3294,49 @@ pub struct CancelExpiredLimitOrderByOperator<'info> {
     /// Token program for input tokens
     pub input_token_program: Interface<'info, TokenInterface>,
 
+    /// CHECK: ["receipt", limit_order] PDA, recorded into when the order was created with a receipt
+    #[account(
+        mut,
+        seeds = [b"receipt", limit_order.key().as_ref()],
+        bump
+    )]
+    pub order_receipt: UncheckedAccount<'info>,
+
+    /// CHECK: ["vault_rent_payer", input_vault] PDA, read when a sponsor funded the vault
+    #[account(
+        mut,
+        seeds = [b"vault_rent_payer", input_vault.key().as_ref()],
+        bump
+    )]
+    pub vault_rent_payer: UncheckedAccount<'info>,
+
+    /// Sponsor recorded in vault_rent_payer, receiving the vault rent (required when recorded)
+    /// CHECK: Must match the recorded payer, or the treasury its obligations were transferred to,
+    /// checked in vault_rent_destination
+    #[account(mut)]
+    pub rent_payer: Option<UncheckedAccount<'info>>,
+
+    /// Sponsoring operator's OperatorStats (required when an operator sponsored the vault)
+    /// CHECK: Must belong to the recorded payer, checked in vault_rent_destination
+    #[account(mut)]
+    pub rent_payer_stats: Option<UncheckedAccount<'info>>,
+
     /// Operator cancelling the order (must be registered, receives rent from closed limit_order and input_vault)
     #[account(
         mut,
         signer,
-        constraint = adapter_registry.operators.contains(&operator.key()) @ ErrorCode::InvalidOperator
+        constraint = adapter_registry.is_active_operator(&operator.key()) @ ErrorCode::InvalidOperator
     )]
     pub operator: Signer<'info>,
 
     pub system_program: Program<'info, System>,
 }
 
-/// Cancels an expired open limit order by operator and refunds tokens to creator
+/// Cancels an expired open (or under review) limit order by operator and refunds tokens to creator
 /// Rent from limit_order account goes to operator, rent from input_vault + tokens go to creator
 pub fn cancel_expired_limit_order_by_operator(ctx: Context<CancelExpiredLimitOrderByOperator>) -> Result<()> {
     // Check if order has expired
-    let now = Clock::get()?.unix_timestamp;
+    let now = current_unix_timestamp(ctx.remaining_accounts)?;
     if now < ctx.accounts.limit_order.expiry {
         return Err(ErrorCode::InvalidExpiry.into());
     }
@@ -1000,25 +3365,44 @@ pub fn cancel_expired_limit_order_by_operator(ctx: Context<CancelExpiredLimitOrd
         ctx.accounts.input_mint.decimals,
     )?;
 
+    OrderReceipt::update(&ctx.accounts.order_receipt.to_account_info(), ctx.program_id, |receipt| {
+        receipt.record(OrderStatus::Cancelled, 0, 0, ctx.accounts.operator.key(), now)
+    })?;
+
     // Emit cancellation event before account is closed
     emit_cpi!(LimitOrderCancelled {
         order: ctx.accounts.limit_order.key(),
         creator: ctx.accounts.limit_order.creator,
+        refund_account: Some(ctx.accounts.user_input_token_account.key()),
     });
+    emit_cpi!(ctx.accounts.limit_order.open_interest_change(false));
 
-    // Close input_vault and return rent to operator
+    // Close input_vault and return rent to operator (or the vault's sponsor)
     // All tokens have been refunded to creator, so vault is empty
+    let rent_destination = vault_rent_destination(
+        &ctx.accounts.vault_rent_payer.to_account_info(),
+        ctx.accounts.rent_payer.as_ref().map(|payer| payer.to_account_info()).as_ref(),
+        ctx.accounts.rent_payer_stats.as_ref().map(|stats| stats.to_account_info()).as_ref(),
+        &ctx.accounts.operator.to_account_info(),
+        ctx.program_id,
+    )?;
     close_account(
         CpiContext::new_with_signer(
             ctx.accounts.input_token_program.to_account_info(),
             CloseAccount {
                 account: ctx.accounts.input_vault.to_account_info(),
-                destination: ctx.accounts.operator.to_account_info(), // Rent goes to operator
+                destination: rent_destination.clone(),
                 authority: ctx.accounts.vault_authority.to_account_info(),
             },
             signer_seeds
         )
     )?;
+    close_vault_rent_payer(
+        &ctx.accounts.vault_rent_payer.to_account_info(),
+        &rent_destination,
+        ctx.accounts.rent_payer_stats.as_ref().map(|stats| stats.to_account_info()).as_ref(),
+        ctx.program_id,
+    )?;
 
     // Account will be closed automatically and rent transferred to operator due to `close = operator`
     Ok(())
@@ -1049,7 +3433,8 @@ pub struct CloseLimitOrderByOperator<'info> {
     /// Operator cannot close Open orders - only creator can cancel them
     #[account(
         mut,
-        close = operator
+        close = operator,
+        constraint = limit_order.input_token_program == input_token_program.key() @ ErrorCode::InvalidTokenProgram
     )]
     pub limit_order: Account<'info, LimitOrder>,
 
@@ -1065,11 +3450,52 @@ pub struct CloseLimitOrderByOperator<'info> {
     /// Token program for input tokens
     pub input_token_program: Interface<'info, TokenInterface>,
 
+    /// Input token mint, required with dust_vault
+    #[account(
+        constraint = input_mint.key() == limit_order.input_mint @ ErrorCode::InvalidMint
+    )]
+    pub input_mint: Option<InterfaceAccount<'info, Mint>>,
+
+    /// Protocol vault for the input mint, receiving input left in the order vault
+    #[account(
+        mut,
+        seeds = [b"vault", limit_order.input_mint.as_ref()],
+        bump
+    )]
+    pub dust_vault: Option<Box<InterfaceAccount<'info, TokenAccount>>>,
+
+    /// CHECK: ["receipt", limit_order] PDA, recorded into when the order was created with a receipt
+    #[account(
+        mut,
+        seeds = [b"receipt", limit_order.key().as_ref()],
+        bump
+    )]
+    pub order_receipt: UncheckedAccount<'info>,
+
+    /// CHECK: ["vault_rent_payer", input_vault] PDA, read when a sponsor funded the vault
+    #[account(
+        mut,
+        seeds = [b"vault_rent_payer", input_vault.key().as_ref()],
+        bump
+    )]
+    pub vault_rent_payer: UncheckedAccount<'info>,
+
+    /// Sponsor recorded in vault_rent_payer, receiving the vault rent (required when recorded)
+    /// CHECK: Must match the recorded payer, or the treasury its obligations were transferred to,
+    /// checked in vault_rent_destination
+    #[account(mut)]
+    pub rent_payer: Option<UncheckedAccount<'info>>,
+
+    /// Sponsoring operator's OperatorStats (required when an operator sponsored the vault)
+    /// CHECK: Must belong to the recorded payer, checked in vault_rent_destination
+    #[account(mut)]
+    pub rent_payer_stats: Option<UncheckedAccount<'info>>,
+
     /// Operator closing the order (must be registered, receives rent from closed input_vault)
     #[account(
         mut,
         signer,
-        constraint = adapter_registry.operators.contains(&operator.key()) @ ErrorCode::InvalidOperator
+        constraint = adapter_registry.is_active_operator(&operator.key()) @ ErrorCode::InvalidOperator
     )]
     pub operator: Signer<'info>,
 
@@ -1102,22 +3528,58 @@ pub fn close_limit_order_by_operator(ctx: Context<CloseLimitOrderByOperator>) ->
     ];
     let signer_seeds: &[&[&[u8]]] = &[authority_seeds];
 
+    // Input left behind by a fill (see close_filled_order) goes to the protocol vault
+    if let (Some(dust_vault), Some(input_mint)) = (&ctx.accounts.dust_vault, &ctx.accounts.input_mint) {
+        sweep_input_dust(
+            &ctx.accounts.input_vault.to_account_info(),
+            &dust_vault.to_account_info(),
+            input_mint,
+            &ctx.accounts.input_token_program.to_account_info(),
+            &ctx.accounts.vault_authority.to_account_info(),
+            signer_seeds,
+        )?;
+    }
+
     // Close input_vault and return rent to operator
     // For Init status: vault is empty (no tokens were transferred)
     // For filled orders: vault is empty (tokens were transferred during execution)
     // For cancelled orders: vault should be empty (tokens were refunded during cancellation)
-    // If vault still exists and is empty, close it to recover rent
+    // If vault still exists and is empty, close it to recover rent (a sponsor's rent goes back to it)
+    let rent_destination = vault_rent_destination(
+        &ctx.accounts.vault_rent_payer.to_account_info(),
+        ctx.accounts.rent_payer.as_ref().map(|payer| payer.to_account_info()).as_ref(),
+        ctx.accounts.rent_payer_stats.as_ref().map(|stats| stats.to_account_info()).as_ref(),
+        &ctx.accounts.operator.to_account_info(),
+        ctx.program_id,
+    )?;
     close_account(
         CpiContext::new_with_signer(
             ctx.accounts.input_token_program.to_account_info(),
             CloseAccount {
                 account: ctx.accounts.input_vault.to_account_info(),
-                destination: ctx.accounts.operator.to_account_info(),
+                destination: rent_destination.clone(),
                 authority: ctx.accounts.vault_authority.to_account_info(),
             },
             signer_seeds
         )
     )?;
+    close_vault_rent_payer(
+        &ctx.accounts.vault_rent_payer.to_account_info(),
+        &rent_destination,
+        ctx.accounts.rent_payer_stats.as_ref().map(|stats| stats.to_account_info()).as_ref(),
+        ctx.program_id,
+    )?;
+
+    // Filled and Cancelled orders were recorded when they got there; an Init order never
+    // opened, so closing it finalizes its receipt as Cancelled
+    let final_status = if status == OrderStatus::Init { OrderStatus::Cancelled } else { status };
+    let now = current_unix_timestamp(ctx.remaining_accounts)?;
+    OrderReceipt::update(&ctx.accounts.order_receipt.to_account_info(), ctx.program_id, |receipt| {
+        if !receipt.is_final() {
+            receipt.record(final_status, 0, 0, operator_key, now);
+        }
+        receipt.vault_close_pending = false;
+    })?;
 
     // Account is automatically closed by Anchor and rent is sent to operator
     emit_cpi!(LimitOrderClosed {
@@ -1128,6 +3590,189 @@ pub fn close_limit_order_by_operator(ctx: Context<CloseLimitOrderByOperator>) ->
     Ok(())
 }
 
+/// Close order receipt instruction accounts
+#[derive(Accounts)]
+pub struct CloseOrderReceipt<'info> {
+    /// Receipt to close (rent goes back to creator)
+    #[account(
+        mut,
+        close = creator,
+        seeds = [b"receipt", limit_order.key().as_ref()],
+        bump = order_receipt.bump,
+        constraint = order_receipt.creator == creator.key() @ ErrorCode::UnauthorizedAdmin
+    )]
+    pub order_receipt: Account<'info, OrderReceipt>,
+
+    /// Limit order the receipt belongs to (usually already closed)
+    /// CHECK: Only the address (receipt seed) and lamports are read
+    #[account(address = order_receipt.order @ ErrorCode::InvalidOrderReceipt)]
+    pub limit_order: UncheckedAccount<'info>,
+
+    /// Order creator (must sign, receives receipt rent)
+    #[account(mut, signer)]
+    pub creator: Signer<'info>,
+}
+
+/// Closes an order receipt and returns its rent to the creator
+/// Allowed once the receipt holds a final status, or once the order account no longer
+/// exists (e.g. an Init order closed by an operator) so the rent can never be stranded
+pub fn close_order_receipt(ctx: Context<CloseOrderReceipt>) -> Result<()> {
+    let order_closed = ctx.accounts.limit_order.lamports() == 0;
+    require!(
+        ctx.accounts.order_receipt.is_final() || order_closed,
+        ErrorCode::InvalidOrderStatus
+    );
+    Ok(())
+}
+
+/// Migrate limit order instruction accounts
+#[derive(Accounts)]
+pub struct MigrateLimitOrder<'info> {
+    /// CHECK: Manually validated in handler. Cannot use Account<LimitOrder> because orders
+    /// created before the token programs, failed_attempts, activate_at, execution_fee_bps,
+    /// unwrap_output or generation were stored use the LEGACY_SPACE, PRE_FAILED_ATTEMPTS_SPACE,
+    /// PRE_ACTIVATE_AT_SPACE, PRE_EXECUTION_FEE_SPACE, PRE_UNWRAP_OUTPUT_SPACE or PRE_GENERATION_SPACE
+    /// layout and Anchor cannot deserialize them into the current LimitOrder struct. LEGACY_SPACE
+    /// orders get their token programs written; the other sizes are only grown.
+    #[account(mut)]
+    pub limit_order: AccountInfo<'info>,
+
+    /// CHECK: Must be the order's input vault (checked in handler); its owner is the input token program
+    pub input_vault: AccountInfo<'info>,
+
+    /// CHECK: Must be the order's output mint (checked in handler, ignored for Init orders);
+    /// its owner is the output token program
+    pub output_mint: AccountInfo<'info>,
+
+    /// Pays for the extra rent (112 bytes for legacy orders, 48, 47, 39, 37 or 4 bytes otherwise)
+    #[account(mut)]
+    pub payer: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+/// Reads (input_vault, output_mint) from limit order data in the LEGACY_SPACE layout
+///
+/// # Arguments
+/// * `data` - Raw account data, including the discriminator
+pub fn legacy_limit_order_keys(data: &[u8]) -> Result<(Pubkey, Pubkey)> {
+    require!(data.len() == LimitOrder::LEGACY_SPACE, ErrorCode::InvalidAccount);
+    require!(&data[..8] == LimitOrder::DISCRIMINATOR, ErrorCode::InvalidAccount);
+
+    // Layout: discriminator (8) + creator (32) + input_mint (32) + output_mint (32) + input_vault (32) + ...
+    let output_mint_bytes: [u8; 32] = data[72..104].try_into().unwrap();
+    let input_vault_bytes: [u8; 32] = data[104..136].try_into().unwrap();
+    Ok((Pubkey::from(input_vault_bytes), Pubkey::from(output_mint_bytes)))
+}
+
+/// Tops up a limit order's rent from payer and reallocates it to LimitOrder::SPACE
+fn grow_limit_order<'info>(
+    limit_order: &AccountInfo<'info>,
+    payer: &Signer<'info>,
+    system_program: &Program<'info, System>,
+) -> Result<()> {
+    let new_size: usize = LimitOrder::SPACE;
+    let rent = Rent::get()?;
+    let new_minimum_balance = rent.minimum_balance(new_size);
+    let lamports_diff = new_minimum_balance.saturating_sub(limit_order.lamports());
+
+    if lamports_diff > 0 {
+        anchor_lang::solana_program::program::invoke(
+            &anchor_lang::solana_program::system_instruction::transfer(
+                &payer.key(),
+                &limit_order.key(),
+                lamports_diff,
+            ),
+            &[
+                payer.to_account_info(),
+                limit_order.to_account_info(),
+                system_program.to_account_info(),
+            ],
+        )?;
+    }
+
+    limit_order.resize(new_size)?;
+    Ok(())
+}
+
+/// Grows a limit order created before the token programs were stored and snapshots them.
+/// The input token program is the owner of the order's input vault and the output token program
+/// is the owner of its output mint, so the caller cannot choose them; anyone may pay for the migration.
+/// Orders that already store their token programs are only grown to make room for the newer
+/// fields (failed_attempts, activate_at, execution_fee_bps, unwrap_output, user_lamport_destination,
+/// generation), which start zeroed; a migrated order reads generation 0.
+pub fn migrate_limit_order(ctx: Context<MigrateLimitOrder>) -> Result<()> {
+    let limit_order = &ctx.accounts.limit_order;
+
+    // Verify the account is owned by this program
+    require!(
+        limit_order.owner == ctx.program_id,
+        ErrorCode::InvalidAccount
+    );
+
+    let data = limit_order.try_borrow_data()?;
+    // Orders that already store their token programs only lack the trailing fields
+    let old_len = data.len();
+    if old_len == LimitOrder::PRE_FAILED_ATTEMPTS_SPACE
+        || old_len == LimitOrder::PRE_ACTIVATE_AT_SPACE
+        || old_len == LimitOrder::PRE_EXECUTION_FEE_SPACE
+        || old_len == LimitOrder::PRE_UNWRAP_OUTPUT_SPACE
+        || old_len == LimitOrder::PRE_GENERATION_SPACE
+    {
+        require!(&data[..8] == LimitOrder::DISCRIMINATOR, ErrorCode::InvalidAccount);
+        drop(data); // Release borrow before realloc
+
+        grow_limit_order(limit_order, &ctx.accounts.payer, &ctx.accounts.system_program)?;
+        limit_order.try_borrow_mut_data()?[old_len..].fill(0);
+
+        msg!("Migrated limit order {}. Grown from {} to {} bytes", limit_order.key(), old_len, LimitOrder::SPACE);
+        return Ok(());
+    }
+    let (input_vault, output_mint) = legacy_limit_order_keys(&data)?;
+    drop(data); // Release borrow before realloc
+
+    require!(
+        ctx.accounts.input_vault.key() == input_vault,
+        ErrorCode::InvalidVaultAddress
+    );
+    let input_token_program = *ctx.accounts.input_vault.owner;
+
+    // Init orders have no output mint yet; create_limit_order snapshots the output token program
+    let output_token_program = if output_mint == Pubkey::default() {
+        Pubkey::default()
+    } else {
+        require!(
+            ctx.accounts.output_mint.key() == output_mint,
+            ErrorCode::InvalidMint
+        );
+        *ctx.accounts.output_mint.owner
+    };
+
+    let is_token_program = |program: &Pubkey| *program == anchor_spl::token::ID || *program == TOKEN_2022_PROGRAM_ID;
+    require!(is_token_program(&input_token_program), ErrorCode::InvalidTokenProgram);
+    require!(
+        output_token_program == Pubkey::default() || is_token_program(&output_token_program),
+        ErrorCode::InvalidTokenProgram
+    );
+
+    grow_limit_order(limit_order, &ctx.accounts.payer, &ctx.accounts.system_program)?;
+
+    // Write new fields into the reallocated account
+    let mut data = limit_order.try_borrow_mut_data()?;
+    let legacy_end = LimitOrder::LEGACY_SPACE;
+    data[legacy_end..legacy_end + 32].copy_from_slice(input_token_program.as_ref());
+    data[legacy_end + 32..legacy_end + 64].copy_from_slice(output_token_program.as_ref());
+    data[legacy_end + 64..].fill(0); // failed_attempts, activate_at, execution_fee_bps, unwrap_output, generation
+
+    msg!(
+        "Migrated limit order {}. Input token program: {}, output token program: {}",
+        limit_order.key(),
+        input_token_program,
+        output_token_program
+    );
+    Ok(())
+}
+
 /// Route and create order instruction accounts
 #[event_cpi]
 #[derive(Accounts)]
@@ -1183,21 +3828,36 @@ pub struct RouteAndCreateOrder<'info> {
     pub user_destination_account: Box<InterfaceAccount<'info, TokenAccount>>,
 
     /// Swap input token mint
+    #[account(
+        constraint = input_mint.to_account_info().owner == &input_token_program.key() @ ErrorCode::InvalidTokenProgram
+    )]
     pub input_mint: Box<InterfaceAccount<'info, Mint>>,
 
     /// Swap output token mint (becomes order input mint, and order output mint)
+    #[account(
+        constraint = output_mint.to_account_info().owner == &output_token_program.key() @ ErrorCode::InvalidTokenProgram
+    )]
     pub output_mint: Box<InterfaceAccount<'info, Mint>>,
 
-    /// Token program for swap input tokens
+    /// Token program for swap input tokens (snapshotted as the order's output token program)
     pub input_token_program: Interface<'info, TokenInterface>,
 
-    /// Token program for swap/order output tokens
+    /// Token program for swap/order output tokens (snapshotted as the order's input token program)
     pub output_token_program: Interface<'info, TokenInterface>,
 
     /// Optional platform fee collection account
     #[account(mut)]
     pub platform_fee_account: Option<Box<InterfaceAccount<'info, TokenAccount>>>,
 
+    /// Treasury receiving the order creation fee (required when the fee is non-zero)
+    /// CHECK: Must match adapter_registry.treasury, checked in collect_order_creation_fee
+    #[account(mut)]
+    pub treasury: Option<UncheckedAccount<'info>>,
+
+    /// CHECK: ProtocolConfig PDA; default limits apply until create_protocol_config has created it
+    #[account(seeds = [b"protocol_config"], bump)]
+    pub protocol_config: UncheckedAccount<'info>,
+
     /// Order creator (must sign)
     #[account(mut, signer)]
     pub creator: Signer<'info>,
@@ -1211,7 +3871,8 @@ pub struct RouteAndCreateOrder<'info> {
 /// # Arguments
 /// * `order_nonce` - Unique identifier for order creation
 /// * `route_plan` - Swap route to execute
-/// * `in_amount` - Amount of input tokens to swap
+/// * `in_amount` - Amount of input tokens to swap, or SWEEP_SOURCE_BALANCE for the whole
+///   user_input_account balance
 /// * `quoted_out_amount` - Expected output amount from swap quote
 /// * `slippage_bps` - Slippage tolerance for swap in basis points
 /// * `platform_fee_bps` - Platform fee for swap in basis points
@@ -1219,6 +3880,7 @@ pub struct RouteAndCreateOrder<'info> {
 /// * `order_trigger_price_bps` - Trigger deviation percentage in basis points
 /// * `order_expiry` - Order expiration timestamp
 /// * `order_slippage_bps` - Slippage tolerance for order execution
+/// * `emit_step_events` - Per-step SwapEvents for a single-hop route (None = registry setting)
 ///
 /// # Returns
 /// * `Result<(u64, Pubkey)>` - (Swap output amount, Created order pubkey)
@@ -1234,32 +3896,30 @@ pub fn route_and_create_order<'info>(
     order_trigger_price_bps: u32,
     order_expiry: i64,
     order_slippage_bps: u16,
+    emit_step_events: Option<bool>,
 ) -> Result<(u64, Pubkey)> {
     // ===== VALIDATION =====
 
+    // Resolve a sweep to the current balance; every step below uses the resolved amount
+    let in_amount = resolve_in_amount(in_amount, ctx.accounts.user_input_account.amount)?;
+
     // Validate swap parameters
     require!(in_amount > 0, ErrorCode::InvalidAmount);
     require!(quoted_out_amount > 0, ErrorCode::InvalidAmount);
-    require!(slippage_bps <= 1000, ErrorCode::InvalidSlippage);
+    let protocol_config = ProtocolConfig::load_config(&ctx.accounts.protocol_config, ctx.program_id)?;
+    protocol_config.require_creation_open()?;
+    let limits = protocol_config.limits;
+    limits.check_route_slippage(slippage_bps)?;
 
-    // Validate order parameters
+    // Validate order parameters (always TakeProfit for route_and_create_order)
     require!(order_min_output_amount > 0, ErrorCode::InvalidAmount);
-    require!(
-        order_trigger_price_bps > 0,
-        ErrorCode::InvalidTriggerPrice
-    );
-    // Validate upper bound for TakeProfit (always TakeProfit for route_and_create_order)
-    // TakeProfit: Allow up to 100,000 (1000%) for higher profit targets
-    // 10_000 + 100_000 = 110_000 fits in u64, so no overflow risk
-    require!(
-        order_trigger_price_bps <= 100_000,
-        ErrorCode::InvalidTriggerPrice
-    );
-    require!(
-        order_expiry > Clock::get()?.unix_timestamp,
-        ErrorCode::InvalidExpiry
-    );
-    require!(order_slippage_bps <= 1000, ErrorCode::InvalidSlippage);
+    // The order sells the swap output back into the swap input
+    require_distinct_order_mints(&ctx.accounts.output_mint.key(), &ctx.accounts.input_mint.key())?;
+    validate_trigger_price(&limits, TriggerType::TakeProfit, order_trigger_price_bps)?;
+    let now = current_unix_timestamp(ctx.remaining_accounts)?;
+    require!(order_expiry > now, ErrorCode::InvalidExpiry);
+    limits.check_order_duration(order_expiry, now)?;
+    limits.check_order_slippage(order_slippage_bps)?;
 
     // Validate that input_vault is created with correct seeds for this limit_order
     // Vault uses limit_order.key() as seed
@@ -1272,6 +3932,13 @@ pub fn route_and_create_order<'info>(
         ErrorCode::InvalidVaultAddress
     );
 
+    collect_order_creation_fee(
+        &ctx.accounts.adapter_registry,
+        &ctx.accounts.creator.to_account_info(),
+        ctx.accounts.treasury.as_deref(),
+        &ctx.accounts.system_program.to_account_info(),
+    )?;
+
     // ===== STEP 1: VALIDATE SWAP ROUTE =====
 
     route_validator_module::validate_route(
@@ -1285,6 +3952,8 @@ pub fn route_and_create_order<'info>(
         ctx.remaining_accounts,
         ctx.program_id,
         in_amount,
+        &ctx.accounts.creator.key(),
+        false,
     )?;
 
     // CRITICAL: Validate that the last step's output_index points to ctx.accounts.input_vault
@@ -1305,15 +3974,8 @@ pub fn route_and_create_order<'info>(
     let input_vault = ctx.remaining_accounts
         .iter()
         .find(|acc| {
-            if let Ok(account_data) = acc.try_borrow_data() {
-                if let Ok(token_account) = TokenAccount::try_deserialize(&mut account_data.as_ref()) {
-                    token_account.mint == ctx.accounts.input_mint.key()
-                } else {
-                    false
-                }
-            } else {
-                false
-            }
+            route_validator_module::scan_token_account(acc)
+                .is_some_and(|token_account| token_account.mint == ctx.accounts.input_mint.key())
         })
         .ok_or(ErrorCode::VaultNotFound)?;
 
@@ -1343,7 +4005,7 @@ pub fn route_and_create_order<'info>(
     let signer_seeds: &[&[&[u8]]] = &[authority_seeds];
 
     // Execute swap route
-    let (mut out_amount, event_data) = route_executor_module::execute_route(
+    let (mut out_amount, event_data, volume_warnings) = route_executor_module::execute_route(
         &ctx.accounts.adapter_registry,
         &ctx.accounts.input_token_program.to_account_info(),
         &ctx.accounts.vault_authority.to_account_info(),
@@ -1353,30 +4015,43 @@ pub fn route_and_create_order<'info>(
         ctx.remaining_accounts,
         ctx.program_id,
         in_amount,
+        false,
+        protocol_config.pool_volume_warning_pct(),
     )?;
 
-    // Emit swap events
-    for event in event_data {
-        emit_cpi!(SwapEvent {
-            amm: event.amm,
-            input_mint: event.input_mint,
-            input_amount: event.input_amount,
-            output_mint: event.output_mint,
-            output_amount: event.output_amount,
-        });
+    for warning in volume_warnings {
+        emit_cpi!(warning);
+    }
+
+    // Emit swap events; a single-hop SwapEvent may be skipped since RouterSwapEvent repeats it
+    if ctx.accounts.adapter_registry.should_emit_step_events(event_data.len(), emit_step_events) {
+        for event in event_data {
+            emit_cpi!(SwapEvent {
+                amm: event.amm,
+                input_mint: event.input_mint,
+                input_amount: event.input_amount,
+                output_mint: event.output_mint,
+                output_amount: event.output_amount,
+                host_fee_amount: event.host_fee_amount,
+            });
+        }
     }
 
     // ===== STEP 4: COLLECT PLATFORM FEE FROM SWAP =====
 
     let mut fee_amount = 0u64;
     let mut fee_account: Option<Pubkey> = None;
-    if let Some(platform_fee_account) = &ctx.accounts.platform_fee_account {
+    if let Some(platform_fee_account) = route_validator_module::charged_fee_account(platform_fee_bps, ctx.accounts.platform_fee_account.as_ref())? {
         require!(
             platform_fee_account.mint == ctx.accounts.output_mint.key(),
             ErrorCode::InvalidPlatformFeeMint
         );
+        route_validator_module::validate_fee_account_authorities(
+            platform_fee_account,
+            &ctx.accounts.vault_authority.key(),
+        )?;
 
-        fee_amount = (out_amount as u128 * platform_fee_bps as u128 / 10_000) as u64;
+        fee_amount = math::apply_bps_fee(out_amount, platform_fee_bps.into())?;
         if fee_amount > 0 {
             transfer_checked(
                 CpiContext::new_with_signer(
@@ -1397,6 +4072,9 @@ pub fn route_and_create_order<'info>(
                 account: platform_fee_account.key(),
                 mint: ctx.accounts.output_mint.key(),
                 amount: fee_amount,
+                net_amount: route_validator_module::net_transfer_amount(&ctx.accounts.output_mint.to_account_info(), fee_amount)?,
+                fee_bps: platform_fee_bps,
+                source: FeeSource::RouteAndCreateOrder,
             });
 
             fee_account = Some(platform_fee_account.key());
@@ -1408,15 +4086,7 @@ pub fn route_and_create_order<'info>(
 
     // ===== STEP 5: VERIFY SLIPPAGE (AFTER fees are deducted, consistent with swap_processor_module::route) =====
 
-    let min_out_amount = (quoted_out_amount as u128)
-        .checked_mul(
-            (10_000u128)
-                .checked_sub(slippage_bps as u128)
-                .ok_or(ErrorCode::InvalidCalculation)?
-        )
-        .ok_or(ErrorCode::InvalidCalculation)?
-        .checked_div(10_000)
-        .ok_or(ErrorCode::InvalidCalculation)? as u64;
+    let min_out_amount = math::apply_bps_discount(quoted_out_amount, slippage_bps)?;
 
     require!(
         out_amount >= min_out_amount,
@@ -1434,6 +4104,10 @@ pub fn route_and_create_order<'info>(
         fee_amount,
         fee_account,
         slippage_bps,
+        net_output_amount: out_amount, // Output stays in the order vault, no transfer
+        accept_high_impact: false,
+        fee_mint: fee_account.map(|_| ctx.accounts.output_mint.key()),
+        ui_amount_multiplier: route_validator_module::ui_amount_multiplier(&ctx.accounts.output_mint.to_account_info())?,
     });
 
     // ===== STEP 6: UPDATE LIMIT ORDER =====
@@ -1455,6 +4129,13 @@ pub fn route_and_create_order<'info>(
     order.expiry = order_expiry;
     order.status = OrderStatus::Open;
     order.slippage_bps = order_slippage_bps;
+    order.failed_attempts = 0;
+    order.activate_at = 0;
+    order.execution_fee_bps = None;
+    order.unwrap_output = false;
+    order.user_lamport_destination = Pubkey::default();
+    order.input_token_program = ctx.accounts.output_token_program.key();
+    order.output_token_program = ctx.accounts.input_token_program.key();
 
     let order_key = order.key();
 
@@ -1470,7 +4151,12 @@ pub fn route_and_create_order<'info>(
         trigger_price_bps: order.trigger_price_bps,
         trigger_type: order.trigger_type as u8,
         expiry: order.expiry,
+        activate_at: order.activate_at,
+        generation: order.generation,
+        ui_amount_multiplier: route_validator_module::ui_amount_multiplier(&ctx.accounts.input_mint.to_account_info())?,
+        replaces: None,
     });
+    emit_cpi!(order.open_interest_change(true));
 
     emit_cpi!(RouteAndCreateOrderEvent {
         order: order_key,
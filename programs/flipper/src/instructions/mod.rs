@@ -10,9 +10,14 @@ pub mod route_executor_module;
 pub mod limit_orders_module;
 
 // New shared modules for Jupiter CPI integration
+pub mod jupiter_cpi;
 pub mod shared_route_module;
 pub mod shared_limit_orders_module;
 
+// Mock clock for localnet tests, see the "test-clock" feature
+#[cfg(feature = "test-clock")]
+pub mod test_clock_module;
+
 pub use adapter_registry_module::*;
 pub use swap_processor_module::*;
 pub use vault_manager_module::*;
@@ -22,4 +27,6 @@ pub use limit_orders_module::*;
 
 // Export new shared modules
 pub use shared_route_module::*;
-pub use shared_limit_orders_module::*;
\ No newline at end of file
+pub use shared_limit_orders_module::*;
+#[cfg(feature = "test-clock")]
+pub use test_clock_module::*;
@@ -1,17 +1,189 @@
 use anchor_lang::prelude::*;
+use anchor_spl::token::spl_token::native_mint;
 use anchor_spl::token_interface::TokenAccount;
 use crate::adapters::adapter_connector_module::{AdapterContext, get_adapter};
+use crate::adapters::wsol;
 use crate::errors::ErrorCode;
+use crate::math;
 use crate::state::*;
 
 /// Represents data needed for emitting a SwapEvent for each step
 #[derive(Clone)]
 pub struct SwapEventData {
+    pub swap: Swap,
     pub amm: Pubkey,
     pub input_mint: Pubkey,
     pub input_amount: u64,
     pub output_mint: Pubkey,
     pub output_amount: u64,
+    pub host_fee_amount: u64,
+}
+
+/// Collects the distinct venues used by an executed route, in execution order,
+/// capped at MAX_REPORTED_VENUES
+pub fn route_venues(event_data: &[SwapEventData]) -> Vec<Swap> {
+    let mut venues: Vec<Swap> = Vec::new();
+    for event in event_data {
+        if venues.len() == MAX_REPORTED_VENUES {
+            break;
+        }
+        if !venues.contains(&event.swap) {
+            venues.push(event.swap.clone());
+        }
+    }
+    venues
+}
+
+/// Amounts a route has delivered into each vault it passes through, keyed by vault address
+///
+/// A step's input is what the route credited to its input vault, so the branches of a split
+/// that hop through different intermediate vaults each continue with their own output. Vaults
+/// are never debited: all steps splitting a vault take their percent of the same amount.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct VaultLedger {
+    credits: Vec<(Pubkey, u64)>,
+}
+
+impl VaultLedger {
+    /// Starts a ledger holding the route's input in its source vault
+    pub fn new(source_vault: Pubkey, in_amount: u64) -> Self {
+        Self { credits: vec![(source_vault, in_amount)] }
+    }
+
+    /// Amount the route has delivered into `vault` so far (0 if none)
+    pub fn amount(&self, vault: &Pubkey) -> u64 {
+        self.credits
+            .iter()
+            .find(|(key, _)| key == vault)
+            .map(|(_, amount)| *amount)
+            .unwrap_or(0)
+    }
+
+    /// Records a step's output arriving in `vault`; merging branches add up
+    pub fn credit(&mut self, vault: Pubkey, amount: u64) -> Result<()> {
+        match self.credits.iter_mut().find(|(key, _)| *key == vault) {
+            Some((_, total)) => {
+                *total = total.checked_add(amount).ok_or(ErrorCode::InvalidCalculation)?;
+            }
+            None => self.credits.push((vault, amount)),
+        }
+        Ok(())
+    }
+}
+
+/// Reads a vault's mint and balance; the lamport escrow of WrapSol and UnwrapSol steps
+/// reads as a native mint vault holding its lamports above the rent reserve
+fn vault_balance(account: &AccountInfo) -> Result<(Pubkey, u64)> {
+    if wsol::is_sol_escrow(account) {
+        return Ok((native_mint::ID, wsol::escrow_balance(account)?));
+    }
+    let token_account = TokenAccount::try_deserialize(&mut account.try_borrow_data()?.as_ref())?;
+    Ok((token_account.mint, token_account.amount))
+}
+
+/// Key of the vault the route's first step reads, which holds the route input
+fn source_vault_key(route_plan: &[RoutePlanStep], remaining_accounts: &[AccountInfo]) -> Result<Pubkey> {
+    let first_step = route_plan.first().ok_or(ErrorCode::EmptyRoute)?;
+    remaining_accounts
+        .get(first_step.input_index as usize)
+        .map(|account| account.key())
+        .ok_or(ErrorCode::InvalidAccountIndex.into())
+}
+
+/// Calculates the input amount for the next route step
+///
+/// Steps of a percent split share an input vault. Each takes `percent` of the current amount,
+/// except the last step of a split summing to 100%, which takes the remainder so the split's
+/// step amounts add up exactly to its input (no dust left behind by integer division).
+///
+/// # Arguments
+/// * `route_plan` - Full route plan
+/// * `prior_step_amounts` - Amounts already assigned to steps 0..step_index
+/// * `current_amount` - Amount available to the step's input vault
+pub fn calculate_step_amount(
+    route_plan: &[RoutePlanStep],
+    prior_step_amounts: &[u64],
+    current_amount: u64,
+) -> Result<u64> {
+    let step_index = prior_step_amounts.len();
+    let step = route_plan.get(step_index).ok_or(ErrorCode::InvalidCalculation)?;
+    if step.percent == 100 {
+        return Ok(current_amount);
+    }
+
+    let group_percent: u32 = route_plan
+        .iter()
+        .filter(|s| s.input_index == step.input_index)
+        .map(|s| s.percent as u32)
+        .sum();
+    let is_last_in_group = !route_plan
+        .iter()
+        .skip(step_index + 1)
+        .any(|s| s.input_index == step.input_index);
+
+    if is_last_in_group && group_percent == 100 {
+        let allocated: u64 = route_plan
+            .iter()
+            .zip(prior_step_amounts)
+            .filter(|(s, _)| s.input_index == step.input_index)
+            .map(|(_, amount)| *amount)
+            .sum();
+        current_amount.checked_sub(allocated).ok_or(ErrorCode::InvalidCalculation.into())
+    } else {
+        Ok((current_amount as u128 * step.percent as u128 / 100) as u64)
+    }
+}
+
+/// Checks a step's estimated price impact against its adapter's cap
+///
+/// Impact is estimated from the step amount and the pool's input reserve as for a
+/// constant-product pool (see math::price_impact_bps). A cap of 0 means uncapped.
+/// Steps over the cap fail with ExcessivePriceImpact unless the user accepted high impact.
+///
+/// # Returns
+/// * `Result<u16>` - Estimated impact of the step in basis points
+pub fn check_step_impact(
+    step_amount: u64,
+    reserve_in: u64,
+    max_step_impact_bps: u16,
+    accept_high_impact: bool,
+) -> Result<u16> {
+    let impact_bps = math::price_impact_bps(step_amount, reserve_in)?;
+    if max_step_impact_bps > 0 && impact_bps > max_step_impact_bps {
+        if !accept_high_impact {
+            msg!("Step price impact {} bps exceeds the {} bps cap", impact_bps, max_step_impact_bps);
+            return Err(ErrorCode::ExcessivePriceImpact.into());
+        }
+        msg!("Step price impact {} bps exceeds the {} bps cap, accepted by the user", impact_bps, max_step_impact_bps);
+    }
+    Ok(impact_bps)
+}
+
+/// Adds a step's input to its pool's window volume, see PoolInfo::record_volume
+///
+/// Only capped pools are written back, so their PoolInfo must be passed writable while
+/// uncapped pools' may stay read-only.
+///
+/// # Returns
+/// * `Result<Option<PoolVolumeWarning>>` - The warning to emit when the window volume is now
+///   above `warning_pct` percent of the cap
+fn record_pool_volume<'info>(
+    pool_info_account: &'info AccountInfo<'info>,
+    amount: u64,
+    now: i64,
+    warning_pct: u8,
+) -> Result<Option<PoolVolumeWarning>> {
+    let mut pool_info = PoolInfo::load_checked(pool_info_account)?.into_inner();
+    if pool_info.max_volume_per_window == 0 {
+        return Ok(None);
+    }
+    let warn = pool_info.record_volume(amount, now, warning_pct)?;
+    pool_info.try_serialize(&mut &mut pool_info_account.try_borrow_mut_data()?[..])?;
+    Ok(warn.then(|| PoolVolumeWarning {
+        pool: pool_info.pool_address,
+        window_volume: pool_info.window_volume,
+        cap: pool_info.max_volume_per_window,
+    }))
 }
 
 /// Calculate the remaining accounts range for a specific step
@@ -28,7 +200,71 @@ fn calculate_adapter_accounts_range(
     (start_index, count)
 }
 
+/// Quotes a route plan from the pools' current state, without executing it
+///
+/// Amounts flow between vaults exactly as in execute_route, with each adapter's quote()
+/// standing in for its swap.
+///
+/// # Returns
+/// * `Result<Option<u64>>` - Estimated amount delivered into the destination vault, or None
+///   when a step's pool cannot be quoted from its vault balances
+pub fn quote_route<'info>(
+    adapter_registry: &Account<'info, AdapterRegistry>,
+    input_token_program: &AccountInfo<'info>,
+    vault_authority: &AccountInfo<'info>,
+    destination_vault: &AccountInfo<'info>,
+    route_plan: &[RoutePlanStep],
+    remaining_accounts: &'info [AccountInfo<'info>],
+    program_id: &Pubkey,
+    in_amount: u64,
+) -> Result<Option<u64>> {
+    let source_vault = source_vault_key(route_plan, remaining_accounts)?;
+    let mut ledger = VaultLedger::new(source_vault, in_amount);
+    let mut step_amounts: Vec<u64> = Vec::with_capacity(route_plan.len());
+
+    for (i, step) in route_plan.iter().enumerate() {
+        let input_account = remaining_accounts[step.input_index as usize].clone();
+        let step_amount = calculate_step_amount(route_plan, &step_amounts, ledger.amount(&input_account.key()))?;
+        step_amounts.push(step_amount);
+
+        let output_account_info = remaining_accounts[step.output_index as usize].clone();
+        let adapter = get_adapter(&step.swap, adapter_registry)?;
+        let adapter_ctx = AdapterContext {
+            token_program: input_token_program.clone(),
+            authority: vault_authority.clone(),
+            input_account,
+            output_account: output_account_info.clone(),
+            remaining_accounts,
+            program_id: *program_id,
+        };
+        let (adapter_start_index, adapter_accounts_count) = calculate_adapter_accounts_range(step, route_plan, i);
+        let Some(quoted_amount) = adapter.quote(adapter_ctx, step_amount, adapter_start_index, adapter_accounts_count)? else {
+            return Ok(None);
+        };
+
+        ledger.credit(output_account_info.key(), quoted_amount)?;
+    }
+
+    // As in execute_route, a route ending where it starts does not count its own input
+    let delivered = ledger.amount(&destination_vault.key());
+    if destination_vault.key() == source_vault {
+        return delivered.checked_sub(in_amount).map(Some).ok_or(ErrorCode::InvalidCalculation.into());
+    }
+    Ok(Some(delivered))
+}
+
 /// Executes a route plan, handling partial swaps, multi-hop swaps, and partial multi-hop swaps
+///
+/// Amounts are tracked per vault: each step's output is the balance change of its output
+/// vault, and the route's total is the balance change of the destination vault over the
+/// whole route, so splits whose branches hop through different vaults are counted exactly.
+///
+/// Each step is checked against its adapter's price impact cap right before it executes,
+/// when its input amount is known; `accept_high_impact` lets steps over the cap through.
+/// Its input is then counted against its pool's volume cap; the returned warnings are the
+/// PoolVolumeWarnings of steps that took a pool above `pool_volume_warning_pct` of its cap.
+/// A two-hop step counts against its first pool only.
+#[allow(clippy::too_many_arguments)]
 pub fn execute_route<'info>(
     adapter_registry: &Account<'info, AdapterRegistry>,
     input_token_program: &AccountInfo<'info>,
@@ -39,37 +275,43 @@ pub fn execute_route<'info>(
     remaining_accounts: &'info [AccountInfo<'info>],
     program_id: &Pubkey,
     in_amount: u64,
-) -> Result<(u64, Vec<SwapEventData>)> {
-    let mut current_amount = in_amount;
-    let mut total_output_amount: u64 = 0;
+    accept_high_impact: bool,
+    pool_volume_warning_pct: u8,
+) -> Result<(u64, Vec<SwapEventData>, Vec<PoolVolumeWarning>)> {
+    let source_vault = source_vault_key(route_plan, remaining_accounts)?;
+    let mut ledger = VaultLedger::new(source_vault, in_amount);
     let mut event_data: Vec<SwapEventData> = Vec::new();
+    let mut volume_warnings: Vec<PoolVolumeWarning> = Vec::new();
+    let now = Clock::get()?.unix_timestamp;
 
-    // Get destination mint from vault
-    let account_data = destination_vault.try_borrow_data()?;
-    let destination_vault_data = TokenAccount::try_deserialize(&mut account_data.as_ref())?;
-    let destination_mint = destination_vault_data.mint;
-    drop(account_data); // Release borrow
+    // The destination vault's balance change is the authoritative route output. The input
+    // leaves the source vault, so a route ending where it starts (SOL -> X -> SOL through the
+    // lamport escrow) counts from the balance without it
+    let (_, mut destination_balance_before) = vault_balance(destination_vault)?;
+    if destination_vault.key() == source_vault {
+        destination_balance_before = destination_balance_before
+            .checked_sub(in_amount)
+            .ok_or(ErrorCode::InvalidCalculation)?;
+    }
+
+    let mut step_amounts: Vec<u64> = Vec::with_capacity(route_plan.len());
 
     // Process each step in the route plan
     for (i, step) in route_plan.iter().enumerate() {
-        // Calculate input amount for this step
-        let step_amount = if step.percent == 100 {
-            current_amount
-        } else {
-            (current_amount as u128 * step.percent as u128 / 100) as u64
-        };
-
         let input_vault_account = &remaining_accounts[step.input_index as usize];
 
+        // Calculate input amount for this step from what the route put in its input vault
+        // (last step of a split takes the remainder)
+        let step_amount = calculate_step_amount(route_plan, &step_amounts, ledger.amount(&input_vault_account.key()))?;
+        step_amounts.push(step_amount);
+
         // Determine input mint from the input vault (not from previous event)
         // This is important for partial swaps where multiple steps share the same input_index
-        let input_vault_data = input_vault_account.try_borrow_data()?;
-        let input_vault_token_account = TokenAccount::try_deserialize(&mut input_vault_data.as_ref())?;
-        let step_input_mint = input_vault_token_account.mint;
-        drop(input_vault_data);
+        let (step_input_mint, _) = vault_balance(input_vault_account)?;
 
         // Always use vault for output (either intermediate or destination)
         let output_account_info = remaining_accounts[step.output_index as usize].clone();
+        let (_, output_balance_before) = vault_balance(&output_account_info)?;
 
         // Get adapter
         let adapter = get_adapter(&step.swap, adapter_registry)?;
@@ -77,7 +319,7 @@ pub fn execute_route<'info>(
             .supported_adapters
             .iter()
             .find(|a| a.swap_type == step.swap)
-            .ok_or(ErrorCode::SwapNotSupported)?;
+            .ok_or(ErrorCode::AdapterNotConfigured)?;
 
         // Create adapter context
         let adapter_ctx = AdapterContext {
@@ -89,56 +331,62 @@ pub fn execute_route<'info>(
             program_id: *program_id,
         };
 
+        // The vault authority only signs for its own vaults
+        adapter_ctx.verify_input_owner()?;
+
         // Calculate correct start index and count for adapter
         let (adapter_start_index, adapter_accounts_count) = calculate_adapter_accounts_range(step, route_plan, i);
 
-        // Execute the swap with correct range
-        let swap_result = adapter.execute_swap(adapter_ctx, step_amount, adapter_start_index, adapter_accounts_count)?;
-
-        // Determine output mint
-        let account_data = output_account_info.try_borrow_data()?;
-        let output_vault_data = TokenAccount::try_deserialize(&mut account_data.as_ref())?;
-        let output_mint = output_vault_data.mint;
-        drop(account_data);
-
-        // Check if this is part of a partial swap (multiple steps share the same input_index)
-        let is_partial_swap_step = step.percent < 100 && route_plan.iter().any(|s| 
-            s.input_index == step.input_index && s.percent < 100
-        );
-
-        // Check if there are more steps with the same input_index after this one
-        let has_more_partial_steps = is_partial_swap_step && route_plan.iter().skip(i + 1).any(|s| 
-            s.input_index == step.input_index
-        );
-
-        // Update amounts: accumulate only if output mint matches destination_mint
-        if output_mint == destination_mint {
-            total_output_amount = total_output_amount.saturating_add(swap_result.output_amount);
-        } else {
-            // For non-final steps in multi-hop, update current_amount
-            // BUT: Don't update for partial swap steps that output to intermediate tokens
-            // if there are more partial steps with the same input_index, as those steps
-            // still need to use the original input amount
-            if !has_more_partial_steps {
-                // This is the last step with this input_index, safe to update current_amount
-                current_amount = swap_result.output_amount;
+        // Reject steps that would move the pool price beyond the adapter's cap
+        let max_step_impact_bps = adapter_registry.max_step_impact_bps(&step.swap);
+        if max_step_impact_bps > 0 {
+            let reserve_in = adapter.input_reserve(adapter_ctx.clone(), adapter_start_index, adapter_accounts_count)?;
+            check_step_impact(step_amount, reserve_in, max_step_impact_bps, accept_high_impact)?;
+        }
+
+        // Count the step against its pool's volume cap; escrow steps have no PoolInfo
+        if !matches!(step.swap, Swap::WrapSol | Swap::UnwrapSol) {
+            let pool_info_account = remaining_accounts
+                .get(step.input_index as usize + 1)
+                .ok_or(ErrorCode::NotEnoughAccountKeys)?;
+            if let Some(warning) = record_pool_volume(pool_info_account, step_amount, now, pool_volume_warning_pct)? {
+                volume_warnings.push(warning);
             }
-            // For partial swap steps with intermediate output and more steps to come,
-            // keep current_amount unchanged so subsequent partial steps can still
-            // calculate based on original input
         }
 
+        // Execute the swap with correct range
+        let swap_result = adapter
+            .execute_swap(adapter_ctx, step_amount, adapter_start_index, adapter_accounts_count)
+            .map_err(|err| {
+                msg!("Route step {} ({:?}) failed", i, step.swap);
+                err
+            })?;
+
+        // Credit what actually arrived in the output vault to the branch continuing from it
+        let (output_mint, output_balance_after) = vault_balance(&output_account_info)?;
+        let step_output_amount = output_balance_after
+            .checked_sub(output_balance_before)
+            .ok_or(ErrorCode::InvalidCalculation)?;
+        ledger.credit(output_account_info.key(), step_output_amount)?;
+
         // Record swap event
         // Use step_input_mint (from input vault) instead of previous event's output_mint
         // This correctly handles partial swaps where multiple steps share the same input_index
         event_data.push(SwapEventData {
+            swap: step.swap.clone(),
             amm: adapter_info.program_id,
             input_mint: step_input_mint,
             input_amount: step_amount,
             output_mint,
             output_amount: swap_result.output_amount,
+            host_fee_amount: swap_result.host_fee_amount,
         });
     }
 
-    Ok((total_output_amount, event_data))
-}
\ No newline at end of file
+    let (_, destination_balance_after) = vault_balance(destination_vault)?;
+    let total_output_amount = destination_balance_after
+        .checked_sub(destination_balance_before)
+        .ok_or(ErrorCode::InvalidCalculation)?;
+
+    Ok((total_output_amount, event_data, volume_warnings))
+}
@@ -1,7 +1,19 @@
 use anchor_lang::prelude::*;
 use anchor_spl::token_interface::{TokenAccount, TokenInterface};
-use crate::adapters::adapter_connector_module::{AdapterContext, get_adapter};
+use anchor_spl::token_2022::spl_token_2022::{
+    extension::{
+        interest_bearing_mint::InterestBearingConfig, transfer_fee::TransferFeeConfig, BaseStateWithExtensions,
+        StateWithExtensions,
+    },
+    state::Mint as Token2022Mint,
+};
+use anchor_spl::token::spl_token::native_mint;
+use crate::adapters::adapter_connector_module::{AdapterContext, adapter_min_accounts, adapter_status, get_adapter};
+use crate::adapters::whirlpool_two_hop;
+use crate::adapters::wsol::is_sol_escrow;
 use crate::errors::ErrorCode;
+use crate::math;
+use crate::instructions::route_executor_module::{calculate_step_amount, VaultLedger};
 use crate::state::*;
 
 // Test modules
@@ -21,6 +33,34 @@ pub fn validate_token_program(program_account: &AccountInfo) -> Result<()> {
     Ok(())
 }
 
+/// Deserializes a token account the validator relies on, after checking it is owned by SPL Token
+/// or Token-2022; any other owner could hand over layout-compatible data with arbitrary fields
+pub fn read_token_account(account: &AccountInfo) -> Result<TokenAccount> {
+    if account.owner != &TOKEN_PROGRAM_ID && account.owner != &TOKEN_2022_PROGRAM_ID {
+        return Err(ErrorCode::InvalidAccountOwner.into());
+    }
+    // token_interface::TokenAccount only reads the base 165 bytes, so Token-2022
+    // accounts with extensions deserialize too
+    TokenAccount::try_deserialize(&mut account.try_borrow_data()?.as_ref())
+}
+
+/// Size of the base token account layout shared by SPL Token and Token-2022
+pub const TOKEN_ACCOUNT_BASE_LEN: usize = 165;
+
+/// Offset of the AccountState byte in the base token account layout (0 = uninitialized)
+const TOKEN_ACCOUNT_STATE_OFFSET: usize = 108;
+
+/// Reads an account met while scanning remaining_accounts for a vault, or None if it is not a
+/// token account. Accounts too short for the base layout or uninitialized are skipped before
+/// any deserialization work.
+pub fn scan_token_account(account: &AccountInfo) -> Option<TokenAccount> {
+    let data = account.try_borrow_data().ok()?;
+    if data.len() < TOKEN_ACCOUNT_BASE_LEN || data[TOKEN_ACCOUNT_STATE_OFFSET] == 0 {
+        return None;
+    }
+    TokenAccount::try_deserialize(&mut data.as_ref()).ok()
+}
+
 /// Validates that a mint is compatible with the specified token program
 pub fn validate_mint_program_compatibility(mint_account: &AccountInfo, token_program: &AccountInfo) -> Result<()> {
     if mint_account.owner != &token_program.key() {
@@ -29,6 +69,252 @@ pub fn validate_mint_program_compatibility(mint_account: &AccountInfo, token_pro
     Ok(())
 }
 
+/// Returns the Token-2022 transfer fee withheld when transferring `amount` of this mint
+/// in the current epoch. SPL Token mints and mints without TransferFeeConfig charge nothing.
+pub fn calculate_transfer_fee(mint_account: &AccountInfo, amount: u64) -> Result<u64> {
+    if mint_account.owner != &TOKEN_2022_PROGRAM_ID {
+        return Ok(0);
+    }
+    let data = mint_account.try_borrow_data()?;
+    let mint = StateWithExtensions::<Token2022Mint>::unpack(&data)?;
+    match mint.get_extension::<TransferFeeConfig>() {
+        Ok(config) => calculate_epoch_transfer_fee(config, Clock::get()?.epoch, amount),
+        Err(_) => Ok(0),
+    }
+}
+
+/// Returns the transfer fee for `amount` under the fee schedule active in `epoch`
+pub fn calculate_epoch_transfer_fee(config: &TransferFeeConfig, epoch: u64, amount: u64) -> Result<u64> {
+    config
+        .calculate_epoch_fee(epoch, amount)
+        .ok_or(ErrorCode::InvalidCalculation.into())
+}
+
+/// Returns the amount that actually arrives when transferring `amount` of this mint
+pub fn net_transfer_amount(mint_account: &AccountInfo, amount: u64) -> Result<u64> {
+    let fee = calculate_transfer_fee(mint_account, amount)?;
+    amount.checked_sub(fee).ok_or(ErrorCode::InvalidCalculation.into())
+}
+
+/// Seconds per year used by the Token-2022 interest-bearing extension to annualize its rate
+pub const INTEREST_SECONDS_PER_YEAR: f64 = 60.0 * 60.0 * 24.0 * 365.24;
+
+/// Returns the mint's InterestBearingConfig, or None for SPL Token mints and Token-2022 mints
+/// without the extension
+pub fn interest_bearing_config(mint_account: &AccountInfo) -> Result<Option<InterestBearingConfig>> {
+    if mint_account.owner != &TOKEN_2022_PROGRAM_ID {
+        return Ok(None);
+    }
+    let data = mint_account.try_borrow_data()?;
+    let mint = StateWithExtensions::<Token2022Mint>::unpack(&data)?;
+    Ok(mint.get_extension::<InterestBearingConfig>().ok().copied())
+}
+
+/// Returns the factor wallets apply to raw amounts of this mint to display UI amounts, scaled
+/// by UI_AMOUNT_MULTIPLIER_SCALE. Mints without the interest-bearing extension report exactly
+/// the scale; quotes, floors and triggers always use raw amounts.
+pub fn ui_amount_multiplier(mint_account: &AccountInfo) -> Result<u64> {
+    match interest_bearing_config(mint_account)? {
+        Some(config) => calculate_interest_multiplier(&config, Clock::get()?.unix_timestamp),
+        None => Ok(UI_AMOUNT_MULTIPLIER_SCALE),
+    }
+}
+
+/// Returns the interest accrued by an interest-bearing mint at `unix_timestamp`, scaled by
+/// UI_AMOUNT_MULTIPLIER_SCALE: the average rate up to the last rate update compounded
+/// continuously, then the current rate since
+pub fn calculate_interest_multiplier(config: &InterestBearingConfig, unix_timestamp: i64) -> Result<u64> {
+    let initialized_at = i64::from(config.initialization_timestamp);
+    let last_update = i64::from(config.last_update_timestamp);
+    let growth = |rate_bps: i16, timespan: i64| -> f64 {
+        (f64::from(rate_bps) / 10_000.0 * timespan.max(0) as f64 / INTEREST_SECONDS_PER_YEAR).exp()
+    };
+
+    let multiplier = growth(i16::from(config.pre_update_average_rate), last_update.saturating_sub(initialized_at))
+        * growth(i16::from(config.current_rate), unix_timestamp.saturating_sub(last_update))
+        * UI_AMOUNT_MULTIPLIER_SCALE as f64;
+    require!(
+        multiplier.is_finite() && multiplier < u64::MAX as f64,
+        ErrorCode::InvalidCalculation
+    );
+    Ok(multiplier as u64)
+}
+
+/// Returns the lowest output allowed by a quote and slippage tolerance
+pub fn calculate_slippage_min_out(quoted_out_amount: u64, slippage_bps: u16) -> Result<u64> {
+    math::apply_bps_discount(quoted_out_amount, slippage_bps)
+}
+
+/// Checks the net output against the quote-based floor and, when non-zero, the caller's own
+/// min_out_amount. Both must hold, so whichever floor is stricter decides.
+pub fn validate_output_floors(
+    net_output_amount: u64,
+    quoted_out_amount: u64,
+    slippage_bps: u16,
+    min_out_amount: u64,
+) -> Result<()> {
+    require!(
+        net_output_amount >= calculate_slippage_min_out(quoted_out_amount, slippage_bps)?,
+        ErrorCode::SlippageToleranceExceeded
+    );
+    if min_out_amount > 0 && net_output_amount < min_out_amount {
+        msg!("Output {} is below user minimum {}", net_output_amount, min_out_amount);
+        return Err(ErrorCode::BelowUserMinimum.into());
+    }
+    Ok(())
+}
+
+/// Checks a caller's quoted_out_amount lies within band_bps of the on-chain quote, edges included,
+/// so a tampered quote cannot loosen the slippage check
+pub fn check_quote_sanity(quoted_out_amount: u64, onchain_quote: u64, band_bps: u16) -> Result<()> {
+    let band_bps = band_bps as u128;
+    let onchain_quote = onchain_quote as u128;
+    // Compare quoted * 10000 against on-chain * (10000 ± band) to avoid rounding the band edges
+    let scaled_quote = quoted_out_amount as u128 * 10_000;
+    let lower = onchain_quote * (10_000 - band_bps);
+    let upper = onchain_quote * (10_000 + band_bps);
+    if scaled_quote < lower || scaled_quote > upper {
+        msg!("Quoted output {} is outside {} bps of the on-chain quote {}", quoted_out_amount, band_bps, onchain_quote);
+        return Err(ErrorCode::ImplausibleQuote.into());
+    }
+    Ok(())
+}
+
+/// Rejects a platform fee account with a delegate, or with a close authority other than the
+/// vault authority, since either could move or destroy accrued fees before they are withdrawn
+pub fn validate_fee_account_authorities(fee_account: &TokenAccount, vault_authority: &Pubkey) -> Result<()> {
+    if fee_account.delegate.is_some() {
+        return Err(ErrorCode::UnsafeFeeAccount.into());
+    }
+    if let Some(close_authority) = Option::<Pubkey>::from(fee_account.close_authority) {
+        if close_authority != *vault_authority {
+            return Err(ErrorCode::UnsafeFeeAccount.into());
+        }
+    }
+    Ok(())
+}
+
+/// Picks the platform fee account a fee site collects into. A fee of 0 bps collects nothing,
+/// so a passed account is ignored without validation; any other fee requires the account.
+///
+/// # Returns
+/// * `Result<Option<T>>` - None when fee_bps is 0, the account otherwise, or MissingFeeAccount
+pub fn charged_fee_account<T>(fee_bps: u8, platform_fee_account: Option<T>) -> Result<Option<T>> {
+    if fee_bps == 0 {
+        return Ok(None);
+    }
+    platform_fee_account
+        .map(Some)
+        .ok_or_else(|| ErrorCode::MissingFeeAccount.into())
+}
+
+/// Checks a platform fee account is a vault authority owned account of fee_mint that is
+/// safe to accrue fees into
+pub fn validate_platform_fee_vault(fee_account: &TokenAccount, fee_mint: &Pubkey, vault_authority: &Pubkey) -> Result<()> {
+    if fee_account.owner != *vault_authority {
+        return Err(ErrorCode::InvalidPlatformFeeOwner.into());
+    }
+    if fee_account.mint != *fee_mint {
+        return Err(ErrorCode::InvalidPlatformFeeMint.into());
+    }
+    validate_fee_account_authorities(fee_account, vault_authority)
+}
+
+/// Finds the fee vault the FeeVaultRegistry maps fee_mint to among remaining_accounts,
+/// so routes called with use_registered_fee_vault cannot collect into a mismatched account
+///
+/// # Arguments
+/// * `fee_vault_registry` - The ["fee_vault_registry"] PDA
+/// * `fee_mint` - Mint the route collects its platform fee in
+/// * `vault_authority` - Vault authority PDA the fee vault must belong to
+/// * `remaining_accounts` - The route's remaining accounts, which must include the fee vault
+/// * `program_id` - This program's ID
+///
+/// # Returns
+/// * `Result<&AccountInfo>` - FeeVaultNotRegistered without an entry for fee_mint,
+///   RegisteredFeeVaultNotProvided when the vault is not among remaining_accounts
+pub fn find_registered_fee_vault<'a, 'info>(
+    fee_vault_registry: &AccountInfo<'info>,
+    fee_mint: &Pubkey,
+    vault_authority: &Pubkey,
+    remaining_accounts: &'a [AccountInfo<'info>],
+    program_id: &Pubkey,
+) -> Result<&'a AccountInfo<'info>> {
+    let fee_vault_key = FeeVaultRegistry::load(fee_vault_registry, program_id)?
+        .and_then(|registry| registry.fee_vault_for(fee_mint))
+        .ok_or(ErrorCode::FeeVaultNotRegistered)?;
+    let fee_vault = remaining_accounts
+        .iter()
+        .find(|account| account.key() == fee_vault_key)
+        .ok_or(ErrorCode::RegisteredFeeVaultNotProvided)?;
+
+    // The vault may have been closed or reassigned since it was registered
+    let token_account = read_token_account(fee_vault)?;
+    validate_platform_fee_vault(&token_account, fee_mint, vault_authority)?;
+    Ok(fee_vault)
+}
+
+/// State of a vault authority owned token account taken before a route that skipped
+/// validate_route, so the route's effect on protocol and escrow vaults can be checked afterwards
+#[derive(Clone, Debug, PartialEq)]
+pub struct VaultSnapshot {
+    pub key: Pubkey,
+    pub amount: u64,
+    pub delegate: Option<Pubkey>,
+    pub close_authority: Option<Pubkey>,
+}
+
+/// Reads a token account owned by the vault authority; anything else yields None
+fn read_vault(account: &AccountInfo, vault_authority: &Pubkey) -> Option<TokenAccount> {
+    let token_account = read_token_account(account).ok()?;
+    (token_account.owner == *vault_authority).then_some(token_account)
+}
+
+/// Snapshots every distinct vault authority owned token account among `accounts`
+pub fn snapshot_vaults(accounts: &[AccountInfo], vault_authority: &Pubkey) -> Vec<VaultSnapshot> {
+    let mut snapshots: Vec<VaultSnapshot> = Vec::new();
+    for account in accounts {
+        if snapshots.iter().any(|snapshot| snapshot.key == account.key()) {
+            continue;
+        }
+        if let Some(vault) = read_vault(account, vault_authority) {
+            snapshots.push(VaultSnapshot {
+                key: account.key(),
+                amount: vault.amount,
+                delegate: vault.delegate.into(),
+                close_authority: vault.close_authority.into(),
+            });
+        }
+    }
+    snapshots
+}
+
+/// Checks that no snapshotted vault was closed, handed to another owner, given a new delegate or
+/// close authority, or left with less than its snapshotted balance
+pub fn verify_vault_snapshots(
+    snapshots: &[VaultSnapshot],
+    accounts: &[AccountInfo],
+    vault_authority: &Pubkey,
+) -> Result<()> {
+    for snapshot in snapshots {
+        let vault = accounts
+            .iter()
+            .find(|account| account.key() == snapshot.key)
+            .and_then(|account| read_vault(account, vault_authority))
+            .ok_or(ErrorCode::VaultInvariantViolated)?;
+        let delegate: Option<Pubkey> = vault.delegate.into();
+        let close_authority: Option<Pubkey> = vault.close_authority.into();
+        if vault.amount < snapshot.amount
+            || delegate != snapshot.delegate
+            || close_authority != snapshot.close_authority
+        {
+            msg!("Vault {} changed: amount {} -> {}", snapshot.key, snapshot.amount, vault.amount);
+            return Err(ErrorCode::VaultInvariantViolated.into());
+        }
+    }
+    Ok(())
+}
+
 /// Calculate the remaining accounts range for a specific step
 fn calculate_adapter_accounts_range(
     step: &RoutePlanStep,
@@ -43,226 +329,515 @@ fn calculate_adapter_accounts_range(
     (start_index, count)
 }
 
-/// Validates the route plan and associated accounts
-pub fn validate_route<'info>(
-    adapter_registry: &Account<'info, AdapterRegistry>,
-    input_token_program: &AccountInfo<'info>,
-    output_token_program: &AccountInfo<'info>,
-    vault_authority: &AccountInfo<'info>,
-    source_mint: &AccountInfo<'info>,
-    destination_mint: &AccountInfo<'info>,
+/// Validates that remaining_accounts is long enough for every step of the route plan
+///
+/// Each step needs its input vault, the adapter accounts (pool_info first, program id last,
+/// at least the adapter's MIN_ACCOUNTS) and its output vault. Steps may share vaults,
+/// so the total requirement is the highest account index used by any step plus one.
+/// On failure the shortfall is logged before returning InsufficientRouteAccounts.
+pub fn validate_route_accounts_count(
     route_plan: &[RoutePlanStep],
-    remaining_accounts: &'info [AccountInfo<'info>],
-    program_id: &Pubkey,
-    in_amount: u64,
+    remaining_accounts_len: usize,
 ) -> Result<()> {
-    // Validate route plan emptiness
-    if route_plan.is_empty() {
-        return Err(ErrorCode::EmptyRoute.into());
+    let mut required_len = 0usize;
+
+    for (i, step) in route_plan.iter().enumerate() {
+        let min_accounts = adapter_min_accounts(&step.swap)?;
+        let input_index = step.input_index as usize;
+        let output_index = step.output_index as usize;
+
+        // Adapter accounts sit strictly between the input and output vaults
+        let adapter_accounts_count = output_index.saturating_sub(input_index + 1);
+        if adapter_accounts_count < min_accounts {
+            msg!(
+                "Step {}: {:?} requires {} adapter accounts between input_index {} and output_index {}, got {} (short by {})",
+                i,
+                step.swap,
+                min_accounts,
+                input_index,
+                output_index,
+                adapter_accounts_count,
+                min_accounts - adapter_accounts_count
+            );
+            return Err(ErrorCode::InsufficientRouteAccounts.into());
+        }
+
+        required_len = required_len.max(output_index + 1);
     }
 
-    // Validate token programs
-    validate_token_program(input_token_program)?;
-    validate_token_program(output_token_program)?;
+    if remaining_accounts_len < required_len {
+        msg!(
+            "Route requires {} remaining accounts, got {} (short by {})",
+            required_len,
+            remaining_accounts_len,
+            required_len - remaining_accounts_len
+        );
+        return Err(ErrorCode::InsufficientRouteAccounts.into());
+    }
 
-    // Validate mint compatibility with token programs
-    validate_mint_program_compatibility(source_mint, input_token_program)?;
-    validate_mint_program_compatibility(destination_mint, output_token_program)?;
+    Ok(())
+}
 
-    // Validate input vault using token_interface
-    // Note: token_interface::TokenAccount::try_deserialize works with Token2022 accounts
-    // that have extensions (e.g., confidential transfer, 179 bytes = 165 base + 14 extension)
-    // because it only reads the base TokenAccount structure (first 165 bytes),
-    // and extensions remain untouched.
-    let input_vault = remaining_accounts
-        .iter()
-        .find(|acc| {
-            // Try to deserialize as TokenAccount using interface
-            if let Ok(account_data) = acc.try_borrow_data() {
-                if let Ok(token_account) = TokenAccount::try_deserialize(&mut account_data.as_ref()) {
-                    token_account.mint == source_mint.key()
-                } else {
-                    false
-                }
-            } else {
-                false
-            }
+/// Returns the inclusive remaining_accounts index range touched by a route plan
+pub fn route_plan_index_range(route_plan: &[RoutePlanStep]) -> Option<(u8, u8)> {
+    route_plan.iter().fold(None, |range, step| {
+        let low = step.input_index.min(step.output_index);
+        let high = step.input_index.max(step.output_index);
+        Some(match range {
+            None => (low, high),
+            Some((min, max)) => (min.min(low), max.max(high)),
         })
-        .ok_or(ErrorCode::VaultNotFound)?;
+    })
+}
 
-    // Detect partial swaps and multi-hop swaps
-    let mut input_vault_key = None;
-    let mut is_partial_swap = false;
-    let mut is_multi_hop = false;
-    let mut used_dexes = Vec::new();
-    let mut output_mints = Vec::new();
-    let mut current_amount = in_amount;
+/// Returns true if two route plans use overlapping remaining_accounts index ranges
+pub fn route_plans_overlap(a: &[RoutePlanStep], b: &[RoutePlanStep]) -> bool {
+    match (route_plan_index_range(a), route_plan_index_range(b)) {
+        (Some((a_min, a_max)), Some((b_min, b_max))) => a_min <= b_max && b_min <= a_max,
+        _ => false,
+    }
+}
 
-    for i in 0..route_plan.len() {
-        let step = &route_plan[i];
+/// A route plan step with its accounts resolved to the data the route rules depend on,
+/// so those rules can be checked without AccountInfo
+#[derive(Clone, Debug, PartialEq)]
+pub struct ResolvedStep {
+    pub input_vault: Pubkey,  // Key of remaining_accounts[input_index]
+    pub input_mint: Pubkey,   // Mint of the input vault
+    pub output_vault: Pubkey, // Key of remaining_accounts[output_index]
+    pub output_mint: Pubkey,  // Mint of the output vault
+    pub pool: Pubkey,         // Pool address of the step's PoolInfo
+    pub input_vault_delegated: bool,  // Whether the input vault has a delegate set
+    pub output_vault_delegated: bool, // Whether the output vault has a delegate set
+    pub pool_verified_slot: Option<u64>, // PoolInfo.last_verified_slot (None for escrow steps, which have no pool)
+    pub swap: Swap,
+    pub percent: u8,
+    pub input_index: u8,
+    pub output_index: u8,
+}
 
-        // Validate percent is non-zero and within bounds
-        if step.percent == 0 || step.percent > 100 {
-            return Err(ErrorCode::InvalidPercent.into());
+impl ResolvedStep {
+    /// Returns the route plan step this was resolved from
+    pub fn plan_step(&self) -> RoutePlanStep {
+        RoutePlanStep {
+            swap: self.swap.clone(),
+            percent: self.percent,
+            input_index: self.input_index,
+            output_index: self.output_index,
         }
+    }
+}
 
-        // Validate account indices
-        if step.input_index as usize >= remaining_accounts.len() {
-            return Err(ErrorCode::InvalidAccountIndex.into());
+/// Returns true if a step reads the vault the immediately previous step wrote to.
+/// Partial swaps sharing an input vault share an output vault too, so a hop always
+/// chains from step i - 1.
+fn chains_from_previous(steps: &[ResolvedStep], i: usize) -> bool {
+    i > 0 && steps[i].input_vault == steps[i - 1].output_vault
+}
+
+/// Checks every step's percent is within 1..=100
+pub fn validate_step_percents(steps: &[ResolvedStep]) -> Result<()> {
+    if steps.iter().any(|step| step.percent == 0 || step.percent > 100) {
+        return Err(ErrorCode::InvalidPercent.into());
+    }
+    Ok(())
+}
+
+/// Checks that no step's share of its input rounds to zero, simulating how amounts flow
+/// from vault to vault (each step passing its full input on, as execute_route's ledger would)
+pub fn validate_step_amounts(steps: &[ResolvedStep], in_amount: u64) -> Result<()> {
+    let route_plan: Vec<RoutePlanStep> = steps.iter().map(ResolvedStep::plan_step).collect();
+    let source_vault = steps.first().ok_or(ErrorCode::EmptyRoute)?.input_vault;
+    let mut ledger = VaultLedger::new(source_vault, in_amount);
+    let mut step_amounts: Vec<u64> = Vec::with_capacity(steps.len());
+
+    for (i, step) in steps.iter().enumerate() {
+        // Last step of a split takes the remainder
+        let available = ledger.amount(&step.input_vault);
+        let step_amount = calculate_step_amount(&route_plan, &step_amounts, available)?;
+        if step_amount == 0 {
+            msg!(
+                "Step {} amount rounds to zero ({}% of {}); increase the input amount",
+                i,
+                step.percent,
+                available
+            );
+            return Err(ErrorCode::StepAmountTooSmall.into());
         }
+        step_amounts.push(step_amount);
+        ledger.credit(step.output_vault, step_amount)?; // Simulate passing amount to next step
+    }
 
-        // Validate output_index for all steps (including final step)
-        // This prevents out-of-bounds access when accessing remaining_accounts[output_index]
-        if step.output_index as usize >= remaining_accounts.len() {
-            return Err(ErrorCode::InvalidAccountIndex.into());
+    Ok(())
+}
+
+/// Checks the route starts at source_mint, ends at destination_mint and, once it turns
+/// multi-hop, that each step's input mint is the previous step's output mint
+pub fn validate_hop_chain(steps: &[ResolvedStep], source_mint: &Pubkey, destination_mint: &Pubkey) -> Result<()> {
+    let (first, last) = match (steps.first(), steps.last()) {
+        (Some(first), Some(last)) => (first, last),
+        _ => return Err(ErrorCode::EmptyRoute.into()),
+    };
+
+    let mut is_multi_hop = false;
+    for i in 0..steps.len() {
+        is_multi_hop |= chains_from_previous(steps, i);
+        if is_multi_hop && i > 0 && steps[i].input_mint != steps[i - 1].output_mint {
+            return Err(ErrorCode::InvalidMultiHopRoute.into());
         }
+    }
 
-        let input_vault_account = &remaining_accounts[step.input_index as usize];
-        let output_account_info = remaining_accounts[step.output_index as usize].clone();
+    if last.output_mint != *destination_mint || first.input_mint != *source_mint {
+        return Err(ErrorCode::InvalidMint.into());
+    }
+    Ok(())
+}
 
-        // Check step amount
-        let step_amount = if step.percent == 100 {
-            current_amount
-        } else {
-            (current_amount as u128 * step.percent as u128 / 100) as u64
-        };
-        if step_amount == 0 {
-            return Err(ErrorCode::InvalidCalculation.into());
+/// Checks every step uses an enabled adapter and a pool its whitelist allows
+pub fn validate_step_pools(steps: &[ResolvedStep], adapter_registry: &AdapterRegistry) -> Result<()> {
+    for step in steps {
+        adapter_status(&step.swap, adapter_registry).require_enabled(&step.swap)?;
+        if !adapter_registry.is_pool_allowed(&step.swap, &step.pool) {
+            return Err(ErrorCode::PoolNotWhitelisted.into());
         }
+    }
+    Ok(())
+}
 
-        // Check for partial swaps: same input vault and percent < 100
-        if step.percent < 100 {
-            if input_vault_key.is_none() {
-                input_vault_key = Some(input_vault_account.key());
-            }
-            if input_vault_key == Some(input_vault_account.key()) {
-                is_partial_swap = true;
-            }
-            // Track DEXes for partial swaps
-            let adapter_info = adapter_registry
-                .supported_adapters
-                .iter()
-                .find(|a| a.swap_type == step.swap)
-                .ok_or(ErrorCode::SwapNotSupported)?;
-            if !used_dexes.contains(&adapter_info.program_id) {
-                used_dexes.push(adapter_info.program_id);
-            }
+/// Checks no step goes through a sandboxed adapter the route's user is not allowed on
+pub fn validate_sandbox_access(route_plan: &[RoutePlanStep], adapter_registry: &AdapterRegistry, route_user: &Pubkey) -> Result<()> {
+    for step in route_plan {
+        if !adapter_registry.is_sandbox_user_allowed(&step.swap, route_user) {
+            return Err(ErrorCode::AdapterInSandbox.into());
         }
+    }
+    Ok(())
+}
 
-        // Check for multi-hop: input vault matches the immediately previous step's output vault
-        // Note: Since partial swaps with the same input vault must use the same output vault,
-        // multi-hop always chains from the immediately previous step (i-1)
-        if i > 0 {
-            let prev_step = &route_plan[i - 1];
-            let prev_output_vault = &remaining_accounts[prev_step.output_index as usize];
-            if input_vault_account.key() == prev_output_vault.key() {
-                is_multi_hop = true;
-            }
+/// Checks partial swaps: the steps splitting the first partially spent input vault must add
+/// up to 100%, and partial steps must go through at least two distinct pools. Two pools of
+/// the same DEX qualify; splitting into the same pool twice does not.
+pub fn validate_partial_swaps(steps: &[ResolvedStep]) -> Result<()> {
+    let mut split_vault = None;
+    let mut used_pools = Vec::new();
+
+    for step in steps.iter().filter(|step| step.percent < 100) {
+        split_vault.get_or_insert(step.input_vault);
+        if !used_pools.contains(&step.pool) {
+            used_pools.push(step.pool);
         }
+    }
 
-        // Validate multi-hop: ensure input mint matches previous step's output mint
-        if is_multi_hop && i > 0 {
-            let account_data = input_vault_account.try_borrow_data()?;
-            let input_vault_data = TokenAccount::try_deserialize(&mut account_data.as_ref())?;
-            let prev_output_mint = output_mints[i - 1];
-            if input_vault_data.mint != prev_output_mint {
-                return Err(ErrorCode::InvalidMultiHopRoute.into());
-            }
+    if let Some(split_vault) = split_vault {
+        let total_percent: u32 = steps
+            .iter()
+            .filter(|step| step.input_vault == split_vault)
+            .map(|step| step.percent as u32)
+            .sum();
+        if total_percent != 100 {
+            return Err(ErrorCode::InvalidPartialSwapPercent.into());
+        }
+        if used_pools.len() < 2 {
+            return Err(ErrorCode::InsufficientDexesForPartialSwap.into());
         }
+    }
 
-        // Determine output mint by reading from the actual account (consistent with executor)
-        let account_data = output_account_info.try_borrow_data()?;
-        let output_vault_data = TokenAccount::try_deserialize(&mut account_data.as_ref())?;
-        let output_mint = output_vault_data.mint;
-        drop(account_data);
+    Ok(())
+}
 
-        // For the last step, verify that the output account's mint matches destination_mint
-        if i == route_plan.len() - 1 {
-            if output_mint != destination_mint.key() {
-                return Err(ErrorCode::InvalidMint.into());
+/// Checks no step reads from or writes to a vault that has a delegate set, so a stale
+/// approval cannot move funds while they pass through the route
+pub fn validate_vault_delegates(steps: &[ResolvedStep]) -> Result<()> {
+    for (i, step) in steps.iter().enumerate() {
+        for (side, vault, delegated) in [
+            ("input", &step.input_vault, step.input_vault_delegated),
+            ("output", &step.output_vault, step.output_vault_delegated),
+        ] {
+            if delegated {
+                msg!("Step {}: {} vault {} has a delegate", i, side, vault);
+                return Err(ErrorCode::VaultHasDelegate.into());
             }
         }
+    }
+    Ok(())
+}
 
-        output_mints.push(output_mint);
+/// Checks at least one step produces destination_mint
+pub fn validate_output_produced(steps: &[ResolvedStep], destination_mint: &Pubkey) -> Result<()> {
+    if !steps.iter().any(|step| step.output_mint == *destination_mint) {
+        return Err(ErrorCode::NoOutputProduced.into());
+    }
+    Ok(())
+}
 
-        // Update current_amount for multi-hop validation
-        if is_multi_hop && output_mint != destination_mint.key() {
-            current_amount = step_amount; // Simulate passing amount to next step
+/// Checks every step spending `input_mint` reads the order's own input vault or a vault an
+/// earlier step of the plan paid into. Limit order routes run under the vault authority's
+/// signature, so a step reading any other vault of the mint (another order's vault passed in
+/// remaining_accounts) would spend funds the order does not own.
+pub fn validate_order_step_inputs(steps: &[ResolvedStep], order_vault: &Pubkey, input_mint: &Pubkey) -> Result<()> {
+    let mut produced_vaults: Vec<Pubkey> = Vec::with_capacity(steps.len());
+    for (i, step) in steps.iter().enumerate() {
+        if step.input_mint == *input_mint
+            && step.input_vault != *order_vault
+            && !produced_vaults.contains(&step.input_vault)
+        {
+            msg!("Step {}: input vault {} is neither the order's vault nor produced earlier in the route", i, step.input_vault);
+            return Err(ErrorCode::InvalidVaultAddress.into());
         }
+        produced_vaults.push(step.output_vault);
+    }
+    Ok(())
+}
 
-        // Validate adapter and pool
-        if !adapter_registry.is_supported_adapter(&step.swap) {
-            return Err(ErrorCode::SwapNotSupported.into());
+/// Checks the lamport escrow is only spent by a WrapSol step after something paid into it,
+/// and that the route only ends in it when it can pay out lamports.
+///
+/// With `native_endpoints` (user routes) the route may take native SOL: opening WrapSol steps
+/// spend the user's lamports, moved into the escrow at route entry, and a closing UnwrapSol
+/// step is paid out to the user, so SOL -> X -> SOL works. Without it (limit orders) input
+/// arrives in and output leaves from token vaults, so the escrow may only sit in the middle.
+pub fn validate_sol_escrow_steps(steps: &[ResolvedStep], native_endpoints: bool) -> Result<()> {
+    let mut escrow_funded = false;
+    for (i, step) in steps.iter().enumerate() {
+        match step.swap {
+            Swap::WrapSol if native_endpoints && steps[..i].iter().all(|prior| prior.swap == Swap::WrapSol) => {}
+            Swap::WrapSol if !escrow_funded => {
+                msg!("Step {}: WrapSol spends the lamport escrow before any UnwrapSol step pays into it", i);
+                return Err(ErrorCode::SolEscrowAtRouteEndpoint.into());
+            }
+            Swap::UnwrapSol => escrow_funded = true,
+            _ => {}
         }
+    }
+    if !native_endpoints && steps.last().map_or(false, |step| step.swap == Swap::UnwrapSol) {
+        return Err(ErrorCode::SolEscrowAtRouteEndpoint.into());
+    }
+    Ok(())
+}
+
+/// Runs every route rule that does not need account access over resolved steps
+/// `native_endpoints` lets the route start and end in native SOL (see validate_sol_escrow_steps)
+pub fn validate_resolved_route(
+    steps: &[ResolvedStep],
+    adapter_registry: &AdapterRegistry,
+    source_mint: &Pubkey,
+    destination_mint: &Pubkey,
+    in_amount: u64,
+    native_endpoints: bool,
+) -> Result<()> {
+    if steps.is_empty() {
+        return Err(ErrorCode::EmptyRoute.into());
+    }
+    validate_step_percents(steps)?;
+    validate_step_amounts(steps, in_amount)?;
+    validate_hop_chain(steps, source_mint, destination_mint)?;
+    validate_sol_escrow_steps(steps, native_endpoints)?;
+    validate_step_pools(steps, adapter_registry)?;
+    validate_partial_swaps(steps)?;
+    if adapter_registry.reject_delegated_vaults {
+        validate_vault_delegates(steps)?;
+    }
+    validate_output_produced(steps, destination_mint)
+}
 
-        let account_data = input_vault_account.try_borrow_data()?;
-        let input_vault_data = TokenAccount::try_deserialize(&mut account_data.as_ref())?;
-        if i == 0 && input_vault_data.mint != source_mint.key() {
-            return Err(ErrorCode::InvalidMint.into());
+/// Checks every pooled step's PoolInfo was attested within `max_age_slots` of `slot`.
+/// A max age of 0 turns the check off; a pool never attested (or cleared by a failed
+/// attestation) has a last_verified_slot of 0 and is always stale while the check is on.
+pub fn validate_pool_attestations(steps: &[ResolvedStep], max_age_slots: u64, slot: u64) -> Result<()> {
+    if max_age_slots == 0 {
+        return Ok(());
+    }
+    for (i, step) in steps.iter().enumerate() {
+        let Some(verified_slot) = step.pool_verified_slot else {
+            continue;
+        };
+        if verified_slot == 0 || slot.saturating_sub(verified_slot) > max_age_slots {
+            msg!("Step {}: pool {} last attested at slot {}, now {}", i, step.pool, verified_slot, slot);
+            return Err(ErrorCode::PoolAttestationStale.into());
         }
+    }
+    Ok(())
+}
 
-        // Validate pool info
-        let pool_info_account = &remaining_accounts[step.input_index as usize + 1];
-        let pool_info = Account::<PoolInfo>::try_from(pool_info_account)?;
-        if pool_info.adapter_swap_type != step.swap || !pool_info.enabled {
-            return Err(ErrorCode::InvalidPoolAddress.into());
+/// Checks the second pool of a fused two-hop step against the rules every step's own pool gets
+/// from validate_step_pools and validate_pool_attestations: the adapter's whitelist and, when
+/// `max_age_slots` is non-zero, the PoolInfo's attestation age
+pub fn validate_second_hop_pool(
+    swap: &Swap,
+    pool_info: &PoolInfo,
+    adapter_registry: &AdapterRegistry,
+    max_age_slots: u64,
+    slot: u64,
+) -> Result<()> {
+    if !adapter_registry.is_pool_allowed(swap, &pool_info.pool_address) {
+        return Err(ErrorCode::PoolNotWhitelisted.into());
+    }
+    let verified_slot = pool_info.last_verified_slot;
+    if max_age_slots != 0 && (verified_slot == 0 || slot.saturating_sub(verified_slot) > max_age_slots) {
+        msg!("Second hop pool {} last attested at slot {}, now {}", pool_info.pool_address, verified_slot, slot);
+        return Err(ErrorCode::PoolAttestationStale.into());
+    }
+    Ok(())
+}
+
+/// Reads a step vault's mint and whether it has a delegate. The lamport escrow stands in for a
+/// native mint vault, on the escrow side of a WrapSol or UnwrapSol step only.
+fn read_step_vault(account: &AccountInfo, escrow_side: bool) -> Result<(Pubkey, bool)> {
+    if escrow_side {
+        if !is_sol_escrow(account) {
+            return Err(ErrorCode::InvalidSolEscrow.into());
         }
+        return Ok((native_mint::ID, false));
+    }
+    let token_account = read_token_account(account)?;
+    Ok((token_account.mint, token_account.delegate.is_some()))
+}
 
-        // Calculate adapter accounts range once
-        let (adapter_start_index, adapter_accounts_count) = calculate_adapter_accounts_range(step, route_plan, i);
+/// Reads the vaults and PoolInfo of every route step from remaining_accounts.
+/// The PoolInfo must sit right after the input vault, match the step's swap type and be enabled.
+/// WrapSol and UnwrapSol steps have no PoolInfo: the lamport escrow takes its slot and is the pool.
+pub fn resolve_route_steps<'info>(route_plan: &[RoutePlanStep], remaining_accounts: &'info [AccountInfo<'info>]) -> Result<Vec<ResolvedStep>> {
+    let mut steps = Vec::with_capacity(route_plan.len());
+    for (i, step) in route_plan.iter().enumerate() {
+        let input_index = step.input_index as usize;
+        let output_index = step.output_index as usize;
+        if input_index >= remaining_accounts.len() || output_index >= remaining_accounts.len() {
+            return Err(ErrorCode::InvalidAccountIndex.into());
+        }
 
-        // Calculate end index for bounds check
-        let adapter_end_index = adapter_start_index + adapter_accounts_count;
+        let input_vault = &remaining_accounts[input_index];
+        let (input_mint, input_vault_delegated) = read_step_vault(input_vault, step.swap == Swap::WrapSol)?;
+        let output_vault = &remaining_accounts[output_index];
+        let (output_mint, output_vault_delegated) = read_step_vault(output_vault, step.swap == Swap::UnwrapSol)?;
+
+        let pool_info_account = remaining_accounts
+            .get(input_index + 1)
+            .ok_or(ErrorCode::NotEnoughAccountKeys)?;
+        let (pool, pool_verified_slot) = if matches!(step.swap, Swap::WrapSol | Swap::UnwrapSol) {
+            if !is_sol_escrow(pool_info_account) {
+                return Err(ErrorCode::InvalidSolEscrow.into());
+            }
+            (pool_info_account.key(), None)
+        } else {
+            let pool_info = PoolInfo::load_checked(pool_info_account).map_err(|error| {
+                msg!("Step {}: {} is not a usable PoolInfo account", i, pool_info_account.key());
+                error
+            })?;
+            if pool_info.adapter_swap_type != step.swap || !pool_info.enabled {
+                return Err(ErrorCode::InvalidPoolAddress.into());
+            }
+            (pool_info.pool_address, Some(pool_info.last_verified_slot))
+        };
+
+        steps.push(ResolvedStep {
+            input_vault: input_vault.key(),
+            input_mint,
+            output_vault: output_vault.key(),
+            output_mint,
+            pool,
+            input_vault_delegated,
+            output_vault_delegated,
+            pool_verified_slot,
+            swap: step.swap.clone(),
+            percent: step.percent,
+            input_index: step.input_index,
+            output_index: step.output_index,
+        });
+    }
+    Ok(steps)
+}
+
+/// Validates the route plan and associated accounts
+/// `route_user` is the user_transfer_authority, or the order creator for limit orders; it must
+/// be on the sandbox list of every sandboxed adapter the route uses. `native_endpoints` lets
+/// a user route take and deliver native SOL through the lamport escrow.
+#[allow(clippy::too_many_arguments)]
+pub fn validate_route<'info>(
+    adapter_registry: &Account<'info, AdapterRegistry>,
+    input_token_program: &AccountInfo<'info>,
+    output_token_program: &AccountInfo<'info>,
+    vault_authority: &AccountInfo<'info>,
+    source_mint: &AccountInfo<'info>,
+    destination_mint: &AccountInfo<'info>,
+    route_plan: &[RoutePlanStep],
+    remaining_accounts: &'info [AccountInfo<'info>],
+    program_id: &Pubkey,
+    in_amount: u64,
+    route_user: &Pubkey,
+    native_endpoints: bool,
+) -> Result<()> {
+    // Validate route plan emptiness
+    if route_plan.is_empty() {
+        return Err(ErrorCode::EmptyRoute.into());
+    }
+
+    // Validate remaining accounts cover every step before touching any of them
+    validate_route_accounts_count(route_plan, remaining_accounts.len())?;
 
-        // Ensure we have enough accounts for this adapter
+    // Validate token programs
+    validate_token_program(input_token_program)?;
+    validate_token_program(output_token_program)?;
+
+    // Validate mint compatibility with token programs
+    validate_mint_program_compatibility(source_mint, input_token_program)?;
+    validate_mint_program_compatibility(destination_mint, output_token_program)?;
+
+    // A vault of the source mint must be among the route accounts; accounts that are not token
+    // program owned never count, whatever their data. A native SOL input starts in the escrow.
+    let native_input = native_endpoints && route_plan[0].swap == Swap::WrapSol;
+    if !native_input {
+        remaining_accounts
+            .iter()
+            .find(|acc| {
+                read_token_account(acc).map_or(false, |token_account| token_account.mint == source_mint.key())
+            })
+            .ok_or(ErrorCode::VaultNotFound)?;
+    }
+
+    let steps = resolve_route_steps(route_plan, remaining_accounts)?;
+    let slot = Clock::get()?.slot;
+    validate_resolved_route(&steps, adapter_registry, &source_mint.key(), &destination_mint.key(), in_amount, native_endpoints)?;
+    validate_pool_attestations(&steps, adapter_registry.pool_attestation_max_age_slots, slot)?;
+    validate_sandbox_access(route_plan, adapter_registry, route_user)?;
+
+    // Validate each step's adapter accounts and program
+    for (i, step) in route_plan.iter().enumerate() {
+        let (adapter_start_index, adapter_accounts_count) = calculate_adapter_accounts_range(step, route_plan, i);
+        let adapter_end_index = adapter_start_index + adapter_accounts_count;
         if remaining_accounts.len() < adapter_end_index {
             return Err(ErrorCode::NotEnoughAccountKeys.into());
         }
 
-        // Validate adapter accounts with correct range
         let adapter = get_adapter(&step.swap, adapter_registry)?;
-
         let adapter_ctx = AdapterContext {
             token_program: input_token_program.clone(),
             authority: vault_authority.clone(),
-            input_account: input_vault_account.clone(),
-            output_account: output_account_info,
+            input_account: remaining_accounts[step.input_index as usize].clone(),
+            output_account: remaining_accounts[step.output_index as usize].clone(),
             remaining_accounts,
             program_id: *program_id,
         };
 
-        let program_index = adapter_end_index - 1;
-        let program_account = &remaining_accounts[program_index];
-
-        // validate program id
+        let program_account = &remaining_accounts[adapter_end_index - 1];
         adapter.validate_cpi(program_account.key)?;
-
-        // Use both start index and count for adapter validation
+        adapter_ctx.verify_input_owner()?;
         adapter.validate_accounts(adapter_ctx, adapter_start_index, adapter_accounts_count)?;
-    }
 
-    // Validate partial swap: ensure multiple DEXes and same input vault
-    if is_partial_swap {
-        let total_percent: u8 = route_plan
-            .iter()
-            .filter(|step| {
-                let input_vault = &remaining_accounts[step.input_index as usize];
-                input_vault_key == Some(input_vault.key())
-            })
-            .map(|step| step.percent)
-            .sum();
-        if total_percent != 100 {
-            return Err(ErrorCode::InvalidPartialSwapPercent.into());
-        }
-        if used_dexes.len() < 2 {
-            return Err(ErrorCode::InsufficientDexesForPartialSwap.into());
+        // A two-hop step consumes the source and produces the destination directly; its second
+        // pool is not the step's pool, so the pool rules are applied to it here
+        if matches!(step.swap, Swap::WhirlpoolTwoHop { .. }) {
+            let pool_info_two = PoolInfo::load_checked(
+                &remaining_accounts[adapter_start_index + whirlpool_two_hop::POOL_INFO_TWO_INDEX],
+            )?;
+            validate_second_hop_pool(
+                &step.swap,
+                &pool_info_two,
+                adapter_registry,
+                adapter_registry.pool_attestation_max_age_slots,
+                slot,
+            )?;
         }
     }
 
-    // Ensure at least one step produces destination_mint
-    if !output_mints.iter().any(|mint| *mint == destination_mint.key()) {
-        return Err(ErrorCode::NoOutputProduced.into());
-    }
-
     Ok(())
-}
\ No newline at end of file
+}
@@ -1,7 +1,9 @@
 #[cfg(test)]
 mod tests {
     use super::super::*;
+    use crate::errors::ErrorCode;
     use crate::state::*;
+    use crate::test_utils::{empty_account, leak_account_infos, mint_account, pool_info_account, program_account, token_account, TestAccount};
     use anchor_lang::prelude::*;
 
     fn create_test_route_plan_step(
@@ -201,4 +203,1344 @@ mod tests {
             assert_eq!(step.percent, 100);
         }
     }
+
+    // Adapter accounts for a step are input_index + 1 .. output_index (exclusive),
+    // so a step with exactly MIN_ACCOUNTS has output_index = input_index + 1 + MIN_ACCOUNTS
+    fn exact_step(swap: Swap, input_index: u8) -> RoutePlanStep {
+        let min_accounts = crate::adapters::adapter_connector_module::adapter_min_accounts(&swap).unwrap();
+        create_test_route_plan_step(swap, 100, input_index, input_index + 1 + min_accounts as u8)
+    }
+
+    fn one_short_step(swap: Swap, input_index: u8) -> RoutePlanStep {
+        let mut step = exact_step(swap, input_index);
+        step.output_index -= 1;
+        step
+    }
+
+    #[test]
+    fn test_route_accounts_count_exact_for_each_adapter() {
+        for swap in [
+            Swap::Raydium,
+            Swap::Whirlpool { a_to_b: true },
+            Swap::Meteora,
+            Swap::WhirlpoolTwoHop { a_to_b_one: true, a_to_b_two: false },
+        ] {
+            let step = exact_step(swap, 0);
+            let len = step.output_index as usize + 1;
+            assert!(validate_route_accounts_count(&[step], len).is_ok());
+        }
+    }
+
+    #[test]
+    fn test_route_accounts_count_raydium_one_adapter_account_short() {
+        let step = one_short_step(Swap::Raydium, 0);
+        let len = step.output_index as usize + 1;
+        assert!(validate_route_accounts_count(&[step], len).is_err());
+    }
+
+    #[test]
+    fn test_route_accounts_count_whirlpool_one_adapter_account_short() {
+        let step = one_short_step(Swap::Whirlpool { a_to_b: false }, 0);
+        let len = step.output_index as usize + 1;
+        assert!(validate_route_accounts_count(&[step], len).is_err());
+    }
+
+    #[test]
+    fn test_route_accounts_count_meteora_one_adapter_account_short() {
+        let step = one_short_step(Swap::Meteora, 0);
+        let len = step.output_index as usize + 1;
+        assert!(validate_route_accounts_count(&[step], len).is_err());
+    }
+
+    #[test]
+    fn test_route_accounts_count_one_remaining_account_short() {
+        for swap in [Swap::Raydium, Swap::Whirlpool { a_to_b: true }, Swap::Meteora] {
+            let step = exact_step(swap, 0);
+            // Output vault itself is missing
+            let len = step.output_index as usize;
+            assert!(validate_route_accounts_count(&[step], len).is_err());
+        }
+    }
+
+    #[test]
+    fn test_route_accounts_count_multi_hop_shares_vaults() {
+        // Step 2 reuses step 1's output vault as its input vault
+        let first = exact_step(Swap::Raydium, 0);
+        let second = exact_step(Swap::Meteora, first.output_index);
+        let len = second.output_index as usize + 1;
+
+        assert!(validate_route_accounts_count(&[first.clone(), second.clone()], len).is_ok());
+        assert!(validate_route_accounts_count(&[first, second], len - 1).is_err());
+    }
+
+    #[test]
+    fn test_route_accounts_count_unsupported_swap() {
+        let step = create_test_route_plan_step(Swap::Lifinity, 100, 0, 20);
+        assert!(validate_route_accounts_count(&[step], 21).is_err());
+    }
+
+    #[test]
+    fn test_route_plan_index_range() {
+        assert_eq!(route_plan_index_range(&[]), None);
+
+        let plan = vec![
+            create_test_route_plan_step(Swap::Raydium, 100, 0, 12),
+            create_test_route_plan_step(Swap::Meteora, 100, 12, 29),
+        ];
+        assert_eq!(route_plan_index_range(&plan), Some((0, 29)));
+    }
+
+    #[test]
+    fn test_route_plans_disjoint_do_not_overlap() {
+        let plan_a = vec![create_test_route_plan_step(Swap::Raydium, 100, 0, 12)];
+        let plan_b = vec![create_test_route_plan_step(Swap::Whirlpool { a_to_b: true }, 100, 13, 29)];
+
+        assert!(!route_plans_overlap(&plan_a, &plan_b));
+        assert!(!route_plans_overlap(&plan_b, &plan_a));
+    }
+
+    #[test]
+    fn test_route_plans_sharing_index_overlap() {
+        // Plan B reuses plan A's output vault slot
+        let plan_a = vec![create_test_route_plan_step(Swap::Raydium, 100, 0, 12)];
+        let plan_b = vec![create_test_route_plan_step(Swap::Raydium, 100, 12, 24)];
+        assert!(route_plans_overlap(&plan_a, &plan_b));
+
+        // Plan B nested inside plan A's adapter accounts
+        let plan_c = vec![create_test_route_plan_step(Swap::Raydium, 100, 3, 5)];
+        assert!(route_plans_overlap(&plan_a, &plan_c));
+    }
+
+    #[test]
+    fn test_route_plans_empty_never_overlap() {
+        let plan_a = vec![create_test_route_plan_step(Swap::Raydium, 100, 0, 12)];
+        assert!(!route_plans_overlap(&plan_a, &[]));
+    }
+
+    fn transfer_fee_config(older_bps: u16, newer_bps: u16, newer_epoch: u64) -> TransferFeeConfig {
+        use anchor_spl::token_2022::spl_token_2022::extension::transfer_fee::TransferFee;
+
+        TransferFeeConfig {
+            transfer_fee_config_authority: Default::default(),
+            withdraw_withheld_authority: Default::default(),
+            withheld_amount: 0u64.into(),
+            older_transfer_fee: TransferFee {
+                epoch: 0u64.into(),
+                maximum_fee: u64::MAX.into(),
+                transfer_fee_basis_points: older_bps.into(),
+            },
+            newer_transfer_fee: TransferFee {
+                epoch: newer_epoch.into(),
+                maximum_fee: u64::MAX.into(),
+                transfer_fee_basis_points: newer_bps.into(),
+            },
+        }
+    }
+
+    #[test]
+    fn test_epoch_transfer_fee_100_bps() {
+        let config = transfer_fee_config(100, 100, 0);
+
+        // 1% of 1,000,000 is withheld, so 990,000 arrives
+        let fee = calculate_epoch_transfer_fee(&config, 10, 1_000_000).unwrap();
+        assert_eq!(fee, 10_000);
+        assert_eq!(1_000_000 - fee, 990_000);
+    }
+
+    #[test]
+    fn test_epoch_transfer_fee_uses_schedule_for_epoch() {
+        // 100 bps until epoch 50, then 200 bps
+        let config = transfer_fee_config(100, 200, 50);
+
+        assert_eq!(calculate_epoch_transfer_fee(&config, 49, 1_000_000).unwrap(), 10_000);
+        assert_eq!(calculate_epoch_transfer_fee(&config, 50, 1_000_000).unwrap(), 20_000);
+    }
+
+    #[test]
+    fn test_epoch_transfer_fee_zero_amount() {
+        let config = transfer_fee_config(100, 100, 0);
+        assert_eq!(calculate_epoch_transfer_fee(&config, 0, 0).unwrap(), 0);
+    }
+
+    #[test]
+    fn test_net_transfer_amount_spl_token_mint_is_unchanged() {
+        let mut mint = mint_account(6, TOKEN_PROGRAM_ID);
+
+        assert_eq!(net_transfer_amount(&mint.to_account_info(), 1_000_000).unwrap(), 1_000_000);
+    }
+
+    const YEAR: i64 = INTEREST_SECONDS_PER_YEAR as i64;
+
+    fn interest_config(initialized_at: i64, average_bps: i16, last_update: i64, current_bps: i16) -> InterestBearingConfig {
+        InterestBearingConfig {
+            rate_authority: Default::default(),
+            initialization_timestamp: initialized_at.into(),
+            pre_update_average_rate: average_bps.into(),
+            last_update_timestamp: last_update.into(),
+            current_rate: current_bps.into(),
+        }
+    }
+
+    // Token-2022 mint account carrying an InterestBearingConfig extension
+    fn interest_bearing_mint(config: InterestBearingConfig) -> crate::test_utils::TestAccount {
+        use anchor_spl::token_2022::spl_token_2022::extension::{BaseStateWithExtensionsMut, ExtensionType, StateWithExtensionsMut};
+
+        let space = ExtensionType::try_calculate_account_len::<Token2022Mint>(&[ExtensionType::InterestBearingConfig]).unwrap();
+        let mut data = vec![0u8; space];
+        let mut mint = StateWithExtensionsMut::<Token2022Mint>::unpack_uninitialized(&mut data).unwrap();
+        mint.base = Token2022Mint {
+            decimals: 6,
+            is_initialized: true,
+            ..Default::default()
+        };
+        mint.pack_base();
+        mint.init_account_type().unwrap();
+        *mint.init_extension::<InterestBearingConfig>(true).unwrap() = config;
+        crate::test_utils::TestAccount::new(TOKEN_2022_PROGRAM_ID, data)
+    }
+
+    #[test]
+    fn test_interest_multiplier_without_accrual_is_scale() {
+        // No time elapsed, or a zero rate, leaves UI amounts equal to raw amounts
+        assert_eq!(calculate_interest_multiplier(&interest_config(0, 0, 0, 500), 0).unwrap(), UI_AMOUNT_MULTIPLIER_SCALE);
+        assert_eq!(calculate_interest_multiplier(&interest_config(0, 0, 0, 0), YEAR).unwrap(), UI_AMOUNT_MULTIPLIER_SCALE);
+    }
+
+    #[test]
+    fn test_interest_multiplier_compounds_continuously() {
+        // 5% for one year: e^0.05
+        let multiplier = calculate_interest_multiplier(&interest_config(0, 0, 0, 500), YEAR).unwrap();
+        assert!(multiplier.abs_diff(1_051_271_096) <= 1, "{}", multiplier);
+
+        // -5% for one year: e^-0.05
+        let multiplier = calculate_interest_multiplier(&interest_config(0, 0, 0, -500), YEAR).unwrap();
+        assert!(multiplier.abs_diff(951_229_424) <= 1, "{}", multiplier);
+    }
+
+    #[test]
+    fn test_interest_multiplier_chains_average_and_current_rate() {
+        // 5% average for the year before the last update, then 0% since: still e^0.05
+        let config = interest_config(0, 500, YEAR, 0);
+        let multiplier = calculate_interest_multiplier(&config, 3 * YEAR).unwrap();
+        assert!(multiplier.abs_diff(1_051_271_096) <= 1, "{}", multiplier);
+
+        // A clock behind the last update accrues nothing further
+        assert_eq!(
+            calculate_interest_multiplier(&config, 0).unwrap(),
+            calculate_interest_multiplier(&config, YEAR).unwrap()
+        );
+    }
+
+    #[test]
+    fn test_interest_bearing_config_detection() {
+        let config = interest_config(100, 0, 100, 250);
+        let mut interest_mint = interest_bearing_mint(config);
+        let detected = interest_bearing_config(&interest_mint.to_account_info()).unwrap().unwrap();
+        assert_eq!(i16::from(detected.current_rate), 250);
+        assert_eq!(i64::from(detected.initialization_timestamp), 100);
+
+        // SPL Token and plain Token-2022 mints report the scale without reading the clock
+        for mut mint in [mint_account(6, TOKEN_PROGRAM_ID), mint_account(6, TOKEN_2022_PROGRAM_ID)] {
+            assert!(interest_bearing_config(&mint.to_account_info()).unwrap().is_none());
+            assert_eq!(ui_amount_multiplier(&mint.to_account_info()).unwrap(), UI_AMOUNT_MULTIPLIER_SCALE);
+        }
+    }
+
+    #[test]
+    fn test_interest_bearing_mint_keeps_raw_amount_accounting() {
+        let mut interest_mint = interest_bearing_mint(interest_config(0, 0, 0, 1_000));
+        let mint_info = interest_mint.to_account_info();
+
+        // Interest is a display factor only: transfers neither withhold nor add tokens
+        assert_eq!(net_transfer_amount(&mint_info, 1_000_000).unwrap(), 1_000_000);
+
+        // Splits and floors work on raw amounts, whatever the wallet shows
+        let plan = [
+            create_test_route_plan_step(Swap::Raydium, 60, 0, 13),
+            create_test_route_plan_step(Swap::Raydium, 40, 0, 13),
+        ];
+        assert_eq!(split_amounts(&plan, 1_000_000), vec![600_000, 400_000]);
+        assert!(validate_output_floors(990_000, 1_000_000, 100, 990_000).is_ok());
+        let err = validate_output_floors(989_999, 1_000_000, 100, 0).unwrap_err();
+        assert_eq!(err, ErrorCode::SlippageToleranceExceeded.into());
+    }
+
+    #[test]
+    fn test_validate_mint_program_compatibility() {
+        let mut mint = mint_account(6, TOKEN_2022_PROGRAM_ID);
+        let mut token_2022 = program_account(TOKEN_2022_PROGRAM_ID);
+        let mut token = program_account(TOKEN_PROGRAM_ID);
+
+        assert!(validate_mint_program_compatibility(&mint.to_account_info(), &token_2022.to_account_info()).is_ok());
+        let err = validate_mint_program_compatibility(&mint.to_account_info(), &token.to_account_info()).unwrap_err();
+        assert_eq!(err, ErrorCode::InvalidMint.into());
+    }
+
+    fn split_amounts(route_plan: &[RoutePlanStep], in_amount: u64) -> Vec<u64> {
+        let mut amounts = Vec::new();
+        for _ in route_plan {
+            let amount = calculate_step_amount(route_plan, &amounts, in_amount).unwrap();
+            amounts.push(amount);
+        }
+        amounts
+    }
+
+    #[test]
+    fn test_step_amount_full_step_takes_everything() {
+        let plan = vec![create_test_route_plan_step(Swap::Raydium, 100, 0, 12)];
+        assert_eq!(split_amounts(&plan, 7), vec![7]);
+    }
+
+    #[test]
+    fn test_step_amount_two_way_split_sums_to_input() {
+        let plan = vec![
+            create_test_route_plan_step(Swap::Raydium, 50, 0, 12),
+            create_test_route_plan_step(Swap::Meteora, 50, 0, 29),
+        ];
+
+        for in_amount in [2u64, 3, 5, 101, 999_999] {
+            let amounts = split_amounts(&plan, in_amount);
+            assert_eq!(amounts.iter().sum::<u64>(), in_amount);
+            assert!(amounts.iter().all(|amount| *amount > 0));
+        }
+
+        // 3 split 50/50: floor for the first step, remainder for the last
+        assert_eq!(split_amounts(&plan, 3), vec![1, 2]);
+    }
+
+    #[test]
+    fn test_step_amount_three_way_split_sums_to_input() {
+        let plan = vec![
+            create_test_route_plan_step(Swap::Raydium, 33, 0, 12),
+            create_test_route_plan_step(Swap::Meteora, 33, 0, 29),
+            create_test_route_plan_step(Swap::Whirlpool { a_to_b: true }, 34, 0, 45),
+        ];
+
+        for in_amount in [4u64, 10, 100, 1_000_003] {
+            let amounts = split_amounts(&plan, in_amount);
+            assert_eq!(amounts.iter().sum::<u64>(), in_amount);
+        }
+
+        // 10 split 33/33/34: 3 + 3 + remainder 4
+        assert_eq!(split_amounts(&plan, 10), vec![3, 3, 4]);
+    }
+
+    #[test]
+    fn test_step_amount_dust_leaves_zero_step() {
+        // 3 split 33/33/34: the first two steps floor to zero, which the validator rejects
+        // with StepAmountTooSmall; the last step still receives the full remainder
+        let plan = vec![
+            create_test_route_plan_step(Swap::Raydium, 33, 0, 12),
+            create_test_route_plan_step(Swap::Meteora, 33, 0, 29),
+            create_test_route_plan_step(Swap::Whirlpool { a_to_b: true }, 34, 0, 45),
+        ];
+        assert_eq!(split_amounts(&plan, 3), vec![0, 0, 3]);
+
+        // 1 split 50/50: the first step floors to zero
+        let plan = vec![
+            create_test_route_plan_step(Swap::Raydium, 50, 0, 12),
+            create_test_route_plan_step(Swap::Meteora, 50, 0, 29),
+        ];
+        assert_eq!(split_amounts(&plan, 1), vec![0, 1]);
+    }
+
+    #[test]
+    fn test_step_amount_incomplete_split_does_not_take_remainder() {
+        // A lone 50% step is not a complete split, so it gets its floor share only
+        let plan = vec![create_test_route_plan_step(Swap::Raydium, 50, 0, 12)];
+        assert_eq!(split_amounts(&plan, 5), vec![2]);
+    }
+
+    #[test]
+    fn test_vault_ledger_credits_and_merges_per_vault() {
+        use crate::instructions::route_executor_module::VaultLedger;
+        let (source, branch_a, branch_b) = (vault_key(0), vault_key(12), vault_key(24));
+
+        let mut ledger = VaultLedger::new(source, 1_000);
+        assert_eq!(ledger.amount(&source), 1_000);
+        assert_eq!(ledger.amount(&branch_a), 0);
+
+        ledger.credit(branch_a, 400).unwrap();
+        ledger.credit(branch_b, 700).unwrap();
+        assert_eq!(ledger.amount(&branch_a), 400);
+        assert_eq!(ledger.amount(&branch_b), 700);
+
+        // Branches merging into one vault add up
+        ledger.credit(branch_a, 50).unwrap();
+        assert_eq!(ledger.amount(&branch_a), 450);
+
+        let err = ledger.credit(branch_b, u64::MAX).unwrap_err();
+        assert_eq!(err, ErrorCode::InvalidCalculation.into());
+    }
+
+    #[test]
+    fn test_split_then_hop_feeds_each_hop_its_own_branch() {
+        use crate::instructions::route_executor_module::VaultLedger;
+        // Source splits 50/50 into A (12) and B (24), then A and B each hop into the destination (36)
+        let plan = vec![
+            create_test_route_plan_step(Swap::Raydium, 50, 0, 12),
+            create_test_route_plan_step(Swap::Whirlpool { a_to_b: true }, 50, 0, 24),
+            create_test_route_plan_step(Swap::Raydium, 100, 12, 36),
+            create_test_route_plan_step(Swap::Whirlpool { a_to_b: true }, 100, 24, 36),
+        ];
+        // Each step's pool pays out a different rate, so a hop fed the wrong branch shows up
+        let outputs_per_input = [3u64, 5, 2, 7];
+
+        let mut ledger = VaultLedger::new(vault_key(0), 1_000);
+        let mut step_amounts = Vec::new();
+        for (step, rate) in plan.iter().zip(outputs_per_input) {
+            let available = ledger.amount(&vault_key(step.input_index));
+            let step_amount = calculate_step_amount(&plan, &step_amounts, available).unwrap();
+            step_amounts.push(step_amount);
+            ledger.credit(vault_key(step.output_index), step_amount * rate).unwrap();
+        }
+
+        // The A hop spends A's 1_500, not the 2_500 the sibling branch left in B
+        assert_eq!(step_amounts, vec![500, 500, 1_500, 2_500]);
+        assert_eq!(ledger.amount(&vault_key(36)), 1_500 * 2 + 2_500 * 7);
+    }
+
+    #[test]
+    fn test_calculate_slippage_min_out() {
+        assert_eq!(calculate_slippage_min_out(1_000_000, 100).unwrap(), 990_000);
+        assert_eq!(calculate_slippage_min_out(1_000_000, 0).unwrap(), 1_000_000);
+        assert_eq!(calculate_slippage_min_out(1_000_000, 10_000).unwrap(), 0);
+        assert!(calculate_slippage_min_out(1_000_000, 10_001).is_err());
+    }
+
+    #[test]
+    fn test_validate_output_floors_zero_user_minimum_is_disabled() {
+        // Only the quote-based check applies
+        assert!(validate_output_floors(990_000, 1_000_000, 100, 0).is_ok());
+        let err = validate_output_floors(989_999, 1_000_000, 100, 0).unwrap_err();
+        assert_eq!(err, ErrorCode::SlippageToleranceExceeded.into());
+    }
+
+    #[test]
+    fn test_validate_output_floors_user_minimum_stricter() {
+        // Quote allows down to 990_000, user insists on 995_000
+        assert!(validate_output_floors(995_000, 1_000_000, 100, 995_000).is_ok());
+        let err = validate_output_floors(992_000, 1_000_000, 100, 995_000).unwrap_err();
+        assert_eq!(err, ErrorCode::BelowUserMinimum.into());
+    }
+
+    #[test]
+    fn test_validate_output_floors_quote_stricter() {
+        // Inflated slippage cannot go below the user's floor, but a looser user floor
+        // does not relax the quote-based check either
+        let err = validate_output_floors(985_000, 1_000_000, 100, 980_000).unwrap_err();
+        assert_eq!(err, ErrorCode::SlippageToleranceExceeded.into());
+
+        // Frontend set 50% slippage; the user's own minimum still holds
+        let err = validate_output_floors(600_000, 1_000_000, 5_000, 990_000).unwrap_err();
+        assert_eq!(err, ErrorCode::BelowUserMinimum.into());
+    }
+
+    #[test]
+    fn test_check_quote_sanity_accepts_quotes_within_band() {
+        assert!(check_quote_sanity(1_000_000, 1_000_000, 2_000).is_ok());
+        // Band edges are included
+        assert!(check_quote_sanity(800_000, 1_000_000, 2_000).is_ok());
+        assert!(check_quote_sanity(1_200_000, 1_000_000, 2_000).is_ok());
+    }
+
+    #[test]
+    fn test_check_quote_sanity_rejects_quotes_far_below_band() {
+        for quoted_out_amount in [1u64, 500_000, 799_999] {
+            let err = check_quote_sanity(quoted_out_amount, 1_000_000, 2_000).unwrap_err();
+            assert_eq!(err, ErrorCode::ImplausibleQuote.into());
+        }
+    }
+
+    #[test]
+    fn test_check_quote_sanity_rejects_quotes_far_above_band() {
+        for quoted_out_amount in [1_200_001u64, 10_000_000, u64::MAX] {
+            let err = check_quote_sanity(quoted_out_amount, 1_000_000, 2_000).unwrap_err();
+            assert_eq!(err, ErrorCode::ImplausibleQuote.into());
+        }
+        // An empty pool only accepts a zero quote
+        let err = check_quote_sanity(1, 0, 2_000).unwrap_err();
+        assert_eq!(err, ErrorCode::ImplausibleQuote.into());
+    }
+
+    fn fee_account(delegate: Option<Pubkey>, close_authority: Option<Pubkey>) -> TokenAccount {
+        use anchor_lang::solana_program::program_pack::Pack;
+        use anchor_spl::token_2022::spl_token_2022::state::{Account, AccountState};
+
+        let account = Account {
+            mint: Pubkey::new_unique(),
+            owner: Pubkey::new_unique(),
+            amount: 1_000,
+            delegate: delegate.into(),
+            state: AccountState::Initialized,
+            is_native: None.into(),
+            delegated_amount: if delegate.is_some() { 1_000 } else { 0 },
+            close_authority: close_authority.into(),
+        };
+        let mut data = vec![0u8; Account::LEN];
+        Account::pack(account, &mut data).unwrap();
+        TokenAccount::try_deserialize(&mut data.as_slice()).unwrap()
+    }
+
+    #[test]
+    fn test_validate_fee_account_authorities_plain_account() {
+        let vault_authority = Pubkey::new_unique();
+        assert!(validate_fee_account_authorities(&fee_account(None, None), &vault_authority).is_ok());
+        // Vault authority as close authority cannot be abused by anyone else
+        assert!(validate_fee_account_authorities(&fee_account(None, Some(vault_authority)), &vault_authority).is_ok());
+    }
+
+    #[test]
+    fn test_validate_fee_account_authorities_rejects_delegate() {
+        let vault_authority = Pubkey::new_unique();
+        let err = validate_fee_account_authorities(&fee_account(Some(Pubkey::new_unique()), None), &vault_authority).unwrap_err();
+        assert_eq!(err, ErrorCode::UnsafeFeeAccount.into());
+    }
+
+    #[test]
+    fn test_validate_fee_account_authorities_rejects_foreign_close_authority() {
+        let vault_authority = Pubkey::new_unique();
+        let err = validate_fee_account_authorities(&fee_account(None, Some(Pubkey::new_unique())), &vault_authority).unwrap_err();
+        assert_eq!(err, ErrorCode::UnsafeFeeAccount.into());
+    }
+
+    #[test]
+    fn test_charged_fee_account_ignores_account_without_fee() {
+        let account = fee_account(Some(Pubkey::new_unique()), None);
+        // Even an unsafe account is never looked at when nothing is collected
+        assert!(charged_fee_account(0, Some(&account)).unwrap().is_none());
+        assert!(charged_fee_account::<&TokenAccount>(0, None).unwrap().is_none());
+    }
+
+    #[test]
+    fn test_charged_fee_account_requires_account_with_fee() {
+        let account = fee_account(None, None);
+        assert!(charged_fee_account(1, Some(&account)).unwrap().is_some());
+        let err = charged_fee_account::<&TokenAccount>(1, None).unwrap_err();
+        assert_eq!(err, ErrorCode::MissingFeeAccount.into());
+    }
+
+    #[test]
+    fn test_validate_platform_fee_vault_checks_owner_and_mint() {
+        let (vault_authority, mint) = (Pubkey::new_unique(), Pubkey::new_unique());
+        let fixture = token_account(mint, vault_authority, 0, TOKEN_PROGRAM_ID);
+        let account = TokenAccount::try_deserialize(&mut fixture.data.as_slice()).unwrap();
+
+        assert!(validate_platform_fee_vault(&account, &mint, &vault_authority).is_ok());
+        let err = validate_platform_fee_vault(&account, &Pubkey::new_unique(), &vault_authority).unwrap_err();
+        assert_eq!(err, ErrorCode::InvalidPlatformFeeMint.into());
+        let err = validate_platform_fee_vault(&account, &mint, &Pubkey::new_unique()).unwrap_err();
+        assert_eq!(err, ErrorCode::InvalidPlatformFeeOwner.into());
+    }
+
+    fn fee_vault_registry_account(entries: Vec<FeeVaultEntry>) -> crate::test_utils::TestAccount {
+        let mut data = Vec::with_capacity(FeeVaultRegistry::SPACE);
+        FeeVaultRegistry { entries, bump: 255 }.try_serialize(&mut data).unwrap();
+        crate::test_utils::TestAccount::new(crate::ID, data)
+    }
+
+    #[test]
+    fn test_find_registered_fee_vault_picks_the_fee_mints_vault() {
+        let vault_authority = Pubkey::new_unique();
+        let (mint_a, mint_b) = (Pubkey::new_unique(), Pubkey::new_unique());
+        let accounts = leak_account_infos(vec![
+            token_account(mint_a, vault_authority, 0, TOKEN_PROGRAM_ID),
+            token_account(mint_b, vault_authority, 0, TOKEN_PROGRAM_ID),
+        ]);
+        let registry = leak_account_infos(vec![fee_vault_registry_account(vec![
+            FeeVaultEntry { mint: mint_a, fee_vault: accounts[0].key() },
+            FeeVaultEntry { mint: mint_b, fee_vault: accounts[1].key() },
+        ])]);
+
+        let fee_vault = find_registered_fee_vault(&registry[0], &mint_b, &vault_authority, accounts, &crate::ID).unwrap();
+        assert_eq!(fee_vault.key(), accounts[1].key());
+
+        // Only the registered vault may be picked, whatever else is passed
+        let err = find_registered_fee_vault(&registry[0], &mint_b, &vault_authority, &accounts[..1], &crate::ID).unwrap_err();
+        assert_eq!(err, ErrorCode::RegisteredFeeVaultNotProvided.into());
+        let err = find_registered_fee_vault(&registry[0], &Pubkey::new_unique(), &vault_authority, accounts, &crate::ID).unwrap_err();
+        assert_eq!(err, ErrorCode::FeeVaultNotRegistered.into());
+    }
+
+    #[test]
+    fn test_find_registered_fee_vault_without_registry_or_with_stale_vault() {
+        let vault_authority = Pubkey::new_unique();
+        let mint = Pubkey::new_unique();
+
+        // set_fee_vault has never run: the PDA is an empty system account
+        let accounts = leak_account_infos(vec![
+            crate::test_utils::empty_account(),
+            token_account(mint, Pubkey::new_unique(), 0, TOKEN_PROGRAM_ID),
+        ]);
+        let err = find_registered_fee_vault(&accounts[0], &mint, &vault_authority, &accounts[1..], &crate::ID).unwrap_err();
+        assert_eq!(err, ErrorCode::FeeVaultNotRegistered.into());
+
+        // The registered vault was handed to another owner after registration
+        let registry = leak_account_infos(vec![fee_vault_registry_account(vec![FeeVaultEntry {
+            mint,
+            fee_vault: accounts[1].key(),
+        }])]);
+        let err = find_registered_fee_vault(&registry[0], &mint, &vault_authority, &accounts[1..], &crate::ID).unwrap_err();
+        assert_eq!(err, ErrorCode::InvalidPlatformFeeOwner.into());
+    }
+
+    // Rewrites a packed SPL token account in place
+    fn update_token_account(account: &AccountInfo, update: impl FnOnce(&mut anchor_spl::token_2022::spl_token_2022::state::Account)) {
+        use anchor_lang::solana_program::program_pack::Pack;
+        use anchor_spl::token_2022::spl_token_2022::state::Account;
+
+        let mut data = account.try_borrow_mut_data().unwrap();
+        let mut token_account = Account::unpack(&data).unwrap();
+        update(&mut token_account);
+        Account::pack(token_account, &mut data).unwrap();
+    }
+
+    fn vault_fixtures(vault_authority: Pubkey) -> &'static [AccountInfo<'static>] {
+        leak_account_infos(vec![
+            token_account(Pubkey::new_unique(), vault_authority, 1_000, TOKEN_PROGRAM_ID),
+            token_account(Pubkey::new_unique(), Pubkey::new_unique(), 500, TOKEN_PROGRAM_ID),
+            program_account(Pubkey::new_unique()),
+            token_account(Pubkey::new_unique(), vault_authority, 2_000, TOKEN_2022_PROGRAM_ID),
+        ])
+    }
+
+    #[test]
+    fn test_snapshot_vaults_only_records_vault_authority_accounts() {
+        let vault_authority = Pubkey::new_unique();
+        let accounts = vault_fixtures(vault_authority);
+        // Repeated accounts are recorded once
+        let with_duplicate = [accounts, &accounts[..1]].concat();
+
+        let snapshots = snapshot_vaults(&with_duplicate, &vault_authority);
+        assert_eq!(snapshots.len(), 2);
+        assert_eq!(snapshots[0].key, accounts[0].key());
+        assert_eq!(snapshots[0].amount, 1_000);
+        assert_eq!(snapshots[1].key, accounts[3].key());
+        assert_eq!(snapshots[1].amount, 2_000);
+    }
+
+    #[test]
+    fn test_verify_vault_snapshots_allows_unchanged_or_grown_vaults() {
+        let vault_authority = Pubkey::new_unique();
+        let accounts = vault_fixtures(vault_authority);
+        let snapshots = snapshot_vaults(accounts, &vault_authority);
+
+        update_token_account(&accounts[0], |vault| vault.amount += 1);
+        // Accounts not owned by the vault authority are not protected
+        update_token_account(&accounts[1], |account| account.amount = 0);
+        assert!(verify_vault_snapshots(&snapshots, accounts, &vault_authority).is_ok());
+    }
+
+    #[test]
+    fn test_verify_vault_snapshots_rejects_drained_vault() {
+        let vault_authority = Pubkey::new_unique();
+        let accounts = vault_fixtures(vault_authority);
+        let snapshots = snapshot_vaults(accounts, &vault_authority);
+
+        update_token_account(&accounts[3], |vault| vault.amount -= 1);
+        let err = verify_vault_snapshots(&snapshots, accounts, &vault_authority).unwrap_err();
+        assert_eq!(err, ErrorCode::VaultInvariantViolated.into());
+    }
+
+    #[test]
+    fn test_verify_vault_snapshots_rejects_authority_changes() {
+        let vault_authority = Pubkey::new_unique();
+
+        let accounts = vault_fixtures(vault_authority);
+        let snapshots = snapshot_vaults(accounts, &vault_authority);
+        update_token_account(&accounts[0], |vault| vault.owner = Pubkey::new_unique());
+        let err = verify_vault_snapshots(&snapshots, accounts, &vault_authority).unwrap_err();
+        assert_eq!(err, ErrorCode::VaultInvariantViolated.into());
+
+        let accounts = vault_fixtures(vault_authority);
+        let snapshots = snapshot_vaults(accounts, &vault_authority);
+        update_token_account(&accounts[0], |vault| vault.delegate = Some(Pubkey::new_unique()).into());
+        let err = verify_vault_snapshots(&snapshots, accounts, &vault_authority).unwrap_err();
+        assert_eq!(err, ErrorCode::VaultInvariantViolated.into());
+
+        let accounts = vault_fixtures(vault_authority);
+        let snapshots = snapshot_vaults(accounts, &vault_authority);
+        update_token_account(&accounts[0], |vault| vault.close_authority = Some(Pubkey::new_unique()).into());
+        let err = verify_vault_snapshots(&snapshots, accounts, &vault_authority).unwrap_err();
+        assert_eq!(err, ErrorCode::VaultInvariantViolated.into());
+    }
+
+    #[test]
+    fn test_check_step_impact_rejects_trades_over_the_cap() {
+        use crate::instructions::route_executor_module::check_step_impact;
+
+        // Trading 30% of the pool's depth moves its price by about 41%
+        let err = check_step_impact(300_000, 1_000_000, 100, false).unwrap_err();
+        assert_eq!(err, ErrorCode::ExcessivePriceImpact.into());
+
+        // Accepted by the user, or uncapped
+        assert_eq!(check_step_impact(300_000, 1_000_000, 100, true).unwrap(), 4_083);
+        assert_eq!(check_step_impact(300_000, 1_000_000, 0, false).unwrap(), 4_083);
+
+        // Up to the cap is allowed
+        assert_eq!(check_step_impact(5_000, 1_000_000, 100, false).unwrap(), 100);
+    }
+
+    // Registry with Raydium and Whirlpool (a_to_b) on distinct programs, Meteora unregistered
+    fn resolved_route_registry(adapter_pools: Vec<AdapterPools>) -> AdapterRegistry {
+        let adapter = |name: &str, swap_type: Swap| AdapterInfo {
+            name: name.to_string(),
+            program_id: Pubkey::new_unique(),
+            swap_type,
+        };
+        AdapterRegistry {
+            authority: Pubkey::new_unique(),
+            operators: vec![],
+            supported_adapters: vec![
+                adapter("Raydium", Swap::Raydium),
+                adapter("Whirlpool", Swap::Whirlpool { a_to_b: true }),
+            ],
+            bump: 255,
+            memo_program_id: Pubkey::default(),
+            quote_signer: Pubkey::default(),
+            suspended_operators: vec![],
+            order_creation_fee_lamports: 0,
+            treasury: Pubkey::default(),
+            adapter_pools,
+            trusted_routers: vec![],
+            adapter_impact_caps: vec![],
+            order_price_band_bps: 0,
+            suppress_step_events: false,
+            enforce_quote_sanity: false,
+            quote_sanity_band_bps: 0,
+            reject_delegated_vaults: false,
+            pool_attestation_max_age_slots: 0,
+            paused_adapters: vec![],
+            sandboxed_adapters: vec![],
+            retired_adapters: vec![],
+            adapter_pool_counts: vec![],
+        }
+    }
+
+    // Vault keys are derived from their remaining_accounts index so equal indices mean equal vaults
+    fn vault_key(index: u8) -> Pubkey {
+        Pubkey::new_from_array([index; 32])
+    }
+
+    fn resolved_step(
+        swap: Swap,
+        percent: u8,
+        (input_index, input_mint): (u8, Pubkey),
+        (output_index, output_mint): (u8, Pubkey),
+    ) -> ResolvedStep {
+        ResolvedStep {
+            input_vault: vault_key(input_index),
+            input_mint,
+            output_vault: vault_key(output_index),
+            output_mint,
+            pool: Pubkey::new_unique(),
+            input_vault_delegated: false,
+            output_vault_delegated: false,
+            pool_verified_slot: (!matches!(swap, Swap::WrapSol | Swap::UnwrapSol)).then_some(0),
+            swap,
+            percent,
+            input_index,
+            output_index,
+        }
+    }
+
+    #[test]
+    fn test_resolved_route_accepts_single_step() {
+        let registry = resolved_route_registry(vec![]);
+        let (source, destination) = (Pubkey::new_unique(), Pubkey::new_unique());
+        let steps = vec![resolved_step(Swap::Raydium, 100, (0, source), (12, destination))];
+
+        assert!(validate_resolved_route(&steps, &registry, &source, &destination, 1_000, false).is_ok());
+    }
+
+    #[test]
+    fn test_resolved_route_accepts_multi_hop_chain() {
+        let registry = resolved_route_registry(vec![]);
+        let (source, middle, destination) = (Pubkey::new_unique(), Pubkey::new_unique(), Pubkey::new_unique());
+        let steps = vec![
+            resolved_step(Swap::Raydium, 100, (0, source), (12, middle)),
+            resolved_step(Swap::Whirlpool { a_to_b: true }, 100, (12, middle), (24, destination)),
+        ];
+
+        assert!(validate_resolved_route(&steps, &registry, &source, &destination, 1_000, false).is_ok());
+    }
+
+    #[test]
+    fn test_resolved_route_accepts_split_across_dexes() {
+        let registry = resolved_route_registry(vec![]);
+        let (source, destination) = (Pubkey::new_unique(), Pubkey::new_unique());
+        let steps = vec![
+            resolved_step(Swap::Raydium, 60, (0, source), (30, destination)),
+            resolved_step(Swap::Whirlpool { a_to_b: true }, 40, (0, source), (30, destination)),
+        ];
+
+        assert!(validate_resolved_route(&steps, &registry, &source, &destination, 1_000, false).is_ok());
+    }
+
+    #[test]
+    fn test_resolved_route_accepts_split_then_hop() {
+        let registry = resolved_route_registry(vec![]);
+        let (source, branch_a, branch_b, destination) =
+            (Pubkey::new_unique(), Pubkey::new_unique(), Pubkey::new_unique(), Pubkey::new_unique());
+        let steps = vec![
+            resolved_step(Swap::Raydium, 50, (0, source), (12, branch_a)),
+            resolved_step(Swap::Whirlpool { a_to_b: true }, 50, (0, source), (24, branch_b)),
+            resolved_step(Swap::Raydium, 100, (12, branch_a), (36, destination)),
+            resolved_step(Swap::Whirlpool { a_to_b: true }, 100, (24, branch_b), (36, destination)),
+        ];
+
+        assert!(validate_resolved_route(&steps, &registry, &source, &destination, 1_000, false).is_ok());
+        // 1 split 50/50 leaves the first branch nothing to swap
+        let err = validate_resolved_route(&steps, &registry, &source, &destination, 1, false).unwrap_err();
+        assert_eq!(err, ErrorCode::StepAmountTooSmall.into());
+    }
+
+    #[test]
+    fn test_step_amounts_reject_step_reading_unfunded_vault() {
+        let (source, middle, destination) = (Pubkey::new_unique(), Pubkey::new_unique(), Pubkey::new_unique());
+
+        // The second step reads vault 24, which no earlier step delivers into
+        let steps = vec![
+            resolved_step(Swap::Raydium, 100, (0, source), (12, middle)),
+            resolved_step(Swap::Whirlpool { a_to_b: true }, 100, (24, middle), (36, destination)),
+        ];
+        let err = validate_step_amounts(&steps, 1_000).unwrap_err();
+        assert_eq!(err, ErrorCode::StepAmountTooSmall.into());
+    }
+
+    #[test]
+    fn test_resolved_route_rejects_empty_route() {
+        let registry = resolved_route_registry(vec![]);
+        let err = validate_resolved_route(&[], &registry, &Pubkey::new_unique(), &Pubkey::new_unique(), 1_000, false).unwrap_err();
+        assert_eq!(err, ErrorCode::EmptyRoute.into());
+    }
+
+    #[test]
+    fn test_resolved_route_rejects_bad_percents() {
+        let registry = resolved_route_registry(vec![]);
+        let (source, destination) = (Pubkey::new_unique(), Pubkey::new_unique());
+
+        for percent in [0u8, 101, 255] {
+            let steps = vec![resolved_step(Swap::Raydium, percent, (0, source), (12, destination))];
+            let err = validate_resolved_route(&steps, &registry, &source, &destination, 1_000, false).unwrap_err();
+            assert_eq!(err, ErrorCode::InvalidPercent.into());
+        }
+    }
+
+    #[test]
+    fn test_sol_escrow_steps_only_in_the_middle_of_a_route() {
+        let wsol = anchor_spl::token::spl_token::native_mint::ID;
+        let (source, destination) = (Pubkey::new_unique(), Pubkey::new_unique());
+        let unwrap = resolved_step(Swap::UnwrapSol, 100, (6, wsol), (12, wsol));
+        let wrap = resolved_step(Swap::WrapSol, 100, (12, wsol), (18, wsol));
+
+        let steps = vec![
+            resolved_step(Swap::Raydium, 100, (0, source), (6, wsol)),
+            unwrap.clone(),
+            wrap.clone(),
+            resolved_step(Swap::Raydium, 100, (18, wsol), (30, destination)),
+        ];
+        assert!(validate_sol_escrow_steps(&steps, false).is_ok());
+
+        // Spending the escrow before anything paid into it, or ending the route in it
+        for steps in [vec![wrap.clone()], vec![wrap.clone(), unwrap.clone()], vec![unwrap.clone()]] {
+            let err = validate_sol_escrow_steps(&steps, false).unwrap_err();
+            assert_eq!(err, ErrorCode::SolEscrowAtRouteEndpoint.into());
+        }
+    }
+
+    #[test]
+    fn test_sol_escrow_steps_at_native_route_endpoints() {
+        let wsol = anchor_spl::token::spl_token::native_mint::ID;
+        let token = Pubkey::new_unique();
+        let wrap = resolved_step(Swap::WrapSol, 100, (0, wsol), (6, wsol));
+        let unwrap = resolved_step(Swap::UnwrapSol, 100, (18, wsol), (0, wsol));
+
+        // SOL -> X -> SOL: the user's lamports are wrapped first and the output paid out last
+        let steps = vec![
+            wrap.clone(),
+            resolved_step(Swap::Raydium, 100, (6, wsol), (12, token)),
+            resolved_step(Swap::Raydium, 100, (12, token), (18, wsol)),
+            unwrap.clone(),
+        ];
+        assert!(validate_sol_escrow_steps(&steps, true).is_ok());
+        assert_eq!(
+            validate_sol_escrow_steps(&steps, false).unwrap_err(),
+            ErrorCode::SolEscrowAtRouteEndpoint.into()
+        );
+
+        // Only the route's opening steps may spend lamports nothing paid into the escrow
+        let steps = vec![
+            resolved_step(Swap::Raydium, 100, (0, token), (6, wsol)),
+            wrap.clone(),
+        ];
+        assert_eq!(
+            validate_sol_escrow_steps(&steps, true).unwrap_err(),
+            ErrorCode::SolEscrowAtRouteEndpoint.into()
+        );
+    }
+
+    #[test]
+    fn test_resolve_route_steps_uses_escrow_as_pool_sentinel() {
+        let wsol = anchor_spl::token::spl_token::native_mint::ID;
+        let escrow = crate::sdk::derive_sol_escrow().0;
+        let vault_authority = Pubkey::new_unique();
+        let accounts = || {
+            vec![
+                token_account(wsol, vault_authority, 1_000, anchor_spl::token::ID),
+                empty_account().with_key(escrow),
+                empty_account().with_key(crate::sdk::derive_sol_escrow_unwrap_account().0),
+                mint_account(9, anchor_spl::token::ID).with_key(wsol),
+                program_account(anchor_spl::token::ID),
+                program_account(anchor_lang::system_program::ID),
+                empty_account().with_key(escrow),
+            ]
+        };
+        let unwrap = RoutePlanStep { swap: Swap::UnwrapSol, percent: 100, input_index: 0, output_index: 6 };
+
+        let steps = resolve_route_steps(&[unwrap.clone()], leak_account_infos(accounts())).unwrap();
+        assert_eq!(steps[0].pool, escrow);
+        assert_eq!((steps[0].input_mint, steps[0].output_mint), (wsol, wsol));
+        assert!(!steps[0].output_vault_delegated);
+        assert_eq!(steps[0].pool_verified_slot, None);
+
+        // Anything other than the escrow in the PoolInfo slot or on the escrow side is rejected
+        let mut wrong_sentinel = accounts();
+        wrong_sentinel[1] = empty_account();
+        let err = resolve_route_steps(&[unwrap.clone()], leak_account_infos(wrong_sentinel)).unwrap_err();
+        assert_eq!(err, ErrorCode::InvalidSolEscrow.into());
+
+        let mut wrong_output = accounts();
+        wrong_output[6] = token_account(wsol, vault_authority, 0, anchor_spl::token::ID);
+        let err = resolve_route_steps(&[unwrap], leak_account_infos(wrong_output)).unwrap_err();
+        assert_eq!(err, ErrorCode::InvalidSolEscrow.into());
+    }
+
+    #[test]
+    fn test_read_token_account_rejects_layout_compatible_fakes() {
+        let (mint, vault_authority) = (Pubkey::new_unique(), Pubkey::new_unique());
+        let fake_program = Pubkey::new_unique();
+        let mut fake_vault = token_account(mint, vault_authority, 1_000, anchor_spl::token::ID);
+        fake_vault.owner = fake_program;
+        let accounts = leak_account_infos(vec![
+            token_account(mint, vault_authority, 1_000, anchor_spl::token::ID),
+            token_account(mint, vault_authority, 1_000, anchor_spl::token_2022::ID),
+            fake_vault,
+        ]);
+
+        assert_eq!(read_token_account(&accounts[0]).unwrap().mint, mint);
+        assert_eq!(read_token_account(&accounts[1]).unwrap().mint, mint);
+        let err = read_token_account(&accounts[2]).unwrap_err();
+        assert_eq!(err, ErrorCode::InvalidAccountOwner.into());
+        // Snapshots skip the fake instead of trusting its owner field
+        assert_eq!(snapshot_vaults(accounts, &vault_authority).len(), 2);
+    }
+
+    #[test]
+    fn test_resolve_route_steps_rejects_fake_step_vaults() {
+        let (source, destination) = (Pubkey::new_unique(), Pubkey::new_unique());
+        let vault_authority = Pubkey::new_unique();
+        let fake_program = Pubkey::new_unique();
+        let accounts = |fake_index: usize| {
+            let mut accounts = vec![
+                token_account(source, vault_authority, 1_000, anchor_spl::token::ID),
+                crate::test_utils::pool_info_account(Swap::Raydium, Pubkey::new_unique(), true),
+                token_account(destination, vault_authority, 0, anchor_spl::token::ID),
+            ];
+            accounts[fake_index].owner = fake_program;
+            accounts
+        };
+        let step = RoutePlanStep { swap: Swap::Raydium, percent: 100, input_index: 0, output_index: 2 };
+
+        for fake_index in [0, 2] {
+            let err = resolve_route_steps(&[step.clone()], leak_account_infos(accounts(fake_index))).unwrap_err();
+            assert_eq!(err, ErrorCode::InvalidAccountOwner.into());
+        }
+    }
+
+    #[test]
+    fn test_resolved_route_rejects_dust_step() {
+        let registry = resolved_route_registry(vec![]);
+        let (source, destination) = (Pubkey::new_unique(), Pubkey::new_unique());
+        let steps = vec![
+            resolved_step(Swap::Raydium, 50, (0, source), (30, destination)),
+            resolved_step(Swap::Whirlpool { a_to_b: true }, 50, (0, source), (30, destination)),
+        ];
+
+        let err = validate_resolved_route(&steps, &registry, &source, &destination, 1, false).unwrap_err();
+        assert_eq!(err, ErrorCode::StepAmountTooSmall.into());
+        assert!(validate_resolved_route(&steps, &registry, &source, &destination, 2, false).is_ok());
+    }
+
+    #[test]
+    fn test_resolved_route_rejects_broken_chain() {
+        let registry = resolved_route_registry(vec![]);
+        let (source, middle, destination) = (Pubkey::new_unique(), Pubkey::new_unique(), Pubkey::new_unique());
+
+        // Second hop reads the first hop's vault but claims another mint
+        let steps = vec![
+            resolved_step(Swap::Raydium, 100, (0, source), (12, middle)),
+            resolved_step(Swap::Whirlpool { a_to_b: true }, 100, (12, Pubkey::new_unique()), (24, destination)),
+        ];
+        let err = validate_resolved_route(&steps, &registry, &source, &destination, 1_000, false).unwrap_err();
+        assert_eq!(err, ErrorCode::InvalidMultiHopRoute.into());
+    }
+
+    #[test]
+    fn test_resolved_route_rejects_wrong_endpoints() {
+        let registry = resolved_route_registry(vec![]);
+        let (source, destination) = (Pubkey::new_unique(), Pubkey::new_unique());
+
+        // First step does not spend source_mint
+        let steps = vec![resolved_step(Swap::Raydium, 100, (0, Pubkey::new_unique()), (12, destination))];
+        let err = validate_resolved_route(&steps, &registry, &source, &destination, 1_000, false).unwrap_err();
+        assert_eq!(err, ErrorCode::InvalidMint.into());
+
+        // Last step does not produce destination_mint
+        let steps = vec![resolved_step(Swap::Raydium, 100, (0, source), (12, Pubkey::new_unique()))];
+        let err = validate_resolved_route(&steps, &registry, &source, &destination, 1_000, false).unwrap_err();
+        assert_eq!(err, ErrorCode::InvalidMint.into());
+    }
+
+    #[test]
+    fn test_resolved_route_accepts_same_dex_two_pools() {
+        let registry = resolved_route_registry(vec![]);
+        let (source, destination) = (Pubkey::new_unique(), Pubkey::new_unique());
+
+        // 50/50 across two Raydium pools
+        let steps = vec![
+            resolved_step(Swap::Raydium, 50, (0, source), (30, destination)),
+            resolved_step(Swap::Raydium, 50, (0, source), (30, destination)),
+        ];
+        assert_ne!(steps[0].pool, steps[1].pool);
+        assert!(validate_resolved_route(&steps, &registry, &source, &destination, 1_000, false).is_ok());
+    }
+
+    #[test]
+    fn test_resolved_route_rejects_same_pool_twice() {
+        let registry = resolved_route_registry(vec![]);
+        let (source, destination) = (Pubkey::new_unique(), Pubkey::new_unique());
+        let mut steps = vec![
+            resolved_step(Swap::Raydium, 50, (0, source), (30, destination)),
+            resolved_step(Swap::Raydium, 50, (0, source), (30, destination)),
+        ];
+        steps[1].pool = steps[0].pool;
+
+        let err = validate_resolved_route(&steps, &registry, &source, &destination, 1_000, false).unwrap_err();
+        assert_eq!(err, ErrorCode::InsufficientDexesForPartialSwap.into());
+    }
+
+    #[test]
+    fn test_resolved_route_rejects_incomplete_split() {
+        let registry = resolved_route_registry(vec![]);
+        let (source, destination) = (Pubkey::new_unique(), Pubkey::new_unique());
+
+        let steps = vec![
+            resolved_step(Swap::Raydium, 60, (0, source), (30, destination)),
+            resolved_step(Swap::Whirlpool { a_to_b: true }, 30, (0, source), (30, destination)),
+        ];
+        let err = validate_resolved_route(&steps, &registry, &source, &destination, 1_000, false).unwrap_err();
+        assert_eq!(err, ErrorCode::InvalidPartialSwapPercent.into());
+
+        // Percents over 255 in total are rejected rather than overflowing
+        let steps = vec![
+            resolved_step(Swap::Raydium, 100, (0, source), (30, destination)),
+            resolved_step(Swap::Whirlpool { a_to_b: true }, 90, (0, source), (30, destination)),
+            resolved_step(Swap::Raydium, 90, (0, source), (30, destination)),
+        ];
+        let err = validate_partial_swaps(&steps).unwrap_err();
+        assert_eq!(err, ErrorCode::InvalidPartialSwapPercent.into());
+    }
+
+    #[test]
+    fn test_resolved_route_rejects_unsupported_adapter_and_pool() {
+        let (source, destination) = (Pubkey::new_unique(), Pubkey::new_unique());
+
+        let registry = resolved_route_registry(vec![]);
+        let steps = vec![resolved_step(Swap::Meteora, 100, (0, source), (12, destination))];
+        let err = validate_resolved_route(&steps, &registry, &source, &destination, 1_000, false).unwrap_err();
+        assert_eq!(err, ErrorCode::AdapterNotConfigured.into());
+
+        // A swap type the program has no adapter for is told apart from a missing AdapterInfo
+        assert_eq!(adapter_status(&Swap::Lifinity, &registry), AdapterStatus::NotImplemented);
+        let steps = vec![resolved_step(Swap::Lifinity, 100, (0, source), (12, destination))];
+        let err = validate_resolved_route(&steps, &registry, &source, &destination, 1_000, false).unwrap_err();
+        assert_eq!(err, ErrorCode::AdapterNotImplemented.into());
+
+        let registry = resolved_route_registry(vec![AdapterPools {
+            swap_type: Swap::Raydium,
+            pool_addresses: vec![Pubkey::new_unique()],
+        }]);
+        let steps = vec![resolved_step(Swap::Raydium, 100, (0, source), (12, destination))];
+        let err = validate_resolved_route(&steps, &registry, &source, &destination, 1_000, false).unwrap_err();
+        assert_eq!(err, ErrorCode::PoolNotWhitelisted.into());
+    }
+
+    #[test]
+    fn test_validate_output_produced() {
+        let (source, destination) = (Pubkey::new_unique(), Pubkey::new_unique());
+        let steps = vec![resolved_step(Swap::Raydium, 100, (0, source), (12, Pubkey::new_unique()))];
+
+        let err = validate_output_produced(&steps, &destination).unwrap_err();
+        assert_eq!(err, ErrorCode::NoOutputProduced.into());
+        assert!(validate_output_produced(&[], &destination).is_err());
+
+        let steps = vec![resolved_step(Swap::Raydium, 100, (0, source), (12, destination))];
+        assert!(validate_output_produced(&steps, &destination).is_ok());
+    }
+
+    #[test]
+    fn test_order_step_inputs_accept_order_vault_and_produced_vaults() {
+        let (input, middle, output) = (Pubkey::new_unique(), Pubkey::new_unique(), Pubkey::new_unique());
+        let order_vault = vault_key(0);
+
+        // Split of the order vault, then back through the input mint via a vault the route paid into
+        let steps = vec![
+            resolved_step(Swap::Raydium, 50, (0, input), (12, middle)),
+            resolved_step(Swap::Whirlpool { a_to_b: true }, 50, (0, input), (12, middle)),
+            resolved_step(Swap::Raydium, 100, (12, middle), (24, input)),
+            resolved_step(Swap::Whirlpool { a_to_b: true }, 100, (24, input), (36, output)),
+        ];
+        assert!(validate_order_step_inputs(&steps, &order_vault, &input).is_ok());
+    }
+
+    #[test]
+    fn test_order_step_inputs_reject_another_orders_vault() {
+        let (input, middle, output) = (Pubkey::new_unique(), Pubkey::new_unique(), Pubkey::new_unique());
+        let order_vault = vault_key(0);
+        // Vault 48: a second (victim) order's vault of the same input mint in remaining_accounts
+
+        // Second branch of a split reads the victim's vault
+        let steps = vec![
+            resolved_step(Swap::Raydium, 50, (0, input), (12, output)),
+            resolved_step(Swap::Whirlpool { a_to_b: true }, 50, (48, input), (12, output)),
+        ];
+        let err = validate_order_step_inputs(&steps, &order_vault, &input).unwrap_err();
+        assert_eq!(err, ErrorCode::InvalidVaultAddress.into());
+
+        // A later step reads it after the order vault was spent in full
+        let steps = vec![
+            resolved_step(Swap::Raydium, 100, (0, input), (12, middle)),
+            resolved_step(Swap::Raydium, 100, (12, middle), (24, output)),
+            resolved_step(Swap::Whirlpool { a_to_b: true }, 100, (48, input), (24, output)),
+        ];
+        let err = validate_order_step_inputs(&steps, &order_vault, &input).unwrap_err();
+        assert_eq!(err, ErrorCode::InvalidVaultAddress.into());
+
+        // Paying into it later does not make an earlier read legitimate
+        let steps = vec![
+            resolved_step(Swap::Raydium, 100, (0, input), (12, middle)),
+            resolved_step(Swap::Whirlpool { a_to_b: true }, 100, (48, input), (24, output)),
+            resolved_step(Swap::Raydium, 100, (12, middle), (48, input)),
+        ];
+        let err = validate_order_step_inputs(&steps, &order_vault, &input).unwrap_err();
+        assert_eq!(err, ErrorCode::InvalidVaultAddress.into());
+    }
+
+    #[test]
+    fn test_resolved_route_rejects_delegated_vault_only_when_enabled() {
+        let mut registry = resolved_route_registry(vec![]);
+        let (source, middle, destination) = (Pubkey::new_unique(), Pubkey::new_unique(), Pubkey::new_unique());
+
+        // A stale delegate on the intermediate vault both hops pass through
+        let mut steps = vec![
+            resolved_step(Swap::Raydium, 100, (0, source), (12, middle)),
+            resolved_step(Swap::Whirlpool { a_to_b: true }, 100, (12, middle), (24, destination)),
+        ];
+        steps[0].output_vault_delegated = true;
+        steps[1].input_vault_delegated = true;
+        assert!(validate_resolved_route(&steps, &registry, &source, &destination, 1_000, false).is_ok());
+
+        registry.reject_delegated_vaults = true;
+        let err = validate_resolved_route(&steps, &registry, &source, &destination, 1_000, false).unwrap_err();
+        assert_eq!(err, ErrorCode::VaultHasDelegate.into());
+
+        steps[0].output_vault_delegated = false;
+        steps[1].input_vault_delegated = false;
+        assert!(validate_resolved_route(&steps, &registry, &source, &destination, 1_000, false).is_ok());
+    }
+
+    #[test]
+    fn test_pool_attestations_only_required_with_max_age() {
+        let (source, middle, destination) = (Pubkey::new_unique(), Pubkey::new_unique(), Pubkey::new_unique());
+        let mut steps = vec![
+            resolved_step(Swap::Raydium, 100, (0, source), (12, middle)),
+            resolved_step(Swap::Whirlpool { a_to_b: true }, 100, (12, middle), (24, destination)),
+        ];
+        // Never attested pools pass while the check is off
+        assert!(validate_pool_attestations(&steps, 0, 1_000).is_ok());
+        let err = validate_pool_attestations(&steps, 100, 1_000).unwrap_err();
+        assert_eq!(err, ErrorCode::PoolAttestationStale.into());
+
+        steps[0].pool_verified_slot = Some(900);
+        steps[1].pool_verified_slot = Some(950);
+        assert!(validate_pool_attestations(&steps, 100, 1_000).is_ok());
+
+        // Any stale step rejects the route
+        let err = validate_pool_attestations(&steps, 100, 1_001).unwrap_err();
+        assert_eq!(err, ErrorCode::PoolAttestationStale.into());
+    }
+
+    #[test]
+    fn test_pool_attestations_skip_escrow_steps() {
+        let wsol = anchor_spl::token::spl_token::native_mint::ID;
+        let (source, destination) = (Pubkey::new_unique(), Pubkey::new_unique());
+        let mut steps = vec![
+            resolved_step(Swap::Raydium, 100, (0, source), (12, wsol)),
+            resolved_step(Swap::UnwrapSol, 100, (12, wsol), (18, wsol)),
+            resolved_step(Swap::WrapSol, 100, (18, wsol), (24, wsol)),
+            resolved_step(Swap::Raydium, 100, (24, wsol), (36, destination)),
+        ];
+        steps[0].pool_verified_slot = Some(500);
+        steps[3].pool_verified_slot = Some(500);
+        assert!(validate_pool_attestations(&steps, 10, 505).is_ok());
+    }
+
+    #[test]
+    fn test_second_hop_pool_follows_whitelist_and_attestation() {
+        let swap = Swap::WhirlpoolTwoHop { a_to_b_one: true, a_to_b_two: true };
+        let mut pool_info = PoolInfo {
+            adapter_swap_type: swap.clone(),
+            pool_address: Pubkey::new_unique(),
+            enabled: true,
+            last_verified_slot: 0,
+            min_liquidity_threshold: 0,
+            window_start: 0,
+            window_volume: 0,
+            max_volume_per_window: 0,
+            counted: true,
+        };
+
+        let registry = resolved_route_registry(vec![]);
+        assert!(validate_second_hop_pool(&swap, &pool_info, &registry, 0, 1_000).is_ok());
+        let err = validate_second_hop_pool(&swap, &pool_info, &registry, 100, 1_000).unwrap_err();
+        assert_eq!(err, ErrorCode::PoolAttestationStale.into());
+        pool_info.last_verified_slot = 950;
+        assert!(validate_second_hop_pool(&swap, &pool_info, &registry, 100, 1_000).is_ok());
+
+        // A whitelist covering only the step's first pool rejects the second
+        let registry = resolved_route_registry(vec![AdapterPools {
+            swap_type: swap.clone(),
+            pool_addresses: vec![Pubkey::new_unique()],
+        }]);
+        let err = validate_second_hop_pool(&swap, &pool_info, &registry, 100, 1_000).unwrap_err();
+        assert_eq!(err, ErrorCode::PoolNotWhitelisted.into());
+    }
+
+    #[test]
+    fn test_validate_vault_delegates_checks_both_sides_of_every_step() {
+        let (source, destination) = (Pubkey::new_unique(), Pubkey::new_unique());
+        let steps = vec![resolved_step(Swap::Raydium, 100, (0, source), (12, destination))];
+        assert!(validate_vault_delegates(&steps).is_ok());
+
+        for (input_delegated, output_delegated) in [(true, false), (false, true)] {
+            let mut steps = steps.clone();
+            steps[0].input_vault_delegated = input_delegated;
+            steps[0].output_vault_delegated = output_delegated;
+            let err = validate_vault_delegates(&steps).unwrap_err();
+            assert_eq!(err, ErrorCode::VaultHasDelegate.into());
+        }
+    }
+
+    #[test]
+    fn test_sandboxed_adapter_admits_only_allow_listed_users() {
+        let (tester, outsider) = (Pubkey::new_unique(), Pubkey::new_unique());
+        let mut registry = resolved_route_registry(vec![]);
+        registry.sandboxed_adapters = vec![AdapterSandbox { swap_type: Swap::Raydium, sandbox_users: vec![tester] }];
+
+        let raydium_route = vec![create_test_route_plan_step(Swap::Raydium, 100, 0, 12)];
+        assert!(validate_sandbox_access(&raydium_route, &registry, &tester).is_ok());
+        let err = validate_sandbox_access(&raydium_route, &registry, &outsider).unwrap_err();
+        assert_eq!(err, ErrorCode::AdapterInSandbox.into());
+
+        // Any sandboxed step blocks the route; other adapters stay open to everyone
+        let whirlpool_route = vec![create_test_route_plan_step(Swap::Whirlpool { a_to_b: true }, 100, 0, 12)];
+        assert!(validate_sandbox_access(&whirlpool_route, &registry, &outsider).is_ok());
+        let mixed_route = vec![
+            create_test_route_plan_step(Swap::Whirlpool { a_to_b: true }, 100, 0, 6),
+            create_test_route_plan_step(Swap::Raydium, 100, 6, 12),
+        ];
+        let err = validate_sandbox_access(&mixed_route, &registry, &outsider).unwrap_err();
+        assert_eq!(err, ErrorCode::AdapterInSandbox.into());
+    }
+
+    #[test]
+    fn test_graduated_adapter_admits_everyone() {
+        let outsider = Pubkey::new_unique();
+        let mut registry = resolved_route_registry(vec![]);
+        registry.sandboxed_adapters = vec![AdapterSandbox { swap_type: Swap::Raydium, sandbox_users: vec![Pubkey::new_unique()] }];
+        registry.sandboxed_adapters.retain(|entry| entry.swap_type != Swap::Raydium);
+
+        // Graduation drops the adapter's sandbox entry, leaving it open to everyone
+        let raydium_route = vec![create_test_route_plan_step(Swap::Raydium, 100, 0, 12)];
+        assert!(validate_sandbox_access(&raydium_route, &registry, &outsider).is_ok());
+    }
+
+    // Input vault, Raydium PoolInfo and output vault of a one-step route
+    fn raydium_step_accounts() -> Vec<TestAccount> {
+        vec![
+            token_account(Pubkey::new_unique(), Pubkey::new_unique(), 1_000, TOKEN_PROGRAM_ID),
+            pool_info_account(Swap::Raydium, Pubkey::new_unique(), true),
+            token_account(Pubkey::new_unique(), Pubkey::new_unique(), 0, TOKEN_PROGRAM_ID),
+        ]
+    }
+
+    fn resolve_raydium_step(accounts: Vec<TestAccount>) -> Result<Vec<ResolvedStep>> {
+        let route_plan = vec![create_test_route_plan_step(Swap::Raydium, 100, 0, 2)];
+        resolve_route_steps(&route_plan, leak_account_infos(accounts))
+    }
+
+    #[test]
+    fn test_resolve_route_steps_reads_pool_info() {
+        let accounts = raydium_step_accounts();
+        let pool = PoolInfo::try_deserialize(&mut accounts[1].data.as_slice()).unwrap().pool_address;
+
+        let steps = resolve_raydium_step(accounts).unwrap();
+        assert_eq!(steps.len(), 1);
+        assert_eq!(steps[0].pool, pool);
+    }
+
+    #[test]
+    fn test_resolve_route_steps_rejects_truncated_pool_info() {
+        let mut accounts = raydium_step_accounts();
+        accounts[1].data.truncate(PoolInfo::MIN_DATA_LEN - 1);
+        let err = resolve_raydium_step(accounts).unwrap_err();
+        assert_eq!(err, ErrorCode::InvalidPoolInfoAccount.into());
+
+        // Too short to even hold a discriminator
+        let mut accounts = raydium_step_accounts();
+        accounts[1].data.truncate(4);
+        let err = resolve_raydium_step(accounts).unwrap_err();
+        assert_eq!(err, ErrorCode::InvalidPoolInfoAccount.into());
+    }
+
+    #[test]
+    fn test_resolve_route_steps_rejects_pool_info_without_discriminator() {
+        let mut accounts = raydium_step_accounts();
+        accounts[1].data[..8].fill(0);
+        let err = resolve_raydium_step(accounts).unwrap_err();
+        assert_eq!(err, ErrorCode::InvalidPoolInfoAccount.into());
+
+        // A token account in the PoolInfo slot is the usual client mistake
+        let mut accounts = raydium_step_accounts();
+        accounts[1] = token_account(Pubkey::new_unique(), Pubkey::new_unique(), 0, TOKEN_PROGRAM_ID);
+        let err = resolve_raydium_step(accounts).unwrap_err();
+        assert_eq!(err, ErrorCode::InvalidPoolInfoAccount.into());
+    }
+
+    #[test]
+    fn test_scan_token_account_skips_short_and_uninitialized_accounts() {
+        let mint = Pubkey::new_unique();
+        let mut account = token_account(mint, Pubkey::new_unique(), 5, TOKEN_PROGRAM_ID);
+        assert_eq!(scan_token_account(&account.to_account_info()).map(|token| token.mint), Some(mint));
+
+        let mut truncated = token_account(mint, Pubkey::new_unique(), 5, TOKEN_PROGRAM_ID);
+        truncated.data.truncate(TOKEN_ACCOUNT_BASE_LEN - 1);
+        assert!(scan_token_account(&truncated.to_account_info()).is_none());
+
+        let mut uninitialized = TestAccount::new(TOKEN_PROGRAM_ID, vec![0u8; TOKEN_ACCOUNT_BASE_LEN]);
+        assert!(scan_token_account(&uninitialized.to_account_info()).is_none());
+
+        assert!(scan_token_account(&empty_account().to_account_info()).is_none());
+    }
 }
@@ -3,20 +3,19 @@ use anchor_lang::solana_program::{instruction::Instruction, program::invoke_sign
 use anchor_spl::token_interface::{
     Mint, TokenAccount, TokenInterface,
     transfer_checked, TransferChecked,
-    close_account, CloseAccount,
 };
 use crate::errors::ErrorCode;
+use crate::math;
+use crate::instructions::{jupiter_cpi, route_validator_module};
 use crate::state::*;
-use crate::instructions::limit_orders_module::{LimitOrder, OrderStatus};
+use anchor_spl::associated_token::AssociatedToken;
+use crate::instructions::limit_orders_module::{
+    LimitOrder, OrderReceipt, OrderStatus, verify_quote_attestation, resolve_user_destination,
+    refund_destination_rent, require_destination_not_frozen,
+    deliver_unwrapped_output, close_filled_order, current_unix_timestamp,
+};
 use crate::instructions::vault_manager_module::VaultAuthority;
 
-/// Jupiter shared_accounts_route account indices (must match Jupiter IDL).
-/// 2: user_transfer_authority (vault_authority - we sign), 3: user_source, 6: user_destination
-const JUPITER_USER_TRANSFER_AUTHORITY_INDEX: usize = 2;
-const JUPITER_USER_SOURCE_INDEX: usize = 3;
-const JUPITER_USER_DESTINATION_INDEX: usize = 6;
-const JUPITER_MIN_ACCOUNTS: usize = 13;
-
 /// Shared execute limit order instruction
 /// Uses Jupiter CPI for swap execution (different from regular execute_limit_order)
 /// Note: For creating and canceling limit orders, use the regular limit_orders_module functions
@@ -38,12 +37,13 @@ pub struct SharedExecuteLimitOrder<'info> {
     )]
     pub vault_authority: Account<'info, VaultAuthority>,
 
-    /// Limit order to execute (will be closed, rent goes to operator)
+    /// Limit order to execute (closed with rent to the operator once its input vault closes)
     #[account(
         mut,
-        close = operator,
         constraint = limit_order.status == OrderStatus::Open @ ErrorCode::InvalidOrderStatus,
-        constraint = limit_order.input_vault == input_vault.key() @ ErrorCode::InvalidVaultAddress
+        constraint = limit_order.input_vault == input_vault.key() @ ErrorCode::InvalidVaultAddress,
+        constraint = limit_order.input_token_program == input_token_program.key() @ ErrorCode::InvalidTokenProgram,
+        constraint = limit_order.output_token_program == output_token_program.key() @ ErrorCode::InvalidTokenProgram
     )]
     pub limit_order: Account<'info, LimitOrder>,
 
@@ -70,14 +70,13 @@ pub struct SharedExecuteLimitOrder<'info> {
     /// Token program for output tokens
     pub output_token_program: Interface<'info, TokenInterface>,
 
-    /// User's destination account for output tokens
+    /// User's destination account for output tokens, as recorded on the order
+    /// CHECK: May have been closed since creation; validated in resolve_user_destination
     #[account(
         mut,
-        constraint = user_destination_token_account.key() == limit_order.user_destination_account,
-        constraint = user_destination_token_account.mint == limit_order.output_mint,
-        constraint = user_destination_token_account.owner == limit_order.creator
+        constraint = user_destination_token_account.key() == limit_order.user_destination_account
     )]
-    pub user_destination_token_account: Box<InterfaceAccount<'info, TokenAccount>>,
+    pub user_destination_token_account: UncheckedAccount<'info>,
 
     /// Input token mint
     #[account(
@@ -95,15 +94,93 @@ pub struct SharedExecuteLimitOrder<'info> {
     #[account(mut)]
     pub platform_fee_account: Option<InterfaceAccount<'info, TokenAccount>>,
 
+    /// CHECK: FeeSchedule PDA of the input mint, applied once set_fee_schedule has created it
+    #[account(seeds = [b"fee_schedule", limit_order.input_mint.as_ref()], bump)]
+    pub fee_schedule: UncheckedAccount<'info>,
+
+    /// CHECK: ProtocolConfig PDA; nothing is paused until create_protocol_config has created it
+    #[account(seeds = [b"protocol_config"], bump)]
+    pub protocol_config: UncheckedAccount<'info>,
+
+    /// Protocol vault for the input mint, receiving input left in the order vault
+    #[account(
+        mut,
+        seeds = [b"vault", limit_order.input_mint.as_ref()],
+        bump
+    )]
+    pub dust_vault: Option<Box<InterfaceAccount<'info, TokenAccount>>>,
+
     /// Jupiter program for CPI swap
     /// CHECK: Jupiter program ID will be validated in the CPI call
     pub jupiter_program: AccountInfo<'info>,
 
+    /// CHECK: ["receipt", limit_order] PDA, recorded into when the order was created with a receipt
+    #[account(
+        mut,
+        seeds = [b"receipt", limit_order.key().as_ref()],
+        bump
+    )]
+    pub order_receipt: UncheckedAccount<'info>,
+
+    /// CHECK: ["vault_rent_payer", input_vault] PDA, read when a sponsor funded the vault
+    #[account(
+        mut,
+        seeds = [b"vault_rent_payer", input_vault.key().as_ref()],
+        bump
+    )]
+    pub vault_rent_payer: UncheckedAccount<'info>,
+
+    /// Sponsor recorded in vault_rent_payer, receiving the vault rent (required when recorded)
+    /// CHECK: Must match the recorded payer, or the treasury its obligations were transferred to,
+    /// checked in vault_rent_destination
+    #[account(mut)]
+    pub rent_payer: Option<UncheckedAccount<'info>>,
+
+    /// Sponsoring operator's OperatorStats (required when an operator sponsored the vault)
+    /// CHECK: Must belong to the recorded payer, checked in vault_rent_destination
+    #[account(mut)]
+    pub rent_payer_stats: Option<UncheckedAccount<'info>>,
+
+    /// Instructions sysvar for quote attestation (required when the registry has a quote signer)
+    /// CHECK: address is constrained to the instructions sysvar
+    #[account(address = anchor_lang::solana_program::sysvar::instructions::ID)]
+    pub instructions_sysvar: Option<UncheckedAccount<'info>>,
+
+    /// Creator's output-mint ATA, created and used if the recorded destination was closed
+    /// CHECK: Must be the creator's ATA for the output mint, checked in resolve_user_destination
+    #[account(mut)]
+    pub creator_destination_ata: Option<UncheckedAccount<'info>>,
+
+    /// Order creator, owner of creator_destination_ata
+    /// CHECK: Must be the order creator, checked in resolve_user_destination
+    pub creator: Option<UncheckedAccount<'info>>,
+
+    /// Associated token program for creating creator_destination_ata
+    pub associated_token_program: Option<Program<'info, AssociatedToken>>,
+
+    /// Transient WSOL account ["unwrap_account", limit_order] (required when the order has unwrap_output)
+    /// CHECK: Created, filled and closed in deliver_unwrapped_output, which checks the address
+    #[account(mut)]
+    pub unwrap_account: Option<UncheckedAccount<'info>>,
+
+    /// Creator's system account receiving unwrapped output (required when the order has unwrap_output)
+    /// CHECK: Must be limit_order.user_lamport_destination, checked in deliver_unwrapped_output
+    #[account(mut)]
+    pub user_lamport_destination: Option<UncheckedAccount<'info>>,
+
+    /// Executing operator's liveness record, refreshed when passed
+    #[account(
+        mut,
+        seeds = [b"operator_stats", operator.key().as_ref()],
+        bump = operator_stats.bump
+    )]
+    pub operator_stats: Option<Account<'info, OperatorStats>>,
+
     /// Operator executing the order (must be registered, receives rent from closed order)
     #[account(
         mut,
         signer,
-        constraint = adapter_registry.operators.contains(&operator.key()) @ ErrorCode::InvalidOperator
+        constraint = adapter_registry.is_active_operator(&operator.key()) @ ErrorCode::InvalidOperator
     )]
     pub operator: Signer<'info>,
 
@@ -113,72 +190,74 @@ pub struct SharedExecuteLimitOrder<'info> {
 }
 
 /// Executes a limit order using Jupiter CPI. Client passes `data` and Jupiter accounts in `remaining_accounts`.
+/// When the registry has a quote signer, `quote_signature`/`quote_publisher` must reference an
+/// ed25519 attestation earlier in the transaction (same rules as execute_limit_order).
 pub fn shared_execute_limit_order<'info>(
     ctx: Context<'_, '_, 'info, 'info, SharedExecuteLimitOrder<'info>>,
     quoted_out_amount: u64,
     platform_fee_bps: u8,
     data: Vec<u8>,
+    quote_signature: Option<[u8; 64]>,
+    quote_publisher: Option<Pubkey>,
 ) -> Result<u64> {
-    let now = Clock::get()?.unix_timestamp;
+    ProtocolConfig::load_config(&ctx.accounts.protocol_config, ctx.program_id)?.require_execution_open()?;
+
+    let clock = Clock::get()?;
+    let now = current_unix_timestamp(ctx.remaining_accounts)?;
     require!(now < ctx.accounts.limit_order.expiry, ErrorCode::OrderExpired);
+    require!(ctx.accounts.limit_order.is_active(now), ErrorCode::OrderNotActiveYet);
     require!(
         ctx.accounts.limit_order.should_execute(quoted_out_amount)?,
         ErrorCode::TriggerPriceNotMet
     );
 
+    let instructions_sysvar = ctx.accounts.instructions_sysvar.as_ref().map(|sysvar| sysvar.to_account_info());
+    verify_quote_attestation(
+        &ctx.accounts.adapter_registry.quote_signer,
+        instructions_sysvar.as_ref(),
+        quote_signature,
+        quote_publisher,
+        &ctx.accounts.limit_order.key(),
+        quoted_out_amount,
+        clock.slot,
+    )?;
+
+    // A frozen destination would revert the final transfer after the Jupiter swap has run
+    require_destination_not_frozen(
+        &ctx.accounts.limit_order,
+        &ctx.accounts.user_destination_token_account,
+        ctx.accounts.creator_destination_ata.as_deref(),
+    )?;
+
     let in_amount = ctx.accounts.limit_order.input_amount;
     require!(in_amount > 0, ErrorCode::InvalidAmount);
     require!(quoted_out_amount > 0, ErrorCode::InvalidAmount);
-    require!(!data.is_empty(), ErrorCode::EmptyRoute);
-
     // Validate Jupiter program matches the one stored in vault_authority
     require!(
         ctx.accounts.jupiter_program.key() == ctx.accounts.vault_authority.jupiter_program_id,
         ErrorCode::InvalidJupiterProgram
     );
-
-    require!(
-        ctx.remaining_accounts.len() >= JUPITER_MIN_ACCOUNTS,
-        ErrorCode::NotEnoughJupiterAccounts
-    );
-    // Validate that user_transfer_authority (index 2) is our vault_authority
-    require!(
-        ctx.remaining_accounts[JUPITER_USER_TRANSFER_AUTHORITY_INDEX].key() == ctx.accounts.vault_authority.key(),
-        ErrorCode::JupiterProgramAuthorityMismatch
-    );
-    // Validate that user_source (index 3) is our input_vault
-    require!(
-        ctx.remaining_accounts[JUPITER_USER_SOURCE_INDEX].key() == ctx.accounts.input_vault.key(),
-        ErrorCode::JupiterProgramSourceMismatch
-    );
-    // Validate that user_destination (index 6) is our output_vault
-    require!(
-        ctx.remaining_accounts[JUPITER_USER_DESTINATION_INDEX].key() == ctx.accounts.output_vault.key(),
-        ErrorCode::JupiterProgramDestinationMismatch
-    );
+    jupiter_cpi::validate_jupiter_data(&data, ctx.accounts.vault_authority.jupiter_data_limit())?;
+
+    // Jupiter's user_transfer_authority, user_source and user_destination must be our accounts
+    jupiter_cpi::validate_fixed_positions(
+        ctx.remaining_accounts,
+        &ctx.accounts.vault_authority.key(),
+        &ctx.accounts.input_vault.key(),
+        &ctx.accounts.output_vault.key(),
+    )?;
 
     let vault_authority_bump = ctx.bumps.vault_authority;
     let authority_seeds: &[&[u8]] = &[b"vault_authority".as_ref(), &[vault_authority_bump]];
     let signer_seeds: &[&[&[u8]]] = &[authority_seeds];
 
+    ctx.accounts.input_vault.reload()?;
+    let input_balance_before = ctx.accounts.input_vault.amount;
     ctx.accounts.output_vault.reload()?;
     let balance_before = ctx.accounts.output_vault.amount;
 
-    // Build account metas from remaining_accounts; user_transfer_authority (index 2) must be signer
-    let accounts: Vec<anchor_lang::solana_program::instruction::AccountMeta> = ctx
-        .remaining_accounts
-        .iter()
-        .enumerate()
-        .map(|(idx, acc)| {
-            // Only user_transfer_authority (index 2 = vault_authority) should be marked as signer
-            let is_signer = idx == JUPITER_USER_TRANSFER_AUTHORITY_INDEX;
-            anchor_lang::solana_program::instruction::AccountMeta {
-                pubkey: *acc.key,
-                is_signer,
-                is_writable: acc.is_writable,
-            }
-        })
-        .collect();
+    // Build account metas from remaining_accounts; only user_transfer_authority (index 2) signs
+    let accounts = jupiter_cpi::build_account_metas(ctx.remaining_accounts, false);
 
     let account_infos: Vec<AccountInfo> = ctx.remaining_accounts.iter().map(|acc| acc.clone()).collect();
 
@@ -200,6 +279,28 @@ pub fn shared_execute_limit_order<'info>(
 
     msg!("Jupiter swap completed. Output amount: {}", output_amount);
 
+    // A route that produced nothing and spent nothing is recorded rather than reverted:
+    // an error would roll the counter back along with everything else
+    if output_amount == 0 {
+        ctx.accounts.input_vault.reload()?;
+        require!(
+            ctx.accounts.input_vault.amount == input_balance_before,
+            ErrorCode::TriggerPriceNotMet
+        );
+
+        let limit_order = &mut ctx.accounts.limit_order;
+        limit_order.failed_attempts = limit_order.failed_attempts.saturating_add(1);
+        msg!("Execution returned no output; failed attempts: {}", limit_order.failed_attempts);
+
+        emit_cpi!(LimitOrderExecutionFailed {
+            order: limit_order.key(),
+            executor: ctx.accounts.operator.key(),
+            failed_attempts: limit_order.failed_attempts,
+        });
+
+        return Ok(0);
+    }
+
     require!(
         ctx.accounts.limit_order.should_execute(output_amount)?,
         ErrorCode::TriggerPriceNotMet
@@ -208,12 +309,18 @@ pub fn shared_execute_limit_order<'info>(
     let mut fee_amount = 0u64;
     let mut fee_account: Option<Pubkey> = None;
 
-    if let Some(platform_fee_account) = &ctx.accounts.platform_fee_account {
+    let fee_schedule = FeeSchedule::load(&ctx.accounts.fee_schedule, ctx.program_id)?;
+    let fee_bps = ctx.accounts.limit_order.execution_fee_bps(fee_schedule.as_ref(), platform_fee_bps);
+    if let Some(platform_fee_account) = route_validator_module::charged_fee_account(fee_bps, ctx.accounts.platform_fee_account.as_ref())? {
         require!(
             platform_fee_account.mint == ctx.accounts.output_mint.key(),
             ErrorCode::InvalidPlatformFeeMint
         );
-        fee_amount = (output_amount as u128 * platform_fee_bps as u128 / 10_000) as u64;
+        route_validator_module::validate_fee_account_authorities(
+            platform_fee_account,
+            &ctx.accounts.vault_authority.key(),
+        )?;
+        fee_amount = math::apply_bps_fee(output_amount, fee_bps.into())?;
         if fee_amount > 0 {
             transfer_checked(
                 CpiContext::new_with_signer(
@@ -233,6 +340,9 @@ pub fn shared_execute_limit_order<'info>(
                 account: platform_fee_account.key(),
                 mint: ctx.accounts.output_mint.key(),
                 amount: fee_amount,
+                net_amount: route_validator_module::net_transfer_amount(&ctx.accounts.output_mint.to_account_info(), fee_amount)?,
+                fee_bps,
+                source: FeeSource::SharedLimitOrder,
             });
             fee_account = Some(platform_fee_account.key());
             output_amount = output_amount
@@ -243,28 +353,81 @@ pub fn shared_execute_limit_order<'info>(
 
     let min_acceptable = ctx.accounts.limit_order
         .calculate_min_acceptable_output(quoted_out_amount)?;
+    // Compare what actually lands in the user account (net of any Token-2022 transfer fee)
+    let net_output_amount = route_validator_module::net_transfer_amount(
+        &ctx.accounts.output_mint.to_account_info(),
+        output_amount,
+    )?;
     require!(
-        output_amount >= min_acceptable,
+        net_output_amount >= min_acceptable,
         ErrorCode::SlippageToleranceExceeded
     );
 
-    transfer_checked(
-        CpiContext::new_with_signer(
-            ctx.accounts.output_token_program.to_account_info(),
-            TransferChecked {
-                from: ctx.accounts.output_vault.to_account_info(),
-                to: ctx.accounts.user_destination_token_account.to_account_info(),
-                authority: ctx.accounts.vault_authority.to_account_info(),
-                mint: ctx.accounts.output_mint.to_account_info(),
-            },
+    let (user_destination, destination_rent) = if ctx.accounts.limit_order.unwrap_output {
+        // Deliver as native SOL to the creator's system account
+        let lamport_destination = deliver_unwrapped_output(
+            &ctx.accounts.limit_order,
+            &ctx.accounts.output_vault.to_account_info(),
+            ctx.accounts.unwrap_account.as_deref(),
+            ctx.accounts.user_lamport_destination.as_deref(),
+            &ctx.accounts.output_mint,
+            &ctx.accounts.output_token_program.to_account_info(),
+            &ctx.accounts.vault_authority.to_account_info(),
+            &ctx.accounts.operator.to_account_info(),
+            &ctx.accounts.system_program.to_account_info(),
+            ctx.program_id,
+            output_amount,
             signer_seeds,
-        ),
-        output_amount,
-        ctx.accounts.output_mint.decimals,
-    )?;
+        )?;
+        (lamport_destination, 0)
+    } else {
+        // Deliver to the recorded destination, or the creator's ATA if it was closed
+        let (user_destination, created_rent) = resolve_user_destination(
+            &ctx.accounts.user_destination_token_account,
+            &ctx.accounts.limit_order,
+            ctx.accounts.creator_destination_ata.as_deref(),
+            ctx.accounts.creator.as_deref(),
+            ctx.accounts.associated_token_program.as_deref(),
+            &ctx.accounts.operator.to_account_info(),
+            &ctx.accounts.output_mint.to_account_info(),
+            &ctx.accounts.output_token_program.to_account_info(),
+            &ctx.accounts.system_program.to_account_info(),
+        )?;
+        refund_destination_rent(
+            &ctx.accounts.limit_order.to_account_info(),
+            &ctx.accounts.operator.to_account_info(),
+            created_rent,
+        )?;
+
+        transfer_checked(
+            CpiContext::new_with_signer(
+                ctx.accounts.output_token_program.to_account_info(),
+                TransferChecked {
+                    from: ctx.accounts.output_vault.to_account_info(),
+                    to: user_destination.clone(),
+                    authority: ctx.accounts.vault_authority.to_account_info(),
+                    mint: ctx.accounts.output_mint.to_account_info(),
+                },
+                signer_seeds,
+            ),
+            output_amount,
+            ctx.accounts.output_mint.decimals,
+        )?;
+        (user_destination, created_rent)
+    };
 
     ctx.accounts.limit_order.status = OrderStatus::Filled;
 
+    let finalized_at = current_unix_timestamp(ctx.remaining_accounts)?;
+    OrderReceipt::update(&ctx.accounts.order_receipt.to_account_info(), ctx.program_id, |receipt| {
+        receipt.record(OrderStatus::Filled, output_amount, fee_amount, ctx.accounts.operator.key(), finalized_at)
+    })?;
+
+    let effective_price = ctx.accounts.limit_order.calculate_effective_price(output_amount)?;
+    let trigger_threshold_amount = ctx.accounts.limit_order.calculate_trigger_threshold()?;
+    let execution_premium_bps = ctx.accounts.limit_order.calculate_execution_premium_bps(output_amount)?;
+
+    // Jupiter routes are opaque to us, so no venues are reported
     emit_cpi!(LimitOrderExecuted {
         order: ctx.accounts.limit_order.key(),
         executor: ctx.accounts.operator.key(),
@@ -273,12 +436,19 @@ pub fn shared_execute_limit_order<'info>(
         fee_amount,
         trigger_type: ctx.accounts.limit_order.trigger_type as u8,
         min_output_amount: ctx.accounts.limit_order.min_output_amount,
+        venues: Vec::new(),
+        effective_price,
+        fee_account,
+        quoted_out_amount,
+        trigger_threshold_amount,
+        execution_premium_bps,
     });
+    emit_cpi!(ctx.accounts.limit_order.open_interest_change(false));
 
     emit_cpi!(LimitOrderSwapEvent {
         order: ctx.accounts.limit_order.key(),
         sender: ctx.accounts.limit_order.creator,
-        recipient: ctx.accounts.user_destination_token_account.key(),
+        recipient: user_destination.key(),
         executor: ctx.accounts.operator.key(),
         input_mint: ctx.accounts.input_mint.key(),
         output_mint: ctx.accounts.output_mint.key(),
@@ -287,20 +457,35 @@ pub fn shared_execute_limit_order<'info>(
         fee_amount,
         fee_account,
         trigger_type: ctx.accounts.limit_order.trigger_type as u8,
+        net_output_amount,
     });
 
-    close_account(
-        CpiContext::new_with_signer(
-            ctx.accounts.input_token_program.to_account_info(),
-            CloseAccount {
-                account: ctx.accounts.input_vault.to_account_info(),
-                destination: ctx.accounts.operator.to_account_info(),
-                authority: ctx.accounts.vault_authority.to_account_info(),
-            },
-            signer_seeds,
-        ),
+    // Best effort, same as execute_limit_order: the fill is final either way
+    close_filled_order(
+        &ctx.accounts.limit_order,
+        &ctx.accounts.input_vault.to_account_info(),
+        ctx.accounts.dust_vault.as_ref().map(|vault| vault.to_account_info()).as_ref(),
+        &ctx.accounts.order_receipt.to_account_info(),
+        &ctx.accounts.input_mint,
+        &ctx.accounts.input_token_program.to_account_info(),
+        &ctx.accounts.vault_authority.to_account_info(),
+        &ctx.accounts.operator.to_account_info(),
+        &ctx.accounts.vault_rent_payer.to_account_info(),
+        ctx.accounts.rent_payer.as_ref().map(|payer| payer.to_account_info()).as_ref(),
+        ctx.accounts.rent_payer_stats.as_ref().map(|stats| stats.to_account_info()).as_ref(),
+        destination_rent,
+        ctx.program_id,
+        signer_seeds,
     )?;
 
+    // Execution counts as operator activity; only standalone heartbeats are throttled.
+    // An operator filling an order it sponsored has just had that obligation settled in its
+    // own stats, so they are reloaded first and the exit does not write back a stale copy
+    if let Some(operator_stats) = ctx.accounts.operator_stats.as_mut() {
+        operator_stats.reload()?;
+        operator_stats.last_active = now;
+    }
+
     msg!("Shared limit order executed: {}", ctx.accounts.limit_order.key());
     Ok(output_amount)
 }
@@ -5,23 +5,50 @@ use anchor_spl::token_interface::{
     transfer_checked, TransferChecked
 };
 use crate::errors::ErrorCode;
+#[cfg(feature = "invariant-checks")]
+use crate::invariants;
+#[cfg(feature = "telemetry")]
+use crate::telemetry;
+use crate::math;
+use crate::instructions::{jupiter_cpi, route_validator_module};
+use crate::instructions::jupiter_cpi::JUPITER_SHARED_ACCOUNTS_ROUTE_DISCRIMINATOR;
 use crate::state::*;
+use crate::instructions::swap_processor_module::{
+    can_spend_source, record_trader_volume, require_source_allowance, trader_volume_of, volume_tier_fee_bps,
+};
 use crate::instructions::vault_manager_module::VaultAuthority;
-use crate::instructions::limit_orders_module::{LimitOrder, OrderStatus, TriggerType};
-
-/// Jupiter shared_accounts_route fixed account indices (must match Jupiter IDL order).
-/// 0: token_program
-/// 1: program_authority (Jupiter PDA - not ours!)
-/// 2: user_transfer_authority  <-- Must match our vault_authority (we sign for this)
-/// 3: user_source_token_account  <-- Must match our vault_source
-/// 4: program_source_token_account (Jupiter's intermediate account)
-/// 5: program_destination_token_account (Jupiter's intermediate account)
-/// 6: user_destination_token_account  <-- Must match our vault_destination
-/// 7-12: source_mint, destination_mint, platform_fee, token2022, event_authority, program
-const JUPITER_USER_TRANSFER_AUTHORITY_INDEX: usize = 2;
-const JUPITER_USER_SOURCE_INDEX: usize = 3;
-const JUPITER_USER_DESTINATION_INDEX: usize = 6;
-const JUPITER_MIN_ACCOUNTS: usize = 13; // 0..=12: token_program, program_authority, ..., program
+use crate::instructions::limit_orders_module::{
+    collect_order_creation_fee, current_unix_timestamp, require_distinct_order_mints, validate_trigger_price, LimitOrder,
+    OrderStatus, TriggerType,
+};
+
+/// shared_accounts_route args end with in_amount (u64), quoted_out_amount (u64), slippage_bps (u16)
+/// and platform_fee_bps (u8), after the variable-length route_plan
+const JUPITER_ROUTE_TRAILING_ARGS_LEN: usize = 8 + 8 + 2 + 1;
+
+// Test modules
+#[cfg(test)]
+mod shared_route_test;
+
+/// Reads in_amount from serialized Jupiter shared_accounts_route instruction data
+/// The fixed-size args after route_plan are read from the end, so the route plan itself
+/// does not need to be decoded
+///
+/// # Returns
+/// * `Result<u64>` - The in_amount Jupiter will pull from the user source account
+pub fn jupiter_route_in_amount(data: &[u8]) -> Result<u64> {
+    // discriminator + id (u8) + route_plan length (u32) + trailing args
+    if data.len() < 8 + 1 + 4 + JUPITER_ROUTE_TRAILING_ARGS_LEN
+        || data[..8] != JUPITER_SHARED_ACCOUNTS_ROUTE_DISCRIMINATOR
+    {
+        return Err(ErrorCode::JupiterInAmountMismatch.into());
+    }
+
+    let start = data.len() - JUPITER_ROUTE_TRAILING_ARGS_LEN;
+    let mut in_amount = [0u8; 8];
+    in_amount.copy_from_slice(&data[start..start + 8]);
+    Ok(u64::from_le_bytes(in_amount))
+}
 
 /// Shared route instruction for Jupiter CPI-based swaps
 /// All swaps now go through vault-first approach using Jupiter CPI
@@ -40,11 +67,11 @@ pub struct SharedRoute<'info> {
     )]
     pub vault_authority: Account<'info, VaultAuthority>,
 
-    /// User's source account for input tokens
+    /// User's source account for input tokens, owned by or delegated to user_transfer_authority
     #[account(
         mut,
         constraint = user_source_token_account.mint == source_mint.key() @ ErrorCode::InvalidMint,
-        constraint = user_source_token_account.owner == user_transfer_authority.key() @ ErrorCode::InvalidAccount
+        constraint = can_spend_source(&user_source_token_account, &user_transfer_authority.key()) @ ErrorCode::InvalidAccount
     )]
     pub user_source_token_account: Box<InterfaceAccount<'info, TokenAccount>>,
 
@@ -89,12 +116,32 @@ pub struct SharedRoute<'info> {
     pub output_token_program: Interface<'info, TokenInterface>,
 
     /// User/operator initiating the swap
+    #[account(mut)]
     pub user_transfer_authority: Signer<'info>,
 
-    /// Optional platform fee collection account
+    /// Optional platform fee collection account (source mint with fee_on_input, destination mint otherwise)
     #[account(mut)]
     pub platform_fee_account: Option<Box<InterfaceAccount<'info, TokenAccount>>>,
 
+    /// CHECK: FeeVaultRegistry PDA, read when use_registered_fee_vault is set
+    #[account(seeds = [b"fee_vault_registry"], bump)]
+    pub fee_vault_registry: UncheckedAccount<'info>,
+
+    /// CHECK: ProtocolConfig PDA; default limits apply until create_protocol_config has created it
+    #[account(seeds = [b"protocol_config"], bump)]
+    pub protocol_config: UncheckedAccount<'info>,
+
+    /// The trader's volume record, created on first use; without it the swap earns no volume
+    /// tier rebate and its volume is not counted
+    #[account(
+        init_if_needed,
+        payer = user_transfer_authority,
+        space = TraderVolume::SPACE,
+        seeds = [b"trader_volume", user_transfer_authority.key().as_ref()],
+        bump
+    )]
+    pub trader_volume: Option<Box<Account<'info, TraderVolume>>>,
+
     /// Jupiter program for CPI swap
     /// CHECK: Jupiter program ID will be validated in the CPI call
     pub jupiter_program: AccountInfo<'info>,
@@ -112,57 +159,101 @@ pub struct SharedRoute<'info> {
 /// * `in_amount` - Input token amount (for user->vault transfer)
 /// * `quoted_out_amount` - Expected output amount from Jupiter quote (same as route)
 /// * `slippage_bps` - Slippage tolerance in basis points (same as route)
-/// * `platform_fee_bps` - Platform fee in basis points
+/// * `platform_fee_bps` - Platform fee in basis points, lowered by the trader's volume tier
+///   when trader_volume is passed
+/// * `min_out_amount` - Caller's own minimum net output after fees (0 = none, same as route)
+/// * `fee_on_input` - Take the platform fee in the source mint before the swap instead of from the output;
+///   the in_amount in `data` must then equal in_amount minus the fee at the volume tier rate
+/// * `use_registered_fee_vault` - Collect the fee into the fee mint's registered fee vault, passed in
+///   remaining_accounts, instead of platform_fee_account
 /// * `data` - Serialized Jupiter shared_accounts_route instruction data (from API) - LAST to avoid Borsh deserialization issues
+#[allow(clippy::too_many_arguments)]
 pub fn shared_route<'info>(
     ctx: Context<'_, '_, 'info, 'info, SharedRoute<'info>>,
     in_amount: u64,
     quoted_out_amount: u64,
     slippage_bps: u16,
     platform_fee_bps: u8,
+    min_out_amount: u64,
+    fee_on_input: bool,
+    use_registered_fee_vault: bool,
     data: Vec<u8>,
 ) -> Result<u64> {
+    #[cfg(feature = "telemetry")]
+    let compute_meter = telemetry::ComputeMeter::start(telemetry::SHARED_ROUTE_TELEMETRY_TAG);
+    // Read before `data` moves into the Jupiter instruction
+    #[cfg(feature = "telemetry")]
+    let jupiter_steps = telemetry::jupiter_route_steps(&data);
+
     require!(in_amount > 0, ErrorCode::InvalidAmount);
     require!(quoted_out_amount > 0, ErrorCode::InvalidAmount);
-    require!(slippage_bps <= 10_000, ErrorCode::InvalidSlippage);
-    require!(!data.is_empty(), ErrorCode::EmptyRoute);
-
+    let protocol_config = ProtocolConfig::load_config(&ctx.accounts.protocol_config, ctx.program_id)?;
+    protocol_config.limits.check_route_slippage(slippage_bps)?;
     // Validate Jupiter program matches the one stored in vault_authority
     require!(
         ctx.accounts.jupiter_program.key() == ctx.accounts.vault_authority.jupiter_program_id,
         ErrorCode::InvalidJupiterProgram
     );
+    jupiter_cpi::validate_jupiter_data(&data, ctx.accounts.vault_authority.jupiter_data_limit())?;
+
+    // Jupiter's user_transfer_authority, user_source and user_destination must be our accounts
+    jupiter_cpi::validate_fixed_positions(
+        ctx.remaining_accounts,
+        &ctx.accounts.vault_authority.key(),
+        &ctx.accounts.vault_source.key(),
+        &ctx.accounts.vault_destination.key(),
+    )?;
 
-    require!(
-        ctx.remaining_accounts.len() >= JUPITER_MIN_ACCOUNTS,
-        ErrorCode::NotEnoughJupiterAccounts
-    );
-    // Validate that user_transfer_authority (index 2) is our vault_authority
-    require!(
-        ctx.remaining_accounts[JUPITER_USER_TRANSFER_AUTHORITY_INDEX].key() == ctx.accounts.vault_authority.key(),
-        ErrorCode::JupiterProgramAuthorityMismatch
-    );
-    // Validate that user_source (index 3) is our vault_source
-    require!(
-        ctx.remaining_accounts[JUPITER_USER_SOURCE_INDEX].key() == ctx.accounts.vault_source.key(),
-        ErrorCode::JupiterProgramSourceMismatch
-    );
-    // Validate that user_destination (index 6) is our vault_destination
-    require!(
-        ctx.remaining_accounts[JUPITER_USER_DESTINATION_INDEX].key() == ctx.accounts.vault_destination.key(),
-        ErrorCode::JupiterProgramDestinationMismatch
-    );
+    let fee_mint = if fee_on_input {
+        ctx.accounts.source_mint.key()
+    } else {
+        ctx.accounts.destination_mint.key()
+    };
+    // Without a fee no account is used or validated; with one it is mandatory
+    let platform_fee_account = if use_registered_fee_vault && platform_fee_bps > 0 {
+        // One fee account per route, so the registry cannot be bypassed by also naming one
+        require!(ctx.accounts.platform_fee_account.is_none(), ErrorCode::RegisteredFeeVaultNotProvided);
+        Some(
+            route_validator_module::find_registered_fee_vault(
+                &ctx.accounts.fee_vault_registry.to_account_info(),
+                &fee_mint,
+                &ctx.accounts.vault_authority.key(),
+                ctx.remaining_accounts,
+                ctx.program_id,
+            )?
+            .clone(),
+        )
+    } else if let Some(platform_fee_account) = route_validator_module::charged_fee_account(
+        platform_fee_bps,
+        ctx.accounts.platform_fee_account.as_ref(),
+    )? {
+        route_validator_module::validate_platform_fee_vault(
+            platform_fee_account,
+            &fee_mint,
+            &ctx.accounts.vault_authority.key(),
+        )?;
+        Some(platform_fee_account.to_account_info())
+    } else {
+        None
+    };
+    // The fee account requirement follows the requested fee; only the charged rate is rebated
+    let platform_fee_bps = volume_tier_fee_bps(&protocol_config, trader_volume_of(&ctx.accounts.trader_volume), platform_fee_bps)?;
+
+    #[cfg(feature = "invariant-checks")]
+    let mut conservation = {
+        let mut tracked = vec![
+            ctx.accounts.user_source_token_account.to_account_info(),
+            ctx.accounts.user_destination_token_account.to_account_info(),
+            ctx.accounts.vault_source.to_account_info(),
+            ctx.accounts.vault_destination.to_account_info(),
+        ];
+        if let Some(platform_fee_account) = &platform_fee_account {
+            tracked.push(platform_fee_account.clone());
+        }
+        invariants::ConservationCheck::start(tracked)
+    };
 
-    if let Some(platform_fee_account) = &ctx.accounts.platform_fee_account {
-        require!(
-            platform_fee_account.owner == ctx.accounts.vault_authority.key(),
-            ErrorCode::InvalidPlatformFeeOwner
-        );
-        require!(
-            platform_fee_account.mint == ctx.accounts.destination_mint.key(),
-            ErrorCode::InvalidPlatformFeeMint
-        );
-    }
+    require_source_allowance(&ctx.accounts.user_source_token_account, &ctx.accounts.user_transfer_authority.key(), in_amount)?;
 
     // Transfer tokens from user to vault_source
     transfer_checked(
@@ -179,30 +270,64 @@ pub fn shared_route<'info>(
         ctx.accounts.source_mint.decimals,
     )?;
 
-    ctx.accounts.vault_destination.reload()?;
-    let dest_balance_before = ctx.accounts.vault_destination.amount;
-
     let vault_authority_bump = ctx.bumps.vault_authority;
     let authority_seeds: &[&[u8]] = &[b"vault_authority".as_ref(), &[vault_authority_bump]];
     let signer_seeds: &[&[&[u8]]] = &[authority_seeds];
 
+    let mut fee_amount = 0u64;
+    let mut fee_account: Option<Pubkey> = None;
+
+    // Fee on input: skim the source mint fee from vault_source before Jupiter pulls the rest
+    if fee_on_input {
+        let mut swap_in_amount = in_amount;
+        if let Some(platform_fee_account) = &platform_fee_account {
+            fee_amount = math::apply_bps_fee(in_amount, platform_fee_bps.into())?;
+            if fee_amount > 0 {
+                transfer_checked(
+                    CpiContext::new_with_signer(
+                        ctx.accounts.input_token_program.to_account_info(),
+                        TransferChecked {
+                            from: ctx.accounts.vault_source.to_account_info(),
+                            to: platform_fee_account.clone(),
+                            authority: ctx.accounts.vault_authority.to_account_info(),
+                            mint: ctx.accounts.source_mint.to_account_info(),
+                        },
+                        signer_seeds,
+                    ),
+                    fee_amount,
+                    ctx.accounts.source_mint.decimals,
+                )?;
+                emit_cpi!(FeeEvent {
+                    account: platform_fee_account.key(),
+                    mint: ctx.accounts.source_mint.key(),
+                    amount: fee_amount,
+                    net_amount: route_validator_module::net_transfer_amount(&ctx.accounts.source_mint.to_account_info(), fee_amount)?,
+                    fee_bps: platform_fee_bps,
+                    source: FeeSource::SharedRoute,
+                });
+                fee_account = Some(platform_fee_account.key());
+                swap_in_amount = in_amount
+                    .checked_sub(fee_amount)
+                    .ok_or(ErrorCode::InvalidCalculation)?;
+            }
+        }
+
+        // Jupiter must swap exactly what is left, not the user's gross deposit
+        require!(
+            jupiter_route_in_amount(&data)? == swap_in_amount,
+            ErrorCode::JupiterInAmountMismatch
+        );
+    }
+
+    // Snapshot right before the CPI: anything deposited into the shared vault earlier, including by
+    // a previous instruction of the same transaction, is not counted as output. Jupiter cannot
+    // re-enter this program during the CPI, so only its own transfers move the balance.
+    ctx.accounts.vault_destination.reload()?;
+    let dest_balance_before = ctx.accounts.vault_destination.amount;
+
     // Build account metas from remaining_accounts
-    // user_transfer_authority (index 2) must be signer (PDA via invoke_signed)
     // Also preserve is_signer flag for accounts that were signers in original tx (e.g., mock_pool_authority)
-    let accounts: Vec<anchor_lang::solana_program::instruction::AccountMeta> = ctx
-        .remaining_accounts
-        .iter()
-        .enumerate()
-        .map(|(idx, acc)| {
-            // Index 2 will be signed by PDA, OR keep original signer status
-            let is_signer = idx == JUPITER_USER_TRANSFER_AUTHORITY_INDEX || acc.is_signer;
-            anchor_lang::solana_program::instruction::AccountMeta {
-                pubkey: *acc.key,
-                is_signer,
-                is_writable: acc.is_writable,
-            }
-        })
-        .collect();
+    let accounts = jupiter_cpi::build_account_metas(ctx.remaining_accounts, true);
 
     let account_infos: Vec<AccountInfo> = ctx
         .remaining_accounts
@@ -215,6 +340,9 @@ pub fn shared_route<'info>(
     // Our `data` param now contains properly formatted instruction data from client
     let jupiter_instruction_data = data;
 
+    #[cfg(feature = "invariant-checks")]
+    conservation.begin_swap();
+
     invoke_signed(
         &Instruction {
             program_id: ctx.accounts.jupiter_program.key(),
@@ -225,6 +353,9 @@ pub fn shared_route<'info>(
         signer_seeds,
     )?;
 
+    #[cfg(feature = "invariant-checks")]
+    conservation.end_swap();
+
     ctx.accounts.vault_destination.reload()?;
     let dest_balance_after = ctx.accounts.vault_destination.amount;
     let mut output_amount = dest_balance_after
@@ -233,18 +364,15 @@ pub fn shared_route<'info>(
 
     msg!("Jupiter swap completed. Output amount: {}", output_amount);
 
-    let mut fee_amount = 0u64;
-    let mut fee_account: Option<Pubkey> = None;
-
-    if let Some(platform_fee_account) = &ctx.accounts.platform_fee_account {
-        fee_amount = (output_amount as u128 * platform_fee_bps as u128 / 10_000) as u64;
+    if let Some(platform_fee_account) = platform_fee_account.as_ref().filter(|_| !fee_on_input) {
+        fee_amount = math::apply_bps_fee(output_amount, platform_fee_bps.into())?;
         if fee_amount > 0 {
             transfer_checked(
                 CpiContext::new_with_signer(
                     ctx.accounts.output_token_program.to_account_info(),
                     TransferChecked {
                         from: ctx.accounts.vault_destination.to_account_info(),
-                        to: platform_fee_account.to_account_info(),
+                        to: platform_fee_account.clone(),
                         authority: ctx.accounts.vault_authority.to_account_info(),
                         mint: ctx.accounts.destination_mint.to_account_info(),
                     },
@@ -257,6 +385,9 @@ pub fn shared_route<'info>(
                 account: platform_fee_account.key(),
                 mint: ctx.accounts.destination_mint.key(),
                 amount: fee_amount,
+                net_amount: route_validator_module::net_transfer_amount(&ctx.accounts.destination_mint.to_account_info(), fee_amount)?,
+                fee_bps: platform_fee_bps,
+                source: FeeSource::SharedRoute,
             });
             fee_account = Some(platform_fee_account.key());
             output_amount = output_amount
@@ -265,19 +396,17 @@ pub fn shared_route<'info>(
         }
     }
 
-    let min_out_amount = (quoted_out_amount as u128)
-        .checked_mul(
-            (10_000u128)
-                .checked_sub(slippage_bps as u128)
-                .ok_or(ErrorCode::InvalidCalculation)?
-        )
-        .ok_or(ErrorCode::InvalidCalculation)?
-        .checked_div(10_000)
-        .ok_or(ErrorCode::InvalidCalculation)? as u64;
-    require!(
-        output_amount >= min_out_amount,
-        ErrorCode::SlippageToleranceExceeded
-    );
+    // Compare what actually lands in the user account (net of any Token-2022 transfer fee)
+    let net_output_amount = route_validator_module::net_transfer_amount(
+        &ctx.accounts.destination_mint.to_account_info(),
+        output_amount,
+    )?;
+    route_validator_module::validate_output_floors(
+        net_output_amount,
+        quoted_out_amount,
+        slippage_bps,
+        min_out_amount,
+    )?;
 
     transfer_checked(
         CpiContext::new_with_signer(
@@ -294,6 +423,15 @@ pub fn shared_route<'info>(
         ctx.accounts.destination_mint.decimals,
     )?;
 
+    #[cfg(feature = "invariant-checks")]
+    {
+        let (input_fee, output_fee) = if fee_on_input { (fee_amount, 0) } else { (0, fee_amount) };
+        conservation.finish(&[
+            invariants::transfer_fee_allowance(&ctx.accounts.source_mint.to_account_info(), &[in_amount, input_fee])?,
+            invariants::transfer_fee_allowance(&ctx.accounts.destination_mint.to_account_info(), &[output_fee, output_amount])?,
+        ])?;
+    }
+
     emit_cpi!(RouterSwapEvent {
         sender: ctx.accounts.user_transfer_authority.key(),
         recipient: ctx.accounts.user_destination_token_account.key(),
@@ -304,9 +442,24 @@ pub fn shared_route<'info>(
         fee_amount,
         fee_account,
         slippage_bps,
+        net_output_amount,
+        accept_high_impact: false,
+        fee_mint: fee_account.map(|_| fee_mint),
+        ui_amount_multiplier: route_validator_module::ui_amount_multiplier(&ctx.accounts.destination_mint.to_account_info())?,
     });
 
+    // Count the swap towards the trader's volume tier
+    let volume = protocol_config.route_volume(&ctx.accounts.source_mint.key(), &ctx.accounts.destination_mint.key(), in_amount, output_amount);
+    let trader = ctx.accounts.user_transfer_authority.key();
+    if let Some(trader_volume) = ctx.accounts.trader_volume.as_deref_mut() {
+        record_trader_volume(trader_volume, trader, ctx.bumps.trader_volume, volume)?;
+    }
+
     msg!("Shared route completed. Final output: {}", output_amount);
+
+    #[cfg(feature = "telemetry")]
+    emit_cpi!(compute_meter.finish(jupiter_steps));
+
     Ok(output_amount)
 }
 
@@ -323,6 +476,13 @@ pub struct SharedRouteAndCreateOrder<'info> {
     )]
     pub vault_authority: Account<'info, VaultAuthority>,
 
+    /// Adapter registry holding the order creation fee
+    #[account(
+        seeds = [b"adapter_registry"],
+        bump
+    )]
+    pub adapter_registry: Box<Account<'info, AdapterRegistry>>,
+
     /// Limit order account (must be initialized separately using init_limit_order)
     #[account(
         mut,
@@ -369,15 +529,21 @@ pub struct SharedRouteAndCreateOrder<'info> {
     pub swap_destination_vault: Box<InterfaceAccount<'info, TokenAccount>>,
 
     /// Swap input token mint
+    #[account(
+        constraint = swap_input_mint.to_account_info().owner == &input_token_program.key() @ ErrorCode::InvalidTokenProgram
+    )]
     pub swap_input_mint: Box<InterfaceAccount<'info, Mint>>,
 
     /// Swap output token mint (becomes order input mint)
+    #[account(
+        constraint = swap_output_mint.to_account_info().owner == &output_token_program.key() @ ErrorCode::InvalidTokenProgram
+    )]
     pub swap_output_mint: Box<InterfaceAccount<'info, Mint>>,
 
-    /// Token program for swap input tokens
+    /// Token program for swap input tokens (snapshotted as the order's output token program)
     pub input_token_program: Interface<'info, TokenInterface>,
 
-    /// Token program for swap/order output tokens
+    /// Token program for swap/order output tokens (snapshotted as the order's input token program)
     pub output_token_program: Interface<'info, TokenInterface>,
 
     /// Optional platform fee collection account
@@ -388,6 +554,15 @@ pub struct SharedRouteAndCreateOrder<'info> {
     /// CHECK: Jupiter program ID will be validated in the CPI call
     pub jupiter_program: AccountInfo<'info>,
 
+    /// Treasury receiving the order creation fee (required when the fee is non-zero)
+    /// CHECK: Must match adapter_registry.treasury, checked in collect_order_creation_fee
+    #[account(mut)]
+    pub treasury: Option<UncheckedAccount<'info>>,
+
+    /// CHECK: ProtocolConfig PDA; default limits apply until create_protocol_config has created it
+    #[account(seeds = [b"protocol_config"], bump)]
+    pub protocol_config: UncheckedAccount<'info>,
+
     /// Order creator (must sign)
     #[account(mut, signer)]
     pub creator: Signer<'info>,
@@ -414,25 +589,25 @@ pub fn shared_route_and_create_order<'info>(
 ) -> Result<(u64, Pubkey)> {
     require!(swap_in_amount > 0, ErrorCode::InvalidAmount);
     require!(swap_quoted_out_amount > 0, ErrorCode::InvalidAmount);
-    require!(swap_slippage_bps <= 10_000, ErrorCode::InvalidSlippage);
-    require!(!data.is_empty(), ErrorCode::EmptyRoute);
-
+    let protocol_config = ProtocolConfig::load_config(&ctx.accounts.protocol_config, ctx.program_id)?;
+    protocol_config.require_creation_open()?;
+    let limits = protocol_config.limits;
+    limits.check_route_slippage(swap_slippage_bps)?;
     // Validate Jupiter program matches the one stored in vault_authority
     require!(
         ctx.accounts.jupiter_program.key() == ctx.accounts.vault_authority.jupiter_program_id,
         ErrorCode::InvalidJupiterProgram
     );
+    jupiter_cpi::validate_jupiter_data(&data, ctx.accounts.vault_authority.jupiter_data_limit())?;
 
     require!(order_min_output_amount > 0, ErrorCode::InvalidAmount);
-    require!(
-        order_trigger_price_bps > 0 && order_trigger_price_bps <= 100_000,
-        ErrorCode::InvalidTriggerPrice
-    );
-    require!(
-        order_expiry > Clock::get()?.unix_timestamp,
-        ErrorCode::InvalidExpiry
-    );
-    require!(order_slippage_bps <= 10_000, ErrorCode::InvalidSlippage);
+    // The order sells the swap output back into the swap input
+    require_distinct_order_mints(&ctx.accounts.swap_output_mint.key(), &ctx.accounts.swap_input_mint.key())?;
+    validate_trigger_price(&limits, TriggerType::TakeProfit, order_trigger_price_bps)?;
+    let now = current_unix_timestamp(ctx.remaining_accounts)?;
+    require!(order_expiry > now, ErrorCode::InvalidExpiry);
+    limits.check_order_duration(order_expiry, now)?;
+    limits.check_order_slippage(order_slippage_bps)?;
     require!(
         ctx.accounts.limit_order.creator == ctx.accounts.creator.key(),
         ErrorCode::UnauthorizedAdmin
@@ -441,27 +616,19 @@ pub fn shared_route_and_create_order<'info>(
         ctx.accounts.swap_destination_vault.key() == ctx.accounts.limit_order.input_vault,
         ErrorCode::InvalidVaultAddress
     );
-    require!(
-        ctx.remaining_accounts.len() >= JUPITER_MIN_ACCOUNTS,
-        ErrorCode::NotEnoughJupiterAccounts
-    );
-    // Validate that user_transfer_authority (index 2) is our vault_authority
-    require!(
-        ctx.remaining_accounts[JUPITER_USER_TRANSFER_AUTHORITY_INDEX].key() == ctx.accounts.vault_authority.key(),
-        ErrorCode::JupiterProgramAuthorityMismatch
-    );
-    // Validate that user_source (index 3) is our swap_source_vault
-    require!(
-        ctx.remaining_accounts[JUPITER_USER_SOURCE_INDEX].key() == ctx.accounts.swap_source_vault.key(),
-        ErrorCode::JupiterProgramSourceMismatch
-    );
-    // Validate that user_destination (index 6) is our swap_destination_vault
-    require!(
-        ctx.remaining_accounts[JUPITER_USER_DESTINATION_INDEX].key() == ctx.accounts.swap_destination_vault.key(),
-        ErrorCode::JupiterProgramDestinationMismatch
-    );
+    // Jupiter's user_transfer_authority, user_source and user_destination must be our accounts
+    jupiter_cpi::validate_fixed_positions(
+        ctx.remaining_accounts,
+        &ctx.accounts.vault_authority.key(),
+        &ctx.accounts.swap_source_vault.key(),
+        &ctx.accounts.swap_destination_vault.key(),
+    )?;
 
-    if let Some(platform_fee_account) = &ctx.accounts.platform_fee_account {
+    let platform_fee_account = route_validator_module::charged_fee_account(
+        platform_fee_bps,
+        ctx.accounts.platform_fee_account.as_ref(),
+    )?;
+    if let Some(platform_fee_account) = platform_fee_account {
         require!(
             platform_fee_account.owner == ctx.accounts.vault_authority.key(),
             ErrorCode::InvalidPlatformFeeOwner
@@ -470,8 +637,19 @@ pub fn shared_route_and_create_order<'info>(
             platform_fee_account.mint == ctx.accounts.swap_output_mint.key(),
             ErrorCode::InvalidPlatformFeeMint
         );
+        route_validator_module::validate_fee_account_authorities(
+            platform_fee_account,
+            &ctx.accounts.vault_authority.key(),
+        )?;
     }
 
+    collect_order_creation_fee(
+        &ctx.accounts.adapter_registry,
+        &ctx.accounts.creator.to_account_info(),
+        ctx.accounts.treasury.as_deref(),
+        &ctx.accounts.system_program.to_account_info(),
+    )?;
+
     transfer_checked(
         CpiContext::new(
             ctx.accounts.input_token_program.to_account_info(),
@@ -494,22 +672,8 @@ pub fn shared_route_and_create_order<'info>(
     let signer_seeds: &[&[&[u8]]] = &[authority_seeds];
 
     // Build account metas from remaining_accounts
-    // user_transfer_authority (index 2) must be signer (PDA via invoke_signed)
     // Also preserve is_signer flag for accounts that were signers in original tx
-    let accounts: Vec<anchor_lang::solana_program::instruction::AccountMeta> = ctx
-        .remaining_accounts
-        .iter()
-        .enumerate()
-        .map(|(idx, acc)| {
-            // Index 2 will be signed by PDA, OR keep original signer status
-            let is_signer = idx == JUPITER_USER_TRANSFER_AUTHORITY_INDEX || acc.is_signer;
-            anchor_lang::solana_program::instruction::AccountMeta {
-                pubkey: *acc.key,
-                is_signer,
-                is_writable: acc.is_writable,
-            }
-        })
-        .collect();
+    let accounts = jupiter_cpi::build_account_metas(ctx.remaining_accounts, true);
 
     let account_infos: Vec<AccountInfo> = ctx.remaining_accounts.iter().map(|acc| acc.clone()).collect();
 
@@ -533,8 +697,8 @@ pub fn shared_route_and_create_order<'info>(
 
     let mut fee_amount = 0u64;
     let mut fee_account: Option<Pubkey> = None;
-    if let Some(platform_fee_account) = &ctx.accounts.platform_fee_account {
-        fee_amount = (swap_output_amount as u128 * platform_fee_bps as u128 / 10_000) as u64;
+    if let Some(platform_fee_account) = platform_fee_account {
+        fee_amount = math::apply_bps_fee(swap_output_amount, platform_fee_bps.into())?;
         if fee_amount > 0 {
             transfer_checked(
                 CpiContext::new_with_signer(
@@ -554,6 +718,9 @@ pub fn shared_route_and_create_order<'info>(
                 account: platform_fee_account.key(),
                 mint: ctx.accounts.swap_output_mint.key(),
                 amount: fee_amount,
+                net_amount: route_validator_module::net_transfer_amount(&ctx.accounts.swap_output_mint.to_account_info(), fee_amount)?,
+                fee_bps: platform_fee_bps,
+                source: FeeSource::RouteAndCreateOrder,
             });
             fee_account = Some(platform_fee_account.key());
             swap_output_amount = swap_output_amount
@@ -562,15 +729,7 @@ pub fn shared_route_and_create_order<'info>(
         }
     }
 
-    let min_swap_out_amount = (swap_quoted_out_amount as u128)
-        .checked_mul(
-            (10_000u128)
-                .checked_sub(swap_slippage_bps as u128)
-                .ok_or(ErrorCode::InvalidCalculation)?
-        )
-        .ok_or(ErrorCode::InvalidCalculation)?
-        .checked_div(10_000)
-        .ok_or(ErrorCode::InvalidCalculation)? as u64;
+    let min_swap_out_amount = math::apply_bps_discount(swap_quoted_out_amount, swap_slippage_bps)?;
     require!(
         swap_output_amount >= min_swap_out_amount,
         ErrorCode::SlippageToleranceExceeded
@@ -586,6 +745,10 @@ pub fn shared_route_and_create_order<'info>(
         fee_amount,
         fee_account,
         slippage_bps: swap_slippage_bps,
+        net_output_amount: swap_output_amount, // Output stays in the order vault, no transfer
+        accept_high_impact: false,
+        fee_mint: fee_account.map(|_| ctx.accounts.swap_output_mint.key()),
+        ui_amount_multiplier: route_validator_module::ui_amount_multiplier(&ctx.accounts.swap_output_mint.to_account_info())?,
     });
 
     let order = &mut ctx.accounts.limit_order;
@@ -599,6 +762,11 @@ pub fn shared_route_and_create_order<'info>(
     order.expiry = order_expiry;
     order.status = OrderStatus::Open;
     order.slippage_bps = order_slippage_bps;
+    order.failed_attempts = 0;
+    order.activate_at = 0;
+    order.execution_fee_bps = None;
+    order.input_token_program = ctx.accounts.output_token_program.key();
+    order.output_token_program = ctx.accounts.input_token_program.key();
 
     let order_key = order.key();
 
@@ -612,7 +780,12 @@ pub fn shared_route_and_create_order<'info>(
         trigger_price_bps: order.trigger_price_bps,
         trigger_type: order.trigger_type as u8,
         expiry: order.expiry,
+        activate_at: order.activate_at,
+        generation: order.generation,
+        ui_amount_multiplier: route_validator_module::ui_amount_multiplier(&ctx.accounts.swap_input_mint.to_account_info())?,
+        replaces: None,
     });
+    emit_cpi!(order.open_interest_change(true));
 
     emit_cpi!(RouteAndCreateOrderEvent {
         order: order_key,
@@ -0,0 +1,39 @@
+#[cfg(test)]
+mod tests {
+    use super::super::*;
+
+    // shared_accounts_route data as the Jupiter API builds it: one opaque route plan step
+    fn jupiter_route_data(in_amount: u64, quoted_out_amount: u64) -> Vec<u8> {
+        let mut data = JUPITER_SHARED_ACCOUNTS_ROUTE_DISCRIMINATOR.to_vec();
+        data.push(0); // id
+        data.extend_from_slice(&1u32.to_le_bytes()); // route_plan length
+        data.extend_from_slice(&[7, 1, 100, 0, 1]); // Swap variant, percent, input_index, output_index
+        data.extend_from_slice(&in_amount.to_le_bytes());
+        data.extend_from_slice(&quoted_out_amount.to_le_bytes());
+        data.extend_from_slice(&50u16.to_le_bytes()); // slippage_bps
+        data.push(0); // platform_fee_bps
+        data
+    }
+
+    #[test]
+    fn test_jupiter_route_in_amount() {
+        let data = jupiter_route_data(995_000, 1_492_500);
+        assert_eq!(jupiter_route_in_amount(&data).unwrap(), 995_000);
+
+        let data = jupiter_route_data(u64::MAX, 1);
+        assert_eq!(jupiter_route_in_amount(&data).unwrap(), u64::MAX);
+    }
+
+    #[test]
+    fn test_jupiter_route_in_amount_rejects_other_instructions() {
+        let mut data = jupiter_route_data(995_000, 1_492_500);
+        data[0] ^= 1;
+        let err = jupiter_route_in_amount(&data).unwrap_err();
+        assert_eq!(err, ErrorCode::JupiterInAmountMismatch.into());
+
+        // Too short to hold the id, route plan length and trailing args
+        let data = jupiter_route_data(995_000, 1_492_500);
+        let err = jupiter_route_in_amount(&data[..8 + 1 + 4 + 18]).unwrap_err();
+        assert_eq!(err, ErrorCode::JupiterInAmountMismatch.into());
+    }
+}
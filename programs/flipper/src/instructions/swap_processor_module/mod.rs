@@ -1,19 +1,53 @@
 use anchor_lang::prelude::*;
 use anchor_spl::token_interface::{
     Mint, TokenAccount, TokenInterface,
-    transfer_checked, TransferChecked
+    sync_native, transfer_checked, SyncNative, TransferChecked
 };
-use anchor_lang::solana_program::{program::invoke_signed, system_instruction};
+use anchor_lang::solana_program::{hash::hashv, program::invoke_signed, program_option::COption, system_instruction};
 use crate::adapters::adapter_connector_module::AdapterContext;
+use crate::adapters::wsol;
 use crate::errors::ErrorCode;
+#[cfg(feature = "invariant-checks")]
+use crate::invariants;
+#[cfg(feature = "telemetry")]
+use crate::telemetry;
+use crate::math;
+use crate::sdk::{
+    METEORA_SWAP_TAG, RAYDIUM_SWAP_TAG, SUPPORTED_SWAP_TAGS, UNWRAP_SOL_SWAP_TAG, WHIRLPOOL_SWAP_TAG,
+    WHIRLPOOL_TWO_HOP_SWAP_TAG, WRAP_SOL_SWAP_TAG,
+};
 use crate::state::*;
 use crate::instructions::route_validator_module;
 use crate::instructions::route_executor_module;
 use crate::instructions::vault_manager_module::{VaultAuthority};
 
+// Test modules
+#[cfg(test)]
+mod swap_processor_test;
+
+/// Maximum number of alternative plans accepted by route_with_fallback
+pub const MAX_FALLBACK_PLANS: usize = 2;
+
+/// `in_amount` sentinel telling route to swap the entire source balance at execution time
+pub const SWEEP_SOURCE_BALANCE: u64 = u64::MAX;
+
+/// Bytes of a route_compact step that swaps its whole input: header, input_index, output_index.
+/// A split step carries its percent right after the header, one byte more.
+pub const COMPACT_STEP_LEN: usize = 3;
+
+/// route_compact header bit marking a split step, whose percent byte follows the header
+pub const COMPACT_SPLIT_FLAG: u8 = 0x20;
+
+/// Amounts of an executed route, written to return data by route
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Debug, PartialEq, Eq)]
+pub struct RouteAmounts {
+    pub in_amount: u64,
+    pub out_amount: u64,
+}
+
+// Shared by route and route_with_fallback, so it does not bind instruction args
 #[event_cpi]
 #[derive(Accounts)]
-#[instruction(route_plan: Vec<RoutePlanStep>)]
 pub struct Route<'info> {
     #[account(
         seeds = [b"adapter_registry"],
@@ -30,13 +64,13 @@ pub struct Route<'info> {
     pub input_token_program: Interface<'info, TokenInterface>,
     pub output_token_program: Interface<'info, TokenInterface>,
 
-    #[account(signer)]
+    #[account(mut, signer)]
     pub user_transfer_authority: Signer<'info>,
 
     #[account(
         mut,
         constraint = user_source_token_account.mint == source_mint.key(),
-        constraint = user_source_token_account.owner == user_transfer_authority.key()
+        constraint = can_spend_source(&user_source_token_account, &user_transfer_authority.key())
     )]
     pub user_source_token_account: Box<InterfaceAccount<'info, TokenAccount>>,
 
@@ -53,9 +87,51 @@ pub struct Route<'info> {
     #[account(mut)]
     pub platform_fee_account: Option<Box<InterfaceAccount<'info, TokenAccount>>>,
 
+    /// CHECK: FeeVaultRegistry PDA, read by route when use_registered_fee_vault is set
+    #[account(seeds = [b"fee_vault_registry"], bump)]
+    pub fee_vault_registry: UncheckedAccount<'info>,
+
+    /// CHECK: ProtocolConfig PDA; default limits apply until create_protocol_config has created it
+    #[account(seeds = [b"protocol_config"], bump)]
+    pub protocol_config: UncheckedAccount<'info>,
+
+    /// The trader's volume record, created on first use; without it the route earns no volume
+    /// tier rebate and its volume is not counted
+    #[account(
+        init_if_needed,
+        payer = user_transfer_authority,
+        space = TraderVolume::SPACE,
+        seeds = [b"trader_volume", user_transfer_authority.key().as_ref()],
+        bump
+    )]
+    pub trader_volume: Option<Box<Account<'info, TraderVolume>>>,
+
+    /// VaultMeta of a route vault the client cached, checked against expected_vault_generation by
+    /// route and route_compact; see check_route_vault_generation
+    pub vault_meta: Option<Box<Account<'info, VaultMeta>>>,
+
     pub system_program: Program<'info, System>
 }
 
+/// Executes a route plan through the DEX adapters
+///
+/// # Arguments
+/// * `route_plan` - Swap route to execute
+/// * `in_amount` - Amount of input tokens to swap, or SWEEP_SOURCE_BALANCE for the whole source balance
+/// * `quoted_out_amount` - Quoted output, used for the slippage check
+/// * `slippage_bps` - Slippage tolerance in basis points
+/// * `platform_fee_bps` - Platform fee in basis points, lowered by the trader's volume tier
+///   when trader_volume is passed
+/// * `min_out_amount` - Caller's own minimum net output after fees (0 = none)
+/// * `accept_high_impact` - Lets steps exceed their adapter's price impact cap
+/// * `emit_step_events` - Per-step SwapEvents for a single-hop route (None = registry setting)
+/// * `use_registered_fee_vault` - Collect the fee into the destination mint's registered fee vault,
+///   passed in remaining_accounts, instead of platform_fee_account
+/// * `expected_vault_generation` - Generation the client cached for the vault of vault_meta (None = unchecked)
+///
+/// # Returns
+/// * `Result<RouteAmounts>` - Resolved input amount and final output amount after fees
+#[allow(clippy::too_many_arguments)]
 pub fn route<'info>(
     ctx: Context<'_, '_, 'info, 'info, Route<'info>>,
     route_plan: Vec<RoutePlanStep>,
@@ -63,22 +139,465 @@ pub fn route<'info>(
     quoted_out_amount: u64,
     slippage_bps: u16,
     platform_fee_bps: u8,
+    min_out_amount: u64,
+    accept_high_impact: bool,
+    emit_step_events: Option<bool>,
+    use_registered_fee_vault: bool,
+    expected_vault_generation: Option<u32>,
+) -> Result<RouteAmounts> {
+    #[cfg(feature = "telemetry")]
+    let compute_meter = telemetry::ComputeMeter::start(telemetry::ROUTE_TELEMETRY_TAG);
+
+    let protocol_config = ProtocolConfig::load_config(&ctx.accounts.protocol_config, ctx.program_id)?;
+    protocol_config.limits.check_route_slippage(slippage_bps)?;
+    check_route_vault_generation(
+        ctx.accounts.vault_meta.as_deref().map(|vault_meta| &**vault_meta),
+        expected_vault_generation,
+        ctx.remaining_accounts,
+    )?;
+
+    // Read the balance at execution so transfers landing earlier in the transaction are swept too
+    let in_amount = resolve_in_amount(
+        in_amount,
+        spendable_source_amount(&ctx.accounts.user_source_token_account, &ctx.accounts.user_transfer_authority.key()),
+    )?;
+
+    let platform_fee_account = resolve_platform_fee_account(&ctx, platform_fee_bps, use_registered_fee_vault)?;
+
+    // Validate route and accounts
+    validate_route_plan(&ctx, &route_plan, in_amount)?;
+
+    let fee_bps = volume_tier_fee_bps(&protocol_config, trader_volume_of(&ctx.accounts.trader_volume), platform_fee_bps)?;
+    let out_amount = execute_validated_route(&ctx, platform_fee_account.as_ref(), &route_plan, in_amount, quoted_out_amount, slippage_bps, fee_bps, min_out_amount, accept_high_impact, emit_step_events)?;
+    record_route_volume(&mut *ctx.accounts, ctx.bumps.trader_volume, &protocol_config, in_amount, out_amount)?;
+
+    #[cfg(feature = "telemetry")]
+    emit_cpi!(compute_meter.finish(route_plan.len()));
+
+    Ok(RouteAmounts { in_amount, out_amount })
+}
+
+/// Executes a route plan given in the packed route_compact encoding
+///
+/// The plan is decoded into RoutePlanStep records up front, then handled exactly like `route`.
+///
+/// # Arguments
+/// * `plan_bytes` - Packed steps, see decode_compact_route_plan
+/// * remaining arguments as for `route`
+///
+/// # Returns
+/// * `Result<RouteAmounts>` - Resolved input amount and final output amount after fees
+#[allow(clippy::too_many_arguments)]
+pub fn route_compact<'info>(
+    ctx: Context<'_, '_, 'info, 'info, Route<'info>>,
+    plan_bytes: Vec<u8>,
+    in_amount: u64,
+    quoted_out_amount: u64,
+    slippage_bps: u16,
+    platform_fee_bps: u8,
+    min_out_amount: u64,
+    accept_high_impact: bool,
+    emit_step_events: Option<bool>,
+    use_registered_fee_vault: bool,
+    expected_vault_generation: Option<u32>,
+) -> Result<RouteAmounts> {
+    let route_plan = decode_compact_route_plan(&plan_bytes)?;
+    route(ctx, route_plan, in_amount, quoted_out_amount, slippage_bps, platform_fee_bps, min_out_amount, accept_high_impact, emit_step_events, use_registered_fee_vault, expected_vault_generation)
+}
+
+/// Maps a compact step header (without COMPACT_SPLIT_FLAG) to its Swap. Bits 0-2 index
+/// sdk::SUPPORTED_SWAP_TAGS, bits 3-4 hold the variant's direction flags as in byte 1 of
+/// Swap::to_bytes, and any higher bit is rejected.
+pub fn decode_compact_swap(header: u8) -> Result<Swap> {
+    let swap_tag = *SUPPORTED_SWAP_TAGS
+        .get(usize::from(header & 0x07))
+        .ok_or(ErrorCode::UnknownCompactSwapTag)?;
+    let flag = header >> 3;
+    match (swap_tag, flag) {
+        (RAYDIUM_SWAP_TAG, 0) => Ok(Swap::Raydium),
+        (WHIRLPOOL_SWAP_TAG, 0 | 1) => Ok(Swap::Whirlpool { a_to_b: flag == 1 }),
+        (METEORA_SWAP_TAG, 0) => Ok(Swap::Meteora),
+        (WRAP_SOL_SWAP_TAG, 0) => Ok(Swap::WrapSol),
+        (UNWRAP_SOL_SWAP_TAG, 0) => Ok(Swap::UnwrapSol),
+        (WHIRLPOOL_TWO_HOP_SWAP_TAG, 0..=3) => Ok(Swap::WhirlpoolTwoHop {
+            a_to_b_one: flag & 1 == 1,
+            a_to_b_two: flag & 2 == 2,
+        }),
+        _ => Err(ErrorCode::UnknownCompactSwapTag.into()),
+    }
+}
+
+/// Decodes a route_compact plan into route plan steps
+///
+/// Each step is a header byte (see decode_compact_swap, plus COMPACT_SPLIT_FLAG), the percent
+/// for split steps only (others swap 100%), then input_index and output_index. That is 3 bytes
+/// for a whole-input step and 4 for a split one, below the 4-6 bytes of a Borsh RoutePlanStep.
+///
+/// # Returns
+/// * `Result<Vec<RoutePlanStep>>` - EmptyRoute, InvalidCompactRoutePlan or UnknownCompactSwapTag on bad input
+pub fn decode_compact_route_plan(plan_bytes: &[u8]) -> Result<Vec<RoutePlanStep>> {
+    if plan_bytes.is_empty() {
+        return Err(ErrorCode::EmptyRoute.into());
+    }
+
+    let mut route_plan = Vec::with_capacity(plan_bytes.len() / COMPACT_STEP_LEN);
+    let mut rest = plan_bytes;
+    while let Some((&header, tail)) = rest.split_first() {
+        let swap = decode_compact_swap(header & !COMPACT_SPLIT_FLAG)?;
+        let (percent, tail) = if header & COMPACT_SPLIT_FLAG != 0 {
+            let (&percent, tail) = tail.split_first().ok_or(ErrorCode::InvalidCompactRoutePlan)?;
+            (percent, tail)
+        } else {
+            (100, tail)
+        };
+        let [input_index, output_index, tail @ ..] = tail else {
+            return Err(ErrorCode::InvalidCompactRoutePlan.into());
+        };
+
+        route_plan.push(RoutePlanStep { swap, percent, input_index: *input_index, output_index: *output_index });
+        rest = tail;
+    }
+    Ok(route_plan)
+}
+
+/// Fails with VaultGenerationMismatch unless `vault_meta` is at `expected_vault_generation`, so a
+/// client that cached a vault since closed (and maybe re-created empty) aborts before routing
+/// through it. The VaultMeta must belong to one of the route's accounts. Without an expected
+/// generation nothing is checked.
+pub fn check_route_vault_generation(
+    vault_meta: Option<&VaultMeta>,
+    expected_vault_generation: Option<u32>,
+    remaining_accounts: &[AccountInfo],
+) -> Result<()> {
+    let Some(expected_generation) = expected_vault_generation else {
+        return Ok(());
+    };
+    let vault_meta = vault_meta.ok_or(ErrorCode::VaultMetaNotProvided)?;
+    if !remaining_accounts.iter().any(|account| account.key() == vault_meta.vault) {
+        return Err(ErrorCode::InvalidVaultAddress.into());
+    }
+    if vault_meta.vault_generation != expected_generation {
+        msg!("Vault {} is at generation {}, expected {}", vault_meta.vault, vault_meta.vault_generation, expected_generation);
+        return Err(ErrorCode::VaultGenerationMismatch.into());
+    }
+    Ok(())
+}
+
+/// Resolves the SWEEP_SOURCE_BALANCE sentinel to the current source balance, which must be non-zero.
+/// Any other amount is used as is.
+pub fn resolve_in_amount(in_amount: u64, source_balance: u64) -> Result<u64> {
+    if in_amount != SWEEP_SOURCE_BALANCE {
+        return Ok(in_amount);
+    }
+    if source_balance == 0 {
+        return Err(ErrorCode::InvalidAmount.into());
+    }
+    Ok(source_balance)
+}
+
+/// Whether `authority` may move tokens out of `source`: as its owner, or as its delegate
+/// (permit-style trading by custodial integrations that hold a delegation, not the account)
+pub fn can_spend_source(source: &TokenAccount, authority: &Pubkey) -> bool {
+    source.owner == *authority || source.delegate == COption::Some(*authority)
+}
+
+/// Tokens `authority` can move out of `source`: the whole balance as owner, the remaining
+/// delegation (capped by the balance) as delegate. Sweeps resolve to this amount.
+pub fn spendable_source_amount(source: &TokenAccount, authority: &Pubkey) -> u64 {
+    if source.owner == *authority {
+        source.amount
+    } else {
+        source.amount.min(source.delegated_amount)
+    }
+}
+
+/// InsufficientDelegation when a delegate moves more out of `source` than it was delegated.
+/// The token program deducts the transfer from delegated_amount; checking first gives the
+/// caller a clear error instead of a token program failure.
+pub fn require_source_allowance(source: &TokenAccount, authority: &Pubkey, in_amount: u64) -> Result<()> {
+    if source.owner != *authority {
+        require!(in_amount <= source.delegated_amount, ErrorCode::InsufficientDelegation);
+    }
+    Ok(())
+}
+
+/// Executes a route plan without validate_route, for trusted routers only
+///
+/// Trusted routers re-send the same pre-validated route shape and take responsibility for the
+/// adapter accounts of each step. The vault authority still signs every adapter CPI, so instead
+/// of validating steps up front, every vault authority owned token account the instruction can
+/// reach is snapshotted and checked afterwards: none may be closed, change owner, delegate or
+/// close authority, or end with less than it started with. A bad route can therefore only cost
+/// the caller its own input, never protocol or escrow funds.
+///
+/// # Arguments
+/// Same as route
+///
+/// # Returns
+/// * `Result<RouteAmounts>` - Resolved input amount and final output amount after fees
+#[allow(clippy::too_many_arguments)]
+pub fn route_prevalidated<'info>(
+    ctx: Context<'_, '_, 'info, 'info, Route<'info>>,
+    route_plan: Vec<RoutePlanStep>,
+    in_amount: u64,
+    quoted_out_amount: u64,
+    slippage_bps: u16,
+    platform_fee_bps: u8,
+    min_out_amount: u64,
+    accept_high_impact: bool,
+    emit_step_events: Option<bool>,
+    use_registered_fee_vault: bool,
+) -> Result<RouteAmounts> {
+    if !ctx.accounts.adapter_registry.is_trusted_router(&ctx.accounts.user_transfer_authority.key()) {
+        return Err(ErrorCode::UntrustedRouter.into());
+    }
+    let protocol_config = ProtocolConfig::load_config(&ctx.accounts.protocol_config, ctx.program_id)?;
+    protocol_config.limits.check_route_slippage(slippage_bps)?;
+    if route_plan.is_empty() {
+        return Err(ErrorCode::EmptyRoute.into());
+    }
+    route_validator_module::validate_route_accounts_count(&route_plan, ctx.remaining_accounts.len())?;
+    // Trusted routers skip route validation, not adapter sandboxes
+    route_validator_module::validate_sandbox_access(
+        &route_plan,
+        &ctx.accounts.adapter_registry,
+        &ctx.accounts.user_transfer_authority.key(),
+    )?;
+
+    let in_amount = resolve_in_amount(
+        in_amount,
+        spendable_source_amount(&ctx.accounts.user_source_token_account, &ctx.accounts.user_transfer_authority.key()),
+    )?;
+
+    let platform_fee_account = resolve_platform_fee_account(&ctx, platform_fee_bps, use_registered_fee_vault)?;
+
+    // Source and destination vaults must still be program vaults of the route's mints
+    let vault_authority = ctx.accounts.vault_authority.key();
+    let (input_vault, destination_vault) = find_route_vaults(&ctx, &route_plan, None)?;
+    for vault in [input_vault, destination_vault] {
+        if wsol::is_sol_escrow(vault) {
+            continue;
+        }
+        let token_account = TokenAccount::try_deserialize(&mut vault.try_borrow_data()?.as_ref())?;
+        if token_account.owner != vault_authority {
+            return Err(ErrorCode::InvalidVaultOwner.into());
+        }
+    }
+
+    let mut reachable_accounts = ctx.remaining_accounts.to_vec();
+    if let Some(platform_fee_account) = &platform_fee_account {
+        reachable_accounts.push(platform_fee_account.clone());
+    }
+    let snapshots = route_validator_module::snapshot_vaults(&reachable_accounts, &vault_authority);
+
+    let fee_bps = volume_tier_fee_bps(&protocol_config, trader_volume_of(&ctx.accounts.trader_volume), platform_fee_bps)?;
+    let out_amount = execute_validated_route(&ctx, platform_fee_account.as_ref(), &route_plan, in_amount, quoted_out_amount, slippage_bps, fee_bps, min_out_amount, accept_high_impact, emit_step_events)?;
+
+    route_validator_module::verify_vault_snapshots(&snapshots, &reachable_accounts, &vault_authority)?;
+    record_route_volume(&mut *ctx.accounts, ctx.bumps.trader_volume, &protocol_config, in_amount, out_amount)?;
+
+    Ok(RouteAmounts { in_amount, out_amount })
+}
+
+/// Executes the best of up to MAX_FALLBACK_PLANS alternative route plans
+///
+/// Clients submit alternative plans whose accounts coexist in remaining_accounts with
+/// non-overlapping index ranges. Every plan that validates is quoted from the pools' current
+/// state and the one expected to deliver the most output is executed. Plans through
+/// concentrated liquidity pools have no on-chain quote (see quote_route) and are ranked by
+/// their `quoted_out_amounts` entry instead. Ties go to the earlier plan.
+///
+/// # Arguments
+/// * `route_plans` - Alternative route plans, earlier plans winning ties
+/// * `in_amount` - Amount of input tokens to swap, or SWEEP_SOURCE_BALANCE for the whole source balance
+/// * `quoted_out_amounts` - Quoted output for each plan, used for its slippage check
+/// * `slippage_bps` - Slippage tolerance in basis points
+/// * `platform_fee_bps` - Platform fee in basis points
+/// * `min_out_amount` - Hard floor on the output after fees, whichever plan executes
+/// * `accept_high_impact`, `emit_step_events`, `use_registered_fee_vault` - As for `route`
+///
+/// # Returns
+/// * `Result<u64>` - Final output amount after fees
+#[allow(clippy::too_many_arguments)]
+pub fn route_with_fallback<'info>(
+    ctx: Context<'_, '_, 'info, 'info, Route<'info>>,
+    route_plans: Vec<Vec<RoutePlanStep>>,
+    in_amount: u64,
+    quoted_out_amounts: Vec<u64>,
+    slippage_bps: u16,
+    platform_fee_bps: u8,
+    min_out_amount: u64,
+    accept_high_impact: bool,
+    emit_step_events: Option<bool>,
+    use_registered_fee_vault: bool,
 ) -> Result<u64> {
-    if slippage_bps > 10_000 {
-        return Err(ErrorCode::InvalidSlippage.into());
+    if route_plans.is_empty() {
+        return Err(ErrorCode::EmptyRoute.into());
     }
+    if route_plans.len() > MAX_FALLBACK_PLANS || quoted_out_amounts.len() != route_plans.len() {
+        return Err(ErrorCode::InvalidRoutePlanCount.into());
+    }
+    let protocol_config = ProtocolConfig::load_config(&ctx.accounts.protocol_config, ctx.program_id)?;
+    protocol_config.limits.check_route_slippage(slippage_bps)?;
 
-    // Validate platform_fee_account if provided
-    if let Some(platform_fee_account) = &ctx.accounts.platform_fee_account {
-        if platform_fee_account.owner != ctx.accounts.vault_authority.key() {
-            return Err(ErrorCode::InvalidPlatformFeeOwner.into());
+    // Plans must not share account slots, otherwise one plan could rewire the other
+    for i in 0..route_plans.len() {
+        for j in i + 1..route_plans.len() {
+            if route_validator_module::route_plans_overlap(&route_plans[i], &route_plans[j]) {
+                return Err(ErrorCode::OverlappingRoutePlans.into());
+            }
         }
-        if platform_fee_account.mint != ctx.accounts.destination_mint.key() {
-            return Err(ErrorCode::InvalidPlatformFeeMint.into());
+    }
+
+    let in_amount = resolve_in_amount(
+        in_amount,
+        spendable_source_amount(&ctx.accounts.user_source_token_account, &ctx.accounts.user_transfer_authority.key()),
+    )?;
+
+    let platform_fee_account = resolve_platform_fee_account(&ctx, platform_fee_bps, use_registered_fee_vault)?;
+
+    // Validation and quoting are read-only, so plans failing either are safe to skip
+    let mut expected_outputs = Vec::with_capacity(route_plans.len());
+    let mut last_error = None;
+    for (i, plan) in route_plans.iter().enumerate() {
+        let expected_output = validate_route_plan(&ctx, plan, in_amount)
+            .and_then(|()| quote_route_plan(&ctx, platform_fee_account.as_ref(), plan, in_amount));
+        match expected_output {
+            Ok(onchain_quote) => expected_outputs.push(Some(onchain_quote.unwrap_or(quoted_out_amounts[i]))),
+            Err(err) => {
+                msg!("Route plan {} failed validation: {:?}", i, err);
+                expected_outputs.push(None);
+                last_error = Some(err);
+            }
         }
     }
+    let plan_index = match select_fallback_plan(&expected_outputs) {
+        Some(index) => index,
+        None => return Err(last_error.unwrap_or_else(|| ErrorCode::EmptyRoute.into())),
+    };
 
-    // Validate route and accounts
+    let fee_bps = volume_tier_fee_bps(&protocol_config, trader_volume_of(&ctx.accounts.trader_volume), platform_fee_bps)?;
+    let output_amount = execute_validated_route(
+        &ctx,
+        platform_fee_account.as_ref(),
+        &route_plans[plan_index],
+        in_amount,
+        quoted_out_amounts[plan_index],
+        slippage_bps,
+        fee_bps,
+        min_out_amount,
+        accept_high_impact,
+        emit_step_events,
+    )?;
+    record_route_volume(&mut *ctx.accounts, ctx.bumps.trader_volume, &protocol_config, in_amount, output_amount)?;
+
+    emit_cpi!(RouteFallbackEvent {
+        sender: ctx.accounts.user_transfer_authority.key(),
+        plan_index: plan_index as u8,
+        plans_count: route_plans.len() as u8,
+        output_amount,
+    });
+
+    Ok(output_amount)
+}
+
+/// Returns the index of the plan with the highest expected output, the earlier plan on ties
+///
+/// `expected_outputs` holds None for plans that failed validation.
+pub(crate) fn select_fallback_plan(expected_outputs: &[Option<u64>]) -> Option<usize> {
+    let mut best: Option<(usize, u64)> = None;
+    for (index, expected_output) in expected_outputs.iter().enumerate() {
+        let Some(expected_output) = *expected_output else { continue };
+        match best {
+            Some((_, best_output)) if best_output >= expected_output => {}
+            _ => best = Some((index, expected_output)),
+        }
+    }
+    best.map(|(index, _)| index)
+}
+
+/// Picks the route's platform fee account: platform_fee_account, or with use_registered_fee_vault
+/// the destination mint's registered fee vault from remaining_accounts. Either must be a program
+/// vault for the destination mint that no delegate or foreign close authority can drain.
+/// Without a fee no account is used or validated; with one it is mandatory.
+fn resolve_platform_fee_account<'info>(
+    ctx: &Context<'_, '_, 'info, 'info, Route<'info>>,
+    platform_fee_bps: u8,
+    use_registered_fee_vault: bool,
+) -> Result<Option<AccountInfo<'info>>> {
+    let vault_authority = ctx.accounts.vault_authority.key();
+    let destination_mint = ctx.accounts.destination_mint.key();
+
+    if use_registered_fee_vault && platform_fee_bps > 0 {
+        // One fee account per route, so the registry cannot be bypassed by also naming one
+        if ctx.accounts.platform_fee_account.is_some() {
+            return Err(ErrorCode::RegisteredFeeVaultNotProvided.into());
+        }
+        let fee_vault = route_validator_module::find_registered_fee_vault(
+            &ctx.accounts.fee_vault_registry.to_account_info(),
+            &destination_mint,
+            &vault_authority,
+            ctx.remaining_accounts,
+            ctx.program_id,
+        )?;
+        return Ok(Some(fee_vault.clone()));
+    }
+
+    match route_validator_module::charged_fee_account(platform_fee_bps, ctx.accounts.platform_fee_account.as_ref())? {
+        Some(platform_fee_account) => {
+            route_validator_module::validate_platform_fee_vault(platform_fee_account, &destination_mint, &vault_authority)?;
+            Ok(Some(platform_fee_account.to_account_info()))
+        }
+        None => Ok(None),
+    }
+}
+
+/// The TraderVolume behind an optional trader_volume account
+pub fn trader_volume_of<'a>(trader_volume: &'a Option<Box<Account<'_, TraderVolume>>>) -> Option<&'a TraderVolume> {
+    trader_volume.as_deref().map(|account| &**account)
+}
+
+/// Platform fee after the trader's volume tier rebate, see ProtocolConfig::volume_fee_bps.
+/// A trader without a TraderVolume has no recorded volume and pays the full fee.
+pub fn volume_tier_fee_bps(protocol_config: &ProtocolConfig, trader_volume: Option<&TraderVolume>, platform_fee_bps: u8) -> Result<u8> {
+    let window_volume = match trader_volume {
+        Some(trader_volume) => trader_volume.window_volume(Clock::get()?.epoch),
+        None => 0,
+    };
+    protocol_config.volume_fee_bps(platform_fee_bps, window_volume)
+}
+
+/// Adds `volume` to the current epoch of a trader's TraderVolume, filling in the owner and
+/// bump of one created by this instruction
+pub fn record_trader_volume(trader_volume: &mut TraderVolume, trader: Pubkey, bump: Option<u8>, volume: u64) -> Result<()> {
+    trader_volume.trader = trader;
+    trader_volume.bump = bump.ok_or(ErrorCode::InvalidAccount)?;
+    trader_volume.record(Clock::get()?.epoch, volume);
+    Ok(())
+}
+
+/// Counts an executed route towards the volume tiers when trader_volume was passed
+fn record_route_volume(
+    accounts: &mut Route<'_>,
+    trader_volume_bump: Option<u8>,
+    protocol_config: &ProtocolConfig,
+    in_amount: u64,
+    out_amount: u64,
+) -> Result<()> {
+    let volume = protocol_config.route_volume(&accounts.source_mint.key(), &accounts.destination_mint.key(), in_amount, out_amount);
+    let trader = accounts.user_transfer_authority.key();
+    if let Some(trader_volume) = accounts.trader_volume.as_deref_mut() {
+        record_trader_volume(trader_volume, trader, trader_volume_bump, volume)?;
+    }
+    Ok(())
+}
+
+/// Validates a single route plan against the Route accounts
+fn validate_route_plan<'info>(
+    ctx: &Context<'_, '_, 'info, 'info, Route<'info>>,
+    route_plan: &[RoutePlanStep],
+    in_amount: u64,
+) -> Result<()> {
     route_validator_module::validate_route(
         &ctx.accounts.adapter_registry,
         &ctx.accounts.input_token_program.to_account_info(),
@@ -86,12 +605,91 @@ pub fn route<'info>(
         &ctx.accounts.vault_authority.to_account_info(),
         &ctx.accounts.source_mint.to_account_info(),
         &ctx.accounts.destination_mint.to_account_info(),
-        &route_plan,
+        route_plan,
         ctx.remaining_accounts,
         ctx.program_id,
-        in_amount
-    )?;
+        in_amount,
+        &ctx.accounts.user_transfer_authority.key(),
+        true,
+    )
+}
 
+/// Whether the account is a token account of the given mint
+fn is_token_account_of_mint(account: &AccountInfo, mint: &Pubkey) -> bool {
+    route_validator_module::scan_token_account(account).is_some_and(|token_account| token_account.mint == *mint)
+}
+
+/// Finds the route's input vault (first source mint account) and destination vault
+/// (last destination mint account) in remaining_accounts, skipping a registered fee vault
+/// passed there. A route opening with WrapSol starts, and one closing with UnwrapSol ends,
+/// in the lamport escrow (see validate_sol_escrow_steps).
+fn find_route_vaults<'info>(
+    ctx: &Context<'_, '_, 'info, 'info, Route<'info>>,
+    route_plan: &[RoutePlanStep],
+    fee_vault: Option<&Pubkey>,
+) -> Result<(&'info AccountInfo<'info>, &'info AccountInfo<'info>)> {
+    let source_mint = ctx.accounts.source_mint.key();
+    let destination_mint = ctx.accounts.destination_mint.key();
+    let step_account = |index: u8| -> Result<&'info AccountInfo<'info>> {
+        ctx.remaining_accounts
+            .get(index as usize)
+            .ok_or(ErrorCode::InvalidAccountIndex.into())
+    };
+    let input_vault = match route_plan.first() {
+        Some(step) if step.swap == Swap::WrapSol => step_account(step.input_index)?,
+        _ => ctx.remaining_accounts
+            .iter()
+            .find(|acc| is_token_account_of_mint(acc, &source_mint))
+            .ok_or(ErrorCode::VaultNotFound)?,
+    };
+    let destination_vault = match route_plan.last() {
+        Some(step) if step.swap == Swap::UnwrapSol => step_account(step.output_index)?,
+        _ => ctx.remaining_accounts
+            .iter()
+            .rev()
+            .filter(|acc| Some(acc.key) != fee_vault)
+            .find(|acc| is_token_account_of_mint(acc, &destination_mint))
+            .ok_or(ErrorCode::VaultNotFound)?,
+    };
+    Ok((input_vault, destination_vault))
+}
+
+/// Quotes a validated route plan from the pools' current state, None for routes through
+/// concentrated liquidity pools (see quote_route)
+fn quote_route_plan<'info>(
+    ctx: &Context<'_, '_, 'info, 'info, Route<'info>>,
+    platform_fee_account: Option<&AccountInfo<'info>>,
+    route_plan: &[RoutePlanStep],
+    in_amount: u64,
+) -> Result<Option<u64>> {
+    let (_, destination_vault) = find_route_vaults(ctx, route_plan, platform_fee_account.map(|account| account.key))?;
+    route_executor_module::quote_route(
+        &ctx.accounts.adapter_registry,
+        &ctx.accounts.input_token_program.to_account_info(),
+        &ctx.accounts.vault_authority.to_account_info(),
+        destination_vault,
+        route_plan,
+        ctx.remaining_accounts,
+        ctx.program_id,
+        in_amount,
+    )
+}
+
+/// Moves user funds into the vaults, executes an already validated route plan,
+/// collects the platform fee, checks slippage and pays out the user
+#[allow(clippy::too_many_arguments)]
+fn execute_validated_route<'info>(
+    ctx: &Context<'_, '_, 'info, 'info, Route<'info>>,
+    platform_fee_account: Option<&AccountInfo<'info>>,
+    route_plan: &[RoutePlanStep],
+    in_amount: u64,
+    quoted_out_amount: u64,
+    slippage_bps: u16,
+    platform_fee_bps: u8,
+    min_out_amount: u64,
+    accept_high_impact: bool,
+    emit_step_events: Option<bool>,
+) -> Result<u64> {
     let vault_authority_bump = ctx.bumps.vault_authority;
     let authority_seeds: &[&[u8]] = &[
         b"vault_authority".as_ref(),
@@ -99,91 +697,124 @@ pub fn route<'info>(
     ];
     let signer_seeds: &[&[&[u8]]] = &[authority_seeds];
 
-    // Find input vault that matches source mint
-    let input_vault = ctx.remaining_accounts
-        .iter()
-        .find(|acc| {
-            if let Ok(account_data) = acc.try_borrow_data() {
-                if let Ok(token_account) = TokenAccount::try_deserialize(&mut account_data.as_ref()) {
-                    token_account.mint == ctx.accounts.source_mint.key()
-                } else {
-                    false
-                }
-            } else {
-                false
-            }
-        })
-        .ok_or(ErrorCode::VaultNotFound)?;
-
-    // Find destination vault
-    let destination_vault = ctx.remaining_accounts
-        .iter()
-        .rev()
-        .find(|acc| {
-            if let Ok(account_data) = acc.try_borrow_data() {
-                if let Ok(token_account) = TokenAccount::try_deserialize(&mut account_data.as_ref()) {
-                    token_account.mint == ctx.accounts.destination_mint.key()
-                } else {
-                    false
-                }
-            } else {
-                false
-            }
-        })
-        .ok_or(ErrorCode::VaultNotFound)?;
-
-    // Transfer initial funds from user to input vault using input token program
-    transfer_checked(
-        CpiContext::new(
-            ctx.accounts.input_token_program.to_account_info(),
-            TransferChecked {
-                from: ctx.accounts.user_source_token_account.to_account_info(),
-                to: input_vault.clone(),
-                authority: ctx.accounts.user_transfer_authority.to_account_info(),
-                mint: ctx.accounts.source_mint.to_account_info(),
-            },
-        ),
-        in_amount,
-        ctx.accounts.source_mint.decimals,
-    )?;
+    let (input_vault, destination_vault) = find_route_vaults(ctx, route_plan, platform_fee_account.map(|account| account.key))?;
+    // Native SOL routes take the user's lamports and pay out lamports through the escrow
+    let native_input = wsol::is_sol_escrow(input_vault);
+    let native_output = wsol::is_sol_escrow(destination_vault);
+
+    // Reject quotes far from what the pools offer, which would make the slippage check meaningless.
+    // Routes through concentrated liquidity pools have no on-chain quote and are not checked
+    let adapter_registry = &ctx.accounts.adapter_registry;
+    if adapter_registry.enforce_quote_sanity {
+        if let Some(onchain_quote) = quote_route_plan(ctx, platform_fee_account, route_plan, in_amount)? {
+            route_validator_module::check_quote_sanity(quoted_out_amount, onchain_quote, adapter_registry.quote_band_bps())?;
+        }
+    }
+
+    #[cfg(feature = "invariant-checks")]
+    let mut conservation = {
+        let mut tracked = vec![
+            ctx.accounts.user_source_token_account.to_account_info(),
+            ctx.accounts.user_destination_token_account.to_account_info(),
+            input_vault.clone(),
+            destination_vault.clone(),
+        ];
+        // A native SOL fee arrives as lamports from the escrow, outside the tracked token balances
+        if let Some(platform_fee_account) = platform_fee_account.filter(|_| !native_output) {
+            tracked.push(platform_fee_account.clone());
+        }
+        invariants::ConservationCheck::start(tracked)
+    };
+
+    if native_input {
+        wsol::fund_escrow(
+            &ctx.accounts.user_transfer_authority.to_account_info(),
+            input_vault,
+            &ctx.accounts.system_program.to_account_info(),
+            in_amount,
+        )?;
+    } else {
+        require_source_allowance(&ctx.accounts.user_source_token_account, &ctx.accounts.user_transfer_authority.key(), in_amount)?;
+
+        // Transfer initial funds from user to input vault using input token program
+        transfer_checked(
+            CpiContext::new(
+                ctx.accounts.input_token_program.to_account_info(),
+                TransferChecked {
+                    from: ctx.accounts.user_source_token_account.to_account_info(),
+                    to: input_vault.clone(),
+                    authority: ctx.accounts.user_transfer_authority.to_account_info(),
+                    mint: ctx.accounts.source_mint.to_account_info(),
+                },
+            ),
+            in_amount,
+            ctx.accounts.source_mint.decimals,
+        )?;
+    }
+
+    #[cfg(feature = "invariant-checks")]
+    conservation.begin_swap();
+
+    emit_cpi!(RoutePlanEvent::from_plan(route_plan, ctx.remaining_accounts)?);
+
+    let pool_volume_warning_pct = ProtocolConfig::load_config(&ctx.accounts.protocol_config, ctx.program_id)?.pool_volume_warning_pct();
 
     // Execute the route - now using destination_vault instead of user account
-    let (mut output_amount, event_data) = route_executor_module::execute_route(
+    let (mut output_amount, event_data, volume_warnings) = route_executor_module::execute_route(
         &ctx.accounts.adapter_registry,
         &ctx.accounts.input_token_program.to_account_info(),
         &ctx.accounts.vault_authority.to_account_info(),
         &ctx.accounts.source_mint.to_account_info(),
         destination_vault, // Changed: use vault instead of user account
-        &route_plan,
+        route_plan,
         ctx.remaining_accounts,
         ctx.program_id,
         in_amount,
+        accept_high_impact,
+        pool_volume_warning_pct,
     )?;
 
-    // Emit swap events
-    for event in event_data {
-        emit_cpi!(SwapEvent {
-            amm: event.amm,
-            input_mint: event.input_mint,
-            input_amount: event.input_amount,
-            output_mint: event.output_mint,
-            output_amount: event.output_amount,
-        });
+    #[cfg(feature = "invariant-checks")]
+    conservation.end_swap();
+
+    for warning in volume_warnings {
+        emit_cpi!(warning);
+    }
+
+    // Emit swap events; a single-hop SwapEvent may be skipped since RouterSwapEvent repeats it
+    if ctx.accounts.adapter_registry.should_emit_step_events(event_data.len(), emit_step_events) {
+        for event in event_data {
+            emit_cpi!(SwapEvent {
+                amm: event.amm,
+                input_mint: event.input_mint,
+                input_amount: event.input_amount,
+                output_mint: event.output_mint,
+                output_amount: event.output_amount,
+                host_fee_amount: event.host_fee_amount,
+            });
+        }
     }
 
     // Apply platform fee if specified
     let mut fee_amount = 0u64;
     let mut fee_account: Option<Pubkey> = None;
-    if let Some(platform_fee_account) = &ctx.accounts.platform_fee_account {
-        fee_amount = (output_amount as u128 * platform_fee_bps as u128 / 10_000) as u64;
-        if fee_amount > 0 {
+    if let Some(platform_fee_account) = platform_fee_account {
+        fee_amount = math::apply_bps_fee(output_amount, platform_fee_bps.into())?;
+        if fee_amount > 0 && native_output {
+            // The fee account is a WSOL account; lamports sent to it are synced into its balance
+            wsol::pay_from_escrow(destination_vault, platform_fee_account, &ctx.accounts.system_program.to_account_info(), fee_amount)?;
+            sync_native(CpiContext::new(
+                ctx.accounts.output_token_program.to_account_info(),
+                SyncNative { account: platform_fee_account.clone() },
+            ))?;
+        } else if fee_amount > 0 {
             // Transfer fee using output token program
             transfer_checked(
                 CpiContext::new_with_signer(
                     ctx.accounts.output_token_program.to_account_info(),
                     TransferChecked {
                         from: destination_vault.clone(),
-                        to: platform_fee_account.to_account_info(),
+                        to: platform_fee_account.clone(),
                         authority: ctx.accounts.vault_authority.to_account_info(),
                         mint: ctx.accounts.destination_mint.to_account_info(),
                     },
@@ -192,12 +823,16 @@ pub fn route<'info>(
                 fee_amount,
                 ctx.accounts.destination_mint.decimals,
             )?;
-
+        }
+        if fee_amount > 0 {
             // Emit fee event
             emit_cpi!(FeeEvent {
                 account: platform_fee_account.key(),
                 mint: ctx.accounts.destination_mint.key(),
                 amount: fee_amount,
+                net_amount: route_validator_module::net_transfer_amount(&ctx.accounts.destination_mint.to_account_info(), fee_amount)?,
+                fee_bps: platform_fee_bps,
+                source: FeeSource::Route,
             });
 
             fee_account = Some(platform_fee_account.key());
@@ -205,43 +840,58 @@ pub fn route<'info>(
         }
     }
 
-    // Check slippage tolerance
-    let min_out_amount = (quoted_out_amount as u128)
-        .checked_mul(
-            (10_000u128)
-                .checked_sub(slippage_bps as u128)
-                .ok_or(ErrorCode::InvalidCalculation)?
-        )
-        .ok_or(ErrorCode::InvalidCalculation)?
-        .checked_div(10_000)
-        .ok_or(ErrorCode::InvalidCalculation)? as u64;
-
-    require!(
-        output_amount >= min_out_amount,
-        ErrorCode::SlippageToleranceExceeded
-    );
-
-
-    // Transfer final amount from destination vault to user
-    transfer_checked(
-        CpiContext::new_with_signer(
-            ctx.accounts.output_token_program.to_account_info(),
-            TransferChecked {
-                from: destination_vault.clone(),
-                to: ctx.accounts.user_destination_token_account.to_account_info(),
-                authority: ctx.accounts.vault_authority.to_account_info(),
-                mint: ctx.accounts.destination_mint.to_account_info(),
-            },
-            signer_seeds
-        ),
+    // Check slippage tolerance and the user's own floor against what actually lands
+    // in the user account (net of any Token-2022 transfer fee)
+    let net_output_amount = route_validator_module::net_transfer_amount(
+        &ctx.accounts.destination_mint.to_account_info(),
         output_amount,
-        ctx.accounts.destination_mint.decimals,
     )?;
+    route_validator_module::validate_output_floors(
+        net_output_amount,
+        quoted_out_amount,
+        slippage_bps,
+        min_out_amount,
+    )?;
+
+
+    // Transfer final amount from destination vault to user; native SOL output is paid in
+    // lamports to the user's wallet
+    let recipient = if native_output {
+        wsol::pay_from_escrow(
+            destination_vault,
+            &ctx.accounts.user_transfer_authority.to_account_info(),
+            &ctx.accounts.system_program.to_account_info(),
+            output_amount,
+        )?;
+        ctx.accounts.user_transfer_authority.key()
+    } else {
+        transfer_checked(
+            CpiContext::new_with_signer(
+                ctx.accounts.output_token_program.to_account_info(),
+                TransferChecked {
+                    from: destination_vault.clone(),
+                    to: ctx.accounts.user_destination_token_account.to_account_info(),
+                    authority: ctx.accounts.vault_authority.to_account_info(),
+                    mint: ctx.accounts.destination_mint.to_account_info(),
+                },
+                signer_seeds
+            ),
+            output_amount,
+            ctx.accounts.destination_mint.decimals,
+        )?;
+        ctx.accounts.user_destination_token_account.key()
+    };
+
+    #[cfg(feature = "invariant-checks")]
+    conservation.finish(&[
+        invariants::transfer_fee_allowance(&ctx.accounts.source_mint.to_account_info(), &[in_amount])?,
+        invariants::transfer_fee_allowance(&ctx.accounts.destination_mint.to_account_info(), &[fee_amount, output_amount])?,
+    ])?;
 
     // Emit global router swap event
     emit_cpi!(RouterSwapEvent {
         sender: ctx.accounts.user_transfer_authority.key(),
-        recipient: ctx.accounts.user_destination_token_account.key(),
+        recipient,
         input_mint: ctx.accounts.source_mint.key(),
         output_mint: ctx.accounts.destination_mint.key(),
         input_amount: in_amount,
@@ -249,7 +899,266 @@ pub fn route<'info>(
         fee_amount,
         fee_account,
         slippage_bps,
+        net_output_amount,
+        accept_high_impact,
+        fee_mint: fee_account.map(|_| ctx.accounts.destination_mint.key()),
+        ui_amount_multiplier: route_validator_module::ui_amount_multiplier(&ctx.accounts.destination_mint.to_account_info())?,
     });
 
     Ok(output_amount)
-}
\ No newline at end of file
+}
+/// Maximum steps of a staged route plan (bounds RouteSession::SPACE)
+pub const MAX_STAGED_ROUTE_STEPS: usize = 8;
+
+/// Maximum number of slots a staged route stays executable
+pub const MAX_ROUTE_SESSION_SLOTS: u64 = 150;
+
+/// Serialized size reserved for one RoutePlanStep (swap + percent + input_index + output_index)
+pub const ROUTE_PLAN_STEP_SPACE: usize = SWAP_TYPE_SPACE + 3;
+
+/// Route plan and swap arguments staged by stage_route for execute_staged_route
+///
+/// Moving the plan and arguments into an account keeps the executing instruction small,
+/// and the accounts hash binds execution to the exact remaining_accounts chosen at staging.
+/// One session per user; it is closed on execution, so a staged route runs at most once.
+#[account]
+pub struct RouteSession {
+    /// User whose tokens the staged route swaps
+    pub user: Pubkey,
+    /// Hash of the remaining account keys, in order (see route_accounts_hash)
+    pub accounts_hash: [u8; 32],
+    /// Staged route plan
+    pub route_plan: Vec<RoutePlanStep>,
+    pub in_amount: u64,
+    pub quoted_out_amount: u64,
+    pub slippage_bps: u16,
+    pub platform_fee_bps: u8,
+    pub min_out_amount: u64,
+    /// Last slot in which the route can be executed
+    pub expires_at_slot: u64,
+    /// PDA bump seed
+    pub bump: u8,
+}
+
+impl RouteSession {
+    pub const SPACE: usize = 8 // discriminator
+        + 32 // user
+        + 32 // accounts_hash
+        + 4 + MAX_STAGED_ROUTE_STEPS * ROUTE_PLAN_STEP_SPACE // route_plan
+        + 8 // in_amount
+        + 8 // quoted_out_amount
+        + 2 // slippage_bps
+        + 1 // platform_fee_bps
+        + 8 // min_out_amount
+        + 8 // expires_at_slot
+        + 1; // bump
+}
+
+/// Hash committing to the keys of the route's remaining accounts, in order
+pub fn route_accounts_hash(remaining_accounts: &[AccountInfo]) -> [u8; 32] {
+    let keys: Vec<&[u8]> = remaining_accounts.iter().map(|account| account.key.as_ref()).collect();
+    hashv(&keys).to_bytes()
+}
+
+#[derive(Accounts)]
+pub struct StageRoute<'info> {
+    #[account(
+        init,
+        payer = user,
+        space = RouteSession::SPACE,
+        seeds = [b"route_session", user.key().as_ref()],
+        bump
+    )]
+    pub route_session: Account<'info, RouteSession>,
+
+    /// CHECK: ProtocolConfig PDA; default limits apply until create_protocol_config has created it
+    #[account(seeds = [b"protocol_config"], bump)]
+    pub protocol_config: UncheckedAccount<'info>,
+
+    #[account(mut)]
+    pub user: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+/// Stages a route plan for execute_staged_route, in this transaction or a later one
+///
+/// # Arguments
+/// * `route_plan` - Swap route to execute
+/// * `in_amount` - Amount of input tokens to swap
+/// * `quoted_out_amount` - Quoted output, used for the slippage check
+/// * `slippage_bps` - Slippage tolerance in basis points
+/// * `platform_fee_bps` - Platform fee in basis points
+/// * `min_out_amount` - Caller's own minimum net output after fees (0 = none)
+/// * `accounts_hash` - route_accounts_hash of the remaining accounts execution will use
+/// * `valid_for_slots` - Slots the session stays executable (1..=MAX_ROUTE_SESSION_SLOTS)
+pub fn stage_route(
+    ctx: Context<StageRoute>,
+    route_plan: Vec<RoutePlanStep>,
+    in_amount: u64,
+    quoted_out_amount: u64,
+    slippage_bps: u16,
+    platform_fee_bps: u8,
+    min_out_amount: u64,
+    accounts_hash: [u8; 32],
+    valid_for_slots: u64,
+) -> Result<()> {
+    if route_plan.is_empty() {
+        return Err(ErrorCode::EmptyRoute.into());
+    }
+    // Checked on the serialized plan, so steps with larger Swap payloads can't overflow the account
+    if route_plan.len() > MAX_STAGED_ROUTE_STEPS
+        || route_plan.try_to_vec()?.len() > 4 + MAX_STAGED_ROUTE_STEPS * ROUTE_PLAN_STEP_SPACE
+    {
+        return Err(ErrorCode::StagedRouteTooLong.into());
+    }
+    ProtocolConfig::load(&ctx.accounts.protocol_config, ctx.program_id)?.check_route_slippage(slippage_bps)?;
+    if valid_for_slots == 0 || valid_for_slots > MAX_ROUTE_SESSION_SLOTS {
+        return Err(ErrorCode::InvalidRouteSessionWindow.into());
+    }
+
+    let route_session = &mut ctx.accounts.route_session;
+    route_session.user = ctx.accounts.user.key();
+    route_session.accounts_hash = accounts_hash;
+    route_session.route_plan = route_plan;
+    route_session.in_amount = in_amount;
+    route_session.quoted_out_amount = quoted_out_amount;
+    route_session.slippage_bps = slippage_bps;
+    route_session.platform_fee_bps = platform_fee_bps;
+    route_session.min_out_amount = min_out_amount;
+    route_session.expires_at_slot = Clock::get()?
+        .slot
+        .checked_add(valid_for_slots - 1)
+        .ok_or(ErrorCode::InvalidCalculation)?;
+    route_session.bump = ctx.bumps.route_session;
+
+    Ok(())
+}
+
+/// Number of leading remaining_accounts execute_staged_route reads as route accounts:
+/// adapter_registry, vault_authority, user_source_token_account, user_destination_token_account,
+/// source_mint, destination_mint, platform_fee_account, fee_vault_registry, protocol_config and
+/// trader_volume, in that order (optional accounts passed as the program id)
+pub const STAGED_ROUTE_ACCOUNTS: usize = 10;
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct ExecuteStagedRoute<'info> {
+    #[account(
+        mut,
+        seeds = [b"route_session", user_transfer_authority.key().as_ref()],
+        bump = route_session.bump
+    )]
+    pub route_session: Account<'info, RouteSession>,
+
+    #[account(mut)]
+    pub user_transfer_authority: Signer<'info>,
+
+    pub input_token_program: Interface<'info, TokenInterface>,
+    pub output_token_program: Interface<'info, TokenInterface>,
+
+    pub system_program: Program<'info, System>,
+}
+
+/// Executes a route staged by stage_route and closes the session, returning its rent to the user
+///
+/// Only the session, the signer and the programs are named accounts. The vaults, mints and
+/// other route accounts are the first STAGED_ROUTE_ACCOUNTS remaining_accounts, followed by
+/// the swap accounts the staged plan indexes into; all of them are covered by the staged
+/// accounts hash, so execution runs against exactly the accounts chosen at staging. The route
+/// accounts then go through the same constraints and checks as route.
+///
+/// # Returns
+/// * `Result<u64>` - Final output amount after fees
+pub fn execute_staged_route<'info>(
+    ctx: Context<'_, '_, 'info, 'info, ExecuteStagedRoute<'info>>,
+) -> Result<u64> {
+    let route_session = &ctx.accounts.route_session;
+    if Clock::get()?.slot > route_session.expires_at_slot {
+        return Err(ErrorCode::RouteSessionExpired.into());
+    }
+    if route_accounts_hash(ctx.remaining_accounts) != route_session.accounts_hash {
+        return Err(ErrorCode::RouteCommitmentMismatch.into());
+    }
+    if ctx.remaining_accounts.len() < STAGED_ROUTE_ACCOUNTS {
+        return Err(ErrorCode::NotEnoughAccountKeys.into());
+    }
+    let (staged, swap_accounts) = ctx.remaining_accounts.split_at(STAGED_ROUTE_ACCOUNTS);
+
+    let route_plan = route_session.route_plan.clone();
+    let in_amount = route_session.in_amount;
+    let quoted_out_amount = route_session.quoted_out_amount;
+    let slippage_bps = route_session.slippage_bps;
+    let platform_fee_bps = route_session.platform_fee_bps;
+    let min_out_amount = route_session.min_out_amount;
+
+    // Route account order, with the named accounts slotted in. The list must outlive the
+    // instruction's 'info borrows; the program heap is never freed, so leaking it costs nothing
+    let mut route_account_infos: &'info [AccountInfo<'info>] = Vec::leak(vec![
+        staged[0].clone(),
+        staged[1].clone(),
+        ctx.accounts.input_token_program.to_account_info(),
+        ctx.accounts.output_token_program.to_account_info(),
+        ctx.accounts.user_transfer_authority.to_account_info(),
+        staged[2].clone(),
+        staged[3].clone(),
+        staged[4].clone(),
+        staged[5].clone(),
+        staged[6].clone(),
+        staged[7].clone(),
+        staged[8].clone(),
+        staged[9].clone(),
+        // No vault_meta: Anchor reads the program ID as an omitted optional account
+        ctx.accounts.program.to_account_info(),
+        ctx.accounts.system_program.to_account_info(),
+        ctx.accounts.event_authority.to_account_info(),
+        ctx.accounts.program.to_account_info(),
+    ]);
+    let mut route_bumps = RouteBumps::default();
+    let mut route_accounts = Route::try_accounts(
+        ctx.program_id,
+        &mut route_account_infos,
+        &[],
+        &mut route_bumps,
+        &mut std::collections::BTreeSet::new(),
+    )?;
+
+    let route_ctx = Context::new(ctx.program_id, &mut route_accounts, swap_accounts, route_bumps);
+    let output_amount = route(route_ctx, route_plan, in_amount, quoted_out_amount, slippage_bps, platform_fee_bps, min_out_amount, false, None, false, None)?.out_amount;
+    // Route was loaded by hand, so Anchor does not exit it; this persists the trader volume
+    route_accounts.exit(ctx.program_id)?;
+
+    // Close the session so the staged route cannot be replayed
+    let user = ctx.accounts.user_transfer_authority.to_account_info();
+    ctx.accounts.route_session.close(user)?;
+
+    Ok(output_amount)
+}
+
+#[derive(Accounts)]
+pub struct CloseRouteSession<'info> {
+    #[account(
+        mut,
+        seeds = [b"route_session", user.key().as_ref()],
+        bump = route_session.bump,
+        has_one = user @ ErrorCode::InvalidAccount,
+        close = user
+    )]
+    pub route_session: Account<'info, RouteSession>,
+
+    /// CHECK: Session owner, receives the rent; validated by has_one
+    #[account(mut)]
+    pub user: UncheckedAccount<'info>,
+
+    /// The session owner at any time, or anyone once the session has expired
+    pub closer: Signer<'info>,
+}
+
+/// Closes an unexecuted route session and returns its rent to the user
+pub fn close_route_session(ctx: Context<CloseRouteSession>) -> Result<()> {
+    let expired = Clock::get()?.slot > ctx.accounts.route_session.expires_at_slot;
+    if ctx.accounts.closer.key() != ctx.accounts.user.key() && !expired {
+        return Err(ErrorCode::RouteSessionNotExpired.into());
+    }
+    Ok(())
+}
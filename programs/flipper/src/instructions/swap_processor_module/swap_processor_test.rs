@@ -0,0 +1,323 @@
+#[cfg(test)]
+mod tests {
+    use super::super::*;
+    use crate::errors::ErrorCode;
+    use crate::test_utils::*;
+    use anchor_lang::prelude::*;
+    use anchor_lang::solana_program::program_option::COption;
+    use anchor_lang::solana_program::program_pack::Pack;
+    use anchor_spl::token::spl_token::state::{Account as SplTokenAccount, AccountState};
+
+    fn accounts(count: usize) -> Vec<TestAccount> {
+        (0..count).map(|_| empty_account()).collect()
+    }
+
+    fn max_size_session(swap: Swap) -> RouteSession {
+        RouteSession {
+            user: Pubkey::new_unique(),
+            accounts_hash: [7u8; 32],
+            route_plan: (0..MAX_STAGED_ROUTE_STEPS)
+                .map(|i| RoutePlanStep {
+                    swap: swap.clone(),
+                    percent: 100,
+                    input_index: i as u8,
+                    output_index: i as u8 + 1,
+                })
+                .collect(),
+            in_amount: u64::MAX,
+            quoted_out_amount: u64::MAX,
+            slippage_bps: 10_000,
+            platform_fee_bps: u8::MAX,
+            min_out_amount: u64::MAX,
+            expires_at_slot: u64::MAX,
+            bump: 255,
+        }
+    }
+
+    #[test]
+    fn test_route_accounts_hash_deterministic() {
+        let remaining_accounts = leak_account_infos(accounts(4));
+        assert_eq!(
+            route_accounts_hash(remaining_accounts),
+            route_accounts_hash(remaining_accounts)
+        );
+    }
+
+    #[test]
+    fn test_route_accounts_hash_matches_concatenated_keys() {
+        // Off-chain clients commit to sha256 over the concatenated keys
+        let remaining_accounts = leak_account_infos(accounts(3));
+        let concatenated: Vec<u8> = remaining_accounts
+            .iter()
+            .flat_map(|account| account.key.to_bytes())
+            .collect();
+
+        assert_eq!(
+            route_accounts_hash(remaining_accounts),
+            anchor_lang::solana_program::hash::hash(&concatenated).to_bytes()
+        );
+    }
+
+    #[test]
+    fn test_route_accounts_hash_order_sensitive() {
+        let fixtures = accounts(3);
+        let keys: Vec<Pubkey> = fixtures.iter().map(|account| account.key).collect();
+        let original = route_accounts_hash(leak_account_infos(fixtures));
+
+        let reordered = vec![
+            empty_account().with_key(keys[1]),
+            empty_account().with_key(keys[0]),
+            empty_account().with_key(keys[2]),
+        ];
+        assert_ne!(route_accounts_hash(leak_account_infos(reordered)), original);
+    }
+
+    #[test]
+    fn test_route_accounts_hash_detects_substituted_account() {
+        let fixtures = accounts(3);
+        let keys: Vec<Pubkey> = fixtures.iter().map(|account| account.key).collect();
+        let original = route_accounts_hash(leak_account_infos(fixtures));
+
+        let substituted = vec![
+            empty_account().with_key(keys[0]),
+            empty_account(),
+            empty_account().with_key(keys[2]),
+        ];
+        assert_ne!(route_accounts_hash(leak_account_infos(substituted)), original);
+    }
+
+    #[test]
+    fn test_route_session_space_fits_max_route() {
+        for swap in [Swap::Raydium, Swap::Whirlpool { a_to_b: true }, Swap::Meteora] {
+            let mut data = Vec::new();
+            max_size_session(swap).try_serialize(&mut data).unwrap();
+            assert!(data.len() <= RouteSession::SPACE);
+        }
+    }
+
+    #[test]
+    fn test_resolve_in_amount_passes_explicit_amount_through() {
+        assert_eq!(resolve_in_amount(500, 1_000).unwrap(), 500);
+        // An explicit amount is not checked against the balance here; the transfer enforces it
+        assert_eq!(resolve_in_amount(5_000, 0).unwrap(), 5_000);
+    }
+
+    #[test]
+    fn test_resolve_in_amount_sweeps_source_balance() {
+        assert_eq!(resolve_in_amount(SWEEP_SOURCE_BALANCE, 1_234).unwrap(), 1_234);
+        assert_eq!(resolve_in_amount(SWEEP_SOURCE_BALANCE, u64::MAX - 1).unwrap(), u64::MAX - 1);
+    }
+
+    #[test]
+    fn test_resolve_in_amount_rejects_empty_source_sweep() {
+        let err = resolve_in_amount(SWEEP_SOURCE_BALANCE, 0).unwrap_err();
+        assert_eq!(err, ErrorCode::InvalidAmount.into());
+    }
+
+    // Source token account of `owner`, optionally delegating `delegated_amount` to `delegate`
+    fn source_account(owner: Pubkey, amount: u64, delegate: Option<(Pubkey, u64)>) -> TokenAccount {
+        let account = SplTokenAccount {
+            mint: Pubkey::new_unique(),
+            owner,
+            amount,
+            delegate: delegate.map(|(key, _)| key).into(),
+            state: AccountState::Initialized,
+            is_native: COption::None,
+            delegated_amount: delegate.map_or(0, |(_, delegated)| delegated),
+            close_authority: COption::None,
+        };
+        let mut data = vec![0u8; SplTokenAccount::LEN];
+        SplTokenAccount::pack(account, &mut data).unwrap();
+        TokenAccount::try_deserialize(&mut &data[..]).unwrap()
+    }
+
+    #[test]
+    fn test_owner_spends_whole_source_balance() {
+        let owner = Pubkey::new_unique();
+        let source = source_account(owner, 1_000, Some((Pubkey::new_unique(), 100)));
+
+        assert!(can_spend_source(&source, &owner));
+        assert_eq!(spendable_source_amount(&source, &owner), 1_000);
+        // The delegation only limits the delegate
+        assert!(require_source_allowance(&source, &owner, 1_000).is_ok());
+    }
+
+    #[test]
+    fn test_delegate_spends_up_to_its_delegation() {
+        let delegate = Pubkey::new_unique();
+        let source = source_account(Pubkey::new_unique(), 1_000, Some((delegate, 400)));
+
+        assert!(can_spend_source(&source, &delegate));
+        assert_eq!(spendable_source_amount(&source, &delegate), 400);
+        assert!(require_source_allowance(&source, &delegate, 400).is_ok());
+        let err = require_source_allowance(&source, &delegate, 401).unwrap_err();
+        assert_eq!(err, ErrorCode::InsufficientDelegation.into());
+
+        // A delegation larger than the balance sweeps only the balance
+        let source = source_account(Pubkey::new_unique(), 300, Some((delegate, 400)));
+        assert_eq!(spendable_source_amount(&source, &delegate), 300);
+    }
+
+    #[test]
+    fn test_signer_neither_owner_nor_delegate_is_rejected() {
+        let source = source_account(Pubkey::new_unique(), 1_000, Some((Pubkey::new_unique(), 400)));
+        assert!(!can_spend_source(&source, &Pubkey::new_unique()));
+
+        // Without a delegate nobody but the owner may spend
+        let source = source_account(Pubkey::new_unique(), 1_000, None);
+        assert!(!can_spend_source(&source, &Pubkey::new_unique()));
+        assert!(!can_spend_source(&source, &Pubkey::default()));
+    }
+
+    fn compact_step(header: u8, percent: u8, input_index: u8, output_index: u8) -> Vec<u8> {
+        if percent == 100 {
+            vec![header, input_index, output_index]
+        } else {
+            vec![header | COMPACT_SPLIT_FLAG, percent, input_index, output_index]
+        }
+    }
+
+    #[test]
+    fn test_decode_compact_route_plan_maps_supported_tags() {
+        let mut plan_bytes = compact_step(0, 100, 0, 13);
+        plan_bytes.extend(compact_step(0x09, 60, 13, 30));
+        plan_bytes.extend(compact_step(0x01, 40, 13, 47));
+        plan_bytes.extend(compact_step(2, 100, 47, 64));
+
+        let plan = decode_compact_route_plan(&plan_bytes).unwrap();
+        let swaps: Vec<Swap> = plan.iter().map(|step| step.swap.clone()).collect();
+        assert_eq!(
+            swaps,
+            vec![
+                Swap::Raydium,
+                Swap::Whirlpool { a_to_b: true },
+                Swap::Whirlpool { a_to_b: false },
+                Swap::Meteora,
+            ]
+        );
+        assert_eq!((plan[0].percent, plan[0].input_index, plan[0].output_index), (100, 0, 13));
+        assert_eq!((plan[1].percent, plan[1].input_index, plan[1].output_index), (60, 13, 30));
+        assert_eq!((plan[3].percent, plan[3].input_index, plan[3].output_index), (100, 47, 64));
+    }
+
+    #[test]
+    fn test_decode_compact_route_plan_maps_wsol_steps() {
+        let mut plan_bytes = compact_step(4, 100, 0, 6);
+        plan_bytes.extend(compact_step(3, 100, 6, 12));
+
+        let plan = decode_compact_route_plan(&plan_bytes).unwrap();
+        assert_eq!(plan[0].swap, Swap::UnwrapSol);
+        assert_eq!(plan[1].swap, Swap::WrapSol);
+        assert!(decode_compact_route_plan(&compact_step(0x0B, 100, 0, 6)).is_err());
+    }
+
+    #[test]
+    fn test_decode_compact_route_plan_maps_whirlpool_two_hop_directions() {
+        // Both hop directions share the flag bits, as they share byte 1 of Swap::to_bytes
+        for (flag, a_to_b_one, a_to_b_two) in [(0u8, false, false), (1, true, false), (2, false, true), (3, true, true)] {
+            let plan_bytes = compact_step((flag << 3) | 5, 100, 0, 25);
+            let plan = decode_compact_route_plan(&plan_bytes).unwrap();
+            let swap = Swap::WhirlpoolTwoHop { a_to_b_one, a_to_b_two };
+            assert_eq!(plan[0].swap, swap);
+            assert_eq!(crate::sdk::compact_route_plan(&plan).unwrap(), plan_bytes);
+        }
+        assert!(decode_compact_route_plan(&compact_step(0x45, 100, 0, 25)).is_err());
+    }
+
+    #[test]
+    fn test_decode_compact_route_plan_rejects_empty_and_truncated_plans() {
+        assert_eq!(decode_compact_route_plan(&[]).err().unwrap(), ErrorCode::EmptyRoute.into());
+
+        let mut plan_bytes = compact_step(0, 100, 0, 13);
+        plan_bytes.extend(compact_step(2, 50, 13, 30));
+        for len in 1..plan_bytes.len() {
+            let result = decode_compact_route_plan(&plan_bytes[..len]);
+            if len == COMPACT_STEP_LEN {
+                assert_eq!(result.unwrap().len(), 1);
+            } else {
+                assert_eq!(result.err().unwrap(), ErrorCode::InvalidCompactRoutePlan.into(), "len {}", len);
+            }
+        }
+        assert_eq!(decode_compact_route_plan(&plan_bytes).unwrap().len(), 2);
+    }
+
+    #[test]
+    fn test_decode_compact_route_plan_rejects_unknown_tags() {
+        // Stray flag bits on supported swaps, kinds past SUPPORTED_SWAP_TAGS and high bits
+        for header in [0x08u8, 0x11, 0x0A, 0x0C, 6, 7, 0x40, 0x80, 0xC5] {
+            let err = decode_compact_route_plan(&compact_step(header, 100, 0, 13)).err().unwrap();
+            assert_eq!(err, ErrorCode::UnknownCompactSwapTag.into(), "header {:#04x}", header);
+        }
+    }
+
+    #[test]
+    fn test_decode_compact_route_plan_reads_split_percent() {
+        // Only a header with COMPACT_SPLIT_FLAG is followed by a percent byte
+        let plan = decode_compact_route_plan(&[COMPACT_SPLIT_FLAG, 100, 0, 13]).unwrap();
+        assert_eq!((plan[0].percent, plan[0].input_index, plan[0].output_index), (100, 0, 13));
+        let plan = decode_compact_route_plan(&[COMPACT_SPLIT_FLAG | 2, 0, 5, 9]).unwrap();
+        assert_eq!((plan[0].swap.clone(), plan[0].percent), (Swap::Meteora, 0));
+    }
+
+    #[test]
+    fn test_decode_compact_route_plan_never_panics_on_arbitrary_bytes() {
+        // Deterministic xorshift bytes of every length up to a few records
+        let mut state = 0x9e37_79b9_7f4a_7c15u64;
+        for len in 0..=4 * (COMPACT_STEP_LEN + 1) {
+            for _ in 0..64 {
+                let bytes: Vec<u8> = (0..len)
+                    .map(|_| {
+                        state ^= state << 13;
+                        state ^= state >> 7;
+                        state ^= state << 17;
+                        state as u8
+                    })
+                    .collect();
+                if let Ok(plan) = decode_compact_route_plan(&bytes) {
+                    assert!(plan.len() * COMPACT_STEP_LEN <= len && len <= plan.len() * (COMPACT_STEP_LEN + 1));
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_select_fallback_plan_picks_highest_expected_output() {
+        assert_eq!(select_fallback_plan(&[Some(1_000), Some(1_200)]), Some(1));
+        assert_eq!(select_fallback_plan(&[Some(1_200), Some(1_000)]), Some(0));
+    }
+
+    #[test]
+    fn test_select_fallback_plan_prefers_earlier_plan_on_ties() {
+        assert_eq!(select_fallback_plan(&[Some(1_000), Some(1_000)]), Some(0));
+    }
+
+    #[test]
+    fn test_select_fallback_plan_skips_invalid_plans() {
+        assert_eq!(select_fallback_plan(&[None, Some(1)]), Some(1));
+        assert_eq!(select_fallback_plan(&[Some(0), None]), Some(0));
+        assert_eq!(select_fallback_plan(&[None, None]), None);
+        assert_eq!(select_fallback_plan(&[]), None);
+    }
+
+    #[test]
+    fn test_check_route_vault_generation() {
+        let vault = Pubkey::new_unique();
+        let remaining_accounts = leak_account_infos(vec![empty_account().with_key(vault), empty_account()]);
+        let vault_meta = VaultMeta { vault, vault_generation: 2, bump: 255 };
+
+        // Nothing is checked without an expected generation, whatever vault_meta holds
+        assert!(check_route_vault_generation(None, None, remaining_accounts).is_ok());
+        assert!(check_route_vault_generation(Some(&vault_meta), None, remaining_accounts).is_ok());
+
+        assert!(check_route_vault_generation(Some(&vault_meta), Some(2), remaining_accounts).is_ok());
+        let err = check_route_vault_generation(Some(&vault_meta), Some(1), remaining_accounts).unwrap_err();
+        assert_eq!(err, ErrorCode::VaultGenerationMismatch.into());
+        let err = check_route_vault_generation(None, Some(2), remaining_accounts).unwrap_err();
+        assert_eq!(err, ErrorCode::VaultMetaNotProvided.into());
+
+        // The VaultMeta of a vault the route does not use proves nothing
+        let foreign_meta = VaultMeta { vault: Pubkey::new_unique(), vault_generation: 2, bump: 255 };
+        let err = check_route_vault_generation(Some(&foreign_meta), Some(2), remaining_accounts).unwrap_err();
+        assert_eq!(err, ErrorCode::InvalidVaultAddress.into());
+    }
+}
@@ -0,0 +1,71 @@
+use anchor_lang::prelude::*;
+
+// Deterministic clock for localnet tests, compiled only with the "test-clock" feature
+//
+// Limit order instructions read their unix timestamp through current_unix_timestamp. In these
+// builds a MockClock passed anywhere in remaining_accounts overrides the Clock sysvar, so tests
+// can expire or activate orders without warping the validator. Anyone may set the mock time.
+
+// Test modules
+#[cfg(test)]
+mod test_clock_test;
+
+/// Seed of the MockClock PDA
+pub const MOCK_CLOCK_SEED: &[u8] = b"mock_clock";
+
+/// Unix timestamp the limit order instructions use instead of the Clock sysvar
+#[account]
+pub struct MockClock {
+    /// Timestamp reported while the account is passed
+    pub unix_timestamp: i64,
+    /// PDA bump seed
+    pub bump: u8,
+}
+
+impl MockClock {
+    pub const SPACE: usize = 8 + 8 + 1;
+}
+
+/// Reads the MockClock from remaining_accounts, if it was passed
+///
+/// # Returns
+/// * `Result<Option<i64>>` - The mock timestamp, or None to fall back to the Clock sysvar
+pub fn mock_unix_timestamp(remaining_accounts: &[AccountInfo]) -> Result<Option<i64>> {
+    let (mock_clock, _) = Pubkey::find_program_address(&[MOCK_CLOCK_SEED], &crate::ID);
+    let Some(account) = remaining_accounts.iter().find(|account| account.key() == mock_clock) else {
+        return Ok(None);
+    };
+    if account.owner != &crate::ID {
+        return Ok(None);
+    }
+    let clock = MockClock::try_deserialize(&mut account.try_borrow_data()?.as_ref())?;
+    Ok(Some(clock.unix_timestamp))
+}
+
+/// Accounts for setting the mock time.
+#[derive(Accounts)]
+pub struct SetMockClock<'info> {
+    #[account(
+        init_if_needed,
+        payer = payer,
+        space = MockClock::SPACE,
+        seeds = [MOCK_CLOCK_SEED],
+        bump
+    )]
+    pub mock_clock: Account<'info, MockClock>,
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+/// Sets the timestamp reported by the MockClock, creating it on first use
+///
+/// # Arguments
+/// * `unix_timestamp` - Time the limit order instructions see while the MockClock is passed
+pub fn set_mock_clock(ctx: Context<SetMockClock>, unix_timestamp: i64) -> Result<()> {
+    let mock_clock = &mut ctx.accounts.mock_clock;
+    mock_clock.unix_timestamp = unix_timestamp;
+    mock_clock.bump = ctx.bumps.mock_clock;
+    msg!("Mock clock set to {}", unix_timestamp);
+    Ok(())
+}
@@ -0,0 +1,32 @@
+#[cfg(test)]
+mod tests {
+    use super::super::*;
+    use crate::test_utils::*;
+
+    fn mock_clock_account(unix_timestamp: i64, owner: Pubkey) -> TestAccount {
+        let (key, bump) = Pubkey::find_program_address(&[MOCK_CLOCK_SEED], &crate::ID);
+        let mut data = Vec::new();
+        MockClock { unix_timestamp, bump }.try_serialize(&mut data).unwrap();
+        TestAccount::new(owner, data).with_key(key)
+    }
+
+    #[test]
+    fn test_mock_clock_is_read_from_remaining_accounts() {
+        let accounts = leak_account_infos(vec![empty_account(), mock_clock_account(1_700_000_000, crate::ID)]);
+        assert_eq!(mock_unix_timestamp(accounts).unwrap(), Some(1_700_000_000));
+        assert_eq!(mock_unix_timestamp(&accounts[..1]).unwrap(), None);
+    }
+
+    #[test]
+    fn test_mock_clock_ignores_foreign_owned_account() {
+        let accounts = leak_account_infos(vec![mock_clock_account(1, Pubkey::new_unique())]);
+        assert_eq!(mock_unix_timestamp(accounts).unwrap(), None);
+    }
+
+    #[test]
+    fn test_mock_clock_space() {
+        let mut data = Vec::new();
+        MockClock { unix_timestamp: -1, bump: 255 }.try_serialize(&mut data).unwrap();
+        assert_eq!(data.len(), MockClock::SPACE);
+    }
+}
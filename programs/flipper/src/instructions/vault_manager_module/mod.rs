@@ -2,10 +2,14 @@ use anchor_lang::prelude::*;
 use anchor_spl::token_interface::{
     self, TokenInterface, TokenAccount, Mint,
     transfer_checked, TransferChecked,
-    initialize_account3, InitializeAccount3
+    initialize_account3, InitializeAccount3,
+    revoke, Revoke,
+    sync_native, SyncNative
 };
 use anchor_spl::token_2022::ID as TOKEN_2022_PROGRAM_ID;
 use crate::errors::ErrorCode;
+use crate::instructions::jupiter_cpi::{DEFAULT_MAX_JUPITER_DATA_LEN, JUPITER_DATA_HARD_CAP, JUPITER_MIN_DATA_LEN};
+use crate::state::{AdminAction, AdminLog, VaultClosed, VaultCreated, VaultMeta, VaultRegistry};
 
 // Test modules
 #[cfg(test)]
@@ -16,6 +20,21 @@ pub struct VaultAuthority {
     pub admin: Pubkey,
     pub bump: u8,
     pub jupiter_program_id: Pubkey,
+    pub max_jupiter_data_len: u16, // 0 = DEFAULT_MAX_JUPITER_DATA_LEN
+}
+
+impl VaultAuthority {
+    // Account size: discriminator + admin + bump + jupiter_program_id + max_jupiter_data_len
+    pub const SPACE: usize = 8 + 32 + 1 + 32 + 2;
+
+    /// Largest Jupiter instruction data the shared_* instructions accept
+    pub fn jupiter_data_limit(&self) -> usize {
+        if self.max_jupiter_data_len == 0 {
+            DEFAULT_MAX_JUPITER_DATA_LEN as usize
+        } else {
+            self.max_jupiter_data_len as usize
+        }
+    }
 }
 
 #[account]
@@ -24,12 +43,30 @@ pub struct GlobalManager {
     pub bump: u8,
 }
 
+impl GlobalManager {
+    // Account size: discriminator + manager + bump
+    pub const SPACE: usize = 8 + 32 + 1;
+}
+
+// Pending vault authority admin rotation, closed when the new admin accepts
+#[account]
+pub struct VaultAdminProposal {
+    pub pending_admin: Pubkey,
+    pub proposed_by: Pubkey,
+    pub bump: u8,
+}
+
+impl VaultAdminProposal {
+    // Account size: discriminator + pending_admin + proposed_by + bump
+    pub const SPACE: usize = 8 + 32 + 32 + 1;
+}
+
 #[derive(Accounts)]
 pub struct CreateVaultAuthority<'info> {
     #[account(
         init,
         payer = payer,
-        space = 8 + 32 + 1 + 32,
+        space = VaultAuthority::SPACE,
         seeds = [b"vault_authority"],
         bump
     )]
@@ -38,7 +75,10 @@ pub struct CreateVaultAuthority<'info> {
     #[account(mut)]
     pub payer: Signer<'info>,
 
-    pub admin: Signer<'info>,
+    /// CHECK: Only recorded as the vault authority admin, attested by the payer. It need not
+    /// sign, so a multisig or governance PDA can be set at genesis; admin instructions still
+    /// require its signature.
+    pub admin: UncheckedAccount<'info>,
 
     pub system_program: Program<'info, System>,
 }
@@ -48,6 +88,7 @@ pub fn create_vault_authority(ctx: Context<CreateVaultAuthority>) -> Result<()>
     vault_authority.admin = ctx.accounts.admin.key();
     vault_authority.bump = ctx.bumps.vault_authority;
     vault_authority.jupiter_program_id = Pubkey::default();
+    vault_authority.max_jupiter_data_len = 0;
 
     msg!("Created vault authority: {}", vault_authority.key());
     Ok(())
@@ -58,7 +99,7 @@ pub struct CreateGlobalManager<'info> {
     #[account(
         init,
         payer = payer,
-        space = 8 + 32 + 1,
+        space = GlobalManager::SPACE,
         seeds = [b"global_manager"],
         bump
     )]
@@ -112,9 +153,34 @@ pub struct CreateVault<'info> {
     )]
     pub vault: InterfaceAccount<'info, TokenAccount>,
 
+    /// Generation of the vault address; kept across a close and re-creation
+    #[account(
+        init_if_needed,
+        payer = payer,
+        space = VaultMeta::SPACE,
+        seeds = [b"vault_meta", vault.key().as_ref()],
+        bump
+    )]
+    pub vault_meta: Account<'info, VaultMeta>,
+
     pub vault_mint: InterfaceAccount<'info, Mint>,
     pub vault_token_program: Interface<'info, TokenInterface>,
     pub system_program: Program<'info, System>,
+
+    /// CHECK: AdminLog PDA, recorded to once initialize_admin_log has created it
+    #[account(mut, seeds = [b"admin_log"], bump)]
+    pub admin_log: UncheckedAccount<'info>,
+    /// CHECK: VaultRegistry PDA, recorded to once initialize_vault_registry has created it
+    #[account(mut, seeds = [b"vault_registry"], bump)]
+    pub vault_registry: UncheckedAccount<'info>,
+}
+
+/// Binds a vault's VaultMeta on creation and emits VaultCreated. A re-created vault keeps the
+/// generation its last close left, so caches from before the close stay stale.
+fn record_vault_created(vault_meta: &mut Account<VaultMeta>, bump: u8, vault: Pubkey, mint: Pubkey) {
+    vault_meta.vault = vault;
+    vault_meta.bump = bump;
+    emit!(VaultCreated { vault, mint, vault_generation: vault_meta.vault_generation });
 }
 
 pub fn create_vault(ctx: Context<CreateVault>) -> Result<()> {
@@ -132,6 +198,16 @@ pub fn create_vault(ctx: Context<CreateVault>) -> Result<()> {
          ctx.accounts.vault.key(),
          ctx.accounts.vault_mint.key(),
          creator);
+    record_vault_created(&mut ctx.accounts.vault_meta, ctx.bumps.vault_meta, ctx.accounts.vault.key(), ctx.accounts.vault_mint.key());
+    AdminLog::record(&ctx.accounts.admin_log, ctx.program_id, AdminAction::CreateVault, creator, ctx.accounts.vault.key())?;
+    register_vault_mint(
+        &ctx.accounts.vault_registry,
+        &ctx.accounts.payer,
+        &ctx.accounts.system_program,
+        ctx.program_id,
+        ctx.accounts.vault_mint.key(),
+    )?;
+
     Ok(())
 }
 
@@ -153,7 +229,8 @@ pub struct CloseVault<'info> {
     #[account(
         mut,
         constraint = vault.owner == vault_authority.key() @ ErrorCode::InvalidVaultOwner,
-        constraint = vault.amount == 0 @ ErrorCode::VaultNotEmpty,
+        // Native (WSOL) vaults may hold a balance: closing unwraps it to destination
+        constraint = vault.amount == 0 || vault.is_native() @ ErrorCode::VaultNotEmpty,
     )]
     pub vault: InterfaceAccount<'info, TokenAccount>,
 
@@ -161,9 +238,29 @@ pub struct CloseVault<'info> {
     /// CHECK: validated by code
     pub destination: AccountInfo<'info>,
 
+    /// Pays for the VaultMeta of a vault created before VaultMeta existed
+    #[account(mut)]
     pub closer: Signer<'info>,
 
+    /// Generation of the vault address, incremented by the close
+    #[account(
+        init_if_needed,
+        payer = closer,
+        space = VaultMeta::SPACE,
+        seeds = [b"vault_meta", vault.key().as_ref()],
+        bump
+    )]
+    pub vault_meta: Account<'info, VaultMeta>,
+
     pub token_program: Interface<'info, TokenInterface>,
+    pub system_program: Program<'info, System>,
+
+    /// CHECK: AdminLog PDA, recorded to once initialize_admin_log has created it
+    #[account(mut, seeds = [b"admin_log"], bump)]
+    pub admin_log: UncheckedAccount<'info>,
+    /// CHECK: VaultRegistry PDA, recorded to once initialize_vault_registry has created it
+    #[account(mut, seeds = [b"vault_registry"], bump)]
+    pub vault_registry: UncheckedAccount<'info>,
 }
 
 pub fn close_vault(ctx: Context<CloseVault>) -> Result<()> {
@@ -185,6 +282,12 @@ pub fn close_vault(ctx: Context<CloseVault>) -> Result<()> {
     ];
     let signer_seeds = &[&authority_seeds[..]];
 
+    if ctx.accounts.vault.is_native() && ctx.accounts.vault.amount > 0 {
+        msg!("Unwrapping {} lamports from native vault to {}",
+             ctx.accounts.vault.amount,
+             ctx.accounts.destination.key());
+    }
+
     let close_account_ctx = CpiContext::new_with_signer(
         ctx.accounts.token_program.to_account_info(),
         anchor_spl::token_interface::CloseAccount {
@@ -198,6 +301,208 @@ pub fn close_vault(ctx: Context<CloseVault>) -> Result<()> {
     anchor_spl::token_interface::close_account(close_account_ctx)?;
 
     msg!("Successfully closed vault: {} by {}", ctx.accounts.vault.key(), closer);
+    let vault_meta = &mut ctx.accounts.vault_meta;
+    vault_meta.vault = ctx.accounts.vault.key();
+    vault_meta.bump = ctx.bumps.vault_meta;
+    vault_meta.vault_generation = vault_meta
+        .vault_generation
+        .checked_add(1)
+        .ok_or(ErrorCode::InvalidCalculation)?;
+    emit!(VaultClosed {
+        vault: ctx.accounts.vault.key(),
+        mint: ctx.accounts.vault.mint,
+        vault_generation: vault_meta.vault_generation,
+    });
+    AdminLog::record(&ctx.accounts.admin_log, ctx.program_id, AdminAction::CloseVault, closer, ctx.accounts.vault.key())?;
+
+    // Only the ["vault", mint] vault is listed; closing another vault authority account keeps the entry
+    let mint = ctx.accounts.vault.mint;
+    if ctx.accounts.vault.key() == get_vault_address(&mint, ctx.program_id).0 {
+        unregister_vault_mint(&ctx.accounts.vault_registry, ctx.program_id, &mint)?;
+    }
+
+    Ok(())
+}
+
+#[derive(Accounts)]
+pub struct WrapIntoVault<'info> {
+    #[account(
+        seeds = [b"vault_authority"],
+        bump = vault_authority.bump,
+        constraint = vault_authority.admin != Pubkey::default() @ ErrorCode::VaultAuthorityNotInitialized,
+        constraint = vault_authority.admin == admin.key() @ ErrorCode::UnauthorizedAdmin,
+    )]
+    pub vault_authority: Account<'info, VaultAuthority>,
+
+    pub admin: Signer<'info>,
+
+    /// Funds the wrapped lamports
+    #[account(mut)]
+    pub payer: Signer<'info>,
+
+    #[account(
+        mut,
+        seeds = [b"vault", native_mint.key().as_ref()],
+        bump,
+        token::mint = native_mint,
+        token::authority = vault_authority,
+        token::token_program = token_program,
+    )]
+    pub vault: InterfaceAccount<'info, TokenAccount>,
+
+    #[account(address = anchor_spl::token::spl_token::native_mint::ID @ ErrorCode::InvalidMint)]
+    pub native_mint: InterfaceAccount<'info, Mint>,
+
+    pub token_program: Interface<'info, TokenInterface>,
+    pub system_program: Program<'info, System>,
+
+    /// CHECK: AdminLog PDA, recorded to once initialize_admin_log has created it
+    #[account(mut, seeds = [b"admin_log"], bump)]
+    pub admin_log: UncheckedAccount<'info>,
+}
+
+/// Wraps lamports from the payer into the native mint (WSOL) vault and syncs its token balance.
+pub fn wrap_into_vault(ctx: Context<WrapIntoVault>, lamports: u64) -> Result<()> {
+    require!(lamports > 0, ErrorCode::InvalidAmount);
+
+    anchor_lang::system_program::transfer(
+        CpiContext::new(
+            ctx.accounts.system_program.to_account_info(),
+            anchor_lang::system_program::Transfer {
+                from: ctx.accounts.payer.to_account_info(),
+                to: ctx.accounts.vault.to_account_info(),
+            },
+        ),
+        lamports,
+    )?;
+
+    sync_native(CpiContext::new(
+        ctx.accounts.token_program.to_account_info(),
+        SyncNative {
+            account: ctx.accounts.vault.to_account_info(),
+        },
+    ))?;
+
+    msg!("Wrapped {} lamports into native vault {} from {}",
+         lamports,
+         ctx.accounts.vault.key(),
+         ctx.accounts.payer.key());
+    AdminLog::record(&ctx.accounts.admin_log, ctx.program_id, AdminAction::WrapIntoVault, ctx.accounts.admin.key(), ctx.accounts.vault.key())?;
+
+    Ok(())
+}
+
+#[derive(Accounts)]
+pub struct UnwrapFromVault<'info> {
+    #[account(
+        seeds = [b"vault_authority"],
+        bump = vault_authority.bump,
+        constraint = vault_authority.admin != Pubkey::default() @ ErrorCode::VaultAuthorityNotInitialized,
+        constraint = vault_authority.admin == admin.key() @ ErrorCode::UnauthorizedAdmin,
+    )]
+    pub vault_authority: Account<'info, VaultAuthority>,
+
+    pub admin: Signer<'info>,
+
+    /// Pays the rent of the temporary unwrap account and gets it back in the same instruction
+    #[account(mut)]
+    pub payer: Signer<'info>,
+
+    #[account(
+        mut,
+        seeds = [b"vault", native_mint.key().as_ref()],
+        bump,
+        token::mint = native_mint,
+        token::authority = vault_authority,
+        token::token_program = token_program,
+    )]
+    pub vault: InterfaceAccount<'info, TokenAccount>,
+
+    /// Temporary WSOL account the amount is moved to and closed from, since a native
+    /// account only releases its lamports when closed
+    #[account(
+        init,
+        payer = payer,
+        seeds = [b"unwrap_account"],
+        bump,
+        token::mint = native_mint,
+        token::authority = vault_authority,
+        token::token_program = token_program,
+    )]
+    pub unwrap_account: InterfaceAccount<'info, TokenAccount>,
+
+    #[account(mut)]
+    /// CHECK: Any writable account; only receives lamports
+    pub destination: AccountInfo<'info>,
+
+    #[account(address = anchor_spl::token::spl_token::native_mint::ID @ ErrorCode::InvalidMint)]
+    pub native_mint: InterfaceAccount<'info, Mint>,
+
+    pub token_program: Interface<'info, TokenInterface>,
+    pub system_program: Program<'info, System>,
+
+    /// CHECK: AdminLog PDA, recorded to once initialize_admin_log has created it
+    #[account(mut, seeds = [b"admin_log"], bump)]
+    pub admin_log: UncheckedAccount<'info>,
+}
+
+/// Unwraps `amount` lamports from the native mint (WSOL) vault to the destination.
+pub fn unwrap_from_vault(ctx: Context<UnwrapFromVault>, amount: u64) -> Result<()> {
+    require!(amount > 0, ErrorCode::InvalidAmount);
+    require!(ctx.accounts.vault.amount >= amount, ErrorCode::InsufficientFunds);
+
+    let vault_authority_bump = ctx.accounts.vault_authority.bump;
+    let authority_seeds = [
+        b"vault_authority".as_ref(),
+        &[vault_authority_bump],
+    ];
+    let signer_seeds = &[&authority_seeds[..]];
+
+    transfer_checked(
+        CpiContext::new_with_signer(
+            ctx.accounts.token_program.to_account_info(),
+            TransferChecked {
+                from: ctx.accounts.vault.to_account_info(),
+                to: ctx.accounts.unwrap_account.to_account_info(),
+                authority: ctx.accounts.vault_authority.to_account_info(),
+                mint: ctx.accounts.native_mint.to_account_info(),
+            },
+            signer_seeds,
+        ),
+        amount,
+        ctx.accounts.native_mint.decimals,
+    )?;
+
+    // Closing releases the amount plus the rent the payer put up; the payer keeps the rent
+    anchor_spl::token_interface::close_account(CpiContext::new_with_signer(
+        ctx.accounts.token_program.to_account_info(),
+        anchor_spl::token_interface::CloseAccount {
+            account: ctx.accounts.unwrap_account.to_account_info(),
+            destination: ctx.accounts.payer.to_account_info(),
+            authority: ctx.accounts.vault_authority.to_account_info(),
+        },
+        signer_seeds,
+    ))?;
+
+    if ctx.accounts.destination.key() != ctx.accounts.payer.key() {
+        anchor_lang::system_program::transfer(
+            CpiContext::new(
+                ctx.accounts.system_program.to_account_info(),
+                anchor_lang::system_program::Transfer {
+                    from: ctx.accounts.payer.to_account_info(),
+                    to: ctx.accounts.destination.to_account_info(),
+                },
+            ),
+            amount,
+        )?;
+    }
+
+    msg!("Unwrapped {} lamports from native vault {} to {}",
+         amount,
+         ctx.accounts.vault.key(),
+         ctx.accounts.destination.key());
+    AdminLog::record(&ctx.accounts.admin_log, ctx.program_id, AdminAction::UnwrapFromVault, ctx.accounts.admin.key(), ctx.accounts.vault.key())?;
+
     Ok(())
 }
 
@@ -213,6 +518,19 @@ pub fn initialize_vaults(ctx: Context<InitializeVaults>) -> Result<()> {
     require!(is_authorized, ErrorCode::UnauthorizedVaultCreator);
 
     msg!("Vaults initialized successfully by {}", creator);
+    record_vault_created(&mut ctx.accounts.input_vault_meta, ctx.bumps.input_vault_meta, ctx.accounts.input_vault.key(), ctx.accounts.source_mint.key());
+    record_vault_created(&mut ctx.accounts.output_vault_meta, ctx.bumps.output_vault_meta, ctx.accounts.output_vault.key(), ctx.accounts.destination_mint.key());
+    AdminLog::record(&ctx.accounts.admin_log, ctx.program_id, AdminAction::InitializeVaults, creator, ctx.accounts.input_vault.key())?;
+    for mint in [ctx.accounts.source_mint.key(), ctx.accounts.destination_mint.key()] {
+        register_vault_mint(
+            &ctx.accounts.vault_registry,
+            &ctx.accounts.payer,
+            &ctx.accounts.system_program,
+            ctx.program_id,
+            mint,
+        )?;
+    }
+
     Ok(())
 }
 
@@ -258,17 +576,41 @@ pub struct InitializeVaults<'info> {
     )]
     pub output_vault: InterfaceAccount<'info, TokenAccount>,
 
+    /// Generations of the two vault addresses, as for create_vault
+    #[account(
+        init_if_needed,
+        payer = payer,
+        space = VaultMeta::SPACE,
+        seeds = [b"vault_meta", input_vault.key().as_ref()],
+        bump
+    )]
+    pub input_vault_meta: Box<Account<'info, VaultMeta>>,
+    #[account(
+        init_if_needed,
+        payer = payer,
+        space = VaultMeta::SPACE,
+        seeds = [b"vault_meta", output_vault.key().as_ref()],
+        bump
+    )]
+    pub output_vault_meta: Box<Account<'info, VaultMeta>>,
+
     pub source_mint: InterfaceAccount<'info, Mint>,
     pub destination_mint: InterfaceAccount<'info, Mint>,
     pub source_token_program: Interface<'info, TokenInterface>,
     pub destination_token_program: Interface<'info, TokenInterface>,
     pub system_program: Program<'info, System>,
+
+    /// CHECK: AdminLog PDA, recorded to once initialize_admin_log has created it
+    #[account(mut, seeds = [b"admin_log"], bump)]
+    pub admin_log: UncheckedAccount<'info>,
+    /// CHECK: VaultRegistry PDA, recorded to once initialize_vault_registry has created it
+    #[account(mut, seeds = [b"vault_registry"], bump)]
+    pub vault_registry: UncheckedAccount<'info>,
 }
 
 #[derive(Accounts)]
 pub struct ChangeVaultAuthorityAdmin<'info> {
     #[account(
-        mut,
         seeds = [b"vault_authority"],
         bump = vault_authority.bump,
         constraint = vault_authority.admin != Pubkey::default() @ ErrorCode::VaultAuthorityNotInitialized,
@@ -283,35 +625,106 @@ pub struct ChangeVaultAuthorityAdmin<'info> {
     )]
     pub global_manager: Account<'info, GlobalManager>,
 
+    #[account(mut)]
     pub manager: Signer<'info>,
 
-    ///CHECK: will be trusted
+    /// CHECK: Only recorded as the pending admin; it must sign accept_vault_authority_admin
+    #[account(
+        constraint = new_admin.key() != Pubkey::default() @ ErrorCode::InvalidVaultAdmin
+    )]
     pub new_admin: AccountInfo<'info>,
+
+    #[account(
+        init_if_needed,
+        payer = manager,
+        space = VaultAdminProposal::SPACE,
+        seeds = [b"vault_admin_proposal"],
+        bump
+    )]
+    pub admin_proposal: Account<'info, VaultAdminProposal>,
+
+    pub system_program: Program<'info, System>,
+
+    /// CHECK: AdminLog PDA, recorded to once initialize_admin_log has created it
+    #[account(mut, seeds = [b"admin_log"], bump)]
+    pub admin_log: UncheckedAccount<'info>,
 }
 
+/// Proposes a new vault authority admin; the current admin keeps its powers until
+/// the proposed admin signs accept_vault_authority_admin. A new proposal replaces a pending one.
 pub fn change_vault_authority_admin(ctx: Context<ChangeVaultAuthorityAdmin>) -> Result<()> {
+    let admin_proposal = &mut ctx.accounts.admin_proposal;
+    admin_proposal.pending_admin = ctx.accounts.new_admin.key();
+    admin_proposal.proposed_by = ctx.accounts.manager.key();
+    admin_proposal.bump = ctx.bumps.admin_proposal;
+
+    emit!(crate::state::VaultAuthorityAdminProposed {
+        current_admin: ctx.accounts.vault_authority.admin,
+        pending_admin: admin_proposal.pending_admin,
+        proposed_by: admin_proposal.proposed_by,
+    });
+
+    msg!("Proposed vault authority admin {} by global manager {}",
+         admin_proposal.pending_admin,
+         admin_proposal.proposed_by);
+    AdminLog::record(&ctx.accounts.admin_log, ctx.program_id, AdminAction::ProposeVaultAuthorityAdmin, ctx.accounts.manager.key(), ctx.accounts.new_admin.key())?;
+
+    Ok(())
+}
+
+#[derive(Accounts)]
+pub struct AcceptVaultAuthorityAdmin<'info> {
+    #[account(
+        mut,
+        seeds = [b"vault_authority"],
+        bump = vault_authority.bump,
+        constraint = vault_authority.admin != Pubkey::default() @ ErrorCode::VaultAuthorityNotInitialized,
+    )]
+    pub vault_authority: Account<'info, VaultAuthority>,
+
+    #[account(
+        mut,
+        seeds = [b"vault_admin_proposal"],
+        bump = admin_proposal.bump,
+        constraint = admin_proposal.pending_admin == new_admin.key() @ ErrorCode::UnauthorizedAdmin,
+        close = new_admin
+    )]
+    pub admin_proposal: Account<'info, VaultAdminProposal>,
+
+    #[account(mut)]
+    pub new_admin: Signer<'info>,
+
+    /// CHECK: AdminLog PDA, recorded to once initialize_admin_log has created it
+    #[account(mut, seeds = [b"admin_log"], bump)]
+    pub admin_log: UncheckedAccount<'info>,
+}
+
+/// Completes an admin rotation started by change_vault_authority_admin.
+/// The proposal account is closed, returning its rent to the new admin.
+pub fn accept_vault_authority_admin(ctx: Context<AcceptVaultAuthorityAdmin>) -> Result<()> {
     let vault_authority = &mut ctx.accounts.vault_authority;
     let old_admin = vault_authority.admin;
     vault_authority.admin = ctx.accounts.new_admin.key();
 
     emit!(crate::state::VaultAuthorityAdminChanged {
         old_admin,
-        new_admin: ctx.accounts.new_admin.key(),
-        changed_by: ctx.accounts.manager.key(),
+        new_admin: vault_authority.admin,
+        changed_by: ctx.accounts.admin_proposal.proposed_by,
     });
 
-    msg!("Changed vault authority admin from {} to {} by global manager {}", 
-         old_admin, 
-         vault_authority.admin,
-         ctx.accounts.manager.key());
+    msg!("Changed vault authority admin from {} to {}",
+         old_admin,
+         vault_authority.admin);
+    AdminLog::record(&ctx.accounts.admin_log, ctx.program_id, AdminAction::AcceptVaultAuthorityAdmin, ctx.accounts.new_admin.key(), ctx.accounts.vault_authority.key())?;
+
     Ok(())
 }
 
 #[derive(Accounts)]
 pub struct MigrateVaultAuthority<'info> {
     /// CHECK: Manually validated in handler. Cannot use Account<VaultAuthority> because
-    /// the on-chain account uses the old layout (41 bytes, without jupiter_program_id)
-    /// and Anchor cannot deserialize it into the new 73-byte VaultAuthority struct.
+    /// the on-chain account may use an old layout (41 bytes without jupiter_program_id, or
+    /// 73 bytes without max_jupiter_data_len) that Anchor cannot deserialize into VaultAuthority.
     #[account(
         mut,
         seeds = [b"vault_authority"],
@@ -348,10 +761,11 @@ pub fn migrate_vault_authority(ctx: Context<MigrateVaultAuthority>, jupiter_prog
     let stored_admin = Pubkey::from(admin_bytes);
     require!(stored_admin == ctx.accounts.admin.key(), ErrorCode::UnauthorizedAdmin);
 
+    let old_size = data.len();
     drop(data); // Release borrow before realloc
 
-    // Realloc: 8 (discriminator) + 32 (admin) + 1 (bump) + 32 (jupiter_program_id) = 73
-    let new_size: usize = 8 + 32 + 1 + 32;
+    // Realloc: 8 (discriminator) + 32 (admin) + 1 (bump) + 32 (jupiter_program_id) + 2 (max_jupiter_data_len) = 75
+    let new_size: usize = VaultAuthority::SPACE;
     let rent = Rent::get()?;
     let new_minimum_balance = rent.minimum_balance(new_size);
     let lamports_diff = new_minimum_balance.saturating_sub(vault_authority.lamports());
@@ -378,6 +792,10 @@ pub fn migrate_vault_authority(ctx: Context<MigrateVaultAuthority>, jupiter_prog
     let bump = ctx.bumps.vault_authority;
     data[40] = bump;
     data[41..73].copy_from_slice(jupiter_program_id.as_ref());
+    // A configured data limit survives re-running the migration; older layouts get the default
+    if old_size < new_size {
+        data[73..75].fill(0);
+    }
 
     msg!("Migrated vault authority. Jupiter program: {}", jupiter_program_id);
     Ok(())
@@ -402,11 +820,60 @@ pub struct SetJupiterProgram<'info> {
     pub global_manager: Account<'info, GlobalManager>,
 
     pub manager: Signer<'info>,
+
+    /// CHECK: AdminLog PDA, recorded to once initialize_admin_log has created it
+    #[account(mut, seeds = [b"admin_log"], bump)]
+    pub admin_log: UncheckedAccount<'info>,
 }
 
 pub fn set_jupiter_program(ctx: Context<SetJupiterProgram>, jupiter_program_id: Pubkey) -> Result<()> {
     ctx.accounts.vault_authority.jupiter_program_id = jupiter_program_id;
     msg!("Set Jupiter program ID: {}", jupiter_program_id);
+    AdminLog::record(&ctx.accounts.admin_log, ctx.program_id, AdminAction::SetJupiterProgram, ctx.accounts.manager.key(), jupiter_program_id)?;
+
+    Ok(())
+}
+
+#[derive(Accounts)]
+pub struct SetJupiterDataLimit<'info> {
+    #[account(
+        mut,
+        seeds = [b"vault_authority"],
+        bump = vault_authority.bump,
+        constraint = vault_authority.admin != Pubkey::default() @ ErrorCode::VaultAuthorityNotInitialized,
+    )]
+    pub vault_authority: Account<'info, VaultAuthority>,
+
+    #[account(
+        seeds = [b"global_manager"],
+        bump = global_manager.bump,
+        constraint = global_manager.manager != Pubkey::default() @ ErrorCode::GlobalManagerNotInitialized,
+        constraint = global_manager.manager == manager.key() @ ErrorCode::UnauthorizedGlobalManager
+    )]
+    pub global_manager: Account<'info, GlobalManager>,
+
+    pub manager: Signer<'info>,
+
+    /// CHECK: AdminLog PDA, recorded to once initialize_admin_log has created it
+    #[account(mut, seeds = [b"admin_log"], bump)]
+    pub admin_log: UncheckedAccount<'info>,
+}
+
+/// Checks a max_jupiter_data_len value: 0 restores the default, anything else must fit in one packet
+pub fn validate_jupiter_data_limit(max_len: u16) -> Result<()> {
+    require!(
+        max_len == 0 || (max_len as usize >= JUPITER_MIN_DATA_LEN && max_len <= JUPITER_DATA_HARD_CAP),
+        ErrorCode::InvalidJupiterDataLimit
+    );
+    Ok(())
+}
+
+pub fn set_jupiter_data_limit(ctx: Context<SetJupiterDataLimit>, max_len: u16) -> Result<()> {
+    validate_jupiter_data_limit(max_len)?;
+    ctx.accounts.vault_authority.max_jupiter_data_len = max_len;
+    msg!("Set Jupiter data limit: {}", ctx.accounts.vault_authority.jupiter_data_limit());
+    AdminLog::record(&ctx.accounts.admin_log, ctx.program_id, AdminAction::SetJupiterDataLimit, ctx.accounts.manager.key(), ctx.accounts.vault_authority.key())?;
+
     Ok(())
 }
 
@@ -446,6 +913,10 @@ pub struct ChangeGlobalManager<'info> {
 
     ///CHECK: will be trusted
     pub new_manager: AccountInfo<'info>,
+
+    /// CHECK: AdminLog PDA, recorded to once initialize_admin_log has created it
+    #[account(mut, seeds = [b"admin_log"], bump)]
+    pub admin_log: UncheckedAccount<'info>,
 }
 
 pub fn change_global_manager(ctx: Context<ChangeGlobalManager>) -> Result<()> {
@@ -461,6 +932,8 @@ pub fn change_global_manager(ctx: Context<ChangeGlobalManager>) -> Result<()> {
     msg!("Changed global manager from {} to {}", 
          old_manager, 
          global_manager.manager);
+    AdminLog::record(&ctx.accounts.admin_log, ctx.program_id, AdminAction::ChangeGlobalManager, ctx.accounts.current_manager.key(), ctx.accounts.new_manager.key())?;
+
     Ok(())
 }
 
@@ -508,6 +981,10 @@ pub struct WithdrawPlatformFees<'info> {
     pub mint: InterfaceAccount<'info, Mint>,
     pub manager: Signer<'info>,
     pub token_program: Interface<'info, TokenInterface>,
+
+    /// CHECK: AdminLog PDA, recorded to once initialize_admin_log has created it
+    #[account(mut, seeds = [b"admin_log"], bump)]
+    pub admin_log: UncheckedAccount<'info>,
 }
 
 pub fn withdraw_platform_fees(ctx: Context<WithdrawPlatformFees>, amount: u64) -> Result<()> {
@@ -541,6 +1018,8 @@ pub fn withdraw_platform_fees(ctx: Context<WithdrawPlatformFees>, amount: u64) -
          ctx.accounts.manager.key(),
          amount, 
          ctx.accounts.destination.key());
+    AdminLog::record(&ctx.accounts.admin_log, ctx.program_id, AdminAction::WithdrawPlatformFees, ctx.accounts.manager.key(), ctx.accounts.platform_fee_account.key())?;
+
     Ok(())
 }
 
@@ -582,6 +1061,16 @@ pub struct CreateVaultWithExtensions<'info> {
     #[account(mut)]
     pub vault: AccountInfo<'info>,
 
+    /// Generation of the vault address; kept across a close and re-creation
+    #[account(
+        init_if_needed,
+        payer = payer,
+        space = VaultMeta::SPACE,
+        seeds = [b"vault_meta", vault.key().as_ref()],
+        bump
+    )]
+    pub vault_meta: Account<'info, VaultMeta>,
+
     #[account(
         constraint = vault_mint.to_account_info().owner == &vault_token_program.key() @ ErrorCode::InvalidCpiInterface
     )]
@@ -589,6 +1078,13 @@ pub struct CreateVaultWithExtensions<'info> {
     pub vault_token_program: Interface<'info, TokenInterface>,
     pub system_program: Program<'info, System>,
     pub rent: Sysvar<'info, Rent>,
+
+    /// CHECK: AdminLog PDA, recorded to once initialize_admin_log has created it
+    #[account(mut, seeds = [b"admin_log"], bump)]
+    pub admin_log: UncheckedAccount<'info>,
+    /// CHECK: VaultRegistry PDA, recorded to once initialize_vault_registry has created it
+    #[account(mut, seeds = [b"vault_registry"], bump)]
+    pub vault_registry: UncheckedAccount<'info>,
 }
 
 pub fn create_vault_with_extensions(ctx: Context<CreateVaultWithExtensions>, account_space: u16) -> Result<()> {
@@ -678,5 +1174,362 @@ pub fn create_vault_with_extensions(ctx: Context<CreateVaultWithExtensions>, acc
         total_size,
         creator
     );
+    record_vault_created(&mut ctx.accounts.vault_meta, ctx.bumps.vault_meta, ctx.accounts.vault.key(), mint_key);
+    AdminLog::record(&ctx.accounts.admin_log, ctx.program_id, AdminAction::CreateVaultWithExtensions, creator, ctx.accounts.vault.key())?;
+    register_vault_mint(
+        &ctx.accounts.vault_registry,
+        &ctx.accounts.payer,
+        &ctx.accounts.system_program,
+        ctx.program_id,
+        mint_key,
+    )?;
+
+    Ok(())
+}
+
+#[derive(Accounts)]
+pub struct RevokeVaultDelegations<'info> {
+    #[account(
+        seeds = [b"vault_authority"],
+        bump = vault_authority.bump,
+        constraint = vault_authority.admin != Pubkey::default() @ ErrorCode::VaultAuthorityNotInitialized,
+    )]
+    pub vault_authority: Account<'info, VaultAuthority>,
+
+    #[account(
+        seeds = [b"adapter_registry"],
+        bump = adapter_registry.bump,
+    )]
+    pub adapter_registry: Account<'info, crate::state::AdapterRegistry>,
+
+    pub caller: Signer<'info>,
+
+    /// Token program owning every vault passed in remaining_accounts
+    pub token_program: Interface<'info, TokenInterface>,
+
+    /// CHECK: AdminLog PDA, recorded to once initialize_admin_log has created it
+    #[account(mut, seeds = [b"admin_log"], bump)]
+    pub admin_log: UncheckedAccount<'info>,
+}
+
+/// Checks a vault is owned by the vault authority and reports whether it has a delegate to revoke
+pub fn vault_needs_revoke(vault_data: &[u8], vault_authority: &Pubkey) -> Result<bool> {
+    let vault = TokenAccount::try_deserialize(&mut &vault_data[..])
+        .map_err(|_| ErrorCode::InvalidAccount)?;
+    if vault.owner != *vault_authority {
+        return Err(ErrorCode::InvalidVaultOwner.into());
+    }
+    Ok(vault.delegate.is_some())
+}
+
+/// Revokes any token delegation on the vaults passed in remaining_accounts (all writable,
+/// all owned by token_program). Vaults without a delegate are skipped. Admin or operator only.
+pub fn revoke_vault_delegations<'info>(
+    ctx: Context<'_, '_, 'info, 'info, RevokeVaultDelegations<'info>>,
+) -> Result<()> {
+    let adapter_registry = &ctx.accounts.adapter_registry;
+    let caller = ctx.accounts.caller.key();
+    let vault_authority_admin = ctx.accounts.vault_authority.admin;
+
+    // Check if caller is vault authority admin or an operator
+    let is_authorized = caller == vault_authority_admin
+        || adapter_registry.is_authorized_operator(&caller);
+
+    require!(is_authorized, ErrorCode::UnauthorizedVaultCreator);
+    require!(!ctx.remaining_accounts.is_empty(), ErrorCode::NotEnoughAccountKeys);
+
+    let vault_authority_bump = ctx.accounts.vault_authority.bump;
+    let authority_seeds = [
+        b"vault_authority".as_ref(),
+        &[vault_authority_bump],
+    ];
+    let signer_seeds = &[&authority_seeds[..]];
+
+    let vault_authority_key = ctx.accounts.vault_authority.key();
+    let mut revoked = 0u32;
+    for vault in ctx.remaining_accounts.iter() {
+        if vault.owner != &ctx.accounts.token_program.key() {
+            return Err(ErrorCode::InvalidAccount.into());
+        }
+
+        let needs_revoke = vault_needs_revoke(&vault.try_borrow_data()?, &vault_authority_key)?;
+        if !needs_revoke {
+            continue;
+        }
+
+        revoke(CpiContext::new_with_signer(
+            ctx.accounts.token_program.to_account_info(),
+            Revoke {
+                source: vault.to_account_info(),
+                authority: ctx.accounts.vault_authority.to_account_info(),
+            },
+            signer_seeds,
+        ))?;
+        revoked += 1;
+    }
+
+    msg!("Revoked delegations on {} of {} vaults by {}",
+         revoked,
+         ctx.remaining_accounts.len(),
+         caller);
+    AdminLog::record(&ctx.accounts.admin_log, ctx.program_id, AdminAction::RevokeVaultDelegations, caller, ctx.accounts.vault_authority.key())?;
+
+    Ok(())
+}
+
+/// Adds `mint` to the VaultRegistry once initialize_vault_registry has created it; before that
+/// the ["vault_registry"] PDA is an empty system account and nothing is recorded. A full
+/// registry grows by one entry, with the payer covering the extra rent.
+pub fn register_vault_mint<'info>(
+    vault_registry: &AccountInfo<'info>,
+    payer: &AccountInfo<'info>,
+    system_program: &AccountInfo<'info>,
+    program_id: &Pubkey,
+    mint: Pubkey,
+) -> Result<()> {
+    if vault_registry.owner != program_id || vault_registry.data_is_empty() {
+        return Ok(());
+    }
+
+    let mut registry = VaultRegistry::try_deserialize(&mut &vault_registry.try_borrow_data()?[..])?;
+    if !registry.add(mint) {
+        return Ok(());
+    }
+
+    let required_space = VaultRegistry::space(registry.mints.len());
+    if vault_registry.data_len() < required_space {
+        let rent_due = Rent::get()?
+            .minimum_balance(required_space)
+            .saturating_sub(vault_registry.lamports());
+        if rent_due > 0 {
+            anchor_lang::system_program::transfer(
+                CpiContext::new(
+                    system_program.clone(),
+                    anchor_lang::system_program::Transfer {
+                        from: payer.clone(),
+                        to: vault_registry.clone(),
+                    },
+                ),
+                rent_due,
+            )?;
+        }
+        vault_registry.resize(required_space)?;
+    }
+
+    registry.try_serialize(&mut &mut vault_registry.try_borrow_mut_data()?[..])?;
+    Ok(())
+}
+
+/// Removes `mint` from the VaultRegistry if it has been created. The account keeps its size,
+/// so the next vault takes the freed room without reallocating.
+pub fn unregister_vault_mint(vault_registry: &AccountInfo, program_id: &Pubkey, mint: &Pubkey) -> Result<()> {
+    if vault_registry.owner != program_id || vault_registry.data_is_empty() {
+        return Ok(());
+    }
+
+    let mut data = vault_registry.try_borrow_mut_data()?;
+    let mut registry = VaultRegistry::try_deserialize(&mut &data[..])?;
+    if registry.remove(mint) {
+        registry.try_serialize(&mut &mut data[..])?;
+    }
+    Ok(())
+}
+
+#[derive(Accounts)]
+pub struct InitializeVaultRegistry<'info> {
+    #[account(
+        seeds = [b"vault_authority"],
+        bump = vault_authority.bump,
+        constraint = vault_authority.admin != Pubkey::default() @ ErrorCode::VaultAuthorityNotInitialized,
+    )]
+    pub vault_authority: Account<'info, VaultAuthority>,
+
+    #[account(
+        seeds = [b"adapter_registry"],
+        bump = adapter_registry.bump,
+    )]
+    pub adapter_registry: Account<'info, crate::state::AdapterRegistry>,
+
+    #[account(
+        init,
+        payer = payer,
+        space = VaultRegistry::space(0),
+        seeds = [b"vault_registry"],
+        bump
+    )]
+    pub vault_registry: Account<'info, VaultRegistry>,
+
+    #[account(mut)]
+    pub payer: Signer<'info>,
+
+    pub creator: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+
+    /// CHECK: AdminLog PDA, recorded to once initialize_admin_log has created it
+    #[account(mut, seeds = [b"admin_log"], bump)]
+    pub admin_log: UncheckedAccount<'info>,
+}
+
+/// Creates the empty VaultRegistry. Vault creation and closure keep it current from then on;
+/// vaults that already exist are listed with register_existing_vaults. Admin or operator only.
+pub fn initialize_vault_registry(ctx: Context<InitializeVaultRegistry>) -> Result<()> {
+    let adapter_registry = &ctx.accounts.adapter_registry;
+    let creator = ctx.accounts.creator.key();
+    let vault_authority_admin = ctx.accounts.vault_authority.admin;
+
+    // Check if creator is vault authority admin or an operator
+    let is_authorized = creator == vault_authority_admin
+        || adapter_registry.is_authorized_operator(&creator);
+
+    require!(is_authorized, ErrorCode::UnauthorizedVaultCreator);
+
+    let vault_registry = &mut ctx.accounts.vault_registry;
+    vault_registry.mints = Vec::new();
+    vault_registry.bump = ctx.bumps.vault_registry;
+
+    msg!("Created vault registry: {} by {}", vault_registry.key(), creator);
+    AdminLog::record(&ctx.accounts.admin_log, ctx.program_id, AdminAction::InitializeVaultRegistry, creator, vault_registry.key())?;
+
+    Ok(())
+}
+
+#[derive(Accounts)]
+pub struct RegisterExistingVaults<'info> {
+    #[account(
+        seeds = [b"vault_authority"],
+        bump = vault_authority.bump,
+        constraint = vault_authority.admin != Pubkey::default() @ ErrorCode::VaultAuthorityNotInitialized,
+    )]
+    pub vault_authority: Account<'info, VaultAuthority>,
+
+    #[account(
+        seeds = [b"adapter_registry"],
+        bump = adapter_registry.bump,
+    )]
+    pub adapter_registry: Account<'info, crate::state::AdapterRegistry>,
+
+    #[account(
+        mut,
+        seeds = [b"vault_registry"],
+        bump = vault_registry.bump,
+    )]
+    pub vault_registry: Account<'info, VaultRegistry>,
+
+    #[account(mut)]
+    pub payer: Signer<'info>,
+
+    pub caller: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+
+    /// CHECK: AdminLog PDA, recorded to once initialize_admin_log has created it
+    #[account(mut, seeds = [b"admin_log"], bump)]
+    pub admin_log: UncheckedAccount<'info>,
+}
+
+/// Reads the mint of a vault passed to register_existing_vaults, which must be the
+/// ["vault", mint] token account of that mint owned by the vault authority
+pub fn existing_vault_mint(vault: &AccountInfo, vault_authority: &Pubkey, program_id: &Pubkey) -> Result<Pubkey> {
+    if vault.owner != &anchor_spl::token::ID && vault.owner != &TOKEN_2022_PROGRAM_ID {
+        return Err(ErrorCode::InvalidAccount.into());
+    }
+    let token_account = TokenAccount::try_deserialize(&mut &vault.try_borrow_data()?[..])
+        .map_err(|_| ErrorCode::InvalidAccount)?;
+    if token_account.owner != *vault_authority {
+        return Err(ErrorCode::InvalidVaultOwner.into());
+    }
+    if vault.key() != get_vault_address(&token_account.mint, program_id).0 {
+        return Err(ErrorCode::InvalidVaultAddress.into());
+    }
+    Ok(token_account.mint)
+}
+
+/// Lists the vaults passed in remaining_accounts that predate the VaultRegistry (or were missed),
+/// growing the registry as needed. Already listed vaults are skipped. Admin or operator only.
+pub fn register_existing_vaults<'info>(
+    ctx: Context<'_, '_, 'info, 'info, RegisterExistingVaults<'info>>,
+) -> Result<()> {
+    let adapter_registry = &ctx.accounts.adapter_registry;
+    let caller = ctx.accounts.caller.key();
+    let vault_authority_admin = ctx.accounts.vault_authority.admin;
+
+    // Check if caller is vault authority admin or an operator
+    let is_authorized = caller == vault_authority_admin
+        || adapter_registry.is_authorized_operator(&caller);
+
+    require!(is_authorized, ErrorCode::UnauthorizedVaultCreator);
+    require!(!ctx.remaining_accounts.is_empty(), ErrorCode::NotEnoughAccountKeys);
+
+    let vault_authority_key = ctx.accounts.vault_authority.key();
+    let mut added = 0u32;
+    for vault in ctx.remaining_accounts.iter() {
+        let mint = existing_vault_mint(vault, &vault_authority_key, ctx.program_id)?;
+        if ctx.accounts.vault_registry.add(mint) {
+            added += 1;
+        }
+    }
+
+    // Grow the account before Anchor serializes the registry back into it
+    let required_space = VaultRegistry::space(ctx.accounts.vault_registry.mints.len());
+    let registry_info = ctx.accounts.vault_registry.to_account_info();
+    if registry_info.data_len() < required_space {
+        let rent_due = Rent::get()?
+            .minimum_balance(required_space)
+            .saturating_sub(registry_info.lamports());
+        if rent_due > 0 {
+            anchor_lang::system_program::transfer(
+                CpiContext::new(
+                    ctx.accounts.system_program.to_account_info(),
+                    anchor_lang::system_program::Transfer {
+                        from: ctx.accounts.payer.to_account_info(),
+                        to: registry_info.clone(),
+                    },
+                ),
+                rent_due,
+            )?;
+        }
+        registry_info.resize(required_space)?;
+    }
+
+    msg!("Registered {} of {} existing vaults by {}",
+         added,
+         ctx.remaining_accounts.len(),
+         caller);
+    AdminLog::record(&ctx.accounts.admin_log, ctx.program_id, AdminAction::RegisterExistingVaults, caller, registry_info.key())?;
+
     Ok(())
-}
\ No newline at end of file
+}
+
+#[derive(Accounts)]
+pub struct ListVaults<'info> {
+    #[account(seeds = [b"vault_registry"], bump = vault_registry.bump)]
+    pub vault_registry: Account<'info, VaultRegistry>,
+}
+
+/// Returns the mints of up to `limit` registered vaults starting at `offset`, capped at
+/// VAULT_REGISTRY_PAGE_SIZE. A page shorter than requested is the last one.
+pub fn list_vaults(ctx: Context<ListVaults>, offset: u32, limit: u8) -> Result<Vec<Pubkey>> {
+    Ok(ctx.accounts.vault_registry.page(offset as usize, limit as usize))
+}
+
+#[derive(Accounts)]
+pub struct AssertVaultGeneration<'info> {
+    /// CHECK: Vault address the client cached; it may be closed
+    pub vault: UncheckedAccount<'info>,
+    /// CHECK: ["vault_meta", vault] PDA; generation 0 until the vault is first closed
+    #[account(seeds = [b"vault_meta", vault.key().as_ref()], bump)]
+    pub vault_meta: UncheckedAccount<'info>,
+}
+
+/// Fails with VaultGenerationMismatch unless the vault is still at `expected_generation`.
+/// Clients that cache vault addresses put it ahead of a route in the same transaction, so a
+/// vault closed (and maybe re-created empty) since they cached it aborts the route.
+pub fn assert_vault_generation(ctx: Context<AssertVaultGeneration>, expected_generation: u32) -> Result<()> {
+    let generation = VaultMeta::load_generation(&ctx.accounts.vault_meta, ctx.program_id)?;
+    if generation != expected_generation {
+        msg!("Vault {} is at generation {}, expected {}", ctx.accounts.vault.key(), generation, expected_generation);
+        return Err(ErrorCode::VaultGenerationMismatch.into());
+    }
+    Ok(())
+}
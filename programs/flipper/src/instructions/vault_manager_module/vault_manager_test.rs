@@ -1,6 +1,7 @@
 #[cfg(test)]
 mod tests {
     use super::super::*;
+    use crate::errors::ErrorCode;
     use anchor_lang::prelude::*;
 
     #[test]
@@ -67,6 +68,7 @@ mod tests {
             admin: Pubkey::new_unique(),
             bump: 255,
             jupiter_program_id: Pubkey::default(),
+            max_jupiter_data_len: 0,
         };
 
         assert_ne!(vault_authority.admin, Pubkey::default());
@@ -80,12 +82,41 @@ mod tests {
             admin: Pubkey::default(),
             bump: 128,
             jupiter_program_id: Pubkey::default(),
+            max_jupiter_data_len: 0,
         };
 
         assert_eq!(vault_authority.admin, Pubkey::default());
         assert_eq!(vault_authority.bump, 128);
     }
 
+    #[test]
+    fn test_jupiter_data_limit_defaults_when_unset() {
+        let mut vault_authority = VaultAuthority {
+            admin: Pubkey::new_unique(),
+            bump: 255,
+            jupiter_program_id: Pubkey::new_unique(),
+            max_jupiter_data_len: 0,
+        };
+        assert_eq!(vault_authority.jupiter_data_limit(), DEFAULT_MAX_JUPITER_DATA_LEN as usize);
+
+        vault_authority.max_jupiter_data_len = 600;
+        assert_eq!(vault_authority.jupiter_data_limit(), 600);
+    }
+
+    #[test]
+    fn test_validate_jupiter_data_limit_boundaries() {
+        assert!(validate_jupiter_data_limit(0).is_ok());
+        assert!(validate_jupiter_data_limit(JUPITER_MIN_DATA_LEN as u16).is_ok());
+        assert!(validate_jupiter_data_limit(JUPITER_DATA_HARD_CAP).is_ok());
+
+        for max_len in [1, JUPITER_MIN_DATA_LEN as u16 - 1, JUPITER_DATA_HARD_CAP + 1, u16::MAX] {
+            assert_eq!(
+                validate_jupiter_data_limit(max_len).unwrap_err(),
+                ErrorCode::InvalidJupiterDataLimit.into()
+            );
+        }
+    }
+
     #[test]
     fn test_vault_address_consistency() {
         let mint = Pubkey::new_unique();
@@ -199,4 +230,124 @@ mod tests {
         // Bump should be a valid value
         assert!(bump > 0 && bump <= 255);
     }
+
+    #[test]
+    fn test_vault_authority_space_matches_serialized_size() {
+        let vault_authority = VaultAuthority {
+            admin: Pubkey::new_unique(),
+            bump: 255,
+            jupiter_program_id: Pubkey::new_unique(),
+            max_jupiter_data_len: 1232,
+        };
+        let data = vault_authority.try_to_vec().unwrap();
+        assert_eq!(VaultAuthority::SPACE, 8 + data.len());
+    }
+
+    #[test]
+    fn test_global_manager_space_matches_serialized_size() {
+        let global_manager = GlobalManager {
+            manager: Pubkey::new_unique(),
+            bump: 255,
+        };
+        let data = global_manager.try_to_vec().unwrap();
+        assert_eq!(GlobalManager::SPACE, 8 + data.len());
+    }
+
+    #[test]
+    fn test_vault_admin_proposal_space_matches_serialized_size() {
+        let admin_proposal = VaultAdminProposal {
+            pending_admin: Pubkey::new_unique(),
+            proposed_by: Pubkey::new_unique(),
+            bump: 255,
+        };
+        let data = admin_proposal.try_to_vec().unwrap();
+        assert_eq!(VaultAdminProposal::SPACE, 8 + data.len());
+    }
+
+    fn create_test_vault_data(owner: Pubkey, delegate: Option<Pubkey>) -> Vec<u8> {
+        use anchor_spl::token::spl_token::state::{Account, AccountState};
+        use anchor_lang::solana_program::program_option::COption;
+        use anchor_lang::solana_program::program_pack::Pack;
+
+        let vault = Account {
+            mint: Pubkey::new_unique(),
+            owner,
+            amount: 1_000,
+            delegate: delegate.map_or(COption::None, COption::Some),
+            state: AccountState::Initialized,
+            is_native: COption::None,
+            delegated_amount: if delegate.is_some() { 1_000 } else { 0 },
+            close_authority: COption::None,
+        };
+        let mut data = vec![0u8; Account::LEN];
+        Account::pack(vault, &mut data).unwrap();
+        data
+    }
+
+    #[test]
+    fn test_vault_needs_revoke_with_delegate() {
+        let vault_authority = Pubkey::new_unique();
+        let data = create_test_vault_data(vault_authority, Some(Pubkey::new_unique()));
+
+        assert!(vault_needs_revoke(&data, &vault_authority).unwrap());
+    }
+
+    #[test]
+    fn test_vault_needs_revoke_without_delegate() {
+        let vault_authority = Pubkey::new_unique();
+        let data = create_test_vault_data(vault_authority, None);
+
+        assert!(!vault_needs_revoke(&data, &vault_authority).unwrap());
+    }
+
+    #[test]
+    fn test_vault_needs_revoke_rejects_foreign_vault() {
+        let data = create_test_vault_data(Pubkey::new_unique(), Some(Pubkey::new_unique()));
+
+        let err = vault_needs_revoke(&data, &Pubkey::new_unique()).unwrap_err();
+        assert_eq!(err, ErrorCode::InvalidVaultOwner.into());
+
+        let err = vault_needs_revoke(&[0u8; 10], &Pubkey::new_unique()).unwrap_err();
+        assert_eq!(err, ErrorCode::InvalidAccount.into());
+    }
+
+    #[test]
+    fn test_existing_vault_mint_reads_canonical_vault() {
+        use crate::test_utils::{leak_account_infos, token_account};
+
+        let vault_authority = Pubkey::new_unique();
+        let program_id = Pubkey::new_unique();
+        let mint = Pubkey::new_unique();
+        let vault_key = get_vault_address(&mint, &program_id).0;
+
+        for token_program in [anchor_spl::token::ID, TOKEN_2022_PROGRAM_ID] {
+            let vault = leak_account_infos(vec![
+                token_account(mint, vault_authority, 0, token_program).with_key(vault_key),
+            ]);
+            assert_eq!(existing_vault_mint(&vault[0], &vault_authority, &program_id).unwrap(), mint);
+        }
+    }
+
+    #[test]
+    fn test_existing_vault_mint_rejects_other_accounts() {
+        use crate::test_utils::{empty_account, leak_account_infos, token_account};
+
+        let vault_authority = Pubkey::new_unique();
+        let program_id = Pubkey::new_unique();
+        let mint = Pubkey::new_unique();
+        let vault_key = get_vault_address(&mint, &program_id).0;
+        let accounts = leak_account_infos(vec![
+            // A vault authority token account that is not the ["vault", mint] PDA
+            token_account(mint, vault_authority, 0, anchor_spl::token::ID),
+            token_account(mint, Pubkey::new_unique(), 0, anchor_spl::token::ID).with_key(vault_key),
+            empty_account().with_key(vault_key),
+        ]);
+
+        let err = existing_vault_mint(&accounts[0], &vault_authority, &program_id).unwrap_err();
+        assert_eq!(err, ErrorCode::InvalidVaultAddress.into());
+        let err = existing_vault_mint(&accounts[1], &vault_authority, &program_id).unwrap_err();
+        assert_eq!(err, ErrorCode::InvalidVaultOwner.into());
+        let err = existing_vault_mint(&accounts[2], &vault_authority, &program_id).unwrap_err();
+        assert_eq!(err, ErrorCode::InvalidAccount.into());
+    }
 }
@@ -0,0 +1,140 @@
+use anchor_lang::prelude::*;
+use anchor_spl::token_interface::TokenAccount;
+use crate::errors::ErrorCode;
+use crate::instructions::route_validator_module;
+
+// Balance conservation checks, compiled only with the "invariant-checks" feature
+//
+// An instruction tracks every token account it moves funds between (user accounts, vaults,
+// fee accounts) and brackets the DEX or Jupiter CPI, where tokens legitimately enter and
+// leave that set. Outside the swap leg the tracked balances of each mint must sum to the
+// same total, less at most the Token-2022 fees withheld on the instruction's own transfers.
+
+/// Token balance of one tracked account
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct TrackedBalance {
+    pub key: Pubkey,
+    pub mint: Pubkey,
+    pub amount: u64,
+}
+
+/// Reads a token account balance; None for closed or non-token accounts
+pub fn read_balance(account: &AccountInfo) -> Option<TrackedBalance> {
+    let data = account.try_borrow_data().ok()?;
+    let token_account = TokenAccount::try_deserialize(&mut data.as_ref()).ok()?;
+    Some(TrackedBalance {
+        key: account.key(),
+        mint: token_account.mint,
+        amount: token_account.amount,
+    })
+}
+
+/// Balances of the tracked accounts, one entry per key
+pub fn capture_balances(accounts: &[AccountInfo]) -> Vec<TrackedBalance> {
+    let mut balances: Vec<TrackedBalance> = Vec::with_capacity(accounts.len());
+    for account in accounts {
+        if balances.iter().any(|balance| balance.key == account.key()) {
+            continue;
+        }
+        if let Some(balance) = read_balance(account) {
+            balances.push(balance);
+        }
+    }
+    balances
+}
+
+/// Net change per mint between two captures. Accounts missing from one side count as empty,
+/// so accounts created or closed in between are covered.
+pub fn mint_deltas(before: &[TrackedBalance], after: &[TrackedBalance]) -> Vec<(Pubkey, i128)> {
+    let mut deltas: Vec<(Pubkey, i128)> = Vec::new();
+    let mut add = |mint: Pubkey, amount: i128| {
+        match deltas.iter_mut().find(|(delta_mint, _)| *delta_mint == mint) {
+            Some((_, total)) => *total += amount,
+            None => deltas.push((mint, amount)),
+        }
+    };
+    for balance in after {
+        add(balance.mint, balance.amount as i128);
+    }
+    for balance in before {
+        add(balance.mint, -(balance.amount as i128));
+    }
+    deltas
+}
+
+/// Checks that, per mint, the change outside the swap leg is between -allowance and 0
+///
+/// # Arguments
+/// * `total` - Mint deltas from the start to the end of the instruction
+/// * `swap_leg` - Mint deltas across the DEX or Jupiter CPI
+/// * `allowances` - Token-2022 transfer fees the instruction's own transfers may withhold, per mint
+///
+/// # Returns
+/// * `Result<()>` - BalanceConservationViolated for the first mint that gained or lost tokens
+pub fn check_conservation(
+    total: &[(Pubkey, i128)],
+    swap_leg: &[(Pubkey, i128)],
+    allowances: &[(Pubkey, u64)],
+) -> Result<()> {
+    let sum_for = |deltas: &[(Pubkey, i128)], mint: &Pubkey| -> i128 {
+        deltas.iter().filter(|(delta_mint, _)| delta_mint == mint).map(|(_, amount)| amount).sum()
+    };
+
+    for mint in total.iter().chain(swap_leg.iter()).map(|(mint, _)| mint) {
+        let residual = sum_for(total, mint) - sum_for(swap_leg, mint);
+        let allowance: i128 = allowances
+            .iter()
+            .filter(|(allowance_mint, _)| allowance_mint == mint)
+            .map(|(_, amount)| *amount as i128)
+            .sum();
+        if residual > 0 || residual < -allowance {
+            msg!("Mint {} is off by {} outside the swap (allowed -{}..=0)", mint, residual, allowance);
+            return Err(ErrorCode::BalanceConservationViolated.into());
+        }
+    }
+    Ok(())
+}
+
+/// Token-2022 fees withheld on the given transfers of a mint, as an allowance entry
+pub fn transfer_fee_allowance(mint_account: &AccountInfo, amounts: &[u64]) -> Result<(Pubkey, u64)> {
+    let mut allowance = 0u64;
+    for amount in amounts {
+        allowance = allowance
+            .checked_add(route_validator_module::calculate_transfer_fee(mint_account, *amount)?)
+            .ok_or(ErrorCode::InvalidCalculation)?;
+    }
+    Ok((mint_account.key(), allowance))
+}
+
+/// Balance-snapshot plumbing for one instruction: capture at the start, bracket the swap
+/// with begin_swap / end_swap, then finish after the last transfer
+pub struct ConservationCheck<'info> {
+    accounts: Vec<AccountInfo<'info>>,
+    start: Vec<TrackedBalance>,
+    swap_start: Vec<TrackedBalance>,
+    swap_end: Vec<TrackedBalance>,
+}
+
+impl<'info> ConservationCheck<'info> {
+    pub fn start(accounts: Vec<AccountInfo<'info>>) -> Self {
+        let start = capture_balances(&accounts);
+        Self { accounts, start, swap_start: Vec::new(), swap_end: Vec::new() }
+    }
+
+    pub fn begin_swap(&mut self) {
+        self.swap_start = capture_balances(&self.accounts);
+    }
+
+    pub fn end_swap(&mut self) {
+        self.swap_end = capture_balances(&self.accounts);
+    }
+
+    pub fn finish(&self, allowances: &[(Pubkey, u64)]) -> Result<()> {
+        let end = capture_balances(&self.accounts);
+        check_conservation(
+            &mint_deltas(&self.start, &end),
+            &mint_deltas(&self.swap_start, &self.swap_end),
+            allowances,
+        )
+    }
+}
@@ -0,0 +1,143 @@
+#[cfg(test)]
+mod tests {
+    use super::super::errors::ErrorCode;
+    use super::super::invariants::*;
+    use crate::test_utils::*;
+    use anchor_lang::prelude::*;
+    use anchor_lang::solana_program::program_pack::Pack;
+    use anchor_spl::token::spl_token::state::Account as SplTokenAccount;
+
+    const TOKEN_PROGRAM_ID: Pubkey = anchor_spl::token::ID;
+
+    fn balance(key: Pubkey, mint: Pubkey, amount: u64) -> TrackedBalance {
+        TrackedBalance { key, mint, amount }
+    }
+
+    fn set_amount(account: &AccountInfo, amount: u64) {
+        let mut data = account.try_borrow_mut_data().unwrap();
+        let mut token_account = SplTokenAccount::unpack(&data).unwrap();
+        token_account.amount = amount;
+        SplTokenAccount::pack(token_account, &mut data).unwrap();
+    }
+
+    #[test]
+    fn test_mint_deltas_sum_per_mint() {
+        let (mint_a, mint_b) = (Pubkey::new_unique(), Pubkey::new_unique());
+        let (user, vault, fee) = (Pubkey::new_unique(), Pubkey::new_unique(), Pubkey::new_unique());
+        let before = [balance(user, mint_a, 1_000), balance(vault, mint_a, 50), balance(fee, mint_b, 0)];
+        let after = [balance(user, mint_a, 400), balance(vault, mint_a, 650), balance(fee, mint_b, 7)];
+
+        let deltas = mint_deltas(&before, &after);
+        assert_eq!(deltas.len(), 2);
+        assert!(deltas.contains(&(mint_a, 0)));
+        assert!(deltas.contains(&(mint_b, 7)));
+    }
+
+    #[test]
+    fn test_mint_deltas_treat_missing_accounts_as_empty() {
+        let mint = Pubkey::new_unique();
+        let (created, closed) = (Pubkey::new_unique(), Pubkey::new_unique());
+
+        // An ATA created mid-instruction and an order vault closed at zero balance
+        let deltas = mint_deltas(&[balance(closed, mint, 0)], &[balance(created, mint, 25)]);
+        assert_eq!(deltas, vec![(mint, 25)]);
+    }
+
+    #[test]
+    fn test_check_conservation_accepts_balanced_transfers() {
+        let (mint_in, mint_out) = (Pubkey::new_unique(), Pubkey::new_unique());
+
+        // User paid 1_000 in, the DEX took 1_000 and paid 1_500 out; fee and payout stayed inside
+        let total = [(mint_in, -1_000), (mint_out, 1_500)];
+        let swap_leg = [(mint_in, -1_000), (mint_out, 1_500)];
+        assert!(check_conservation(&total, &swap_leg, &[]).is_ok());
+    }
+
+    #[test]
+    fn test_check_conservation_rejects_created_or_lost_tokens() {
+        let mint = Pubkey::new_unique();
+        let swap_leg = [(mint, 1_500)];
+
+        // Fee math paid out one token more than the swap produced
+        let err = check_conservation(&[(mint, 1_501)], &swap_leg, &[]).unwrap_err();
+        assert_eq!(err, ErrorCode::BalanceConservationViolated.into());
+
+        // A refund went missing
+        let err = check_conservation(&[(mint, 1_499)], &swap_leg, &[]).unwrap_err();
+        assert_eq!(err, ErrorCode::BalanceConservationViolated.into());
+    }
+
+    #[test]
+    fn test_check_conservation_allows_transfer_fees_up_to_allowance() {
+        let mint = Pubkey::new_unique();
+        let swap_leg = [(mint, 1_500)];
+
+        assert!(check_conservation(&[(mint, 1_497)], &swap_leg, &[(mint, 3)]).is_ok());
+        assert!(check_conservation(&[(mint, 1_500)], &swap_leg, &[(mint, 3)]).is_ok());
+
+        let err = check_conservation(&[(mint, 1_496)], &swap_leg, &[(mint, 3)]).unwrap_err();
+        assert_eq!(err, ErrorCode::BalanceConservationViolated.into());
+
+        // Allowances only cover withheld fees, never a gain
+        let err = check_conservation(&[(mint, 1_501)], &swap_leg, &[(mint, 3)]).unwrap_err();
+        assert_eq!(err, ErrorCode::BalanceConservationViolated.into());
+    }
+
+    #[test]
+    fn test_check_conservation_checks_mints_only_in_swap_leg() {
+        let mint = Pubkey::new_unique();
+
+        // The swap moved tokens but the instruction as a whole shows nothing
+        let err = check_conservation(&[], &[(mint, 10)], &[]).unwrap_err();
+        assert_eq!(err, ErrorCode::BalanceConservationViolated.into());
+    }
+
+    #[test]
+    fn test_capture_balances_skips_duplicates_and_non_token_accounts() {
+        let mint = Pubkey::new_unique();
+        let owner = Pubkey::new_unique();
+        let accounts = leak_account_infos(vec![
+            token_account(mint, owner, 100, TOKEN_PROGRAM_ID),
+            empty_account(),
+            token_account(mint, owner, 5, TOKEN_PROGRAM_ID),
+        ]);
+        let mut tracked = accounts.to_vec();
+        tracked.push(accounts[0].clone());
+
+        let balances = capture_balances(&tracked);
+        assert_eq!(balances.len(), 2);
+        assert_eq!(balances[0], balance(accounts[0].key(), mint, 100));
+        assert_eq!(balances[1], balance(accounts[2].key(), mint, 5));
+    }
+
+    #[test]
+    fn test_conservation_check_over_route_transfers() {
+        let (mint_in, mint_out) = (Pubkey::new_unique(), Pubkey::new_unique());
+        let (user, vault_authority) = (Pubkey::new_unique(), Pubkey::new_unique());
+        let accounts = leak_account_infos(vec![
+            token_account(mint_in, user, 1_000, TOKEN_PROGRAM_ID),         // user source
+            token_account(mint_out, user, 0, TOKEN_PROGRAM_ID),            // user destination
+            token_account(mint_in, vault_authority, 0, TOKEN_PROGRAM_ID),  // input vault
+            token_account(mint_out, vault_authority, 0, TOKEN_PROGRAM_ID), // destination vault
+            token_account(mint_out, vault_authority, 0, TOKEN_PROGRAM_ID), // platform fee account
+        ]);
+        let (user_source, user_destination, input_vault, destination_vault, fee_vault) =
+            (&accounts[0], &accounts[1], &accounts[2], &accounts[3], &accounts[4]);
+
+        let mut check = ConservationCheck::start(accounts.to_vec());
+        set_amount(user_source, 0);
+        set_amount(input_vault, 1_000);
+        check.begin_swap();
+        set_amount(input_vault, 0);
+        set_amount(destination_vault, 1_500);
+        check.end_swap();
+        set_amount(destination_vault, 0);
+        set_amount(fee_vault, 30);
+        set_amount(user_destination, 1_470);
+        assert!(check.finish(&[]).is_ok());
+
+        // The payout is short by one token: it stayed nowhere
+        set_amount(user_destination, 1_469);
+        assert_eq!(check.finish(&[]).unwrap_err(), ErrorCode::BalanceConservationViolated.into());
+    }
+}
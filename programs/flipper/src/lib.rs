@@ -4,13 +4,38 @@ use anchor_lang::prelude::*;
 pub mod adapters;
 pub mod instructions;
 pub mod errors;
+pub mod math;
 pub mod state;
+pub mod sdk;
+#[cfg(feature = "invariant-checks")]
+pub mod invariants;
+#[cfg(feature = "telemetry")]
+pub mod telemetry;
+
+// Anyone can set the mock clock, so test-clock only builds as part of a localnet test build
+#[cfg(all(feature = "test-clock", not(feature = "localnet")))]
+compile_error!("the test-clock feature is for localnet test builds only; build with --features test-clock,localnet");
+
+/// Whether this is a localnet test build (`localnet` or `test-clock` enabled). Exported in the IDL,
+/// where the CI release build fails unless it is false, since neither feature may be deployed.
+#[constant]
+pub const TEST_BUILD: bool = cfg!(any(feature = "localnet", feature = "test-clock"));
 
 // Test modules
 #[cfg(test)]
 mod state_test;
 #[cfg(test)]
 mod errors_test;
+#[cfg(test)]
+mod math_test;
+#[cfg(test)]
+mod sdk_test;
+#[cfg(test)]
+mod test_utils;
+#[cfg(all(test, feature = "invariant-checks"))]
+mod invariants_test;
+#[cfg(all(test, feature = "telemetry"))]
+mod telemetry_test;
 
 declare_id!("fLpRcgQSJxKeeUogb6M7bWe1iyYQbahjGXGwr4HgHit");
 
@@ -25,6 +50,8 @@ pub mod flipper {
         shared_route_module::*,
         shared_limit_orders_module::*
     };
+    #[cfg(feature = "test-clock")]
+    pub use instructions::test_clock_module::*;
     pub use errors::ErrorCode;
     pub use state::{
         AdapterInfo,
@@ -33,7 +60,14 @@ pub mod flipper {
         RoutePlanStep,
         SwapEvent,
         FeeEvent,
+        FeeSource,
         PoolInfo,
+        AdminLogEntry,
+        FeeTier,
+        ProtocolLimits,
+        GovernanceCall,
+        VolumeFeeTier,
+        AdapterStatus,
     };
 
     pub fn initialize_adapter_registry(ctx: Context<InitializeAdapterRegistry>, adapters: Vec<AdapterInfo>, operators: Vec<Pubkey>) -> Result<()> {
@@ -44,6 +78,7 @@ pub mod flipper {
         instructions::initialize_pool_info(ctx, swap_type, pool_address)
     }
 
+    /// Adds or updates an adapter. Changing the program_id of an existing adapter requires all of its PoolInfos to be disabled and an executable governance proposal of the change.
     pub fn configure_adapter(ctx: Context<ConfigureAdapter>, adapter: AdapterInfo) -> Result<()> {
         instructions::configure_adapter(ctx, adapter)
     }
@@ -52,32 +87,233 @@ pub mod flipper {
         instructions::add_operator(ctx, operator)
     }
 
-    pub fn remove_operator(ctx: Context<RemoveOperator>, operator: Pubkey) -> Result<()> {
-        instructions::remove_operator(ctx, operator)
+    /// Removes an operator. Registry authority only. `force` hands the sponsored order vaults
+    /// the operator still has to the treasury instead of failing with OperatorHasObligations
+    pub fn remove_operator(ctx: Context<RemoveOperator>, operator: Pubkey, force: bool) -> Result<()> {
+        instructions::remove_operator(ctx, operator, force)
+    }
+
+    /// Allows an integrator to call route_prevalidated. Registry authority only.
+    pub fn add_trusted_router(ctx: Context<AddTrustedRouter>, router: Pubkey) -> Result<()> {
+        instructions::add_trusted_router(ctx, router)
+    }
+
+    /// Revokes an integrator's access to route_prevalidated. Registry authority only.
+    pub fn remove_trusted_router(ctx: Context<RemoveTrustedRouter>, router: Pubkey) -> Result<()> {
+        instructions::remove_trusted_router(ctx, router)
+    }
+
+    /// Suspends the signing operator's own key until the authority reinstates it
+    pub fn suspend_self(ctx: Context<SuspendSelf>) -> Result<()> {
+        instructions::suspend_self(ctx)
+    }
+
+    /// Records the signing operator's liveness in its OperatorStats PDA, at most once per minute
+    pub fn operator_heartbeat(ctx: Context<OperatorHeartbeat>) -> Result<i64> {
+        instructions::operator_heartbeat(ctx)
+    }
+
+    /// Suspends another operator's key, recording a non-zero reason code
+    pub fn suspend_operator(ctx: Context<SuspendOperator>, operator: Pubkey, reason_code: u16) -> Result<()> {
+        instructions::suspend_operator(ctx, operator, reason_code)
+    }
+
+    pub fn reinstate_operator(ctx: Context<ReinstateOperator>, operator: Pubkey) -> Result<()> {
+        instructions::reinstate_operator(ctx, operator)
     }
 
     pub fn disable_adapter(ctx: Context<DisableAdapter>, swap_type: Swap) -> Result<()> {
         instructions::disable_adapter(ctx, swap_type)
     }
 
+    /// Pauses or resumes an adapter without removing it from the registry. Operator or authority.
+    pub fn set_adapter_enabled(ctx: Context<SetAdapterEnabled>, swap_type: Swap, enabled: bool) -> Result<()> {
+        instructions::set_adapter_enabled(ctx, swap_type, enabled)
+    }
+
+    /// Puts an adapter in sandbox mode, restricted to the listed wallets. Operator or authority.
+    pub fn set_adapter_sandbox(ctx: Context<SetAdapterSandbox>, swap_type: Swap, sandbox_users: Vec<Pubkey>) -> Result<()> {
+        instructions::set_adapter_sandbox(ctx, swap_type, sandbox_users)
+    }
+
+    /// Opens a sandboxed adapter to all users and clears its sandbox list. Operator or authority.
+    pub fn graduate_adapter(ctx: Context<GraduateAdapter>, swap_type: Swap) -> Result<()> {
+        instructions::graduate_adapter(ctx, swap_type)
+    }
+
     pub fn disable_pool(ctx: Context<DisablePool>, swap_type: Swap, pool_address: Pubkey) -> Result<()> {
         instructions::disable_pool(ctx, swap_type, pool_address)
     }
 
+    /// Stamps a pool's PoolInfo with the current slot if both reserves exceed its threshold, or clears the stamp. Permissionless.
+    pub fn attest_pool_liquidity(ctx: Context<AttestPoolLiquidity>) -> Result<()> {
+        instructions::attest_pool_liquidity(ctx)
+    }
+
+    /// Sets the amount each reserve of a pool must exceed to be attested, and clears its current stamp
+    pub fn set_pool_liquidity_threshold(ctx: Context<SetPoolLiquidityThreshold>, swap_type: Swap, pool_address: Pubkey, min_liquidity_threshold: u64) -> Result<()> {
+        instructions::set_pool_liquidity_threshold(ctx, swap_type, pool_address, min_liquidity_threshold)
+    }
+
+    /// Caps the step input routes may send through a pool per volume window (0 = uncapped)
+    pub fn set_pool_volume_cap(ctx: Context<SetPoolVolumeCap>, swap_type: Swap, pool_address: Pubkey, max_volume_per_window: u64) -> Result<()> {
+        instructions::set_pool_volume_cap(ctx, swap_type, pool_address, max_volume_per_window)
+    }
+
     pub fn change_authority(ctx: Context<ChangeAuthority>) -> Result<()> {
         instructions::change_authority(ctx)
     }
 
+    /// Replaces the adapters and operators and clears the other registry lists. Needs an executable governance proposal of the same arguments.
     pub fn reset_adapter_registry(ctx: Context<ResetAdapterRegistry>, adapters: Vec<AdapterInfo>, operators: Vec<Pubkey>) -> Result<()> {
         instructions::reset_adapter_registry(ctx, adapters, operators)
     }
 
+    /// Sets the memo program Whirlpool and Meteora swaps must receive. Registry authority only.
+    pub fn set_memo_program(ctx: Context<SetMemoProgram>, memo_program_id: Pubkey) -> Result<()> {
+        instructions::set_memo_program(ctx, memo_program_id)
+    }
+
+    /// Sets the key whose ed25519 attestation limit order executions require. Registry authority only.
+    pub fn set_quote_signer(ctx: Context<SetQuoteSigner>, quote_signer: Pubkey) -> Result<()> {
+        instructions::set_quote_signer(ctx, quote_signer)
+    }
+
+    /// Sets the lamport fee charged per limit order creation and its treasury (0 disables). Registry authority only.
+    pub fn set_order_creation_fee(ctx: Context<SetOrderCreationFee>, fee_lamports: u64, treasury: Pubkey) -> Result<()> {
+        instructions::set_order_creation_fee(ctx, fee_lamports, treasury)
+    }
+
+    /// Sets the band around the attested reference price that strict limit orders must fall within (0 restores the default). Registry authority only.
+    pub fn set_order_price_band(ctx: Context<SetOrderPriceBand>, band_bps: u16) -> Result<()> {
+        instructions::set_order_price_band(ctx, band_bps)
+    }
+
+    /// Chooses whether single-hop routes emit per-step SwapEvents next to the aggregated event. Registry authority only.
+    pub fn set_emit_step_events(ctx: Context<SetEmitStepEvents>, emit_step_events: bool) -> Result<()> {
+        instructions::set_emit_step_events(ctx, emit_step_events)
+    }
+
+    /// Turns the route quote sanity check on or off and sets its band around the on-chain quote (0 = default ±20%)
+    pub fn set_quote_sanity(ctx: Context<SetQuoteSanity>, enforce_quote_sanity: bool, band_bps: u16) -> Result<()> {
+        instructions::set_quote_sanity(ctx, enforce_quote_sanity, band_bps)
+    }
+
+    /// Makes routes reject vaults that have a delegate set (VaultHasDelegate), or allows them again
+    pub fn set_reject_delegated_vaults(ctx: Context<SetRejectDelegatedVaults>, reject_delegated_vaults: bool) -> Result<()> {
+        instructions::set_reject_delegated_vaults(ctx, reject_delegated_vaults)
+    }
+
+    /// Makes routes reject pools not attested within max_age_slots (PoolAttestationStale); 0 turns the check off
+    pub fn set_pool_attestation_max_age(ctx: Context<SetPoolAttestationMaxAge>, max_age_slots: u64) -> Result<()> {
+        instructions::set_pool_attestation_max_age(ctx, max_age_slots)
+    }
+
+    /// Restricts an adapter to the given pools (empty clears the whitelist). Pass each pool's PoolInfo in remaining_accounts.
+    pub fn set_adapter_pools<'info>(
+        ctx: Context<'_, '_, 'info, 'info, SetAdapterPools<'info>>,
+        swap_type: Swap,
+        pool_addresses: Vec<Pubkey>,
+    ) -> Result<()> {
+        instructions::set_adapter_pools(ctx, swap_type, pool_addresses)
+    }
+
+    /// Caps the estimated price impact of each route step through an adapter (0 removes the cap)
+    pub fn set_max_step_impact(ctx: Context<SetMaxStepImpact>, swap_type: Swap, max_step_impact_bps: u16) -> Result<()> {
+        instructions::set_max_step_impact(ctx, swap_type, max_step_impact_bps)
+    }
+
+    /// Sets the execution fee tiers, by order size, for limit orders selling `mint`.
+    pub fn set_fee_schedule(ctx: Context<SetFeeSchedule>, mint: Pubkey, tiers: Vec<FeeTier>) -> Result<()> {
+        instructions::set_fee_schedule(ctx, mint, tiers)
+    }
+
+    /// Registers the passed token account as the platform fee vault of its mint, for routes called with use_registered_fee_vault
+    pub fn set_fee_vault(ctx: Context<SetFeeVault>) -> Result<()> {
+        instructions::set_fee_vault(ctx)
+    }
+
+    /// Unregisters the platform fee vault of `mint`
+    pub fn remove_fee_vault(ctx: Context<RemoveFeeVault>, mint: Pubkey) -> Result<()> {
+        instructions::remove_fee_vault(ctx, mint)
+    }
+
+    /// Creates the ProtocolConfig PDA with the default protocol-wide limits
+    pub fn create_protocol_config(ctx: Context<CreateProtocolConfig>) -> Result<()> {
+        instructions::create_protocol_config(ctx)
+    }
+
+    /// Sets the protocol-wide slippage and trigger caps
+    pub fn set_protocol_limits(ctx: Context<SetProtocolLimits>, limits: ProtocolLimits) -> Result<()> {
+        instructions::set_protocol_limits(ctx, limits)
+    }
+
+    /// Sets the volume tiers that lower the route platform fee for high-volume traders. Registry authority only.
+    pub fn set_volume_fee_tiers(ctx: Context<SetVolumeFeeTiers>, volume_mint: Pubkey, tiers: Vec<VolumeFeeTier>) -> Result<()> {
+        instructions::set_volume_fee_tiers(ctx, volume_mint, tiers)
+    }
+
+    /// Sets the percent of a pool's volume cap above which routes emit PoolVolumeWarning (0 = default 80). Registry authority only.
+    pub fn set_pool_volume_warning_pct(ctx: Context<SetProtocolLimits>, warning_pct: u8) -> Result<()> {
+        instructions::set_pool_volume_warning_pct(ctx, warning_pct)
+    }
+
+    /// Pauses or resumes new limit orders; cancels and withdrawals still work
+    pub fn set_order_creation_paused(ctx: Context<SetOrderPause>, paused: bool) -> Result<()> {
+        instructions::set_order_creation_paused(ctx, paused)
+    }
+
+    /// Pauses or resumes limit order execution; cancels and withdrawals still work
+    pub fn set_order_execution_paused(ctx: Context<SetOrderPause>, paused: bool) -> Result<()> {
+        instructions::set_order_execution_paused(ctx, paused)
+    }
+
     /// Migrates the adapter registry to write the PDA bump into the account data.
     /// Must be called once after upgrading from a version that did not store bump.
     pub fn migrate_adapter_registry(ctx: Context<MigrateAdapterRegistry>) -> Result<()> {
         instructions::migrate_adapter_registry(ctx)
     }
 
+    /// Grows a PoolInfo created before the liquidity attestation, volume cap or pool count fields existed, counting it among its adapter's enabled pools if it is enabled. Permissionless; the payer covers the rent.
+    pub fn migrate_pool_info(ctx: Context<MigratePoolInfo>) -> Result<()> {
+        instructions::migrate_pool_info(ctx)
+    }
+
+    /// Grows a ProtocolConfig created before the order pause flags, the governance delay, the volume fee tiers or the pool volume warning threshold existed. Permissionless; the payer covers the rent.
+    pub fn migrate_protocol_config(ctx: Context<MigrateProtocolConfig>) -> Result<()> {
+        instructions::migrate_protocol_config(ctx)
+    }
+
+    /// Proposes a reset_adapter_registry, adapter program_id change or governance delay decrease, executable after the governance delay. Registry authority only.
+    pub fn propose_governance_call(ctx: Context<ProposeGovernanceCall>, call: GovernanceCall) -> Result<()> {
+        instructions::propose_governance_call(ctx, call)
+    }
+
+    /// Cancels the pending governance proposal: the registry authority at any time, anyone once it expired.
+    pub fn cancel_governance_proposal(ctx: Context<CancelGovernanceProposal>) -> Result<()> {
+        instructions::cancel_governance_proposal(ctx)
+    }
+
+    /// Sets the governance delay; lowering it needs an executable governance proposal. Registry authority only.
+    pub fn set_governance_delay(ctx: Context<SetGovernanceDelay>, delay_secs: i64) -> Result<()> {
+        instructions::set_governance_delay(ctx, delay_secs)
+    }
+
+    /// Creates the AdminLog ring buffer of recent administrative actions.
+    pub fn initialize_admin_log(ctx: Context<InitializeAdminLog>) -> Result<()> {
+        instructions::initialize_admin_log(ctx)
+    }
+
+    /// Returns up to 12 recent admin actions, newest first, after skipping `skip` newer ones.
+    pub fn get_admin_log(ctx: Context<GetAdminLog>, skip: u8) -> Result<Vec<AdminLogEntry>> {
+        instructions::get_admin_log(ctx, skip)
+    }
+
+    /// Reports each swap type's AdapterStatus (enabled, disabled, not configured or not implemented) as return data
+    pub fn describe_adapters(ctx: Context<DescribeAdapters>, swap_types: Vec<Swap>) -> Result<Vec<AdapterStatus>> {
+        instructions::describe_adapters(ctx, swap_types)
+    }
+
     pub fn create_vault_authority(ctx: Context<CreateVaultAuthority>) -> Result<()> {
         instructions::create_vault_authority(ctx)
     }
@@ -93,6 +329,11 @@ pub mod flipper {
         instructions::set_jupiter_program(ctx, jupiter_program_id)
     }
 
+    /// Sets the maximum Jupiter instruction data length (0 = default). Global manager only.
+    pub fn set_jupiter_data_limit(ctx: Context<SetJupiterDataLimit>, max_len: u16) -> Result<()> {
+        instructions::set_jupiter_data_limit(ctx, max_len)
+    }
+
     pub fn create_vault(ctx: Context<CreateVault>) -> Result<()> {
         instructions::create_vault(ctx)
     }
@@ -105,14 +346,55 @@ pub mod flipper {
         instructions::close_vault(ctx)
     }
 
+    /// Wraps lamports from the payer into the WSOL vault. Vault authority admin only.
+    pub fn wrap_into_vault(ctx: Context<WrapIntoVault>, lamports: u64) -> Result<()> {
+        instructions::wrap_into_vault(ctx, lamports)
+    }
+
+    /// Unwraps lamports from the WSOL vault to the destination. Vault authority admin only.
+    pub fn unwrap_from_vault(ctx: Context<UnwrapFromVault>, amount: u64) -> Result<()> {
+        instructions::unwrap_from_vault(ctx, amount)
+    }
+
     pub fn initialize_vaults(ctx: Context<InitializeVaults>) -> Result<()> {
         instructions::initialize_vaults(ctx)
     }
 
+    /// Proposes a new vault authority admin. Global manager only; takes effect on acceptance.
     pub fn change_vault_authority_admin(ctx: Context<ChangeVaultAuthorityAdmin>) -> Result<()> {
         instructions::change_vault_authority_admin(ctx)
     }
 
+    /// Accepts a pending vault authority admin proposal. Signed by the proposed admin.
+    pub fn accept_vault_authority_admin(ctx: Context<AcceptVaultAuthorityAdmin>) -> Result<()> {
+        instructions::accept_vault_authority_admin(ctx)
+    }
+
+    /// Revokes token delegations on the vaults passed in remaining_accounts. Admin or operator.
+    pub fn revoke_vault_delegations<'info>(ctx: Context<'_, '_, 'info, 'info, RevokeVaultDelegations<'info>>) -> Result<()> {
+        instructions::revoke_vault_delegations(ctx)
+    }
+
+    /// Creates the VaultRegistry that vault creation and close_vault keep current. Admin or operator.
+    pub fn initialize_vault_registry(ctx: Context<InitializeVaultRegistry>) -> Result<()> {
+        instructions::initialize_vault_registry(ctx)
+    }
+
+    /// Lists the existing vaults passed in remaining_accounts in the VaultRegistry. Admin or operator.
+    pub fn register_existing_vaults<'info>(ctx: Context<'_, '_, 'info, 'info, RegisterExistingVaults<'info>>) -> Result<()> {
+        instructions::register_existing_vaults(ctx)
+    }
+
+    /// Returns up to 31 registered vault mints starting at `offset`.
+    pub fn list_vaults(ctx: Context<ListVaults>, offset: u32, limit: u8) -> Result<Vec<Pubkey>> {
+        instructions::list_vaults(ctx, offset, limit)
+    }
+
+    /// Fails unless the vault has been closed exactly `expected_generation` times. Include it ahead of a route to reject stale cached vaults.
+    pub fn assert_vault_generation(ctx: Context<AssertVaultGeneration>, expected_generation: u32) -> Result<()> {
+        instructions::assert_vault_generation(ctx, expected_generation)
+    }
+
     pub fn withdraw_platform_fees(ctx: Context<WithdrawPlatformFees>, amount: u64) -> Result<()> {
         instructions::withdraw_platform_fees(ctx, amount)
     }
@@ -132,8 +414,99 @@ pub mod flipper {
         quoted_out_amount: u64,
         slippage_bps: u16,
         platform_fee_bps: u8,
+        min_out_amount: u64,
+        accept_high_impact: bool,
+        emit_step_events: Option<bool>,
+        use_registered_fee_vault: bool,
+        expected_vault_generation: Option<u32>,
+    ) -> Result<RouteAmounts> {
+        instructions::route(ctx, route_plan, in_amount, quoted_out_amount, slippage_bps, platform_fee_bps, min_out_amount, accept_high_impact, emit_step_events, use_registered_fee_vault, expected_vault_generation)
+    }
+
+    /// Same as route, with the plan packed as 3-byte (header, input_index, output_index) records, 4 bytes with a split percent
+    pub fn route_compact<'info>(
+        ctx: Context<'_, '_, 'info, 'info, Route<'info>>,
+        plan_bytes: Vec<u8>,
+        in_amount: u64,
+        quoted_out_amount: u64,
+        slippage_bps: u16,
+        platform_fee_bps: u8,
+        min_out_amount: u64,
+        accept_high_impact: bool,
+        emit_step_events: Option<bool>,
+        use_registered_fee_vault: bool,
+        expected_vault_generation: Option<u32>,
+    ) -> Result<RouteAmounts> {
+        instructions::route_compact(ctx, plan_bytes, in_amount, quoted_out_amount, slippage_bps, platform_fee_bps, min_out_amount, accept_high_impact, emit_step_events, use_registered_fee_vault, expected_vault_generation)
+    }
+
+    /// Executes a route without validate_route for trusted routers; protocol vaults are checked after the swap
+    pub fn route_prevalidated<'info>(
+        ctx: Context<'_, '_, 'info, 'info, Route<'info>>,
+        route_plan: Vec<RoutePlanStep>,
+        in_amount: u64,
+        quoted_out_amount: u64,
+        slippage_bps: u16,
+        platform_fee_bps: u8,
+        min_out_amount: u64,
+        accept_high_impact: bool,
+        emit_step_events: Option<bool>,
+        use_registered_fee_vault: bool,
+    ) -> Result<RouteAmounts> {
+        instructions::route_prevalidated(ctx, route_plan, in_amount, quoted_out_amount, slippage_bps, platform_fee_bps, min_out_amount, accept_high_impact, emit_step_events, use_registered_fee_vault)
+    }
+
+    /// Executes the best quoted of up to two alternative route plans that pass validation
+    pub fn route_with_fallback<'info>(
+        ctx: Context<'_, '_, 'info, 'info, Route<'info>>,
+        route_plans: Vec<Vec<RoutePlanStep>>,
+        in_amount: u64,
+        quoted_out_amounts: Vec<u64>,
+        slippage_bps: u16,
+        platform_fee_bps: u8,
+        min_out_amount: u64,
+        accept_high_impact: bool,
+        emit_step_events: Option<bool>,
+        use_registered_fee_vault: bool,
+    ) -> Result<u64> {
+        instructions::route_with_fallback(ctx, route_plans, in_amount, quoted_out_amounts, slippage_bps, platform_fee_bps, min_out_amount, accept_high_impact, emit_step_events, use_registered_fee_vault)
+    }
+
+    /// Stages a route plan and a commitment to its remaining accounts for execute_staged_route
+    pub fn stage_route(
+        ctx: Context<StageRoute>,
+        route_plan: Vec<RoutePlanStep>,
+        in_amount: u64,
+        quoted_out_amount: u64,
+        slippage_bps: u16,
+        platform_fee_bps: u8,
+        min_out_amount: u64,
+        accounts_hash: [u8; 32],
+        valid_for_slots: u64,
+    ) -> Result<()> {
+        instructions::stage_route(
+            ctx,
+            route_plan,
+            in_amount,
+            quoted_out_amount,
+            slippage_bps,
+            platform_fee_bps,
+            min_out_amount,
+            accounts_hash,
+            valid_for_slots
+        )
+    }
+
+    /// Executes the caller's staged route and closes its session
+    pub fn execute_staged_route<'info>(
+        ctx: Context<'_, '_, 'info, 'info, ExecuteStagedRoute<'info>>,
     ) -> Result<u64> {
-        instructions::route(ctx, route_plan, in_amount, quoted_out_amount, slippage_bps, platform_fee_bps)
+        instructions::execute_staged_route(ctx)
+    }
+
+    /// Closes an unexecuted route session (by its user, or by anyone once expired)
+    pub fn close_route_session(ctx: Context<CloseRouteSession>) -> Result<()> {
+        instructions::close_route_session(ctx)
     }
 
     // Limit Orders functions
@@ -147,7 +520,11 @@ pub mod flipper {
         trigger_price_bps: u32,
         trigger_type: TriggerType,
         expiry: i64,
-        slippage_bps: u16
+        slippage_bps: u16,
+        strict_price_check: bool,
+        price_signature: Option<[u8; 64]>,
+        price_publisher: Option<Pubkey>,
+        activate_at: i64,
     ) -> Result<()> {
         instructions::create_limit_order(
             ctx,
@@ -157,22 +534,34 @@ pub mod flipper {
             trigger_price_bps,
             trigger_type,
             expiry,
-            slippage_bps
+            slippage_bps,
+            strict_price_check,
+            price_signature,
+            price_publisher,
+            activate_at,
         )
     }
 
     /// Executes a limit order when trigger conditions are met
+    /// quote_signature/quote_publisher reference an ed25519 quote attestation when the registry has a quote signer
+    /// emit_step_events overrides the registry's single-hop SwapEvent setting (None = registry setting)
     pub fn execute_limit_order<'info>(
         ctx: Context<'_, '_, 'info, 'info, ExecuteLimitOrder<'info>>,
         route_plan: Vec<RoutePlanStep>,
         quoted_out_amount: u64,
         platform_fee_bps: u8,
+        quote_signature: Option<[u8; 64]>,
+        quote_publisher: Option<Pubkey>,
+        emit_step_events: Option<bool>,
     ) -> Result<u64> {
         instructions::execute_limit_order(
             ctx,
             route_plan,
             quoted_out_amount,
             platform_fee_bps,
+            quote_signature,
+            quote_publisher,
+            emit_step_events,
         )
     }
 
@@ -193,6 +582,72 @@ pub mod flipper {
         instructions::cancel_limit_order(ctx)
     }
 
+    /// Replaces an open limit order with a new one, moving tokens vault to vault
+    pub fn replace_limit_order(
+        ctx: Context<ReplaceLimitOrder>,
+        old_nonce: u64,
+        new_nonce: u64,
+        input_amount: u64,
+        min_output_amount: u64,
+        trigger_price_bps: u32,
+        trigger_type: TriggerType,
+        expiry: i64,
+        slippage_bps: u16,
+        activate_at: i64,
+    ) -> Result<()> {
+        instructions::replace_limit_order(
+            ctx,
+            old_nonce,
+            new_nonce,
+            input_amount,
+            min_output_amount,
+            trigger_price_bps,
+            trigger_type,
+            expiry,
+            slippage_bps,
+            activate_at,
+        )
+    }
+
+    /// Pauses an open limit order after repeated zero-output executions (registry authority only)
+    pub fn flag_order_for_review(ctx: Context<FlagOrderForReview>) -> Result<()> {
+        instructions::flag_order_for_review(ctx)
+    }
+
+    /// Reopens a limit order flagged for review with new min output and slippage (creator only)
+    pub fn reopen_limit_order(
+        ctx: Context<ReopenLimitOrder>,
+        min_output_amount: u64,
+        slippage_bps: u16,
+    ) -> Result<()> {
+        instructions::reopen_limit_order(ctx, min_output_amount, slippage_bps)
+    }
+
+    /// Overrides the execution fee of a limit order; None falls back to the fee schedule (creator only)
+    pub fn set_order_execution_fee(
+        ctx: Context<SetOrderExecutionFee>,
+        fee_bps: Option<u8>,
+    ) -> Result<()> {
+        instructions::set_order_execution_fee(ctx, fee_bps)
+    }
+
+    /// Runs execute_limit_order's pre-swap checks without moving funds; returns OrderExecutionCheck
+    pub fn validate_order_execution<'info>(
+        ctx: Context<'_, '_, 'info, 'info, ValidateOrderExecution<'info>>,
+        route_plan: Vec<RoutePlanStep>,
+        quoted_out_amount: u64,
+    ) -> Result<OrderExecutionCheck> {
+        instructions::validate_order_execution(ctx, route_plan, quoted_out_amount)
+    }
+
+    /// Fills a WSOL-output limit order as native SOL to the creator's system account (creator only)
+    pub fn set_order_unwrap_output(
+        ctx: Context<SetOrderUnwrapOutput>,
+        unwrap_output: bool,
+    ) -> Result<()> {
+        instructions::set_order_unwrap_output(ctx, unwrap_output)
+    }
+
     /// Closes a filled or cancelled limit order by operator and collects rent
     pub fn close_limit_order_by_operator(
         ctx: Context<CloseLimitOrderByOperator>,
@@ -212,6 +667,7 @@ pub mod flipper {
         order_trigger_price_bps: u32,
         order_expiry: i64,
         order_slippage_bps: u16,
+        emit_step_events: Option<bool>,
     ) -> Result<(u64, Pubkey)> {
         instructions::route_and_create_order(
             ctx,
@@ -224,32 +680,51 @@ pub mod flipper {
             order_min_output_amount,
             order_trigger_price_bps,
             order_expiry,
-            order_slippage_bps
+            order_slippage_bps,
+            emit_step_events
         )
     }
 
     /// Initializes a limit order account and its associated vault with support for Token 2022 extensions
     /// This instruction supports tokens with extensions like confidential transactions (xstocks)
+    /// Set create_receipt to also create an OrderReceipt PDA recording the order outcome
     pub fn init_limit_order(
         ctx: Context<InitLimitOrder>,
         nonce: u64,
         account_space: u16,
+        create_receipt: bool,
     ) -> Result<()> {
-        instructions::init_limit_order(ctx, nonce, account_space)
+        instructions::init_limit_order(ctx, nonce, account_space, create_receipt)
+    }
+
+    /// Closes a finalized order receipt and returns its rent to the creator
+    pub fn close_order_receipt(
+        ctx: Context<CloseOrderReceipt>,
+    ) -> Result<()> {
+        instructions::close_order_receipt(ctx)
+    }
+
+    /// Grows a limit order created before token programs or failed_attempts were stored on LimitOrder.
+    /// Must be called once per such order before it can be executed or cancelled. Permissionless.
+    pub fn migrate_limit_order(ctx: Context<MigrateLimitOrder>) -> Result<()> {
+        instructions::migrate_limit_order(ctx)
     }
 
     // ===== SHARED INSTRUCTIONS (Jupiter CPI Integration) =====
 
-    /// Shared route: Jupiter CPI via shared_accounts_route. Same params as route: quoted_out_amount, slippage_bps.
+    /// Shared route: Jupiter CPI via shared_accounts_route. Same params as route: quoted_out_amount, slippage_bps, min_out_amount; fee_on_input takes the fee in the source mint.
     pub fn shared_route<'info>(
         ctx: Context<'_, '_, 'info, 'info, SharedRoute<'info>>,
         in_amount: u64,
         quoted_out_amount: u64,
         slippage_bps: u16,
         platform_fee_bps: u8,
+        min_out_amount: u64,
+        fee_on_input: bool,
+        use_registered_fee_vault: bool,
         data: Vec<u8>,
     ) -> Result<u64> {
-        instructions::shared_route(ctx, in_amount, quoted_out_amount, slippage_bps, platform_fee_bps, data)
+        instructions::shared_route(ctx, in_amount, quoted_out_amount, slippage_bps, platform_fee_bps, min_out_amount, fee_on_input, use_registered_fee_vault, data)
     }
 
     /// Executes a limit order using Jupiter CPI (data + remaining_accounts).
@@ -258,8 +733,10 @@ pub mod flipper {
         quoted_out_amount: u64,
         platform_fee_bps: u8,
         data: Vec<u8>,
+        quote_signature: Option<[u8; 64]>,
+        quote_publisher: Option<Pubkey>,
     ) -> Result<u64> {
-        instructions::shared_execute_limit_order(ctx, quoted_out_amount, platform_fee_bps, data)
+        instructions::shared_execute_limit_order(ctx, quoted_out_amount, platform_fee_bps, data, quote_signature, quote_publisher)
     }
 
     /// Jupiter CPI swap + limit order creation. Same swap params as route_and_create_order: quoted_out_amount, slippage_bps.
@@ -290,4 +767,10 @@ pub mod flipper {
             data,
         )
     }
-}
\ No newline at end of file
+
+    /// Sets the MockClock time read by limit order instructions. test-clock builds only, callable by anyone.
+    #[cfg(feature = "test-clock")]
+    pub fn set_mock_clock(ctx: Context<SetMockClock>, unix_timestamp: i64) -> Result<()> {
+        instructions::set_mock_clock(ctx, unix_timestamp)
+    }
+}
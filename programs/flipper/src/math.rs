@@ -0,0 +1,153 @@
+use anchor_lang::prelude::*;
+use crate::errors::ErrorCode;
+
+/// Basis points in 100%
+pub const BPS_DENOMINATOR: u128 = 10_000;
+
+/// Returns `amount * (10_000 - bps) / 10_000`, rounded down
+/// Used for slippage floors: the lowest output a quote allows
+///
+/// # Arguments
+/// * `amount` - Amount to discount
+/// * `bps` - Discount in basis points (must not exceed 10_000)
+pub fn apply_bps_discount(amount: u64, bps: u16) -> Result<u64> {
+    let remaining_bps = BPS_DENOMINATOR
+        .checked_sub(bps as u128)
+        .ok_or(ErrorCode::InvalidCalculation)?;
+    let discounted = (amount as u128)
+        .checked_mul(remaining_bps)
+        .ok_or(ErrorCode::InvalidCalculation)?
+        .checked_div(BPS_DENOMINATOR)
+        .ok_or(ErrorCode::InvalidCalculation)?;
+    u64::try_from(discounted).map_err(|_| ErrorCode::InvalidCalculation.into())
+}
+
+/// Returns `amount * bps / 10_000`, rounded down
+/// Used for platform fees taken out of an output amount
+///
+/// # Arguments
+/// * `amount` - Amount the fee is charged on
+/// * `bps` - Fee in basis points
+pub fn apply_bps_fee(amount: u64, bps: u16) -> Result<u64> {
+    let fee = (amount as u128)
+        .checked_mul(bps as u128)
+        .ok_or(ErrorCode::InvalidCalculation)?
+        .checked_div(BPS_DENOMINATOR)
+        .ok_or(ErrorCode::InvalidCalculation)?;
+    u64::try_from(fee).map_err(|_| ErrorCode::InvalidCalculation.into())
+}
+
+/// Returns `amount * (10_000 + bps) / 10_000`, rounded down
+/// Used for take-profit thresholds, which may exceed 100% above the base amount
+///
+/// # Arguments
+/// * `amount` - Amount to raise
+/// * `bps` - Premium in basis points
+pub fn apply_bps_premium(amount: u64, bps: u32) -> Result<u64> {
+    let raised = (amount as u128)
+        .checked_mul(BPS_DENOMINATOR + bps as u128)
+        .ok_or(ErrorCode::InvalidCalculation)?
+        .checked_div(BPS_DENOMINATOR)
+        .ok_or(ErrorCode::InvalidCalculation)?;
+    u64::try_from(raised).map_err(|_| ErrorCode::InvalidCalculation.into())
+}
+
+/// Returns how far `amount` lies from `reference` in basis points of `reference`,
+/// negative when below it and rounded towards zero. A zero reference yields 0, and results
+/// beyond the i64 range saturate, since the value is only reported.
+///
+/// # Arguments
+/// * `amount` - Amount to compare
+/// * `reference` - Amount the difference is measured against
+pub fn signed_bps_difference(amount: u64, reference: u64) -> i64 {
+    if reference == 0 {
+        return 0;
+    }
+    // |amount - reference| * 10_000 < 2^78, so i128 cannot overflow
+    let difference = (amount as i128 - reference as i128) * BPS_DENOMINATOR as i128 / reference as i128;
+    difference.clamp(i64::MIN as i128, i64::MAX as i128) as i64
+}
+
+/// Rescales a raw token amount from one decimal precision to another
+/// Rounds down when reducing precision
+///
+/// # Arguments
+/// * `amount` - Raw amount expressed with `from_decimals`
+/// * `from_decimals` - Decimals of `amount`
+/// * `to_decimals` - Decimals of the result
+pub fn scale_by_decimals(amount: u64, from_decimals: u8, to_decimals: u8) -> Result<u64> {
+    let scaled = if to_decimals >= from_decimals {
+        let factor = 10u128
+            .checked_pow((to_decimals - from_decimals) as u32)
+            .ok_or(ErrorCode::InvalidCalculation)?;
+        (amount as u128)
+            .checked_mul(factor)
+            .ok_or(ErrorCode::InvalidCalculation)?
+    } else {
+        // 10^39 overflows u128, but any u64 divided by a factor that large is 0
+        match 10u128.checked_pow((from_decimals - to_decimals) as u32) {
+            Some(factor) => (amount as u128)
+                .checked_div(factor)
+                .ok_or(ErrorCode::InvalidCalculation)?,
+            None => 0,
+        }
+    };
+    u64::try_from(scaled).map_err(|_| ErrorCode::InvalidCalculation.into())
+}
+
+/// Fixed-point scale of the reserve ratio used by price_impact_bps
+const IMPACT_RATIO_SCALE: u128 = 1_000_000;
+
+/// Estimates how far a swap moves a constant-product pool's price, in basis points
+/// Swapping `amount_in` into reserve `x` moves the marginal price by 1 - (x / (x + amount_in))^2.
+/// Fees are ignored and the ratio is rounded down, so the estimate errs towards higher impact.
+/// An empty input reserve yields 10_000.
+///
+/// # Arguments
+/// * `amount_in` - Input amount of the swap
+/// * `reserve_in` - Pool reserve of the input token before the swap
+pub fn price_impact_bps(amount_in: u64, reserve_in: u64) -> Result<u16> {
+    if amount_in == 0 {
+        return Ok(0);
+    }
+    let reserve_after = (reserve_in as u128)
+        .checked_add(amount_in as u128)
+        .ok_or(ErrorCode::InvalidCalculation)?;
+    let ratio = (reserve_in as u128)
+        .checked_mul(IMPACT_RATIO_SCALE)
+        .ok_or(ErrorCode::InvalidCalculation)?
+        .checked_div(reserve_after)
+        .ok_or(ErrorCode::InvalidCalculation)?;
+    // ratio^2 carries IMPACT_RATIO_SCALE^2; bring it down to basis points
+    let remaining_bps = ratio
+        .checked_mul(ratio)
+        .ok_or(ErrorCode::InvalidCalculation)?
+        .checked_div(IMPACT_RATIO_SCALE * IMPACT_RATIO_SCALE / BPS_DENOMINATOR)
+        .ok_or(ErrorCode::InvalidCalculation)?;
+    let impact = BPS_DENOMINATOR
+        .checked_sub(remaining_bps)
+        .ok_or(ErrorCode::InvalidCalculation)?;
+    u16::try_from(impact).map_err(|_| ErrorCode::InvalidCalculation.into())
+}
+
+/// Estimates the output of swapping `amount_in` into a constant-product pool, fees ignored
+/// out = amount_in * reserve_out / (reserve_in + amount_in), rounded down.
+/// An empty pool quotes 0.
+///
+/// # Arguments
+/// * `amount_in` - Input amount of the swap
+/// * `reserve_in` - Pool reserve of the input token before the swap
+/// * `reserve_out` - Pool reserve of the output token before the swap
+pub fn constant_product_out(amount_in: u64, reserve_in: u64, reserve_out: u64) -> Result<u64> {
+    let reserve_after = (reserve_in as u128)
+        .checked_add(amount_in as u128)
+        .ok_or(ErrorCode::InvalidCalculation)?;
+    if reserve_after == 0 {
+        return Ok(0);
+    }
+    let out = (amount_in as u128)
+        .checked_mul(reserve_out as u128)
+        .ok_or(ErrorCode::InvalidCalculation)?
+        / reserve_after;
+    u64::try_from(out).map_err(|_| ErrorCode::InvalidCalculation.into())
+}
@@ -0,0 +1,207 @@
+#[cfg(test)]
+mod tests {
+    use super::super::errors::ErrorCode;
+    use super::super::math::*;
+
+    // Deterministic xorshift generator so randomized cases are reproducible
+    struct XorShift(u64);
+
+    impl XorShift {
+        fn next(&mut self) -> u64 {
+            self.0 ^= self.0 << 13;
+            self.0 ^= self.0 >> 7;
+            self.0 ^= self.0 << 17;
+            self.0
+        }
+
+        fn next_bps(&mut self) -> u16 {
+            (self.next() % 10_001) as u16
+        }
+    }
+
+    const CASES: usize = 2_000;
+
+    #[test]
+    fn test_apply_bps_discount_known_values() {
+        assert_eq!(apply_bps_discount(1_000_000, 100).unwrap(), 990_000);
+        assert_eq!(apply_bps_discount(1_000_000, 0).unwrap(), 1_000_000);
+        assert_eq!(apply_bps_discount(1_000_000, 10_000).unwrap(), 0);
+        // Rounded down
+        assert_eq!(apply_bps_discount(999, 1).unwrap(), 998);
+        assert_eq!(apply_bps_discount(u64::MAX, 0).unwrap(), u64::MAX);
+    }
+
+    #[test]
+    fn test_apply_bps_discount_rejects_more_than_100_percent() {
+        let err = apply_bps_discount(1_000, 10_001).unwrap_err();
+        assert_eq!(err, ErrorCode::InvalidCalculation.into());
+    }
+
+    #[test]
+    fn test_apply_bps_fee_known_values() {
+        assert_eq!(apply_bps_fee(1_000_000, 10).unwrap(), 1_000);
+        assert_eq!(apply_bps_fee(1_000_000, 0).unwrap(), 0);
+        assert_eq!(apply_bps_fee(1_000_000, 10_000).unwrap(), 1_000_000);
+        // Rounded down, so dust amounts pay no fee
+        assert_eq!(apply_bps_fee(999, 10).unwrap(), 0);
+        assert_eq!(apply_bps_fee(u64::MAX, 10_000).unwrap(), u64::MAX);
+    }
+
+    #[test]
+    fn test_apply_bps_fee_overflow_returns_error() {
+        // More than 100% of u64::MAX does not fit in u64
+        let err = apply_bps_fee(u64::MAX, u16::MAX).unwrap_err();
+        assert_eq!(err, ErrorCode::InvalidCalculation.into());
+    }
+
+    #[test]
+    fn test_apply_bps_discount_and_fee_randomized() {
+        let mut rng = XorShift(0x9E37_79B9_7F4A_7C15);
+        for _ in 0..CASES {
+            let amount = rng.next();
+            let bps = rng.next_bps();
+
+            let discounted = apply_bps_discount(amount, bps).unwrap();
+            let fee = apply_bps_fee(amount, bps).unwrap();
+
+            // Never more than the original amount
+            assert!(discounted <= amount);
+            assert!(fee <= amount);
+            // Discount and fee split the amount, each rounded down, so at most 1 unit is lost
+            let total = discounted as u128 + fee as u128;
+            assert!(total <= amount as u128);
+            assert!(amount as u128 - total <= 1);
+
+            // Monotonic in bps
+            if bps < 10_000 {
+                assert!(apply_bps_discount(amount, bps + 1).unwrap() <= discounted);
+                assert!(apply_bps_fee(amount, bps + 1).unwrap() >= fee);
+            }
+
+            // Monotonic in amount
+            let smaller = amount / 2;
+            assert!(apply_bps_discount(smaller, bps).unwrap() <= discounted);
+            assert!(apply_bps_fee(smaller, bps).unwrap() <= fee);
+        }
+    }
+
+    #[test]
+    fn test_apply_bps_helpers_never_panic() {
+        let mut rng = XorShift(0xD1B5_4A32_D192_ED03);
+        for _ in 0..CASES {
+            let amount = rng.next();
+            let bps = rng.next() as u16;
+            // Any input yields Ok or InvalidCalculation, never a panic
+            let _ = apply_bps_discount(amount, bps);
+            let _ = apply_bps_fee(amount, bps);
+        }
+    }
+
+    #[test]
+    fn test_scale_by_decimals_known_values() {
+        assert_eq!(scale_by_decimals(1_500_000, 6, 9).unwrap(), 1_500_000_000);
+        assert_eq!(scale_by_decimals(1_500_000_000, 9, 6).unwrap(), 1_500_000);
+        assert_eq!(scale_by_decimals(42, 6, 6).unwrap(), 42);
+        // Rounded down when reducing precision
+        assert_eq!(scale_by_decimals(1_999, 9, 6).unwrap(), 1);
+        // Any amount divided by a factor beyond u128 is 0
+        assert_eq!(scale_by_decimals(u64::MAX, 255, 0).unwrap(), 0);
+    }
+
+    #[test]
+    fn test_scale_by_decimals_overflow_returns_error() {
+        let err = scale_by_decimals(u64::MAX, 6, 9).unwrap_err();
+        assert_eq!(err, ErrorCode::InvalidCalculation.into());
+        let err = scale_by_decimals(1, 0, 255).unwrap_err();
+        assert_eq!(err, ErrorCode::InvalidCalculation.into());
+    }
+
+    #[test]
+    fn test_scale_by_decimals_randomized() {
+        let mut rng = XorShift(0x2545_F491_4F6C_DD1D);
+        for _ in 0..CASES {
+            let amount = rng.next() >> (rng.next() % 64);
+            let from_decimals = (rng.next() % 19) as u8;
+            let to_decimals = (rng.next() % 19) as u8;
+
+            let Ok(scaled) = scale_by_decimals(amount, from_decimals, to_decimals) else {
+                // Only scaling up can overflow
+                assert!(to_decimals > from_decimals);
+                continue;
+            };
+
+            if to_decimals >= from_decimals {
+                assert!(scaled >= amount);
+                // Scaling back is lossless
+                assert_eq!(scale_by_decimals(scaled, to_decimals, from_decimals).unwrap(), amount);
+            } else {
+                assert!(scaled <= amount);
+            }
+
+            // Monotonic in amount
+            let smaller = amount / 2;
+            assert!(scale_by_decimals(smaller, from_decimals, to_decimals).unwrap() <= scaled);
+        }
+    }
+
+    #[test]
+    fn test_price_impact_bps_known_values() {
+        assert_eq!(price_impact_bps(0, 1_000_000).unwrap(), 0);
+        // 30% of depth: 1 - (1 / 1.3)^2 = 40.8%
+        assert_eq!(price_impact_bps(300_000, 1_000_000).unwrap(), 4_083);
+        // 0.5% of depth is 99.3 bps, rounded up
+        assert_eq!(price_impact_bps(5_000, 1_000_000).unwrap(), 100);
+        assert_eq!(price_impact_bps(1, 0).unwrap(), 10_000);
+        assert_eq!(price_impact_bps(u64::MAX, u64::MAX).unwrap(), 7_500);
+    }
+
+    #[test]
+    fn test_price_impact_bps_randomized() {
+        let mut rng = XorShift(0x94D0_49BB_1331_11EB);
+        for _ in 0..CASES {
+            let reserve = rng.next() >> (rng.next() % 64);
+            let amount = rng.next() >> (rng.next() % 64);
+            let impact = price_impact_bps(amount, reserve).unwrap();
+            assert!(impact <= 10_000);
+
+            // Larger trades into the same pool never move the price less
+            let larger = amount.saturating_add(amount / 2 + 1);
+            assert!(price_impact_bps(larger, reserve).unwrap() >= impact);
+        }
+    }
+
+    #[test]
+    fn test_constant_product_out_known_values() {
+        // 10% of depth into a 1:1 pool: 100_000 * 1_000_000 / 1_100_000
+        assert_eq!(constant_product_out(100_000, 1_000_000, 1_000_000).unwrap(), 90_909);
+        // 1:2 pool gives about twice the input for small trades
+        assert_eq!(constant_product_out(1_000, 1_000_000, 2_000_000).unwrap(), 1_998);
+        assert_eq!(constant_product_out(0, 1_000_000, 1_000_000).unwrap(), 0);
+        assert_eq!(constant_product_out(0, 0, 1_000_000).unwrap(), 0);
+        // Never drains the output reserve
+        assert_eq!(constant_product_out(u64::MAX, 1, u64::MAX).unwrap(), u64::MAX - 1);
+    }
+
+    #[test]
+    fn test_apply_bps_premium() {
+        assert_eq!(apply_bps_premium(100_000, 0).unwrap(), 100_000);
+        assert_eq!(apply_bps_premium(100_000, 1_000).unwrap(), 110_000);
+        assert_eq!(apply_bps_premium(100_000, 100_000).unwrap(), 1_100_000);
+        assert_eq!(apply_bps_premium(3, 5_000).unwrap(), 4);
+        assert!(apply_bps_premium(u64::MAX, 1).is_err());
+    }
+
+    #[test]
+    fn test_signed_bps_difference() {
+        assert_eq!(signed_bps_difference(110_880, 110_000), 80);
+        assert_eq!(signed_bps_difference(85_500, 90_000), -500);
+        assert_eq!(signed_bps_difference(0, 90_000), -10_000);
+        // Rounded towards zero on both sides
+        assert_eq!(signed_bps_difference(10_001, 10_000), 1);
+        assert_eq!(signed_bps_difference(9_999, 10_000), -1);
+        assert_eq!(signed_bps_difference(19_999, 20_000), 0);
+        assert_eq!(signed_bps_difference(5, 0), 0);
+        // Differences beyond i64 basis points saturate instead of failing the execution
+        assert_eq!(signed_bps_difference(u64::MAX, 1), i64::MAX);
+    }
+}
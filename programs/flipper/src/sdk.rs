@@ -0,0 +1,323 @@
+use anchor_lang::prelude::*;
+use anchor_lang::{Discriminator, InstructionData};
+use crate::errors::ErrorCode;
+use crate::instructions::limit_orders_module::TriggerType;
+use crate::state::{RoutePlanStep, Swap};
+
+// Stable constants and helpers for programs and clients integrating flipper via CPI
+//
+// Everything here is derived from the same definitions the program uses, so integrators can
+// depend on the crate with the "cpi" feature instead of copying seeds and encodings.
+
+/// PDA seeds, in the order they appear in each account's seeds
+pub const ADAPTER_REGISTRY_SEED: &[u8] = b"adapter_registry";
+pub const VAULT_AUTHORITY_SEED: &[u8] = b"vault_authority";
+pub const VAULT_SEED: &[u8] = b"vault";
+pub const POOL_INFO_SEED: &[u8] = b"pool_info";
+pub const LIMIT_ORDER_SEED: &[u8] = b"limit_order";
+pub const ORDER_VAULT_SEED: &[u8] = b"order_vault";
+pub const UNWRAP_ACCOUNT_SEED: &[u8] = b"unwrap_account";
+pub const RECEIPT_SEED: &[u8] = b"receipt";
+pub const ORDER_COUNTER_SEED: &[u8] = b"order_counter";
+pub const FEE_SCHEDULE_SEED: &[u8] = b"fee_schedule";
+pub const FEE_VAULT_REGISTRY_SEED: &[u8] = b"fee_vault_registry";
+pub const PROTOCOL_CONFIG_SEED: &[u8] = b"protocol_config";
+pub const OPERATOR_STATS_SEED: &[u8] = b"operator_stats";
+pub const ROUTE_SESSION_SEED: &[u8] = b"route_session";
+pub const GLOBAL_MANAGER_SEED: &[u8] = b"global_manager";
+pub const ADMIN_LOG_SEED: &[u8] = b"admin_log";
+pub const SOL_ESCROW_SEED: &[u8] = b"sol_escrow";
+pub const VAULT_REGISTRY_SEED: &[u8] = b"vault_registry";
+pub const VAULT_RENT_PAYER_SEED: &[u8] = b"vault_rent_payer";
+pub const VAULT_META_SEED: &[u8] = b"vault_meta";
+pub const GOVERNANCE_PROPOSAL_SEED: &[u8] = b"governance_proposal";
+pub const TRADER_VOLUME_SEED: &[u8] = b"trader_volume";
+
+/// Swap tags of the adapters the program dispatches to (first byte of Swap::to_bytes)
+pub const RAYDIUM_SWAP_TAG: u8 = 7;
+pub const WHIRLPOOL_SWAP_TAG: u8 = 17;
+pub const METEORA_SWAP_TAG: u8 = 19;
+pub const WRAP_SOL_SWAP_TAG: u8 = 125;
+pub const UNWRAP_SOL_SWAP_TAG: u8 = 126;
+pub const WHIRLPOOL_TWO_HOP_SWAP_TAG: u8 = 127;
+pub const SUPPORTED_SWAP_TAGS: [u8; 6] = [
+    RAYDIUM_SWAP_TAG,
+    WHIRLPOOL_SWAP_TAG,
+    METEORA_SWAP_TAG,
+    WRAP_SOL_SWAP_TAG,
+    UNWRAP_SOL_SWAP_TAG,
+    WHIRLPOOL_TWO_HOP_SWAP_TAG,
+];
+
+/// Stable tag of a Swap variant, used as the first byte of PoolInfo seeds
+///
+/// The tag equals the variant's Borsh index, so it is also the first byte of an encoded Swap.
+///
+/// # Example
+/// ```
+/// use flipper::sdk::{swap_tag, RAYDIUM_SWAP_TAG, WHIRLPOOL_SWAP_TAG};
+/// use flipper::state::Swap;
+///
+/// assert_eq!(swap_tag(&Swap::Raydium), RAYDIUM_SWAP_TAG);
+/// assert_eq!(swap_tag(&Swap::Whirlpool { a_to_b: false }), WHIRLPOOL_SWAP_TAG);
+/// ```
+pub fn swap_tag(swap: &Swap) -> u8 {
+    swap.to_bytes()[0]
+}
+
+/// Whether the program has an adapter implementation for the swap type
+pub fn is_supported_swap(swap: &Swap) -> bool {
+    SUPPORTED_SWAP_TAGS.contains(&swap_tag(swap))
+}
+
+/// The adapter registry PDA ["adapter_registry"]
+pub fn derive_adapter_registry() -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[ADAPTER_REGISTRY_SEED], &crate::ID)
+}
+
+/// The vault authority PDA ["vault_authority"]
+pub fn derive_vault_authority() -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[VAULT_AUTHORITY_SEED], &crate::ID)
+}
+
+/// The protocol vault PDA ["vault", mint]
+///
+/// # Example
+/// ```
+/// use anchor_lang::prelude::Pubkey;
+/// use flipper::sdk::{derive_vault, VAULT_SEED};
+///
+/// let mint = Pubkey::new_unique();
+/// let (vault, _bump) = derive_vault(&mint);
+/// assert_eq!(vault, Pubkey::find_program_address(&[VAULT_SEED, mint.as_ref()], &flipper::ID).0);
+/// ```
+pub fn derive_vault(mint: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[VAULT_SEED, mint.as_ref()], &crate::ID)
+}
+
+/// The fee vault registry PDA ["fee_vault_registry"]
+pub fn derive_fee_vault_registry() -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[FEE_VAULT_REGISTRY_SEED], &crate::ID)
+}
+
+/// The protocol config PDA ["protocol_config"]
+pub fn derive_protocol_config() -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[PROTOCOL_CONFIG_SEED], &crate::ID)
+}
+
+/// The PoolInfo PDA ["pool_info", swap.to_bytes(), pool]
+pub fn derive_pool_info(swap: &Swap, pool: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[POOL_INFO_SEED, swap.to_bytes().as_ref(), pool.as_ref()], &crate::ID)
+}
+
+/// The LimitOrder PDA ["limit_order", creator, nonce (le bytes)]
+///
+/// # Example
+/// ```
+/// use anchor_lang::prelude::Pubkey;
+/// use flipper::sdk::{derive_limit_order, derive_order_vault};
+///
+/// let creator = Pubkey::new_unique();
+/// let (order, _) = derive_limit_order(&creator, 42);
+/// let (order_vault, _) = derive_order_vault(&order);
+/// assert_ne!(order, derive_limit_order(&creator, 43).0);
+/// assert_ne!(order, order_vault);
+/// ```
+pub fn derive_limit_order(creator: &Pubkey, nonce: u64) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[LIMIT_ORDER_SEED, creator.as_ref(), nonce.to_le_bytes().as_ref()], &crate::ID)
+}
+
+/// The order's input vault PDA ["order_vault", limit_order]
+pub fn derive_order_vault(limit_order: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[ORDER_VAULT_SEED, limit_order.as_ref()], &crate::ID)
+}
+
+/// The sponsor record PDA ["vault_rent_payer", order_vault] of a vault funded by a non-creator payer
+pub fn derive_vault_rent_payer(order_vault: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[VAULT_RENT_PAYER_SEED, order_vault.as_ref()], &crate::ID)
+}
+
+/// The generation record PDA ["vault_meta", vault] of a token vault
+pub fn derive_vault_meta(vault: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[VAULT_META_SEED, vault.as_ref()], &crate::ID)
+}
+
+/// The creator's order counter PDA ["order_counter", creator]
+pub fn derive_order_counter(creator: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[ORDER_COUNTER_SEED, creator.as_ref()], &crate::ID)
+}
+
+/// The transient WSOL account PDA ["unwrap_account", limit_order] used by unwrap_output fills
+pub fn derive_unwrap_account(limit_order: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[UNWRAP_ACCOUNT_SEED, limit_order.as_ref()], &crate::ID)
+}
+
+/// The lamport escrow PDA ["sol_escrow"] that WrapSol steps spend from and UnwrapSol steps pay into
+pub fn derive_sol_escrow() -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[SOL_ESCROW_SEED], &crate::ID)
+}
+
+/// The transient WSOL account PDA ["unwrap_account", sol_escrow] closed by UnwrapSol steps
+pub fn derive_sol_escrow_unwrap_account() -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[UNWRAP_ACCOUNT_SEED, derive_sol_escrow().0.as_ref()], &crate::ID)
+}
+
+/// The trader's route volume PDA ["trader_volume", trader] read for the volume fee tiers
+pub fn derive_trader_volume(trader: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[TRADER_VOLUME_SEED, trader.as_ref()], &crate::ID)
+}
+
+/// The vault registry PDA ["vault_registry"] listing the mints of created vaults
+pub fn derive_vault_registry() -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[VAULT_REGISTRY_SEED], &crate::ID)
+}
+
+/// Instruction data for `route`
+///
+/// # Example
+/// ```
+/// use flipper::sdk::route_data;
+/// use flipper::state::{RoutePlanStep, Swap};
+///
+/// let plan = vec![RoutePlanStep { swap: Swap::Raydium, percent: 100, input_index: 0, output_index: 13 }];
+/// let data = route_data(plan, 1_000_000, 990_000, 50, 0, 985_000, false, None, false, None);
+/// assert_eq!(&data[..8], flipper::sdk::ROUTE_DISCRIMINATOR);
+/// ```
+#[allow(clippy::too_many_arguments)]
+pub fn route_data(
+    route_plan: Vec<RoutePlanStep>,
+    in_amount: u64,
+    quoted_out_amount: u64,
+    slippage_bps: u16,
+    platform_fee_bps: u8,
+    min_out_amount: u64,
+    accept_high_impact: bool,
+    emit_step_events: Option<bool>,
+    use_registered_fee_vault: bool,
+    expected_vault_generation: Option<u32>,
+) -> Vec<u8> {
+    crate::instruction::Route {
+        route_plan,
+        in_amount,
+        quoted_out_amount,
+        slippage_bps,
+        platform_fee_bps,
+        min_out_amount,
+        accept_high_impact,
+        emit_step_events,
+        use_registered_fee_vault,
+        expected_vault_generation,
+    }
+    .data()
+}
+
+/// Instruction data for `create_limit_order`
+#[allow(clippy::too_many_arguments)]
+pub fn create_limit_order_data(
+    nonce: u64,
+    input_amount: u64,
+    min_output_amount: u64,
+    trigger_price_bps: u32,
+    trigger_type: TriggerType,
+    expiry: i64,
+    slippage_bps: u16,
+    strict_price_check: bool,
+    price_signature: Option<[u8; 64]>,
+    price_publisher: Option<Pubkey>,
+    activate_at: i64,
+) -> Vec<u8> {
+    crate::instruction::CreateLimitOrder {
+        nonce,
+        input_amount,
+        min_output_amount,
+        trigger_price_bps,
+        trigger_type,
+        expiry,
+        slippage_bps,
+        strict_price_check,
+        price_signature,
+        price_publisher,
+        activate_at,
+    }
+    .data()
+}
+
+/// Packs a route plan into route_compact records
+///
+/// Only supported swaps can be packed; steps swapping less than 100% carry their percent.
+///
+/// # Example
+/// ```
+/// use flipper::sdk::compact_route_plan;
+/// use flipper::state::{RoutePlanStep, Swap};
+///
+/// let plan = [RoutePlanStep { swap: Swap::Raydium, percent: 100, input_index: 0, output_index: 13 }];
+/// assert_eq!(compact_route_plan(&plan).unwrap(), vec![0, 0, 13]);
+/// ```
+pub fn compact_route_plan(route_plan: &[RoutePlanStep]) -> Result<Vec<u8>> {
+    let mut plan_bytes = Vec::with_capacity(route_plan.len() * (crate::instructions::COMPACT_STEP_LEN + 1));
+    for step in route_plan {
+        let swap_bytes = step.swap.to_bytes();
+        let kind = SUPPORTED_SWAP_TAGS
+            .iter()
+            .position(|tag| *tag == swap_bytes[0])
+            .ok_or(ErrorCode::UnknownCompactSwapTag)? as u8;
+        let header = kind | (swap_bytes[1] << 3);
+        if step.percent == 100 {
+            plan_bytes.push(header);
+        } else {
+            plan_bytes.extend_from_slice(&[header | crate::instructions::COMPACT_SPLIT_FLAG, step.percent]);
+        }
+        plan_bytes.extend_from_slice(&[step.input_index, step.output_index]);
+    }
+    Ok(plan_bytes)
+}
+
+/// Instruction data for `route_compact`
+#[allow(clippy::too_many_arguments)]
+pub fn route_compact_data(
+    plan_bytes: Vec<u8>,
+    in_amount: u64,
+    quoted_out_amount: u64,
+    slippage_bps: u16,
+    platform_fee_bps: u8,
+    min_out_amount: u64,
+    accept_high_impact: bool,
+    emit_step_events: Option<bool>,
+    use_registered_fee_vault: bool,
+    expected_vault_generation: Option<u32>,
+) -> Vec<u8> {
+    crate::instruction::RouteCompact {
+        plan_bytes,
+        in_amount,
+        quoted_out_amount,
+        slippage_bps,
+        platform_fee_bps,
+        min_out_amount,
+        accept_high_impact,
+        emit_step_events,
+        use_registered_fee_vault,
+        expected_vault_generation,
+    }
+    .data()
+}
+
+/// Instruction discriminators of the builders above
+pub const ROUTE_DISCRIMINATOR: &[u8] = crate::instruction::Route::DISCRIMINATOR;
+pub const CREATE_LIMIT_ORDER_DISCRIMINATOR: &[u8] = crate::instruction::CreateLimitOrder::DISCRIMINATOR;
+pub const ROUTE_COMPACT_DISCRIMINATOR: &[u8] = crate::instruction::RouteCompact::DISCRIMINATOR;
+
+/// Prefix of an emit_cpi! self-invocation carrying event E: the event-CPI tag, then E's discriminator
+///
+/// # Example
+/// ```
+/// use flipper::sdk::event_cpi_prefix;
+/// use flipper::state::SwapEvent;
+///
+/// assert_eq!(event_cpi_prefix::<SwapEvent>().len(), 16);
+/// ```
+pub fn event_cpi_prefix<E: Discriminator>() -> Vec<u8> {
+    let mut prefix = anchor_lang::event::EVENT_IX_TAG_LE.to_vec();
+    prefix.extend_from_slice(E::DISCRIMINATOR);
+    prefix
+}
@@ -0,0 +1,162 @@
+#[cfg(test)]
+mod tests {
+    use super::super::sdk::*;
+    use crate::instructions::limit_orders_module::TriggerType;
+    use crate::state::{RoutePlanStep, Side, Swap, SwapEvent};
+    use anchor_lang::prelude::*;
+    use anchor_lang::Discriminator;
+
+    #[test]
+    fn test_supported_swap_tags_match_borsh_index() {
+        for swap in [
+            Swap::Raydium,
+            Swap::Whirlpool { a_to_b: true },
+            Swap::Meteora,
+            Swap::WrapSol,
+            Swap::UnwrapSol,
+            Swap::WhirlpoolTwoHop { a_to_b_one: true, a_to_b_two: false },
+        ] {
+            let encoded = swap.try_to_vec().unwrap();
+            assert_eq!(encoded[0], swap_tag(&swap));
+            assert!(is_supported_swap(&swap));
+        }
+        assert!(!is_supported_swap(&Swap::Serum { side: Side::Bid }));
+    }
+
+    #[test]
+    fn test_pool_info_uses_full_swap_bytes() {
+        let pool = Pubkey::new_unique();
+        let a_to_b = derive_pool_info(&Swap::Whirlpool { a_to_b: true }, &pool).0;
+        let b_to_a = derive_pool_info(&Swap::Whirlpool { a_to_b: false }, &pool).0;
+        assert_ne!(a_to_b, b_to_a);
+        assert_eq!(
+            a_to_b,
+            Pubkey::find_program_address(
+                &[b"pool_info", Swap::Whirlpool { a_to_b: true }.to_bytes().as_ref(), pool.as_ref()],
+                &crate::ID
+            )
+            .0
+        );
+    }
+
+    #[test]
+    fn test_route_data_round_trips() {
+        let plan = vec![
+            RoutePlanStep { swap: Swap::Raydium, percent: 60, input_index: 0, output_index: 13 },
+            RoutePlanStep { swap: Swap::Meteora, percent: 40, input_index: 0, output_index: 9 },
+        ];
+        let data = route_data(plan, 1_000_000, 990_000, 50, 10, 985_000, true, Some(false), true, Some(3));
+
+        assert_eq!(&data[..8], crate::instruction::Route::DISCRIMINATOR);
+        let decoded = crate::instruction::Route::try_from_slice(&data[8..]).unwrap();
+        assert_eq!(decoded.route_plan.len(), 2);
+        assert_eq!(decoded.route_plan[1].swap, Swap::Meteora);
+        assert_eq!(decoded.route_plan[1].percent, 40);
+        assert_eq!(decoded.route_plan[1].output_index, 9);
+        assert_eq!(decoded.in_amount, 1_000_000);
+        assert_eq!(decoded.quoted_out_amount, 990_000);
+        assert_eq!(decoded.slippage_bps, 50);
+        assert_eq!(decoded.platform_fee_bps, 10);
+        assert_eq!(decoded.min_out_amount, 985_000);
+        assert!(decoded.accept_high_impact);
+        assert_eq!(decoded.emit_step_events, Some(false));
+        assert!(decoded.use_registered_fee_vault);
+        assert_eq!(decoded.expected_vault_generation, Some(3));
+    }
+
+    #[test]
+    fn test_create_limit_order_data_round_trips() {
+        let publisher = Pubkey::new_unique();
+        let data = create_limit_order_data(
+            7,
+            50_000_000,
+            33_000_000,
+            500,
+            TriggerType::StopLoss,
+            1_700_000_000,
+            300,
+            true,
+            Some([9u8; 64]),
+            Some(publisher),
+            1_699_999_000,
+        );
+
+        assert_eq!(&data[..8], CREATE_LIMIT_ORDER_DISCRIMINATOR);
+        let decoded = crate::instruction::CreateLimitOrder::try_from_slice(&data[8..]).unwrap();
+        assert_eq!(decoded.nonce, 7);
+        assert_eq!(decoded.input_amount, 50_000_000);
+        assert_eq!(decoded.min_output_amount, 33_000_000);
+        assert_eq!(decoded.trigger_price_bps, 500);
+        assert_eq!(decoded.trigger_type, TriggerType::StopLoss);
+        assert_eq!(decoded.expiry, 1_700_000_000);
+        assert_eq!(decoded.slippage_bps, 300);
+        assert!(decoded.strict_price_check);
+        assert_eq!(decoded.price_signature, Some([9u8; 64]));
+        assert_eq!(decoded.price_publisher, Some(publisher));
+        assert_eq!(decoded.activate_at, 1_699_999_000);
+    }
+
+    #[test]
+    fn test_event_cpi_prefix_ends_with_event_discriminator() {
+        let prefix = event_cpi_prefix::<SwapEvent>();
+        assert_eq!(&prefix[..8], anchor_lang::event::EVENT_IX_TAG_LE);
+        assert_eq!(&prefix[8..], SwapEvent::DISCRIMINATOR);
+    }
+
+    #[test]
+    fn test_compact_route_plan_round_trips_through_decoder() {
+        let plan = vec![
+            RoutePlanStep { swap: Swap::Whirlpool { a_to_b: false }, percent: 50, input_index: 0, output_index: 16 },
+            RoutePlanStep { swap: Swap::Meteora, percent: 50, input_index: 0, output_index: 33 },
+            RoutePlanStep { swap: Swap::Raydium, percent: 100, input_index: 33, output_index: 46 },
+        ];
+        let plan_bytes = compact_route_plan(&plan).unwrap();
+        // Two split steps carry their percent
+        assert_eq!(plan_bytes.len(), plan.len() * crate::instructions::COMPACT_STEP_LEN + 2);
+
+        let data = route_compact_data(plan_bytes.clone(), 1_000, 900, 100, 0, 0, false, None, true, None);
+        assert_eq!(&data[..8], ROUTE_COMPACT_DISCRIMINATOR);
+        let decoded = crate::instruction::RouteCompact::try_from_slice(&data[8..]).unwrap();
+        assert_eq!(decoded.plan_bytes, plan_bytes);
+        assert!(decoded.use_registered_fee_vault);
+
+        let steps = crate::instructions::decode_compact_route_plan(&decoded.plan_bytes).unwrap();
+        for (step, original) in steps.iter().zip(plan.iter()) {
+            assert_eq!(step.swap, original.swap);
+            assert_eq!(step.percent, original.percent);
+            assert_eq!(step.input_index, original.input_index);
+            assert_eq!(step.output_index, original.output_index);
+        }
+    }
+
+    #[test]
+    fn test_compact_route_plan_rejects_unsupported_swaps() {
+        let step = |swap: Swap| RoutePlanStep { swap, percent: 100, input_index: 0, output_index: 0 };
+        assert!(compact_route_plan(&[step(Swap::Serum { side: Side::Ask })]).is_err());
+        assert!(compact_route_plan(&[step(Swap::Raydium)]).is_ok());
+    }
+
+    #[test]
+    fn test_compact_route_plan_is_smaller_than_borsh() {
+        let swaps = [
+            Swap::Raydium,
+            Swap::Whirlpool { a_to_b: true },
+            Swap::Meteora,
+            Swap::WrapSol,
+            Swap::UnwrapSol,
+            Swap::WhirlpoolTwoHop { a_to_b_one: true, a_to_b_two: false },
+        ];
+        for swap in swaps {
+            for percent in [100, 40] {
+                let plan = [RoutePlanStep { swap: swap.clone(), percent, input_index: 200, output_index: 250 }];
+                let compact = compact_route_plan(&plan).unwrap();
+                let borsh = plan[0].try_to_vec().unwrap();
+                if percent == 100 {
+                    assert!(compact.len() < borsh.len(), "{:?}", swap);
+                } else {
+                    assert!(compact.len() <= borsh.len(), "{:?}", swap);
+                }
+            }
+        }
+    }
+}
@@ -1,5 +1,6 @@
 use anchor_lang::prelude::*;
 use crate::errors::ErrorCode;
+use crate::math;
 
 // Stores the adapter registry state, including authority and supported adapters
 #[account]
@@ -8,18 +9,188 @@ pub struct AdapterRegistry {
     pub operators: Vec<Pubkey>,      // List of operator public keys authorized to manage adapters and pools
     pub supported_adapters: Vec<AdapterInfo>, // List of supported DEX adapters
     pub bump: u8,                     // Bump seed for PDA
+    pub memo_program_id: Pubkey,      // Memo program adapters must receive (default pubkey = SPL Memo)
+    pub quote_signer: Pubkey,         // Key that must attest limit order quotes (default pubkey = not required)
+    pub suspended_operators: Vec<Pubkey>, // Operators suspended until the authority reinstates them
+    pub order_creation_fee_lamports: u64, // Flat fee charged to limit order creators (0 = disabled)
+    pub treasury: Pubkey,             // Receives order creation fees
+    pub adapter_pools: Vec<AdapterPools>, // Per-adapter pool whitelists (no entry = any enabled pool)
+    pub trusted_routers: Vec<Pubkey>, // Integrators allowed to call route_prevalidated
+    pub adapter_impact_caps: Vec<AdapterImpactCap>, // Per-adapter price impact caps (no entry = uncapped)
+    pub order_price_band_bps: u16,    // Band around the reference price for strict limit orders (0 = default)
+    pub suppress_step_events: bool,   // Skip per-step SwapEvents on single-hop routes (false = emit_step_events)
+    pub enforce_quote_sanity: bool,   // Reject route quotes outside the band around the on-chain quote
+    pub quote_sanity_band_bps: u16,   // Band around the on-chain quote for route quotes (0 = default)
+    pub reject_delegated_vaults: bool, // Refuse to route through vaults that have a delegate set
+    pub pool_attestation_max_age_slots: u64, // Slots a pool liquidity attestation stays fresh for routes (0 = not required)
+    pub paused_adapters: Vec<Swap>,   // Adapters paused with set_adapter_enabled (no entry = enabled)
+    pub sandboxed_adapters: Vec<AdapterSandbox>, // Adapters restricted to a sandbox list (no entry = open to all)
+    pub retired_adapters: Vec<AdapterInfo>, // Last registration of each removed adapter, so re-adding it elsewhere is governed
+    pub adapter_pool_counts: Vec<AdapterPoolCount>, // Enabled PoolInfos per adapter (no entry = none)
 }
 
+// Band used for strict limit order price checks until the authority sets one (±50%)
+pub const DEFAULT_ORDER_PRICE_BAND_BPS: u16 = 5_000;
+
+// Band used for route quote sanity checks until an operator sets one (±20%)
+pub const DEFAULT_QUOTE_SANITY_BAND_BPS: u16 = 2_000;
+
+// SPL Memo program, expected as the memo_program account of Whirlpool and Meteora swaps
+pub const SPL_MEMO_PROGRAM_ID: Pubkey = anchor_lang::solana_program::pubkey!("MemoSq4gqABAXKb96qnH8TysNcWxMyWCqXgDLGmfcHr");
+
 // Stores information about a single pool
 #[account]
 pub struct PoolInfo {
     pub adapter_swap_type: Swap, // The swap type of the adapter this pool belongs to
     pub pool_address: Pubkey,   // The pool's public key
     pub enabled: bool,          // Whether the pool is enabled or disabled
+    pub last_verified_slot: u64, // Slot of the last attestation that found both reserves above the threshold (0 = never)
+    pub min_liquidity_threshold: u64, // Amount each reserve must exceed for an attestation to pass
+    pub window_start: i64,      // Unix timestamp the current volume window started at
+    pub window_volume: u64,     // Step input routed through the pool since window_start
+    pub max_volume_per_window: u64, // Most step input the pool takes per POOL_VOLUME_WINDOW_SECS (0 = uncapped)
+    pub counted: bool,          // Whether the pool is included in its adapter's enabled pool count
+}
+
+// Serialized size reserved for a supported Swap variant (enum tag + small payload)
+pub const SWAP_TYPE_SPACE: usize = 4;
+
+// Length of the window a pool's volume cap counts step input over
+pub const POOL_VOLUME_WINDOW_SECS: i64 = 86_400;
+
+// Percent of a pool's volume cap its window volume must exceed for a PoolVolumeWarning, until
+// the authority sets one in the ProtocolConfig
+pub const DEFAULT_POOL_VOLUME_WARNING_PCT: u8 = 80;
+
+// Whether `window_volume` is above `warning_pct` percent of `cap`
+pub fn exceeds_volume_warning(window_volume: u64, cap: u64, warning_pct: u8) -> bool {
+    u128::from(window_volume) * 100 > u128::from(cap) * u128::from(warning_pct)
+}
+
+impl PoolInfo {
+    // Account size: discriminator + adapter_swap_type + pool_address + enabled + last_verified_slot
+    // + min_liquidity_threshold + window_start + window_volume + max_volume_per_window + counted
+    pub const SPACE: usize = 8 + SWAP_TYPE_SPACE + 32 + 1 + 8 + 8 + 8 + 8 + 8 + 1;
+    // Account size before last_verified_slot and min_liquidity_threshold, grown by migrate_pool_info
+    pub const LEGACY_SPACE: usize = 8 + SWAP_TYPE_SPACE + 32 + 1;
+    // Account size before the volume cap fields, grown by migrate_pool_info
+    pub const PRE_VOLUME_CAP_SPACE: usize = Self::LEGACY_SPACE + 8 + 8;
+    // Account size before counted, grown by migrate_pool_info
+    pub const PRE_POOL_COUNT_SPACE: usize = Self::PRE_VOLUME_CAP_SPACE + 8 + 8 + 8;
+    // Smallest data a current PoolInfo can have: discriminator + one-byte Swap tag + pool_address
+    // + enabled + last_verified_slot + min_liquidity_threshold + the volume cap fields + counted
+    pub const MIN_DATA_LEN: usize = 8 + 1 + 32 + 1 + 8 + 8 + 8 + 8 + 8 + 1;
+
+    // Adds a step's input to the pool's window volume, first starting a new window if
+    // POOL_VOLUME_WINDOW_SECS have passed since window_start. Uncapped pools are not counted.
+    // Fails with PoolVolumeCapExceeded if the step would take the window over the cap.
+    // # Arguments
+    // * `amount` - Input amount of the step routed through the pool
+    // * `now` - Current unix timestamp
+    // * `warning_pct` - Percent of the cap above which the caller warns, see exceeds_volume_warning
+    // # Returns
+    // * `bool` - Whether the new window volume is above the warning threshold
+    pub fn record_volume(&mut self, amount: u64, now: i64, warning_pct: u8) -> Result<bool> {
+        if self.max_volume_per_window == 0 {
+            return Ok(false);
+        }
+        if now.saturating_sub(self.window_start) >= POOL_VOLUME_WINDOW_SECS {
+            self.window_start = now;
+            self.window_volume = 0;
+        }
+        let window_volume = self.window_volume.checked_add(amount).ok_or(ErrorCode::InvalidCalculation)?;
+        if window_volume > self.max_volume_per_window {
+            msg!("Pool {}: {} of {} window volume used, step adds {}", self.pool_address, self.window_volume, self.max_volume_per_window, amount);
+            return Err(ErrorCode::PoolVolumeCapExceeded.into());
+        }
+        self.window_volume = window_volume;
+        Ok(exceeds_volume_warning(window_volume, self.max_volume_per_window, warning_pct))
+    }
+
+    // Loads a PoolInfo, first rejecting accounts too short to hold one (un-migrated legacy
+    // PoolInfos included) or without the PoolInfo discriminator, so common client mistakes fail
+    // cheaply with InvalidPoolInfoAccount instead of an opaque deserialization error
+    pub fn load_checked<'a>(account: &'a AccountInfo<'a>) -> Result<Account<'a, PoolInfo>> {
+        {
+            let data = account.try_borrow_data()?;
+            if data.len() < Self::MIN_DATA_LEN || &data[..8] != Self::DISCRIMINATOR {
+                msg!("PoolInfo account {}: {} bytes, discriminator {:?}", account.key(), data.len(), &data[..data.len().min(8)]);
+                return Err(ErrorCode::InvalidPoolInfoAccount.into());
+            }
+        }
+        Account::<PoolInfo>::try_from(account)
+    }
 }
 
 // Implementation of methods for AdapterRegistry
 impl AdapterRegistry {
+    // Maximum number of operators the registry account has room for
+    pub const MAX_OPERATORS: usize = 10;
+    // Maximum number of adapters the registry account has room for
+    pub const MAX_ADAPTERS: usize = 10;
+    // Maximum number of wallets allowed through a sandboxed adapter
+    pub const MAX_SANDBOX_USERS: usize = 8;
+    // Room per AdapterInfo: name length prefix + 32 bytes shared by name and swap_type + program_id
+    pub const ADAPTER_INFO_SPACE: usize = 4 + 32 + 32;
+    // Maximum number of removed adapters the registry remembers the program of
+    pub const MAX_RETIRED_ADAPTERS: usize = 2 * Self::MAX_ADAPTERS;
+    // Maximum number of trusted routers the registry account has room for
+    pub const MAX_TRUSTED_ROUTERS: usize = 10;
+    // Maximum number of whitelisted pools per adapter
+    pub const MAX_ADAPTER_POOLS: usize = 8;
+    // Room per AdapterPools: swap_type + pool_addresses vec
+    pub const ADAPTER_POOLS_SPACE: usize = SWAP_TYPE_SPACE + 4 + Self::MAX_ADAPTER_POOLS * 32;
+    // Room per AdapterImpactCap: swap_type + max_step_impact_bps
+    pub const ADAPTER_IMPACT_CAP_SPACE: usize = SWAP_TYPE_SPACE + 2;
+    // Room per AdapterSandbox: swap_type + sandbox_users vec
+    pub const ADAPTER_SANDBOX_SPACE: usize = SWAP_TYPE_SPACE + 4 + Self::MAX_SANDBOX_USERS * 32;
+    // Maximum number of adapters with enabled pools: registered ones plus retired ones not yet drained
+    pub const MAX_ADAPTER_POOL_COUNTS: usize = Self::MAX_ADAPTERS + Self::MAX_RETIRED_ADAPTERS;
+    // Room per AdapterPoolCount: swap_type + enabled_pools
+    pub const ADAPTER_POOL_COUNT_SPACE: usize = SWAP_TYPE_SPACE + 4;
+    // Account size: discriminator + authority + operators vec + supported_adapters vec + bump
+    // + memo_program_id + quote_signer + suspended_operators vec + order_creation_fee_lamports + treasury
+    // + adapter_pools vec + trusted_routers vec + adapter_impact_caps vec + order_price_band_bps
+    // + suppress_step_events + enforce_quote_sanity + quote_sanity_band_bps + reject_delegated_vaults
+    // + pool_attestation_max_age_slots + paused_adapters vec + sandboxed_adapters vec + retired_adapters vec
+    // + adapter_pool_counts vec
+    pub const SPACE: usize = 8
+        + 32
+        + 4 + Self::MAX_ADAPTERS * Self::ADAPTER_INFO_SPACE
+        + 4 + Self::MAX_OPERATORS * 32
+        + 1
+        + 32
+        + 32
+        + 4 + Self::MAX_OPERATORS * 32
+        + 8
+        + 32
+        + 4 + Self::MAX_ADAPTERS * Self::ADAPTER_POOLS_SPACE
+        + 4 + Self::MAX_TRUSTED_ROUTERS * 32
+        + 4 + Self::MAX_ADAPTERS * Self::ADAPTER_IMPACT_CAP_SPACE
+        + 2
+        + 1
+        + 1
+        + 2
+        + 1
+        + 8
+        + 4 + Self::MAX_ADAPTERS * SWAP_TYPE_SPACE
+        + 4 + Self::MAX_ADAPTERS * Self::ADAPTER_SANDBOX_SPACE
+        + 4 + Self::MAX_RETIRED_ADAPTERS * Self::ADAPTER_INFO_SPACE
+        + 4 + Self::MAX_ADAPTER_POOL_COUNTS * Self::ADAPTER_POOL_COUNT_SPACE;
+
+    // Returns the memo program adapters must receive
+    // Registries created before memo_program_id existed read it as the default pubkey,
+    // which falls back to the SPL Memo program
+    // # Returns
+    // * `Pubkey` - Configured memo program, or SPL Memo if unset
+    pub fn memo_program(&self) -> Pubkey {
+        if self.memo_program_id == Pubkey::default() {
+            SPL_MEMO_PROGRAM_ID
+        } else {
+            self.memo_program_id
+        }
+    }
+
     // Checks if a swap type is supported by the registry
     // # Arguments
     // * `swap` - The swap type to check
@@ -29,26 +200,985 @@ impl AdapterRegistry {
         self.supported_adapters.iter().any(|adapter| adapter.swap_type == *swap)
     }
 
+    // Checks if a swap type is registered and not paused
+    // # Arguments
+    // * `swap` - The swap type to check
+    // # Returns
+    // * `bool` - True if the adapter is registered and enabled, false otherwise
+    pub fn is_adapter_enabled(&self, swap: &Swap) -> bool {
+        self.is_supported_adapter(swap) && !self.paused_adapters.contains(swap)
+    }
+
+    // Reports the registry's part of whether a swap type can be routed through: whether it has
+    // an AdapterInfo and is not paused. Whether the program implements the swap type at all is
+    // up to adapter_connector_module::adapter_status, which wraps this
+    // # Arguments
+    // * `swap` - The swap type to describe
+    // # Returns
+    // * `AdapterStatus` - Enabled, Disabled or NotConfigured
+    pub fn describe_adapter(&self, swap: &Swap) -> AdapterStatus {
+        if !self.is_supported_adapter(swap) {
+            AdapterStatus::NotConfigured
+        } else if self.paused_adapters.contains(swap) {
+            AdapterStatus::Disabled
+        } else {
+            AdapterStatus::Enabled
+        }
+    }
+
     // Retrieves the program ID for a given swap type
     // # Arguments
     // * `swap` - The swap type to query
     // # Returns
-    // * `Result<Pubkey>` - Program ID of the adapter, or error if not supported
+    // * `Result<Pubkey>` - Program ID of the adapter, or AdapterNotConfigured without an AdapterInfo
     pub fn get_adapter_program_id(&self, swap: &Swap) -> Result<Pubkey> {
         self.supported_adapters
             .iter()
             .find(|adapter| adapter.swap_type == *swap)
             .map(|adapter| adapter.program_id)
-            .ok_or(error!(ErrorCode::SwapNotSupported))
+            .ok_or(error!(ErrorCode::AdapterNotConfigured))
+    }
+
+    // Returns the program a swap type was last registered with, whether it is still in
+    // supported_adapters or was removed by disable_adapter
+    // # Arguments
+    // * `swap` - The swap type to query
+    // # Returns
+    // * `Option<Pubkey>` - Last registered program ID, or None if the swap type was never registered
+    pub fn last_program_id(&self, swap: &Swap) -> Option<Pubkey> {
+        self.supported_adapters
+            .iter()
+            .chain(self.retired_adapters.iter())
+            .find(|adapter| adapter.swap_type == *swap)
+            .map(|adapter| adapter.program_id)
+    }
+
+    // Returns how many enabled PoolInfos a swap type has
+    // # Arguments
+    // * `swap` - The swap type to query
+    // # Returns
+    // * `u32` - Enabled PoolInfos counted for the swap type
+    pub fn enabled_pool_count(&self, swap: &Swap) -> u32 {
+        self.adapter_pool_counts
+            .iter()
+            .find(|entry| entry.swap_type == *swap)
+            .map_or(0, |entry| entry.enabled_pools)
+    }
+
+    // Counts a newly enabled PoolInfo of a swap type
+    // # Arguments
+    // * `swap` - The swap type of the PoolInfo
+    // # Returns
+    // * `Result<()>` - AdapterPoolCountsFull if no entry is left for a new swap type
+    pub fn add_enabled_pool(&mut self, swap: &Swap) -> Result<()> {
+        if let Some(entry) = self.adapter_pool_counts.iter_mut().find(|entry| entry.swap_type == *swap) {
+            entry.enabled_pools = entry.enabled_pools.checked_add(1).ok_or(ErrorCode::InvalidCalculation)?;
+            return Ok(());
+        }
+        require!(self.adapter_pool_counts.len() < Self::MAX_ADAPTER_POOL_COUNTS, ErrorCode::AdapterPoolCountsFull);
+        self.adapter_pool_counts.push(AdapterPoolCount { swap_type: swap.clone(), enabled_pools: 1 });
+        Ok(())
+    }
+
+    // Uncounts a disabled PoolInfo of a swap type, dropping the entry once none is left
+    // # Arguments
+    // * `swap` - The swap type of the PoolInfo
+    // # Returns
+    // * `Result<()>` - InvalidCalculation if the swap type has no counted PoolInfo
+    pub fn remove_enabled_pool(&mut self, swap: &Swap) -> Result<()> {
+        let entry = self
+            .adapter_pool_counts
+            .iter_mut()
+            .find(|entry| entry.swap_type == *swap)
+            .ok_or(ErrorCode::InvalidCalculation)?;
+        entry.enabled_pools = entry.enabled_pools.checked_sub(1).ok_or(ErrorCode::InvalidCalculation)?;
+        if entry.enabled_pools == 0 {
+            self.adapter_pool_counts.retain(|entry| entry.swap_type != *swap);
+        }
+        Ok(())
+    }
+
+    // Checks if a public key is an operator that is not suspended
+    // # Arguments
+    // * `key` - The public key to check
+    // # Returns
+    // * `bool` - True if the key is a registered, unsuspended operator, false otherwise
+    pub fn is_active_operator(&self, key: &Pubkey) -> bool {
+        self.operators.contains(key) && !self.is_suspended_operator(key)
+    }
+
+    // Checks if a public key is a suspended operator
+    // # Arguments
+    // * `key` - The public key to check
+    // # Returns
+    // * `bool` - True if the key is suspended, false otherwise
+    pub fn is_suspended_operator(&self, key: &Pubkey) -> bool {
+        self.suspended_operators.contains(key)
     }
 
     // Checks if a public key is an authorized operator
     // # Arguments
     // * `key` - The public key to check
     // # Returns
-    // * `bool` - True if the key is an operator or the authority, false otherwise
+    // * `bool` - True if the key is an active operator or the authority, false otherwise
     pub fn is_authorized_operator(&self, key: &Pubkey) -> bool {
-        self.authority == *key || self.operators.contains(key)
+        self.authority == *key || self.is_active_operator(key)
+    }
+
+    // Checks if a pool may be routed through for a swap type
+    // Adapters without a pool whitelist accept any enabled PoolInfo
+    // # Arguments
+    // * `swap` - The swap type of the route step
+    // * `pool_address` - The pool of the step's PoolInfo
+    // # Returns
+    // * `bool` - True if the adapter has no whitelist or the pool is on it
+    pub fn is_pool_allowed(&self, swap: &Swap, pool_address: &Pubkey) -> bool {
+        self.adapter_pools
+            .iter()
+            .find(|entry| entry.swap_type == *swap)
+            .map_or(true, |entry| entry.pool_addresses.contains(pool_address))
+    }
+
+    // Checks if a user may route through an adapter
+    // Adapters in sandbox mode only accept the wallets on their sandbox_users list
+    // # Arguments
+    // * `swap` - The swap type of the route step
+    // * `user` - The route's user_transfer_authority, or the order creator for limit orders
+    // # Returns
+    // * `bool` - True if the adapter is not sandboxed or the user is on its list
+    pub fn is_sandbox_user_allowed(&self, swap: &Swap, user: &Pubkey) -> bool {
+        self.sandboxed_adapters
+            .iter()
+            .find(|entry| entry.swap_type == *swap)
+            .map_or(true, |entry| entry.sandbox_users.contains(user))
+    }
+
+    // Checks if a key may skip route validation through route_prevalidated
+    // # Arguments
+    // * `key` - The public key to check
+    // # Returns
+    // * `bool` - True if the key is a trusted router
+    pub fn is_trusted_router(&self, key: &Pubkey) -> bool {
+        self.trusted_routers.contains(key)
+    }
+
+    // Returns the highest price impact a single route step through the adapter may have
+    // # Arguments
+    // * `swap` - The swap type of the route step
+    // # Returns
+    // * `u16` - Cap in basis points, or 0 if the adapter is uncapped
+    pub fn max_step_impact_bps(&self, swap: &Swap) -> u16 {
+        self.adapter_impact_caps
+            .iter()
+            .find(|entry| entry.swap_type == *swap)
+            .map_or(0, |entry| entry.max_step_impact_bps)
+    }
+
+    // Returns the band strict limit orders must fall within around the reference price
+    // Registries migrated before order_price_band_bps existed read it as 0, which falls back
+    // to DEFAULT_ORDER_PRICE_BAND_BPS
+    // # Returns
+    // * `u16` - Configured band in basis points, or the default if unset
+    pub fn price_band_bps(&self) -> u16 {
+        if self.order_price_band_bps == 0 {
+            DEFAULT_ORDER_PRICE_BAND_BPS
+        } else {
+            self.order_price_band_bps
+        }
+    }
+
+    // Returns whether the registry emits per-step SwapEvents for single-hop routes
+    // Stored inverted so registries migrated before the flag existed keep emitting them
+    pub fn emit_step_events(&self) -> bool {
+        !self.suppress_step_events
+    }
+
+    // Decides whether a route emits its per-step SwapEvents
+    // Multi-hop routes always do; a single-hop route's SwapEvent repeats the aggregated event,
+    // so it follows the instruction override, else the registry setting
+    // # Arguments
+    // * `steps` - Number of executed route steps
+    // * `emit_override` - Instruction-level choice (None = registry setting)
+    pub fn should_emit_step_events(&self, steps: usize, emit_override: Option<bool>) -> bool {
+        steps > 1 || emit_override.unwrap_or_else(|| self.emit_step_events())
+    }
+
+    // Returns the band route quotes must fall within around the on-chain quote
+    // # Returns
+    // * `u16` - Configured band in basis points, or DEFAULT_QUOTE_SANITY_BAND_BPS if unset
+    pub fn quote_band_bps(&self) -> u16 {
+        if self.quote_sanity_band_bps == 0 {
+            DEFAULT_QUOTE_SANITY_BAND_BPS
+        } else {
+            self.quote_sanity_band_bps
+        }
+    }
+}
+
+// Tracks when an operator last did work, so clients can tell whether any keeper is alive,
+// and the sponsored order vaults whose rent is still owed back to it
+#[account]
+pub struct OperatorStats {
+    pub operator: Pubkey,                   // Operator the stats belong to
+    pub last_active: i64,                   // Unix timestamp of the last heartbeat or execution (0 = never)
+    pub bump: u8,                           // PDA bump seed
+    pub outstanding_obligations: u32,       // Order vaults sponsored by the operator and not closed yet
+    pub obligations_transferred_to: Pubkey, // Treasury refunded instead after a forced removal (default = none)
+}
+
+impl OperatorStats {
+    // Account size: discriminator + operator + last_active + bump + outstanding_obligations
+    // + obligations_transferred_to
+    pub const SPACE: usize = 8 + 32 + 8 + 1 + 4 + 32;
+    // Minimum number of seconds between two recorded activities
+    pub const HEARTBEAT_INTERVAL: i64 = 60;
+
+    // Records a heartbeat at `now` unless the previous activity is less than HEARTBEAT_INTERVAL old
+    // # Arguments
+    // * `now` - Current unix timestamp
+    // # Returns
+    // * `bool` - True if last_active was updated
+    pub fn record_activity(&mut self, now: i64) -> bool {
+        if self.last_active != 0 && now < self.last_active.saturating_add(Self::HEARTBEAT_INTERVAL) {
+            return false;
+        }
+        self.last_active = now;
+        true
+    }
+
+    // Reads the ["operator_stats", operator] PDA, if it has been created
+    // # Arguments
+    // * `operator_stats` - The operator's stats PDA
+    // * `program_id` - This program's ID
+    pub fn load(operator_stats: &AccountInfo, program_id: &Pubkey) -> Result<Option<OperatorStats>> {
+        if operator_stats.owner != program_id || operator_stats.data_is_empty() {
+            return Ok(None);
+        }
+        let data = operator_stats.try_borrow_data()?;
+        Ok(Some(OperatorStats::try_deserialize(&mut &data[..])?))
+    }
+
+    // Writes the stats back into their account
+    pub fn store(&self, operator_stats: &AccountInfo) -> Result<()> {
+        self.try_serialize(&mut &mut operator_stats.try_borrow_mut_data()?[..])
+    }
+
+    // Account a vault sponsored by this operator refunds its rent to
+    // # Returns
+    // * `Pubkey` - The operator, or the treasury its obligations were transferred to
+    pub fn refund_destination(&self) -> Pubkey {
+        if self.obligations_transferred_to == Pubkey::default() {
+            self.operator
+        } else {
+            self.obligations_transferred_to
+        }
+    }
+
+    // Counts a newly sponsored order vault
+    pub fn add_obligation(&mut self) -> Result<()> {
+        self.outstanding_obligations = self.outstanding_obligations
+            .checked_add(1)
+            .ok_or(ErrorCode::InvalidCalculation)?;
+        Ok(())
+    }
+
+    // Settles a sponsored order vault once it is closed and its rent refunded
+    pub fn settle_obligation(&mut self) {
+        self.outstanding_obligations = self.outstanding_obligations.saturating_sub(1);
+    }
+}
+
+// Administrative action recorded in the AdminLog
+#[repr(u8)]
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug, PartialEq, Eq, Default)]
+pub enum AdminAction {
+    #[default]
+    None,                      // Unused ring buffer slot
+    InitializePoolInfo,
+    AddOperator,
+    RemoveOperator,
+    AddTrustedRouter,
+    RemoveTrustedRouter,
+    SuspendSelf,
+    SuspendOperator,
+    ReinstateOperator,
+    ConfigureAdapter,
+    DisableAdapter,
+    SetAdapterEnabled,
+    DisablePool,
+    ChangeAuthority,
+    ResetAdapterRegistry,
+    SetMemoProgram,
+    SetQuoteSigner,
+    SetOrderCreationFee,
+    SetOrderPriceBand,
+    SetAdapterPools,
+    SetMaxStepImpact,
+    CreateVault,
+    CloseVault,
+    WrapIntoVault,
+    UnwrapFromVault,
+    InitializeVaults,
+    ProposeVaultAuthorityAdmin,
+    AcceptVaultAuthorityAdmin,
+    SetJupiterProgram,
+    ChangeGlobalManager,
+    WithdrawPlatformFees,
+    CreateVaultWithExtensions,
+    RevokeVaultDelegations,
+    SetFeeSchedule,
+    SetEmitStepEvents,
+    SetQuoteSanity,
+    SetJupiterDataLimit,
+    SetAdapterSandbox,
+    GraduateAdapter,
+    SetRejectDelegatedVaults,
+    SetFeeVault,
+    RemoveFeeVault,
+    CreateProtocolConfig,
+    SetProtocolLimits,
+    InitializeVaultRegistry,
+    RegisterExistingVaults,
+    SetPoolLiquidityThreshold,
+    SetPoolAttestationMaxAge,
+    SetOrderCreationPaused,
+    SetOrderExecutionPaused,
+    ProposeGovernanceCall,
+    CancelGovernanceProposal,
+    SetGovernanceDelay,
+    SetVolumeFeeTiers,
+    SetPoolVolumeCap,
+    SetPoolVolumeWarningPct,
+    FlagOrderForReview,
+}
+
+// One AdminLog entry
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug, PartialEq, Eq, Default)]
+pub struct AdminLogEntry {
+    pub action: AdminAction, // What was done
+    pub actor: Pubkey,       // Signer that did it
+    pub target: Pubkey,      // Key the action applied to (the registry for registry-wide settings)
+    pub timestamp: i64,      // Unix timestamp of the action
+}
+
+// Number of entries the AdminLog keeps before overwriting the oldest
+pub const ADMIN_LOG_CAPACITY: usize = 32;
+
+// Most entries get_admin_log returns at once, bounded by the 1024-byte return data limit
+pub const ADMIN_LOG_PAGE_SIZE: usize = 12;
+
+// Ring buffer of the latest administrative actions, PDA ["admin_log"]
+#[account]
+pub struct AdminLog {
+    pub entries: [AdminLogEntry; ADMIN_LOG_CAPACITY], // Slot total_actions % ADMIN_LOG_CAPACITY is written next
+    pub total_actions: u64,                            // Actions recorded since the log was created
+    pub bump: u8,                                      // PDA bump seed
+}
+
+impl AdminLog {
+    // Room per AdminLogEntry: action + actor + target + timestamp
+    pub const ENTRY_SPACE: usize = 1 + 32 + 32 + 8;
+    // Account size: discriminator + entries + total_actions + bump
+    pub const SPACE: usize = 8 + ADMIN_LOG_CAPACITY * Self::ENTRY_SPACE + 8 + 1;
+
+    // Appends an entry, overwriting the oldest once the buffer is full
+    // # Arguments
+    // * `entry` - The action to record
+    pub fn push(&mut self, entry: AdminLogEntry) {
+        let slot = (self.total_actions % ADMIN_LOG_CAPACITY as u64) as usize;
+        self.entries[slot] = entry;
+        self.total_actions = self.total_actions.saturating_add(1);
+    }
+
+    // Returns recorded entries, newest first
+    // # Arguments
+    // * `skip` - Number of newest entries to skip
+    // * `limit` - Maximum number of entries to return
+    // # Returns
+    // * `Vec<AdminLogEntry>` - Up to `limit` entries still held in the buffer
+    pub fn recent(&self, skip: usize, limit: usize) -> Vec<AdminLogEntry> {
+        let held = self.total_actions.min(ADMIN_LOG_CAPACITY as u64) as usize;
+        (skip..held)
+            .take(limit)
+            .map(|age| {
+                let sequence = self.total_actions - 1 - age as u64;
+                self.entries[(sequence % ADMIN_LOG_CAPACITY as u64) as usize]
+            })
+            .collect()
+    }
+
+    // Records an action in the AdminLog account if it has been created
+    // Admin instructions always pass the ["admin_log"] PDA; before initialize_admin_log it is
+    // an empty system account and nothing is recorded
+    // # Arguments
+    // * `admin_log` - The ["admin_log"] PDA
+    // * `program_id` - This program's ID
+    // * `action` - What was done
+    // * `actor` - Signer that did it
+    // * `target` - Key the action applied to
+    pub fn record(
+        admin_log: &AccountInfo,
+        program_id: &Pubkey,
+        action: AdminAction,
+        actor: Pubkey,
+        target: Pubkey,
+    ) -> Result<()> {
+        if admin_log.owner != program_id || admin_log.data_is_empty() {
+            return Ok(());
+        }
+
+        let mut data = admin_log.try_borrow_mut_data()?;
+        let mut log = AdminLog::try_deserialize(&mut &data[..])?;
+        log.push(AdminLogEntry {
+            action,
+            actor,
+            target,
+            timestamp: Clock::get()?.unix_timestamp,
+        });
+        log.try_serialize(&mut &mut data[..])?;
+        Ok(())
+    }
+}
+
+// One tier of an execution fee schedule
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug, PartialEq, Eq, Default)]
+pub struct FeeTier {
+    pub max_input_amount: u64, // Largest order input (raw units of the schedule's mint) this tier covers
+    pub fee_bps: u8,           // Execution fee for orders in this tier
+}
+
+// Most tiers a FeeSchedule holds
+pub const MAX_FEE_TIERS: usize = 4;
+
+// Execution fee tiers by order size for limit orders selling one mint, PDA ["fee_schedule", mint]
+// Orders without a creator override pay the tier covering their input_amount instead of the
+// operator-supplied platform_fee_bps
+#[account]
+pub struct FeeSchedule {
+    pub mint: Pubkey,                       // Input mint the tiers are denominated in
+    pub tiers: [FeeTier; MAX_FEE_TIERS],    // First tier_count entries, max_input_amount strictly increasing
+    pub tier_count: u8,                     // Tiers in use (0 = schedule disabled)
+    pub bump: u8,                           // PDA bump seed
+}
+
+impl FeeSchedule {
+    // Account size: discriminator + mint + tiers + tier_count + bump
+    pub const SPACE: usize = 8 + 32 + MAX_FEE_TIERS * (8 + 1) + 1 + 1;
+
+    // Checks tiers before they are stored: at most MAX_FEE_TIERS, max_input_amount strictly increasing
+    // # Arguments
+    // * `tiers` - Tiers in ascending order
+    pub fn validate_tiers(tiers: &[FeeTier]) -> Result<()> {
+        require!(tiers.len() <= MAX_FEE_TIERS, ErrorCode::InvalidFeeSchedule);
+        require!(
+            tiers.windows(2).all(|pair| pair[0].max_input_amount < pair[1].max_input_amount),
+            ErrorCode::InvalidFeeSchedule
+        );
+        Ok(())
+    }
+
+    // Returns the fee of the first tier whose max_input_amount covers `input_amount`;
+    // orders larger than every tier pay the last tier
+    // # Returns
+    // * `Option<u8>` - None when the schedule has no tiers
+    pub fn fee_bps_for(&self, input_amount: u64) -> Option<u8> {
+        let tiers = &self.tiers[..(self.tier_count as usize).min(MAX_FEE_TIERS)];
+        tiers
+            .iter()
+            .find(|tier| input_amount <= tier.max_input_amount)
+            .or(tiers.last())
+            .map(|tier| tier.fee_bps)
+    }
+
+    // Reads the schedule passed as the ["fee_schedule", mint] PDA, if set_fee_schedule has created it
+    // # Arguments
+    // * `fee_schedule` - The ["fee_schedule", input_mint] PDA
+    // * `program_id` - This program's ID
+    pub fn load(fee_schedule: &AccountInfo, program_id: &Pubkey) -> Result<Option<FeeSchedule>> {
+        if fee_schedule.owner != program_id || fee_schedule.data_is_empty() {
+            return Ok(None);
+        }
+        let data = fee_schedule.try_borrow_data()?;
+        Ok(Some(FeeSchedule::try_deserialize(&mut &data[..])?))
+    }
+}
+
+// One FeeVaultRegistry entry
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug, PartialEq, Eq, Default)]
+pub struct FeeVaultEntry {
+    pub mint: Pubkey,      // Mint the platform fee is collected in
+    pub fee_vault: Pubkey, // Vault authority owned token account of that mint receiving the fee
+}
+
+// Most mints a FeeVaultRegistry maps
+pub const MAX_FEE_VAULTS: usize = 32;
+
+// Canonical platform fee vault per mint, PDA ["fee_vault_registry"]
+// Routes called with use_registered_fee_vault pick the fee account of their fee mint from here
+// instead of trusting the caller to pass the right one
+#[account]
+pub struct FeeVaultRegistry {
+    pub entries: Vec<FeeVaultEntry>, // At most one entry per mint
+    pub bump: u8,                    // PDA bump seed
+}
+
+impl FeeVaultRegistry {
+    // Account size: discriminator + entries vec + bump
+    pub const SPACE: usize = 8 + 4 + MAX_FEE_VAULTS * (32 + 32) + 1;
+
+    // Returns the fee vault registered for `mint`
+    pub fn fee_vault_for(&self, mint: &Pubkey) -> Option<Pubkey> {
+        self.entries
+            .iter()
+            .find(|entry| entry.mint == *mint)
+            .map(|entry| entry.fee_vault)
+    }
+
+    // Registers `fee_vault` for `mint`, replacing any vault registered for it before
+    // # Returns
+    // * `Result<Option<Pubkey>>` - The replaced vault; FeeVaultRegistryFull when a new mint does not fit
+    pub fn register(&mut self, mint: Pubkey, fee_vault: Pubkey) -> Result<Option<Pubkey>> {
+        if let Some(entry) = self.entries.iter_mut().find(|entry| entry.mint == mint) {
+            let replaced = entry.fee_vault;
+            entry.fee_vault = fee_vault;
+            return Ok(Some(replaced));
+        }
+        require!(self.entries.len() < MAX_FEE_VAULTS, ErrorCode::FeeVaultRegistryFull);
+        self.entries.push(FeeVaultEntry { mint, fee_vault });
+        Ok(None)
+    }
+
+    // Removes the entry of `mint`
+    // # Returns
+    // * `Result<Pubkey>` - The vault that was registered; FeeVaultNotRegistered if there was none
+    pub fn unregister(&mut self, mint: &Pubkey) -> Result<Pubkey> {
+        let index = self
+            .entries
+            .iter()
+            .position(|entry| entry.mint == *mint)
+            .ok_or(ErrorCode::FeeVaultNotRegistered)?;
+        Ok(self.entries.remove(index).fee_vault)
+    }
+
+    // Reads the registry passed as the ["fee_vault_registry"] PDA, if set_fee_vault has created it
+    // # Arguments
+    // * `fee_vault_registry` - The ["fee_vault_registry"] PDA
+    // * `program_id` - This program's ID
+    pub fn load(fee_vault_registry: &AccountInfo, program_id: &Pubkey) -> Result<Option<FeeVaultRegistry>> {
+        if fee_vault_registry.owner != program_id || fee_vault_registry.data_is_empty() {
+            return Ok(None);
+        }
+        let data = fee_vault_registry.try_borrow_data()?;
+        Ok(Some(FeeVaultRegistry::try_deserialize(&mut &data[..])?))
+    }
+}
+
+// Most vault mints list_vaults returns at once, bounded by the 1024-byte return data limit
+pub const VAULT_REGISTRY_PAGE_SIZE: usize = 31;
+
+// Mints of the ["vault", mint] vaults that exist, PDA ["vault_registry"]
+// Vault creation adds to it and close_vault removes from it, so reconciliation and sweep
+// tooling can enumerate vaults without scanning program accounts
+#[account]
+pub struct VaultRegistry {
+    pub mints: Vec<Pubkey>, // One entry per vault, in the order the vaults were registered
+    pub bump: u8,           // PDA bump seed
+}
+
+impl VaultRegistry {
+    // Account size holding `count` mints: discriminator + mints vec + bump
+    pub const fn space(count: usize) -> usize {
+        8 + 4 + count * 32 + 1
+    }
+
+    // Adds `mint`
+    // # Returns
+    // * `bool` - False if the mint was already listed
+    pub fn add(&mut self, mint: Pubkey) -> bool {
+        if self.mints.contains(&mint) {
+            return false;
+        }
+        self.mints.push(mint);
+        true
+    }
+
+    // Removes `mint`, keeping the order of the others so pages stay stable
+    // # Returns
+    // * `bool` - False if the mint was not listed
+    pub fn remove(&mut self, mint: &Pubkey) -> bool {
+        match self.mints.iter().position(|listed| listed == mint) {
+            Some(index) => {
+                self.mints.remove(index);
+                true
+            }
+            None => false,
+        }
+    }
+
+    // Returns up to `limit` mints starting at `offset`, at most VAULT_REGISTRY_PAGE_SIZE
+    pub fn page(&self, offset: usize, limit: usize) -> Vec<Pubkey> {
+        self.mints
+            .iter()
+            .skip(offset)
+            .take(limit.min(VAULT_REGISTRY_PAGE_SIZE))
+            .copied()
+            .collect()
+    }
+}
+
+// Generation of a vault address, PDA ["vault_meta", vault]
+// close_vault increments it, so a client that cached the vault can tell it was closed (and
+// maybe re-created empty) since, see assert_vault_generation
+#[account]
+pub struct VaultMeta {
+    pub vault: Pubkey,         // Vault the generation belongs to
+    pub vault_generation: u32, // Times the vault has been closed
+    pub bump: u8,              // PDA bump seed
+}
+
+impl VaultMeta {
+    // Account size: discriminator + vault + vault_generation + bump
+    pub const SPACE: usize = 8 + 32 + 4 + 1;
+
+    // Reads the generation from the ["vault_meta", vault] PDA, or 0 for a vault created before
+    // VaultMeta existed and never closed since
+    // # Arguments
+    // * `vault_meta` - The ["vault_meta", vault] PDA
+    // * `program_id` - This program's ID
+    pub fn load_generation(vault_meta: &AccountInfo, program_id: &Pubkey) -> Result<u32> {
+        if vault_meta.owner != program_id || vault_meta.data_is_empty() {
+            return Ok(0);
+        }
+        let data = vault_meta.try_borrow_data()?;
+        Ok(VaultMeta::try_deserialize(&mut &data[..])?.vault_generation)
+    }
+}
+
+// Slippage and trigger caps applied protocol-wide
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug, PartialEq, Eq)]
+pub struct ProtocolLimits {
+    pub max_route_slippage_bps: u16,      // Slippage cap of swaps (route, shared_route, the swap leg of route_and_create_order)
+    pub max_order_slippage_bps: u16,      // Slippage cap stored on limit orders
+    pub max_stop_loss_trigger_bps: u32,   // Trigger cap of StopLoss orders (at most MAX_STOP_LOSS_TRIGGER_BPS)
+    pub max_take_profit_trigger_bps: u32, // Trigger cap of TakeProfit orders
+    pub min_order_duration_seconds: i64,  // Shortest time from creation to expiry of a limit order
+    pub max_order_duration_seconds: i64,  // Longest time from creation to expiry of a limit order (0 = unbounded)
+}
+
+// Hard ceiling of any slippage cap (100%)
+pub const MAX_SLIPPAGE_BPS: u16 = 10_000;
+
+// Hard ceiling of the StopLoss trigger cap: should_execute computes 10_000 - trigger_price_bps
+pub const MAX_STOP_LOSS_TRIGGER_BPS: u32 = 10_000;
+
+// Limits used until the authority creates a ProtocolConfig
+pub const DEFAULT_PROTOCOL_LIMITS: ProtocolLimits = ProtocolLimits {
+    max_route_slippage_bps: MAX_SLIPPAGE_BPS,
+    max_order_slippage_bps: 1_000,
+    max_stop_loss_trigger_bps: MAX_STOP_LOSS_TRIGGER_BPS,
+    max_take_profit_trigger_bps: 100_000, // 10_000 + 100_000 fits the u64 trigger math
+    min_order_duration_seconds: 0,
+    max_order_duration_seconds: 0,
+};
+
+impl Default for ProtocolLimits {
+    fn default() -> Self {
+        DEFAULT_PROTOCOL_LIMITS
+    }
+}
+
+impl ProtocolLimits {
+    // Checks limits before they are stored: slippage caps within 100%, StopLoss cap within
+    // MAX_STOP_LOSS_TRIGGER_BPS, order durations non-negative with the minimum within the maximum
+    pub fn validate(&self) -> Result<()> {
+        require!(
+            self.max_route_slippage_bps <= MAX_SLIPPAGE_BPS
+                && self.max_order_slippage_bps <= MAX_SLIPPAGE_BPS
+                && self.max_stop_loss_trigger_bps <= MAX_STOP_LOSS_TRIGGER_BPS,
+            ErrorCode::InvalidProtocolLimits
+        );
+        require!(
+            self.min_order_duration_seconds >= 0
+                && self.max_order_duration_seconds >= 0
+                && (self.max_order_duration_seconds == 0
+                    || self.min_order_duration_seconds <= self.max_order_duration_seconds),
+            ErrorCode::InvalidProtocolLimits
+        );
+        Ok(())
+    }
+
+    // InvalidSlippage unless `slippage_bps` is within the swap slippage cap
+    pub fn check_route_slippage(&self, slippage_bps: u16) -> Result<()> {
+        require!(slippage_bps <= self.max_route_slippage_bps, ErrorCode::InvalidSlippage);
+        Ok(())
+    }
+
+    // InvalidSlippage unless `slippage_bps` is within the limit order slippage cap
+    pub fn check_order_slippage(&self, slippage_bps: u16) -> Result<()> {
+        require!(slippage_bps <= self.max_order_slippage_bps, ErrorCode::InvalidSlippage);
+        Ok(())
+    }
+
+    // OrderDurationTooShort / OrderDurationTooLong unless an order created at `now` expiring
+    // at `expiry` lasts between the duration bounds (both inclusive)
+    pub fn check_order_duration(&self, expiry: i64, now: i64) -> Result<()> {
+        let duration = expiry.saturating_sub(now);
+        require!(duration >= self.min_order_duration_seconds, ErrorCode::OrderDurationTooShort);
+        require!(
+            self.max_order_duration_seconds == 0 || duration <= self.max_order_duration_seconds,
+            ErrorCode::OrderDurationTooLong
+        );
+        Ok(())
+    }
+}
+
+// One volume tier of the route platform fee
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug, PartialEq, Eq, Default)]
+pub struct VolumeFeeTier {
+    pub min_volume: u64,         // 30-day volume (raw units of the volume mint) the tier starts at
+    pub fee_multiplier_bps: u16, // Share of platform_fee_bps charged in this tier (10000 = full fee)
+}
+
+// Most volume tiers a ProtocolConfig holds
+pub const MAX_VOLUME_FEE_TIERS: usize = 4;
+
+// Protocol-wide limits, order pauses, the governance delay and the route fee volume tiers,
+// PDA ["protocol_config"]
+#[account]
+pub struct ProtocolConfig {
+    pub limits: ProtocolLimits,      // Caps every instruction checks its arguments against
+    pub bump: u8,                    // PDA bump seed
+    pub creation_paused: bool,       // Whether new limit orders are rejected (cancels and withdrawals still work)
+    pub execution_paused: bool,      // Whether limit orders are left unexecuted (cancels and withdrawals still work)
+    pub governance_delay_secs: i64,  // Wait between proposing and executing a GovernanceCall
+    pub volume_mint: Pubkey,         // Mint TraderVolume counts route volume in (default = not counted)
+    pub volume_fee_tiers: [VolumeFeeTier; MAX_VOLUME_FEE_TIERS], // First volume_tier_count entries, min_volume strictly increasing
+    pub volume_tier_count: u8,       // Volume tiers in use (0 = no rebates)
+    pub pool_volume_warning_pct: u8, // Percent of a pool's volume cap that triggers PoolVolumeWarning (0 = default)
+}
+
+impl ProtocolConfig {
+    // Account size: discriminator + limits + bump + creation_paused + execution_paused + governance_delay_secs
+    // + volume_mint + volume_fee_tiers + volume_tier_count + pool_volume_warning_pct
+    pub const SPACE: usize = 8 + 2 + 2 + 4 + 4 + 8 + 8 + 1 + 1 + 1 + 8 + 32 + MAX_VOLUME_FEE_TIERS * (8 + 2) + 1 + 1;
+    // Account size before creation_paused and execution_paused, grown by migrate_protocol_config
+    pub const LEGACY_SPACE: usize = 8 + 2 + 2 + 4 + 4 + 8 + 8 + 1;
+    // Account size before governance_delay_secs, grown by migrate_protocol_config
+    pub const PRE_GOVERNANCE_SPACE: usize = Self::LEGACY_SPACE + 1 + 1;
+    // Account size before the volume tiers, grown by migrate_protocol_config
+    pub const PRE_VOLUME_TIERS_SPACE: usize = Self::PRE_GOVERNANCE_SPACE + 8;
+    // Account size before pool_volume_warning_pct, grown by migrate_protocol_config
+    pub const PRE_POOL_VOLUME_WARNING_SPACE: usize = Self::PRE_VOLUME_TIERS_SPACE + 32 + MAX_VOLUME_FEE_TIERS * (8 + 2) + 1;
+
+    // Reads the ["protocol_config"] PDA, or DEFAULT_PROTOCOL_LIMITS with nothing paused, the
+    // minimum governance delay and no volume tiers until create_protocol_config has created it.
+    // Fields a config not yet grown by migrate_protocol_config lacks read as unpaused, a delay
+    // of 0 (so governance_delay is the minimum) and no volume tiers.
+    // # Arguments
+    // * `protocol_config` - The ["protocol_config"] PDA
+    // * `program_id` - This program's ID
+    pub fn load_config(protocol_config: &AccountInfo, program_id: &Pubkey) -> Result<ProtocolConfig> {
+        if protocol_config.owner != program_id || protocol_config.data_is_empty() {
+            return Ok(ProtocolConfig {
+                limits: DEFAULT_PROTOCOL_LIMITS,
+                bump: 0,
+                creation_paused: false,
+                execution_paused: false,
+                governance_delay_secs: MIN_GOVERNANCE_DELAY_SECS,
+                volume_mint: Pubkey::default(),
+                volume_fee_tiers: [VolumeFeeTier::default(); MAX_VOLUME_FEE_TIERS],
+                volume_tier_count: 0,
+                pool_volume_warning_pct: 0,
+            });
+        }
+        let data = protocol_config.try_borrow_data()?;
+        if data.len() < Self::SPACE {
+            let mut grown = data.to_vec();
+            grown.resize(Self::SPACE, 0);
+            return Ok(ProtocolConfig::try_deserialize(&mut &grown[..])?);
+        }
+        Ok(ProtocolConfig::try_deserialize(&mut &data[..])?)
+    }
+
+    // Reads only the limits; see load_config
+    pub fn load(protocol_config: &AccountInfo, program_id: &Pubkey) -> Result<ProtocolLimits> {
+        Ok(Self::load_config(protocol_config, program_id)?.limits)
+    }
+
+    // Wait between proposing and executing a GovernanceCall: governance_delay_secs, but never
+    // less than MIN_GOVERNANCE_DELAY_SECS
+    pub fn governance_delay(&self) -> i64 {
+        self.governance_delay_secs.max(MIN_GOVERNANCE_DELAY_SECS)
+    }
+
+    // Percent of a pool's volume cap its window volume must exceed for a PoolVolumeWarning:
+    // pool_volume_warning_pct, or DEFAULT_POOL_VOLUME_WARNING_PCT while it is unset
+    pub fn pool_volume_warning_pct(&self) -> u8 {
+        if self.pool_volume_warning_pct == 0 {
+            DEFAULT_POOL_VOLUME_WARNING_PCT
+        } else {
+            self.pool_volume_warning_pct
+        }
+    }
+
+    // OrderCreationPaused while new limit orders are paused
+    pub fn require_creation_open(&self) -> Result<()> {
+        require!(!self.creation_paused, ErrorCode::OrderCreationPaused);
+        Ok(())
+    }
+
+    // OrderExecutionPaused while limit order execution is paused
+    pub fn require_execution_open(&self) -> Result<()> {
+        require!(!self.execution_paused, ErrorCode::OrderExecutionPaused);
+        Ok(())
+    }
+
+    // Checks volume tiers before they are stored: at most MAX_VOLUME_FEE_TIERS, min_volume
+    // strictly increasing and no multiplier above 10000, so a tier can only lower the fee
+    // # Arguments
+    // * `tiers` - Tiers in ascending order
+    pub fn validate_volume_tiers(tiers: &[VolumeFeeTier]) -> Result<()> {
+        require!(tiers.len() <= MAX_VOLUME_FEE_TIERS, ErrorCode::InvalidVolumeFeeTiers);
+        require!(
+            tiers.windows(2).all(|pair| pair[0].min_volume < pair[1].min_volume),
+            ErrorCode::InvalidVolumeFeeTiers
+        );
+        require!(tiers.iter().all(|tier| tier.fee_multiplier_bps <= 10_000), ErrorCode::InvalidVolumeFeeTiers);
+        Ok(())
+    }
+
+    // Returns platform_fee_bps scaled by the multiplier of the highest tier `window_volume`
+    // reaches, rounded down; below the first tier the fee is unchanged
+    // # Arguments
+    // * `platform_fee_bps` - Fee the route was called with
+    // * `window_volume` - The trader's volume before this route, see TraderVolume::window_volume
+    // # Returns
+    // * `Result<u8>` - Scaled fee, or InvalidCalculation if it does not fit a u8
+    pub fn volume_fee_bps(&self, platform_fee_bps: u8, window_volume: u64) -> Result<u8> {
+        let tiers = &self.volume_fee_tiers[..(self.volume_tier_count as usize).min(MAX_VOLUME_FEE_TIERS)];
+        match tiers.iter().rev().find(|tier| window_volume >= tier.min_volume) {
+            Some(tier) => {
+                let fee_bps = math::apply_bps_fee(u64::from(platform_fee_bps), tier.fee_multiplier_bps)?;
+                u8::try_from(fee_bps).map_err(|_| ErrorCode::InvalidCalculation.into())
+            }
+            None => Ok(platform_fee_bps),
+        }
+    }
+
+    // Volume a route adds to its trader: in_amount when it sells the volume mint, out_amount
+    // when it buys it, nothing for other pairs or while no volume mint is set
+    // # Arguments
+    // * `source_mint` - Mint the route sells
+    // * `destination_mint` - Mint the route buys
+    // * `in_amount` - Input amount of the route
+    // * `out_amount` - Output amount delivered to the trader
+    pub fn route_volume(&self, source_mint: &Pubkey, destination_mint: &Pubkey, in_amount: u64, out_amount: u64) -> u64 {
+        if self.volume_mint == Pubkey::default() {
+            0
+        } else if *source_mint == self.volume_mint {
+            in_amount
+        } else if *destination_mint == self.volume_mint {
+            out_amount
+        } else {
+            0
+        }
+    }
+}
+
+// Epoch buckets of a TraderVolume; at about two days per epoch they span roughly 30 days
+pub const TRADER_VOLUME_EPOCHS: usize = 15;
+
+// A wallet's route volume in the ProtocolConfig volume mint, PDA ["trader_volume", trader]
+// Each epoch has a bucket that is reused TRADER_VOLUME_EPOCHS epochs later, so the buckets of
+// the last TRADER_VOLUME_EPOCHS epochs approximate a rolling 30-day window
+#[account]
+pub struct TraderVolume {
+    pub trader: Pubkey,                                // Wallet the volume belongs to
+    pub bucket_epochs: [u64; TRADER_VOLUME_EPOCHS],    // Epoch each bucket currently holds
+    pub bucket_volumes: [u64; TRADER_VOLUME_EPOCHS],   // Volume routed in that epoch
+    pub bump: u8,                                      // PDA bump seed
+}
+
+impl TraderVolume {
+    // Account size: discriminator + trader + bucket_epochs + bucket_volumes + bump
+    pub const SPACE: usize = 8 + 32 + TRADER_VOLUME_EPOCHS * 8 + TRADER_VOLUME_EPOCHS * 8 + 1;
+
+    // Volume of the buckets within the TRADER_VOLUME_EPOCHS epochs ending at `epoch`
+    pub fn window_volume(&self, epoch: u64) -> u64 {
+        self.bucket_epochs
+            .iter()
+            .zip(self.bucket_volumes.iter())
+            .filter(|(bucket_epoch, _)| **bucket_epoch <= epoch && epoch - **bucket_epoch < TRADER_VOLUME_EPOCHS as u64)
+            .fold(0u64, |total, (_, volume)| total.saturating_add(*volume))
+    }
+
+    // Adds `amount` to the bucket of `epoch`, clearing it first if it still holds an older epoch
+    pub fn record(&mut self, epoch: u64, amount: u64) {
+        let index = (epoch % TRADER_VOLUME_EPOCHS as u64) as usize;
+        if self.bucket_epochs[index] != epoch {
+            self.bucket_epochs[index] = epoch;
+            self.bucket_volumes[index] = 0;
+        }
+        self.bucket_volumes[index] = self.bucket_volumes[index].saturating_add(amount);
+    }
+}
+
+// Shortest governance delay; configs that predate the delay or never set one wait this long.
+// Localnet test builds shorten it so the TypeScript suite can wait proposals out
+#[cfg(not(feature = "localnet"))]
+pub const MIN_GOVERNANCE_DELAY_SECS: i64 = 24 * 60 * 60;
+#[cfg(feature = "localnet")]
+pub const MIN_GOVERNANCE_DELAY_SECS: i64 = 2;
+
+// Longest governance delay set_governance_delay accepts, so a mistaken delay cannot hold
+// governed changes back indefinitely
+pub const MAX_GOVERNANCE_DELAY_SECS: i64 = 30 * 24 * 60 * 60;
+
+// How long a proposal stays executable once its delay has passed
+pub const GOVERNANCE_PROPOSAL_TTL_SECS: i64 = 7 * 24 * 60 * 60;
+
+// Registry change that must be proposed with propose_governance_call and wait out the
+// governance delay before the instruction it names accepts exactly these arguments
+#[derive(AnchorSerialize, AnchorDeserialize, Clone)]
+pub enum GovernanceCall {
+    ResetAdapterRegistry { adapters: Vec<AdapterInfo>, operators: Vec<Pubkey> }, // reset_adapter_registry
+    ConfigureAdapter { adapter: AdapterInfo },                                  // configure_adapter changing an adapter's program_id
+    SetGovernanceDelay { delay_secs: i64 },                                     // set_governance_delay lowering the delay
+}
+
+impl GovernanceCall {
+    // Hash of the call and its arguments, which a proposal commits to
+    pub fn hash(&self) -> Result<[u8; 32]> {
+        Ok(anchor_lang::solana_program::hash::hash(&self.try_to_vec()?).to_bytes())
+    }
+}
+
+// Pending GovernanceCall, PDA ["governance_proposal"]; one proposal at a time
+#[account]
+pub struct GovernanceProposal {
+    pub call_hash: [u8; 32], // GovernanceCall::hash of the proposed call
+    pub proposer: Pubkey,    // Registry authority that proposed it
+    pub proposed_at: i64,    // Unix timestamp of the proposal
+    pub executable_at: i64,  // proposed_at plus the governance delay in force when proposed
+    pub expires_at: i64,     // executable_at plus GOVERNANCE_PROPOSAL_TTL_SECS
+    pub bump: u8,            // PDA bump seed
+}
+
+impl GovernanceProposal {
+    // Account size: discriminator + call_hash + proposer + proposed_at + executable_at + expires_at + bump
+    pub const SPACE: usize = 8 + 32 + 32 + 8 + 8 + 8 + 1;
+
+    // Checks `call` is the proposed call and that `now` falls in the proposal's execution window
+    pub fn require_executable(&self, call: &GovernanceCall, now: i64) -> Result<()> {
+        require!(self.call_hash == call.hash()?, ErrorCode::GovernanceProposalMismatch);
+        require!(now >= self.executable_at, ErrorCode::GovernanceDelayNotElapsed);
+        require!(!self.is_expired(now), ErrorCode::GovernanceProposalExpired);
+        Ok(())
+    }
+
+    // Whether the execution window has closed; expired proposals can only be cancelled
+    pub fn is_expired(&self, now: i64) -> bool {
+        now >= self.expires_at
     }
 }
 
@@ -60,6 +1190,62 @@ pub struct AdapterInfo {
     pub swap_type: Swap,             // Type of swap (e.g., Raydium, Whirlpool)
 }
 
+// Configuration state of a swap type's adapter, from adapter_connector_module::adapter_status
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum AdapterStatus {
+    Enabled,        // Registered and not paused
+    Disabled,       // Registered but paused with set_adapter_enabled
+    NotConfigured,  // Implemented, but the registry has no AdapterInfo for it
+    NotImplemented, // The program has no adapter for the swap type
+}
+
+impl AdapterStatus {
+    // Fails with the error for a status other than Enabled, logging the swap tag so the
+    // missing configuration can be told apart from the transaction logs
+    // # Arguments
+    // * `swap` - The swap type the status was described for
+    // # Returns
+    // * `Result<()>` - AdapterNotImplemented, AdapterNotConfigured or AdapterDisabled
+    pub fn require_enabled(self, swap: &Swap) -> Result<()> {
+        let error = match self {
+            AdapterStatus::Enabled => return Ok(()),
+            AdapterStatus::Disabled => ErrorCode::AdapterDisabled,
+            AdapterStatus::NotConfigured => ErrorCode::AdapterNotConfigured,
+            AdapterStatus::NotImplemented => ErrorCode::AdapterNotImplemented,
+        };
+        msg!("Adapter for swap tag {} is {:?}", swap.to_bytes()[0], self);
+        Err(error.into())
+    }
+}
+
+// Stores the pools an adapter is restricted to
+#[derive(AnchorSerialize, AnchorDeserialize, Clone)]
+pub struct AdapterPools {
+    pub swap_type: Swap,             // Adapter the whitelist applies to
+    pub pool_addresses: Vec<Pubkey>, // Whitelisted pools, each with a PoolInfo account
+}
+
+// Stores the price impact cap of an adapter
+#[derive(AnchorSerialize, AnchorDeserialize, Clone)]
+pub struct AdapterImpactCap {
+    pub swap_type: Swap,          // Adapter the cap applies to
+    pub max_step_impact_bps: u16, // Highest estimated price move per route step, in basis points
+}
+
+// Stores how many enabled PoolInfos an adapter has
+#[derive(AnchorSerialize, AnchorDeserialize, Clone)]
+pub struct AdapterPoolCount {
+    pub swap_type: Swap,     // Adapter the PoolInfos belong to
+    pub enabled_pools: u32,  // PoolInfos initialized or migrated while enabled and not disabled since
+}
+
+// Stores the wallets a sandboxed adapter is restricted to
+#[derive(AnchorSerialize, AnchorDeserialize, Clone)]
+pub struct AdapterSandbox {
+    pub swap_type: Swap,            // Sandboxed adapter
+    pub sandbox_users: Vec<Pubkey>, // Wallets allowed through it (max MAX_SANDBOX_USERS)
+}
+
 // Event emitted when an adapter is configured in the registry
 #[event]
 pub struct AdapterConfigured {
@@ -73,6 +1259,13 @@ pub struct AdapterDisabled {
     pub swap_type: Swap, // Type of swap for the disabled adapter
 }
 
+// Event emitted when an adapter is paused or resumed without being removed
+#[event]
+pub struct AdapterStatusChanged {
+    pub swap_type: Swap, // Type of swap for the adapter
+    pub enabled: bool,   // New status (false = paused)
+}
+
 // Event emitted when a pool is disabled in an adapter
 #[event]
 pub struct PoolDisabled {
@@ -103,7 +1296,69 @@ pub struct OperatorAdded {
 // Event emitted when an operator is removed from the registry
 #[event]
 pub struct OperatorRemoved {
-    pub operator: Pubkey, // Public key of the removed operator
+    pub operator: Pubkey,              // Public key of the removed operator
+    pub transferred_obligations: u32,  // Sponsored order vaults handed to the treasury (0 unless forced)
+}
+
+// Event emitted when a trusted router is added to the registry
+#[event]
+pub struct TrustedRouterAdded {
+    pub router: Pubkey, // Public key of the added trusted router
+}
+
+// Event emitted when a trusted router is removed from the registry
+#[event]
+pub struct TrustedRouterRemoved {
+    pub router: Pubkey, // Public key of the removed trusted router
+}
+
+// Event emitted when the order creation fee or treasury changes
+#[event]
+pub struct OrderCreationFeeUpdated {
+    pub old_fee_lamports: u64, // Previous fee
+    pub new_fee_lamports: u64, // New fee (0 = disabled)
+    pub treasury: Pubkey,      // Account receiving the fee
+}
+
+// Event emitted when an adapter's pool whitelist changes
+#[event]
+pub struct AdapterPoolsUpdated {
+    pub swap_type: Swap,             // Adapter the whitelist applies to
+    pub pool_addresses: Vec<Pubkey>, // New whitelist (empty = any enabled pool)
+}
+
+// Event emitted when an adapter enters sandbox mode or its sandbox list changes
+#[event]
+pub struct AdapterSandboxUpdated {
+    pub swap_type: Swap,            // Sandboxed adapter
+    pub sandbox_users: Vec<Pubkey>, // Wallets now allowed through it
+}
+
+// Event emitted when a sandboxed adapter is opened to all users
+#[event]
+pub struct AdapterGraduated {
+    pub swap_type: Swap, // Graduated adapter
+}
+
+// Event emitted when an adapter's price impact cap changes
+#[event]
+pub struct AdapterImpactCapUpdated {
+    pub swap_type: Swap,          // Adapter the cap applies to
+    pub max_step_impact_bps: u16, // New cap in basis points (0 = uncapped)
+}
+
+// Event emitted when an operator key is suspended
+#[event]
+pub struct OperatorSuspended {
+    pub operator: Pubkey,     // Suspended operator
+    pub suspended_by: Pubkey, // The operator itself, or the operator that suspended it
+    pub reason_code: u16,     // SUSPEND_REASON_SELF for suspend_self, caller-supplied otherwise
+}
+
+// Event emitted when the authority reinstates a suspended operator
+#[event]
+pub struct OperatorReinstated {
+    pub operator: Pubkey, // Reinstated operator
 }
 
 // Event emitted when the global manager is changed
@@ -113,12 +1368,47 @@ pub struct GlobalManagerChanged {
     pub new_manager: Pubkey, // New global manager
 }
 
-// Event emitted when vault authority admin is changed by global manager
+// Event emitted when the global manager proposes a new vault authority admin
+#[event]
+pub struct VaultAuthorityAdminProposed {
+    pub current_admin: Pubkey, // Admin until the proposal is accepted
+    pub pending_admin: Pubkey, // Proposed admin, must accept
+    pub proposed_by: Pubkey,   // Global manager who made the proposal
+}
+
+// Event emitted when the proposed vault authority admin accepts
 #[event]
 pub struct VaultAuthorityAdminChanged {
     pub old_admin: Pubkey, // Previous vault authority admin
     pub new_admin: Pubkey, // New vault authority admin
-    pub changed_by: Pubkey, // Global manager who made the change
+    pub changed_by: Pubkey, // Global manager who proposed the change
+}
+
+// Event emitted when a vault is created
+#[event]
+pub struct VaultCreated {
+    pub vault: Pubkey,         // Created vault
+    pub mint: Pubkey,          // Mint the vault holds
+    pub vault_generation: u32, // Times the vault address was closed before
+}
+
+// Event emitted when a vault is closed
+#[event]
+pub struct VaultClosed {
+    pub vault: Pubkey,         // Closed vault
+    pub mint: Pubkey,          // Mint the vault held
+    pub vault_generation: u32, // Generation after the close, which a re-created vault keeps
+}
+
+// Instruction flow a platform fee was collected in
+#[repr(u8)]
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum FeeSource {
+    Route,               // route and its variants (prevalidated, fallback, compact, staged)
+    SharedRoute,         // shared_route via Jupiter
+    LimitOrder,          // execute_limit_order
+    SharedLimitOrder,    // shared_execute_limit_order via Jupiter
+    RouteAndCreateOrder, // route_and_create_order and shared_route_and_create_order
 }
 
 // Event emitted when a platform fee is applied
@@ -126,7 +1416,10 @@ pub struct VaultAuthorityAdminChanged {
 pub struct FeeEvent {
     pub account: Pubkey, // Account receiving the fee
     pub mint: Pubkey,   // Token mint for the fee
-    pub amount: u64,    // Amount of the fee
+    pub amount: u64,    // Amount of the fee (gross, as sent)
+    pub net_amount: u64, // Amount received after the mint's Token-2022 transfer fee
+    pub fee_bps: u8,    // Fee rate charged, after any volume tier rebate
+    pub source: FeeSource, // Instruction flow that collected the fee
 }
 
 // Event emitted when a swap is executed
@@ -137,6 +1430,7 @@ pub struct SwapEvent {
     pub input_amount: u64,   // Amount of input tokens
     pub output_mint: Pubkey, // Mint of the output token
     pub output_amount: u64,  // Amount of output tokens
+    pub host_fee_amount: u64, // Input tokens earned by the protocol's host fee account in this swap
 }
 
 // Global event emitted when a router swap is completed
@@ -151,6 +1445,23 @@ pub struct RouterSwapEvent {
     pub fee_amount: u64,             // Platform fee amount (0 if no fee)
     pub fee_account: Option<Pubkey>,  // Platform fee account (None if no fee)
     pub slippage_bps: u16,           // Slippage tolerance in basis points
+    pub net_output_amount: u64,      // Output received by recipient after Token-2022 transfer fee
+    pub accept_high_impact: bool,    // Whether the user waived the per-step price impact caps
+    pub fee_mint: Option<Pubkey>,    // Mint the platform fee was taken in (None if no fee)
+    pub ui_amount_multiplier: u64,   // Output mint's raw-to-UI factor, scaled by UI_AMOUNT_MULTIPLIER_SCALE
+}
+
+/// Fixed-point scale of ui_amount_multiplier in RouterSwapEvent and LimitOrderCreated
+/// (1e9 = UI amounts equal raw amounts, as for every mint without the interest-bearing extension)
+pub const UI_AMOUNT_MULTIPLIER_SCALE: u64 = 1_000_000_000;
+
+// Emitted by route_with_fallback to report which alternative plan was executed
+#[event]
+pub struct RouteFallbackEvent {
+    pub sender: Pubkey,              // User who initiated the swap
+    pub plan_index: u8,              // Index of the executed plan (0 = first plan)
+    pub plans_count: u8,             // Number of plans submitted
+    pub output_amount: u64,          // Final output amount (after fees)
 }
 
 #[event]
@@ -158,6 +1469,170 @@ pub struct RegistryReset {
     pub authority: Pubkey,
 }
 
+// Event emitted when the memo program expected by adapters is changed
+#[event]
+pub struct MemoProgramUpdated {
+    pub old_memo_program_id: Pubkey, // Previous memo program
+    pub new_memo_program_id: Pubkey, // New memo program
+}
+
+// Event emitted when the limit order quote signer is changed
+#[event]
+pub struct QuoteSignerUpdated {
+    pub old_quote_signer: Pubkey, // Previous quote signer (default = attestations not required)
+    pub new_quote_signer: Pubkey, // New quote signer (default = attestations not required)
+}
+
+// Event emitted when the strict limit order price band is changed
+#[event]
+pub struct OrderPriceBandUpdated {
+    pub old_band_bps: u16, // Previous band (0 = default)
+    pub new_band_bps: u16, // New band (0 = default)
+}
+
+// Event emitted when the registry's single-hop step event setting is changed
+#[event]
+pub struct StepEventsUpdated {
+    pub emit_step_events: bool, // Whether single-hop routes emit per-step SwapEvents
+}
+
+// Event emitted when the route quote sanity check is configured
+#[event]
+pub struct QuoteSanityUpdated {
+    pub enforce_quote_sanity: bool, // Whether routes check quoted_out_amount against the on-chain quote
+    pub band_bps: u16,              // Allowed distance from the on-chain quote (0 = default)
+}
+
+// Event emitted when the delegated vault policy of routes is changed
+#[event]
+pub struct DelegatedVaultPolicyUpdated {
+    pub reject_delegated_vaults: bool, // Whether routes refuse vaults that have a delegate set
+}
+
+// Event emitted when the execution fee schedule of a mint is set
+#[event]
+pub struct FeeScheduleUpdated {
+    pub mint: Pubkey,         // Input mint the schedule applies to
+    pub tiers: Vec<FeeTier>,  // New tiers (empty = schedule disabled)
+}
+
+// Event emitted when a mint's platform fee vault is registered or replaced
+#[event]
+pub struct FeeVaultRegistered {
+    pub mint: Pubkey,                       // Mint the fee is collected in
+    pub fee_vault: Pubkey,                  // Newly registered vault
+    pub replaced_fee_vault: Option<Pubkey>, // Vault registered before, if any
+}
+
+// Event emitted when a mint's platform fee vault is unregistered
+#[event]
+pub struct FeeVaultUnregistered {
+    pub mint: Pubkey,      // Mint the fee was collected in
+    pub fee_vault: Pubkey, // Vault that was registered
+}
+
+// Event emitted when the protocol-wide limits are created or changed
+#[event]
+pub struct ProtocolLimitsUpdated {
+    pub limits: ProtocolLimits, // Limits now in force
+}
+
+// Event emitted when anyone attests a pool's liquidity
+#[event]
+pub struct PoolLiquidityAttested {
+    pub pool_address: Pubkey, // Pool whose reserves were read
+    pub reserve_a: u64,       // Balance of the pool's first reserve
+    pub reserve_b: u64,       // Balance of the pool's second reserve
+    pub slot: u64,            // Slot of the attestation
+    pub attested: bool,       // Whether both reserves exceeded the threshold (false = stamp cleared)
+}
+
+// Event emitted when a pool's minimum liquidity threshold is set
+#[event]
+pub struct PoolLiquidityThresholdUpdated {
+    pub swap_type: Swap,              // Type of swap for the adapter
+    pub pool_address: Pubkey,         // Pool the threshold applies to
+    pub min_liquidity_threshold: u64, // Amount each reserve must exceed
+}
+
+// Event emitted when a pool's volume cap is set
+#[event]
+pub struct PoolVolumeCapUpdated {
+    pub swap_type: Swap,            // Type of swap for the adapter
+    pub pool_address: Pubkey,       // Pool the cap applies to
+    pub max_volume_per_window: u64, // Most step input per POOL_VOLUME_WINDOW_SECS (0 = uncapped)
+}
+
+// Event emitted when the pool volume warning threshold is changed
+#[event]
+pub struct PoolVolumeWarningPctUpdated {
+    pub warning_pct: u8, // Percent of a pool's volume cap that triggers PoolVolumeWarning
+}
+
+// Event emitted when a route takes a capped pool's window volume above the warning threshold
+#[event]
+pub struct PoolVolumeWarning {
+    pub pool: Pubkey,        // Pool address of the PoolInfo
+    pub window_volume: u64,  // Window volume including the step that triggered the warning
+    pub cap: u64,            // The pool's max_volume_per_window
+}
+
+// Event emitted when the maximum pool attestation age of routes is changed
+#[event]
+pub struct PoolAttestationMaxAgeUpdated {
+    pub max_age_slots: u64, // Slots an attestation stays fresh (0 = not required)
+}
+
+// Event emitted when new limit orders are paused or resumed
+#[event]
+pub struct OrderCreationPauseUpdated {
+    pub paused: bool, // Whether order creation is now paused
+}
+
+// Event emitted when limit order execution is paused or resumed
+#[event]
+pub struct OrderExecutionPauseUpdated {
+    pub paused: bool, // Whether order execution is now paused
+}
+
+// Event emitted when a GovernanceCall is proposed
+#[event]
+pub struct GovernanceCallProposed {
+    pub call_hash: [u8; 32], // GovernanceCall::hash of the proposed call
+    pub proposer: Pubkey,    // Registry authority that proposed it
+    pub executable_at: i64,  // Earliest execution time
+    pub expires_at: i64,     // Time from which it can no longer execute
+}
+
+// Event emitted when a proposed GovernanceCall is executed
+#[event]
+pub struct GovernanceCallExecuted {
+    pub call_hash: [u8; 32], // GovernanceCall::hash of the executed call
+    pub executed_by: Pubkey, // Signer of the governed instruction
+}
+
+// Event emitted when a pending proposal is cancelled
+#[event]
+pub struct GovernanceProposalCancelled {
+    pub call_hash: [u8; 32],  // GovernanceCall::hash of the cancelled call
+    pub cancelled_by: Pubkey, // Registry authority, or anyone once the proposal expired
+    pub expired: bool,        // Whether the proposal had expired unexecuted
+}
+
+// Event emitted when the governance delay changes
+#[event]
+pub struct GovernanceDelayUpdated {
+    pub old_delay_secs: i64, // Previous delay
+    pub new_delay_secs: i64, // New delay
+}
+
+// Event emitted when the route fee volume tiers are set
+#[event]
+pub struct VolumeFeeTiersUpdated {
+    pub volume_mint: Pubkey,        // Mint route volume is counted in
+    pub tiers: Vec<VolumeFeeTier>,  // New tiers (empty = no rebates)
+}
+
 
 use anchor_lang::prelude::*;
 
@@ -174,6 +1649,10 @@ pub struct LimitOrderCreated {
     pub trigger_price_bps: u32,
     pub trigger_type: u8,
     pub expiry: i64,
+    pub activate_at: i64, // Earliest execution timestamp (0 = immediately)
+    pub generation: u32,  // Creator's order count at init; tells apart orders recreated at the same address
+    pub ui_amount_multiplier: u64, // Output mint's raw-to-UI factor at creation, scaled by UI_AMOUNT_MULTIPLIER_SCALE
+    pub replaces: Option<Pubkey>,  // Order cancelled by replace_limit_order in the same instruction (None otherwise)
 }
 
 #[event]
@@ -185,8 +1664,20 @@ pub struct LimitOrderExecuted {
     pub fee_amount: u64,
     pub trigger_type: u8,
     pub min_output_amount: u64,
+    pub venues: Vec<Swap>,              // Distinct route venues in execution order (max MAX_REPORTED_VENUES, empty for Jupiter CPI)
+    pub effective_price: u64,           // Realized output per input unit, scaled by EFFECTIVE_PRICE_SCALE
+    pub fee_account: Option<Pubkey>,    // Platform fee account (None if no fee)
+    pub quoted_out_amount: u64,         // Quote the operator executed against
+    pub trigger_threshold_amount: u64,  // min_output_amount * (1 ± trigger_price_bps), where the trigger fires
+    pub execution_premium_bps: i64,     // output_amount vs trigger_threshold_amount (negative = worse)
 }
 
+/// Maximum number of venues reported in LimitOrderExecuted
+pub const MAX_REPORTED_VENUES: usize = 4;
+
+/// Fixed-point scale for LimitOrderExecuted::effective_price (1e9 = 1 output unit per input unit)
+pub const EFFECTIVE_PRICE_SCALE: u64 = 1_000_000_000;
+
 // Global event emitted when a limit order swap is executed
 #[event]
 pub struct LimitOrderSwapEvent {
@@ -201,12 +1692,14 @@ pub struct LimitOrderSwapEvent {
     pub fee_amount: u64,                // Platform fee amount (0 if no fee)
     pub fee_account: Option<Pubkey>,    // Platform fee account (None if no fee)
     pub trigger_type: u8,               // Trigger type (TakeProfit/StopLoss)
+    pub net_output_amount: u64,         // Output received by recipient after Token-2022 transfer fee
 }
 
 #[event]
 pub struct LimitOrderCancelled {
     pub order: Pubkey,
     pub creator: Pubkey,
+    pub refund_account: Option<Pubkey>, // Token account that received the refund (None if nothing was deposited)
 }
 
 #[event]
@@ -216,6 +1709,57 @@ pub struct LimitOrderClosed {
     pub status: u8,
 }
 
+/// Event emitted when an order's input is locked or released, so summing the deltas per
+/// (input_mint, output_mint) gives the pair's open interest. Funded orders (Open or
+/// NeedsReview) count; flagging and reopening do not change it.
+#[event]
+pub struct OpenInterestChanged {
+    pub input_mint: Pubkey,
+    pub output_mint: Pubkey,
+    pub delta_input_amount: i128, // +input_amount when the order is funded, -input_amount when filled, cancelled or expired
+    pub open_orders_delta: i32,   // +1 or -1
+}
+
+// Event emitted when a Jupiter execution returned no output and left the input untouched
+#[event]
+pub struct LimitOrderExecutionFailed {
+    pub order: Pubkey,
+    pub executor: Pubkey,
+    pub failed_attempts: u8, // Zero-output executions recorded so far
+}
+
+// Event emitted when the registry authority pauses an order after repeated failed executions
+#[event]
+pub struct LimitOrderFlaggedForReview {
+    pub order: Pubkey,
+    pub authority: Pubkey,
+    pub failed_attempts: u8,
+}
+
+// Event emitted when the creator reopens an order flagged for review
+#[event]
+pub struct LimitOrderReopened {
+    pub order: Pubkey,
+    pub creator: Pubkey,
+    pub min_output_amount: u64,
+    pub slippage_bps: u16,
+}
+
+#[event]
+pub struct OrderExecutionFeeSet {
+    pub order: Pubkey,
+    pub creator: Pubkey,
+    pub fee_bps: Option<u8>,
+}
+
+#[event]
+pub struct OrderUnwrapOutputSet {
+    pub order: Pubkey,
+    pub creator: Pubkey,
+    pub unwrap_output: bool,
+    pub user_lamport_destination: Pubkey, // Default pubkey when unwrap_output is off
+}
+
 #[event]
 pub struct RouteAndCreateOrderEvent {
     pub order: Pubkey,
@@ -374,6 +1918,9 @@ pub enum Swap {
     Scorch { swap_id: u128 },
     VaultLiquidUnstake { lst_amounts: [u64; 5], seed: u64 },
     XOrca,
+    WrapSol,
+    UnwrapSol,
+    WhirlpoolTwoHop { a_to_b_one: bool, a_to_b_two: bool },
 }
 
 impl Swap {
@@ -698,12 +2245,58 @@ impl Swap {
                 bytes[1..9].copy_from_slice(&seed.to_le_bytes());
             }
             Swap::XOrca => bytes[0] = 124,
+            Swap::WrapSol => bytes[0] = 125,
+            Swap::UnwrapSol => bytes[0] = 126,
+            // Both directions share bytes[1] so route_compact's two header flag bits carry them
+            Swap::WhirlpoolTwoHop { a_to_b_one, a_to_b_two } => {
+                bytes[0] = 127;
+                bytes[1] = (*a_to_b_one as u8) | ((*a_to_b_two as u8) << 1);
+            }
         }
         bytes
     }
 }
 
 // Defines the side of an order for DEXs like Serum
+// Most steps a RoutePlanEvent lists; longer plans are summarized by their leading steps
+pub const MAX_ROUTE_PLAN_EVENT_STEPS: usize = 6;
+
+// Compact description of one route step as listed in a RoutePlanEvent
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, PartialEq, Debug)]
+pub struct RouteStepSummary {
+    pub swap_tag: u16,   // Swap variant tag (first byte of Swap::to_bytes)
+    pub percent: u8,     // Percentage of the step's input vault that the step swaps
+    pub pool: Pubkey,    // PoolInfo account of the step (the lamport escrow for WrapSol/UnwrapSol)
+}
+
+// Event emitted once before a route or limit order fill executes its validated plan
+#[event]
+pub struct RoutePlanEvent {
+    pub steps: Vec<RouteStepSummary>, // Plan steps in order, at most MAX_ROUTE_PLAN_EVENT_STEPS
+}
+
+impl RoutePlanEvent {
+    // Summarizes a route plan, taking each step's PoolInfo key from the account after its input vault
+    pub fn from_plan(route_plan: &[RoutePlanStep], remaining_accounts: &[AccountInfo]) -> Result<Self> {
+        let steps = route_plan
+            .iter()
+            .take(MAX_ROUTE_PLAN_EVENT_STEPS)
+            .map(|step| -> Result<RouteStepSummary> {
+                let pool = remaining_accounts
+                    .get(step.input_index as usize + 1)
+                    .ok_or(ErrorCode::NotEnoughAccountKeys)?
+                    .key();
+                Ok(RouteStepSummary {
+                    swap_tag: u16::from(step.swap.to_bytes()[0]),
+                    percent: step.percent,
+                    pool,
+                })
+            })
+            .collect::<Result<Vec<_>>>()?;
+        Ok(Self { steps })
+    }
+}
+
 #[derive(AnchorSerialize, AnchorDeserialize, Clone, PartialEq, Debug)]
 pub enum Side {
     Bid,  // Buying the base currency
@@ -722,5 +2315,6 @@ pub struct RoutePlanStep {
 // Result struct for swap operations
 #[derive(AnchorSerialize, AnchorDeserialize)]
 pub struct SwapResult {
-    pub output_amount: u64, // Output amount from the swap
+    pub output_amount: u64,   // Output amount from the swap
+    pub host_fee_amount: u64, // Input tokens paid to the host fee account (Meteora), 0 otherwise
 }
\ No newline at end of file
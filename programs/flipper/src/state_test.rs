@@ -1,6 +1,7 @@
 #[cfg(test)]
 mod tests {
     use super::super::state::*;
+    use crate::errors::ErrorCode;
     use anchor_lang::prelude::*;
 
     #[test]
@@ -16,6 +17,24 @@ mod tests {
             operators: vec![],
             supported_adapters: vec![adapter],
             bump: 255,
+            memo_program_id: Pubkey::default(),
+            quote_signer: Pubkey::default(),
+            suspended_operators: vec![],
+            order_creation_fee_lamports: 0,
+            treasury: Pubkey::default(),
+            adapter_pools: vec![],
+            trusted_routers: vec![],
+            adapter_impact_caps: vec![],
+            order_price_band_bps: 0,
+            suppress_step_events: false,
+            enforce_quote_sanity: false,
+            quote_sanity_band_bps: 0,
+            reject_delegated_vaults: false,
+            pool_attestation_max_age_slots: 0,
+            paused_adapters: vec![],
+            sandboxed_adapters: vec![],
+            retired_adapters: vec![],
+            adapter_pool_counts: vec![],
         };
 
         assert!(registry.is_supported_adapter(&Swap::Raydium));
@@ -36,6 +55,24 @@ mod tests {
             operators: vec![],
             supported_adapters: vec![adapter],
             bump: 255,
+            memo_program_id: Pubkey::default(),
+            quote_signer: Pubkey::default(),
+            suspended_operators: vec![],
+            order_creation_fee_lamports: 0,
+            treasury: Pubkey::default(),
+            adapter_pools: vec![],
+            trusted_routers: vec![],
+            adapter_impact_caps: vec![],
+            order_price_band_bps: 0,
+            suppress_step_events: false,
+            enforce_quote_sanity: false,
+            quote_sanity_band_bps: 0,
+            reject_delegated_vaults: false,
+            pool_attestation_max_age_slots: 0,
+            paused_adapters: vec![],
+            sandboxed_adapters: vec![],
+            retired_adapters: vec![],
+            adapter_pool_counts: vec![],
         };
 
         let result = registry.get_adapter_program_id(&Swap::Raydium);
@@ -58,6 +95,24 @@ mod tests {
             operators: vec![operator1, operator2],
             supported_adapters: vec![],
             bump: 255,
+            memo_program_id: Pubkey::default(),
+            quote_signer: Pubkey::default(),
+            suspended_operators: vec![],
+            order_creation_fee_lamports: 0,
+            treasury: Pubkey::default(),
+            adapter_pools: vec![],
+            trusted_routers: vec![],
+            adapter_impact_caps: vec![],
+            order_price_band_bps: 0,
+            suppress_step_events: false,
+            enforce_quote_sanity: false,
+            quote_sanity_band_bps: 0,
+            reject_delegated_vaults: false,
+            pool_attestation_max_age_slots: 0,
+            paused_adapters: vec![],
+            sandboxed_adapters: vec![],
+            retired_adapters: vec![],
+            adapter_pool_counts: vec![],
         };
 
         assert!(registry.is_authorized_operator(&authority));
@@ -66,6 +121,84 @@ mod tests {
         assert!(!registry.is_authorized_operator(&non_operator));
     }
 
+    #[test]
+    fn test_adapter_registry_suspended_operator_not_authorized() {
+        let authority = Pubkey::new_unique();
+        let operator1 = Pubkey::new_unique();
+        let operator2 = Pubkey::new_unique();
+
+        let mut registry = AdapterRegistry {
+            authority,
+            operators: vec![operator1, operator2],
+            supported_adapters: vec![],
+            bump: 255,
+            memo_program_id: Pubkey::default(),
+            quote_signer: Pubkey::default(),
+            suspended_operators: vec![operator1],
+            order_creation_fee_lamports: 0,
+            treasury: Pubkey::default(),
+            adapter_pools: vec![],
+            trusted_routers: vec![],
+            adapter_impact_caps: vec![],
+            order_price_band_bps: 0,
+            suppress_step_events: false,
+            enforce_quote_sanity: false,
+            quote_sanity_band_bps: 0,
+            reject_delegated_vaults: false,
+            pool_attestation_max_age_slots: 0,
+            paused_adapters: vec![],
+            sandboxed_adapters: vec![],
+            retired_adapters: vec![],
+            adapter_pool_counts: vec![],
+        };
+
+        // Suspended operators stay registered but fail operator checks
+        assert!(registry.operators.contains(&operator1));
+        assert!(registry.is_suspended_operator(&operator1));
+        assert!(!registry.is_active_operator(&operator1));
+        assert!(!registry.is_authorized_operator(&operator1));
+        assert!(registry.is_active_operator(&operator2));
+        assert!(registry.is_authorized_operator(&authority));
+
+        // Reinstated
+        registry.suspended_operators.retain(|op| *op != operator1);
+        assert!(registry.is_active_operator(&operator1));
+        assert!(registry.is_authorized_operator(&operator1));
+    }
+
+    #[test]
+    fn test_adapter_registry_suspended_non_operator_not_active() {
+        // A suspended key that is no longer registered is not an operator either
+        let stale = Pubkey::new_unique();
+        let registry = AdapterRegistry {
+            authority: Pubkey::new_unique(),
+            operators: vec![],
+            supported_adapters: vec![],
+            bump: 255,
+            memo_program_id: Pubkey::default(),
+            quote_signer: Pubkey::default(),
+            suspended_operators: vec![stale],
+            order_creation_fee_lamports: 0,
+            treasury: Pubkey::default(),
+            adapter_pools: vec![],
+            trusted_routers: vec![],
+            adapter_impact_caps: vec![],
+            order_price_band_bps: 0,
+            suppress_step_events: false,
+            enforce_quote_sanity: false,
+            quote_sanity_band_bps: 0,
+            reject_delegated_vaults: false,
+            pool_attestation_max_age_slots: 0,
+            paused_adapters: vec![],
+            sandboxed_adapters: vec![],
+            retired_adapters: vec![],
+            adapter_pool_counts: vec![],
+        };
+
+        assert!(!registry.is_active_operator(&stale));
+        assert!(!registry.is_authorized_operator(&stale));
+    }
+
     #[test]
     fn test_adapter_registry_multiple_adapters() {
         let raydium = AdapterInfo {
@@ -84,6 +217,24 @@ mod tests {
             operators: vec![],
             supported_adapters: vec![raydium, meteora],
             bump: 255,
+            memo_program_id: Pubkey::default(),
+            quote_signer: Pubkey::default(),
+            suspended_operators: vec![],
+            order_creation_fee_lamports: 0,
+            treasury: Pubkey::default(),
+            adapter_pools: vec![],
+            trusted_routers: vec![],
+            adapter_impact_caps: vec![],
+            order_price_band_bps: 0,
+            suppress_step_events: false,
+            enforce_quote_sanity: false,
+            quote_sanity_band_bps: 0,
+            reject_delegated_vaults: false,
+            pool_attestation_max_age_slots: 0,
+            paused_adapters: vec![],
+            sandboxed_adapters: vec![],
+            retired_adapters: vec![],
+            adapter_pool_counts: vec![],
         };
 
         assert!(registry.is_supported_adapter(&Swap::Raydium));
@@ -259,4 +410,1462 @@ mod tests {
         assert_eq!(cloned.name, original.name);
         assert_eq!(cloned.program_id, original.program_id);
     }
+
+    #[test]
+    fn test_pool_info_space_fits_supported_swap_types() {
+        for swap in [Swap::Raydium, Swap::Whirlpool { a_to_b: true }, Swap::Meteora] {
+            let pool_info = PoolInfo {
+                adapter_swap_type: swap,
+                pool_address: Pubkey::new_unique(),
+                enabled: true,
+                last_verified_slot: u64::MAX,
+                min_liquidity_threshold: u64::MAX,
+                window_start: i64::MAX,
+                window_volume: u64::MAX,
+                max_volume_per_window: u64::MAX,
+                counted: true,
+            };
+            let data = pool_info.try_to_vec().unwrap();
+            assert!(8 + data.len() <= PoolInfo::SPACE);
+        }
+        assert_eq!(PoolInfo::PRE_VOLUME_CAP_SPACE, PoolInfo::SPACE - 3 * 8 - 1);
+        assert_eq!(PoolInfo::PRE_POOL_COUNT_SPACE, PoolInfo::SPACE - 1);
+    }
+
+    fn capped_pool_info(window_volume: u64, max_volume_per_window: u64) -> PoolInfo {
+        PoolInfo {
+            adapter_swap_type: Swap::Raydium,
+            pool_address: Pubkey::new_unique(),
+            enabled: true,
+            last_verified_slot: 0,
+            min_liquidity_threshold: 0,
+            window_start: 1_000,
+            window_volume,
+            max_volume_per_window,
+            counted: true,
+        }
+    }
+
+    #[test]
+    fn test_exceeds_volume_warning_boundaries() {
+        // 79% and exactly 80% stay quiet; anything above 80% warns, up to the full cap
+        assert!(!exceeds_volume_warning(79, 100, 80));
+        assert!(!exceeds_volume_warning(80, 100, 80));
+        assert!(exceeds_volume_warning(81, 100, 80));
+        assert!(exceeds_volume_warning(100, 100, 80));
+        assert!(!exceeds_volume_warning(7_900_000, 10_000_000, 80));
+        assert!(exceeds_volume_warning(8_000_001, 10_000_000, 80));
+        assert!(!exceeds_volume_warning(u64::MAX, u64::MAX, 100));
+    }
+
+    #[test]
+    fn test_pool_record_volume_warns_above_threshold() {
+        let mut pool_info = capped_pool_info(0, 100);
+        assert!(!pool_info.record_volume(79, 1_000, 80).unwrap());
+        assert!(!pool_info.record_volume(1, 1_000, 80).unwrap());
+        assert_eq!(pool_info.window_volume, 80);
+        assert!(pool_info.record_volume(20, 1_000, 80).unwrap());
+        assert_eq!(pool_info.window_volume, 100);
+    }
+
+    #[test]
+    fn test_pool_record_volume_rejects_over_cap() {
+        let mut pool_info = capped_pool_info(100, 100);
+        let err = pool_info.record_volume(1, 1_000, 80).unwrap_err();
+        assert_eq!(err, ErrorCode::PoolVolumeCapExceeded.into());
+        assert_eq!(pool_info.window_volume, 100);
+    }
+
+    #[test]
+    fn test_pool_record_volume_starts_new_window() {
+        let mut pool_info = capped_pool_info(100, 100);
+        let now = 1_000 + POOL_VOLUME_WINDOW_SECS;
+        assert!(!pool_info.record_volume(79, now, 80).unwrap());
+        assert_eq!(pool_info.window_start, now);
+        assert_eq!(pool_info.window_volume, 79);
+    }
+
+    #[test]
+    fn test_pool_record_volume_skips_uncapped_pools() {
+        let mut pool_info = capped_pool_info(0, 0);
+        assert!(!pool_info.record_volume(u64::MAX, 1_000, 80).unwrap());
+        assert_eq!(pool_info.window_volume, 0);
+    }
+
+    #[test]
+    fn test_pool_volume_warning_pct_defaults_to_80() {
+        let config = default_protocol_config();
+        assert_eq!(config.pool_volume_warning_pct(), DEFAULT_POOL_VOLUME_WARNING_PCT);
+        let config = ProtocolConfig { pool_volume_warning_pct: 95, ..default_protocol_config() };
+        assert_eq!(config.pool_volume_warning_pct(), 95);
+    }
+
+    #[test]
+    fn test_adapter_registry_space_fits_full_registry() {
+        // Longest adapter name still leaves room for its swap_type within ADAPTER_INFO_SPACE
+        let adapter = || AdapterInfo {
+            name: "W".repeat(32 - 2),
+            program_id: Pubkey::new_unique(),
+            swap_type: Swap::Whirlpool { a_to_b: true },
+        };
+        let adapters = (0..AdapterRegistry::MAX_ADAPTERS).map(|_| adapter()).collect();
+        let registry = AdapterRegistry {
+            authority: Pubkey::new_unique(),
+            operators: (0..AdapterRegistry::MAX_OPERATORS).map(|_| Pubkey::new_unique()).collect(),
+            supported_adapters: adapters,
+            bump: 255,
+            memo_program_id: Pubkey::default(),
+            quote_signer: Pubkey::default(),
+            suspended_operators: (0..AdapterRegistry::MAX_OPERATORS).map(|_| Pubkey::new_unique()).collect(),
+            order_creation_fee_lamports: u64::MAX,
+            treasury: Pubkey::new_unique(),
+            adapter_pools: (0..AdapterRegistry::MAX_ADAPTERS)
+                .map(|_| AdapterPools {
+                    swap_type: Swap::Whirlpool { a_to_b: true },
+                    pool_addresses: (0..AdapterRegistry::MAX_ADAPTER_POOLS).map(|_| Pubkey::new_unique()).collect(),
+                })
+                .collect(),
+            trusted_routers: (0..AdapterRegistry::MAX_TRUSTED_ROUTERS).map(|_| Pubkey::new_unique()).collect(),
+            adapter_impact_caps: (0..AdapterRegistry::MAX_ADAPTERS)
+                .map(|_| AdapterImpactCap {
+                    swap_type: Swap::Whirlpool { a_to_b: true },
+                    max_step_impact_bps: 10_000,
+                })
+                .collect(),
+            order_price_band_bps: 10_000,
+            suppress_step_events: false,
+            enforce_quote_sanity: false,
+            quote_sanity_band_bps: 0,
+            reject_delegated_vaults: false,
+            pool_attestation_max_age_slots: 0,
+            paused_adapters: vec![Swap::Whirlpool { a_to_b: true }; AdapterRegistry::MAX_ADAPTERS],
+            sandboxed_adapters: (0..AdapterRegistry::MAX_ADAPTERS)
+                .map(|_| AdapterSandbox {
+                    swap_type: Swap::Whirlpool { a_to_b: true },
+                    sandbox_users: (0..AdapterRegistry::MAX_SANDBOX_USERS).map(|_| Pubkey::new_unique()).collect(),
+                })
+                .collect(),
+            retired_adapters: (0..AdapterRegistry::MAX_RETIRED_ADAPTERS).map(|_| adapter()).collect(),
+            adapter_pool_counts: (0..AdapterRegistry::MAX_ADAPTER_POOL_COUNTS)
+                .map(|_| AdapterPoolCount {
+                    swap_type: Swap::Whirlpool { a_to_b: true },
+                    enabled_pools: u32::MAX,
+                })
+                .collect(),
+        };
+        let data = registry.try_to_vec().unwrap();
+        // Each whitelist, impact cap, pause, sandbox list and pool count reserves SWAP_TYPE_SPACE for its swap type,
+        // of which Whirlpool uses 2 bytes
+        let swap_type_slack = (4 * AdapterRegistry::MAX_ADAPTERS + AdapterRegistry::MAX_ADAPTER_POOL_COUNTS)
+            * (SWAP_TYPE_SPACE - Swap::Whirlpool { a_to_b: true }.try_to_vec().unwrap().len());
+        assert_eq!(AdapterRegistry::SPACE, 8 + data.len() + swap_type_slack);
+    }
+
+    #[test]
+    fn test_adapter_registry_serialized_size_of_populated_registry() {
+        let adapter = |name: &str, swap_type: Swap| AdapterInfo { name: name.to_string(), program_id: Pubkey::new_unique(), swap_type };
+        let registry = AdapterRegistry {
+            authority: Pubkey::new_unique(),
+            operators: vec![Pubkey::new_unique(), Pubkey::new_unique()],
+            supported_adapters: vec![adapter("Raydium", Swap::Raydium), adapter("Whirlpool", Swap::Whirlpool { a_to_b: true })],
+            bump: 255,
+            memo_program_id: SPL_MEMO_PROGRAM_ID,
+            quote_signer: Pubkey::new_unique(),
+            suspended_operators: vec![Pubkey::new_unique()],
+            order_creation_fee_lamports: 5_000,
+            treasury: Pubkey::new_unique(),
+            adapter_pools: vec![AdapterPools {
+                swap_type: Swap::Raydium,
+                pool_addresses: vec![Pubkey::new_unique(), Pubkey::new_unique(), Pubkey::new_unique()],
+            }],
+            trusted_routers: vec![Pubkey::new_unique()],
+            adapter_impact_caps: vec![AdapterImpactCap { swap_type: Swap::Whirlpool { a_to_b: true }, max_step_impact_bps: 300 }],
+            order_price_band_bps: 2_500,
+            suppress_step_events: true,
+            enforce_quote_sanity: true,
+            quote_sanity_band_bps: 1_000,
+            reject_delegated_vaults: true,
+            pool_attestation_max_age_slots: 150,
+            paused_adapters: vec![Swap::Whirlpool { a_to_b: true }],
+            sandboxed_adapters: vec![AdapterSandbox { swap_type: Swap::Raydium, sandbox_users: vec![Pubkey::new_unique()] }],
+            retired_adapters: vec![adapter("Meteora", Swap::Meteora)],
+            adapter_pool_counts: vec![AdapterPoolCount { swap_type: Swap::Raydium, enabled_pools: 3 }],
+        };
+        let mut data = Vec::new();
+        registry.try_serialize(&mut data).unwrap();
+
+        // Raydium and Meteora serialize as a 1-byte tag, Whirlpool as a tag and its a_to_b flag
+        let expected = 8 // discriminator
+            + 32 // authority
+            + 4 + 2 * 32 // operators
+            + 4 + (4 + 7 + 32 + 1) + (4 + 9 + 32 + 2) // supported_adapters
+            + 1 // bump
+            + 32 + 32 // memo_program_id, quote_signer
+            + 4 + 32 // suspended_operators
+            + 8 + 32 // order_creation_fee_lamports, treasury
+            + 4 + (1 + 4 + 3 * 32) // adapter_pools
+            + 4 + 32 // trusted_routers
+            + 4 + (2 + 2) // adapter_impact_caps
+            + 2 + 1 + 1 + 2 + 1 + 8 // band, step events, quote sanity, delegated vaults, attestation age
+            + 4 + 2 // paused_adapters
+            + 4 + (1 + 4 + 32) // sandboxed_adapters
+            + 4 + (4 + 7 + 32 + 1) // retired_adapters
+            + 4 + (1 + 4); // adapter_pool_counts
+        assert_eq!(data.len(), expected);
+        assert!(data.len() <= AdapterRegistry::SPACE);
+
+        let loaded = AdapterRegistry::try_deserialize(&mut &data[..]).unwrap();
+        assert_eq!(loaded.enabled_pool_count(&Swap::Raydium), 3);
+        assert_eq!(loaded.retired_adapters[0].name, "Meteora");
+    }
+
+    #[test]
+    fn test_adapter_registry_reads_registries_from_before_adapter_pauses() {
+        let program_id = Pubkey::new_unique();
+        let registry = AdapterRegistry {
+            authority: Pubkey::new_unique(),
+            operators: vec![Pubkey::new_unique()],
+            supported_adapters: vec![AdapterInfo { name: "Raydium".to_string(), program_id, swap_type: Swap::Raydium }],
+            bump: 255,
+            memo_program_id: Pubkey::default(),
+            quote_signer: Pubkey::default(),
+            suspended_operators: vec![],
+            order_creation_fee_lamports: 0,
+            treasury: Pubkey::default(),
+            adapter_pools: vec![],
+            trusted_routers: vec![],
+            adapter_impact_caps: vec![],
+            order_price_band_bps: 0,
+            suppress_step_events: false,
+            enforce_quote_sanity: false,
+            quote_sanity_band_bps: 0,
+            reject_delegated_vaults: false,
+            pool_attestation_max_age_slots: 0,
+            paused_adapters: vec![],
+            sandboxed_adapters: vec![],
+            retired_adapters: vec![],
+            adapter_pool_counts: vec![],
+        };
+        let mut data = Vec::new();
+        registry.try_serialize(&mut data).unwrap();
+
+        // A deployed registry ends at pool_attestation_max_age_slots, followed by the zeroed slack
+        // migrate_adapter_registry leaves, which reads as no paused or sandboxed adapters and no counted pools
+        data.truncate(data.len() - 4 - 4 - 4 - 4);
+        data.resize(AdapterRegistry::SPACE, 0);
+        let loaded = AdapterRegistry::try_deserialize(&mut &data[..]).unwrap();
+        assert_eq!(loaded.get_adapter_program_id(&Swap::Raydium).unwrap(), program_id);
+        assert!(loaded.is_adapter_enabled(&Swap::Raydium));
+        assert!(loaded.is_sandbox_user_allowed(&Swap::Raydium, &Pubkey::new_unique()));
+    }
+
+    #[test]
+    fn test_adapter_registry_last_program_id_covers_retired_adapters() {
+        let (live_program, retired_program) = (Pubkey::new_unique(), Pubkey::new_unique());
+        let mut registry = AdapterRegistry {
+            authority: Pubkey::new_unique(),
+            operators: vec![],
+            supported_adapters: vec![AdapterInfo { name: "Raydium".to_string(), program_id: live_program, swap_type: Swap::Raydium }],
+            bump: 255,
+            memo_program_id: Pubkey::default(),
+            quote_signer: Pubkey::default(),
+            suspended_operators: vec![],
+            order_creation_fee_lamports: 0,
+            treasury: Pubkey::default(),
+            adapter_pools: vec![],
+            trusted_routers: vec![],
+            adapter_impact_caps: vec![],
+            order_price_band_bps: 0,
+            suppress_step_events: false,
+            enforce_quote_sanity: false,
+            quote_sanity_band_bps: 0,
+            reject_delegated_vaults: false,
+            pool_attestation_max_age_slots: 0,
+            paused_adapters: vec![],
+            sandboxed_adapters: vec![],
+            retired_adapters: vec![AdapterInfo { name: "Meteora".to_string(), program_id: retired_program, swap_type: Swap::Meteora }],
+            adapter_pool_counts: vec![],
+        };
+
+        assert_eq!(registry.last_program_id(&Swap::Raydium), Some(live_program));
+        // A removed adapter is no longer supported, but its program is remembered
+        assert!(!registry.is_supported_adapter(&Swap::Meteora));
+        assert_eq!(registry.last_program_id(&Swap::Meteora), Some(retired_program));
+        assert_eq!(registry.last_program_id(&Swap::Whirlpool { a_to_b: true }), None);
+
+        // disable_adapter moves the entry over
+        let disabled = registry.supported_adapters.remove(0);
+        registry.retired_adapters.push(disabled);
+        assert_eq!(registry.last_program_id(&Swap::Raydium), Some(live_program));
+    }
+
+    #[test]
+    fn test_adapter_registry_memo_program_defaults_to_spl_memo() {
+        let mut registry = AdapterRegistry {
+            authority: Pubkey::new_unique(),
+            operators: vec![],
+            supported_adapters: vec![],
+            bump: 255,
+            memo_program_id: Pubkey::default(),
+            quote_signer: Pubkey::default(),
+            suspended_operators: vec![],
+            order_creation_fee_lamports: 0,
+            treasury: Pubkey::default(),
+            adapter_pools: vec![],
+            trusted_routers: vec![],
+            adapter_impact_caps: vec![],
+            order_price_band_bps: 0,
+            suppress_step_events: false,
+            enforce_quote_sanity: false,
+            quote_sanity_band_bps: 0,
+            reject_delegated_vaults: false,
+            pool_attestation_max_age_slots: 0,
+            paused_adapters: vec![],
+            sandboxed_adapters: vec![],
+            retired_adapters: vec![],
+            adapter_pool_counts: vec![],
+        };
+
+        // Unset (including registries migrated from before the field existed)
+        assert_eq!(registry.memo_program(), SPL_MEMO_PROGRAM_ID);
+
+        let fork_memo = Pubkey::new_unique();
+        registry.memo_program_id = fork_memo;
+        assert_eq!(registry.memo_program(), fork_memo);
+    }
+
+    #[test]
+    fn test_adapter_registry_pre_memo_account_deserializes() {
+        // Account data written before memo_program_id existed, padded with zeroed slack
+        let mut data = Vec::new();
+        Pubkey::new_unique().serialize(&mut data).unwrap();
+        Vec::<Pubkey>::new().serialize(&mut data).unwrap();
+        Vec::<AdapterInfo>::new().serialize(&mut data).unwrap();
+        254u8.serialize(&mut data).unwrap();
+        data.resize(AdapterRegistry::SPACE - 8, 0);
+
+        let registry = AdapterRegistry::deserialize(&mut data.as_slice()).unwrap();
+        assert_eq!(registry.bump, 254);
+        assert_eq!(registry.memo_program(), SPL_MEMO_PROGRAM_ID);
+        assert_eq!(registry.quote_signer, Pubkey::default());
+        assert!(registry.suspended_operators.is_empty());
+        // Order creation fee is disabled until the authority sets it
+        assert_eq!(registry.order_creation_fee_lamports, 0);
+        // No pool whitelists: every enabled pool stays routable
+        assert!(registry.adapter_pools.is_empty());
+        assert!(registry.trusted_routers.is_empty());
+        assert!(registry.is_pool_allowed(&Swap::Raydium, &Pubkey::new_unique()));
+        // No impact caps until set
+        assert_eq!(registry.max_step_impact_bps(&Swap::Raydium), 0);
+        // Strict limit orders use the default price band until set
+        assert_eq!(registry.price_band_bps(), DEFAULT_ORDER_PRICE_BAND_BPS);
+        // Step events stay on until the authority turns them off
+        assert!(registry.emit_step_events());
+        // Delegated vaults stay routable until an operator turns on the strict policy
+        assert!(!registry.reject_delegated_vaults);
+        // Routes don't require pool liquidity attestations until an operator sets a maximum age
+        assert_eq!(registry.pool_attestation_max_age_slots, 0);
+    }
+
+    #[test]
+    fn test_adapter_registry_is_adapter_enabled() {
+        let registry = AdapterRegistry {
+            authority: Pubkey::new_unique(),
+            operators: vec![],
+            supported_adapters: vec![
+                AdapterInfo {
+                    name: "Raydium".to_string(),
+                    program_id: Pubkey::new_unique(),
+                    swap_type: Swap::Raydium,
+                },
+                AdapterInfo {
+                    name: "Meteora".to_string(),
+                    program_id: Pubkey::new_unique(),
+                    swap_type: Swap::Meteora,
+                },
+            ],
+            bump: 255,
+            memo_program_id: Pubkey::default(),
+            quote_signer: Pubkey::default(),
+            suspended_operators: vec![],
+            order_creation_fee_lamports: 0,
+            treasury: Pubkey::default(),
+            adapter_pools: vec![],
+            trusted_routers: vec![],
+            adapter_impact_caps: vec![],
+            order_price_band_bps: 0,
+            suppress_step_events: false,
+            enforce_quote_sanity: false,
+            quote_sanity_band_bps: 0,
+            reject_delegated_vaults: false,
+            pool_attestation_max_age_slots: 0,
+            paused_adapters: vec![Swap::Meteora],
+            sandboxed_adapters: vec![],
+            retired_adapters: vec![],
+            adapter_pool_counts: vec![],
+        };
+
+        assert!(registry.is_adapter_enabled(&Swap::Raydium));
+        // Paused adapters stay registered but are not enabled
+        assert!(registry.is_supported_adapter(&Swap::Meteora));
+        assert!(!registry.is_adapter_enabled(&Swap::Meteora));
+        assert!(!registry.is_adapter_enabled(&Swap::Whirlpool { a_to_b: true }));
+
+        // describe_adapter tells the reasons apart
+        assert_eq!(registry.describe_adapter(&Swap::Raydium), AdapterStatus::Enabled);
+        assert_eq!(registry.describe_adapter(&Swap::Meteora), AdapterStatus::Disabled);
+        assert_eq!(registry.describe_adapter(&Swap::Whirlpool { a_to_b: true }), AdapterStatus::NotConfigured);
+
+        assert!(AdapterStatus::Enabled.require_enabled(&Swap::Raydium).is_ok());
+        let errors = [
+            (AdapterStatus::Disabled, ErrorCode::AdapterDisabled),
+            (AdapterStatus::NotConfigured, ErrorCode::AdapterNotConfigured),
+            (AdapterStatus::NotImplemented, ErrorCode::AdapterNotImplemented),
+        ];
+        for (status, error) in errors {
+            let err = status.require_enabled(&Swap::Meteora).unwrap_err();
+            assert_eq!(err, error.into());
+        }
+    }
+
+    #[test]
+    fn test_adapter_registry_is_sandbox_user_allowed() {
+        let tester = Pubkey::new_unique();
+        let registry = AdapterRegistry {
+            authority: Pubkey::new_unique(),
+            operators: vec![],
+            supported_adapters: vec![
+                AdapterInfo {
+                    name: "Raydium".to_string(),
+                    program_id: Pubkey::new_unique(),
+                    swap_type: Swap::Raydium,
+                },
+                AdapterInfo {
+                    name: "Meteora".to_string(),
+                    program_id: Pubkey::new_unique(),
+                    swap_type: Swap::Meteora,
+                },
+            ],
+            bump: 255,
+            memo_program_id: Pubkey::default(),
+            quote_signer: Pubkey::default(),
+            suspended_operators: vec![],
+            order_creation_fee_lamports: 0,
+            treasury: Pubkey::default(),
+            adapter_pools: vec![],
+            trusted_routers: vec![],
+            adapter_impact_caps: vec![],
+            order_price_band_bps: 0,
+            suppress_step_events: false,
+            enforce_quote_sanity: false,
+            quote_sanity_band_bps: 0,
+            reject_delegated_vaults: false,
+            pool_attestation_max_age_slots: 0,
+            paused_adapters: vec![],
+            sandboxed_adapters: vec![AdapterSandbox { swap_type: Swap::Meteora, sandbox_users: vec![tester] }],
+            retired_adapters: vec![],
+            adapter_pool_counts: vec![],
+        };
+
+        assert!(registry.is_sandbox_user_allowed(&Swap::Raydium, &Pubkey::new_unique()));
+        assert!(registry.is_sandbox_user_allowed(&Swap::Meteora, &tester));
+        assert!(!registry.is_sandbox_user_allowed(&Swap::Meteora, &Pubkey::new_unique()));
+        // Unregistered adapters are left to the supported-adapter checks
+        assert!(registry.is_sandbox_user_allowed(&Swap::Whirlpool { a_to_b: true }, &Pubkey::new_unique()));
+    }
+
+    #[test]
+    fn test_adapter_registry_is_pool_allowed() {
+        let whitelisted_pool = Pubkey::new_unique();
+        let registry = AdapterRegistry {
+            authority: Pubkey::new_unique(),
+            operators: vec![],
+            supported_adapters: vec![],
+            bump: 255,
+            memo_program_id: Pubkey::default(),
+            quote_signer: Pubkey::default(),
+            suspended_operators: vec![],
+            order_creation_fee_lamports: 0,
+            treasury: Pubkey::default(),
+            adapter_pools: vec![AdapterPools {
+                swap_type: Swap::Raydium,
+                pool_addresses: vec![whitelisted_pool],
+            }],
+            trusted_routers: vec![],
+            adapter_impact_caps: vec![],
+            order_price_band_bps: 0,
+            suppress_step_events: false,
+            enforce_quote_sanity: false,
+            quote_sanity_band_bps: 0,
+            reject_delegated_vaults: false,
+            pool_attestation_max_age_slots: 0,
+            paused_adapters: vec![],
+            sandboxed_adapters: vec![],
+            retired_adapters: vec![],
+            adapter_pool_counts: vec![],
+        };
+
+        // Whitelisted adapter only accepts its listed pools
+        assert!(registry.is_pool_allowed(&Swap::Raydium, &whitelisted_pool));
+        assert!(!registry.is_pool_allowed(&Swap::Raydium, &Pubkey::new_unique()));
+        // Adapters without a whitelist accept any pool
+        assert!(registry.is_pool_allowed(&Swap::Meteora, &Pubkey::new_unique()));
+    }
+
+    #[test]
+    fn test_adapter_registry_is_trusted_router() {
+        let router = Pubkey::new_unique();
+        let authority = Pubkey::new_unique();
+        let registry = AdapterRegistry {
+            authority,
+            operators: vec![],
+            supported_adapters: vec![],
+            bump: 255,
+            memo_program_id: Pubkey::default(),
+            quote_signer: Pubkey::default(),
+            suspended_operators: vec![],
+            order_creation_fee_lamports: 0,
+            treasury: Pubkey::default(),
+            adapter_pools: vec![],
+            trusted_routers: vec![router],
+            adapter_impact_caps: vec![],
+            order_price_band_bps: 0,
+            suppress_step_events: false,
+            enforce_quote_sanity: false,
+            quote_sanity_band_bps: 0,
+            reject_delegated_vaults: false,
+            pool_attestation_max_age_slots: 0,
+            paused_adapters: vec![],
+            sandboxed_adapters: vec![],
+            retired_adapters: vec![],
+            adapter_pool_counts: vec![],
+        };
+
+        assert!(registry.is_trusted_router(&router));
+        // The registry authority is not implicitly trusted
+        assert!(!registry.is_trusted_router(&authority));
+        assert!(!registry.is_trusted_router(&Pubkey::new_unique()));
+    }
+
+    #[test]
+    fn test_adapter_registry_max_step_impact_bps() {
+        let registry = AdapterRegistry {
+            authority: Pubkey::new_unique(),
+            operators: vec![],
+            supported_adapters: vec![],
+            bump: 255,
+            memo_program_id: Pubkey::default(),
+            quote_signer: Pubkey::default(),
+            suspended_operators: vec![],
+            order_creation_fee_lamports: 0,
+            treasury: Pubkey::default(),
+            adapter_pools: vec![],
+            trusted_routers: vec![],
+            adapter_impact_caps: vec![AdapterImpactCap {
+                swap_type: Swap::Whirlpool { a_to_b: true },
+                max_step_impact_bps: 100,
+            }],
+            order_price_band_bps: 0,
+            suppress_step_events: false,
+            enforce_quote_sanity: false,
+            quote_sanity_band_bps: 0,
+            reject_delegated_vaults: false,
+            pool_attestation_max_age_slots: 0,
+            paused_adapters: vec![],
+            sandboxed_adapters: vec![],
+            retired_adapters: vec![],
+            adapter_pool_counts: vec![],
+        };
+
+        assert_eq!(registry.max_step_impact_bps(&Swap::Whirlpool { a_to_b: true }), 100);
+        // Caps are per swap type, so each whirlpool direction has its own
+        assert_eq!(registry.max_step_impact_bps(&Swap::Whirlpool { a_to_b: false }), 0);
+        assert_eq!(registry.max_step_impact_bps(&Swap::Raydium), 0);
+    }
+
+    #[test]
+    fn test_adapter_registry_should_emit_step_events() {
+        let mut registry = AdapterRegistry {
+            authority: Pubkey::new_unique(),
+            operators: vec![],
+            supported_adapters: vec![],
+            bump: 255,
+            memo_program_id: Pubkey::default(),
+            quote_signer: Pubkey::default(),
+            suspended_operators: vec![],
+            order_creation_fee_lamports: 0,
+            treasury: Pubkey::default(),
+            adapter_pools: vec![],
+            trusted_routers: vec![],
+            adapter_impact_caps: vec![],
+            order_price_band_bps: 0,
+            suppress_step_events: false,
+            enforce_quote_sanity: false,
+            quote_sanity_band_bps: 0,
+            reject_delegated_vaults: false,
+            pool_attestation_max_age_slots: 0,
+            paused_adapters: vec![],
+            sandboxed_adapters: vec![],
+            retired_adapters: vec![],
+            adapter_pool_counts: vec![],
+        };
+
+        // Default: single-hop routes emit step events unless the instruction opts out
+        assert!(registry.should_emit_step_events(1, None));
+        assert!(!registry.should_emit_step_events(1, Some(false)));
+
+        // Aggregated-only deployments skip them unless the instruction opts in
+        registry.suppress_step_events = true;
+        assert!(!registry.emit_step_events());
+        assert!(!registry.should_emit_step_events(1, None));
+        assert!(registry.should_emit_step_events(1, Some(true)));
+
+        // Multi-hop routes always emit step events
+        assert!(registry.should_emit_step_events(2, None));
+        assert!(registry.should_emit_step_events(3, Some(false)));
+    }
+
+    #[test]
+    fn test_adapter_registry_quote_band_bps_defaults_when_unset() {
+        let mut registry = AdapterRegistry {
+            authority: Pubkey::new_unique(),
+            operators: vec![],
+            supported_adapters: vec![],
+            bump: 255,
+            memo_program_id: Pubkey::default(),
+            quote_signer: Pubkey::default(),
+            suspended_operators: vec![],
+            order_creation_fee_lamports: 0,
+            treasury: Pubkey::default(),
+            adapter_pools: vec![],
+            trusted_routers: vec![],
+            adapter_impact_caps: vec![],
+            order_price_band_bps: 0,
+            suppress_step_events: false,
+            enforce_quote_sanity: false,
+            quote_sanity_band_bps: 0,
+            reject_delegated_vaults: false,
+            pool_attestation_max_age_slots: 0,
+            paused_adapters: vec![],
+            sandboxed_adapters: vec![],
+            retired_adapters: vec![],
+            adapter_pool_counts: vec![],
+        };
+
+        assert_eq!(registry.quote_band_bps(), DEFAULT_QUOTE_SANITY_BAND_BPS);
+        registry.quote_sanity_band_bps = 500;
+        assert_eq!(registry.quote_band_bps(), 500);
+    }
+
+    #[test]
+    fn test_operator_stats_record_activity_throttled() {
+        let mut stats = OperatorStats {
+            operator: Pubkey::new_unique(),
+            last_active: 0,
+            bump: 255,
+            outstanding_obligations: 0,
+            obligations_transferred_to: Pubkey::default(),
+        };
+
+        // First activity is always recorded
+        assert!(stats.record_activity(1_000));
+        assert_eq!(stats.last_active, 1_000);
+
+        // Within the interval the timestamp is kept
+        assert!(!stats.record_activity(1_000 + OperatorStats::HEARTBEAT_INTERVAL - 1));
+        assert_eq!(stats.last_active, 1_000);
+
+        // Once the interval has passed it moves forward
+        assert!(stats.record_activity(1_000 + OperatorStats::HEARTBEAT_INTERVAL));
+        assert_eq!(stats.last_active, 1_000 + OperatorStats::HEARTBEAT_INTERVAL);
+    }
+
+    #[test]
+    fn test_operator_stats_space_matches_serialized_size() {
+        let stats = OperatorStats {
+            operator: Pubkey::new_unique(),
+            last_active: i64::MAX,
+            bump: 255,
+            outstanding_obligations: u32::MAX,
+            obligations_transferred_to: Pubkey::new_unique(),
+        };
+        let mut data = Vec::new();
+        stats.try_serialize(&mut data).unwrap();
+        assert_eq!(data.len(), OperatorStats::SPACE);
+    }
+
+    #[test]
+    fn test_operator_stats_obligations_are_counted_and_settled() {
+        let mut stats = OperatorStats {
+            operator: Pubkey::new_unique(),
+            last_active: 0,
+            bump: 255,
+            outstanding_obligations: 0,
+            obligations_transferred_to: Pubkey::default(),
+        };
+
+        stats.add_obligation().unwrap();
+        stats.add_obligation().unwrap();
+        assert_eq!(stats.outstanding_obligations, 2);
+        stats.settle_obligation();
+        assert_eq!(stats.outstanding_obligations, 1);
+
+        // Settling never underflows
+        stats.settle_obligation();
+        stats.settle_obligation();
+        assert_eq!(stats.outstanding_obligations, 0);
+
+        stats.outstanding_obligations = u32::MAX;
+        assert_eq!(stats.add_obligation().unwrap_err(), ErrorCode::InvalidCalculation.into());
+    }
+
+    #[test]
+    fn test_operator_stats_refund_destination_follows_transfer() {
+        let mut stats = OperatorStats {
+            operator: Pubkey::new_unique(),
+            last_active: 0,
+            bump: 255,
+            outstanding_obligations: 1,
+            obligations_transferred_to: Pubkey::default(),
+        };
+        assert_eq!(stats.refund_destination(), stats.operator);
+
+        let treasury = Pubkey::new_unique();
+        stats.obligations_transferred_to = treasury;
+        assert_eq!(stats.refund_destination(), treasury);
+    }
+
+    fn admin_log() -> AdminLog {
+        AdminLog {
+            entries: [AdminLogEntry::default(); ADMIN_LOG_CAPACITY],
+            total_actions: 0,
+            bump: 255,
+        }
+    }
+
+    fn admin_log_entry(timestamp: i64) -> AdminLogEntry {
+        AdminLogEntry {
+            action: AdminAction::AddOperator,
+            actor: Pubkey::new_unique(),
+            target: Pubkey::new_unique(),
+            timestamp,
+        }
+    }
+
+    #[test]
+    fn test_admin_log_wraps_after_capacity() {
+        let mut log = admin_log();
+        for i in 1..=33 {
+            log.push(admin_log_entry(i));
+        }
+
+        assert_eq!(log.total_actions, 33);
+        // The 33rd action overwrote the 1st in slot 0
+        assert_eq!(log.entries[0].timestamp, 33);
+        assert_eq!(log.entries[1].timestamp, 2);
+
+        let recent = log.recent(0, ADMIN_LOG_CAPACITY + 1);
+        assert_eq!(recent.len(), ADMIN_LOG_CAPACITY);
+        assert_eq!(recent[0].timestamp, 33);
+        assert_eq!(recent[ADMIN_LOG_CAPACITY - 1].timestamp, 2);
+    }
+
+    #[test]
+    fn test_admin_log_recent_pages_newest_first() {
+        let mut log = admin_log();
+        assert!(log.recent(0, ADMIN_LOG_PAGE_SIZE).is_empty());
+
+        for i in 1..=5 {
+            log.push(admin_log_entry(i));
+        }
+        let timestamps = |entries: Vec<AdminLogEntry>| entries.iter().map(|e| e.timestamp).collect::<Vec<_>>();
+        assert_eq!(timestamps(log.recent(0, 2)), vec![5, 4]);
+        assert_eq!(timestamps(log.recent(2, 2)), vec![3, 2]);
+        assert_eq!(timestamps(log.recent(4, 2)), vec![1]);
+        assert!(log.recent(5, 2).is_empty());
+    }
+
+    #[test]
+    fn test_admin_log_space_matches_serialized_size() {
+        let mut log = admin_log();
+        log.total_actions = u64::MAX;
+        let mut data = Vec::new();
+        log.try_serialize(&mut data).unwrap();
+        assert_eq!(data.len(), AdminLog::SPACE);
+
+        // A full page fits in the 1024-byte return data with its Vec length prefix
+        let page = vec![admin_log_entry(i64::MAX); ADMIN_LOG_PAGE_SIZE];
+        assert!(page.try_to_vec().unwrap().len() <= 1024);
+        assert_eq!(AdminAction::RevokeVaultDelegations as u8, 32);
+    }
+
+    #[test]
+    fn test_admin_log_record_skips_uncreated_log() {
+        let key = Pubkey::new_unique();
+        let owner = System::id();
+        let mut lamports = 0;
+        let mut data = vec![];
+        let account = AccountInfo::new(&key, false, true, &mut lamports, &mut data, &owner, false, 0);
+
+        AdminLog::record(&account, &crate::ID, AdminAction::AddOperator, Pubkey::new_unique(), Pubkey::new_unique()).unwrap();
+        assert!(account.data_is_empty());
+    }
+
+    fn fee_schedule(tiers: &[FeeTier]) -> FeeSchedule {
+        let mut stored = [FeeTier::default(); MAX_FEE_TIERS];
+        stored[..tiers.len()].copy_from_slice(tiers);
+        FeeSchedule {
+            mint: Pubkey::new_unique(),
+            tiers: stored,
+            tier_count: tiers.len() as u8,
+            bump: 255,
+        }
+    }
+
+    #[test]
+    fn test_fee_schedule_fee_bps_for_tier_boundaries() {
+        let schedule = fee_schedule(&[
+            FeeTier { max_input_amount: 1_000, fee_bps: 30 },
+            FeeTier { max_input_amount: 100_000, fee_bps: 20 },
+            FeeTier { max_input_amount: 10_000_000, fee_bps: 10 },
+        ]);
+
+        assert_eq!(schedule.fee_bps_for(0), Some(30));
+        // A tier covers its max_input_amount inclusively
+        assert_eq!(schedule.fee_bps_for(1_000), Some(30));
+        assert_eq!(schedule.fee_bps_for(1_001), Some(20));
+        assert_eq!(schedule.fee_bps_for(100_000), Some(20));
+        assert_eq!(schedule.fee_bps_for(100_001), Some(10));
+        // Orders above every tier pay the last tier
+        assert_eq!(schedule.fee_bps_for(u64::MAX), Some(10));
+    }
+
+    #[test]
+    fn test_fee_schedule_without_tiers_has_no_fee() {
+        let schedule = fee_schedule(&[]);
+        assert_eq!(schedule.fee_bps_for(0), None);
+        assert_eq!(schedule.fee_bps_for(u64::MAX), None);
+    }
+
+    #[test]
+    fn test_fee_schedule_validate_tiers() {
+        let tier = |max_input_amount| FeeTier { max_input_amount, fee_bps: 10 };
+
+        assert!(FeeSchedule::validate_tiers(&[]).is_ok());
+        assert!(FeeSchedule::validate_tiers(&[tier(1), tier(2), tier(3), tier(4)]).is_ok());
+
+        let err = FeeSchedule::validate_tiers(&[tier(1), tier(2), tier(3), tier(4), tier(5)]).unwrap_err();
+        assert_eq!(err, ErrorCode::InvalidFeeSchedule.into());
+        let err = FeeSchedule::validate_tiers(&[tier(2), tier(2)]).unwrap_err();
+        assert_eq!(err, ErrorCode::InvalidFeeSchedule.into());
+        let err = FeeSchedule::validate_tiers(&[tier(3), tier(1)]).unwrap_err();
+        assert_eq!(err, ErrorCode::InvalidFeeSchedule.into());
+    }
+
+    #[test]
+    fn test_fee_schedule_space_matches_serialized_size() {
+        let schedule = fee_schedule(&[FeeTier { max_input_amount: u64::MAX, fee_bps: u8::MAX }]);
+        let mut data = Vec::new();
+        schedule.try_serialize(&mut data).unwrap();
+        assert_eq!(data.len(), FeeSchedule::SPACE);
+    }
+
+    #[test]
+    fn test_fee_vault_registry_register_replaces_per_mint() {
+        let mut registry = FeeVaultRegistry { entries: vec![], bump: 255 };
+        let (mint, first_vault, second_vault) = (Pubkey::new_unique(), Pubkey::new_unique(), Pubkey::new_unique());
+
+        assert_eq!(registry.register(mint, first_vault).unwrap(), None);
+        assert_eq!(registry.fee_vault_for(&mint), Some(first_vault));
+        assert_eq!(registry.register(mint, second_vault).unwrap(), Some(first_vault));
+        assert_eq!(registry.entries.len(), 1);
+        assert_eq!(registry.fee_vault_for(&mint), Some(second_vault));
+        assert_eq!(registry.fee_vault_for(&Pubkey::new_unique()), None);
+    }
+
+    #[test]
+    fn test_fee_vault_registry_is_bounded() {
+        let mut registry = FeeVaultRegistry { entries: vec![], bump: 255 };
+        let mints: Vec<Pubkey> = (0..MAX_FEE_VAULTS).map(|_| Pubkey::new_unique()).collect();
+        for mint in &mints {
+            registry.register(*mint, Pubkey::new_unique()).unwrap();
+        }
+
+        let err = registry.register(Pubkey::new_unique(), Pubkey::new_unique()).unwrap_err();
+        assert_eq!(err, ErrorCode::FeeVaultRegistryFull.into());
+        // A full registry still accepts a new vault for a mint it already maps
+        assert!(registry.register(mints[0], Pubkey::new_unique()).is_ok());
+
+        let mut data = Vec::new();
+        registry.try_serialize(&mut data).unwrap();
+        assert_eq!(data.len(), FeeVaultRegistry::SPACE);
+    }
+
+    #[test]
+    fn test_fee_vault_registry_unregister() {
+        let mut registry = FeeVaultRegistry { entries: vec![], bump: 255 };
+        let (mint, fee_vault) = (Pubkey::new_unique(), Pubkey::new_unique());
+        registry.register(mint, fee_vault).unwrap();
+
+        assert_eq!(registry.unregister(&mint).unwrap(), fee_vault);
+        assert_eq!(registry.fee_vault_for(&mint), None);
+        let err = registry.unregister(&mint).unwrap_err();
+        assert_eq!(err, ErrorCode::FeeVaultNotRegistered.into());
+    }
+
+    // ProtocolConfig with default limits and no pauses, governance delay or volume tiers
+    fn default_protocol_config() -> ProtocolConfig {
+        ProtocolConfig {
+            limits: DEFAULT_PROTOCOL_LIMITS,
+            bump: 255,
+            creation_paused: false,
+            execution_paused: false,
+            governance_delay_secs: 0,
+            volume_mint: Pubkey::default(),
+            volume_fee_tiers: [VolumeFeeTier::default(); MAX_VOLUME_FEE_TIERS],
+            volume_tier_count: 0,
+            pool_volume_warning_pct: 0,
+        }
+    }
+
+    #[test]
+    fn test_protocol_config_space_matches_serialized_size() {
+        let config = ProtocolConfig { limits: DEFAULT_PROTOCOL_LIMITS, bump: 255, creation_paused: false, execution_paused: false, governance_delay_secs: 0, ..default_protocol_config() };
+        let mut data = Vec::new();
+        config.try_serialize(&mut data).unwrap();
+        assert_eq!(data.len(), ProtocolConfig::SPACE);
+        let volume_tiers_len = 32 + MAX_VOLUME_FEE_TIERS * (8 + 2) + 1;
+        assert_eq!(ProtocolConfig::LEGACY_SPACE, ProtocolConfig::SPACE - 2 - 8 - volume_tiers_len - 1);
+        assert_eq!(ProtocolConfig::PRE_GOVERNANCE_SPACE, ProtocolConfig::SPACE - 8 - volume_tiers_len - 1);
+        assert_eq!(ProtocolConfig::PRE_VOLUME_TIERS_SPACE, ProtocolConfig::SPACE - volume_tiers_len - 1);
+        assert_eq!(ProtocolConfig::PRE_POOL_VOLUME_WARNING_SPACE, ProtocolConfig::SPACE - 1);
+        assert_eq!(AdminAction::SetProtocolLimits as u8, AdminAction::CreateProtocolConfig as u8 + 1);
+    }
+
+    #[test]
+    fn test_protocol_config_load_falls_back_to_defaults() {
+        let key = Pubkey::new_unique();
+        let owner = System::id();
+        let mut lamports = 0;
+        let mut data = vec![];
+        let account = AccountInfo::new(&key, false, false, &mut lamports, &mut data, &owner, false, 0);
+        assert_eq!(ProtocolConfig::load(&account, &crate::ID).unwrap(), DEFAULT_PROTOCOL_LIMITS);
+
+        let limits = ProtocolLimits { max_order_slippage_bps: 250, ..DEFAULT_PROTOCOL_LIMITS };
+        let mut data = Vec::new();
+        ProtocolConfig { limits, bump: 255, creation_paused: false, execution_paused: false, governance_delay_secs: 0, ..default_protocol_config() }.try_serialize(&mut data).unwrap();
+        let (owner, mut lamports) = (crate::ID, 1);
+        let account = AccountInfo::new(&key, false, false, &mut lamports, &mut data, &owner, false, 0);
+        assert_eq!(ProtocolConfig::load(&account, &crate::ID).unwrap(), limits);
+    }
+
+    #[test]
+    fn test_protocol_config_pauses_are_independent() {
+        let key = Pubkey::new_unique();
+        let (owner, mut lamports) = (crate::ID, 1);
+        for (creation_paused, execution_paused) in [(false, false), (true, false), (false, true), (true, true)] {
+            let mut data = Vec::new();
+            ProtocolConfig { limits: DEFAULT_PROTOCOL_LIMITS, bump: 255, creation_paused, execution_paused, governance_delay_secs: 0, ..default_protocol_config() }
+                .try_serialize(&mut data)
+                .unwrap();
+            let account = AccountInfo::new(&key, false, false, &mut lamports, &mut data, &owner, false, 0);
+            let config = ProtocolConfig::load_config(&account, &crate::ID).unwrap();
+
+            assert_eq!(config.require_creation_open().is_err(), creation_paused);
+            assert_eq!(config.require_execution_open().is_err(), execution_paused);
+        }
+
+        let config = ProtocolConfig { limits: DEFAULT_PROTOCOL_LIMITS, bump: 255, creation_paused: true, execution_paused: true, governance_delay_secs: 0, ..default_protocol_config() };
+        assert_eq!(config.require_creation_open().unwrap_err(), ErrorCode::OrderCreationPaused.into());
+        assert_eq!(config.require_execution_open().unwrap_err(), ErrorCode::OrderExecutionPaused.into());
+    }
+
+    #[test]
+    fn test_protocol_config_legacy_layout_reads_unpaused() {
+        let limits = ProtocolLimits { max_route_slippage_bps: 500, ..DEFAULT_PROTOCOL_LIMITS };
+        let mut data = Vec::new();
+        ProtocolConfig { limits, bump: 255, creation_paused: true, execution_paused: true, governance_delay_secs: 0, ..default_protocol_config() }.try_serialize(&mut data).unwrap();
+        data.truncate(ProtocolConfig::LEGACY_SPACE);
+
+        let key = Pubkey::new_unique();
+        let (owner, mut lamports) = (crate::ID, 1);
+        let account = AccountInfo::new(&key, false, false, &mut lamports, &mut data, &owner, false, 0);
+        let config = ProtocolConfig::load_config(&account, &crate::ID).unwrap();
+        assert_eq!(config.limits, limits);
+        assert!(!config.creation_paused);
+        assert!(!config.execution_paused);
+    }
+
+    #[test]
+    fn test_protocol_config_pre_governance_layout_reads_no_delay() {
+        let mut data = Vec::new();
+        ProtocolConfig { limits: DEFAULT_PROTOCOL_LIMITS, bump: 255, creation_paused: true, execution_paused: false, governance_delay_secs: 3_600, ..default_protocol_config() }
+            .try_serialize(&mut data)
+            .unwrap();
+        data.truncate(ProtocolConfig::PRE_GOVERNANCE_SPACE);
+
+        let key = Pubkey::new_unique();
+        let (owner, mut lamports) = (crate::ID, 1);
+        let account = AccountInfo::new(&key, false, false, &mut lamports, &mut data, &owner, false, 0);
+        let config = ProtocolConfig::load_config(&account, &crate::ID).unwrap();
+        assert!(config.creation_paused);
+        assert_eq!(config.governance_delay_secs, 0);
+        // Unset delays still wait the minimum
+        assert_eq!(config.governance_delay(), MIN_GOVERNANCE_DELAY_SECS);
+    }
+
+    #[test]
+    fn test_protocol_config_governance_delay_has_a_floor() {
+        let mut config = default_protocol_config();
+        assert_eq!(config.governance_delay(), MIN_GOVERNANCE_DELAY_SECS);
+        config.governance_delay_secs = MIN_GOVERNANCE_DELAY_SECS + 1;
+        assert_eq!(config.governance_delay(), MIN_GOVERNANCE_DELAY_SECS + 1);
+
+        // A missing config waits the minimum too
+        let (key, owner, mut lamports, mut data) = (Pubkey::new_unique(), Pubkey::default(), 0, vec![]);
+        let account = AccountInfo::new(&key, false, false, &mut lamports, &mut data, &owner, false, 0);
+        let config = ProtocolConfig::load_config(&account, &crate::ID).unwrap();
+        assert_eq!(config.governance_delay(), MIN_GOVERNANCE_DELAY_SECS);
+    }
+
+    // ProtocolConfig counting volume in `volume_mint` with `tiers`
+    fn volume_tier_config(volume_mint: Pubkey, tiers: &[VolumeFeeTier]) -> ProtocolConfig {
+        let mut config = default_protocol_config();
+        config.volume_mint = volume_mint;
+        config.volume_fee_tiers[..tiers.len()].copy_from_slice(tiers);
+        config.volume_tier_count = tiers.len() as u8;
+        config
+    }
+
+    #[test]
+    fn test_protocol_config_pre_volume_tiers_layout_reads_no_tiers() {
+        let tiers = [VolumeFeeTier { min_volume: 1_000, fee_multiplier_bps: 5_000 }];
+        let mut config = volume_tier_config(Pubkey::new_unique(), &tiers);
+        config.governance_delay_secs = 3_600;
+        let mut data = Vec::new();
+        config.try_serialize(&mut data).unwrap();
+        data.truncate(ProtocolConfig::PRE_VOLUME_TIERS_SPACE);
+
+        let key = Pubkey::new_unique();
+        let (owner, mut lamports) = (crate::ID, 1);
+        let account = AccountInfo::new(&key, false, false, &mut lamports, &mut data, &owner, false, 0);
+        let config = ProtocolConfig::load_config(&account, &crate::ID).unwrap();
+        assert_eq!(config.governance_delay_secs, 3_600);
+        assert_eq!(config.volume_mint, Pubkey::default());
+        assert_eq!(config.volume_tier_count, 0);
+        assert_eq!(config.volume_fee_bps(30, u64::MAX).unwrap(), 30);
+    }
+
+    #[test]
+    fn test_volume_fee_bps_tier_boundaries() {
+        let tiers = [
+            VolumeFeeTier { min_volume: 1_000, fee_multiplier_bps: 8_000 },
+            VolumeFeeTier { min_volume: 10_000, fee_multiplier_bps: 5_000 },
+            VolumeFeeTier { min_volume: 100_000, fee_multiplier_bps: 0 },
+        ];
+        let config = volume_tier_config(Pubkey::new_unique(), &tiers);
+
+        // Below the first tier the fee is unchanged
+        assert_eq!(config.volume_fee_bps(50, 0).unwrap(), 50);
+        assert_eq!(config.volume_fee_bps(50, 999).unwrap(), 50);
+        // A tier applies from exactly its min_volume
+        assert_eq!(config.volume_fee_bps(50, 1_000).unwrap(), 40);
+        assert_eq!(config.volume_fee_bps(50, 9_999).unwrap(), 40);
+        assert_eq!(config.volume_fee_bps(50, 10_000).unwrap(), 25);
+        // The highest tier reached wins
+        assert_eq!(config.volume_fee_bps(50, 100_000).unwrap(), 0);
+        assert_eq!(config.volume_fee_bps(50, u64::MAX).unwrap(), 0);
+        // Scaled fees round down
+        assert_eq!(config.volume_fee_bps(25, 10_000).unwrap(), 12);
+        assert_eq!(config.volume_fee_bps(1, 1_000).unwrap(), 0);
+        assert_eq!(config.volume_fee_bps(0, 1_000).unwrap(), 0);
+
+        // Without tiers nothing is rebated
+        assert_eq!(default_protocol_config().volume_fee_bps(50, u64::MAX).unwrap(), 50);
+    }
+
+    #[test]
+    fn test_validate_volume_tiers() {
+        let tier = |min_volume, fee_multiplier_bps| VolumeFeeTier { min_volume, fee_multiplier_bps };
+        assert!(ProtocolConfig::validate_volume_tiers(&[]).is_ok());
+        assert!(ProtocolConfig::validate_volume_tiers(&[tier(0, 10_000)]).is_ok());
+        assert!(ProtocolConfig::validate_volume_tiers(&[tier(1, 9_000), tier(2, 8_000), tier(3, 7_000), tier(4, 6_000)]).is_ok());
+
+        for tiers in [
+            vec![tier(1, 9_000), tier(2, 8_000), tier(3, 7_000), tier(4, 6_000), tier(5, 5_000)],
+            vec![tier(10, 9_000), tier(10, 8_000)],
+            vec![tier(10, 9_000), tier(5, 8_000)],
+            vec![tier(10, 10_001)],
+        ] {
+            let err = ProtocolConfig::validate_volume_tiers(&tiers).unwrap_err();
+            assert_eq!(err, ErrorCode::InvalidVolumeFeeTiers.into());
+        }
+    }
+
+    #[test]
+    fn test_route_volume_counts_volume_mint_side() {
+        let (volume_mint, other_mint, third_mint) = (Pubkey::new_unique(), Pubkey::new_unique(), Pubkey::new_unique());
+        let config = volume_tier_config(volume_mint, &[]);
+
+        assert_eq!(config.route_volume(&volume_mint, &other_mint, 100, 7), 100);
+        assert_eq!(config.route_volume(&other_mint, &volume_mint, 100, 7), 7);
+        assert_eq!(config.route_volume(&other_mint, &third_mint, 100, 7), 0);
+        // No volume mint, no volume
+        assert_eq!(default_protocol_config().route_volume(&Pubkey::default(), &other_mint, 100, 7), 0);
+    }
+
+    fn empty_trader_volume() -> TraderVolume {
+        TraderVolume {
+            trader: Pubkey::new_unique(),
+            bucket_epochs: [0; TRADER_VOLUME_EPOCHS],
+            bucket_volumes: [0; TRADER_VOLUME_EPOCHS],
+            bump: 255,
+        }
+    }
+
+    #[test]
+    fn test_trader_volume_space_matches_serialized_size() {
+        let mut data = Vec::new();
+        empty_trader_volume().try_serialize(&mut data).unwrap();
+        assert_eq!(data.len(), TraderVolume::SPACE);
+    }
+
+    #[test]
+    fn test_trader_volume_window_covers_recent_epochs() {
+        let mut trader_volume = empty_trader_volume();
+        trader_volume.record(100, 1_000);
+        trader_volume.record(100, 500);
+        trader_volume.record(105, 2_000);
+
+        assert_eq!(trader_volume.window_volume(105), 3_500);
+        // The epoch-100 bucket is in the window for TRADER_VOLUME_EPOCHS epochs
+        let last_epoch = 100 + TRADER_VOLUME_EPOCHS as u64 - 1;
+        assert_eq!(trader_volume.window_volume(last_epoch), 3_500);
+        assert_eq!(trader_volume.window_volume(last_epoch + 1), 2_000);
+        // Buckets recorded after the epoch asked about are not counted
+        assert_eq!(trader_volume.window_volume(99), 0);
+        assert_eq!(trader_volume.window_volume(104), 1_500);
+    }
+
+    #[test]
+    fn test_trader_volume_rollover_clears_stale_bucket() {
+        let mut trader_volume = empty_trader_volume();
+        trader_volume.record(100, 1_000);
+
+        // Same bucket index, TRADER_VOLUME_EPOCHS epochs later
+        let next_epoch = 100 + TRADER_VOLUME_EPOCHS as u64;
+        trader_volume.record(next_epoch, 300);
+        assert_eq!(trader_volume.window_volume(next_epoch), 300);
+        assert_eq!(trader_volume.bucket_volumes.iter().sum::<u64>(), 300);
+
+        trader_volume.record(next_epoch, u64::MAX);
+        assert_eq!(trader_volume.window_volume(next_epoch), u64::MAX);
+    }
+
+    fn governance_adapter(program_id: Pubkey) -> AdapterInfo {
+        AdapterInfo {
+            name: "Raydium".to_string(),
+            program_id,
+            swap_type: Swap::Raydium,
+        }
+    }
+
+    fn governance_proposal(call: &GovernanceCall, executable_at: i64) -> GovernanceProposal {
+        GovernanceProposal {
+            call_hash: call.hash().unwrap(),
+            proposer: Pubkey::new_unique(),
+            proposed_at: executable_at - 3_600,
+            executable_at,
+            expires_at: executable_at + GOVERNANCE_PROPOSAL_TTL_SECS,
+            bump: 255,
+        }
+    }
+
+    #[test]
+    fn test_governance_proposal_space_matches_serialized_size() {
+        let call = GovernanceCall::SetGovernanceDelay { delay_secs: 60 };
+        let mut data = Vec::new();
+        governance_proposal(&call, 1_000).try_serialize(&mut data).unwrap();
+        assert_eq!(data.len(), GovernanceProposal::SPACE);
+    }
+
+    #[test]
+    fn test_governance_proposal_executes_only_the_proposed_call_in_its_window() {
+        let program_id = Pubkey::new_unique();
+        let call = GovernanceCall::ResetAdapterRegistry { adapters: vec![governance_adapter(program_id)], operators: vec![] };
+        let proposal = governance_proposal(&call, 10_000);
+
+        assert!(proposal.require_executable(&call, 10_000).is_ok());
+        assert!(proposal.require_executable(&call, proposal.expires_at - 1).is_ok());
+
+        let err = proposal.require_executable(&call, 9_999).unwrap_err();
+        assert_eq!(err, ErrorCode::GovernanceDelayNotElapsed.into());
+        let err = proposal.require_executable(&call, proposal.expires_at).unwrap_err();
+        assert_eq!(err, ErrorCode::GovernanceProposalExpired.into());
+        assert!(proposal.is_expired(proposal.expires_at));
+
+        // Any change to the arguments, or the same arguments for another call, is a different call
+        let other_operators = GovernanceCall::ResetAdapterRegistry {
+            adapters: vec![governance_adapter(program_id)],
+            operators: vec![Pubkey::new_unique()],
+        };
+        let other_call = GovernanceCall::ConfigureAdapter { adapter: governance_adapter(program_id) };
+        for call in [other_operators, other_call] {
+            let err = proposal.require_executable(&call, 10_000).unwrap_err();
+            assert_eq!(err, ErrorCode::GovernanceProposalMismatch.into());
+        }
+    }
+
+    #[test]
+    fn test_default_protocol_limits_keep_existing_caps() {
+        let limits = DEFAULT_PROTOCOL_LIMITS;
+        assert!(limits.validate().is_ok());
+        assert!(limits.check_route_slippage(10_000).is_ok());
+        assert!(limits.check_order_slippage(1_000).is_ok());
+        assert_eq!(limits.check_order_slippage(1_001).unwrap_err(), ErrorCode::InvalidSlippage.into());
+        assert_eq!(limits.max_stop_loss_trigger_bps, 10_000);
+        assert_eq!(limits.max_take_profit_trigger_bps, 100_000);
+    }
+
+    #[test]
+    fn test_protocol_limits_validate_ceilings() {
+        for limits in [
+            ProtocolLimits { max_route_slippage_bps: 10_001, ..DEFAULT_PROTOCOL_LIMITS },
+            ProtocolLimits { max_order_slippage_bps: 10_001, ..DEFAULT_PROTOCOL_LIMITS },
+            ProtocolLimits { max_stop_loss_trigger_bps: 10_001, ..DEFAULT_PROTOCOL_LIMITS },
+        ] {
+            assert_eq!(limits.validate().unwrap_err(), ErrorCode::InvalidProtocolLimits.into());
+        }
+
+        // Tighter caps and a wider TakeProfit cap are fine
+        let limits = ProtocolLimits {
+            max_route_slippage_bps: 500,
+            max_order_slippage_bps: 100,
+            max_stop_loss_trigger_bps: 5_000,
+            max_take_profit_trigger_bps: 200_000,
+            min_order_duration_seconds: 60,
+            max_order_duration_seconds: 86_400,
+        };
+        assert!(limits.validate().is_ok());
+        assert_eq!(limits.check_route_slippage(501).unwrap_err(), ErrorCode::InvalidSlippage.into());
+    }
+
+    #[test]
+    fn test_protocol_limits_validate_order_durations() {
+        for limits in [
+            ProtocolLimits { min_order_duration_seconds: -1, ..DEFAULT_PROTOCOL_LIMITS },
+            ProtocolLimits { max_order_duration_seconds: -1, ..DEFAULT_PROTOCOL_LIMITS },
+            ProtocolLimits { min_order_duration_seconds: 3_601, max_order_duration_seconds: 3_600, ..DEFAULT_PROTOCOL_LIMITS },
+        ] {
+            assert_eq!(limits.validate().unwrap_err(), ErrorCode::InvalidProtocolLimits.into());
+        }
+
+        // An unbounded maximum accepts any minimum, and the bounds may be equal
+        assert!(ProtocolLimits { min_order_duration_seconds: 3_600, ..DEFAULT_PROTOCOL_LIMITS }.validate().is_ok());
+        let limits = ProtocolLimits {
+            min_order_duration_seconds: 3_600,
+            max_order_duration_seconds: 3_600,
+            ..DEFAULT_PROTOCOL_LIMITS
+        };
+        assert!(limits.validate().is_ok());
+    }
+
+    #[test]
+    fn test_check_order_duration_boundaries() {
+        let now = 1_700_000_000;
+        let limits = ProtocolLimits {
+            min_order_duration_seconds: 300,
+            max_order_duration_seconds: 90 * 86_400,
+            ..DEFAULT_PROTOCOL_LIMITS
+        };
+
+        assert!(limits.check_order_duration(now + 300, now).is_ok());
+        assert_eq!(
+            limits.check_order_duration(now + 299, now).unwrap_err(),
+            ErrorCode::OrderDurationTooShort.into()
+        );
+        assert!(limits.check_order_duration(now + 90 * 86_400, now).is_ok());
+        assert_eq!(
+            limits.check_order_duration(now + 90 * 86_400 + 1, now).unwrap_err(),
+            ErrorCode::OrderDurationTooLong.into()
+        );
+    }
+
+    #[test]
+    fn test_default_order_durations_are_unbounded() {
+        let now = 1_700_000_000;
+        assert!(DEFAULT_PROTOCOL_LIMITS.check_order_duration(now + 1, now).is_ok());
+        assert!(DEFAULT_PROTOCOL_LIMITS.check_order_duration(i64::MAX, now).is_ok());
+        // A past expiry never meets the minimum; instructions reject it with InvalidExpiry first
+        assert!(DEFAULT_PROTOCOL_LIMITS.check_order_duration(0, now).is_err());
+    }
+
+    #[test]
+    fn test_fee_event_source_is_last_byte() {
+        // Indexers decode the source from the final byte, after the existing FeeEvent fields
+        for (source, tag) in [
+            (FeeSource::Route, 0u8),
+            (FeeSource::SharedRoute, 1),
+            (FeeSource::LimitOrder, 2),
+            (FeeSource::SharedLimitOrder, 3),
+            (FeeSource::RouteAndCreateOrder, 4),
+        ] {
+            let event = FeeEvent {
+                account: Pubkey::new_unique(),
+                mint: Pubkey::new_unique(),
+                amount: 10,
+                net_amount: 9,
+                fee_bps: 30,
+                source,
+            };
+            let data = event.try_to_vec().unwrap();
+            assert_eq!(data.len(), 32 + 32 + 8 + 8 + 1 + 1);
+            assert_eq!(data[data.len() - 2], 30);
+            assert_eq!(data[data.len() - 1], tag);
+        }
+    }
+
+    #[test]
+    fn test_vault_registry_add_remove_round_trip() {
+        let mut registry = VaultRegistry { mints: vec![], bump: 255 };
+        let (first, second, third) = (Pubkey::new_unique(), Pubkey::new_unique(), Pubkey::new_unique());
+
+        assert!(registry.add(first));
+        assert!(registry.add(second));
+        assert!(!registry.add(first));
+        assert!(registry.add(third));
+        assert_eq!(registry.mints, vec![first, second, third]);
+
+        // Removal keeps the remaining mints in registration order
+        assert!(registry.remove(&second));
+        assert!(!registry.remove(&second));
+        assert_eq!(registry.mints, vec![first, third]);
+
+        assert!(registry.add(second));
+        assert_eq!(registry.mints, vec![first, third, second]);
+    }
+
+    #[test]
+    fn test_vault_registry_space_matches_serialized_size() {
+        for count in [0usize, 1, 40] {
+            let registry = VaultRegistry {
+                mints: (0..count).map(|_| Pubkey::new_unique()).collect(),
+                bump: 255,
+            };
+            let mut data = Vec::new();
+            registry.try_serialize(&mut data).unwrap();
+            assert_eq!(data.len(), VaultRegistry::space(count));
+        }
+    }
+
+    #[test]
+    fn test_vault_registry_pages() {
+        let registry = VaultRegistry {
+            mints: (0..40).map(|_| Pubkey::new_unique()).collect(),
+            bump: 255,
+        };
+
+        // Oversized limits are capped at the page size
+        let first_page = registry.page(0, usize::MAX);
+        assert_eq!(first_page.len(), VAULT_REGISTRY_PAGE_SIZE);
+        assert_eq!(first_page[..], registry.mints[..VAULT_REGISTRY_PAGE_SIZE]);
+
+        let last_page = registry.page(VAULT_REGISTRY_PAGE_SIZE, VAULT_REGISTRY_PAGE_SIZE);
+        assert_eq!(last_page[..], registry.mints[VAULT_REGISTRY_PAGE_SIZE..]);
+        assert_eq!(registry.page(2, 3)[..], registry.mints[2..5]);
+        assert!(registry.page(40, 10).is_empty());
+        assert!(registry.page(0, 0).is_empty());
+
+        // A full page fits the 1024-byte return data limit with its 4-byte length prefix
+        assert!(4 + VAULT_REGISTRY_PAGE_SIZE * 32 <= 1024);
+    }
+
+    #[test]
+    fn test_vault_meta_generation_defaults_to_zero() {
+        let key = Pubkey::new_unique();
+        let owner = System::id();
+        let mut lamports = 0;
+        let mut data = vec![];
+        let account = AccountInfo::new(&key, false, false, &mut lamports, &mut data, &owner, false, 0);
+        assert_eq!(VaultMeta::load_generation(&account, &crate::ID).unwrap(), 0);
+
+        let meta = VaultMeta { vault: Pubkey::new_unique(), vault_generation: 3, bump: 255 };
+        let mut data = Vec::new();
+        meta.try_serialize(&mut data).unwrap();
+        assert_eq!(data.len(), VaultMeta::SPACE);
+        let (owner, mut lamports) = (crate::ID, 1);
+        let account = AccountInfo::new(&key, false, false, &mut lamports, &mut data, &owner, false, 0);
+        assert_eq!(VaultMeta::load_generation(&account, &crate::ID).unwrap(), 3);
+    }
+
+    fn route_step(swap: Swap, percent: u8, input_index: u8) -> RoutePlanStep {
+        RoutePlanStep { swap, percent, input_index, output_index: input_index + 2 }
+    }
+
+    #[test]
+    fn test_route_plan_event_summarizes_steps_in_order() {
+        use crate::sdk::{RAYDIUM_SWAP_TAG, WHIRLPOOL_SWAP_TAG, WRAP_SOL_SWAP_TAG};
+        use crate::test_utils::*;
+
+        let mut accounts: Vec<TestAccount> = (0..7).map(|_| empty_account()).collect();
+        let keys: Vec<Pubkey> = accounts.iter().map(|account| account.key).collect();
+        let infos: Vec<AccountInfo> = accounts.iter_mut().map(|account| account.to_account_info()).collect();
+
+        let route_plan = vec![
+            route_step(Swap::WrapSol, 100, 0),
+            route_step(Swap::Raydium, 60, 2),
+            route_step(Swap::Whirlpool { a_to_b: false }, 100, 2),
+        ];
+        let event = RoutePlanEvent::from_plan(&route_plan, &infos).unwrap();
+
+        assert_eq!(
+            event.steps,
+            vec![
+                RouteStepSummary { swap_tag: u16::from(WRAP_SOL_SWAP_TAG), percent: 100, pool: keys[1] },
+                RouteStepSummary { swap_tag: u16::from(RAYDIUM_SWAP_TAG), percent: 60, pool: keys[3] },
+                RouteStepSummary { swap_tag: u16::from(WHIRLPOOL_SWAP_TAG), percent: 100, pool: keys[3] },
+            ]
+        );
+    }
+
+    #[test]
+    fn test_route_plan_event_is_bounded() {
+        use crate::test_utils::*;
+
+        let mut accounts: Vec<TestAccount> = (0..2).map(|_| empty_account()).collect();
+        let infos: Vec<AccountInfo> = accounts.iter_mut().map(|account| account.to_account_info()).collect();
+
+        let route_plan: Vec<RoutePlanStep> = (0..MAX_ROUTE_PLAN_EVENT_STEPS + 2)
+            .map(|_| route_step(Swap::Raydium, 100, 0))
+            .collect();
+        let event = RoutePlanEvent::from_plan(&route_plan, &infos).unwrap();
+        assert_eq!(event.steps.len(), MAX_ROUTE_PLAN_EVENT_STEPS);
+
+        // A step whose PoolInfo slot is past the remaining accounts cannot be summarized
+        let short_plan = vec![route_step(Swap::Raydium, 100, 1)];
+        assert_eq!(
+            RoutePlanEvent::from_plan(&short_plan, &infos).err(),
+            Some(ErrorCode::NotEnoughAccountKeys.into())
+        );
+    }
+
+    #[test]
+    fn test_route_plan_event_decodes() {
+        use anchor_lang::Event;
+
+        let event = RoutePlanEvent {
+            steps: vec![
+                RouteStepSummary { swap_tag: 7, percent: 40, pool: Pubkey::new_unique() },
+                RouteStepSummary { swap_tag: 126, percent: 100, pool: Pubkey::new_unique() },
+            ],
+        };
+
+        // emit_cpi! data is the event-CPI prefix followed by the borsh-encoded steps
+        let mut ix_data = anchor_lang::event::EVENT_IX_TAG_LE.to_vec();
+        ix_data.extend(event.data());
+        assert!(ix_data.starts_with(&crate::sdk::event_cpi_prefix::<RoutePlanEvent>()));
+        assert_eq!(ix_data.len(), 16 + 4 + 2 * (2 + 1 + 32));
+        let decoded = RoutePlanEvent::try_from_slice(&ix_data[16..]).unwrap();
+        assert_eq!(decoded.steps, event.steps);
+    }
 }
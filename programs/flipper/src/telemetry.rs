@@ -0,0 +1,58 @@
+use anchor_lang::prelude::*;
+use anchor_lang::solana_program::compute_units::sol_remaining_compute_units;
+
+// Compute unit telemetry, compiled only with the "telemetry" feature
+//
+// route, shared_route and execute_limit_order read the remaining compute units when their
+// handler starts and again just before returning, then emit ComputeTelemetry with the
+// difference. Test builds enable the feature and compare the numbers with the budgets in
+// TESTING.md, so CU regressions fail tests instead of showing up on explorers.
+
+/// instruction_tag values of ComputeTelemetry
+pub const ROUTE_TELEMETRY_TAG: u8 = 0;
+pub const SHARED_ROUTE_TELEMETRY_TAG: u8 = 1;
+pub const EXECUTE_LIMIT_ORDER_TELEMETRY_TAG: u8 = 2;
+
+/// Compute units one instruction handler consumed
+#[event]
+#[derive(Debug, PartialEq, Eq)]
+pub struct ComputeTelemetry {
+    pub instruction_tag: u8,
+    pub cus_consumed: u64,
+    pub steps: u8,
+}
+
+/// Compute units consumed between two sol_remaining_compute_units readings
+pub fn cus_consumed(start: u64, end: u64) -> u64 {
+    start.saturating_sub(end)
+}
+
+/// Number of steps in Jupiter shared_accounts_route data: the route_plan length after the
+/// discriminator and the id byte; 0 when the data is too short to hold it
+pub fn jupiter_route_steps(data: &[u8]) -> usize {
+    data.get(9..13)
+        .and_then(|len| len.try_into().ok())
+        .map(|len| u32::from_le_bytes(len) as usize)
+        .unwrap_or(0)
+}
+
+/// Reading taken at the start of a handler, turned into a ComputeTelemetry event by finish
+pub struct ComputeMeter {
+    instruction_tag: u8,
+    start: u64,
+}
+
+impl ComputeMeter {
+    pub fn start(instruction_tag: u8) -> Self {
+        Self { instruction_tag, start: sol_remaining_compute_units() }
+    }
+
+    /// Event for the caller to emit; `steps` saturates at u8::MAX
+    pub fn finish(&self, steps: usize) -> ComputeTelemetry {
+        ComputeTelemetry {
+            instruction_tag: self.instruction_tag,
+            cus_consumed: cus_consumed(self.start, sol_remaining_compute_units()),
+            steps: u8::try_from(steps).unwrap_or(u8::MAX),
+        }
+    }
+}
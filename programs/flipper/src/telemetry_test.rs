@@ -0,0 +1,56 @@
+#[cfg(test)]
+mod tests {
+    use super::super::telemetry::*;
+    use anchor_lang::Discriminator;
+
+    #[test]
+    fn test_cus_consumed_is_the_difference_of_readings() {
+        assert_eq!(cus_consumed(200_000, 142_500), 57_500);
+        assert_eq!(cus_consumed(200_000, 200_000), 0);
+        // A reading that grew (never on chain) reports nothing rather than wrapping
+        assert_eq!(cus_consumed(1_000, 2_000), 0);
+    }
+
+    #[test]
+    fn test_meter_reports_tag_and_saturated_steps() {
+        let meter = ComputeMeter::start(EXECUTE_LIMIT_ORDER_TELEMETRY_TAG);
+
+        let event = meter.finish(3);
+        assert_eq!(event.instruction_tag, EXECUTE_LIMIT_ORDER_TELEMETRY_TAG);
+        assert_eq!(event.steps, 3);
+        assert_eq!(meter.finish(300).steps, u8::MAX);
+    }
+
+    #[test]
+    fn test_jupiter_route_steps_reads_route_plan_length() {
+        // discriminator, id, route_plan length (u32 LE), then the plan and trailing args
+        let mut data = vec![0u8; 8];
+        data.push(1);
+        data.extend_from_slice(&4u32.to_le_bytes());
+        data.extend_from_slice(&[0u8; 40]);
+        assert_eq!(jupiter_route_steps(&data), 4);
+
+        assert_eq!(jupiter_route_steps(&data[..12]), 0);
+        assert_eq!(jupiter_route_steps(&[]), 0);
+    }
+
+    #[test]
+    fn test_telemetry_tags_are_distinct() {
+        let tags = [ROUTE_TELEMETRY_TAG, SHARED_ROUTE_TELEMETRY_TAG, EXECUTE_LIMIT_ORDER_TELEMETRY_TAG];
+        for (idx, tag) in tags.iter().enumerate() {
+            assert!(tags[idx + 1..].iter().all(|other| other != tag));
+        }
+    }
+
+    #[test]
+    fn test_compute_telemetry_encoding() {
+        let event = ComputeTelemetry { instruction_tag: ROUTE_TELEMETRY_TAG, cus_consumed: 61_234, steps: 2 };
+
+        // Event data: discriminator | tag | u64 LE cus_consumed | steps
+        let data = anchor_lang::Event::data(&event);
+        assert_eq!(&data[..8], ComputeTelemetry::DISCRIMINATOR);
+        assert_eq!(data[8], ROUTE_TELEMETRY_TAG);
+        assert_eq!(&data[9..17], &61_234u64.to_le_bytes());
+        assert_eq!(&data[17..], &[2]);
+    }
+}
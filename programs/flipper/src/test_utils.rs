@@ -0,0 +1,173 @@
+use anchor_lang::prelude::*;
+use anchor_lang::solana_program::program_option::COption;
+use anchor_lang::solana_program::program_pack::Pack;
+use anchor_spl::token::spl_token::state::{Account as SplTokenAccount, AccountState, Mint as SplMint};
+use crate::adapters::adapter_connector_module::AdapterContext;
+use crate::state::{PoolInfo, Swap};
+
+// Account fixtures for native unit tests of adapters and route validation
+// Each fixture owns its key, lamports and data; AccountInfos borrow them
+
+// Rent-paying balance so Anchor does not treat fixtures as uninitialized
+pub const FIXTURE_LAMPORTS: u64 = 1_000_000_000;
+
+pub struct TestAccount {
+    pub key: Pubkey,
+    pub owner: Pubkey,
+    pub lamports: u64,
+    pub data: Vec<u8>,
+    pub is_signer: bool,
+    pub is_writable: bool,
+    pub executable: bool,
+}
+
+impl TestAccount {
+    // Creates a writable, non-signer account with a fresh key
+    pub fn new(owner: Pubkey, data: Vec<u8>) -> Self {
+        Self {
+            key: Pubkey::new_unique(),
+            owner,
+            lamports: FIXTURE_LAMPORTS,
+            data,
+            is_signer: false,
+            is_writable: true,
+            executable: false,
+        }
+    }
+
+    pub fn with_key(mut self, key: Pubkey) -> Self {
+        self.key = key;
+        self
+    }
+
+    pub fn to_account_info(&mut self) -> AccountInfo<'_> {
+        AccountInfo::new(
+            &self.key,
+            self.is_signer,
+            self.is_writable,
+            &mut self.lamports,
+            &mut self.data,
+            &self.owner,
+            self.executable,
+            0,
+        )
+    }
+}
+
+// Account with no data, for slots an adapter only forwards by key (tick arrays, oracles, placeholders)
+pub fn empty_account() -> TestAccount {
+    TestAccount::new(System::id(), vec![])
+}
+
+// Executable account whose key is `program_id` (DEX, token and memo programs)
+pub fn program_account(program_id: Pubkey) -> TestAccount {
+    let mut account = TestAccount::new(Pubkey::default(), vec![]).with_key(program_id);
+    account.is_writable = false;
+    account.executable = true;
+    account
+}
+
+// PoolInfo owned by this program, as created by initialize_pool_info
+pub fn pool_info_account(adapter_swap_type: Swap, pool_address: Pubkey, enabled: bool) -> TestAccount {
+    let pool_info = PoolInfo {
+        adapter_swap_type,
+        pool_address,
+        enabled,
+        last_verified_slot: 0,
+        min_liquidity_threshold: 0,
+        window_start: 0,
+        window_volume: 0,
+        max_volume_per_window: 0,
+        counted: enabled,
+    };
+    let mut data = Vec::with_capacity(PoolInfo::SPACE);
+    pool_info.try_serialize(&mut data).unwrap();
+    TestAccount::new(crate::ID, data)
+}
+
+// Initialized token account in the base (165 byte) layout shared by SPL Token and Token-2022
+pub fn token_account(mint: Pubkey, owner: Pubkey, amount: u64, token_program: Pubkey) -> TestAccount {
+    let account = SplTokenAccount {
+        mint,
+        owner,
+        amount,
+        delegate: COption::None,
+        state: AccountState::Initialized,
+        is_native: COption::None,
+        delegated_amount: 0,
+        close_authority: COption::None,
+    };
+    let mut data = vec![0u8; SplTokenAccount::LEN];
+    SplTokenAccount::pack(account, &mut data).unwrap();
+    TestAccount::new(token_program, data)
+}
+
+// Initialized mint in the base (82 byte) layout shared by SPL Token and Token-2022
+pub fn mint_account(decimals: u8, token_program: Pubkey) -> TestAccount {
+    let mint = SplMint {
+        mint_authority: COption::None,
+        supply: 0,
+        decimals,
+        is_initialized: true,
+        freeze_authority: COption::None,
+    };
+    let mut data = vec![0u8; SplMint::LEN];
+    SplMint::pack(mint, &mut data).unwrap();
+    TestAccount::new(token_program, data)
+}
+
+// Whirlpool data in the mock program layout: discriminator, token_mint_a, token_mint_b, rest of the state
+pub fn mock_whirlpool_data(mint_a: Pubkey, mint_b: Pubkey) -> Vec<u8> {
+    let mut data = vec![0u8; 8 + 32 * 4 + 8 + 8 + 16 + 16 + 4];
+    data[..8].copy_from_slice(&crate::adapters::whirlpool::WHIRLPOOL_DISCRIMINATOR);
+    data[8..40].copy_from_slice(mint_a.as_ref());
+    data[40..72].copy_from_slice(mint_b.as_ref());
+    data
+}
+
+// Oracle PDA the Whirlpool program at `whirlpool_program_id` derives for `whirlpool`
+pub fn whirlpool_oracle_address(whirlpool_program_id: &Pubkey, whirlpool: &Pubkey) -> Pubkey {
+    Pubkey::find_program_address(&[b"oracle", whirlpool.as_ref()], whirlpool_program_id).0
+}
+
+// Pool state (whirlpool, lb_pair, AMM state) owned by the DEX program
+// Adapters only check ownership and keys, so the data is zeroed at the given size
+pub fn pool_state_account(dex_program_id: Pubkey, size: usize) -> TestAccount {
+    TestAccount::new(dex_program_id, vec![0u8; size])
+}
+
+// Leaks the fixtures so their AccountInfos live for 'static,
+// since AdapterContext borrows remaining accounts for its whole 'info lifetime
+pub fn leak_account_infos(accounts: Vec<TestAccount>) -> &'static [AccountInfo<'static>] {
+    let accounts: &'static mut [TestAccount] = Box::leak(accounts.into_boxed_slice());
+    let account_infos: Vec<AccountInfo<'static>> = accounts
+        .iter_mut()
+        .map(|account| account.to_account_info())
+        .collect();
+    Box::leak(account_infos.into_boxed_slice())
+}
+
+// Builds an AdapterContext around the given vaults and adapter accounts
+// The authority is the real vault_authority PDA of this program
+pub fn adapter_context(
+    input_account: TestAccount,
+    output_account: TestAccount,
+    remaining_accounts: Vec<TestAccount>,
+) -> AdapterContext<'static> {
+    let (vault_authority, _) = Pubkey::find_program_address(&[b"vault_authority"], &crate::ID);
+    let fixed = leak_account_infos(vec![
+        program_account(anchor_spl::token::ID),
+        TestAccount::new(crate::ID, vec![]).with_key(vault_authority),
+        input_account,
+        output_account,
+    ]);
+
+    AdapterContext {
+        token_program: fixed[0].clone(),
+        authority: fixed[1].clone(),
+        input_account: fixed[2].clone(),
+        output_account: fixed[3].clone(),
+        remaining_accounts: leak_account_infos(remaining_accounts),
+        program_id: crate::ID,
+    }
+}
@@ -0,0 +1,93 @@
+use anchor_lang::prelude::*;
+use flipper::cpi::accounts::{ChangeAuthority, ConfigureAdapter};
+use flipper::program::Flipper;
+use flipper::state::AdapterInfo;
+
+declare_id!("VwUmPCeYMmEgY4qz3UCmNpdxA4xjT9kxYp6VmBsusGD");
+
+/// Seed of the PDA that holds the flipper registry authority
+pub const GOVERNANCE_SEED: &[u8] = b"governance";
+
+/// Mock governance program acting as the flipper registry authority through its PDA
+#[program]
+pub mod mock_governance {
+    use super::*;
+
+    /// Adds or updates an adapter with the governance PDA signing as registry authority
+    pub fn configure_adapter(ctx: Context<GovernConfigureAdapter>, adapter: AdapterInfo) -> Result<()> {
+        let bump = [ctx.bumps.governance];
+        let signer_seeds: &[&[&[u8]]] = &[&[GOVERNANCE_SEED, &bump]];
+        let accounts = &ctx.accounts;
+
+        flipper::cpi::configure_adapter(
+            CpiContext::new_with_signer(
+                accounts.flipper_program.to_account_info(),
+                ConfigureAdapter {
+                    adapter_registry: accounts.adapter_registry.to_account_info(),
+                    operator: accounts.governance.to_account_info(),
+                    governance_proposal: None,
+                    admin_log: accounts.admin_log.to_account_info(),
+                    event_authority: accounts.flipper_event_authority.to_account_info(),
+                    program: accounts.flipper_program.to_account_info(),
+                },
+                signer_seeds,
+            ),
+            adapter,
+        )
+    }
+
+    /// Hands the registry authority from the governance PDA to new_authority
+    pub fn change_authority(ctx: Context<GovernChangeAuthority>) -> Result<()> {
+        let bump = [ctx.bumps.governance];
+        let signer_seeds: &[&[&[u8]]] = &[&[GOVERNANCE_SEED, &bump]];
+        let accounts = &ctx.accounts;
+
+        flipper::cpi::change_authority(CpiContext::new_with_signer(
+            accounts.flipper_program.to_account_info(),
+            ChangeAuthority {
+                adapter_registry: accounts.adapter_registry.to_account_info(),
+                authority: accounts.governance.to_account_info(),
+                new_authority: accounts.new_authority.to_account_info(),
+                admin_log: accounts.admin_log.to_account_info(),
+                event_authority: accounts.flipper_event_authority.to_account_info(),
+                program: accounts.flipper_program.to_account_info(),
+            },
+            signer_seeds,
+        ))
+    }
+}
+
+#[derive(Accounts)]
+pub struct GovernConfigureAdapter<'info> {
+    /// CHECK: Signing PDA of this program; flipper checks it is the registry authority.
+    /// Writable because flipper refunds governance proposals to the configuring signer.
+    #[account(mut, seeds = [GOVERNANCE_SEED], bump)]
+    pub governance: UncheckedAccount<'info>,
+    /// CHECK: Validated by flipper
+    #[account(mut)]
+    pub adapter_registry: UncheckedAccount<'info>,
+    /// CHECK: Flipper AdminLog PDA, validated by flipper
+    #[account(mut)]
+    pub admin_log: UncheckedAccount<'info>,
+    /// CHECK: Flipper event authority PDA, validated by flipper
+    pub flipper_event_authority: UncheckedAccount<'info>,
+    pub flipper_program: Program<'info, Flipper>,
+}
+
+#[derive(Accounts)]
+pub struct GovernChangeAuthority<'info> {
+    /// CHECK: Signing PDA of this program; flipper checks it is the registry authority
+    #[account(seeds = [GOVERNANCE_SEED], bump)]
+    pub governance: UncheckedAccount<'info>,
+    /// CHECK: Validated by flipper
+    #[account(mut)]
+    pub adapter_registry: UncheckedAccount<'info>,
+    /// CHECK: Recorded by flipper as the new registry authority
+    pub new_authority: UncheckedAccount<'info>,
+    /// CHECK: Flipper AdminLog PDA, validated by flipper
+    #[account(mut)]
+    pub admin_log: UncheckedAccount<'info>,
+    /// CHECK: Flipper event authority PDA, validated by flipper
+    pub flipper_event_authority: UncheckedAccount<'info>,
+    pub flipper_program: Program<'info, Flipper>,
+}
@@ -6,6 +6,12 @@ use anchor_spl::{
 
 declare_id!("3gD2eXxYMEqYsamgosxKb7MdVqgeVYDuSDKdnT9jDTr1");
 
+/// Share of amount_in paid to host_fee_in by swap2 when a host fee account is passed (0.1%)
+pub const HOST_FEE_BPS: u64 = 10;
+
+/// Scale of Oracle prices: PRICE_SCALE = one token X per token Y
+pub const PRICE_SCALE: u128 = 1_000_000_000;
+
 #[program]
 pub mod mock_meteora_swap {
     use super::*;
@@ -65,9 +71,11 @@ pub mod mock_meteora_swap {
             ctx.accounts.token_y_mint.decimals,
         )?;
 
-        // Initialize oracle (mock: set initialized flag)
+        // Initialize oracle at the opening pool price
         let oracle = &mut ctx.accounts.oracle;
         oracle.initialized = true;
+        oracle.last_update_timestamp = Clock::get()?.unix_timestamp;
+        oracle.last_price_x_per_y = price_x_per_y(initial_token_x_amount, initial_token_y_amount)?;
 
         Ok(())
     }
@@ -87,72 +95,38 @@ pub mod mock_meteora_swap {
         Ok(())
     }
 
+    /// Makes every swap2 through the pool return a failure report of `fail_code` instead of
+    /// swapping (0 = swap normally), see report_failure
+    pub fn set_fail_mode(ctx: Context<SetFailMode>, fail_code: u32) -> Result<()> {
+        ctx.accounts.lb_pair.fail_code = fail_code;
+        Ok(())
+    }
+
+    /// Writes SwapAmounts to return data itself rather than returning them, as Anchor would
+    /// overwrite a failure report with the handler's return value
     pub fn swap2(
         ctx: Context<Swap2>,
         amount_in: u64,
         min_amount_out: u64,
         _remaining_accounts_info: RemainingAccountsInfo,
     ) -> Result<()> {
-        // Validate mints are owned by the provided token programs
-        require!(
-            ctx.accounts.token_x_mint.to_account_info().owner == &ctx.accounts.token_x_program.key(),
-            ErrorCode::InvalidTokenProgram
-        );
-        require!(
-            ctx.accounts.token_y_mint.to_account_info().owner == &ctx.accounts.token_y_program.key(),
-            ErrorCode::InvalidTokenProgram
-        );
+        if ctx.accounts.lb_pair.fail_code != 0 {
+            return report_failure(ctx.accounts.lb_pair.fail_code);
+        }
 
         // Validate input amounts
         require!(amount_in > 0, ErrorCode::ZeroAmount);
 
-        // Load lb_pair state
-        let lb_pair = &mut ctx.accounts.lb_pair;
+        let (swap_for_y, reserve_in, reserve_out) = swap_direction(ctx.accounts)?;
 
-        // Determine swap direction based on user_token_in mint
-        let swap_for_y = ctx.accounts.user_token_in.mint == ctx.accounts.token_x_mint.key();
-
-        // Set input/output reserves, vaults, and token programs
-        let (reserve_in, reserve_out, vault_in, vault_out, user_token_in, user_token_out, token_program_in, token_program_out, mint_in, mint_out) = if swap_for_y {
-            (
-                lb_pair.token_x_vault_amount,
-                lb_pair.token_y_vault_amount,
-                &ctx.accounts.reserve_x,
-                &ctx.accounts.reserve_y,
-                &ctx.accounts.user_token_in,
-                &ctx.accounts.user_token_out,
-                &ctx.accounts.token_x_program,
-                &ctx.accounts.token_y_program,
-                &ctx.accounts.token_x_mint,
-                &ctx.accounts.token_y_mint,
-            )
-        } else {
-            (
-                lb_pair.token_y_vault_amount,
-                lb_pair.token_x_vault_amount,
-                &ctx.accounts.reserve_y,
-                &ctx.accounts.reserve_x,
-                &ctx.accounts.user_token_in,
-                &ctx.accounts.user_token_out,
-                &ctx.accounts.token_y_program,
-                &ctx.accounts.token_x_program,
-                &ctx.accounts.token_y_mint,
-                &ctx.accounts.token_x_mint,
-            )
-        };
-
-        // Validate token mints
-        require!(
-            user_token_in.mint == mint_in.key(),
-            ErrorCode::InvalidMint
-        );
-        require!(
-            user_token_out.mint == mint_out.key(),
-            ErrorCode::InvalidMint
-        );
+        // The host fee is taken from the input before it reaches the pool
+        let host_fee = host_fee_amount(ctx.accounts, swap_for_y, amount_in)?;
+        let pool_amount_in = amount_in
+            .checked_sub(host_fee)
+            .ok_or(ErrorCode::ArithmeticOverflow)?;
 
         // Calculate output amount using constant product formula
-        let amount_out = calculate_swap_amount(amount_in, reserve_in, reserve_out)?;
+        let amount_out = calculate_swap_amount(pool_amount_in, reserve_in, reserve_out)?;
 
         // Check slippage protection
         require!(
@@ -160,72 +134,237 @@ pub mod mock_meteora_swap {
             ErrorCode::InsufficientOutputAmount
         );
 
-        // Update pool reserves
-        if swap_for_y {
-            lb_pair.token_x_vault_amount = lb_pair
-                .token_x_vault_amount
-                .checked_add(amount_in)
-                .ok_or(ErrorCode::ArithmeticOverflow)?;
-            lb_pair.token_y_vault_amount = lb_pair
-                .token_y_vault_amount
-                .checked_sub(amount_out)
-                .ok_or(ErrorCode::ArithmeticOverflow)?;
-        } else {
-            lb_pair.token_y_vault_amount = lb_pair
-                .token_y_vault_amount
-                .checked_add(amount_in)
-                .ok_or(ErrorCode::ArithmeticOverflow)?;
-            lb_pair.token_x_vault_amount = lb_pair
-                .token_x_vault_amount
-                .checked_sub(amount_out)
-                .ok_or(ErrorCode::ArithmeticOverflow)?;
-        }
+        let amounts =
+            settle_swap(ctx.accounts, ctx.bumps.lb_pair, swap_for_y, pool_amount_in, amount_out, host_fee)?;
+        anchor_lang::solana_program::program::set_return_data(&amounts.try_to_vec()?);
+        Ok(())
+    }
 
-        // Transfer input tokens from user to vault
+    pub fn swap_exact_out(
+        ctx: Context<Swap2>,
+        amount_out: u64,
+        max_in: u64,
+        _remaining_accounts_info: RemainingAccountsInfo,
+    ) -> Result<SwapAmounts> {
+        // Validate output amount
+        require!(amount_out > 0, ErrorCode::ZeroAmount);
+
+        let (swap_for_y, reserve_in, reserve_out) = swap_direction(ctx.accounts)?;
+
+        // Calculate required input using the reverse constant product formula
+        let amount_in = calculate_swap_amount_in(amount_out, reserve_in, reserve_out)?;
+
+        // Check slippage protection
+        require!(
+            amount_in <= max_in,
+            ErrorCode::AmountInAboveMaximum
+        );
+
+        // Host fees are only charged by swap2
+        settle_swap(ctx.accounts, ctx.bumps.lb_pair, swap_for_y, amount_in, amount_out, 0)
+    }
+
+    /// Writes the oracle's last_price_x_per_y to return data
+    pub fn get_price(ctx: Context<GetPrice>) -> Result<u64> {
+        Ok(ctx.accounts.oracle.last_price_x_per_y)
+    }
+}
+
+/// Realized swap amounts, written to return data by swap2 and swap_exact_out
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Debug, PartialEq, Eq)]
+pub struct SwapAmounts {
+    pub amount_in: u64,
+    pub amount_out: u64,
+    pub host_fee: u64,
+}
+
+/// Returns the host fee charged on amount_in, or 0 when host_fee_in is the program id (not passed)
+/// A passed host fee account must be a token account of the input mint
+fn host_fee_amount(accounts: &Swap2, swap_for_y: bool, amount_in: u64) -> Result<u64> {
+    if accounts.host_fee_in.key() == crate::ID {
+        return Ok(0);
+    }
+
+    let mint_in = if swap_for_y {
+        accounts.token_x_mint.key()
+    } else {
+        accounts.token_y_mint.key()
+    };
+    let host_fee_data = accounts.host_fee_in.try_borrow_data()?;
+    let host_fee_account = TokenAccount::try_deserialize(&mut host_fee_data.as_ref())?;
+    require!(host_fee_account.mint == mint_in, ErrorCode::InvalidMint);
+
+    let host_fee = (amount_in as u128)
+        .checked_mul(HOST_FEE_BPS as u128)
+        .ok_or(ErrorCode::ArithmeticOverflow)?
+        / 10_000;
+    Ok(host_fee as u64)
+}
+
+/// Validates token programs and mints, and returns (swap_for_y, reserve_in, reserve_out)
+fn swap_direction(accounts: &Swap2) -> Result<(bool, u64, u64)> {
+    // Validate mints are owned by the provided token programs
+    require!(
+        accounts.token_x_mint.to_account_info().owner == &accounts.token_x_program.key(),
+        ErrorCode::InvalidTokenProgram
+    );
+    require!(
+        accounts.token_y_mint.to_account_info().owner == &accounts.token_y_program.key(),
+        ErrorCode::InvalidTokenProgram
+    );
+
+    // Determine swap direction based on user_token_in mint
+    let swap_for_y = accounts.user_token_in.mint == accounts.token_x_mint.key();
+
+    let (reserve_in, reserve_out, mint_in, mint_out) = if swap_for_y {
+        (
+            accounts.lb_pair.token_x_vault_amount,
+            accounts.lb_pair.token_y_vault_amount,
+            &accounts.token_x_mint,
+            &accounts.token_y_mint,
+        )
+    } else {
+        (
+            accounts.lb_pair.token_y_vault_amount,
+            accounts.lb_pair.token_x_vault_amount,
+            &accounts.token_y_mint,
+            &accounts.token_x_mint,
+        )
+    };
+
+    // Validate token mints
+    require!(
+        accounts.user_token_in.mint == mint_in.key(),
+        ErrorCode::InvalidMint
+    );
+    require!(
+        accounts.user_token_out.mint == mint_out.key(),
+        ErrorCode::InvalidMint
+    );
+
+    Ok((swap_for_y, reserve_in, reserve_out))
+}
+
+/// Updates reserves, moves tokens, updates the oracle and returns the realized amounts
+/// amount_in is what enters the pool; host_fee is paid on top of it from user_token_in
+fn settle_swap(
+    accounts: &mut Swap2,
+    lb_pair_bump: u8,
+    swap_for_y: bool,
+    amount_in: u64,
+    amount_out: u64,
+    host_fee: u64,
+) -> Result<SwapAmounts> {
+    // Update pool reserves
+    let lb_pair = &mut accounts.lb_pair;
+    if swap_for_y {
+        lb_pair.token_x_vault_amount = lb_pair
+            .token_x_vault_amount
+            .checked_add(amount_in)
+            .ok_or(ErrorCode::ArithmeticOverflow)?;
+        lb_pair.token_y_vault_amount = lb_pair
+            .token_y_vault_amount
+            .checked_sub(amount_out)
+            .ok_or(ErrorCode::ArithmeticOverflow)?;
+    } else {
+        lb_pair.token_y_vault_amount = lb_pair
+            .token_y_vault_amount
+            .checked_add(amount_in)
+            .ok_or(ErrorCode::ArithmeticOverflow)?;
+        lb_pair.token_x_vault_amount = lb_pair
+            .token_x_vault_amount
+            .checked_sub(amount_out)
+            .ok_or(ErrorCode::ArithmeticOverflow)?;
+    }
+
+    // Set input/output vaults, token programs and mints
+    let (vault_in, vault_out, token_program_in, token_program_out, mint_in, mint_out) = if swap_for_y {
+        (
+            &accounts.reserve_x,
+            &accounts.reserve_y,
+            &accounts.token_x_program,
+            &accounts.token_y_program,
+            &accounts.token_x_mint,
+            &accounts.token_y_mint,
+        )
+    } else {
+        (
+            &accounts.reserve_y,
+            &accounts.reserve_x,
+            &accounts.token_y_program,
+            &accounts.token_x_program,
+            &accounts.token_y_mint,
+            &accounts.token_x_mint,
+        )
+    };
+
+    // Transfer input tokens from user to vault
+    transfer_checked(
+        CpiContext::new(
+            token_program_in.to_account_info(),
+            TransferChecked {
+                from: accounts.user_token_in.to_account_info(),
+                to: vault_in.to_account_info(),
+                authority: accounts.user.to_account_info(),
+                mint: mint_in.to_account_info(),
+            },
+        ),
+        amount_in,
+        mint_in.decimals,
+    )?;
+
+    // Transfer the host fee from user to the host fee account
+    if host_fee > 0 {
         transfer_checked(
             CpiContext::new(
                 token_program_in.to_account_info(),
                 TransferChecked {
-                    from: user_token_in.to_account_info(),
-                    to: vault_in.to_account_info(),
-                    authority: ctx.accounts.user.to_account_info(),
+                    from: accounts.user_token_in.to_account_info(),
+                    to: accounts.host_fee_in.to_account_info(),
+                    authority: accounts.user.to_account_info(),
                     mint: mint_in.to_account_info(),
                 },
             ),
-            amount_in,
+            host_fee,
             mint_in.decimals,
         )?;
-
-        // Transfer output tokens from vault to user using lb_pair as authority
-        let token_x_mint_key = ctx.accounts.token_x_mint.key();
-        let token_y_mint_key = ctx.accounts.token_y_mint.key();
-        let authority_seeds = &[
-            b"lb_pair".as_ref(),
-            token_x_mint_key.as_ref(),
-            token_y_mint_key.as_ref(),
-            &[ctx.bumps.lb_pair],
-        ];
-        transfer_checked(
-            CpiContext::new_with_signer(
-                token_program_out.to_account_info(),
-                TransferChecked {
-                    from: vault_out.to_account_info(),
-                    to: user_token_out.to_account_info(),
-                    authority: ctx.accounts.lb_pair.to_account_info(),
-                    mint: mint_out.to_account_info(),
-                },
-                &[authority_seeds],
-            ),
-            amount_out,
-            mint_out.decimals,
-        )?;
-
-        // Update oracle (mock: set timestamp)
-        let oracle = &mut ctx.accounts.oracle;
-        oracle.last_update_timestamp = Clock::get()?.unix_timestamp;
-
-        Ok(())
     }
+
+    // Transfer output tokens from vault to user using lb_pair as authority
+    let token_x_mint_key = accounts.token_x_mint.key();
+    let token_y_mint_key = accounts.token_y_mint.key();
+    let authority_seeds = &[
+        b"lb_pair".as_ref(),
+        token_x_mint_key.as_ref(),
+        token_y_mint_key.as_ref(),
+        &[lb_pair_bump],
+    ];
+    transfer_checked(
+        CpiContext::new_with_signer(
+            token_program_out.to_account_info(),
+            TransferChecked {
+                from: vault_out.to_account_info(),
+                to: accounts.user_token_out.to_account_info(),
+                authority: accounts.lb_pair.to_account_info(),
+                mint: mint_out.to_account_info(),
+            },
+            &[authority_seeds],
+        ),
+        amount_out,
+        mint_out.decimals,
+    )?;
+
+    // Update oracle with the pool price after the swap
+    let price = price_x_per_y(accounts.lb_pair.token_x_vault_amount, accounts.lb_pair.token_y_vault_amount)?;
+    accounts.oracle.record(price, Clock::get()?.unix_timestamp)?;
+
+    Ok(SwapAmounts {
+        amount_in: amount_in
+            .checked_add(host_fee)
+            .ok_or(ErrorCode::ArithmeticOverflow)?,
+        amount_out,
+        host_fee,
+    })
 }
 
 #[derive(Accounts)]
@@ -236,7 +375,7 @@ pub struct InitializePool<'info> {
     #[account(
         init,
         payer = user,
-        space = 8 + 32 + 32 + 8 + 8,
+        space = 8 + 32 + 32 + 8 + 8 + 4,
         seeds = [b"lb_pair", token_x_mint.key().as_ref(), token_y_mint.key().as_ref()],
         bump,
     )]
@@ -281,7 +420,7 @@ pub struct InitializePool<'info> {
     #[account(
         init,
         payer = user,
-        space = 8 + 1 + 8,
+        space = 8 + 1 + 8 + 8 + 16,
         seeds = [b"oracle", lb_pair.key().as_ref()],
         bump,
     )]
@@ -331,6 +470,13 @@ pub struct InitializeUserTokenAccounts<'info> {
     pub rent: Sysvar<'info, Rent>,
 }
 
+#[derive(Accounts)]
+pub struct SetFailMode<'info> {
+    pub user: Signer<'info>,
+    #[account(mut)]
+    pub lb_pair: Account<'info, LbPair>,
+}
+
 #[derive(Accounts)]
 pub struct Swap2<'info> {
     #[account(
@@ -375,10 +521,15 @@ pub struct Swap2<'info> {
     pub token_x_mint: InterfaceAccount<'info, Mint>,
     pub token_y_mint: InterfaceAccount<'info, Mint>,
 
-    #[account(mut)]
+    #[account(
+        mut,
+        seeds = [b"oracle", lb_pair.key().as_ref()],
+        bump,
+    )]
     pub oracle: Account<'info, Oracle>,
 
-    /// CHECK: Optional, validated in program logic if used
+    /// CHECK: Optional; pass the program id for none, otherwise a token account of the input mint
+    /// (validated in host_fee_amount)
     #[account(mut)]
     pub host_fee_in: UncheckedAccount<'info>,
 
@@ -403,6 +554,17 @@ pub struct Swap2<'info> {
     pub program: UncheckedAccount<'info>,
 }
 
+#[derive(Accounts)]
+pub struct GetPrice<'info> {
+    pub lb_pair: Account<'info, LbPair>,
+
+    #[account(
+        seeds = [b"oracle", lb_pair.key().as_ref()],
+        bump,
+    )]
+    pub oracle: Account<'info, Oracle>,
+}
+
 #[account]
 #[derive(Default)]
 pub struct LbPair {
@@ -410,6 +572,8 @@ pub struct LbPair {
     pub reserve_y: Pubkey,
     pub token_x_vault_amount: u64,
     pub token_y_vault_amount: u64,
+    /// Error code swap2 reports instead of swapping (0 = swap normally)
+    pub fail_code: u32,
 }
 
 #[account]
@@ -417,6 +581,28 @@ pub struct LbPair {
 pub struct Oracle {
     pub initialized: bool,
     pub last_update_timestamp: i64,
+    /// Token X per token Y after the last swap, scaled by PRICE_SCALE
+    pub last_price_x_per_y: u64,
+    /// Sum of each price times the seconds it was in force, for time-weighted averages
+    pub cumulative_price: u128,
+}
+
+impl Oracle {
+    /// Accumulates the previous price over the time since the last update, then records `price`
+    fn record(&mut self, price: u64, now: i64) -> Result<()> {
+        let elapsed = now.saturating_sub(self.last_update_timestamp).max(0) as u128;
+        self.cumulative_price = self
+            .cumulative_price
+            .checked_add(
+                (self.last_price_x_per_y as u128)
+                    .checked_mul(elapsed)
+                    .ok_or(ErrorCode::ArithmeticOverflow)?,
+            )
+            .ok_or(ErrorCode::ArithmeticOverflow)?;
+        self.last_price_x_per_y = price;
+        self.last_update_timestamp = now;
+        Ok(())
+    }
 }
 
 #[derive(AnchorSerialize, AnchorDeserialize, Clone, Default)]
@@ -441,6 +627,19 @@ impl Default for AccountsType {
     }
 }
 
+/// Prefix of a failure report, the same as flipper's adapter_errors::ADAPTER_FAILURE_TAG
+pub const FAILURE_REPORT_TAG: [u8; 8] = *b"dexfail\0";
+
+/// Leaves the swap undone and returns `fail_code` after FAILURE_REPORT_TAG as return data.
+/// A program that fails aborts its caller's whole transaction, so this is how the router's
+/// tests make a swap fail with a given code while the router still sees it.
+fn report_failure(fail_code: u32) -> Result<()> {
+    let mut report = FAILURE_REPORT_TAG.to_vec();
+    report.extend_from_slice(&fail_code.to_le_bytes());
+    anchor_lang::solana_program::program::set_return_data(&report);
+    Ok(())
+}
+
 #[error_code]
 pub enum ErrorCode {
     #[msg("Amount cannot be zero")]
@@ -459,6 +658,18 @@ pub enum ErrorCode {
     InvalidOwner,
     #[msg("Invalid token program for mint")]
     InvalidTokenProgram,
+    #[msg("Required input amount is above the maximum specified")]
+    AmountInAboveMaximum,
+}
+
+/// Pool price as token X per token Y, scaled by PRICE_SCALE
+fn price_x_per_y(token_x_amount: u64, token_y_amount: u64) -> Result<u64> {
+    require!(token_y_amount > 0, ErrorCode::ArithmeticOverflow);
+    let price = (token_x_amount as u128)
+        .checked_mul(PRICE_SCALE)
+        .ok_or(ErrorCode::ArithmeticOverflow)?
+        / token_y_amount as u128;
+    u64::try_from(price).map_err(|_| ErrorCode::ArithmeticOverflow.into())
 }
 
 fn calculate_swap_amount(amount_in: u64, reserve_in: u64, reserve_out: u64) -> Result<u64> {
@@ -487,4 +698,31 @@ fn calculate_swap_amount(amount_in: u64, reserve_in: u64, reserve_out: u64) -> R
         .ok_or(ErrorCode::ArithmeticOverflow)?;
 
     Ok(amount_out as u64)
-}
\ No newline at end of file
+}
+
+fn calculate_swap_amount_in(amount_out: u64, reserve_in: u64, reserve_out: u64) -> Result<u64> {
+    // Reverse constant product formula: amount_in = reserve_in * amount_out / (reserve_out - amount_out)
+    // Rounded up so the pool never gives out more than x * y = k allows
+    require!(reserve_in > 0 && reserve_out > 0, ErrorCode::ArithmeticOverflow);
+    require!(amount_out < reserve_out, ErrorCode::InsufficientOutputAmount);
+
+    let reserve_in = reserve_in as u128;
+    let reserve_out = reserve_out as u128;
+    let amount_out = amount_out as u128;
+
+    let numerator = reserve_in
+        .checked_mul(amount_out)
+        .ok_or(ErrorCode::ArithmeticOverflow)?;
+
+    let denominator = reserve_out
+        .checked_sub(amount_out)
+        .ok_or(ErrorCode::ArithmeticOverflow)?;
+
+    let amount_in = numerator
+        .checked_add(denominator - 1)
+        .ok_or(ErrorCode::ArithmeticOverflow)?
+        .checked_div(denominator)
+        .ok_or(ErrorCode::ArithmeticOverflow)?;
+
+    u64::try_from(amount_in).map_err(|_| ErrorCode::ArithmeticOverflow.into())
+}
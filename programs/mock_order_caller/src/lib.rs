@@ -0,0 +1,204 @@
+use anchor_lang::prelude::*;
+use anchor_spl::token_interface::{Mint, TokenAccount, TokenInterface};
+use flipper::cpi::accounts::{CancelLimitOrder, CreateLimitOrder, InitLimitOrder};
+use flipper::program::Flipper;
+use flipper::instructions::TriggerType;
+
+declare_id!("HsC1z117opdvdNPU5efKdFdZ6JuAVdQoKKacFBKhbBvx");
+
+/// Seed of the PDA that owns the caller's tokens and creates orders on its behalf
+pub const ORDER_CREATOR_SEED: &[u8] = b"order_creator";
+
+/// Mock integrator placing flipper limit orders via CPI with a program PDA as the creator
+#[program]
+pub mod mock_order_caller {
+    use super::*;
+
+    /// Initializes and creates a take-profit order owned by the order_creator PDA.
+    /// The payer funds rent and the creation fee, the PDA signs as creator.
+    pub fn create_order(
+        ctx: Context<CreateOrder>,
+        nonce: u64,
+        input_amount: u64,
+        min_output_amount: u64,
+        trigger_price_bps: u32,
+        expiry: i64,
+        slippage_bps: u16,
+    ) -> Result<()> {
+        let bump = [ctx.bumps.order_creator];
+        let signer_seeds: &[&[&[u8]]] = &[&[ORDER_CREATOR_SEED, &bump]];
+        let accounts = &ctx.accounts;
+
+        flipper::cpi::init_limit_order(
+            CpiContext::new_with_signer(
+                accounts.flipper_program.to_account_info(),
+                InitLimitOrder {
+                    vault_authority: accounts.vault_authority.to_account_info(),
+                    limit_order: accounts.limit_order.to_account_info(),
+                    order_counter: accounts.order_counter.to_account_info(),
+                    order_receipt: None,
+                    input_vault: accounts.input_vault.to_account_info(),
+                    vault_rent_payer: accounts.vault_rent_payer.to_account_info(),
+                    adapter_registry: accounts.adapter_registry.to_account_info(),
+                    payer_stats: accounts.payer_stats.to_account_info(),
+                    input_mint: accounts.input_mint.to_account_info(),
+                    input_token_program: accounts.input_token_program.to_account_info(),
+                    creator: accounts.order_creator.to_account_info(),
+                    payer: accounts.payer.to_account_info(),
+                    system_program: accounts.system_program.to_account_info(),
+                    rent: accounts.rent.to_account_info(),
+                },
+                signer_seeds,
+            ),
+            nonce,
+            0,
+            false,
+        )?;
+
+        flipper::cpi::create_limit_order(
+            CpiContext::new_with_signer(
+                accounts.flipper_program.to_account_info(),
+                CreateLimitOrder {
+                    vault_authority: accounts.vault_authority.to_account_info(),
+                    adapter_registry: accounts.adapter_registry.to_account_info(),
+                    limit_order: accounts.limit_order.to_account_info(),
+                    input_vault: accounts.input_vault.to_account_info(),
+                    user_input_token_account: accounts.creator_input_account.to_account_info(),
+                    user_destination_token_account: accounts.creator_destination_account.to_account_info(),
+                    input_mint: accounts.input_mint.to_account_info(),
+                    output_mint: accounts.output_mint.to_account_info(),
+                    input_token_program: accounts.input_token_program.to_account_info(),
+                    output_token_program: accounts.output_token_program.to_account_info(),
+                    treasury: accounts.treasury.as_ref().map(|treasury| treasury.to_account_info()),
+                    instructions_sysvar: None,
+                    protocol_config: accounts.protocol_config.to_account_info(),
+                    creator: accounts.order_creator.to_account_info(),
+                    payer: accounts.payer.to_account_info(),
+                    system_program: accounts.system_program.to_account_info(),
+                    event_authority: accounts.flipper_event_authority.to_account_info(),
+                    program: accounts.flipper_program.to_account_info(),
+                },
+                signer_seeds,
+            ),
+            nonce,
+            input_amount,
+            min_output_amount,
+            trigger_price_bps,
+            TriggerType::TakeProfit,
+            expiry,
+            slippage_bps,
+            false,
+            None,
+            None,
+            0,
+        )
+    }
+
+    /// Cancels an order owned by the order_creator PDA, refunding its tokens to creator_input_account.
+    /// The vault rent goes back to rent_payer, the payer that funded the vault.
+    pub fn cancel_order(ctx: Context<CancelOrder>) -> Result<()> {
+        let bump = [ctx.bumps.order_creator];
+        let signer_seeds: &[&[&[u8]]] = &[&[ORDER_CREATOR_SEED, &bump]];
+        let accounts = &ctx.accounts;
+
+        flipper::cpi::cancel_limit_order(CpiContext::new_with_signer(
+            accounts.flipper_program.to_account_info(),
+            CancelLimitOrder {
+                vault_authority: accounts.vault_authority.to_account_info(),
+                limit_order: accounts.limit_order.to_account_info(),
+                input_vault: accounts.input_vault.to_account_info(),
+                user_input_token_account: accounts.creator_input_account.to_account_info(),
+                input_mint: accounts.input_mint.to_account_info(),
+                input_token_program: accounts.input_token_program.to_account_info(),
+                order_receipt: accounts.order_receipt.to_account_info(),
+                vault_rent_payer: accounts.vault_rent_payer.to_account_info(),
+                rent_payer: Some(accounts.rent_payer.to_account_info()),
+                rent_payer_stats: accounts.rent_payer_stats.as_ref().map(|stats| stats.to_account_info()),
+                creator: accounts.order_creator.to_account_info(),
+                event_authority: accounts.flipper_event_authority.to_account_info(),
+                program: accounts.flipper_program.to_account_info(),
+            },
+            signer_seeds,
+        ))
+    }
+}
+
+#[derive(Accounts)]
+pub struct CreateOrder<'info> {
+    /// CHECK: Signing PDA of this program; flipper records it as the order creator
+    #[account(mut, seeds = [ORDER_CREATOR_SEED], bump)]
+    pub order_creator: UncheckedAccount<'info>,
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    /// CHECK: Validated by flipper
+    pub vault_authority: UncheckedAccount<'info>,
+    /// CHECK: Validated by flipper
+    pub adapter_registry: UncheckedAccount<'info>,
+    /// CHECK: Created by flipper init_limit_order
+    #[account(mut)]
+    pub limit_order: UncheckedAccount<'info>,
+    /// CHECK: Creator's order counter, created or advanced by flipper init_limit_order
+    #[account(mut)]
+    pub order_counter: UncheckedAccount<'info>,
+    /// CHECK: Created by flipper init_limit_order
+    #[account(mut)]
+    pub input_vault: UncheckedAccount<'info>,
+    /// CHECK: Created by flipper init_limit_order, recording the payer as the vault's sponsor
+    #[account(mut)]
+    pub vault_rent_payer: UncheckedAccount<'info>,
+    /// CHECK: Payer's flipper OperatorStats PDA, counting the vault when the payer is an operator
+    #[account(mut)]
+    pub payer_stats: UncheckedAccount<'info>,
+    #[account(mut, token::authority = order_creator)]
+    pub creator_input_account: InterfaceAccount<'info, TokenAccount>,
+    #[account(token::authority = order_creator)]
+    pub creator_destination_account: InterfaceAccount<'info, TokenAccount>,
+    pub input_mint: InterfaceAccount<'info, Mint>,
+    pub output_mint: InterfaceAccount<'info, Mint>,
+    pub input_token_program: Interface<'info, TokenInterface>,
+    pub output_token_program: Interface<'info, TokenInterface>,
+    /// CHECK: Validated by flipper against the registry treasury
+    #[account(mut)]
+    pub treasury: Option<UncheckedAccount<'info>>,
+    /// CHECK: Flipper ProtocolConfig PDA, validated by flipper
+    pub protocol_config: UncheckedAccount<'info>,
+    /// CHECK: Flipper event authority PDA, validated by flipper
+    pub flipper_event_authority: UncheckedAccount<'info>,
+    pub flipper_program: Program<'info, Flipper>,
+    pub system_program: Program<'info, System>,
+    pub rent: Sysvar<'info, Rent>,
+}
+
+#[derive(Accounts)]
+pub struct CancelOrder<'info> {
+    /// CHECK: Signing PDA of this program; receives the order account rent back
+    #[account(mut, seeds = [ORDER_CREATOR_SEED], bump)]
+    pub order_creator: UncheckedAccount<'info>,
+    /// CHECK: Validated by flipper
+    pub vault_authority: UncheckedAccount<'info>,
+    /// CHECK: Validated by flipper
+    #[account(mut)]
+    pub limit_order: UncheckedAccount<'info>,
+    /// CHECK: Validated by flipper
+    #[account(mut)]
+    pub input_vault: UncheckedAccount<'info>,
+    /// CHECK: Flipper ["receipt", limit_order] PDA, validated by flipper
+    #[account(mut)]
+    pub order_receipt: UncheckedAccount<'info>,
+    /// CHECK: Validated by flipper, closed with the vault
+    #[account(mut)]
+    pub vault_rent_payer: UncheckedAccount<'info>,
+    /// CHECK: Validated by flipper against the recorded payer; receives the vault rent
+    #[account(mut)]
+    pub rent_payer: UncheckedAccount<'info>,
+    /// CHECK: Payer's flipper OperatorStats, required by flipper when an operator sponsored the vault
+    #[account(mut)]
+    pub rent_payer_stats: Option<UncheckedAccount<'info>>,
+    #[account(mut, token::authority = order_creator)]
+    pub creator_input_account: InterfaceAccount<'info, TokenAccount>,
+    pub input_mint: InterfaceAccount<'info, Mint>,
+    pub input_token_program: Interface<'info, TokenInterface>,
+    /// CHECK: Flipper event authority PDA, validated by flipper
+    pub flipper_event_authority: UncheckedAccount<'info>,
+    pub flipper_program: Program<'info, Flipper>,
+}
@@ -95,6 +95,13 @@ pub mod mock_raydium {
         Ok(())
     }
 
+    /// Makes every swap through the pool return a failure report of `fail_code` instead of
+    /// swapping (0 = swap normally), see report_failure
+    pub fn set_fail_mode(ctx: Context<SetFailMode>, fail_code: u32) -> Result<()> {
+        ctx.accounts.pool_state.fail_code = fail_code;
+        Ok(())
+    }
+
     pub fn swap_base_input(
         ctx: Context<Swap>,
         amount_in: u64,
@@ -102,6 +109,10 @@ pub mod mock_raydium {
     ) -> Result<()> {
         msg!("call mock program");
 
+        if ctx.accounts.pool_state.fail_code != 0 {
+            return report_failure(ctx.accounts.pool_state.fail_code);
+        }
+
         require!(
             ctx.accounts.input_token_mint.to_account_info().owner == &ctx.accounts.input_token_program.key(),
             ErrorCode::InvalidTokenProgram
@@ -181,10 +192,17 @@ pub mod mock_raydium {
         Ok(())
     }
 
+    pub fn create_amm_config(ctx: Context<CreateAmmConfig>, index: u16) -> Result<()> {
+        let amm_config = &mut ctx.accounts.amm_config;
+        amm_config.index = index;
+        amm_config.bump = ctx.bumps.amm_config;
+        Ok(())
+    }
+
     pub fn migrate_pool_state(ctx: Context<MigratePoolState>) -> Result<()> {
         let pool_state = &mut ctx.accounts.pool_state;
         let account_info = pool_state.to_account_info();
-        let new_space = 8 + 32 + 32 + 8 + 8 + 1 + 8; // New size: 97 bytes (discriminator + token_a_vault + token_b_vault + token_a_vault_amount + token_b_vault_amount + is_custom_price_mode + mock_price)
+        let new_space = 8 + 32 + 32 + 8 + 8 + 1 + 8 + 4; // New size: 101 bytes (discriminator + token_a_vault + token_b_vault + token_a_vault_amount + token_b_vault_amount + is_custom_price_mode + mock_price + fail_code)
         let current_space = account_info.data.borrow().len();
 
         if current_space < new_space {
@@ -215,6 +233,7 @@ pub mod mock_raydium {
         // Initialize new fields
         pool_state.is_custom_price_mode = false;
         pool_state.mock_price = 0;
+        pool_state.fail_code = 0;
 
         Ok(())
     }
@@ -227,7 +246,7 @@ pub struct InitializePool<'info> {
     #[account(
         init,
         payer = user,
-        space = 8 + 32 + 32 + 8 + 8 + 1 + 8, // Added space for is_custom_price_mode (1 byte) and mock_price (8 bytes)
+        space = 8 + 32 + 32 + 8 + 8 + 1 + 8 + 4, // Added space for is_custom_price_mode (1 byte), mock_price (8 bytes) and fail_code (4 bytes)
         seeds = [b"pool_state", token_a_mint.key().as_ref(), token_b_mint.key().as_ref()],
         bump,
     )]
@@ -308,6 +327,22 @@ pub struct InitializeUserTokenAccounts<'info> {
     pub rent: Sysvar<'info, Rent>,
 }
 
+#[derive(Accounts)]
+#[instruction(index: u16)]
+pub struct CreateAmmConfig<'info> {
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    #[account(
+        init,
+        payer = payer,
+        space = 8 + 2 + 1,
+        seeds = [b"amm_config", index.to_be_bytes().as_ref()],
+        bump,
+    )]
+    pub amm_config: Account<'info, AmmConfig>,
+    pub system_program: Program<'info, System>,
+}
+
 #[derive(Accounts)]
 pub struct SetMode<'info> {
     #[account(mut)]
@@ -336,6 +371,13 @@ pub struct SetMockPrice<'info> {
     pub token_b_mint: InterfaceAccount<'info, Mint>,
 }
 
+#[derive(Accounts)]
+pub struct SetFailMode<'info> {
+    pub user: Signer<'info>,
+    #[account(mut)]
+    pub pool_state: Account<'info, PoolState>,
+}
+
 #[derive(Accounts)]
 pub struct Swap<'info> {
     // (Unchanged from original)
@@ -386,6 +428,13 @@ pub struct PoolState {
     pub token_b_vault_amount: u64,
     pub is_custom_price_mode: bool, // New: Indicates if custom price mode is enabled
     pub mock_price: u64,            // New: Custom price (tokens_out per 1 token_in, scaled by decimals)
+    pub fail_code: u32,             // Error code swaps report instead of swapping (0 = swap normally)
+}
+
+#[account]
+pub struct AmmConfig {
+    pub index: u16,
+    pub bump: u8,
 }
 
 #[error_code]
@@ -402,6 +451,19 @@ pub enum ErrorCode {
     MockPriceNotSet,
 }
 
+/// Prefix of a failure report, the same as flipper's adapter_errors::ADAPTER_FAILURE_TAG
+pub const FAILURE_REPORT_TAG: [u8; 8] = *b"dexfail\0";
+
+/// Leaves the swap undone and returns `fail_code` after FAILURE_REPORT_TAG as return data.
+/// A program that fails aborts its caller's whole transaction, so this is how the router's
+/// tests make a swap fail with a given code while the router still sees it.
+fn report_failure(fail_code: u32) -> Result<()> {
+    let mut report = FAILURE_REPORT_TAG.to_vec();
+    report.extend_from_slice(&fail_code.to_le_bytes());
+    anchor_lang::solana_program::program::set_return_data(&report);
+    Ok(())
+}
+
 fn calculate_swap_amount(amount_in: u64, reserve_in: u64, reserve_out: u64) -> Result<u64> {
     let reserve_in = reserve_in as u128;
     let reserve_out = reserve_out as u128;
@@ -91,6 +91,13 @@ pub mod mock_whirlpool_swap {
         Ok(())
     }
 
+    /// Makes every swap through the pool return a failure report of `fail_code` instead of
+    /// swapping (0 = swap normally), see report_failure
+    pub fn set_fail_mode(ctx: Context<SetFailMode>, fail_code: u32) -> Result<()> {
+        ctx.accounts.whirlpool.fail_code = fail_code;
+        Ok(())
+    }
+
     pub fn swap_v2(
         ctx: Context<SwapV2>,
         amount: u64,
@@ -100,6 +107,10 @@ pub mod mock_whirlpool_swap {
         a_to_b: bool,
         _remaining_accounts_info: Option<RemainingAccountsInfo>,
     ) -> Result<()> {
+        if ctx.accounts.whirlpool.fail_code != 0 {
+            return report_failure(ctx.accounts.whirlpool.fail_code);
+        }
+        require!(amount > 0, ErrorCode::ZeroAmount);
 
         require!(
             ctx.accounts.token_mint_a.to_account_info().owner == &ctx.accounts.token_program_a.key(),
@@ -126,6 +137,7 @@ pub mod mock_whirlpool_swap {
                     whirlpool.token_vault_a_amount,
                 )?
             };
+            // Exact in: threshold is the minimum output, 0 means no minimum
             require!(calculated_out >= other_amount_threshold, ErrorCode::AmountOutBelowMinimum);
             (amount, calculated_out)
         } else {
@@ -142,6 +154,7 @@ pub mod mock_whirlpool_swap {
                     whirlpool.token_vault_a_amount,
                 )?
             };
+            // Exact out: threshold is the maximum input, u64::MAX means no maximum
             require!(calculated_in <= other_amount_threshold, ErrorCode::AmountInAboveMaximum);
             (calculated_in, amount)
         };
@@ -241,6 +254,112 @@ pub mod mock_whirlpool_swap {
 
         Ok(())
     }
+
+    pub fn two_hop_swap_v2(
+        ctx: Context<TwoHopSwapV2>,
+        amount: u64,
+        other_amount_threshold: u64,
+        amount_specified_is_input: bool,
+        a_to_b_one: bool,
+        a_to_b_two: bool,
+        _sqrt_price_limit_one: u128,
+        _sqrt_price_limit_two: u128,
+        _remaining_accounts_info: Option<RemainingAccountsInfo>,
+    ) -> Result<()> {
+        // The first pool in fail mode reports its code
+        for whirlpool in [&ctx.accounts.whirlpool_one, &ctx.accounts.whirlpool_two] {
+            if whirlpool.fail_code != 0 {
+                return report_failure(whirlpool.fail_code);
+            }
+        }
+        require!(amount > 0, ErrorCode::ZeroAmount);
+        // Only exact-in is chained; the router never asks for exact-out two-hop swaps
+        require!(amount_specified_is_input, ErrorCode::ExactOutNotSupported);
+        require!(
+            ctx.accounts.whirlpool_one.key() != ctx.accounts.whirlpool_two.key(),
+            ErrorCode::DuplicateTwoHopPool
+        );
+
+        // Each pool must hold the mints of its hop, in the hop's direction
+        let whirlpool_one = &ctx.accounts.whirlpool_one;
+        let whirlpool_two = &ctx.accounts.whirlpool_two;
+        require!(
+            hop_mints(whirlpool_one, a_to_b_one)
+                == (ctx.accounts.token_mint_input.key(), ctx.accounts.token_mint_intermediate.key()),
+            ErrorCode::InvalidIntermediaryMint
+        );
+        require!(
+            hop_mints(whirlpool_two, a_to_b_two)
+                == (ctx.accounts.token_mint_intermediate.key(), ctx.accounts.token_mint_output.key()),
+            ErrorCode::InvalidIntermediaryMint
+        );
+        require!(
+            hop_vaults(whirlpool_one, a_to_b_one)
+                == (ctx.accounts.token_vault_one_input.key(), ctx.accounts.token_vault_one_intermediate.key()),
+            ErrorCode::InvalidVault
+        );
+        require!(
+            hop_vaults(whirlpool_two, a_to_b_two)
+                == (ctx.accounts.token_vault_two_intermediate.key(), ctx.accounts.token_vault_two_output.key()),
+            ErrorCode::InvalidVault
+        );
+
+        let intermediate_amount = apply_hop(&mut ctx.accounts.whirlpool_one, amount, a_to_b_one)?;
+        let amount_out = apply_hop(&mut ctx.accounts.whirlpool_two, intermediate_amount, a_to_b_two)?;
+        // Exact in: threshold is the minimum output, 0 means no minimum
+        require!(amount_out >= other_amount_threshold, ErrorCode::AmountOutBelowMinimum);
+
+        transfer_checked(
+            CpiContext::new(
+                ctx.accounts.token_program_input.to_account_info(),
+                TransferChecked {
+                    from: ctx.accounts.token_owner_account_input.to_account_info(),
+                    to: ctx.accounts.token_vault_one_input.to_account_info(),
+                    authority: ctx.accounts.token_authority.to_account_info(),
+                    mint: ctx.accounts.token_mint_input.to_account_info(),
+                },
+            ),
+            amount,
+            ctx.accounts.token_mint_input.decimals,
+        )?;
+
+        // The intermediate token moves straight from pool one's vault to pool two's
+        let (mint_a_one, mint_b_one) = (ctx.accounts.whirlpool_one.token_mint_a, ctx.accounts.whirlpool_one.token_mint_b);
+        let (_, bump_one) = Pubkey::find_program_address(&[b"whirlpool", mint_a_one.as_ref(), mint_b_one.as_ref()], ctx.program_id);
+        transfer_checked(
+            CpiContext::new_with_signer(
+                ctx.accounts.token_program_intermediate.to_account_info(),
+                TransferChecked {
+                    from: ctx.accounts.token_vault_one_intermediate.to_account_info(),
+                    to: ctx.accounts.token_vault_two_intermediate.to_account_info(),
+                    authority: ctx.accounts.whirlpool_one.to_account_info(),
+                    mint: ctx.accounts.token_mint_intermediate.to_account_info(),
+                },
+                &[&[b"whirlpool".as_ref(), mint_a_one.as_ref(), mint_b_one.as_ref(), &[bump_one]]],
+            ),
+            intermediate_amount,
+            ctx.accounts.token_mint_intermediate.decimals,
+        )?;
+
+        let (mint_a_two, mint_b_two) = (ctx.accounts.whirlpool_two.token_mint_a, ctx.accounts.whirlpool_two.token_mint_b);
+        let (_, bump_two) = Pubkey::find_program_address(&[b"whirlpool", mint_a_two.as_ref(), mint_b_two.as_ref()], ctx.program_id);
+        transfer_checked(
+            CpiContext::new_with_signer(
+                ctx.accounts.token_program_output.to_account_info(),
+                TransferChecked {
+                    from: ctx.accounts.token_vault_two_output.to_account_info(),
+                    to: ctx.accounts.token_owner_account_output.to_account_info(),
+                    authority: ctx.accounts.whirlpool_two.to_account_info(),
+                    mint: ctx.accounts.token_mint_output.to_account_info(),
+                },
+                &[&[b"whirlpool".as_ref(), mint_a_two.as_ref(), mint_b_two.as_ref(), &[bump_two]]],
+            ),
+            amount_out,
+            ctx.accounts.token_mint_output.decimals,
+        )?;
+
+        Ok(())
+    }
 }
 
 #[derive(AnchorSerialize, AnchorDeserialize, Clone)]
@@ -350,6 +469,13 @@ pub struct InitializeSupplementalTickArray<'info> {
     pub system_program: Program<'info, System>,
 }
 
+#[derive(Accounts)]
+pub struct SetFailMode<'info> {
+    pub user: Signer<'info>,
+    #[account(mut)]
+    pub whirlpool: Account<'info, Whirlpool>,
+}
+
 #[derive(Accounts)]
 pub struct SwapV2<'info> {
     pub token_program_a: Interface<'info, TokenInterface>,
@@ -408,6 +534,60 @@ pub struct SwapV2<'info> {
 
 }
 
+#[derive(Accounts)]
+pub struct TwoHopSwapV2<'info> {
+    #[account(mut)]
+    pub whirlpool_one: Box<Account<'info, Whirlpool>>,
+    #[account(mut)]
+    pub whirlpool_two: Box<Account<'info, Whirlpool>>,
+
+    pub token_mint_input: Box<InterfaceAccount<'info, Mint>>,
+    pub token_mint_intermediate: Box<InterfaceAccount<'info, Mint>>,
+    pub token_mint_output: Box<InterfaceAccount<'info, Mint>>,
+
+    pub token_program_input: Interface<'info, TokenInterface>,
+    pub token_program_intermediate: Interface<'info, TokenInterface>,
+    pub token_program_output: Interface<'info, TokenInterface>,
+
+    #[account(mut)]
+    pub token_owner_account_input: Box<InterfaceAccount<'info, TokenAccount>>,
+    #[account(mut)]
+    pub token_vault_one_input: Box<InterfaceAccount<'info, TokenAccount>>,
+    #[account(mut)]
+    pub token_vault_one_intermediate: Box<InterfaceAccount<'info, TokenAccount>>,
+    #[account(mut)]
+    pub token_vault_two_intermediate: Box<InterfaceAccount<'info, TokenAccount>>,
+    #[account(mut)]
+    pub token_vault_two_output: Box<InterfaceAccount<'info, TokenAccount>>,
+    #[account(mut)]
+    pub token_owner_account_output: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    pub token_authority: Signer<'info>,
+
+    #[account(mut, constraint = tick_array_one_0.whirlpool == whirlpool_one.key())]
+    pub tick_array_one_0: Box<Account<'info, TickArray>>,
+    #[account(mut, constraint = tick_array_one_1.whirlpool == whirlpool_one.key())]
+    pub tick_array_one_1: Box<Account<'info, TickArray>>,
+    #[account(mut, constraint = tick_array_one_2.whirlpool == whirlpool_one.key())]
+    pub tick_array_one_2: Box<Account<'info, TickArray>>,
+    #[account(mut, constraint = tick_array_two_0.whirlpool == whirlpool_two.key())]
+    pub tick_array_two_0: Box<Account<'info, TickArray>>,
+    #[account(mut, constraint = tick_array_two_1.whirlpool == whirlpool_two.key())]
+    pub tick_array_two_1: Box<Account<'info, TickArray>>,
+    #[account(mut, constraint = tick_array_two_2.whirlpool == whirlpool_two.key())]
+    pub tick_array_two_2: Box<Account<'info, TickArray>>,
+
+    /// CHECK: Oracle account of whirlpool_one (optional)
+    #[account(mut)]
+    pub oracle_one: UncheckedAccount<'info>,
+    /// CHECK: Oracle account of whirlpool_two (optional)
+    #[account(mut)]
+    pub oracle_two: UncheckedAccount<'info>,
+
+    /// CHECK: Memo program (optional)
+    pub memo_program: UncheckedAccount<'info>,
+}
+
 #[account]
 #[derive(Default)]
 pub struct Whirlpool {
@@ -420,6 +600,8 @@ pub struct Whirlpool {
     pub sqrt_price: u128,
     pub liquidity: u128,
     pub tick_current_index: i32,
+    /// Error code swaps report instead of swapping (0 = swap normally)
+    pub fail_code: u32,
 }
 
 #[account]
@@ -429,6 +611,19 @@ pub struct TickArray {
     pub start_tick_index: i32,
 }
 
+/// Prefix of a failure report, the same as flipper's adapter_errors::ADAPTER_FAILURE_TAG
+pub const FAILURE_REPORT_TAG: [u8; 8] = *b"dexfail\0";
+
+/// Leaves the swap undone and returns `fail_code` after FAILURE_REPORT_TAG as return data.
+/// A program that fails aborts its caller's whole transaction, so this is how the router's
+/// tests make a swap fail with a given code while the router still sees it.
+fn report_failure(fail_code: u32) -> Result<()> {
+    let mut report = FAILURE_REPORT_TAG.to_vec();
+    report.extend_from_slice(&fail_code.to_le_bytes());
+    anchor_lang::solana_program::program::set_return_data(&report);
+    Ok(())
+}
+
 #[error_code]
 pub enum ErrorCode {
     #[msg("Amount cannot be zero")]
@@ -441,6 +636,45 @@ pub enum ErrorCode {
     ArithmeticOverflow,
     #[msg("Invalid token program for mint")]
     InvalidTokenProgram,
+    #[msg("Only exact-in swaps are supported")]
+    ExactOutNotSupported,
+    #[msg("Two-hop swap uses the same pool twice")]
+    DuplicateTwoHopPool,
+    #[msg("Pool mints do not chain through the intermediate mint")]
+    InvalidIntermediaryMint,
+    #[msg("Vault does not belong to the pool")]
+    InvalidVault,
+}
+
+// (from, to) mints of a swap through `whirlpool` in direction `a_to_b`
+fn hop_mints(whirlpool: &Whirlpool, a_to_b: bool) -> (Pubkey, Pubkey) {
+    if a_to_b {
+        (whirlpool.token_mint_a, whirlpool.token_mint_b)
+    } else {
+        (whirlpool.token_mint_b, whirlpool.token_mint_a)
+    }
+}
+
+// (from, to) vaults of a swap through `whirlpool` in direction `a_to_b`
+fn hop_vaults(whirlpool: &Whirlpool, a_to_b: bool) -> (Pubkey, Pubkey) {
+    if a_to_b {
+        (whirlpool.token_vault_a, whirlpool.token_vault_b)
+    } else {
+        (whirlpool.token_vault_b, whirlpool.token_vault_a)
+    }
+}
+
+// Swaps `amount_in` through `whirlpool`, updating its recorded reserves, and returns the output
+fn apply_hop(whirlpool: &mut Whirlpool, amount_in: u64, a_to_b: bool) -> Result<u64> {
+    let (reserve_in, reserve_out) = if a_to_b {
+        (&mut whirlpool.token_vault_a_amount, &mut whirlpool.token_vault_b_amount)
+    } else {
+        (&mut whirlpool.token_vault_b_amount, &mut whirlpool.token_vault_a_amount)
+    };
+    let amount_out = calculate_swap_amount(amount_in, *reserve_in, *reserve_out)?;
+    *reserve_in = reserve_in.checked_add(amount_in).ok_or(ErrorCode::ArithmeticOverflow)?;
+    *reserve_out = reserve_out.checked_sub(amount_out).ok_or(ErrorCode::ArithmeticOverflow)?;
+    Ok(amount_out)
 }
 
 fn calculate_swap_amount(amount_in: u64, reserve_in: u64, reserve_out: u64) -> Result<u64> {
@@ -153,6 +153,7 @@ async function testCreateLimitOrder() {
         inputMint: sourceMint,
         inputTokenProgram: TOKEN_PROGRAM_ID,
         creator: wallet.publicKey,
+        payer: wallet.publicKey,
         systemProgram: SystemProgram.programId,
         rent: anchor.web3.SYSVAR_RENT_PUBKEY,
       })
@@ -185,6 +186,7 @@ async function testCreateLimitOrder() {
         inputTokenProgram: TOKEN_PROGRAM_ID,
         outputTokenProgram: TOKEN_PROGRAM_ID,
         creator: wallet.publicKey,
+        payer: wallet.publicKey,
         systemProgram: SystemProgram.programId,
       })
       .signers([wallet.payer])
@@ -380,6 +382,7 @@ async function testExecuteLimitOrder() {
         inputMint: sourceMint,
         inputTokenProgram: TOKEN_PROGRAM_ID,
         creator: wallet.publicKey,
+        payer: wallet.publicKey,
         systemProgram: SystemProgram.programId,
         rent: anchor.web3.SYSVAR_RENT_PUBKEY,
       })
@@ -408,6 +411,7 @@ async function testExecuteLimitOrder() {
         inputTokenProgram: TOKEN_PROGRAM_ID,
         outputTokenProgram: TOKEN_PROGRAM_ID,
         creator: wallet.publicKey,
+        payer: wallet.publicKey,
         systemProgram: SystemProgram.programId,
       })
       .signers([wallet.payer])
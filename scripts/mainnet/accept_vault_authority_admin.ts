@@ -0,0 +1,111 @@
+import * as anchor from "@coral-xyz/anchor";
+import { Program, AnchorProvider } from "@coral-xyz/anchor";
+import { Keypair, PublicKey, Connection } from "@solana/web3.js";
+import FLIPPER_IDL from "../../target/idl/flipper.json";
+import fs from "fs";
+
+/**
+ * Script to accept a Vault Authority Admin proposal on Mainnet
+ *
+ * Completes the rotation started by change_vault_authority_admin.ts.
+ * Must be signed by the proposed admin; the previous admin loses its powers here.
+ *
+ * Usage:
+ * NEW_ADMIN_KEYPAIR=<path_to_new_admin_keypair> ts-node scripts/mainnet/accept_vault_authority_admin.ts
+ */
+
+// Function to load the proposed admin's keypair
+const loadKeypair = (): Keypair => {
+  const keypairPath = process.env.NEW_ADMIN_KEYPAIR;
+  if (keypairPath && fs.existsSync(keypairPath)) {
+    const secretKey = JSON.parse(fs.readFileSync(keypairPath, "utf8"));
+    return Keypair.fromSecretKey(Uint8Array.from(secretKey));
+  }
+  throw new Error("NEW_ADMIN_KEYPAIR must point to the proposed admin's keypair file");
+};
+
+// Configure connection to Solana Mainnet
+const RPC_ENDPOINT =
+  process.env.RPC_ENDPOINT || "https://api.mainnet-beta.solana.com";
+const connection = new Connection(RPC_ENDPOINT, "confirmed");
+
+// Create wallet and provider for Anchor
+const wallet = new anchor.Wallet(loadKeypair());
+const provider = new AnchorProvider(connection, wallet, {
+  commitment: "confirmed",
+});
+anchor.setProvider(provider);
+
+// Load program
+const flipperProgram = new Program(FLIPPER_IDL, provider);
+
+async function acceptVaultAuthorityAdmin() {
+  console.log("🔄 Accepting Vault Authority Admin on Mainnet...\n");
+
+  const [vaultAuthorityPda] = PublicKey.findProgramAddressSync(
+    [Buffer.from("vault_authority")],
+    flipperProgram.programId
+  );
+  const [adminProposalPda] = PublicKey.findProgramAddressSync(
+    [Buffer.from("vault_admin_proposal")],
+    flipperProgram.programId
+  );
+
+  console.log("📍 Configuration:");
+  console.log("   RPC Endpoint:", RPC_ENDPOINT);
+  console.log("   New Admin (wallet):", wallet.publicKey.toBase58());
+  console.log("   Vault Authority PDA:", vaultAuthorityPda.toBase58());
+  console.log("   Vault Admin Proposal PDA:", adminProposalPda.toBase58(), "\n");
+
+  try {
+    const adminProposal = await (
+      flipperProgram.account as any
+    ).vaultAdminProposal.fetch(adminProposalPda);
+    if (adminProposal.pendingAdmin.toBase58() !== wallet.publicKey.toBase58()) {
+      console.error("❌ ERROR: Your wallet is not the proposed admin!");
+      console.log("   Pending Admin:", adminProposal.pendingAdmin.toBase58());
+      process.exit(1);
+    }
+  } catch (error) {
+    console.error("❌ ERROR: No pending Vault Authority Admin proposal!");
+    console.log("\n💡 The Global Manager must run change_vault_authority_admin.ts first\n");
+    process.exit(1);
+  }
+
+  try {
+    const tx = await flipperProgram.methods
+      .acceptVaultAuthorityAdmin()
+      .accounts({
+        vaultAuthority: vaultAuthorityPda,
+        adminProposal: adminProposalPda,
+        newAdmin: wallet.publicKey,
+      })
+      .rpc();
+
+    console.log("✅ Vault Authority Admin changed successfully!");
+    console.log("   Transaction:", tx);
+    console.log("   Explorer:", `https://solscan.io/tx/${tx}`);
+
+    await new Promise((resolve) => setTimeout(resolve, 2000));
+    const updatedVaultAuth = await (
+      flipperProgram.account as any
+    ).vaultAuthority.fetch(vaultAuthorityPda);
+    console.log("\n📊 Updated Vault Authority Account:");
+    console.log("   Admin:", updatedVaultAuth.admin.toBase58());
+  } catch (error: any) {
+    console.error("\n❌ Error accepting Vault Authority Admin:");
+    console.error(error);
+    process.exit(1);
+  }
+}
+
+// Run the function
+acceptVaultAuthorityAdmin()
+  .then(() => {
+    console.log("\n✅ Script completed successfully");
+    process.exit(0);
+  })
+  .catch((error) => {
+    console.error("\n❌ Script failed:", error);
+    process.exit(1);
+  });
@@ -5,10 +5,11 @@ import FLIPPER_IDL from "../../target/idl/flipper.json";
 import fs from "fs";
 
 /**
- * Script to change Vault Authority Admin on Mainnet
+ * Script to propose a new Vault Authority Admin on Mainnet
  *
- * This changes the admin of the Vault Authority account using Global Manager privileges.
- * Only the current Global Manager can execute this operation.
+ * This proposes a new admin of the Vault Authority account using Global Manager privileges.
+ * Only the current Global Manager can execute this operation. The current admin keeps
+ * its powers until the new admin signs accept_vault_authority_admin.ts.
  *
  * Usage:
  * NEW_ADMIN_PUBKEY=<new_admin_address> ts-node scripts/mainnet/change_vault_authority_admin.ts
@@ -180,34 +181,37 @@ async function changeVaultAuthorityAdmin() {
       })
       .rpc();
 
-    console.log("✅ Vault Authority Admin changed successfully!");
+    console.log("✅ Vault Authority Admin proposed successfully!");
     console.log("   Transaction:", tx);
     console.log("   Explorer:", `https://solscan.io/tx/${tx}`);
 
-    // Verify the change
+    // Verify the proposal
     await new Promise((resolve) => setTimeout(resolve, 2000));
-    const updatedVaultAuth = await (
+    const [adminProposalPda] = PublicKey.findProgramAddressSync(
+      [Buffer.from("vault_admin_proposal")],
+      flipperProgram.programId
+    );
+    const adminProposal = await (
       flipperProgram.account as any
-    ).vaultAuthority.fetch(vaultAuthorityPda);
+    ).vaultAdminProposal.fetch(adminProposalPda);
 
-    console.log("\n📊 Updated Vault Authority Account:");
-    console.log("   Address:", vaultAuthorityPda.toBase58());
-    console.log("   New Admin:", updatedVaultAuth.admin.toBase58());
-    console.log("   Bump:", updatedVaultAuth.bump);
+    console.log("\n📊 Vault Admin Proposal:");
+    console.log("   Address:", adminProposalPda.toBase58());
+    console.log("   Pending Admin:", adminProposal.pendingAdmin.toBase58());
 
-    if (updatedVaultAuth.admin.toBase58() !== newAdminPublicKey.toBase58()) {
-      console.log("\n❌ ERROR: Admin address mismatch!");
+    if (adminProposal.pendingAdmin.toBase58() !== newAdminPublicKey.toBase58()) {
+      console.log("\n❌ ERROR: Pending admin address mismatch!");
       console.log("   Expected:", newAdminPublicKey.toBase58());
-      console.log("   Got:", updatedVaultAuth.admin.toBase58());
+      console.log("   Got:", adminProposal.pendingAdmin.toBase58());
     } else {
       console.log(
-        "\n✅ Verification passed: Vault Authority Admin changed successfully"
+        "\n✅ Verification passed: new admin proposed, waiting for acceptance"
       );
     }
 
     console.log("\n🎯 Important Notes:");
     console.log("   1. Document this change in your records");
-    console.log("   2. The new admin now controls vault operations");
+    console.log("   2. The new admin must run accept_vault_authority_admin.ts to take control");
     console.log("   3. Operators can still create vaults (if authorized)");
     console.log("   4. Only Global Manager can change the admin again");
     console.log("\n⚠️  If the new admin is a multisig:");
@@ -224,6 +228,8 @@ async function changeVaultAuthorityAdmin() {
     } else if (error.message?.includes("GlobalManagerNotInitialized")) {
       console.log("\n💡 Global Manager is not initialized");
       console.log("   Create it first using create_global_manager.ts");
+    } else if (error.message?.includes("InvalidVaultAdmin")) {
+      console.log("\n💡 The default pubkey cannot be the vault authority admin");
     } else if (error.message?.includes("VaultAuthorityNotInitialized")) {
       console.log("\n💡 Vault Authority is not initialized");
       console.log("   Create it first using create_vault_authority.ts");
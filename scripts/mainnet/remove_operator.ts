@@ -47,10 +47,13 @@ async function removeOperator() {
   }
 
   const operator = new PublicKey(operatorPubkey);
+  // FORCE=true hands the order vaults the operator still sponsors to the treasury
+  const force = process.env.FORCE === "true";
 
   console.log("📍 Configuration:");
   console.log("   Authority:", wallet.publicKey.toBase58());
-  console.log("   Operator to remove:", operator.toBase58(), "\n");
+  console.log("   Operator to remove:", operator.toBase58());
+  console.log("   Force:", force, "\n");
 
   // Derive adapter registry PDA
   [adapterRegistry, adapterRegistryBump] = PublicKey.findProgramAddressSync(
@@ -96,7 +99,7 @@ async function removeOperator() {
   console.log("⚙️  Removing operator...");
   try {
     const txSignature = await flipperProgram.methods
-      .removeOperator(operator)
+      .removeOperator(operator, force)
       .accounts({
         adapterRegistry,
         authority: wallet.publicKey,
@@ -98,7 +98,7 @@ async function replaceOperator() {
     console.log("⚙️  Step 1: Removing old operator...");
     try {
       const removeTxSignature = await flipperProgram.methods
-        .removeOperator(oldOperator)
+        .removeOperator(oldOperator, false)
         .accounts({
           adapterRegistry,
           authority: wallet.publicKey,
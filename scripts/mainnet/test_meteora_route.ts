@@ -306,7 +306,7 @@ async function main() {
     { pubkey: WSOL_MINT, isWritable: false, isSigner: false }, // 8: token_x_mint
     { pubkey: USDC_MINT, isWritable: false, isSigner: false }, // 9: token_y_mint
     { pubkey: METEORA_ORACLE, isWritable: true, isSigner: false }, // 10: oracle
-    { pubkey: METEORA_HOST_FEE_IN, isWritable: true, isSigner: false }, // 11: host_fee_in
+    { pubkey: METEORA_HOST_FEE_IN, isWritable: false, isSigner: false }, // 11: host_fee_in (program id = none)
     { pubkey: TOKEN_PROGRAM_ID, isWritable: false, isSigner: false }, // 12: token_x_program
     { pubkey: TOKEN_PROGRAM_ID, isWritable: false, isSigner: false }, // 13: token_y_program
     { pubkey: MEMO_PROGRAM_ID, isWritable: false, isSigner: false }, // 14: memo_program (NEW in swap2)
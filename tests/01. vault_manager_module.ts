@@ -15,6 +15,7 @@ import {
   getAccount,
   getAssociatedTokenAddressSync,
   createAssociatedTokenAccount,
+  NATIVE_MINT,
 } from "@solana/spl-token";
 import { expect } from "chai";
 import { Flipper } from "../target/types/flipper";
@@ -281,10 +282,89 @@ describe("Flipper Swap Protocol - Vault Manager Module", () => {
         expect(error.message).to.include("UnauthorizedGlobalManager");
       }
     });
+
+    it("should set the Jupiter data limit within one packet", async () => {
+      await program.methods
+        .setJupiterDataLimit(600)
+        .accounts({
+          vaultAuthority,
+          globalManager: globalManagerPda,
+          manager: globalManager.publicKey,
+        })
+        .signers([globalManager])
+        .rpc();
+
+      let vaultAuthorityAccount = await program.account.vaultAuthority.fetch(
+        vaultAuthority
+      );
+      expect(vaultAuthorityAccount.maxJupiterDataLen).to.equal(600);
+
+      for (const maxLen of [7, 1233]) {
+        try {
+          await program.methods
+            .setJupiterDataLimit(maxLen)
+            .accounts({
+              vaultAuthority,
+              globalManager: globalManagerPda,
+              manager: globalManager.publicKey,
+            })
+            .signers([globalManager])
+            .rpc();
+          expect.fail("Should have failed");
+        } catch (error) {
+          expect(error.message).to.include("InvalidJupiterDataLimit");
+        }
+      }
+
+      // Back to the default for other tests
+      await program.methods
+        .setJupiterDataLimit(0)
+        .accounts({
+          vaultAuthority,
+          globalManager: globalManagerPda,
+          manager: globalManager.publicKey,
+        })
+        .signers([globalManager])
+        .rpc();
+      vaultAuthorityAccount = await program.account.vaultAuthority.fetch(
+        vaultAuthority
+      );
+      expect(vaultAuthorityAccount.maxJupiterDataLen).to.equal(0);
+    });
   });
 
   describe("Vault Authority Management", () => {
-    it("should change vault authority admin by global manager", async () => {
+    it("should rotate vault authority admin only after the new admin accepts", async () => {
+      const [adminProposal] = PublicKey.findProgramAddressSync(
+        [Buffer.from("vault_admin_proposal")],
+        program.programId
+      );
+      const rotationMint = await createMint(
+        provider.connection,
+        admin,
+        admin.publicKey,
+        null,
+        6
+      );
+      const createVaultAs = (creator: Keypair, mint: PublicKey) =>
+        program.methods
+          .createVault()
+          .accounts({
+            vaultAuthority,
+            adapterRegistry,
+            payer: payer.publicKey,
+            creator: creator.publicKey,
+            vault: PublicKey.findProgramAddressSync(
+              [Buffer.from("vault"), mint.toBuffer()],
+              program.programId
+            )[0],
+            vaultMint: mint,
+            vaultTokenProgram: TOKEN_PROGRAM_ID,
+            systemProgram: SystemProgram.programId,
+          })
+          .signers([payer, creator])
+          .rpc();
+
       await program.methods
         .changeVaultAuthorityAdmin()
         .accounts({
@@ -292,14 +372,48 @@ describe("Flipper Swap Protocol - Vault Manager Module", () => {
           globalManager: globalManagerPda,
           manager: globalManager.publicKey,
           newAdmin: newAdmin.publicKey,
+          adminProposal,
+          systemProgram: SystemProgram.programId,
         })
         .signers([globalManager])
         .rpc();
 
-      const vaultAuthorityAccount = await program.account.vaultAuthority.fetch(
+      // Proposed but not accepted: the old admin is still the admin
+      let vaultAuthorityAccount = await program.account.vaultAuthority.fetch(
+        vaultAuthority
+      );
+      expect(vaultAuthorityAccount.admin.equals(admin.publicKey)).to.be.true;
+      try {
+        await createVaultAs(newAdmin, rotationMint);
+        expect.fail("Pending admin should not have admin powers");
+      } catch (error) {
+        expect(error.message).to.include("UnauthorizedVaultCreator");
+      }
+
+      // Only the proposed admin can accept
+      try {
+        await program.methods
+          .acceptVaultAuthorityAdmin()
+          .accounts({ vaultAuthority, adminProposal, newAdmin: user.publicKey })
+          .signers([user])
+          .rpc();
+        expect.fail("Should have failed");
+      } catch (error) {
+        expect(error.message).to.include("UnauthorizedAdmin");
+      }
+
+      await program.methods
+        .acceptVaultAuthorityAdmin()
+        .accounts({ vaultAuthority, adminProposal, newAdmin: newAdmin.publicKey })
+        .signers([newAdmin])
+        .rpc();
+
+      vaultAuthorityAccount = await program.account.vaultAuthority.fetch(
         vaultAuthority
       );
       expect(vaultAuthorityAccount.admin.equals(newAdmin.publicKey)).to.be.true;
+      expect(await provider.connection.getAccountInfo(adminProposal)).to.be.null;
+      await createVaultAs(newAdmin, rotationMint);
 
       // Change back for other tests
       await program.methods
@@ -309,9 +423,34 @@ describe("Flipper Swap Protocol - Vault Manager Module", () => {
           globalManager: globalManagerPda,
           manager: globalManager.publicKey,
           newAdmin: admin.publicKey,
+          adminProposal,
+          systemProgram: SystemProgram.programId,
         })
         .signers([globalManager])
         .rpc();
+      await program.methods
+        .acceptVaultAuthorityAdmin()
+        .accounts({ vaultAuthority, adminProposal, newAdmin: admin.publicKey })
+        .signers([admin])
+        .rpc();
+    });
+
+    it("should reject the default pubkey as vault authority admin", async () => {
+      try {
+        await program.methods
+          .changeVaultAuthorityAdmin()
+          .accounts({
+            vaultAuthority,
+            globalManager: globalManagerPda,
+            manager: globalManager.publicKey,
+            newAdmin: PublicKey.default,
+          })
+          .signers([globalManager])
+          .rpc();
+        expect.fail("Should have failed");
+      } catch (error) {
+        expect(error.message).to.include("InvalidVaultAdmin");
+      }
     });
 
     it("should fail to change vault authority admin with wrong signer", async () => {
@@ -704,6 +843,17 @@ describe("Flipper Swap Protocol - Vault Manager Module", () => {
       expect(outputVaultAccount.mint.equals(destMint)).to.be.true;
       expect(inputVaultAccount.owner.equals(vaultAuthority)).to.be.true;
       expect(outputVaultAccount.owner.equals(vaultAuthority)).to.be.true;
+
+      // Both vaults get a VaultMeta, as with create_vault
+      for (const vault of [inputVault, outputVault]) {
+        const [vaultMeta] = PublicKey.findProgramAddressSync(
+          [Buffer.from("vault_meta"), vault.toBuffer()],
+          program.programId
+        );
+        const meta = await program.account.vaultMeta.fetch(vaultMeta);
+        expect(meta.vault.equals(vault)).to.be.true;
+        expect(meta.vaultGeneration).to.equal(0);
+      }
     });
 
     it("should initialize vaults (Token-2022 + Token-2022) by operator", async () => {
@@ -1189,6 +1339,350 @@ describe("Flipper Swap Protocol - Vault Manager Module", () => {
         expect(error.message).to.include("UnauthorizedVaultCreator");
       }
     });
+
+    it("should wrap, unwrap and close the native mint vault", async () => {
+      const [nativeVault] = PublicKey.findProgramAddressSync(
+        [Buffer.from("vault"), NATIVE_MINT.toBuffer()],
+        program.programId
+      );
+      const [unwrapAccount] = PublicKey.findProgramAddressSync(
+        [Buffer.from("unwrap_account")],
+        program.programId
+      );
+
+      await program.methods
+        .createVault()
+        .accounts({
+          vaultAuthority,
+          adapterRegistry,
+          payer: payer.publicKey,
+          creator: admin.publicKey,
+          vault: nativeVault,
+          vaultMint: NATIVE_MINT,
+          vaultTokenProgram: TOKEN_PROGRAM_ID,
+          systemProgram: SystemProgram.programId,
+        })
+        .signers([payer, admin])
+        .rpc();
+
+      const wrapAccounts = (signer: PublicKey) => ({
+        vaultAuthority,
+        admin: signer,
+        payer: payer.publicKey,
+        vault: nativeVault,
+        nativeMint: NATIVE_MINT,
+        tokenProgram: TOKEN_PROGRAM_ID,
+        systemProgram: SystemProgram.programId,
+      });
+
+      try {
+        await program.methods
+          .wrapIntoVault(new anchor.BN(LAMPORTS_PER_SOL))
+          .accounts(wrapAccounts(user.publicKey))
+          .signers([user, payer])
+          .rpc();
+        expect.fail("Should have failed");
+      } catch (error) {
+        expect(error.message).to.include("UnauthorizedAdmin");
+      }
+
+      await program.methods
+        .wrapIntoVault(new anchor.BN(LAMPORTS_PER_SOL))
+        .accounts(wrapAccounts(admin.publicKey))
+        .signers([admin, payer])
+        .rpc();
+
+      let vaultAccount = await getAccount(provider.connection, nativeVault);
+      expect(Number(vaultAccount.amount)).to.equal(LAMPORTS_PER_SOL);
+
+      // Partial unwrap: the destination gets exactly the amount, the payer keeps its rent
+      const unwrapAmount = 0.4 * LAMPORTS_PER_SOL;
+      const userLamportsBefore = await provider.connection.getBalance(user.publicKey);
+      await program.methods
+        .unwrapFromVault(new anchor.BN(unwrapAmount))
+        .accounts({
+          vaultAuthority,
+          admin: admin.publicKey,
+          payer: payer.publicKey,
+          vault: nativeVault,
+          unwrapAccount,
+          destination: user.publicKey,
+          nativeMint: NATIVE_MINT,
+          tokenProgram: TOKEN_PROGRAM_ID,
+          systemProgram: SystemProgram.programId,
+        })
+        .signers([admin, payer])
+        .rpc();
+
+      const userLamportsAfter = await provider.connection.getBalance(user.publicKey);
+      expect(userLamportsAfter - userLamportsBefore).to.equal(unwrapAmount);
+      vaultAccount = await getAccount(provider.connection, nativeVault);
+      expect(Number(vaultAccount.amount)).to.equal(LAMPORTS_PER_SOL - unwrapAmount);
+      expect(await provider.connection.getAccountInfo(unwrapAccount)).to.be.null;
+
+      // Closing a native vault with a balance unwraps the rest to the destination
+      const vaultLamports = await provider.connection.getBalance(nativeVault);
+      await program.methods
+        .closeVault()
+        .accounts({
+          vaultAuthority,
+          adapterRegistry,
+          vault: nativeVault,
+          destination: user.publicKey,
+          closer: admin.publicKey,
+          tokenProgram: TOKEN_PROGRAM_ID,
+        })
+        .signers([admin])
+        .rpc();
+
+      expect(await provider.connection.getBalance(user.publicKey)).to.equal(
+        userLamportsAfter + vaultLamports
+      );
+      expect(await provider.connection.getAccountInfo(nativeVault)).to.be.null;
+    });
+  });
+
+  describe("Vault Registry", () => {
+    let vaultRegistry: PublicKey;
+
+    const listAllVaults = async (): Promise<PublicKey[]> => {
+      const mints: PublicKey[] = [];
+      for (let offset = 0; ; offset += 31) {
+        const page = await program.methods
+          .listVaults(offset, 31)
+          .accounts({ vaultRegistry })
+          .view();
+        mints.push(...page);
+        if (page.length < 31) return mints;
+      }
+    };
+
+    before(async () => {
+      [vaultRegistry] = PublicKey.findProgramAddressSync(
+        [Buffer.from("vault_registry")],
+        program.programId
+      );
+    });
+
+    it("should fail to initialize the registry with unauthorized creator", async () => {
+      try {
+        await program.methods
+          .initializeVaultRegistry()
+          .accounts({
+            vaultAuthority,
+            adapterRegistry,
+            payer: payer.publicKey,
+            creator: user.publicKey,
+          })
+          .signers([payer, user])
+          .rpc();
+        expect.fail("Should have thrown error");
+      } catch (error) {
+        expect(error.message).to.include("UnauthorizedVaultCreator");
+      }
+    });
+
+    it("should initialize the registry and backfill existing vaults", async () => {
+      await program.methods
+        .initializeVaultRegistry()
+        .accounts({
+          vaultAuthority,
+          adapterRegistry,
+          payer: payer.publicKey,
+          creator: admin.publicKey,
+        })
+        .signers([payer, admin])
+        .rpc();
+      expect(await listAllVaults()).to.be.empty;
+
+      // Listing a vault twice is a no-op
+      await program.methods
+        .registerExistingVaults()
+        .accounts({
+          vaultAuthority,
+          adapterRegistry,
+          payer: payer.publicKey,
+          caller: operator.publicKey,
+        })
+        .remainingAccounts(
+          [vault, vault2022, vault].map((pubkey) => ({
+            pubkey,
+            isSigner: false,
+            isWritable: false,
+          }))
+        )
+        .signers([payer, operator])
+        .rpc();
+
+      const mints = await listAllVaults();
+      expect(mints.map((mint) => mint.toBase58())).to.deep.equal([
+        tokenMint.toBase58(),
+        token2022Mint.toBase58(),
+      ]);
+    });
+
+    it("should reject backfilling a token account that is not a vault PDA", async () => {
+      try {
+        await program.methods
+          .registerExistingVaults()
+          .accounts({
+            vaultAuthority,
+            adapterRegistry,
+            payer: payer.publicKey,
+            caller: admin.publicKey,
+          })
+          .remainingAccounts([
+            { pubkey: userTokenAccount, isSigner: false, isWritable: false },
+          ])
+          .signers([payer, admin])
+          .rpc();
+        expect.fail("Should have thrown error");
+      } catch (error) {
+        expect(error.message).to.include("InvalidVaultOwner");
+      }
+    });
+
+    it("should list created vaults and drop closed ones", async () => {
+      const registryMint = await createMint(
+        provider.connection,
+        payer,
+        admin.publicKey,
+        admin.publicKey,
+        6,
+        undefined,
+        undefined,
+        TOKEN_PROGRAM_ID
+      );
+      const [registryVault] = PublicKey.findProgramAddressSync(
+        [Buffer.from("vault"), registryMint.toBuffer()],
+        program.programId
+      );
+      const sizeBefore = (await provider.connection.getAccountInfo(vaultRegistry))
+        .data.length;
+
+      await program.methods
+        .createVault()
+        .accounts({
+          vaultAuthority,
+          adapterRegistry,
+          payer: payer.publicKey,
+          creator: admin.publicKey,
+          vault: registryVault,
+          vaultMint: registryMint,
+          vaultTokenProgram: TOKEN_PROGRAM_ID,
+          systemProgram: SystemProgram.programId,
+        })
+        .signers([payer, admin])
+        .rpc();
+
+      // The registry grew by one entry to hold the new mint
+      const sizeAfter = (await provider.connection.getAccountInfo(vaultRegistry))
+        .data.length;
+      expect(sizeAfter).to.equal(sizeBefore + 32);
+      let mints = await listAllVaults();
+      expect(mints[mints.length - 1].equals(registryMint)).to.be.true;
+
+      const page = await program.methods
+        .listVaults(1, 1)
+        .accounts({ vaultRegistry })
+        .view();
+      expect(page).to.have.length(1);
+      expect(page[0].equals(token2022Mint)).to.be.true;
+
+      await program.methods
+        .closeVault()
+        .accounts({
+          vaultAuthority,
+          adapterRegistry,
+          vault: registryVault,
+          destination: user.publicKey,
+          closer: admin.publicKey,
+          tokenProgram: TOKEN_PROGRAM_ID,
+        })
+        .signers([admin])
+        .rpc();
+
+      mints = await listAllVaults();
+      expect(mints.some((mint) => mint.equals(registryMint))).to.be.false;
+      expect(mints).to.have.length(2);
+    });
+
+    it("should bump the vault generation on close and reject stale generations", async () => {
+      const mint = await createMint(
+        provider.connection,
+        payer,
+        admin.publicKey,
+        admin.publicKey,
+        6,
+        undefined,
+        undefined,
+        TOKEN_PROGRAM_ID
+      );
+      const [mintVault] = PublicKey.findProgramAddressSync(
+        [Buffer.from("vault"), mint.toBuffer()],
+        program.programId
+      );
+      const [vaultMeta] = PublicKey.findProgramAddressSync(
+        [Buffer.from("vault_meta"), mintVault.toBuffer()],
+        program.programId
+      );
+      const createMintVault = () =>
+        program.methods
+          .createVault()
+          .accounts({
+            vaultAuthority,
+            adapterRegistry,
+            payer: payer.publicKey,
+            creator: admin.publicKey,
+            vault: mintVault,
+            vaultMint: mint,
+            vaultTokenProgram: TOKEN_PROGRAM_ID,
+            systemProgram: SystemProgram.programId,
+          })
+          .signers([payer, admin])
+          .rpc();
+      const closeMintVault = () =>
+        program.methods
+          .closeVault()
+          .accounts({
+            vaultAuthority,
+            adapterRegistry,
+            vault: mintVault,
+            destination: user.publicKey,
+            closer: admin.publicKey,
+            tokenProgram: TOKEN_PROGRAM_ID,
+          })
+          .signers([admin])
+          .rpc();
+      const assertGeneration = (expected: number) =>
+        program.methods
+          .assertVaultGeneration(expected)
+          .accounts({ vault: mintVault })
+          .rpc();
+      const generation = async () =>
+        (await program.account.vaultMeta.fetch(vaultMeta)).vaultGeneration;
+
+      await createMintVault();
+      expect(await generation()).to.equal(0);
+      await assertGeneration(0);
+
+      await closeMintVault();
+      expect(await generation()).to.equal(1);
+
+      // Re-created at the same address, the vault keeps the generation of its last close
+      await createMintVault();
+      expect(await generation()).to.equal(1);
+      try {
+        await assertGeneration(0);
+        expect.fail("A generation cached before the close should be rejected");
+      } catch (error) {
+        expect(error.toString()).to.include("VaultGenerationMismatch");
+      }
+      await assertGeneration(1);
+
+      await closeMintVault();
+      expect(await generation()).to.equal(2);
+    });
   });
 
   describe("Helper Functions", () => {
@@ -68,6 +68,34 @@ describe("Flipper Swap Protocol - Adapter Registry Module", () => {
     return bytes;
   }
 
+  // MIN_GOVERNANCE_DELAY_SECS of localnet builds (anchor build -- --features localnet)
+  const MIN_GOVERNANCE_DELAY_SECS = 2;
+  const waitOutGovernanceDelay = (delaySecs = MIN_GOVERNANCE_DELAY_SECS) =>
+    new Promise((resolve) => setTimeout(resolve, (delaySecs + 2) * 1000));
+
+  // Proposes a registry reset as a governance call, then executes it once the delay (the
+  // minimum unless a test raises it) has passed
+  async function resetRegistry(
+    authority: Keypair,
+    adapters: any[],
+    operators: PublicKey[]
+  ) {
+    await program.methods
+      .proposeGovernanceCall({ resetAdapterRegistry: { adapters, operators } })
+      .accounts({ authority: authority.publicKey })
+      .signers([authority])
+      .rpc();
+    await waitOutGovernanceDelay();
+    await program.methods
+      .resetAdapterRegistry(adapters, operators)
+      .accounts({
+        adapterRegistry,
+        authority: authority.publicKey,
+      })
+      .signers([authority])
+      .rpc();
+  }
+
   before(async () => {
     try {
       initialAuthority = Keypair.generate();
@@ -83,6 +111,13 @@ describe("Flipper Swap Protocol - Adapter Registry Module", () => {
       );
       await provider.connection.confirmTransaction(airdropSignature);
 
+      // Fund the registry authority, which pays for governance proposals
+      const authorityAirdropSignature = await provider.connection.requestAirdrop(
+        initialAuthority.publicKey,
+        5_000_000_000 // 5 SOL
+      );
+      await provider.connection.confirmTransaction(authorityAirdropSignature);
+
       // Fund the operator account with SOL
       const operatorAirdropSignature = await provider.connection.requestAirdrop(
         operator.publicKey,
@@ -301,28 +336,22 @@ describe("Flipper Swap Protocol - Adapter Registry Module", () => {
         }
 
         // Reset adapter registry state
-        await program.methods
-          .resetAdapterRegistry(
-            [
-              {
-                name: "Raydium",
-                programId: raydiumProgramId,
-                swapType: { raydium: {} },
-              },
-              {
-                name: "Whirlpool",
-                programId: whirlpoolProgramId,
-                swapType: { whirlpool: { aToB: true } },
-              },
-            ],
-            [operator.publicKey]
-          )
-          .accounts({
-            adapterRegistry,
-            authority: initialAuthority.publicKey,
-          })
-          .signers([initialAuthority])
-          .rpc();
+        await resetRegistry(
+          initialAuthority,
+          [
+            {
+              name: "Raydium",
+              programId: raydiumProgramId,
+              swapType: { raydium: {} },
+            },
+            {
+              name: "Whirlpool",
+              programId: whirlpoolProgramId,
+              swapType: { whirlpool: { aToB: true } },
+            },
+          ],
+          [operator.publicKey]
+        );
       }
     } catch (error) {
       if (error instanceof anchor.web3.SendTransactionError) {
@@ -369,6 +398,10 @@ describe("Flipper Swap Protocol - Adapter Registry Module", () => {
           .rpc();
       }
 
+      // Drop this suite's placeholder adapters, so downstream suites add their adapters
+      // instead of re-pointing these ones (which would need a governance proposal)
+      await resetRegistry(wallet.payer, [], [wallet.publicKey]);
+
       // Ensure wallet is an operator so downstream tests can configure adapters
       const updatedRegistry = await program.account.adapterRegistry.fetch(
         adapterRegistry
@@ -551,7 +584,7 @@ describe("Flipper Swap Protocol - Adapter Registry Module", () => {
             ];
 
             await program.methods
-                .route(routePlan, inAmount, quotedOutAmount, slippageBps, platformFeeBps)
+                .route(routePlan, inAmount, quotedOutAmount, slippageBps, platformFeeBps, new BN(0), false, null, false, null)
                 .accounts({
                     adapterRegistry,
                     vaultAuthority,
@@ -632,7 +665,7 @@ describe("Flipper Swap Protocol - Adapter Registry Module", () => {
             ];
 
             await program.methods
-                .route(routePlan, new BN(1000), new BN(1000), 500, 0)
+                .route(routePlan, new BN(1000), new BN(1000), 500, 0, new BN(0), false, null, false, null)
                 .accounts({
                     adapterRegistry,
                     vaultAuthority,
@@ -692,7 +725,7 @@ describe("Flipper Swap Protocol - Adapter Registry Module", () => {
             ];
 
             await program.methods
-                .route(routePlan, new BN(1000), new BN(1000), 500, 0)
+                .route(routePlan, new BN(1000), new BN(1000), 500, 0, new BN(0), false, null, false, null)
                 .accounts({
                     adapterRegistry,
                     vaultAuthority,
@@ -716,6 +749,52 @@ describe("Flipper Swap Protocol - Adapter Registry Module", () => {
         }
     });*/
 
+  it("Restricts an adapter to whitelisted pools and clears the whitelist", async () => {
+    const swapType = { whirlpool: { aToB: true } };
+    const [poolInfo] = PublicKey.findProgramAddressSync(
+      [Buffer.from("pool_info"), getSwapTypeBytes(swapType), poolAddress.toBuffer()],
+      program.programId
+    );
+
+    await program.methods
+      .setAdapterPools(swapType, [poolAddress])
+      .accounts({ adapterRegistry, operator: operator.publicKey })
+      .remainingAccounts([{ pubkey: poolInfo, isWritable: false, isSigner: false }])
+      .signers([operator])
+      .rpc();
+
+    let registryAccount = await program.account.adapterRegistry.fetch(adapterRegistry);
+    const entry = registryAccount.adapterPools.find((pools) => "whirlpool" in pools.swapType);
+    assert.ok(entry.poolAddresses[0].equals(poolAddress));
+
+    // Every whitelisted pool needs its PoolInfo
+    const unknownPool = Keypair.generate().publicKey;
+    const [unknownPoolInfo] = PublicKey.findProgramAddressSync(
+      [Buffer.from("pool_info"), getSwapTypeBytes(swapType), unknownPool.toBuffer()],
+      program.programId
+    );
+    try {
+      await program.methods
+        .setAdapterPools(swapType, [unknownPool])
+        .accounts({ adapterRegistry, operator: operator.publicKey })
+        .remainingAccounts([{ pubkey: unknownPoolInfo, isWritable: false, isSigner: false }])
+        .signers([operator])
+        .rpc();
+      assert.fail("Pool without PoolInfo should be rejected");
+    } catch (error) {
+      assert.include(error.toString(), "InvalidPoolAddress");
+    }
+
+    await program.methods
+      .setAdapterPools(swapType, [])
+      .accounts({ adapterRegistry, operator: operator.publicKey })
+      .signers([operator])
+      .rpc();
+
+    registryAccount = await program.account.adapterRegistry.fetch(adapterRegistry);
+    assert.isUndefined(registryAccount.adapterPools.find((pools) => "whirlpool" in pools.swapType));
+  });
+
   it("Configures a new adapter as operator", async () => {
     const newProgramId = Keypair.generate().publicKey;
     const newAdapter = {
@@ -757,6 +836,212 @@ describe("Flipper Swap Protocol - Adapter Registry Module", () => {
     }
   });
 
+  it("Changes an adapter program id only after its pools are disabled", async () => {
+    const swapType = { raydium: {} };
+    const [poolInfo] = PublicKey.findProgramAddressSync(
+      [Buffer.from("pool_info"), getSwapTypeBytes(swapType), poolAddress.toBuffer()],
+      program.programId
+    );
+    await program.methods
+      .initializePoolInfo(swapType, poolAddress)
+      .accounts({
+        poolInfo,
+        adapterRegistry,
+        payer: payer.publicKey,
+        operator: operator.publicKey,
+        systemProgram: SystemProgram.programId,
+      })
+      .signers([payer, operator])
+      .rpc();
+
+    const repointed = {
+      name: "Raydium",
+      programId: Keypair.generate().publicKey,
+      swapType,
+    };
+
+    const [governanceProposal] = PublicKey.findProgramAddressSync(
+      [Buffer.from("governance_proposal")],
+      program.programId
+    );
+    const configure = (proposal: PublicKey | null) =>
+      program.methods
+        .configureAdapter(repointed)
+        .accounts({
+          adapterRegistry,
+          operator: operator.publicKey,
+          governanceProposal: proposal,
+        })
+        .signers([operator])
+        .rpc();
+
+    // A program id change is a governance call
+    try {
+      await configure(null);
+      assert.fail("Program id change without a proposal should be rejected");
+    } catch (error) {
+      assert.include(error.toString(), "GovernanceProposalRequired");
+    }
+    await program.methods
+      .proposeGovernanceCall({ configureAdapter: { adapter: repointed } })
+      .accounts({ authority: initialAuthority.publicKey })
+      .signers([initialAuthority])
+      .rpc();
+    await waitOutGovernanceDelay();
+
+    // The pool is still enabled, so routes built against the old program are live
+    try {
+      await configure(governanceProposal);
+      assert.fail("Program id change with an enabled pool should be rejected");
+    } catch (error) {
+      assert.include(error.toString(), "AdapterHasActivePools");
+    }
+
+    await program.methods
+      .disablePool(swapType, poolAddress)
+      .accounts({ poolInfo, adapterRegistry, operator: operator.publicKey })
+      .signers([operator])
+      .rpc();
+
+    await configure(governanceProposal);
+
+    const registryAccount = await program.account.adapterRegistry.fetch(adapterRegistry);
+    const raydium = registryAccount.supportedAdapters.find((a) => "raydium" in a.swapType);
+    assert.ok(raydium.programId.equals(repointed.programId));
+    // Executing the change consumes the proposal
+    assert.isNull(await provider.connection.getAccountInfo(governanceProposal));
+  });
+
+  it("Executes governance calls only with the proposed arguments once the delay has passed", async () => {
+    const [protocolConfig] = PublicKey.findProgramAddressSync(
+      [Buffer.from("protocol_config")],
+      program.programId
+    );
+    const [governanceProposal] = PublicKey.findProgramAddressSync(
+      [Buffer.from("governance_proposal")],
+      program.programId
+    );
+    if (!(await provider.connection.getAccountInfo(protocolConfig))) {
+      await program.methods
+        .createProtocolConfig()
+        .accounts({ payer: payer.publicKey, authority: initialAuthority.publicKey })
+        .signers([payer, initialAuthority])
+        .rpc();
+    }
+    const delaySecs = MIN_GOVERNANCE_DELAY_SECS + 2;
+    const waitOutDelay = () => waitOutGovernanceDelay(delaySecs);
+    const setDelay = (delay: number, proposal: PublicKey | null) =>
+      program.methods
+        .setGovernanceDelay(new BN(delay))
+        .accounts({
+          authority: initialAuthority.publicKey,
+          governanceProposal: proposal,
+        })
+        .signers([initialAuthority])
+        .rpc();
+    const propose = (call: any) =>
+      program.methods
+        .proposeGovernanceCall(call)
+        .accounts({ authority: initialAuthority.publicKey })
+        .signers([initialAuthority])
+        .rpc();
+    const reset = (operators: PublicKey[]) =>
+      program.methods
+        .resetAdapterRegistry(adapters, operators)
+        .accounts({ adapterRegistry, authority: initialAuthority.publicKey })
+        .signers([initialAuthority])
+        .rpc();
+    const adapters = [
+      {
+        name: "Raydium",
+        programId: Keypair.generate().publicKey,
+        swapType: { raydium: {} },
+      },
+    ];
+
+    // The delay cannot go below the minimum, which unset configs already wait
+    try {
+      await setDelay(0, null);
+      assert.fail("A delay below the minimum should be rejected");
+    } catch (error) {
+      assert.include(error.toString(), "InvalidGovernanceDelay");
+    }
+
+    // Raising the delay applies at once
+    await setDelay(delaySecs, null);
+    let config = await program.account.protocolConfig.fetch(protocolConfig);
+    assert.equal(config.governanceDelaySecs.toNumber(), delaySecs);
+
+    // Only the registry authority can propose
+    try {
+      await program.methods
+        .proposeGovernanceCall({ resetAdapterRegistry: { adapters, operators: [] } })
+        .accounts({ authority: unauthorized.publicKey })
+        .signers([unauthorized])
+        .rpc();
+      assert.fail("A non-authority should not be able to propose");
+    } catch (error) {
+      assert.include(error.toString(), "InvalidAuthority");
+    }
+
+    await propose({ resetAdapterRegistry: { adapters, operators: [operator.publicKey] } });
+    const proposal = await program.account.governanceProposal.fetch(governanceProposal);
+    assert.equal(proposal.executableAt.sub(proposal.proposedAt).toNumber(), delaySecs);
+    assert.ok(proposal.proposer.equals(initialAuthority.publicKey));
+
+    try {
+      await reset([operator.publicKey]);
+      assert.fail("A reset before the delay has passed should be rejected");
+    } catch (error) {
+      assert.include(error.toString(), "GovernanceDelayNotElapsed");
+    }
+    // Anyone else may only clear a proposal once it expired
+    try {
+      await program.methods
+        .cancelGovernanceProposal()
+        .accounts({ proposer: initialAuthority.publicKey, canceller: unauthorized.publicKey })
+        .signers([unauthorized])
+        .rpc();
+      assert.fail("A pending proposal should only be cancelled by the authority");
+    } catch (error) {
+      assert.include(error.toString(), "GovernanceProposalNotExpired");
+    }
+
+    await waitOutDelay();
+    try {
+      await reset([operator.publicKey, unauthorized.publicKey]);
+      assert.fail("A reset with other arguments than proposed should be rejected");
+    } catch (error) {
+      assert.include(error.toString(), "GovernanceProposalMismatch");
+    }
+    await reset([operator.publicKey]);
+    assert.isNull(await provider.connection.getAccountInfo(governanceProposal));
+    const registry = await program.account.adapterRegistry.fetch(adapterRegistry);
+    assert.ok(registry.supportedAdapters[0].programId.equals(adapters[0].programId));
+
+    // The authority can withdraw a proposal at any time
+    await propose({ resetAdapterRegistry: { adapters: [], operators: [] } });
+    await program.methods
+      .cancelGovernanceProposal()
+      .accounts({ proposer: initialAuthority.publicKey, canceller: initialAuthority.publicKey })
+      .signers([initialAuthority])
+      .rpc();
+    assert.isNull(await provider.connection.getAccountInfo(governanceProposal));
+
+    // Lowering the delay is a governance call itself
+    try {
+      await setDelay(MIN_GOVERNANCE_DELAY_SECS, null);
+      assert.fail("Lowering the delay without a proposal should be rejected");
+    } catch (error) {
+      assert.include(error.toString(), "GovernanceProposalRequired");
+    }
+    await propose({ setGovernanceDelay: { delaySecs: new BN(MIN_GOVERNANCE_DELAY_SECS) } });
+    await waitOutDelay();
+    await setDelay(MIN_GOVERNANCE_DELAY_SECS, governanceProposal);
+    config = await program.account.protocolConfig.fetch(protocolConfig);
+    assert.equal(config.governanceDelaySecs.toNumber(), MIN_GOVERNANCE_DELAY_SECS);
+  });
+
   it("Fails to configure adapter with unauthorized account", async () => {
     const newProgramId = Keypair.generate().publicKey;
     const newAdapter = {
@@ -814,6 +1099,59 @@ describe("Flipper Swap Protocol - Adapter Registry Module", () => {
     }
   });
 
+  it("Governs re-adding a disabled adapter under another program id", async () => {
+    const swapType = { raydium: {} };
+    const [governanceProposal] = PublicKey.findProgramAddressSync(
+      [Buffer.from("governance_proposal")],
+      program.programId
+    );
+    const configure = (adapter: any, proposal: PublicKey | null) =>
+      program.methods
+        .configureAdapter(adapter)
+        .accounts({
+          adapterRegistry,
+          operator: operator.publicKey,
+          governanceProposal: proposal,
+        })
+        .signers([operator])
+        .rpc();
+
+    await program.methods
+      .disableAdapter(swapType)
+      .accounts({ adapterRegistry, operator: operator.publicKey })
+      .signers([operator])
+      .rpc();
+    let registryAccount = await program.account.adapterRegistry.fetch(adapterRegistry);
+    const retired = registryAccount.retiredAdapters.find((a) => "raydium" in a.swapType);
+    assert.ok(retired.programId.equals(raydiumProgramId));
+
+    // Disabling first does not turn a program id change into a plain add
+    const readded = {
+      name: "Raydium",
+      programId: Keypair.generate().publicKey,
+      swapType,
+    };
+    try {
+      await configure(readded, null);
+      assert.fail("Re-adding under another program id without a proposal should be rejected");
+    } catch (error) {
+      assert.include(error.toString(), "GovernanceProposalRequired");
+    }
+
+    await program.methods
+      .proposeGovernanceCall({ configureAdapter: { adapter: readded } })
+      .accounts({ authority: initialAuthority.publicKey })
+      .signers([initialAuthority])
+      .rpc();
+    await waitOutGovernanceDelay();
+    await configure(readded, governanceProposal);
+
+    registryAccount = await program.account.adapterRegistry.fetch(adapterRegistry);
+    const raydium = registryAccount.supportedAdapters.find((a) => "raydium" in a.swapType);
+    assert.ok(raydium.programId.equals(readded.programId));
+    assert.isFalse(registryAccount.retiredAdapters.some((a) => "raydium" in a.swapType));
+  });
+
   it("Fails to disable adapter with unauthorized account", async () => {
     try {
       await program.methods
@@ -974,10 +1312,364 @@ describe("Flipper Swap Protocol - Adapter Registry Module", () => {
     }
   });
 
+  it("Suspends operators without the authority until reinstated", async () => {
+    const leakedOperator = Keypair.generate();
+    const otherOperator = Keypair.generate();
+    for (const op of [leakedOperator, otherOperator]) {
+      await program.methods
+        .addOperator(op.publicKey)
+        .accounts({ adapterRegistry, authority: currentAuthority.publicKey })
+        .signers([currentAuthority])
+        .rpc();
+    }
+    const configureAs = (op: Keypair) =>
+      program.methods
+        .configureAdapter({
+          name: "SuspensionCheck",
+          programId: Keypair.generate().publicKey,
+          swapType: { saber: {} },
+        })
+        .accounts({ adapterRegistry, operator: op.publicKey })
+        .signers([op])
+        .rpc();
+
+    // The operator kills its own key
+    await program.methods
+      .suspendSelf()
+      .accounts({ adapterRegistry, operator: leakedOperator.publicKey })
+      .signers([leakedOperator])
+      .rpc();
+    try {
+      await configureAs(leakedOperator);
+      assert.fail("Suspended operator should be rejected");
+    } catch (error) {
+      assert.include(error.message, "InvalidOperator");
+    }
+
+    await program.methods
+      .reinstateOperator(leakedOperator.publicKey)
+      .accounts({ adapterRegistry, authority: currentAuthority.publicKey })
+      .signers([currentAuthority])
+      .rpc();
+    await configureAs(leakedOperator);
+
+    // Reason code 0 is reserved for suspend_self
+    try {
+      await program.methods
+        .suspendOperator(leakedOperator.publicKey, 0)
+        .accounts({ adapterRegistry, caller: otherOperator.publicKey })
+        .signers([otherOperator])
+        .rpc();
+      assert.fail("Reason code 0 should be rejected");
+    } catch (error) {
+      assert.include(error.message, "InvalidSuspensionReason");
+    }
+
+    // Another operator suspends the key
+    await program.methods
+      .suspendOperator(leakedOperator.publicKey, 7)
+      .accounts({ adapterRegistry, caller: otherOperator.publicKey })
+      .signers([otherOperator])
+      .rpc();
+    const registryAccount = await program.account.adapterRegistry.fetch(adapterRegistry);
+    assert.isTrue(
+      registryAccount.suspendedOperators.some((op) => op.equals(leakedOperator.publicKey))
+    );
+    try {
+      await configureAs(leakedOperator);
+      assert.fail("Suspended operator should be rejected");
+    } catch (error) {
+      assert.include(error.message, "InvalidOperator");
+    }
+
+    // A suspended operator cannot suspend others
+    try {
+      await program.methods
+        .suspendOperator(otherOperator.publicKey, 7)
+        .accounts({ adapterRegistry, caller: leakedOperator.publicKey })
+        .signers([leakedOperator])
+        .rpc();
+      assert.fail("Suspended operator should not suspend others");
+    } catch (error) {
+      assert.include(error.message, "InvalidOperator");
+    }
+
+    // Removing the operator also clears its suspension
+    for (const op of [leakedOperator, otherOperator]) {
+      await program.methods
+        .removeOperator(op.publicKey, false)
+        .accounts({ adapterRegistry, authority: currentAuthority.publicKey })
+        .signers([currentAuthority])
+        .rpc();
+    }
+    const finalRegistry = await program.account.adapterRegistry.fetch(adapterRegistry);
+    assert.isFalse(
+      finalRegistry.suspendedOperators.some((op) => op.equals(leakedOperator.publicKey))
+    );
+  });
+
+  it("Records operator heartbeats at most once per interval", async () => {
+    const keeper = Keypair.generate();
+    const outsider = Keypair.generate();
+    for (const key of [keeper, outsider]) {
+      await provider.connection.confirmTransaction(
+        await provider.connection.requestAirdrop(key.publicKey, 1_000_000_000)
+      );
+    }
+    await program.methods
+      .addOperator(keeper.publicKey)
+      .accounts({ adapterRegistry, authority: currentAuthority.publicKey })
+      .signers([currentAuthority])
+      .rpc();
+
+    const [operatorStats] = PublicKey.findProgramAddressSync(
+      [Buffer.from("operator_stats"), keeper.publicKey.toBuffer()],
+      program.programId
+    );
+    const heartbeat = (signer: Keypair) =>
+      program.methods
+        .operatorHeartbeat()
+        .accounts({
+          adapterRegistry,
+          operatorStats: PublicKey.findProgramAddressSync(
+            [Buffer.from("operator_stats"), signer.publicKey.toBuffer()],
+            program.programId
+          )[0],
+          operator: signer.publicKey,
+          systemProgram: SystemProgram.programId,
+        })
+        .signers([signer])
+        .rpc();
+
+    // First heartbeat creates the stats PDA
+    await heartbeat(keeper);
+    const stats = await program.account.operatorStats.fetch(operatorStats);
+    assert.isTrue(stats.operator.equals(keeper.publicKey));
+    assert.isAbove(stats.lastActive.toNumber(), 0);
+
+    // A second heartbeat within the interval is rejected and leaves last_active unchanged
+    try {
+      await heartbeat(keeper);
+      assert.fail("Heartbeat within the interval should be rejected");
+    } catch (error) {
+      assert.include(error.message, "HeartbeatTooFrequent");
+    }
+    const unchanged = await program.account.operatorStats.fetch(operatorStats);
+    assert.equal(unchanged.lastActive.toString(), stats.lastActive.toString());
+
+    // Keys that are not operators cannot send heartbeats
+    try {
+      await heartbeat(outsider);
+      assert.fail("Non-operator heartbeat should be rejected");
+    } catch (error) {
+      assert.include(error.message, "InvalidOperator");
+    }
+
+    await program.methods
+      .removeOperator(keeper.publicKey, false)
+      .accounts({ adapterRegistry, authority: currentAuthority.publicKey })
+      .signers([currentAuthority])
+      .rpc();
+  });
+
+  it("Adds and removes trusted routers as authority", async () => {
+    const router = Keypair.generate().publicKey;
+    const addTrustedRouter = (signer: Keypair) =>
+      program.methods
+        .addTrustedRouter(router)
+        .accounts({ adapterRegistry, authority: signer.publicKey })
+        .signers([signer])
+        .rpc();
+
+    try {
+      await addTrustedRouter(unauthorized);
+      assert.fail("Should have failed with unauthorized account");
+    } catch (error) {
+      assert.include(error.message, "InvalidAuthority");
+    }
+
+    await addTrustedRouter(currentAuthority);
+    let registryAccount = await program.account.adapterRegistry.fetch(adapterRegistry);
+    assert.isTrue(registryAccount.trustedRouters.some((key) => key.equals(router)));
+
+    try {
+      await addTrustedRouter(currentAuthority);
+      assert.fail("Duplicate trusted router should be rejected");
+    } catch (error) {
+      assert.include(error.message, "TrustedRouterAlreadyExists");
+    }
+
+    await program.methods
+      .removeTrustedRouter(router)
+      .accounts({ adapterRegistry, authority: currentAuthority.publicKey })
+      .signers([currentAuthority])
+      .rpc();
+    registryAccount = await program.account.adapterRegistry.fetch(adapterRegistry);
+    assert.isFalse(registryAccount.trustedRouters.some((key) => key.equals(router)));
+
+    try {
+      await program.methods
+        .removeTrustedRouter(router)
+        .accounts({ adapterRegistry, authority: currentAuthority.publicKey })
+        .signers([currentAuthority])
+        .rpc();
+      assert.fail("Removing an unknown trusted router should be rejected");
+    } catch (error) {
+      assert.include(error.message, "TrustedRouterNotFound");
+    }
+  });
+
+  it("Sets the strict limit order price band as authority", async () => {
+    const setOrderPriceBand = (signer: Keypair, bandBps: number) =>
+      program.methods
+        .setOrderPriceBand(bandBps)
+        .accounts({ adapterRegistry, authority: signer.publicKey })
+        .signers([signer])
+        .rpc();
+
+    try {
+      await setOrderPriceBand(unauthorized, 1_000);
+      assert.fail("Should have failed with unauthorized account");
+    } catch (error) {
+      assert.include(error.message, "InvalidAuthority");
+    }
+
+    try {
+      await setOrderPriceBand(currentAuthority, 10_001);
+      assert.fail("Band above 10000 bps should be rejected");
+    } catch (error) {
+      assert.include(error.message, "InvalidOrderPriceBand");
+    }
+
+    await setOrderPriceBand(currentAuthority, 1_000);
+    let registryAccount = await program.account.adapterRegistry.fetch(adapterRegistry);
+    assert.equal(registryAccount.orderPriceBandBps, 1_000);
+
+    // 0 restores the default band
+    await setOrderPriceBand(currentAuthority, 0);
+    registryAccount = await program.account.adapterRegistry.fetch(adapterRegistry);
+    assert.equal(registryAccount.orderPriceBandBps, 0);
+  });
+
+  it("Sets a limit order execution fee schedule for a mint as authority", async () => {
+    const mint = Keypair.generate().publicKey;
+    const [feeSchedule] = PublicKey.findProgramAddressSync(
+      [Buffer.from("fee_schedule"), mint.toBuffer()],
+      program.programId
+    );
+    const tier = (maxInputAmount: number, feeBps: number) => ({
+      maxInputAmount: new BN(maxInputAmount),
+      feeBps,
+    });
+    const setFeeSchedule = (signer: Keypair, tiers: { maxInputAmount: BN; feeBps: number }[]) =>
+      program.methods
+        .setFeeSchedule(mint, tiers)
+        .accounts({ adapterRegistry, payer: signer.publicKey, authority: signer.publicKey })
+        .signers([signer])
+        .rpc();
+
+    try {
+      await setFeeSchedule(unauthorized, [tier(1_000, 30)]);
+      assert.fail("Should have failed with unauthorized account");
+    } catch (error) {
+      assert.include(error.message, "InvalidAuthority");
+    }
+
+    try {
+      await setFeeSchedule(currentAuthority, [tier(1_000, 30), tier(1_000, 20)]);
+      assert.fail("Tiers without strictly increasing amounts should be rejected");
+    } catch (error) {
+      assert.include(error.message, "InvalidFeeSchedule");
+    }
+
+    await setFeeSchedule(currentAuthority, [tier(1_000, 30), tier(1_000_000, 20)]);
+    let schedule = await program.account.feeSchedule.fetch(feeSchedule);
+    assert.ok(schedule.mint.equals(mint));
+    assert.equal(schedule.tierCount, 2);
+    assert.equal(schedule.tiers[0].maxInputAmount.toNumber(), 1_000);
+    assert.equal(schedule.tiers[1].feeBps, 20);
+
+    // Empty tiers disable the schedule
+    await setFeeSchedule(currentAuthority, []);
+    schedule = await program.account.feeSchedule.fetch(feeSchedule);
+    assert.equal(schedule.tierCount, 0);
+  });
+
+  it("Records admin actions in the admin log once it is created", async () => {
+    const [adminLog] = PublicKey.findProgramAddressSync(
+      [Buffer.from("admin_log")],
+      program.programId
+    );
+
+    try {
+      await program.methods
+        .initializeAdminLog()
+        .accounts({ adapterRegistry, payer: unauthorized.publicKey, authority: unauthorized.publicKey })
+        .signers([unauthorized])
+        .rpc();
+      assert.fail("Should have failed with unauthorized account");
+    } catch (error) {
+      assert.include(error.message, "InvalidAuthority");
+    }
+
+    await program.methods
+      .initializeAdminLog()
+      .accounts({ adapterRegistry, payer: currentAuthority.publicKey, authority: currentAuthority.publicKey })
+      .signers([currentAuthority])
+      .rpc();
+
+    const router = Keypair.generate().publicKey;
+    await program.methods
+      .addTrustedRouter(router)
+      .accounts({ adapterRegistry, authority: currentAuthority.publicKey })
+      .signers([currentAuthority])
+      .rpc();
+    await program.methods
+      .removeTrustedRouter(router)
+      .accounts({ adapterRegistry, authority: currentAuthority.publicKey })
+      .signers([currentAuthority])
+      .rpc();
+
+    const log = await program.account.adminLog.fetch(adminLog);
+    assert.equal(log.totalActions.toNumber(), 2);
+
+    // Newest first
+    const entries = await program.methods.getAdminLog(0).accounts({ adminLog }).view();
+    assert.equal(entries.length, 2);
+    assert.deepEqual(entries[0].action, { removeTrustedRouter: {} });
+    assert.deepEqual(entries[1].action, { addTrustedRouter: {} });
+    for (const entry of entries) {
+      assert.isTrue(entry.actor.equals(currentAuthority.publicKey));
+      assert.isTrue(entry.target.equals(router));
+    }
+
+    const rest = await program.methods.getAdminLog(1).accounts({ adminLog }).view();
+    assert.equal(rest.length, 1);
+    assert.deepEqual(rest[0].action, { addTrustedRouter: {} });
+  });
+
+  it("Describes the configuration state of adapters", async () => {
+    const registry = await program.account.adapterRegistry.fetch(adapterRegistry);
+    const swapTypes = [{ raydium: {} }, { meteora: {} }, { lifinity: {} }];
+    const expected = swapTypes.map((swapType) => {
+      const [name] = Object.keys(swapType);
+      if (name === "lifinity") {
+        return { notImplemented: {} };
+      }
+      if (!registry.supportedAdapters.some((adapter) => name in adapter.swapType)) {
+        return { notConfigured: {} };
+      }
+      return registry.pausedAdapters.some((paused) => name in paused) ? { disabled: {} } : { enabled: {} };
+    });
+
+    const statuses = await program.methods.describeAdapters(swapTypes).accounts({ adapterRegistry }).view();
+    assert.deepEqual(statuses, expected);
+  });
+
   it("Removes an operator as authority", async () => {
     try {
       await program.methods
-        .removeOperator(operator.publicKey)
+        .removeOperator(operator.publicKey, false)
         .accounts({
           adapterRegistry,
           authority: currentAuthority.publicKey,
@@ -1003,7 +1695,7 @@ describe("Flipper Swap Protocol - Adapter Registry Module", () => {
   it("Fails to remove operator with unauthorized account", async () => {
     try {
       await program.methods
-        .removeOperator(operator.publicKey)
+        .removeOperator(operator.publicKey, false)
         .accounts({
           adapterRegistry,
           authority: unauthorized.publicKey,
@@ -9,6 +9,7 @@ import {
 } from "@solana/web3.js";
 import {
   TOKEN_PROGRAM_ID,
+  TOKEN_2022_PROGRAM_ID,
   ASSOCIATED_TOKEN_PROGRAM_ID,
   createMint,
   mintTo,
@@ -16,9 +17,30 @@ import {
   createAssociatedTokenAccount,
   getAccount,
   getOrCreateAssociatedTokenAccount,
+  createAccount,
+  closeAccount,
+  createMintToInstruction,
+  ExtensionType,
+  getAccountLen,
+  getMintLen,
+  createInitializeTransferFeeConfigInstruction,
+  createInitializeMintInstruction,
+  ACCOUNT_SIZE,
+  approve,
+  setAuthority,
+  AuthorityType,
+  freezeAccount,
+  thawAccount,
 } from "@solana/spl-token";
 import { assert } from "chai";
 import { Flipper } from "../target/types/flipper";
+import { hasMockClock, mockClockAccount, setMockClock } from "./utils/mock_clock";
+import {
+  COMPUTE_BUDGETS,
+  ROUTE_TELEMETRY_TAG,
+  computeTelemetryRequired,
+  hasComputeTelemetry,
+} from "./utils/compute_telemetry";
 
 describe("Flipper Swap Protocol - Raydium Swap and Limit Orders", () => {
   const provider = anchor.AnchorProvider.env();
@@ -65,6 +87,30 @@ describe("Flipper Swap Protocol - Raydium Swap and Limit Orders", () => {
     return bytes;
   }
 
+  // FeeEvents emitted through self-CPI by a confirmed transaction
+  async function feeEventsOf(signature: string) {
+    return cpiEventsOf(signature, "FeeEvent");
+  }
+
+  // Events of the given name emitted through self-CPI by a confirmed transaction
+  async function cpiEventsOf(signature: string, name: string) {
+    const txInfo = await provider.connection.getTransaction(signature, {
+      commitment: "confirmed",
+      maxSupportedTransactionVersion: 0,
+    });
+    const accountKeys = txInfo.transaction.message.getAccountKeys();
+    return txInfo.meta.innerInstructions
+      .flatMap((inner) => inner.instructions)
+      .filter((ix) => accountKeys.get(ix.programIdIndex).equals(program.programId))
+      .map((ix) =>
+        program.coder.events.decode(
+          Buffer.from(anchor.utils.bytes.bs58.decode(ix.data).subarray(8)).toString("base64")
+        )
+      )
+      .filter((event) => event?.name === name)
+      .map((event) => event.data);
+  }
+
   before(async () => {
     wallet = provider.wallet as anchor.Wallet;
     admin = wallet.payer;
@@ -231,8 +277,21 @@ describe("Flipper Swap Protocol - Raydium Swap and Limit Orders", () => {
     // Setup mocks and adapters
     mockRaydiumProgramId = mockRaydiumProgram.programId;
 
-    const raydiumAmmConfigKeypair = Keypair.generate();
-    raydiumAmmConfig = raydiumAmmConfigKeypair.publicKey;
+    // amm_config must be owned by the Raydium program (checked by the adapter)
+    [raydiumAmmConfig] = PublicKey.findProgramAddressSync(
+      [Buffer.from("amm_config"), Buffer.from([0, 0])],
+      mockRaydiumProgramId
+    );
+    if (!(await provider.connection.getAccountInfo(raydiumAmmConfig))) {
+      await mockRaydiumProgram.methods
+        .createAmmConfig(0)
+        .accounts({
+          payer: wallet.publicKey,
+          ammConfig: raydiumAmmConfig,
+          systemProgram: SystemProgram.programId,
+        })
+        .rpc();
+    }
 
     // Check if adapter registry exists
     const registryInfo = await provider.connection.getAccountInfo(
@@ -480,7 +539,7 @@ describe("Flipper Swap Protocol - Raydium Swap and Limit Orders", () => {
     ).amount;
 
     await program.methods
-      .route(routePlan, inAmount, quotedOutAmount, slippageBps, platformFeeBps)
+      .route(routePlan, inAmount, quotedOutAmount, slippageBps, platformFeeBps, new BN(0), false, null, false, null)
       .accounts({
         adapterRegistry,
         vaultAuthority,
@@ -554,7 +613,7 @@ describe("Flipper Swap Protocol - Raydium Swap and Limit Orders", () => {
 
     // Initialize limit order and vault first (for standard tokens, account_space = 0)
     await program.methods
-      .initLimitOrder(nonce, 0)
+      .initLimitOrder(nonce, 0, false)
       .accounts({
         vaultAuthority,
         limitOrder,
@@ -562,6 +621,7 @@ describe("Flipper Swap Protocol - Raydium Swap and Limit Orders", () => {
         inputMint: sourceMint,
         inputTokenProgram: TOKEN_PROGRAM_ID,
         creator: user.publicKey,
+        payer: user.publicKey,
         systemProgram: SystemProgram.programId,
         rent: SYSVAR_RENT_PUBKEY,
       })
@@ -580,7 +640,11 @@ describe("Flipper Swap Protocol - Raydium Swap and Limit Orders", () => {
         triggerPriceBps,
         triggerType,
         expiry,
-        slippageBps
+        slippageBps,
+        false,
+        null,
+        null,
+        new BN(0)
       )
       .accounts({
         vaultAuthority,
@@ -593,6 +657,7 @@ describe("Flipper Swap Protocol - Raydium Swap and Limit Orders", () => {
         inputTokenProgram: TOKEN_PROGRAM_ID,
         outputTokenProgram: TOKEN_PROGRAM_ID,
         creator: user.publicKey,
+        payer: user.publicKey,
         systemProgram: SystemProgram.programId,
       })
       .signers([user])
@@ -649,7 +714,7 @@ describe("Flipper Swap Protocol - Raydium Swap and Limit Orders", () => {
 
     // Initialize limit order and vault first (for standard tokens, account_space = 0)
     await program.methods
-      .initLimitOrder(nonce, 0)
+      .initLimitOrder(nonce, 0, false)
       .accounts({
         vaultAuthority,
         limitOrder,
@@ -657,6 +722,7 @@ describe("Flipper Swap Protocol - Raydium Swap and Limit Orders", () => {
         inputMint: sourceMint,
         inputTokenProgram: TOKEN_PROGRAM_ID,
         creator: user.publicKey,
+        payer: user.publicKey,
         systemProgram: SystemProgram.programId,
         rent: SYSVAR_RENT_PUBKEY,
       })
@@ -671,7 +737,11 @@ describe("Flipper Swap Protocol - Raydium Swap and Limit Orders", () => {
         triggerPriceBps,
         triggerType,
         expiry,
-        slippageBps
+        slippageBps,
+        false,
+        null,
+        null,
+        new BN(0)
       )
       .accounts({
         vaultAuthority,
@@ -684,6 +754,7 @@ describe("Flipper Swap Protocol - Raydium Swap and Limit Orders", () => {
         inputTokenProgram: TOKEN_PROGRAM_ID,
         outputTokenProgram: TOKEN_PROGRAM_ID,
         creator: user.publicKey,
+        payer: user.publicKey,
         systemProgram: SystemProgram.programId,
       })
       .signers([user])
@@ -727,8 +798,53 @@ describe("Flipper Swap Protocol - Raydium Swap and Limit Orders", () => {
       await getAccount(provider.connection, userDestinationTokenAccount)
     ).amount;
 
-    await program.methods
-      .executeLimitOrder(routePlan, quotedOutAmount, platformFeeBps)
+    // Dry-run with the same route and accounts resolves the destination vault without moving funds
+    const check = await program.methods
+      .validateOrderExecution(routePlan, quotedOutAmount)
+      .accounts({
+        adapterRegistry,
+        vaultAuthority,
+        limitOrder,
+        inputVault: orderVault,
+        inputTokenProgram: TOKEN_PROGRAM_ID,
+        outputTokenProgram: TOKEN_PROGRAM_ID,
+        inputMint: sourceMint,
+        outputMint: destinationMint,
+      })
+      .remainingAccounts(remainingAccounts)
+      .view();
+    assert(check.order.equals(limitOrder));
+    assert(check.destinationVault.equals(outputVault));
+    assert.equal(
+      (await getAccount(provider.connection, userDestinationTokenAccount)).amount,
+      initialDestBalance,
+      "Dry-run should not move funds"
+    );
+    try {
+      await program.methods
+        .validateOrderExecution(
+          [{ ...routePlan[0], inputIndex: 13 }],
+          quotedOutAmount
+        )
+        .accounts({
+          adapterRegistry,
+          vaultAuthority,
+          limitOrder,
+          inputVault: orderVault,
+          inputTokenProgram: TOKEN_PROGRAM_ID,
+          outputTokenProgram: TOKEN_PROGRAM_ID,
+          inputMint: sourceMint,
+          outputMint: destinationMint,
+        })
+        .remainingAccounts(remainingAccounts)
+        .view();
+      assert.fail("Dry-run should reject a route that does not read from the order vault");
+    } catch (error) {
+      assert.include(error.toString(), "InvalidVaultAddress");
+    }
+
+    const signature = await program.methods
+      .executeLimitOrder(routePlan, quotedOutAmount, platformFeeBps, null, null, null)
       .accounts({
         adapterRegistry,
         vaultAuthority,
@@ -745,7 +861,7 @@ describe("Flipper Swap Protocol - Raydium Swap and Limit Orders", () => {
       })
       .remainingAccounts(remainingAccounts)
       .signers([operator])
-      .rpc();
+      .rpc({ commitment: "confirmed" });
 
     const finalDestBalance = (
       await getAccount(provider.connection, userDestinationTokenAccount)
@@ -755,6 +871,11 @@ describe("Flipper Swap Protocol - Raydium Swap and Limit Orders", () => {
       "Destination balance should increase"
     );
 
+    // The platform fee is attributed to the limit order flow
+    const [feeEvent] = await feeEventsOf(signature);
+    assert.deepEqual(feeEvent.source, { limitOrder: {} });
+    assert(feeEvent.mint.equals(destinationMint));
+
     // Verify order account is closed after execution (rent goes to operator)
     const orderAccountInfo = await provider.connection.getAccountInfo(
       limitOrder
@@ -794,7 +915,7 @@ describe("Flipper Swap Protocol - Raydium Swap and Limit Orders", () => {
 
     // Initialize limit order and vault first (for standard tokens, account_space = 0)
     await program.methods
-      .initLimitOrder(nonce, 0)
+      .initLimitOrder(nonce, 0, false)
       .accounts({
         vaultAuthority,
         limitOrder,
@@ -802,6 +923,7 @@ describe("Flipper Swap Protocol - Raydium Swap and Limit Orders", () => {
         inputMint: sourceMint,
         inputTokenProgram: TOKEN_PROGRAM_ID,
         creator: user.publicKey,
+        payer: user.publicKey,
         systemProgram: SystemProgram.programId,
         rent: SYSVAR_RENT_PUBKEY,
       })
@@ -817,7 +939,11 @@ describe("Flipper Swap Protocol - Raydium Swap and Limit Orders", () => {
         triggerPriceBps,
         triggerType,
         expiry,
-        slippageBps
+        slippageBps,
+        false,
+        null,
+        null,
+        new BN(0)
       )
       .accounts({
         vaultAuthority,
@@ -830,6 +956,7 @@ describe("Flipper Swap Protocol - Raydium Swap and Limit Orders", () => {
         inputTokenProgram: TOKEN_PROGRAM_ID,
         outputTokenProgram: TOKEN_PROGRAM_ID,
         creator: user.publicKey,
+        payer: user.publicKey,
         systemProgram: SystemProgram.programId,
       })
       .signers([user])
@@ -897,7 +1024,7 @@ describe("Flipper Swap Protocol - Raydium Swap and Limit Orders", () => {
 
     // Initialize limit order and vault first (for standard tokens, account_space = 0)
     await program.methods
-      .initLimitOrder(orderNonce, 0)
+      .initLimitOrder(orderNonce, 0, false)
       .accounts({
         vaultAuthority,
         limitOrder,
@@ -905,6 +1032,7 @@ describe("Flipper Swap Protocol - Raydium Swap and Limit Orders", () => {
         inputMint: destinationMint, // For route_and_create_order, vault holds output_mint tokens
         inputTokenProgram: TOKEN_PROGRAM_ID,
         creator: user.publicKey,
+        payer: user.publicKey,
         systemProgram: SystemProgram.programId,
         rent: SYSVAR_RENT_PUBKEY,
       })
@@ -952,7 +1080,7 @@ describe("Flipper Swap Protocol - Raydium Swap and Limit Orders", () => {
     ).amount;
 
     // Execute route_and_create_order
-    await program.methods
+    const signature = await program.methods
       .routeAndCreateOrder(
         orderNonce,
         routePlan,
@@ -963,7 +1091,8 @@ describe("Flipper Swap Protocol - Raydium Swap and Limit Orders", () => {
         orderMinOutputAmount,
         orderTriggerPriceBps,
         orderExpiry,
-        orderSlippageBps
+        orderSlippageBps,
+        null
       )
       .accounts({
         adapterRegistry,
@@ -982,7 +1111,7 @@ describe("Flipper Swap Protocol - Raydium Swap and Limit Orders", () => {
       })
       .remainingAccounts(remainingAccounts)
       .signers([user])
-      .rpc();
+      .rpc({ commitment: "confirmed" });
 
     const finalSourceBalance = (
       await getAccount(provider.connection, userSourceTokenAccount)
@@ -1008,6 +1137,9 @@ describe("Flipper Swap Protocol - Raydium Swap and Limit Orders", () => {
     ).amount;
     assert(orderVaultBalance > 0n, "Order vault should have tokens from swap");
 
+    const [feeEvent] = await feeEventsOf(signature);
+    assert.deepEqual(feeEvent.source, { routeAndCreateOrder: {} });
+
     const minSwapOut = swapQuotedOutAmount
       .mul(new BN(10000 - swapSlippageBps))
       .div(new BN(10000));
@@ -1112,7 +1244,7 @@ describe("Flipper Swap Protocol - Raydium Swap and Limit Orders", () => {
 
     // Initialize limit order and vault first (for standard tokens, account_space = 0)
     await program.methods
-      .initLimitOrder(nonce, 0)
+      .initLimitOrder(nonce, 0, false)
       .accounts({
         vaultAuthority,
         limitOrder,
@@ -1120,6 +1252,7 @@ describe("Flipper Swap Protocol - Raydium Swap and Limit Orders", () => {
         inputMint: sourceMint,
         inputTokenProgram: TOKEN_PROGRAM_ID,
         creator: user.publicKey,
+        payer: user.publicKey,
         systemProgram: SystemProgram.programId,
         rent: SYSVAR_RENT_PUBKEY,
       })
@@ -1134,7 +1267,11 @@ describe("Flipper Swap Protocol - Raydium Swap and Limit Orders", () => {
         triggerPriceBps,
         triggerType,
         expiry,
-        slippageBps
+        slippageBps,
+        false,
+        null,
+        null,
+        new BN(0)
       )
       .accounts({
         vaultAuthority,
@@ -1147,6 +1284,7 @@ describe("Flipper Swap Protocol - Raydium Swap and Limit Orders", () => {
         inputTokenProgram: TOKEN_PROGRAM_ID,
         outputTokenProgram: TOKEN_PROGRAM_ID,
         creator: user.publicKey,
+        payer: user.publicKey,
         systemProgram: SystemProgram.programId,
       })
       .signers([user])
@@ -1204,7 +1342,7 @@ describe("Flipper Swap Protocol - Raydium Swap and Limit Orders", () => {
     assert(priceRatio.lte(new BN(triggerRatio)), "Stop loss should trigger");
 
     await program.methods
-      .executeLimitOrder(routePlan, quotedOutAmount, platformFeeBps)
+      .executeLimitOrder(routePlan, quotedOutAmount, platformFeeBps, null, null, null)
       .accounts({
         adapterRegistry,
         vaultAuthority,
@@ -1244,4 +1382,4924 @@ describe("Flipper Swap Protocol - Raydium Swap and Limit Orders", () => {
 
     //console.log("✓ Stop loss order executed successfully");
   });
+
+  it("7. Execute limit order fails while Raydium adapter is paused", async () => {
+    const nonce = new BN(Date.now());
+    const inputAmount = new BN(50_000_000);
+    const minOutputAmount = new BN(30_000_000);
+    const triggerPriceBps = 500; // 5% price increase
+    const triggerType = { takeProfit: {} };
+    const expiry = new BN(Math.floor(Date.now() / 1000) + 3600);
+    const slippageBps = 300;
+
+    // Create order
+    const [limitOrder] = PublicKey.findProgramAddressSync(
+      [
+        Buffer.from("limit_order"),
+        user.publicKey.toBuffer(),
+        nonce.toArrayLike(Buffer, "le", 8),
+      ],
+      program.programId
+    );
+
+    // Vault now uses limit_order.key() as seed
+    const [orderVault] = PublicKey.findProgramAddressSync(
+      [Buffer.from("order_vault"), limitOrder.toBuffer()],
+      program.programId
+    );
+
+    // Initialize limit order and vault first (for standard tokens, account_space = 0)
+    await program.methods
+      .initLimitOrder(nonce, 0, false)
+      .accounts({
+        vaultAuthority,
+        limitOrder,
+        inputVault: orderVault,
+        inputMint: sourceMint,
+        inputTokenProgram: TOKEN_PROGRAM_ID,
+        creator: user.publicKey,
+        payer: user.publicKey,
+        systemProgram: SystemProgram.programId,
+        rent: SYSVAR_RENT_PUBKEY,
+      })
+      .signers([user])
+      .rpc();
+
+    await program.methods
+      .createLimitOrder(
+        nonce,
+        inputAmount,
+        minOutputAmount,
+        triggerPriceBps,
+        triggerType,
+        expiry,
+        slippageBps,
+        false,
+        null,
+        null,
+        new BN(0)
+      )
+      .accounts({
+        vaultAuthority,
+        limitOrder,
+        inputVault: orderVault,
+        userInputTokenAccount: userSourceTokenAccount,
+        userDestinationTokenAccount: userDestinationTokenAccount,
+        inputMint: sourceMint,
+        outputMint: destinationMint,
+        inputTokenProgram: TOKEN_PROGRAM_ID,
+        outputTokenProgram: TOKEN_PROGRAM_ID,
+        creator: user.publicKey,
+        payer: user.publicKey,
+        systemProgram: SystemProgram.programId,
+      })
+      .signers([user])
+      .rpc();
+
+    // Execute order
+    const quotedOutAmount = new BN(39_486_167);
+    const platformFeeBps = 10; // 0.1%
+
+    const routePlan = [
+      { swap: { raydium: {} }, percent: 100, inputIndex: 0, outputIndex: 13 },
+    ];
+
+    const inputPoolVault =
+      sourceMint.toString() < destinationMint.toString()
+        ? raydiumTokenAVault
+        : raydiumTokenBVault;
+    const outputPoolVault =
+      sourceMint.toString() < destinationMint.toString()
+        ? raydiumTokenBVault
+        : raydiumTokenAVault;
+
+    const remainingAccounts = [
+      { pubkey: orderVault, isWritable: true, isSigner: false },
+      { pubkey: raydiumPoolInfo, isWritable: true, isSigner: false },
+      { pubkey: raydiumPoolAuthority, isWritable: false, isSigner: false },
+      { pubkey: raydiumAmmConfig, isWritable: false, isSigner: false },
+      { pubkey: raydiumPoolState, isWritable: true, isSigner: false },
+      { pubkey: inputPoolVault, isWritable: true, isSigner: false },
+      { pubkey: outputPoolVault, isWritable: true, isSigner: false },
+      { pubkey: TOKEN_PROGRAM_ID, isWritable: false, isSigner: false },
+      { pubkey: TOKEN_PROGRAM_ID, isWritable: false, isSigner: false },
+      { pubkey: sourceMint, isWritable: false, isSigner: false },
+      { pubkey: destinationMint, isWritable: false, isSigner: false },
+      { pubkey: raydiumObservationState, isWritable: true, isSigner: false },
+      { pubkey: mockRaydiumProgramId, isWritable: false, isSigner: false },
+      { pubkey: outputVault, isWritable: true, isSigner: false },
+    ];
+
+    // Pause the adapter without removing it from the registry
+    await program.methods
+      .setAdapterEnabled({ raydium: {} }, false)
+      .accounts({
+        adapterRegistry,
+        operator: wallet.publicKey,
+      })
+      .signers([wallet.payer])
+      .rpc();
+
+    const registryAccount = await program.account.adapterRegistry.fetch(
+      adapterRegistry
+    );
+    const raydiumAdapter = registryAccount.supportedAdapters.find(
+      (adapter) => adapter.swapType.raydium !== undefined
+    );
+    assert.isDefined(raydiumAdapter, "Paused adapter should stay registered");
+    assert.isTrue(
+      registryAccount.pausedAdapters.some((swapType) => swapType.raydium !== undefined)
+    );
+
+    try {
+      await program.methods
+        .executeLimitOrder(routePlan, quotedOutAmount, platformFeeBps, null, null, null)
+        .accounts({
+          adapterRegistry,
+          vaultAuthority,
+          limitOrder,
+          inputVault: orderVault,
+          inputTokenProgram: TOKEN_PROGRAM_ID,
+          outputTokenProgram: TOKEN_PROGRAM_ID,
+          userDestinationTokenAccount,
+          inputMint: sourceMint,
+          outputMint: destinationMint,
+          platformFeeAccount,
+          operator: operator.publicKey,
+          systemProgram: SystemProgram.programId,
+        })
+        .remainingAccounts(remainingAccounts)
+        .signers([operator])
+        .rpc();
+      assert.fail("Execution through a paused adapter should fail");
+    } catch (error) {
+      assert.include(error.toString(), "AdapterDisabled");
+    }
+
+    const vaultBalance = (await getAccount(provider.connection, orderVault))
+      .amount;
+    assert.equal(
+      vaultBalance.toString(),
+      inputAmount.toString(),
+      "Order funds should stay in the vault"
+    );
+
+    // Resume the adapter so later suites can route through it
+    await program.methods
+      .setAdapterEnabled({ raydium: {} }, true)
+      .accounts({
+        adapterRegistry,
+        operator: wallet.publicKey,
+      })
+      .signers([wallet.payer])
+      .rpc();
+
+    const resumedRegistry = await program.account.adapterRegistry.fetch(
+      adapterRegistry
+    );
+    assert.isFalse(
+      resumedRegistry.pausedAdapters.some((swapType) => swapType.raydium !== undefined)
+    );
+  });
+
+  it("8. Cancel limit order refunds to any input mint account chosen by the creator", async () => {
+    const nonce = new BN(Date.now());
+    const inputAmount = new BN(50_000_000);
+    const minOutputAmount = new BN(45_000_000);
+    const triggerPriceBps = 1000;
+    const triggerType = { takeProfit: {} };
+    const expiry = new BN(Math.floor(Date.now() / 1000) + 3600);
+    const slippageBps = 300;
+
+    const [limitOrder] = PublicKey.findProgramAddressSync(
+      [
+        Buffer.from("limit_order"),
+        user.publicKey.toBuffer(),
+        nonce.toArrayLike(Buffer, "le", 8),
+      ],
+      program.programId
+    );
+
+    // Vault now uses limit_order.key() as seed
+    const [orderVault] = PublicKey.findProgramAddressSync(
+      [Buffer.from("order_vault"), limitOrder.toBuffer()],
+      program.programId
+    );
+
+    // Initialize limit order and vault first (for standard tokens, account_space = 0)
+    await program.methods
+      .initLimitOrder(nonce, 0, false)
+      .accounts({
+        vaultAuthority,
+        limitOrder,
+        inputVault: orderVault,
+        inputMint: sourceMint,
+        inputTokenProgram: TOKEN_PROGRAM_ID,
+        creator: user.publicKey,
+        payer: user.publicKey,
+        systemProgram: SystemProgram.programId,
+        rent: SYSVAR_RENT_PUBKEY,
+      })
+      .signers([user])
+      .rpc();
+
+    // Create order
+    await program.methods
+      .createLimitOrder(
+        nonce,
+        inputAmount,
+        minOutputAmount,
+        triggerPriceBps,
+        triggerType,
+        expiry,
+        slippageBps,
+        false,
+        null,
+        null,
+        new BN(0)
+      )
+      .accounts({
+        vaultAuthority,
+        limitOrder,
+        inputVault: orderVault,
+        userInputTokenAccount: userSourceTokenAccount,
+        userDestinationTokenAccount: userDestinationTokenAccount,
+        inputMint: sourceMint,
+        outputMint: destinationMint,
+        inputTokenProgram: TOKEN_PROGRAM_ID,
+        outputTokenProgram: TOKEN_PROGRAM_ID,
+        creator: user.publicKey,
+        payer: user.publicKey,
+        systemProgram: SystemProgram.programId,
+      })
+      .signers([user])
+      .rpc();
+
+    // Refund destination owned by someone other than the creator (e.g. a multisig)
+    const refundOwner = Keypair.generate();
+    const refundAccount = (
+      await getOrCreateAssociatedTokenAccount(
+        provider.connection,
+        wallet.payer,
+        sourceMint,
+        refundOwner.publicKey
+      )
+    ).address;
+
+    const cancelAccounts = (creator: PublicKey, userInputTokenAccount: PublicKey) => ({
+      vaultAuthority,
+      limitOrder,
+      inputVault: orderVault,
+      userInputTokenAccount,
+      inputMint: sourceMint,
+      inputTokenProgram: TOKEN_PROGRAM_ID,
+      creator,
+    });
+
+    // Only the creator's signature can choose the refund destination
+    const stranger = Keypair.generate();
+    try {
+      await program.methods
+        .cancelLimitOrder()
+        .accounts(cancelAccounts(stranger.publicKey, refundAccount))
+        .signers([stranger])
+        .rpc();
+      assert.fail("Non-creator should not be able to cancel");
+    } catch (error) {
+      assert.include(error.toString(), "UnauthorizedAdmin");
+    }
+
+    // The refund destination must still hold the order's input mint
+    try {
+      await program.methods
+        .cancelLimitOrder()
+        .accounts(cancelAccounts(user.publicKey, userDestinationTokenAccount))
+        .signers([user])
+        .rpc();
+      assert.fail("Refund account of another mint should be rejected");
+    } catch (error) {
+      assert.include(error.toString(), "InvalidMint");
+    }
+
+    const creatorBalanceBefore = (
+      await getAccount(provider.connection, userSourceTokenAccount)
+    ).amount;
+
+    await program.methods
+      .cancelLimitOrder()
+      .accounts(cancelAccounts(user.publicKey, refundAccount))
+      .signers([user])
+      .rpc();
+
+    const refundBalance = (await getAccount(provider.connection, refundAccount))
+      .amount;
+    assert.equal(
+      refundBalance.toString(),
+      inputAmount.toString(),
+      "Tokens should be refunded to the chosen account"
+    );
+    const creatorBalanceAfter = (
+      await getAccount(provider.connection, userSourceTokenAccount)
+    ).amount;
+    assert.equal(
+      creatorBalanceAfter.toString(),
+      creatorBalanceBefore.toString(),
+      "Creator's own account should be untouched"
+    );
+
+    const orderAccountInfo = await provider.connection.getAccountInfo(
+      limitOrder
+    );
+    assert.equal(orderAccountInfo, null, "Order account should be closed");
+  });
+
+  it("9. Limit order records token programs and rejects a different one", async () => {
+    const nonce = new BN(Date.now());
+    const inputAmount = new BN(10_000_000);
+
+    const [limitOrder] = PublicKey.findProgramAddressSync(
+      [
+        Buffer.from("limit_order"),
+        user.publicKey.toBuffer(),
+        nonce.toArrayLike(Buffer, "le", 8),
+      ],
+      program.programId
+    );
+    const [orderVault] = PublicKey.findProgramAddressSync(
+      [Buffer.from("order_vault"), limitOrder.toBuffer()],
+      program.programId
+    );
+
+    await program.methods
+      .initLimitOrder(nonce, 0, false)
+      .accounts({
+        vaultAuthority,
+        limitOrder,
+        inputVault: orderVault,
+        inputMint: sourceMint,
+        inputTokenProgram: TOKEN_PROGRAM_ID,
+        creator: user.publicKey,
+        payer: user.publicKey,
+        systemProgram: SystemProgram.programId,
+        rent: SYSVAR_RENT_PUBKEY,
+      })
+      .signers([user])
+      .rpc();
+
+    await program.methods
+      .createLimitOrder(
+        nonce,
+        inputAmount,
+        new BN(9_000_000),
+        1000,
+        { takeProfit: {} },
+        new BN(Math.floor(Date.now() / 1000) + 3600),
+        300,
+        false,
+        null,
+        null,
+        new BN(0)
+      )
+      .accounts({
+        vaultAuthority,
+        limitOrder,
+        inputVault: orderVault,
+        userInputTokenAccount: userSourceTokenAccount,
+        userDestinationTokenAccount: userDestinationTokenAccount,
+        inputMint: sourceMint,
+        outputMint: destinationMint,
+        inputTokenProgram: TOKEN_PROGRAM_ID,
+        outputTokenProgram: TOKEN_PROGRAM_ID,
+        creator: user.publicKey,
+        payer: user.publicKey,
+        systemProgram: SystemProgram.programId,
+      })
+      .signers([user])
+      .rpc();
+
+    const order = await program.account.limitOrder.fetch(limitOrder);
+    assert.ok(order.inputTokenProgram.equals(TOKEN_PROGRAM_ID));
+    assert.ok(order.outputTokenProgram.equals(TOKEN_PROGRAM_ID));
+
+    const cancelAccounts = (inputTokenProgram: PublicKey) => ({
+      vaultAuthority,
+      limitOrder,
+      inputVault: orderVault,
+      userInputTokenAccount: userSourceTokenAccount,
+      inputMint: sourceMint,
+      inputTokenProgram,
+      creator: user.publicKey,
+    });
+
+    try {
+      await program.methods
+        .cancelLimitOrder()
+        .accounts(cancelAccounts(TOKEN_2022_PROGRAM_ID))
+        .signers([user])
+        .rpc();
+      assert.fail("Token program other than the recorded one should be rejected");
+    } catch (error) {
+      assert.include(error.toString(), "InvalidTokenProgram");
+    }
+
+    await program.methods
+      .cancelLimitOrder()
+      .accounts(cancelAccounts(TOKEN_PROGRAM_ID))
+      .signers([user])
+      .rpc();
+  });
+
+  it("10. Order creation fee is paid to the treasury and can be disabled", async () => {
+    const feeLamports = 1_000_000;
+    const feeTreasury = Keypair.generate().publicKey;
+
+    const setOrderCreationFee = (fee: number, treasuryKey: PublicKey) =>
+      program.methods
+        .setOrderCreationFee(new BN(fee), treasuryKey)
+        .accounts({ adapterRegistry, authority: wallet.publicKey })
+        .signers([wallet.payer])
+        .rpc();
+
+    // Inits an order and returns a function creating it with the given treasury
+    const prepareOrder = async () => {
+      const nonce = new BN(Date.now());
+      const [limitOrder] = PublicKey.findProgramAddressSync(
+        [
+          Buffer.from("limit_order"),
+          user.publicKey.toBuffer(),
+          nonce.toArrayLike(Buffer, "le", 8),
+        ],
+        program.programId
+      );
+      const [orderVault] = PublicKey.findProgramAddressSync(
+        [Buffer.from("order_vault"), limitOrder.toBuffer()],
+        program.programId
+      );
+      await program.methods
+        .initLimitOrder(nonce, 0, false)
+        .accounts({
+          vaultAuthority,
+          limitOrder,
+          inputVault: orderVault,
+          inputMint: sourceMint,
+          inputTokenProgram: TOKEN_PROGRAM_ID,
+          creator: user.publicKey,
+          payer: user.publicKey,
+          systemProgram: SystemProgram.programId,
+          rent: SYSVAR_RENT_PUBKEY,
+        })
+        .signers([user])
+        .rpc();
+
+      return (treasuryAccount: PublicKey | null) =>
+        program.methods
+          .createLimitOrder(
+            nonce,
+            new BN(1_000_000),
+            new BN(900_000),
+            1000,
+            { takeProfit: {} },
+            new BN(Math.floor(Date.now() / 1000) + 3600),
+            300,
+            false,
+            null,
+            null,
+            new BN(0)
+          )
+          .accounts({
+            vaultAuthority,
+            adapterRegistry,
+            limitOrder,
+            inputVault: orderVault,
+            userInputTokenAccount: userSourceTokenAccount,
+            userDestinationTokenAccount: userDestinationTokenAccount,
+            inputMint: sourceMint,
+            outputMint: destinationMint,
+            inputTokenProgram: TOKEN_PROGRAM_ID,
+            outputTokenProgram: TOKEN_PROGRAM_ID,
+            treasury: treasuryAccount,
+            creator: user.publicKey,
+            payer: user.publicKey,
+            systemProgram: SystemProgram.programId,
+          })
+          .signers([user])
+          .rpc();
+    };
+
+    await setOrderCreationFee(feeLamports, feeTreasury);
+    try {
+      const createOrder = await prepareOrder();
+
+      // The treasury is required while the fee is enabled
+      try {
+        await createOrder(null);
+        assert.fail("Order creation without the treasury should be rejected");
+      } catch (error) {
+        assert.include(error.toString(), "InvalidTreasury");
+      }
+
+      const creatorBalanceBefore = await provider.connection.getBalance(user.publicKey);
+      await createOrder(feeTreasury);
+      const creatorBalanceAfter = await provider.connection.getBalance(user.publicKey);
+
+      assert.equal(await provider.connection.getBalance(feeTreasury), feeLamports);
+      // The creator pays the fee on top of the transaction fee
+      assert.isAtLeast(creatorBalanceBefore - creatorBalanceAfter, feeLamports);
+    } finally {
+      await setOrderCreationFee(0, PublicKey.default);
+    }
+
+    // Disabled: no treasury needed and nothing is charged
+    const createOrder = await prepareOrder();
+    await createOrder(null);
+    assert.equal(await provider.connection.getBalance(feeTreasury), feeLamports);
+
+    const registryAccount = await program.account.adapterRegistry.fetch(adapterRegistry);
+    assert.equal(registryAccount.orderCreationFeeLamports.toNumber(), 0);
+  });
+
+  it("11. Execute limit order delivers to the creator ATA when the recorded destination is closed", async () => {
+    const nonce = new BN(Date.now());
+    const [limitOrder] = PublicKey.findProgramAddressSync(
+      [
+        Buffer.from("limit_order"),
+        user.publicKey.toBuffer(),
+        nonce.toArrayLike(Buffer, "le", 8),
+      ],
+      program.programId
+    );
+    const [orderVault] = PublicKey.findProgramAddressSync(
+      [Buffer.from("order_vault"), limitOrder.toBuffer()],
+      program.programId
+    );
+
+    // Record a non-ATA destination on the order, then close it before execution
+    const recordedDestination = await createAccount(
+      provider.connection,
+      user,
+      destinationMint,
+      user.publicKey,
+      Keypair.generate(),
+      undefined,
+      TOKEN_PROGRAM_ID
+    );
+
+    await program.methods
+      .initLimitOrder(nonce, 0, false)
+      .accounts({
+        vaultAuthority,
+        limitOrder,
+        inputVault: orderVault,
+        inputMint: sourceMint,
+        inputTokenProgram: TOKEN_PROGRAM_ID,
+        creator: user.publicKey,
+        payer: user.publicKey,
+        systemProgram: SystemProgram.programId,
+        rent: SYSVAR_RENT_PUBKEY,
+      })
+      .signers([user])
+      .rpc();
+
+    await program.methods
+      .createLimitOrder(
+        nonce,
+        new BN(10_000_000),
+        new BN(1_000_000),
+        500,
+        { takeProfit: {} },
+        new BN(Math.floor(Date.now() / 1000) + 3600),
+        300,
+        false,
+        null,
+        null,
+        new BN(0)
+      )
+      .accounts({
+        vaultAuthority,
+        limitOrder,
+        inputVault: orderVault,
+        userInputTokenAccount: userSourceTokenAccount,
+        userDestinationTokenAccount: recordedDestination,
+        inputMint: sourceMint,
+        outputMint: destinationMint,
+        inputTokenProgram: TOKEN_PROGRAM_ID,
+        outputTokenProgram: TOKEN_PROGRAM_ID,
+        creator: user.publicKey,
+        payer: user.publicKey,
+        systemProgram: SystemProgram.programId,
+      })
+      .signers([user])
+      .rpc();
+
+    await closeAccount(
+      provider.connection,
+      user,
+      recordedDestination,
+      user.publicKey,
+      user,
+      [],
+      undefined,
+      TOKEN_PROGRAM_ID
+    );
+
+    const routePlan = [
+      { swap: { raydium: {} }, percent: 100, inputIndex: 0, outputIndex: 13 },
+    ];
+    const inputPoolVault =
+      sourceMint.toString() < destinationMint.toString()
+        ? raydiumTokenAVault
+        : raydiumTokenBVault;
+    const outputPoolVault =
+      sourceMint.toString() < destinationMint.toString()
+        ? raydiumTokenBVault
+        : raydiumTokenAVault;
+    const remainingAccounts = [
+      { pubkey: orderVault, isWritable: true, isSigner: false },
+      { pubkey: raydiumPoolInfo, isWritable: true, isSigner: false },
+      { pubkey: raydiumPoolAuthority, isWritable: false, isSigner: false },
+      { pubkey: raydiumAmmConfig, isWritable: false, isSigner: false },
+      { pubkey: raydiumPoolState, isWritable: true, isSigner: false },
+      { pubkey: inputPoolVault, isWritable: true, isSigner: false },
+      { pubkey: outputPoolVault, isWritable: true, isSigner: false },
+      { pubkey: TOKEN_PROGRAM_ID, isWritable: false, isSigner: false },
+      { pubkey: TOKEN_PROGRAM_ID, isWritable: false, isSigner: false },
+      { pubkey: sourceMint, isWritable: false, isSigner: false },
+      { pubkey: destinationMint, isWritable: false, isSigner: false },
+      { pubkey: raydiumObservationState, isWritable: true, isSigner: false },
+      { pubkey: mockRaydiumProgramId, isWritable: false, isSigner: false },
+      { pubkey: outputVault, isWritable: true, isSigner: false },
+    ];
+
+    const execute = (fallback: {
+      creatorDestinationAta: PublicKey | null;
+      creator: PublicKey | null;
+      associatedTokenProgram: PublicKey | null;
+    }) =>
+      program.methods
+        .executeLimitOrder(routePlan, new BN(1_100_000), 0, null, null, null)
+        .accounts({
+          adapterRegistry,
+          vaultAuthority,
+          limitOrder,
+          inputVault: orderVault,
+          inputTokenProgram: TOKEN_PROGRAM_ID,
+          outputTokenProgram: TOKEN_PROGRAM_ID,
+          userDestinationTokenAccount: recordedDestination,
+          inputMint: sourceMint,
+          outputMint: destinationMint,
+          platformFeeAccount: null,
+          ...fallback,
+          operator: operator.publicKey,
+          systemProgram: SystemProgram.programId,
+        })
+        .remainingAccounts(remainingAccounts)
+        .signers([operator])
+        .rpc();
+
+    try {
+      await execute({ creatorDestinationAta: null, creator: null, associatedTokenProgram: null });
+      assert.fail("Execution without a creator ATA should be rejected");
+    } catch (error) {
+      assert.include(error.toString(), "MissingDestinationAccount");
+    }
+
+    // Any other token account of the creator is not an accepted fallback
+    const otherAccount = await createAccount(
+      provider.connection,
+      user,
+      destinationMint,
+      user.publicKey,
+      Keypair.generate(),
+      undefined,
+      TOKEN_PROGRAM_ID
+    );
+    try {
+      await execute({
+        creatorDestinationAta: otherAccount,
+        creator: user.publicKey,
+        associatedTokenProgram: ASSOCIATED_TOKEN_PROGRAM_ID,
+      });
+      assert.fail("A non-ATA fallback destination should be rejected");
+    } catch (error) {
+      assert.include(error.toString(), "InvalidDestinationAccount");
+    }
+
+    const creatorAta = getAssociatedTokenAddressSync(destinationMint, user.publicKey);
+    const initialAtaBalance = (await getAccount(provider.connection, creatorAta)).amount;
+
+    await execute({
+      creatorDestinationAta: creatorAta,
+      creator: user.publicKey,
+      associatedTokenProgram: ASSOCIATED_TOKEN_PROGRAM_ID,
+    });
+
+    const finalAtaBalance = (await getAccount(provider.connection, creatorAta)).amount;
+    assert(finalAtaBalance > initialAtaBalance, "Creator ATA balance should increase");
+    assert.isNull(await provider.connection.getAccountInfo(limitOrder));
+  });
+
+  it("12. Route and route_with_fallback with in_amount u64::MAX sweep the current source balance", async () => {
+    const sweepAmount = 30_000_000n;
+    const depositAmount = 12_000_000n;
+
+    // Fresh source account so the sweep only moves what this test deposits
+    const sweepSource = await createAccount(
+      provider.connection,
+      user,
+      sourceMint,
+      user.publicKey,
+      Keypair.generate(),
+      undefined,
+      TOKEN_PROGRAM_ID
+    );
+    await mintTo(
+      provider.connection,
+      wallet.payer,
+      sourceMint,
+      sweepSource,
+      wallet.publicKey,
+      sweepAmount
+    );
+
+    const routePlan = [
+      { swap: { raydium: {} }, percent: 100, inputIndex: 0, outputIndex: 13 },
+    ];
+    const [inputPoolVault, outputPoolVault] =
+      sourceMint.toString() < destinationMint.toString()
+        ? [raydiumTokenAVault, raydiumTokenBVault]
+        : [raydiumTokenBVault, raydiumTokenAVault];
+    const remainingAccounts = [
+      { pubkey: inputVault, isWritable: true, isSigner: false },
+      { pubkey: raydiumPoolInfo, isWritable: true, isSigner: false },
+      { pubkey: raydiumPoolAuthority, isWritable: false, isSigner: false },
+      { pubkey: raydiumAmmConfig, isWritable: false, isSigner: false },
+      { pubkey: raydiumPoolState, isWritable: true, isSigner: false },
+      { pubkey: inputPoolVault, isWritable: true, isSigner: false },
+      { pubkey: outputPoolVault, isWritable: true, isSigner: false },
+      { pubkey: TOKEN_PROGRAM_ID, isWritable: false, isSigner: false },
+      { pubkey: TOKEN_PROGRAM_ID, isWritable: false, isSigner: false },
+      { pubkey: sourceMint, isWritable: false, isSigner: false },
+      { pubkey: destinationMint, isWritable: false, isSigner: false },
+      { pubkey: raydiumObservationState, isWritable: true, isSigner: false },
+      { pubkey: mockRaydiumProgramId, isWritable: false, isSigner: false },
+      { pubkey: outputVault, isWritable: true, isSigner: false },
+    ];
+    const sweepIx = () =>
+      program.methods
+        .route(routePlan, new BN("18446744073709551615"), new BN(1), 100, 0, new BN(0), false, null, false, null)
+        .accounts({
+          adapterRegistry,
+          vaultAuthority,
+          inputTokenProgram: TOKEN_PROGRAM_ID,
+          outputTokenProgram: TOKEN_PROGRAM_ID,
+          userTransferAuthority: user.publicKey,
+          userSourceTokenAccount: sweepSource,
+          userDestinationTokenAccount,
+          sourceMint,
+          destinationMint,
+          platformFeeAccount: null,
+          systemProgram: SystemProgram.programId,
+        })
+        .remainingAccounts(remainingAccounts)
+        .instruction();
+
+    const initialDest = (await getAccount(provider.connection, userDestinationTokenAccount)).amount;
+
+    // A deposit landing earlier in the same transaction is swept as well
+    const tx = new Transaction().add(
+      createMintToInstruction(sourceMint, sweepSource, wallet.publicKey, depositAmount),
+      await sweepIx()
+    );
+    const signature = await provider.sendAndConfirm(tx, [wallet.payer, user], { commitment: "confirmed" });
+
+    assert.equal((await getAccount(provider.connection, sweepSource)).amount.toString(), "0");
+    const finalDest = (await getAccount(provider.connection, userDestinationTokenAccount)).amount;
+    assert(finalDest > initialDest, "Destination balance should increase");
+
+    // Return data is RouteAmounts { in_amount, out_amount }
+    const txInfo = await provider.connection.getTransaction(signature, {
+      commitment: "confirmed",
+      maxSupportedTransactionVersion: 0,
+    });
+    const returnData = Buffer.from(txInfo.meta.returnData.data[0], "base64");
+    assert.equal(txInfo.meta.returnData.programId, program.programId.toBase58());
+    assert.equal(
+      new BN(returnData.subarray(0, 8), "le").toString(),
+      (sweepAmount + depositAmount).toString()
+    );
+    assert.equal(
+      new BN(returnData.subarray(8, 16), "le").toString(),
+      (finalDest - initialDest).toString()
+    );
+
+    // Sweeping an empty account is rejected
+    try {
+      await provider.sendAndConfirm(new Transaction().add(await sweepIx()), [user]);
+      assert.fail("Sweeping an empty source account should be rejected");
+    } catch (error) {
+      assert.include(error.toString(), "InvalidAmount");
+    }
+
+    // route_with_fallback resolves the sentinel the same way
+    await mintTo(
+      provider.connection,
+      wallet.payer,
+      sourceMint,
+      sweepSource,
+      wallet.publicKey,
+      depositAmount
+    );
+    const fallbackInitialDest = (await getAccount(provider.connection, userDestinationTokenAccount)).amount;
+    await program.methods
+      .routeWithFallback([routePlan], new BN("18446744073709551615"), [new BN(1)], 100, 0, new BN(0), false, null, false)
+      .accounts({
+        adapterRegistry,
+        vaultAuthority,
+        inputTokenProgram: TOKEN_PROGRAM_ID,
+        outputTokenProgram: TOKEN_PROGRAM_ID,
+        userTransferAuthority: user.publicKey,
+        userSourceTokenAccount: sweepSource,
+        userDestinationTokenAccount,
+        sourceMint,
+        destinationMint,
+        platformFeeAccount: null,
+        systemProgram: SystemProgram.programId,
+      })
+      .remainingAccounts(remainingAccounts)
+      .signers([user])
+      .rpc();
+    assert.equal((await getAccount(provider.connection, sweepSource)).amount.toString(), "0");
+    const fallbackFinalDest = (await getAccount(provider.connection, userDestinationTokenAccount)).amount;
+    assert(fallbackFinalDest > fallbackInitialDest, "Destination balance should increase");
+  });
+
+  it("13. Create limit order with input_amount u64::MAX escrows the whole balance, net of transfer fees", async () => {
+    const sweepAll = new BN("18446744073709551615");
+
+    // Inits an order for the mint and creates it from `source`, sweeping its balance
+    const createSweepOrder = async (
+      source: PublicKey,
+      inputMint: PublicKey,
+      inputTokenProgram: PublicKey,
+      accountSpace: number
+    ) => {
+      const nonce = new BN(Date.now());
+      const [limitOrder] = PublicKey.findProgramAddressSync(
+        [
+          Buffer.from("limit_order"),
+          user.publicKey.toBuffer(),
+          nonce.toArrayLike(Buffer, "le", 8),
+        ],
+        program.programId
+      );
+      const [orderVault] = PublicKey.findProgramAddressSync(
+        [Buffer.from("order_vault"), limitOrder.toBuffer()],
+        program.programId
+      );
+      await program.methods
+        .initLimitOrder(nonce, accountSpace, false)
+        .accounts({
+          vaultAuthority,
+          limitOrder,
+          inputVault: orderVault,
+          inputMint,
+          inputTokenProgram,
+          creator: user.publicKey,
+          payer: user.publicKey,
+          systemProgram: SystemProgram.programId,
+          rent: SYSVAR_RENT_PUBKEY,
+        })
+        .signers([user])
+        .rpc();
+      await program.methods
+        .createLimitOrder(
+          nonce,
+          sweepAll,
+          new BN(1_000_000),
+          1000,
+          { takeProfit: {} },
+          new BN(Math.floor(Date.now() / 1000) + 3600),
+          300,
+          false,
+          null,
+          null,
+          new BN(0)
+        )
+        .accounts({
+          vaultAuthority,
+          limitOrder,
+          inputVault: orderVault,
+          userInputTokenAccount: source,
+          userDestinationTokenAccount,
+          inputMint,
+          outputMint: destinationMint,
+          inputTokenProgram,
+          outputTokenProgram: TOKEN_PROGRAM_ID,
+          creator: user.publicKey,
+          payer: user.publicKey,
+          systemProgram: SystemProgram.programId,
+        })
+        .signers([user])
+        .rpc();
+      return { limitOrder, orderVault };
+    };
+
+    // Standard mint: the order records the full wallet balance
+    const sweepSource = await createAccount(
+      provider.connection,
+      user,
+      sourceMint,
+      user.publicKey,
+      Keypair.generate(),
+      undefined,
+      TOKEN_PROGRAM_ID
+    );
+    await mintTo(provider.connection, wallet.payer, sourceMint, sweepSource, wallet.publicKey, 20_000_000);
+
+    const { limitOrder } = await createSweepOrder(sweepSource, sourceMint, TOKEN_PROGRAM_ID, 0);
+    assert.equal((await getAccount(provider.connection, sweepSource)).amount.toString(), "0");
+    const order = await program.account.limitOrder.fetch(limitOrder);
+    assert.equal(order.inputAmount.toString(), "20000000");
+
+    // Sweeping the now empty account is rejected
+    try {
+      await createSweepOrder(sweepSource, sourceMint, TOKEN_PROGRAM_ID, 0);
+      assert.fail("Sweeping an empty input account should be rejected");
+    } catch (error) {
+      assert.include(error.toString(), "InvalidAmount");
+    }
+
+    // Token-2022 mint with a 1% transfer fee: the order records what reached the vault
+    const feeMint = Keypair.generate();
+    const mintLen = getMintLen([ExtensionType.TransferFeeConfig]);
+    await provider.sendAndConfirm(
+      new Transaction().add(
+        SystemProgram.createAccount({
+          fromPubkey: wallet.publicKey,
+          newAccountPubkey: feeMint.publicKey,
+          space: mintLen,
+          lamports: await provider.connection.getMinimumBalanceForRentExemption(mintLen),
+          programId: TOKEN_2022_PROGRAM_ID,
+        }),
+        createInitializeTransferFeeConfigInstruction(
+          feeMint.publicKey,
+          wallet.publicKey,
+          wallet.publicKey,
+          100,
+          BigInt(1_000_000_000),
+          TOKEN_2022_PROGRAM_ID
+        ),
+        createInitializeMintInstruction(feeMint.publicKey, 9, wallet.publicKey, null, TOKEN_2022_PROGRAM_ID)
+      ),
+      [wallet.payer, feeMint]
+    );
+    const feeSource = await createAssociatedTokenAccount(
+      provider.connection,
+      wallet.payer,
+      feeMint.publicKey,
+      user.publicKey,
+      undefined,
+      TOKEN_2022_PROGRAM_ID
+    );
+    await mintTo(
+      provider.connection,
+      wallet.payer,
+      feeMint.publicKey,
+      feeSource,
+      wallet.publicKey,
+      10_000_000,
+      [],
+      undefined,
+      TOKEN_2022_PROGRAM_ID
+    );
+
+    const vaultSpace = getAccountLen([ExtensionType.TransferFeeAmount]) - ACCOUNT_SIZE;
+    const feeOrder = await createSweepOrder(feeSource, feeMint.publicKey, TOKEN_2022_PROGRAM_ID, vaultSpace);
+    const feeVault = await getAccount(provider.connection, feeOrder.orderVault, undefined, TOKEN_2022_PROGRAM_ID);
+    const feeOrderAccount = await program.account.limitOrder.fetch(feeOrder.limitOrder);
+    assert.equal(feeVault.amount.toString(), "9900000");
+    assert.equal(feeOrderAccount.inputAmount.toString(), "9900000");
+  });
+
+  it("14. Executing a limit order refreshes the operator's last_active", async () => {
+    const [operatorStats] = PublicKey.findProgramAddressSync(
+      [Buffer.from("operator_stats"), operator.publicKey.toBuffer()],
+      program.programId
+    );
+    await program.methods
+      .operatorHeartbeat()
+      .accounts({
+        adapterRegistry,
+        operatorStats,
+        operator: operator.publicKey,
+        systemProgram: SystemProgram.programId,
+      })
+      .signers([operator])
+      .rpc();
+    const heartbeatStats = await program.account.operatorStats.fetch(operatorStats);
+
+    const nonce = new BN(Date.now());
+    const [limitOrder] = PublicKey.findProgramAddressSync(
+      [
+        Buffer.from("limit_order"),
+        user.publicKey.toBuffer(),
+        nonce.toArrayLike(Buffer, "le", 8),
+      ],
+      program.programId
+    );
+    const [orderVault] = PublicKey.findProgramAddressSync(
+      [Buffer.from("order_vault"), limitOrder.toBuffer()],
+      program.programId
+    );
+    await program.methods
+      .initLimitOrder(nonce, 0, false)
+      .accounts({
+        vaultAuthority,
+        limitOrder,
+        inputVault: orderVault,
+        inputMint: sourceMint,
+        inputTokenProgram: TOKEN_PROGRAM_ID,
+        creator: user.publicKey,
+        payer: user.publicKey,
+        systemProgram: SystemProgram.programId,
+        rent: SYSVAR_RENT_PUBKEY,
+      })
+      .signers([user])
+      .rpc();
+    await program.methods
+      .createLimitOrder(
+        nonce,
+        new BN(10_000_000),
+        new BN(1_000_000),
+        500,
+        { takeProfit: {} },
+        new BN(Math.floor(Date.now() / 1000) + 3600),
+        300,
+        false,
+        null,
+        null,
+        new BN(0)
+      )
+      .accounts({
+        vaultAuthority,
+        limitOrder,
+        inputVault: orderVault,
+        userInputTokenAccount: userSourceTokenAccount,
+        userDestinationTokenAccount,
+        inputMint: sourceMint,
+        outputMint: destinationMint,
+        inputTokenProgram: TOKEN_PROGRAM_ID,
+        outputTokenProgram: TOKEN_PROGRAM_ID,
+        creator: user.publicKey,
+        payer: user.publicKey,
+        systemProgram: SystemProgram.programId,
+      })
+      .signers([user])
+      .rpc();
+
+    // Let the cluster clock move past the heartbeat's timestamp
+    await new Promise((resolve) => setTimeout(resolve, 2000));
+
+    const routePlan = [
+      { swap: { raydium: {} }, percent: 100, inputIndex: 0, outputIndex: 13 },
+    ];
+    const [inputPoolVault, outputPoolVault] =
+      sourceMint.toString() < destinationMint.toString()
+        ? [raydiumTokenAVault, raydiumTokenBVault]
+        : [raydiumTokenBVault, raydiumTokenAVault];
+    await program.methods
+      .executeLimitOrder(routePlan, new BN(1_100_000), 0, null, null, null)
+      .accounts({
+        adapterRegistry,
+        vaultAuthority,
+        limitOrder,
+        inputVault: orderVault,
+        inputTokenProgram: TOKEN_PROGRAM_ID,
+        outputTokenProgram: TOKEN_PROGRAM_ID,
+        userDestinationTokenAccount,
+        inputMint: sourceMint,
+        outputMint: destinationMint,
+        platformFeeAccount: null,
+        operatorStats,
+        operator: operator.publicKey,
+        systemProgram: SystemProgram.programId,
+      })
+      .remainingAccounts([
+        { pubkey: orderVault, isWritable: true, isSigner: false },
+        { pubkey: raydiumPoolInfo, isWritable: true, isSigner: false },
+        { pubkey: raydiumPoolAuthority, isWritable: false, isSigner: false },
+        { pubkey: raydiumAmmConfig, isWritable: false, isSigner: false },
+        { pubkey: raydiumPoolState, isWritable: true, isSigner: false },
+        { pubkey: inputPoolVault, isWritable: true, isSigner: false },
+        { pubkey: outputPoolVault, isWritable: true, isSigner: false },
+        { pubkey: TOKEN_PROGRAM_ID, isWritable: false, isSigner: false },
+        { pubkey: TOKEN_PROGRAM_ID, isWritable: false, isSigner: false },
+        { pubkey: sourceMint, isWritable: false, isSigner: false },
+        { pubkey: destinationMint, isWritable: false, isSigner: false },
+        { pubkey: raydiumObservationState, isWritable: true, isSigner: false },
+        { pubkey: mockRaydiumProgramId, isWritable: false, isSigner: false },
+        { pubkey: outputVault, isWritable: true, isSigner: false },
+      ])
+      .signers([operator])
+      .rpc();
+
+    // Execution is not subject to the heartbeat interval
+    const executionStats = await program.account.operatorStats.fetch(operatorStats);
+    assert.isAbove(executionStats.lastActive.toNumber(), heartbeatStats.lastActive.toNumber());
+  });
+
+  it("15. Execute limit order rejects fee accounts with a delegate or foreign close authority", async () => {
+    const nonce = new BN(Date.now());
+    const [limitOrder] = PublicKey.findProgramAddressSync(
+      [
+        Buffer.from("limit_order"),
+        user.publicKey.toBuffer(),
+        nonce.toArrayLike(Buffer, "le", 8),
+      ],
+      program.programId
+    );
+    const [orderVault] = PublicKey.findProgramAddressSync(
+      [Buffer.from("order_vault"), limitOrder.toBuffer()],
+      program.programId
+    );
+    await program.methods
+      .initLimitOrder(nonce, 0, false)
+      .accounts({
+        vaultAuthority,
+        limitOrder,
+        inputVault: orderVault,
+        inputMint: sourceMint,
+        inputTokenProgram: TOKEN_PROGRAM_ID,
+        creator: user.publicKey,
+        payer: user.publicKey,
+        systemProgram: SystemProgram.programId,
+        rent: SYSVAR_RENT_PUBKEY,
+      })
+      .signers([user])
+      .rpc();
+    await program.methods
+      .createLimitOrder(
+        nonce,
+        new BN(10_000_000),
+        new BN(1_000_000),
+        500,
+        { takeProfit: {} },
+        new BN(Math.floor(Date.now() / 1000) + 3600),
+        300,
+        false,
+        null,
+        null,
+        new BN(0)
+      )
+      .accounts({
+        vaultAuthority,
+        limitOrder,
+        inputVault: orderVault,
+        userInputTokenAccount: userSourceTokenAccount,
+        userDestinationTokenAccount,
+        inputMint: sourceMint,
+        outputMint: destinationMint,
+        inputTokenProgram: TOKEN_PROGRAM_ID,
+        outputTokenProgram: TOKEN_PROGRAM_ID,
+        creator: user.publicKey,
+        payer: user.publicKey,
+        systemProgram: SystemProgram.programId,
+      })
+      .signers([user])
+      .rpc();
+
+    const routePlan = [
+      { swap: { raydium: {} }, percent: 100, inputIndex: 0, outputIndex: 13 },
+    ];
+    const [inputPoolVault, outputPoolVault] =
+      sourceMint.toString() < destinationMint.toString()
+        ? [raydiumTokenAVault, raydiumTokenBVault]
+        : [raydiumTokenBVault, raydiumTokenAVault];
+    const execute = (feeAccount: PublicKey) =>
+      program.methods
+        .executeLimitOrder(routePlan, new BN(1_100_000), 10, null, null, null)
+        .accounts({
+          adapterRegistry,
+          vaultAuthority,
+          limitOrder,
+          inputVault: orderVault,
+          inputTokenProgram: TOKEN_PROGRAM_ID,
+          outputTokenProgram: TOKEN_PROGRAM_ID,
+          userDestinationTokenAccount,
+          inputMint: sourceMint,
+          outputMint: destinationMint,
+          platformFeeAccount: feeAccount,
+          operator: operator.publicKey,
+          systemProgram: SystemProgram.programId,
+        })
+        .remainingAccounts([
+          { pubkey: orderVault, isWritable: true, isSigner: false },
+          { pubkey: raydiumPoolInfo, isWritable: true, isSigner: false },
+          { pubkey: raydiumPoolAuthority, isWritable: false, isSigner: false },
+          { pubkey: raydiumAmmConfig, isWritable: false, isSigner: false },
+          { pubkey: raydiumPoolState, isWritable: true, isSigner: false },
+          { pubkey: inputPoolVault, isWritable: true, isSigner: false },
+          { pubkey: outputPoolVault, isWritable: true, isSigner: false },
+          { pubkey: TOKEN_PROGRAM_ID, isWritable: false, isSigner: false },
+          { pubkey: TOKEN_PROGRAM_ID, isWritable: false, isSigner: false },
+          { pubkey: sourceMint, isWritable: false, isSigner: false },
+          { pubkey: destinationMint, isWritable: false, isSigner: false },
+          { pubkey: raydiumObservationState, isWritable: true, isSigner: false },
+          { pubkey: mockRaydiumProgramId, isWritable: false, isSigner: false },
+          { pubkey: outputVault, isWritable: true, isSigner: false },
+        ])
+        .signers([operator])
+        .rpc();
+
+    const newFeeAccount = () =>
+      createAccount(
+        provider.connection,
+        user,
+        destinationMint,
+        user.publicKey,
+        Keypair.generate(),
+        undefined,
+        TOKEN_PROGRAM_ID
+      );
+
+    // A delegate could move accrued fees out
+    const delegatedFeeAccount = await newFeeAccount();
+    await approve(
+      provider.connection,
+      user,
+      delegatedFeeAccount,
+      Keypair.generate().publicKey,
+      user,
+      1
+    );
+    try {
+      await execute(delegatedFeeAccount);
+      assert.fail("Fee account with a delegate should be rejected");
+    } catch (error) {
+      assert.include(error.toString(), "UnsafeFeeAccount");
+    }
+
+    // A foreign close authority could destroy the account
+    const closableFeeAccount = await newFeeAccount();
+    await setAuthority(
+      provider.connection,
+      user,
+      closableFeeAccount,
+      user,
+      AuthorityType.CloseAccount,
+      Keypair.generate().publicKey
+    );
+    try {
+      await execute(closableFeeAccount);
+      assert.fail("Fee account with a foreign close authority should be rejected");
+    } catch (error) {
+      assert.include(error.toString(), "UnsafeFeeAccount");
+    }
+
+    // The program fee vault is accepted
+    await execute(platformFeeAccount);
+    assert.isNull(await provider.connection.getAccountInfo(limitOrder));
+  });
+
+  it("16. route_prevalidated is limited to trusted routers", async () => {
+    const amountIn = new BN(5_000_000);
+    const routePlan = [
+      { swap: { raydium: {} }, percent: 100, inputIndex: 0, outputIndex: 13 },
+    ];
+    const [inputPoolVault, outputPoolVault] =
+      sourceMint.toString() < destinationMint.toString()
+        ? [raydiumTokenAVault, raydiumTokenBVault]
+        : [raydiumTokenBVault, raydiumTokenAVault];
+    const routePrevalidated = () =>
+      program.methods
+        .routePrevalidated(routePlan, amountIn, new BN(1), 100, 0, new BN(0), false, null, false)
+        .accounts({
+          adapterRegistry,
+          vaultAuthority,
+          inputTokenProgram: TOKEN_PROGRAM_ID,
+          outputTokenProgram: TOKEN_PROGRAM_ID,
+          userTransferAuthority: user.publicKey,
+          userSourceTokenAccount,
+          userDestinationTokenAccount,
+          sourceMint,
+          destinationMint,
+          platformFeeAccount: null,
+          systemProgram: SystemProgram.programId,
+        })
+        .remainingAccounts([
+          { pubkey: inputVault, isWritable: true, isSigner: false },
+          { pubkey: raydiumPoolInfo, isWritable: true, isSigner: false },
+          { pubkey: raydiumPoolAuthority, isWritable: false, isSigner: false },
+          { pubkey: raydiumAmmConfig, isWritable: false, isSigner: false },
+          { pubkey: raydiumPoolState, isWritable: true, isSigner: false },
+          { pubkey: inputPoolVault, isWritable: true, isSigner: false },
+          { pubkey: outputPoolVault, isWritable: true, isSigner: false },
+          { pubkey: TOKEN_PROGRAM_ID, isWritable: false, isSigner: false },
+          { pubkey: TOKEN_PROGRAM_ID, isWritable: false, isSigner: false },
+          { pubkey: sourceMint, isWritable: false, isSigner: false },
+          { pubkey: destinationMint, isWritable: false, isSigner: false },
+          { pubkey: raydiumObservationState, isWritable: true, isSigner: false },
+          { pubkey: mockRaydiumProgramId, isWritable: false, isSigner: false },
+          { pubkey: outputVault, isWritable: true, isSigner: false },
+        ])
+        .signers([user])
+        .rpc();
+
+    try {
+      await routePrevalidated();
+      assert.fail("Untrusted callers should be rejected");
+    } catch (error) {
+      assert.include(error.toString(), "UntrustedRouter");
+    }
+
+    await program.methods
+      .addTrustedRouter(user.publicKey)
+      .accounts({ adapterRegistry, authority: wallet.publicKey })
+      .signers([wallet.payer])
+      .rpc();
+    try {
+      const initialDest = (await getAccount(provider.connection, userDestinationTokenAccount)).amount;
+      await routePrevalidated();
+      const finalDest = (await getAccount(provider.connection, userDestinationTokenAccount)).amount;
+      assert(finalDest > initialDest, "Destination balance should increase");
+    } finally {
+      await program.methods
+        .removeTrustedRouter(user.publicKey)
+        .accounts({ adapterRegistry, authority: wallet.publicKey })
+        .signers([wallet.payer])
+        .rpc();
+    }
+
+    const registry = await program.account.adapterRegistry.fetch(adapterRegistry);
+    assert.isFalse(registry.trustedRouters.some((router) => router.equals(user.publicKey)));
+  });
+
+  it("17. Route steps over the adapter's price impact cap need accept_high_impact", async () => {
+    const [inputPoolVault, outputPoolVault] =
+      sourceMint.toString() < destinationMint.toString()
+        ? [raydiumTokenAVault, raydiumTokenBVault]
+        : [raydiumTokenBVault, raydiumTokenAVault];
+
+    // Trade 30% of the pool's input side, far above a 100 bps cap
+    const depth = (await getAccount(provider.connection, inputPoolVault)).amount;
+    const amountIn = (depth * 3n) / 10n;
+    await mintTo(
+      provider.connection,
+      wallet.payer,
+      sourceMint,
+      userSourceTokenAccount,
+      wallet.publicKey,
+      amountIn
+    );
+
+    const setMaxStepImpact = (maxStepImpactBps: number) =>
+      program.methods
+        .setMaxStepImpact({ raydium: {} }, maxStepImpactBps)
+        .accounts({ adapterRegistry, operator: wallet.publicKey })
+        .signers([wallet.payer])
+        .rpc();
+    const route = (acceptHighImpact: boolean) =>
+      program.methods
+        .route(
+          [{ swap: { raydium: {} }, percent: 100, inputIndex: 0, outputIndex: 13 }],
+          new BN(amountIn.toString()),
+          new BN(1),
+          100,
+          0,
+          new BN(0),
+          acceptHighImpact,
+          null,
+          false,
+          null
+        )
+        .accounts({
+          adapterRegistry,
+          vaultAuthority,
+          inputTokenProgram: TOKEN_PROGRAM_ID,
+          outputTokenProgram: TOKEN_PROGRAM_ID,
+          userTransferAuthority: user.publicKey,
+          userSourceTokenAccount,
+          userDestinationTokenAccount,
+          sourceMint,
+          destinationMint,
+          platformFeeAccount: null,
+          systemProgram: SystemProgram.programId,
+        })
+        .remainingAccounts([
+          { pubkey: inputVault, isWritable: true, isSigner: false },
+          { pubkey: raydiumPoolInfo, isWritable: true, isSigner: false },
+          { pubkey: raydiumPoolAuthority, isWritable: false, isSigner: false },
+          { pubkey: raydiumAmmConfig, isWritable: false, isSigner: false },
+          { pubkey: raydiumPoolState, isWritable: true, isSigner: false },
+          { pubkey: inputPoolVault, isWritable: true, isSigner: false },
+          { pubkey: outputPoolVault, isWritable: true, isSigner: false },
+          { pubkey: TOKEN_PROGRAM_ID, isWritable: false, isSigner: false },
+          { pubkey: TOKEN_PROGRAM_ID, isWritable: false, isSigner: false },
+          { pubkey: sourceMint, isWritable: false, isSigner: false },
+          { pubkey: destinationMint, isWritable: false, isSigner: false },
+          { pubkey: raydiumObservationState, isWritable: true, isSigner: false },
+          { pubkey: mockRaydiumProgramId, isWritable: false, isSigner: false },
+          { pubkey: outputVault, isWritable: true, isSigner: false },
+        ])
+        .signers([user])
+        .rpc({ commitment: "confirmed" });
+
+    await setMaxStepImpact(100);
+    try {
+      try {
+        await route(false);
+        assert.fail("Step over the price impact cap should be rejected");
+      } catch (error) {
+        assert.include(error.toString(), "ExcessivePriceImpact");
+      }
+
+      const signature = await route(true);
+
+      // The waiver is recorded in RouterSwapEvent, emitted through a self-CPI
+      const txInfo = await provider.connection.getTransaction(signature, {
+        commitment: "confirmed",
+        maxSupportedTransactionVersion: 0,
+      });
+      const accountKeys = txInfo.transaction.message.getAccountKeys();
+      const events = txInfo.meta.innerInstructions
+        .flatMap((inner) => inner.instructions)
+        .filter((ix) => accountKeys.get(ix.programIdIndex).equals(program.programId))
+        .map((ix) =>
+          program.coder.events.decode(
+            Buffer.from(anchor.utils.bytes.bs58.decode(ix.data).subarray(8)).toString("base64")
+          )
+        );
+      const routerSwapEvent = events.find((event) => event?.name === "RouterSwapEvent");
+      assert.isTrue(routerSwapEvent.data.acceptHighImpact);
+    } finally {
+      await setMaxStepImpact(0);
+    }
+
+    const registry = await program.account.adapterRegistry.fetch(adapterRegistry);
+    assert.lengthOf(registry.adapterImpactCaps, 0);
+  });
+
+  it("18. Limit order cannot execute before activate_at but can be cancelled", async () => {
+    const nonce = new BN(Date.now());
+    const inputAmount = new BN(20_000_000);
+    const now = Math.floor(Date.now() / 1000);
+    const expiry = new BN(now + 3600);
+
+    const [limitOrder] = PublicKey.findProgramAddressSync(
+      [
+        Buffer.from("limit_order"),
+        user.publicKey.toBuffer(),
+        nonce.toArrayLike(Buffer, "le", 8),
+      ],
+      program.programId
+    );
+    const [orderVault] = PublicKey.findProgramAddressSync(
+      [Buffer.from("order_vault"), limitOrder.toBuffer()],
+      program.programId
+    );
+
+    await program.methods
+      .initLimitOrder(nonce, 0, false)
+      .accounts({
+        vaultAuthority,
+        limitOrder,
+        inputVault: orderVault,
+        inputMint: sourceMint,
+        inputTokenProgram: TOKEN_PROGRAM_ID,
+        creator: user.publicKey,
+        payer: user.publicKey,
+        systemProgram: SystemProgram.programId,
+        rent: SYSVAR_RENT_PUBKEY,
+      })
+      .signers([user])
+      .rpc();
+
+    const createLimitOrder = (activateAt: BN) =>
+      program.methods
+        .createLimitOrder(
+          nonce,
+          inputAmount,
+          new BN(10_000_000),
+          100,
+          { takeProfit: {} },
+          expiry,
+          300,
+          false,
+          null,
+          null,
+          activateAt
+        )
+        .accounts({
+          vaultAuthority,
+          limitOrder,
+          inputVault: orderVault,
+          userInputTokenAccount: userSourceTokenAccount,
+          userDestinationTokenAccount: userDestinationTokenAccount,
+          inputMint: sourceMint,
+          outputMint: destinationMint,
+          inputTokenProgram: TOKEN_PROGRAM_ID,
+          outputTokenProgram: TOKEN_PROGRAM_ID,
+          creator: user.publicKey,
+          payer: user.publicKey,
+          systemProgram: SystemProgram.programId,
+        })
+        .signers([user])
+        .rpc();
+
+    // Activation must come before expiry
+    try {
+      await createLimitOrder(expiry);
+      assert.fail("activate_at at expiry should be rejected");
+    } catch (error) {
+      assert.include(error.toString(), "InvalidActivationTime");
+    }
+
+    const activateAt = new BN(now + 1800);
+    await createLimitOrder(activateAt);
+    const order = await program.account.limitOrder.fetch(limitOrder);
+    assert.equal(order.activateAt.toString(), activateAt.toString());
+
+    const [inputPoolVault, outputPoolVault] =
+      sourceMint.toString() < destinationMint.toString()
+        ? [raydiumTokenAVault, raydiumTokenBVault]
+        : [raydiumTokenBVault, raydiumTokenAVault];
+
+    try {
+      await program.methods
+        .executeLimitOrder(
+          [{ swap: { raydium: {} }, percent: 100, inputIndex: 0, outputIndex: 13 }],
+          new BN(15_000_000),
+          0,
+          null,
+          null,
+          null
+        )
+        .accounts({
+          adapterRegistry,
+          vaultAuthority,
+          limitOrder,
+          inputVault: orderVault,
+          inputTokenProgram: TOKEN_PROGRAM_ID,
+          outputTokenProgram: TOKEN_PROGRAM_ID,
+          userDestinationTokenAccount,
+          inputMint: sourceMint,
+          outputMint: destinationMint,
+          platformFeeAccount: null,
+          operator: operator.publicKey,
+          systemProgram: SystemProgram.programId,
+        })
+        .remainingAccounts([
+          { pubkey: orderVault, isWritable: true, isSigner: false },
+          { pubkey: raydiumPoolInfo, isWritable: true, isSigner: false },
+          { pubkey: raydiumPoolAuthority, isWritable: false, isSigner: false },
+          { pubkey: raydiumAmmConfig, isWritable: false, isSigner: false },
+          { pubkey: raydiumPoolState, isWritable: true, isSigner: false },
+          { pubkey: inputPoolVault, isWritable: true, isSigner: false },
+          { pubkey: outputPoolVault, isWritable: true, isSigner: false },
+          { pubkey: TOKEN_PROGRAM_ID, isWritable: false, isSigner: false },
+          { pubkey: TOKEN_PROGRAM_ID, isWritable: false, isSigner: false },
+          { pubkey: sourceMint, isWritable: false, isSigner: false },
+          { pubkey: destinationMint, isWritable: false, isSigner: false },
+          { pubkey: raydiumObservationState, isWritable: true, isSigner: false },
+          { pubkey: mockRaydiumProgramId, isWritable: false, isSigner: false },
+          { pubkey: outputVault, isWritable: true, isSigner: false },
+        ])
+        .signers([operator])
+        .rpc();
+      assert.fail("Execution before activate_at should fail");
+    } catch (error) {
+      assert.include(error.toString(), "OrderNotActiveYet");
+    }
+
+    // Cancellation is allowed before activation
+    const balanceBefore = (await getAccount(provider.connection, userSourceTokenAccount)).amount;
+    await program.methods
+      .cancelLimitOrder()
+      .accounts({
+        vaultAuthority,
+        limitOrder,
+        inputVault: orderVault,
+        userInputTokenAccount: userSourceTokenAccount,
+        inputMint: sourceMint,
+        inputTokenProgram: TOKEN_PROGRAM_ID,
+        creator: user.publicKey,
+      })
+      .signers([user])
+      .rpc();
+    const balanceAfter = (await getAccount(provider.connection, userSourceTokenAccount)).amount;
+    assert.equal((balanceAfter - balanceBefore).toString(), inputAmount.toString());
+  });
+
+  it("19. Single-hop routes emit the per-step SwapEvent only while step events are enabled", async () => {
+    const [inputPoolVault, outputPoolVault] =
+      sourceMint.toString() < destinationMint.toString()
+        ? [raydiumTokenAVault, raydiumTokenBVault]
+        : [raydiumTokenBVault, raydiumTokenAVault];
+    const amountIn = 1_000_000;
+    await mintTo(
+      provider.connection,
+      wallet.payer,
+      sourceMint,
+      userSourceTokenAccount,
+      wallet.publicKey,
+      3 * amountIn
+    );
+
+    const setEmitStepEvents = (emitStepEvents: boolean) =>
+      program.methods
+        .setEmitStepEvents(emitStepEvents)
+        .accounts({ adapterRegistry, authority: wallet.publicKey })
+        .signers([wallet.payer])
+        .rpc();
+    // Returns the names of the events the route emitted through self-CPI
+    const routeEventNames = async (emitStepEvents: boolean | null) => {
+      const signature = await program.methods
+        .route(
+          [{ swap: { raydium: {} }, percent: 100, inputIndex: 0, outputIndex: 13 }],
+          new BN(amountIn),
+          new BN(1),
+          100,
+          0,
+          new BN(0),
+          false,
+          emitStepEvents,
+          false,
+          null
+        )
+        .accounts({
+          adapterRegistry,
+          vaultAuthority,
+          inputTokenProgram: TOKEN_PROGRAM_ID,
+          outputTokenProgram: TOKEN_PROGRAM_ID,
+          userTransferAuthority: user.publicKey,
+          userSourceTokenAccount,
+          userDestinationTokenAccount,
+          sourceMint,
+          destinationMint,
+          platformFeeAccount: null,
+          systemProgram: SystemProgram.programId,
+        })
+        .remainingAccounts([
+          { pubkey: inputVault, isWritable: true, isSigner: false },
+          { pubkey: raydiumPoolInfo, isWritable: true, isSigner: false },
+          { pubkey: raydiumPoolAuthority, isWritable: false, isSigner: false },
+          { pubkey: raydiumAmmConfig, isWritable: false, isSigner: false },
+          { pubkey: raydiumPoolState, isWritable: true, isSigner: false },
+          { pubkey: inputPoolVault, isWritable: true, isSigner: false },
+          { pubkey: outputPoolVault, isWritable: true, isSigner: false },
+          { pubkey: TOKEN_PROGRAM_ID, isWritable: false, isSigner: false },
+          { pubkey: TOKEN_PROGRAM_ID, isWritable: false, isSigner: false },
+          { pubkey: sourceMint, isWritable: false, isSigner: false },
+          { pubkey: destinationMint, isWritable: false, isSigner: false },
+          { pubkey: raydiumObservationState, isWritable: true, isSigner: false },
+          { pubkey: mockRaydiumProgramId, isWritable: false, isSigner: false },
+          { pubkey: outputVault, isWritable: true, isSigner: false },
+        ])
+        .signers([user])
+        .rpc({ commitment: "confirmed" });
+
+      const txInfo = await provider.connection.getTransaction(signature, {
+        commitment: "confirmed",
+        maxSupportedTransactionVersion: 0,
+      });
+      const accountKeys = txInfo.transaction.message.getAccountKeys();
+      return txInfo.meta.innerInstructions
+        .flatMap((inner) => inner.instructions)
+        .filter((ix) => accountKeys.get(ix.programIdIndex).equals(program.programId))
+        .map((ix) =>
+          program.coder.events.decode(
+            Buffer.from(anchor.utils.bytes.bs58.decode(ix.data).subarray(8)).toString("base64")
+          )
+        )
+        .filter((event) => event !== null)
+        .map((event) => event.name);
+    };
+
+    // Default: the step event is emitted alongside the aggregated event
+    assert.includeMembers(await routeEventNames(null), ["SwapEvent", "RouterSwapEvent"]);
+
+    await setEmitStepEvents(false);
+    try {
+      const registry = await program.account.adapterRegistry.fetch(adapterRegistry);
+      assert.isTrue(registry.suppressStepEvents);
+
+      let names = await routeEventNames(null);
+      assert.include(names, "RouterSwapEvent");
+      assert.notInclude(names, "SwapEvent");
+
+      // The instruction override wins over the registry setting
+      names = await routeEventNames(true);
+      assert.includeMembers(names, ["SwapEvent", "RouterSwapEvent"]);
+    } finally {
+      await setEmitStepEvents(true);
+    }
+  });
+
+  it("20. Routes reject quotes outside the band around the on-chain quote while quote sanity is enforced", async () => {
+    const [inputPoolVault, outputPoolVault] =
+      sourceMint.toString() < destinationMint.toString()
+        ? [raydiumTokenAVault, raydiumTokenBVault]
+        : [raydiumTokenBVault, raydiumTokenAVault];
+    const amountIn = new BN(1_000_000);
+    await mintTo(
+      provider.connection,
+      wallet.payer,
+      sourceMint,
+      userSourceTokenAccount,
+      wallet.publicKey,
+      amountIn.toNumber()
+    );
+
+    const setQuoteSanity = (enforce: boolean, bandBps: number) =>
+      program.methods
+        .setQuoteSanity(enforce, bandBps)
+        .accounts({ adapterRegistry, operator: wallet.publicKey })
+        .signers([wallet.payer])
+        .rpc();
+    const routeWithQuote = (quotedOutAmount: BN) =>
+      program.methods
+        .route(
+          [{ swap: { raydium: {} }, percent: 100, inputIndex: 0, outputIndex: 13 }],
+          amountIn,
+          quotedOutAmount,
+          100,
+          0,
+          new BN(0),
+          false,
+          null,
+          false,
+          null
+        )
+        .accounts({
+          adapterRegistry,
+          vaultAuthority,
+          inputTokenProgram: TOKEN_PROGRAM_ID,
+          outputTokenProgram: TOKEN_PROGRAM_ID,
+          userTransferAuthority: user.publicKey,
+          userSourceTokenAccount,
+          userDestinationTokenAccount,
+          sourceMint,
+          destinationMint,
+          platformFeeAccount: null,
+          systemProgram: SystemProgram.programId,
+        })
+        .remainingAccounts([
+          { pubkey: inputVault, isWritable: true, isSigner: false },
+          { pubkey: raydiumPoolInfo, isWritable: true, isSigner: false },
+          { pubkey: raydiumPoolAuthority, isWritable: false, isSigner: false },
+          { pubkey: raydiumAmmConfig, isWritable: false, isSigner: false },
+          { pubkey: raydiumPoolState, isWritable: true, isSigner: false },
+          { pubkey: inputPoolVault, isWritable: true, isSigner: false },
+          { pubkey: outputPoolVault, isWritable: true, isSigner: false },
+          { pubkey: TOKEN_PROGRAM_ID, isWritable: false, isSigner: false },
+          { pubkey: TOKEN_PROGRAM_ID, isWritable: false, isSigner: false },
+          { pubkey: sourceMint, isWritable: false, isSigner: false },
+          { pubkey: destinationMint, isWritable: false, isSigner: false },
+          { pubkey: raydiumObservationState, isWritable: true, isSigner: false },
+          { pubkey: mockRaydiumProgramId, isWritable: false, isSigner: false },
+          { pubkey: outputVault, isWritable: true, isSigner: false },
+        ])
+        .signers([user])
+        .rpc();
+
+    // Constant-product quote from the pool vaults, as the adapter computes it
+    const reserveIn = new BN((await getAccount(provider.connection, inputPoolVault)).amount.toString());
+    const reserveOut = new BN((await getAccount(provider.connection, outputPoolVault)).amount.toString());
+    const onchainQuote = amountIn.mul(reserveOut).div(reserveIn.add(amountIn));
+
+    await setQuoteSanity(true, 2_000);
+    try {
+      const registry = await program.account.adapterRegistry.fetch(adapterRegistry);
+      assert.isTrue(registry.enforceQuoteSanity);
+      assert.equal(registry.quoteSanityBandBps, 2_000);
+
+      for (const bogusQuote of [new BN(1), onchainQuote.muln(2)]) {
+        try {
+          await routeWithQuote(bogusQuote);
+          assert.fail("Quote outside the band should be rejected");
+        } catch (error) {
+          assert.include(error.toString(), "ImplausibleQuote");
+        }
+      }
+
+      // A quote within the band goes through
+      await routeWithQuote(onchainQuote.muln(95).divn(100));
+    } finally {
+      await setQuoteSanity(false, 0);
+    }
+  });
+
+  it("21. Sandboxed adapters only route for wallets on the sandbox list", async () => {
+    const [inputPoolVault, outputPoolVault] =
+      sourceMint.toString() < destinationMint.toString()
+        ? [raydiumTokenAVault, raydiumTokenBVault]
+        : [raydiumTokenBVault, raydiumTokenAVault];
+    const amountIn = new BN(1_000_000);
+    await mintTo(
+      provider.connection,
+      wallet.payer,
+      sourceMint,
+      userSourceTokenAccount,
+      wallet.publicKey,
+      amountIn.toNumber()
+    );
+
+    const setSandbox = (sandboxUsers: PublicKey[]) =>
+      program.methods
+        .setAdapterSandbox({ raydium: {} }, sandboxUsers)
+        .accounts({ adapterRegistry, operator: wallet.publicKey })
+        .signers([wallet.payer])
+        .rpc();
+    const graduate = () =>
+      program.methods
+        .graduateAdapter({ raydium: {} })
+        .accounts({ adapterRegistry, operator: wallet.publicKey })
+        .signers([wallet.payer])
+        .rpc();
+    // Quote just under the constant-product output, so the route clears its slippage check
+    const reserveIn = new BN((await getAccount(provider.connection, inputPoolVault)).amount.toString());
+    const reserveOut = new BN((await getAccount(provider.connection, outputPoolVault)).amount.toString());
+    const quotedOutAmount = amountIn.mul(reserveOut).div(reserveIn.add(amountIn)).muln(95).divn(100);
+    const routeAsUser = () =>
+      program.methods
+        .route(
+          [{ swap: { raydium: {} }, percent: 100, inputIndex: 0, outputIndex: 13 }],
+          amountIn,
+          quotedOutAmount,
+          100,
+          0,
+          new BN(0),
+          false,
+          null,
+          false,
+          null
+        )
+        .accounts({
+          adapterRegistry,
+          vaultAuthority,
+          inputTokenProgram: TOKEN_PROGRAM_ID,
+          outputTokenProgram: TOKEN_PROGRAM_ID,
+          userTransferAuthority: user.publicKey,
+          userSourceTokenAccount,
+          userDestinationTokenAccount,
+          sourceMint,
+          destinationMint,
+          platformFeeAccount: null,
+          systemProgram: SystemProgram.programId,
+        })
+        .remainingAccounts([
+          { pubkey: inputVault, isWritable: true, isSigner: false },
+          { pubkey: raydiumPoolInfo, isWritable: true, isSigner: false },
+          { pubkey: raydiumPoolAuthority, isWritable: false, isSigner: false },
+          { pubkey: raydiumAmmConfig, isWritable: false, isSigner: false },
+          { pubkey: raydiumPoolState, isWritable: true, isSigner: false },
+          { pubkey: inputPoolVault, isWritable: true, isSigner: false },
+          { pubkey: outputPoolVault, isWritable: true, isSigner: false },
+          { pubkey: TOKEN_PROGRAM_ID, isWritable: false, isSigner: false },
+          { pubkey: TOKEN_PROGRAM_ID, isWritable: false, isSigner: false },
+          { pubkey: sourceMint, isWritable: false, isSigner: false },
+          { pubkey: destinationMint, isWritable: false, isSigner: false },
+          { pubkey: raydiumObservationState, isWritable: true, isSigner: false },
+          { pubkey: mockRaydiumProgramId, isWritable: false, isSigner: false },
+          { pubkey: outputVault, isWritable: true, isSigner: false },
+        ])
+        .signers([user])
+        .rpc();
+
+    try {
+      // Blocked: the sandbox list only holds the operator's wallet
+      await setSandbox([wallet.publicKey]);
+      try {
+        await routeAsUser();
+        assert.fail("Route through a sandboxed adapter should be rejected for unlisted users");
+      } catch (error) {
+        assert.include(error.toString(), "AdapterInSandbox");
+      }
+
+      // Allow-listed: the same route goes through once the user is on the list
+      await setSandbox([wallet.publicKey, user.publicKey]);
+      const registry = await program.account.adapterRegistry.fetch(adapterRegistry);
+      const raydium = registry.sandboxedAdapters.find((entry) => entry.swapType.raydium !== undefined);
+      assert.isDefined(raydium);
+      assert.equal(raydium.sandboxUsers.length, 2);
+      await routeAsUser();
+    } finally {
+      await graduate();
+    }
+
+    const registry = await program.account.adapterRegistry.fetch(adapterRegistry);
+    assert.isFalse(registry.sandboxedAdapters.some((entry) => entry.swapType.raydium !== undefined));
+
+    try {
+      await graduate();
+      assert.fail("Graduating an adapter outside sandbox mode should fail");
+    } catch (error) {
+      assert.include(error.toString(), "AdapterNotInSandbox");
+    }
+  });
+
+  it("22. route_compact executes a packed plan and rejects unknown tags", async () => {
+    const [inputPoolVault, outputPoolVault] =
+      sourceMint.toString() < destinationMint.toString()
+        ? [raydiumTokenAVault, raydiumTokenBVault]
+        : [raydiumTokenBVault, raydiumTokenAVault];
+    const amountIn = new BN(1_000_000);
+    await mintTo(
+      provider.connection,
+      wallet.payer,
+      sourceMint,
+      userSourceTokenAccount,
+      wallet.publicKey,
+      amountIn.toNumber()
+    );
+
+    // One whole-input Raydium step: header 0 (SUPPORTED_SWAP_TAGS index), input_index, output_index
+    const raydiumStep = Buffer.from([0, 0, 13]);
+    const routeCompact = (planBytes: Buffer) =>
+      program.methods
+        .routeCompact(planBytes, amountIn, new BN(1), 100, 0, new BN(0), false, null, false, null)
+        .accounts({
+          adapterRegistry,
+          vaultAuthority,
+          inputTokenProgram: TOKEN_PROGRAM_ID,
+          outputTokenProgram: TOKEN_PROGRAM_ID,
+          userTransferAuthority: user.publicKey,
+          userSourceTokenAccount,
+          userDestinationTokenAccount,
+          sourceMint,
+          destinationMint,
+          platformFeeAccount: null,
+          systemProgram: SystemProgram.programId,
+        })
+        .remainingAccounts([
+          { pubkey: inputVault, isWritable: true, isSigner: false },
+          { pubkey: raydiumPoolInfo, isWritable: true, isSigner: false },
+          { pubkey: raydiumPoolAuthority, isWritable: false, isSigner: false },
+          { pubkey: raydiumAmmConfig, isWritable: false, isSigner: false },
+          { pubkey: raydiumPoolState, isWritable: true, isSigner: false },
+          { pubkey: inputPoolVault, isWritable: true, isSigner: false },
+          { pubkey: outputPoolVault, isWritable: true, isSigner: false },
+          { pubkey: TOKEN_PROGRAM_ID, isWritable: false, isSigner: false },
+          { pubkey: TOKEN_PROGRAM_ID, isWritable: false, isSigner: false },
+          { pubkey: sourceMint, isWritable: false, isSigner: false },
+          { pubkey: destinationMint, isWritable: false, isSigner: false },
+          { pubkey: raydiumObservationState, isWritable: true, isSigner: false },
+          { pubkey: mockRaydiumProgramId, isWritable: false, isSigner: false },
+          { pubkey: outputVault, isWritable: true, isSigner: false },
+        ])
+        .signers([user])
+        .rpc();
+
+    try {
+      await routeCompact(Buffer.from([6, 0, 13]));
+      assert.fail("An unknown swap tag should be rejected");
+    } catch (error) {
+      assert.include(error.toString(), "UnknownCompactSwapTag");
+    }
+    try {
+      await routeCompact(raydiumStep.subarray(0, 2));
+      assert.fail("A truncated plan should be rejected");
+    } catch (error) {
+      assert.include(error.toString(), "InvalidCompactRoutePlan");
+    }
+
+    const initialDest = (await getAccount(provider.connection, userDestinationTokenAccount)).amount;
+    await routeCompact(raydiumStep);
+    const finalDest = (await getAccount(provider.connection, userDestinationTokenAccount)).amount;
+    assert(finalDest > initialDest, "Destination balance should increase");
+  });
+
+  it("23. Routes refuse vaults with a delegate set only while delegated vaults are rejected", async () => {
+    const [inputPoolVault, outputPoolVault] =
+      sourceMint.toString() < destinationMint.toString()
+        ? [raydiumTokenAVault, raydiumTokenBVault]
+        : [raydiumTokenBVault, raydiumTokenAVault];
+    const amountIn = new BN(1_000_000);
+    await mintTo(
+      provider.connection,
+      wallet.payer,
+      sourceMint,
+      userSourceTokenAccount,
+      wallet.publicKey,
+      amountIn.toNumber()
+    );
+
+    // A destination account the vault authority can only move funds out of through a stale approval
+    const legacyOwner = Keypair.generate();
+    const delegatedVault = await createAccount(
+      provider.connection,
+      wallet.payer,
+      destinationMint,
+      legacyOwner.publicKey,
+      Keypair.generate(),
+      undefined,
+      TOKEN_PROGRAM_ID
+    );
+    await approve(
+      provider.connection,
+      wallet.payer,
+      delegatedVault,
+      vaultAuthority,
+      legacyOwner,
+      BigInt("18446744073709551615")
+    );
+
+    const setRejectDelegatedVaults = (reject: boolean) =>
+      program.methods
+        .setRejectDelegatedVaults(reject)
+        .accounts({ adapterRegistry, operator: wallet.publicKey })
+        .signers([wallet.payer])
+        .rpc();
+    const routeThroughDelegatedVault = () =>
+      program.methods
+        .route(
+          [{ swap: { raydium: {} }, percent: 100, inputIndex: 0, outputIndex: 13 }],
+          amountIn,
+          new BN(1),
+          100,
+          0,
+          new BN(0),
+          false,
+          null,
+          false,
+          null
+        )
+        .accounts({
+          adapterRegistry,
+          vaultAuthority,
+          inputTokenProgram: TOKEN_PROGRAM_ID,
+          outputTokenProgram: TOKEN_PROGRAM_ID,
+          userTransferAuthority: user.publicKey,
+          userSourceTokenAccount,
+          userDestinationTokenAccount,
+          sourceMint,
+          destinationMint,
+          platformFeeAccount: null,
+          systemProgram: SystemProgram.programId,
+        })
+        .remainingAccounts([
+          { pubkey: inputVault, isWritable: true, isSigner: false },
+          { pubkey: raydiumPoolInfo, isWritable: true, isSigner: false },
+          { pubkey: raydiumPoolAuthority, isWritable: false, isSigner: false },
+          { pubkey: raydiumAmmConfig, isWritable: false, isSigner: false },
+          { pubkey: raydiumPoolState, isWritable: true, isSigner: false },
+          { pubkey: inputPoolVault, isWritable: true, isSigner: false },
+          { pubkey: outputPoolVault, isWritable: true, isSigner: false },
+          { pubkey: TOKEN_PROGRAM_ID, isWritable: false, isSigner: false },
+          { pubkey: TOKEN_PROGRAM_ID, isWritable: false, isSigner: false },
+          { pubkey: sourceMint, isWritable: false, isSigner: false },
+          { pubkey: destinationMint, isWritable: false, isSigner: false },
+          { pubkey: raydiumObservationState, isWritable: true, isSigner: false },
+          { pubkey: mockRaydiumProgramId, isWritable: false, isSigner: false },
+          { pubkey: delegatedVault, isWritable: true, isSigner: false },
+        ])
+        .signers([user])
+        .rpc();
+
+    await setRejectDelegatedVaults(true);
+    try {
+      const registry = await program.account.adapterRegistry.fetch(adapterRegistry);
+      assert.isTrue(registry.rejectDelegatedVaults);
+
+      try {
+        await routeThroughDelegatedVault();
+        assert.fail("A route through a delegated vault should be rejected");
+      } catch (error) {
+        assert.include(error.toString(), "VaultHasDelegate");
+      }
+    } finally {
+      await setRejectDelegatedVaults(false);
+    }
+
+    // With the policy off the stale approval is still usable
+    const initialDest = (await getAccount(provider.connection, userDestinationTokenAccount)).amount;
+    await routeThroughDelegatedVault();
+    const finalDest = (await getAccount(provider.connection, userDestinationTokenAccount)).amount;
+    assert(finalDest > initialDest, "Destination balance should increase");
+  });
+
+  it("24. Routes pay the platform fee into the registered fee vault of the destination mint", async () => {
+    const [inputPoolVault, outputPoolVault] =
+      sourceMint.toString() < destinationMint.toString()
+        ? [raydiumTokenAVault, raydiumTokenBVault]
+        : [raydiumTokenBVault, raydiumTokenAVault];
+    const amountIn = new BN(1_000_000);
+    await mintTo(
+      provider.connection,
+      wallet.payer,
+      sourceMint,
+      userSourceTokenAccount,
+      wallet.publicKey,
+      2 * amountIn.toNumber()
+    );
+
+    // A second vault authority owned account, so the registered vault differs from platformFeeAccount
+    const registeredFeeVault = await createAccount(
+      provider.connection,
+      wallet.payer,
+      destinationMint,
+      vaultAuthority,
+      Keypair.generate(),
+      undefined,
+      TOKEN_PROGRAM_ID
+    );
+    await program.methods
+      .setFeeVault()
+      .accounts({
+        feeVault: registeredFeeVault,
+        payer: wallet.publicKey,
+        authority: wallet.publicKey,
+      })
+      .signers([wallet.payer])
+      .rpc();
+
+    const routeWithRegisteredFeeVault = (feeVaults: PublicKey[]) =>
+      program.methods
+        .route(
+          [{ swap: { raydium: {} }, percent: 100, inputIndex: 0, outputIndex: 13 }],
+          amountIn,
+          new BN(1),
+          100,
+          50,
+          new BN(0),
+          false,
+          null,
+          true,
+          null
+        )
+        .accounts({
+          adapterRegistry,
+          vaultAuthority,
+          inputTokenProgram: TOKEN_PROGRAM_ID,
+          outputTokenProgram: TOKEN_PROGRAM_ID,
+          userTransferAuthority: user.publicKey,
+          userSourceTokenAccount,
+          userDestinationTokenAccount,
+          sourceMint,
+          destinationMint,
+          platformFeeAccount: null,
+          systemProgram: SystemProgram.programId,
+        })
+        .remainingAccounts([
+          { pubkey: inputVault, isWritable: true, isSigner: false },
+          { pubkey: raydiumPoolInfo, isWritable: true, isSigner: false },
+          { pubkey: raydiumPoolAuthority, isWritable: false, isSigner: false },
+          { pubkey: raydiumAmmConfig, isWritable: false, isSigner: false },
+          { pubkey: raydiumPoolState, isWritable: true, isSigner: false },
+          { pubkey: inputPoolVault, isWritable: true, isSigner: false },
+          { pubkey: outputPoolVault, isWritable: true, isSigner: false },
+          { pubkey: TOKEN_PROGRAM_ID, isWritable: false, isSigner: false },
+          { pubkey: TOKEN_PROGRAM_ID, isWritable: false, isSigner: false },
+          { pubkey: sourceMint, isWritable: false, isSigner: false },
+          { pubkey: destinationMint, isWritable: false, isSigner: false },
+          { pubkey: raydiumObservationState, isWritable: true, isSigner: false },
+          { pubkey: mockRaydiumProgramId, isWritable: false, isSigner: false },
+          { pubkey: outputVault, isWritable: true, isSigner: false },
+          ...feeVaults.map((pubkey) => ({ pubkey, isWritable: true, isSigner: false })),
+        ])
+        .signers([user])
+        .rpc();
+
+    try {
+      try {
+        await routeWithRegisteredFeeVault([]);
+        assert.fail("A route without its registered fee vault should be rejected");
+      } catch (error) {
+        assert.include(error.toString(), "RegisteredFeeVaultNotProvided");
+      }
+
+      const initialFee = (await getAccount(provider.connection, registeredFeeVault)).amount;
+      const initialPlatformFee = (await getAccount(provider.connection, platformFeeAccount)).amount;
+      await routeWithRegisteredFeeVault([registeredFeeVault]);
+      const finalFee = (await getAccount(provider.connection, registeredFeeVault)).amount;
+      const finalPlatformFee = (await getAccount(provider.connection, platformFeeAccount)).amount;
+      assert(finalFee > initialFee, "Registered fee vault should collect the platform fee");
+      assert.equal(finalPlatformFee.toString(), initialPlatformFee.toString());
+    } finally {
+      await program.methods
+        .removeFeeVault(destinationMint)
+        .accounts({ authority: wallet.publicKey })
+        .signers([wallet.payer])
+        .rpc();
+    }
+
+    const registry = await program.account.feeVaultRegistry.fetch(
+      PublicKey.findProgramAddressSync([Buffer.from("fee_vault_registry")], program.programId)[0]
+    );
+    assert.isFalse(registry.entries.some((entry) => entry.mint.equals(destinationMint)));
+  });
+
+  it("25. Recreating an order at a reused nonce stamps a new generation", async () => {
+    const nonce = new BN(Date.now());
+    const [limitOrder] = PublicKey.findProgramAddressSync(
+      [Buffer.from("limit_order"), user.publicKey.toBuffer(), nonce.toArrayLike(Buffer, "le", 8)],
+      program.programId
+    );
+    const [orderVault] = PublicKey.findProgramAddressSync(
+      [Buffer.from("order_vault"), limitOrder.toBuffer()],
+      program.programId
+    );
+    const [orderCounter] = PublicKey.findProgramAddressSync(
+      [Buffer.from("order_counter"), user.publicKey.toBuffer()],
+      program.programId
+    );
+
+    const initOrder = () =>
+      program.methods
+        .initLimitOrder(nonce, 0, false)
+        .accounts({
+          vaultAuthority,
+          limitOrder,
+          inputVault: orderVault,
+          inputMint: sourceMint,
+          inputTokenProgram: TOKEN_PROGRAM_ID,
+          creator: user.publicKey,
+          payer: user.publicKey,
+          systemProgram: SystemProgram.programId,
+          rent: SYSVAR_RENT_PUBKEY,
+        })
+        .signers([user])
+        .rpc();
+
+    await initOrder();
+    const firstGeneration = (await program.account.limitOrder.fetch(limitOrder)).generation;
+    assert.equal(
+      (await program.account.creatorOrderCounter.fetch(orderCounter)).ordersCreated,
+      firstGeneration + 1
+    );
+
+    await program.methods
+      .cancelLimitOrder()
+      .accounts({
+        vaultAuthority,
+        limitOrder,
+        inputVault: orderVault,
+        userInputTokenAccount: userSourceTokenAccount,
+        inputMint: sourceMint,
+        inputTokenProgram: TOKEN_PROGRAM_ID,
+        creator: user.publicKey,
+      })
+      .signers([user])
+      .rpc();
+
+    // Same (creator, nonce), so the same address, but a later generation
+    await initOrder();
+    const recreated = await program.account.limitOrder.fetch(limitOrder);
+    assert.equal(recreated.generation, firstGeneration + 1);
+    assert.equal(
+      (await program.account.creatorOrderCounter.fetch(orderCounter)).ordersCreated,
+      firstGeneration + 2
+    );
+  });
+
+  it("26. Limit orders read the mock clock in test-clock builds", async function () {
+    if (!hasMockClock(program)) {
+      this.skip();
+    }
+    const nonce = new BN(Date.now());
+    const [limitOrder] = PublicKey.findProgramAddressSync(
+      [Buffer.from("limit_order"), user.publicKey.toBuffer(), nonce.toArrayLike(Buffer, "le", 8)],
+      program.programId
+    );
+    const [orderVault] = PublicKey.findProgramAddressSync(
+      [Buffer.from("order_vault"), limitOrder.toBuffer()],
+      program.programId
+    );
+    await program.methods
+      .initLimitOrder(nonce, 0, false)
+      .accounts({
+        vaultAuthority,
+        limitOrder,
+        inputVault: orderVault,
+        inputMint: sourceMint,
+        inputTokenProgram: TOKEN_PROGRAM_ID,
+        creator: user.publicKey,
+        payer: user.publicKey,
+        systemProgram: SystemProgram.programId,
+        rent: SYSVAR_RENT_PUBKEY,
+      })
+      .signers([user])
+      .rpc();
+
+    const expiry = Math.floor(Date.now() / 1000) + 3600;
+    const createOrder = (remainingAccounts) =>
+      program.methods
+        .createLimitOrder(
+          nonce,
+          new BN(1_000_000),
+          new BN(1_000_000),
+          500,
+          { takeProfit: {} },
+          new BN(expiry),
+          300,
+          false,
+          null,
+          null,
+          new BN(0)
+        )
+        .accounts({
+          vaultAuthority,
+          limitOrder,
+          inputVault: orderVault,
+          userInputTokenAccount: userSourceTokenAccount,
+          userDestinationTokenAccount,
+          inputMint: sourceMint,
+          outputMint: destinationMint,
+          inputTokenProgram: TOKEN_PROGRAM_ID,
+          outputTokenProgram: TOKEN_PROGRAM_ID,
+          creator: user.publicKey,
+          payer: user.publicKey,
+          systemProgram: SystemProgram.programId,
+        })
+        .remainingAccounts(remainingAccounts)
+        .signers([user])
+        .rpc();
+
+    await mintTo(
+      provider.connection,
+      wallet.payer,
+      sourceMint,
+      userSourceTokenAccount,
+      wallet.publicKey,
+      1_000_000
+    );
+
+    // Two hours ahead the expiry already lies in the past
+    await setMockClock(program, expiry + 3600);
+    try {
+      await createOrder([mockClockAccount(program.programId)]);
+      assert.fail("Order expiring before the mock time should be rejected");
+    } catch (error) {
+      assert.include(error.toString(), "InvalidExpiry");
+    }
+
+    // Without the MockClock the Clock sysvar applies again
+    await createOrder([]);
+    const order = await program.account.limitOrder.fetch(limitOrder);
+    assert.deepEqual(order.status, { open: {} });
+  });
+
+  it("27. Limit orders are capped by the protocol config limits", async () => {
+    const [protocolConfig] = PublicKey.findProgramAddressSync(
+      [Buffer.from("protocol_config")],
+      program.programId
+    );
+    if (!(await provider.connection.getAccountInfo(protocolConfig))) {
+      await program.methods
+        .createProtocolConfig()
+        .accounts({ payer: wallet.publicKey, authority: wallet.publicKey })
+        .signers([wallet.payer])
+        .rpc();
+    }
+    const defaults = (await program.account.protocolConfig.fetch(protocolConfig)).limits;
+    assert.equal(defaults.maxOrderSlippageBps, 1000);
+    assert.equal(defaults.maxTakeProfitTriggerBps, 100_000);
+
+    const nonce = new BN(Date.now());
+    const [limitOrder] = PublicKey.findProgramAddressSync(
+      [Buffer.from("limit_order"), user.publicKey.toBuffer(), nonce.toArrayLike(Buffer, "le", 8)],
+      program.programId
+    );
+    const [orderVault] = PublicKey.findProgramAddressSync(
+      [Buffer.from("order_vault"), limitOrder.toBuffer()],
+      program.programId
+    );
+    await program.methods
+      .initLimitOrder(nonce, 0, false)
+      .accounts({
+        vaultAuthority,
+        limitOrder,
+        inputVault: orderVault,
+        inputMint: sourceMint,
+        inputTokenProgram: TOKEN_PROGRAM_ID,
+        creator: user.publicKey,
+        payer: user.publicKey,
+        systemProgram: SystemProgram.programId,
+        rent: SYSVAR_RENT_PUBKEY,
+      })
+      .signers([user])
+      .rpc();
+    await mintTo(
+      provider.connection,
+      wallet.payer,
+      sourceMint,
+      userSourceTokenAccount,
+      wallet.publicKey,
+      1_000_000
+    );
+
+    const createOrder = () =>
+      program.methods
+        .createLimitOrder(
+          nonce,
+          new BN(1_000_000),
+          new BN(1_000_000),
+          500,
+          { takeProfit: {} },
+          new BN(Math.floor(Date.now() / 1000) + 3600),
+          300,
+          false,
+          null,
+          null,
+          new BN(0)
+        )
+        .accounts({
+          vaultAuthority,
+          limitOrder,
+          inputVault: orderVault,
+          userInputTokenAccount: userSourceTokenAccount,
+          userDestinationTokenAccount,
+          inputMint: sourceMint,
+          outputMint: destinationMint,
+          inputTokenProgram: TOKEN_PROGRAM_ID,
+          outputTokenProgram: TOKEN_PROGRAM_ID,
+          creator: user.publicKey,
+          payer: user.publicKey,
+          systemProgram: SystemProgram.programId,
+        })
+        .signers([user])
+        .rpc();
+    const setLimits = (limits) =>
+      program.methods
+        .setProtocolLimits(limits)
+        .accounts({ authority: wallet.publicKey })
+        .signers([wallet.payer])
+        .rpc();
+
+    // A 3% order slippage is over a 2% cap
+    await setLimits({ ...defaults, maxOrderSlippageBps: 200 });
+    try {
+      await createOrder();
+      assert.fail("Order slippage above the protocol cap should be rejected");
+    } catch (error) {
+      assert.include(error.toString(), "InvalidSlippage");
+    } finally {
+      await setLimits(defaults);
+    }
+
+    await createOrder();
+    const order = await program.account.limitOrder.fetch(limitOrder);
+    assert.equal(order.slippageBps, 300);
+  });
+
+  it("28. Order creation and cancellation report opposite open interest changes", async () => {
+    const nonce = new BN(Date.now());
+    const [limitOrder] = PublicKey.findProgramAddressSync(
+      [Buffer.from("limit_order"), user.publicKey.toBuffer(), nonce.toArrayLike(Buffer, "le", 8)],
+      program.programId
+    );
+    const [orderVault] = PublicKey.findProgramAddressSync(
+      [Buffer.from("order_vault"), limitOrder.toBuffer()],
+      program.programId
+    );
+    await program.methods
+      .initLimitOrder(nonce, 0, false)
+      .accounts({
+        vaultAuthority,
+        limitOrder,
+        inputVault: orderVault,
+        inputMint: sourceMint,
+        inputTokenProgram: TOKEN_PROGRAM_ID,
+        creator: user.publicKey,
+        payer: user.publicKey,
+        systemProgram: SystemProgram.programId,
+        rent: SYSVAR_RENT_PUBKEY,
+      })
+      .signers([user])
+      .rpc();
+    await mintTo(
+      provider.connection,
+      wallet.payer,
+      sourceMint,
+      userSourceTokenAccount,
+      wallet.publicKey,
+      750_000
+    );
+
+    const createSignature = await program.methods
+      .createLimitOrder(
+        nonce,
+        new BN(750_000),
+        new BN(700_000),
+        500,
+        { takeProfit: {} },
+        new BN(Math.floor(Date.now() / 1000) + 3600),
+        100,
+        false,
+        null,
+        null,
+        new BN(0)
+      )
+      .accounts({
+        vaultAuthority,
+        limitOrder,
+        inputVault: orderVault,
+        userInputTokenAccount: userSourceTokenAccount,
+        userDestinationTokenAccount,
+        inputMint: sourceMint,
+        outputMint: destinationMint,
+        inputTokenProgram: TOKEN_PROGRAM_ID,
+        outputTokenProgram: TOKEN_PROGRAM_ID,
+        creator: user.publicKey,
+        payer: user.publicKey,
+        systemProgram: SystemProgram.programId,
+      })
+      .signers([user])
+      .rpc({ commitment: "confirmed" });
+
+    const cancelSignature = await program.methods
+      .cancelLimitOrder()
+      .accounts({
+        vaultAuthority,
+        limitOrder,
+        inputVault: orderVault,
+        userInputTokenAccount: userSourceTokenAccount,
+        inputMint: sourceMint,
+        inputTokenProgram: TOKEN_PROGRAM_ID,
+        creator: user.publicKey,
+      })
+      .signers([user])
+      .rpc({ commitment: "confirmed" });
+
+    const [opened] = await cpiEventsOf(createSignature, "OpenInterestChanged");
+    const [released] = await cpiEventsOf(cancelSignature, "OpenInterestChanged");
+    assert.ok(opened.inputMint.equals(sourceMint));
+    assert.ok(opened.outputMint.equals(destinationMint));
+    assert.equal(opened.deltaInputAmount.toString(), "750000");
+    assert.equal(opened.openOrdersDelta, 1);
+    assert.ok(released.inputMint.equals(sourceMint));
+    assert.equal(released.deltaInputAmount.toString(), "-750000");
+    assert.equal(released.openOrdersDelta, -1);
+  });
+
+  it("29. Routes stay within their compute budget in telemetry builds", async function () {
+    if (!hasComputeTelemetry(program)) {
+      assert.isFalse(computeTelemetryRequired(), "FLIPPER_FEATURES requires a telemetry build");
+      this.skip();
+    }
+    const [inputPoolVault, outputPoolVault] =
+      sourceMint.toString() < destinationMint.toString()
+        ? [raydiumTokenAVault, raydiumTokenBVault]
+        : [raydiumTokenBVault, raydiumTokenAVault];
+    const amountIn = new BN(1_000_000);
+    await mintTo(
+      provider.connection,
+      wallet.payer,
+      sourceMint,
+      userSourceTokenAccount,
+      wallet.publicKey,
+      amountIn.toNumber()
+    );
+
+    const signature = await program.methods
+      .route(
+        [{ swap: { raydium: {} }, percent: 100, inputIndex: 0, outputIndex: 13 }],
+        amountIn,
+        new BN(1),
+        100,
+        0,
+        new BN(0),
+        false,
+        null,
+        false,
+        null
+      )
+      .accounts({
+        adapterRegistry,
+        vaultAuthority,
+        inputTokenProgram: TOKEN_PROGRAM_ID,
+        outputTokenProgram: TOKEN_PROGRAM_ID,
+        userTransferAuthority: user.publicKey,
+        userSourceTokenAccount,
+        userDestinationTokenAccount,
+        sourceMint,
+        destinationMint,
+        platformFeeAccount: null,
+        systemProgram: SystemProgram.programId,
+      })
+      .remainingAccounts([
+        { pubkey: inputVault, isWritable: true, isSigner: false },
+        { pubkey: raydiumPoolInfo, isWritable: true, isSigner: false },
+        { pubkey: raydiumPoolAuthority, isWritable: false, isSigner: false },
+        { pubkey: raydiumAmmConfig, isWritable: false, isSigner: false },
+        { pubkey: raydiumPoolState, isWritable: true, isSigner: false },
+        { pubkey: inputPoolVault, isWritable: true, isSigner: false },
+        { pubkey: outputPoolVault, isWritable: true, isSigner: false },
+        { pubkey: TOKEN_PROGRAM_ID, isWritable: false, isSigner: false },
+        { pubkey: TOKEN_PROGRAM_ID, isWritable: false, isSigner: false },
+        { pubkey: sourceMint, isWritable: false, isSigner: false },
+        { pubkey: destinationMint, isWritable: false, isSigner: false },
+        { pubkey: raydiumObservationState, isWritable: true, isSigner: false },
+        { pubkey: mockRaydiumProgramId, isWritable: false, isSigner: false },
+        { pubkey: outputVault, isWritable: true, isSigner: false },
+      ])
+      .signers([user])
+      .rpc({ commitment: "confirmed" });
+
+    const [telemetry] = await cpiEventsOf(signature, "ComputeTelemetry");
+    assert.equal(telemetry.instructionTag, ROUTE_TELEMETRY_TAG);
+    assert.equal(telemetry.steps, 1);
+    assert.isAbove(telemetry.cusConsumed.toNumber(), 0);
+    assert.isAtMost(
+      telemetry.cusConsumed.toNumber(),
+      COMPUTE_BUDGETS[ROUTE_TELEMETRY_TAG],
+      "route exceeded its compute budget"
+    );
+  });
+
+  it("30. Replacing an order resizes it vault to vault and links the events", async () => {
+    const orderAddresses = (nonce: BN) => {
+      const [limitOrder] = PublicKey.findProgramAddressSync(
+        [Buffer.from("limit_order"), user.publicKey.toBuffer(), nonce.toArrayLike(Buffer, "le", 8)],
+        program.programId
+      );
+      const [orderVault] = PublicKey.findProgramAddressSync(
+        [Buffer.from("order_vault"), limitOrder.toBuffer()],
+        program.programId
+      );
+      return { limitOrder, orderVault };
+    };
+    const initOrder = async (nonce: BN) => {
+      const { limitOrder, orderVault } = orderAddresses(nonce);
+      await program.methods
+        .initLimitOrder(nonce, 0, false)
+        .accounts({
+          vaultAuthority,
+          limitOrder,
+          inputVault: orderVault,
+          inputMint: sourceMint,
+          inputTokenProgram: TOKEN_PROGRAM_ID,
+          creator: user.publicKey,
+          payer: user.publicKey,
+          systemProgram: SystemProgram.programId,
+          rent: SYSVAR_RENT_PUBKEY,
+        })
+        .signers([user])
+        .rpc();
+      return { limitOrder, orderVault };
+    };
+    const sourceBalance = async () =>
+      (await getAccount(provider.connection, userSourceTokenAccount)).amount;
+    const expiry = new BN(Math.floor(Date.now() / 1000) + 3600);
+    const replace = (oldNonce: BN, newNonce: BN, inputAmount: number, creator: Keypair = user) => {
+      const oldOrder = orderAddresses(oldNonce);
+      const newOrder = orderAddresses(newNonce);
+      return program.methods
+        .replaceLimitOrder(
+          oldNonce,
+          newNonce,
+          new BN(inputAmount),
+          new BN(inputAmount),
+          500,
+          { takeProfit: {} },
+          expiry,
+          100,
+          new BN(0)
+        )
+        .accounts({
+          vaultAuthority,
+          oldOrder: oldOrder.limitOrder,
+          oldInputVault: oldOrder.orderVault,
+          newOrder: newOrder.limitOrder,
+          newInputVault: newOrder.orderVault,
+          userInputTokenAccount: userSourceTokenAccount,
+          userDestinationTokenAccount,
+          inputMint: sourceMint,
+          outputMint: destinationMint,
+          inputTokenProgram: TOKEN_PROGRAM_ID,
+          outputTokenProgram: TOKEN_PROGRAM_ID,
+          creator: creator.publicKey,
+          payer: user.publicKey,
+          systemProgram: SystemProgram.programId,
+        })
+        .signers(creator === user ? [user] : [user, creator]);
+    };
+
+    const firstNonce = new BN(Date.now());
+    const secondNonce = firstNonce.addn(1);
+    const thirdNonce = firstNonce.addn(2);
+    const first = await initOrder(firstNonce);
+    await mintTo(
+      provider.connection,
+      wallet.payer,
+      sourceMint,
+      userSourceTokenAccount,
+      wallet.publicKey,
+      1_500_000
+    );
+    await program.methods
+      .createLimitOrder(
+        firstNonce,
+        new BN(1_000_000),
+        new BN(950_000),
+        500,
+        { takeProfit: {} },
+        expiry,
+        100,
+        false,
+        null,
+        null,
+        new BN(0)
+      )
+      .accounts({
+        vaultAuthority,
+        limitOrder: first.limitOrder,
+        inputVault: first.orderVault,
+        userInputTokenAccount: userSourceTokenAccount,
+        userDestinationTokenAccount,
+        inputMint: sourceMint,
+        outputMint: destinationMint,
+        inputTokenProgram: TOKEN_PROGRAM_ID,
+        outputTokenProgram: TOKEN_PROGRAM_ID,
+        creator: user.publicKey,
+        payer: user.publicKey,
+        systemProgram: SystemProgram.programId,
+      })
+      .signers([user])
+      .rpc();
+    const second = await initOrder(secondNonce);
+
+    // Both orders must belong to the signer, and the replacement must not be funded yet
+    const stranger = Keypair.generate();
+    try {
+      await replace(firstNonce, secondNonce, 1_500_000, stranger).rpc();
+      assert.fail("Another signer should not be able to replace the order");
+    } catch (error) {
+      assert.match(error.toString(), /ConstraintSeeds|UnauthorizedAdmin/);
+    }
+    try {
+      await replace(secondNonce, firstNonce, 1_500_000).rpc();
+      assert.fail("An order in Init status should not be replaceable");
+    } catch (error) {
+      assert.include(error.toString(), "InvalidOrderStatus");
+    }
+
+    // Increase: the old vault moves over and the creator tops up the difference
+    let balanceBefore = await sourceBalance();
+    const increaseSignature = await replace(firstNonce, secondNonce, 1_500_000).rpc({ commitment: "confirmed" });
+    assert.equal((balanceBefore - (await sourceBalance())).toString(), "500000");
+    assert.equal((await getAccount(provider.connection, second.orderVault)).amount.toString(), "1500000");
+    assert.isNull(await provider.connection.getAccountInfo(first.limitOrder));
+    assert.isNull(await provider.connection.getAccountInfo(first.orderVault));
+    const secondOrder = await program.account.limitOrder.fetch(second.limitOrder);
+    assert.deepEqual(secondOrder.status, { open: {} });
+    assert.equal(secondOrder.inputAmount.toString(), "1500000");
+
+    const [increaseCancelled] = await cpiEventsOf(increaseSignature, "LimitOrderCancelled");
+    const [increaseCreated] = await cpiEventsOf(increaseSignature, "LimitOrderCreated");
+    assert.ok(increaseCancelled.order.equals(first.limitOrder));
+    assert.isNull(increaseCancelled.refundAccount);
+    assert.ok(increaseCreated.order.equals(second.limitOrder));
+    assert.ok(increaseCreated.replaces.equals(first.limitOrder));
+    const increaseInterest = await cpiEventsOf(increaseSignature, "OpenInterestChanged");
+    assert.deepEqual(
+      increaseInterest.map((event) => event.deltaInputAmount.toString()),
+      ["-1000000", "1500000"]
+    );
+
+    // Decrease: the difference is refunded from the old vault
+    const third = await initOrder(thirdNonce);
+    balanceBefore = await sourceBalance();
+    const decreaseSignature = await replace(secondNonce, thirdNonce, 600_000).rpc({ commitment: "confirmed" });
+    assert.equal(((await sourceBalance()) - balanceBefore).toString(), "900000");
+    assert.equal((await getAccount(provider.connection, third.orderVault)).amount.toString(), "600000");
+    assert.isNull(await provider.connection.getAccountInfo(second.limitOrder));
+
+    const [decreaseCancelled] = await cpiEventsOf(decreaseSignature, "LimitOrderCancelled");
+    const [decreaseCreated] = await cpiEventsOf(decreaseSignature, "LimitOrderCreated");
+    assert.ok(decreaseCancelled.refundAccount.equals(userSourceTokenAccount));
+    assert.ok(decreaseCreated.replaces.equals(second.limitOrder));
+    assert.equal(decreaseCreated.inputAmount.toString(), "600000");
+
+    await program.methods
+      .cancelLimitOrder()
+      .accounts({
+        vaultAuthority,
+        limitOrder: third.limitOrder,
+        inputVault: third.orderVault,
+        userInputTokenAccount: userSourceTokenAccount,
+        inputMint: sourceMint,
+        inputTokenProgram: TOKEN_PROGRAM_ID,
+        creator: user.publicKey,
+      })
+      .signers([user])
+      .rpc();
+  });
+
+  it("31. Order expiries must fall within the protocol duration bounds", async () => {
+    const [protocolConfig] = PublicKey.findProgramAddressSync(
+      [Buffer.from("protocol_config")],
+      program.programId
+    );
+    if (!(await provider.connection.getAccountInfo(protocolConfig))) {
+      await program.methods
+        .createProtocolConfig()
+        .accounts({ payer: wallet.publicKey, authority: wallet.publicKey })
+        .signers([wallet.payer])
+        .rpc();
+    }
+    const defaults = (await program.account.protocolConfig.fetch(protocolConfig)).limits;
+    assert.equal(defaults.minOrderDurationSeconds.toNumber(), 0);
+    assert.equal(defaults.maxOrderDurationSeconds.toNumber(), 0);
+    const setLimits = (limits) =>
+      program.methods
+        .setProtocolLimits(limits)
+        .accounts({ authority: wallet.publicKey })
+        .signers([wallet.payer])
+        .rpc();
+
+    // The minimum may not exceed a set maximum
+    try {
+      await setLimits({ ...defaults, minOrderDurationSeconds: new BN(7200), maxOrderDurationSeconds: new BN(600) });
+      assert.fail("A minimum duration above the maximum should be rejected");
+    } catch (error) {
+      assert.include(error.toString(), "InvalidProtocolLimits");
+    }
+
+    const nonce = new BN(Date.now());
+    const [limitOrder] = PublicKey.findProgramAddressSync(
+      [Buffer.from("limit_order"), user.publicKey.toBuffer(), nonce.toArrayLike(Buffer, "le", 8)],
+      program.programId
+    );
+    const [orderVault] = PublicKey.findProgramAddressSync(
+      [Buffer.from("order_vault"), limitOrder.toBuffer()],
+      program.programId
+    );
+    await program.methods
+      .initLimitOrder(nonce, 0, false)
+      .accounts({
+        vaultAuthority,
+        limitOrder,
+        inputVault: orderVault,
+        inputMint: sourceMint,
+        inputTokenProgram: TOKEN_PROGRAM_ID,
+        creator: user.publicKey,
+        payer: user.publicKey,
+        systemProgram: SystemProgram.programId,
+        rent: SYSVAR_RENT_PUBKEY,
+      })
+      .signers([user])
+      .rpc();
+    await mintTo(
+      provider.connection,
+      wallet.payer,
+      sourceMint,
+      userSourceTokenAccount,
+      wallet.publicKey,
+      1_000_000
+    );
+
+    const createOrder = (secondsToExpiry: number) =>
+      program.methods
+        .createLimitOrder(
+          nonce,
+          new BN(1_000_000),
+          new BN(1_000_000),
+          500,
+          { takeProfit: {} },
+          new BN(Math.floor(Date.now() / 1000) + secondsToExpiry),
+          100,
+          false,
+          null,
+          null,
+          new BN(0)
+        )
+        .accounts({
+          vaultAuthority,
+          limitOrder,
+          inputVault: orderVault,
+          userInputTokenAccount: userSourceTokenAccount,
+          userDestinationTokenAccount,
+          inputMint: sourceMint,
+          outputMint: destinationMint,
+          inputTokenProgram: TOKEN_PROGRAM_ID,
+          outputTokenProgram: TOKEN_PROGRAM_ID,
+          creator: user.publicKey,
+          payer: user.publicKey,
+          systemProgram: SystemProgram.programId,
+        })
+        .signers([user])
+        .rpc();
+
+    // Orders must last between 10 minutes and 2 hours
+    await setLimits({ ...defaults, minOrderDurationSeconds: new BN(600), maxOrderDurationSeconds: new BN(7200) });
+    try {
+      try {
+        await createOrder(30);
+        assert.fail("An order expiring before the minimum duration should be rejected");
+      } catch (error) {
+        assert.include(error.toString(), "OrderDurationTooShort");
+      }
+      try {
+        await createOrder(3 * 365 * 86_400);
+        assert.fail("An order expiring after the maximum duration should be rejected");
+      } catch (error) {
+        assert.include(error.toString(), "OrderDurationTooLong");
+      }
+      await createOrder(3600);
+    } finally {
+      await setLimits(defaults);
+    }
+
+    await program.methods
+      .cancelLimitOrder()
+      .accounts({
+        vaultAuthority,
+        limitOrder,
+        inputVault: orderVault,
+        userInputTokenAccount: userSourceTokenAccount,
+        inputMint: sourceMint,
+        inputTokenProgram: TOKEN_PROGRAM_ID,
+        creator: user.publicKey,
+      })
+      .signers([user])
+      .rpc();
+  });
+
+  it("32. Executing an order into a frozen destination fails before any route work", async () => {
+    // Output mint with a freeze authority, so the creator's destination can be frozen
+    const freezableMint = await createMint(
+      provider.connection,
+      wallet.payer,
+      wallet.publicKey,
+      wallet.publicKey,
+      9,
+      undefined,
+      undefined,
+      TOKEN_PROGRAM_ID
+    );
+    const frozenDestination = await createAssociatedTokenAccount(
+      provider.connection,
+      user,
+      freezableMint,
+      user.publicKey
+    );
+
+    const nonce = new BN(Date.now());
+    const [limitOrder] = PublicKey.findProgramAddressSync(
+      [Buffer.from("limit_order"), user.publicKey.toBuffer(), nonce.toArrayLike(Buffer, "le", 8)],
+      program.programId
+    );
+    const [orderVault] = PublicKey.findProgramAddressSync(
+      [Buffer.from("order_vault"), limitOrder.toBuffer()],
+      program.programId
+    );
+    await program.methods
+      .initLimitOrder(nonce, 0, false)
+      .accounts({
+        vaultAuthority,
+        limitOrder,
+        inputVault: orderVault,
+        inputMint: sourceMint,
+        inputTokenProgram: TOKEN_PROGRAM_ID,
+        creator: user.publicKey,
+        payer: user.publicKey,
+        systemProgram: SystemProgram.programId,
+        rent: SYSVAR_RENT_PUBKEY,
+      })
+      .signers([user])
+      .rpc();
+    await program.methods
+      .createLimitOrder(
+        nonce,
+        new BN(1_000_000),
+        new BN(1_000_000),
+        500,
+        { takeProfit: {} },
+        new BN(Math.floor(Date.now() / 1000) + 3600),
+        300,
+        false,
+        null,
+        null,
+        new BN(0)
+      )
+      .accounts({
+        vaultAuthority,
+        limitOrder,
+        inputVault: orderVault,
+        userInputTokenAccount: userSourceTokenAccount,
+        userDestinationTokenAccount: frozenDestination,
+        inputMint: sourceMint,
+        outputMint: freezableMint,
+        inputTokenProgram: TOKEN_PROGRAM_ID,
+        outputTokenProgram: TOKEN_PROGRAM_ID,
+        creator: user.publicKey,
+        payer: user.publicKey,
+        systemProgram: SystemProgram.programId,
+      })
+      .signers([user])
+      .rpc();
+
+    await freezeAccount(
+      provider.connection,
+      wallet.payer,
+      frozenDestination,
+      freezableMint,
+      wallet.payer
+    );
+
+    // The frozen state is checked ahead of route validation, so even an empty plan reports it
+    const execute = () =>
+      program.methods
+        .executeLimitOrder([], new BN(1_100_000), 0, null, null, null)
+        .accounts({
+          adapterRegistry,
+          vaultAuthority,
+          limitOrder,
+          inputVault: orderVault,
+          inputTokenProgram: TOKEN_PROGRAM_ID,
+          outputTokenProgram: TOKEN_PROGRAM_ID,
+          userDestinationTokenAccount: frozenDestination,
+          inputMint: sourceMint,
+          outputMint: freezableMint,
+          platformFeeAccount: null,
+          creatorDestinationAta: null,
+          creator: null,
+          associatedTokenProgram: null,
+          operator: operator.publicKey,
+          systemProgram: SystemProgram.programId,
+        })
+        .signers([operator])
+        .rpc();
+    try {
+      await execute();
+      assert.fail("Execution into a frozen destination should be rejected");
+    } catch (error) {
+      assert.include(error.toString(), "DestinationFrozen");
+    }
+
+    // Once thawed, execution proceeds to route validation
+    await thawAccount(
+      provider.connection,
+      wallet.payer,
+      frozenDestination,
+      freezableMint,
+      wallet.payer
+    );
+    try {
+      await execute();
+      assert.fail("An empty route should be rejected");
+    } catch (error) {
+      assert.include(error.toString(), "EmptyRoute");
+    }
+
+    await program.methods
+      .cancelLimitOrder()
+      .accounts({
+        vaultAuthority,
+        limitOrder,
+        inputVault: orderVault,
+        userInputTokenAccount: userSourceTokenAccount,
+        inputMint: sourceMint,
+        inputTokenProgram: TOKEN_PROGRAM_ID,
+        creator: user.publicKey,
+      })
+      .signers([user])
+      .rpc();
+  });
+
+  it("33. Routes skip pools whose liquidity attestation is stale while attestations are required", async () => {
+    const [inputPoolVault, outputPoolVault] =
+      sourceMint.toString() < destinationMint.toString()
+        ? [raydiumTokenAVault, raydiumTokenBVault]
+        : [raydiumTokenBVault, raydiumTokenAVault];
+    const amountIn = new BN(1_000_000);
+    await mintTo(
+      provider.connection,
+      wallet.payer,
+      sourceMint,
+      userSourceTokenAccount,
+      wallet.publicKey,
+      amountIn.toNumber()
+    );
+
+    const reserveBalances = async () => [
+      (await getAccount(provider.connection, raydiumTokenAVault)).amount,
+      (await getAccount(provider.connection, raydiumTokenBVault)).amount,
+    ];
+    const setThreshold = (threshold: BN) =>
+      program.methods
+        .setPoolLiquidityThreshold({ raydium: {} }, raydiumPoolState, threshold)
+        .accounts({ poolInfo: raydiumPoolInfo, adapterRegistry, operator: wallet.publicKey })
+        .signers([wallet.payer])
+        .rpc();
+    const setMaxAge = (maxAgeSlots: BN) =>
+      program.methods
+        .setPoolAttestationMaxAge(maxAgeSlots)
+        .accounts({ adapterRegistry, operator: wallet.publicKey })
+        .signers([wallet.payer])
+        .rpc();
+    // Anyone may attest: the user pays, no operator involved
+    const attest = () =>
+      program.methods
+        .attestPoolLiquidity()
+        .accounts({
+          poolInfo: raydiumPoolInfo,
+          pool: raydiumPoolState,
+          reserveA: raydiumTokenAVault,
+          reserveB: raydiumTokenBVault,
+          adapterRegistry,
+        })
+        .signers([user])
+        .rpc();
+    const routeThroughPool = () =>
+      program.methods
+        .route(
+          [{ swap: { raydium: {} }, percent: 100, inputIndex: 0, outputIndex: 13 }],
+          amountIn,
+          new BN(1),
+          100,
+          0,
+          new BN(0),
+          false,
+          null,
+          false,
+          null
+        )
+        .accounts({
+          adapterRegistry,
+          vaultAuthority,
+          inputTokenProgram: TOKEN_PROGRAM_ID,
+          outputTokenProgram: TOKEN_PROGRAM_ID,
+          userTransferAuthority: user.publicKey,
+          userSourceTokenAccount,
+          userDestinationTokenAccount,
+          sourceMint,
+          destinationMint,
+          platformFeeAccount: null,
+          systemProgram: SystemProgram.programId,
+        })
+        .remainingAccounts([
+          { pubkey: inputVault, isWritable: true, isSigner: false },
+          { pubkey: raydiumPoolInfo, isWritable: true, isSigner: false },
+          { pubkey: raydiumPoolAuthority, isWritable: false, isSigner: false },
+          { pubkey: raydiumAmmConfig, isWritable: false, isSigner: false },
+          { pubkey: raydiumPoolState, isWritable: true, isSigner: false },
+          { pubkey: inputPoolVault, isWritable: true, isSigner: false },
+          { pubkey: outputPoolVault, isWritable: true, isSigner: false },
+          { pubkey: TOKEN_PROGRAM_ID, isWritable: false, isSigner: false },
+          { pubkey: TOKEN_PROGRAM_ID, isWritable: false, isSigner: false },
+          { pubkey: sourceMint, isWritable: false, isSigner: false },
+          { pubkey: destinationMint, isWritable: false, isSigner: false },
+          { pubkey: raydiumObservationState, isWritable: true, isSigner: false },
+          { pubkey: mockRaydiumProgramId, isWritable: false, isSigner: false },
+          { pubkey: outputVault, isWritable: true, isSigner: false },
+        ])
+        .signers([user])
+        .rpc();
+
+    // A threshold above both reserves makes the pool look drained
+    const [reserveA, reserveB] = await reserveBalances();
+    const threshold = reserveA > reserveB ? reserveA : reserveB;
+    await setThreshold(new BN(threshold.toString()));
+    await setMaxAge(new BN(1_000));
+    try {
+      const registry = await program.account.adapterRegistry.fetch(adapterRegistry);
+      assert.equal(registry.poolAttestationMaxAgeSlots.toNumber(), 1_000);
+
+      await attest();
+      let poolInfo = await program.account.poolInfo.fetch(raydiumPoolInfo);
+      assert.equal(poolInfo.lastVerifiedSlot.toNumber(), 0, "A drained pool is not attested");
+
+      try {
+        await routeThroughPool();
+        assert.fail("A route through an unattested pool should be rejected");
+      } catch (error) {
+        assert.include(error.toString(), "PoolAttestationStale");
+      }
+
+      // A deposit lifts both reserves over the threshold; the pool stays stale until re-attested
+      const [tokenAMint, tokenBMint] =
+        sourceMint.toString() < destinationMint.toString()
+          ? [sourceMint, destinationMint]
+          : [destinationMint, sourceMint];
+      for (const [mint, poolVault, balance] of [
+        [tokenAMint, raydiumTokenAVault, reserveA],
+        [tokenBMint, raydiumTokenBVault, reserveB],
+      ] as [PublicKey, PublicKey, bigint][]) {
+        await mintTo(provider.connection, wallet.payer, mint, poolVault, wallet.publicKey, threshold - balance + BigInt(1));
+      }
+      try {
+        await routeThroughPool();
+        assert.fail("A route should stay rejected until the pool is re-attested");
+      } catch (error) {
+        assert.include(error.toString(), "PoolAttestationStale");
+      }
+
+      await attest();
+      poolInfo = await program.account.poolInfo.fetch(raydiumPoolInfo);
+      assert.isAbove(poolInfo.lastVerifiedSlot.toNumber(), 0, "The replenished pool is attested");
+
+      const initialDest = (await getAccount(provider.connection, userDestinationTokenAccount)).amount;
+      await routeThroughPool();
+      const finalDest = (await getAccount(provider.connection, userDestinationTokenAccount)).amount;
+      assert(finalDest > initialDest, "Destination balance should increase");
+    } finally {
+      await setMaxAge(new BN(0));
+      await setThreshold(new BN(0));
+    }
+  });
+
+  it("34. Fee accounts are ignored without a platform fee and required with one", async () => {
+    const [inputPoolVault, outputPoolVault] =
+      sourceMint.toString() < destinationMint.toString()
+        ? [raydiumTokenAVault, raydiumTokenBVault]
+        : [raydiumTokenBVault, raydiumTokenAVault];
+    const swapAccounts = (outputAccount: PublicKey, inputAccount: PublicKey = inputVault) => [
+      { pubkey: inputAccount, isWritable: true, isSigner: false },
+      { pubkey: raydiumPoolInfo, isWritable: true, isSigner: false },
+      { pubkey: raydiumPoolAuthority, isWritable: false, isSigner: false },
+      { pubkey: raydiumAmmConfig, isWritable: false, isSigner: false },
+      { pubkey: raydiumPoolState, isWritable: true, isSigner: false },
+      { pubkey: inputPoolVault, isWritable: true, isSigner: false },
+      { pubkey: outputPoolVault, isWritable: true, isSigner: false },
+      { pubkey: TOKEN_PROGRAM_ID, isWritable: false, isSigner: false },
+      { pubkey: TOKEN_PROGRAM_ID, isWritable: false, isSigner: false },
+      { pubkey: sourceMint, isWritable: false, isSigner: false },
+      { pubkey: destinationMint, isWritable: false, isSigner: false },
+      { pubkey: raydiumObservationState, isWritable: true, isSigner: false },
+      { pubkey: mockRaydiumProgramId, isWritable: false, isSigner: false },
+      { pubkey: outputAccount, isWritable: true, isSigner: false },
+    ];
+    const routePlan = [
+      { swap: { raydium: {} }, percent: 100, inputIndex: 0, outputIndex: 13 },
+    ];
+    const amountIn = new BN(1_000_000);
+    await mintTo(
+      provider.connection,
+      wallet.payer,
+      sourceMint,
+      userSourceTokenAccount,
+      wallet.publicKey,
+      amountIn.muln(2).toNumber()
+    );
+
+    // A fee account every fee site would reject as unsafe if it were validated
+    const delegatedFeeAccount = await createAccount(
+      provider.connection,
+      user,
+      destinationMint,
+      user.publicKey,
+      Keypair.generate(),
+      undefined,
+      TOKEN_PROGRAM_ID
+    );
+    await approve(
+      provider.connection,
+      user,
+      delegatedFeeAccount,
+      Keypair.generate().publicKey,
+      user,
+      1
+    );
+    const assertNoFeeTaken = async (signature: string) => {
+      assert.isEmpty(await feeEventsOf(signature), "No FeeEvent without a platform fee");
+      const feeAccount = await getAccount(provider.connection, delegatedFeeAccount);
+      assert.equal(feeAccount.amount.toString(), "0", "The ignored fee account is untouched");
+    };
+
+    // route
+    const route = (platformFeeBps: number, feeAccount: PublicKey | null) =>
+      program.methods
+        .route(routePlan, amountIn, new BN(1), 100, platformFeeBps, new BN(0), false, null, false, null)
+        .accounts({
+          adapterRegistry,
+          vaultAuthority,
+          inputTokenProgram: TOKEN_PROGRAM_ID,
+          outputTokenProgram: TOKEN_PROGRAM_ID,
+          userTransferAuthority: user.publicKey,
+          userSourceTokenAccount,
+          userDestinationTokenAccount,
+          sourceMint,
+          destinationMint,
+          platformFeeAccount: feeAccount,
+          systemProgram: SystemProgram.programId,
+        })
+        .remainingAccounts(swapAccounts(outputVault))
+        .signers([user])
+        .rpc({ commitment: "confirmed" });
+    try {
+      await route(10, null);
+      assert.fail("A route charging a fee without a fee account should be rejected");
+    } catch (error) {
+      assert.include(error.toString(), "MissingFeeAccount");
+    }
+    await assertNoFeeTaken(await route(0, delegatedFeeAccount));
+
+    // execute_limit_order
+    const nonce = new BN(Date.now());
+    const [limitOrder] = PublicKey.findProgramAddressSync(
+      [
+        Buffer.from("limit_order"),
+        user.publicKey.toBuffer(),
+        nonce.toArrayLike(Buffer, "le", 8),
+      ],
+      program.programId
+    );
+    const [orderVault] = PublicKey.findProgramAddressSync(
+      [Buffer.from("order_vault"), limitOrder.toBuffer()],
+      program.programId
+    );
+    await program.methods
+      .initLimitOrder(nonce, 0, false)
+      .accounts({
+        vaultAuthority,
+        limitOrder,
+        inputVault: orderVault,
+        inputMint: sourceMint,
+        inputTokenProgram: TOKEN_PROGRAM_ID,
+        creator: user.publicKey,
+        payer: user.publicKey,
+        systemProgram: SystemProgram.programId,
+        rent: SYSVAR_RENT_PUBKEY,
+      })
+      .signers([user])
+      .rpc();
+    await program.methods
+      .createLimitOrder(
+        nonce,
+        amountIn,
+        new BN(100_000),
+        500,
+        { takeProfit: {} },
+        new BN(Math.floor(Date.now() / 1000) + 3600),
+        300,
+        false,
+        null,
+        null,
+        new BN(0)
+      )
+      .accounts({
+        vaultAuthority,
+        limitOrder,
+        inputVault: orderVault,
+        userInputTokenAccount: userSourceTokenAccount,
+        userDestinationTokenAccount,
+        inputMint: sourceMint,
+        outputMint: destinationMint,
+        inputTokenProgram: TOKEN_PROGRAM_ID,
+        outputTokenProgram: TOKEN_PROGRAM_ID,
+        creator: user.publicKey,
+        payer: user.publicKey,
+        systemProgram: SystemProgram.programId,
+      })
+      .signers([user])
+      .rpc();
+    const execute = (platformFeeBps: number, feeAccount: PublicKey | null) =>
+      program.methods
+        .executeLimitOrder(routePlan, new BN(110_000), platformFeeBps, null, null, null)
+        .accounts({
+          adapterRegistry,
+          vaultAuthority,
+          limitOrder,
+          inputVault: orderVault,
+          inputTokenProgram: TOKEN_PROGRAM_ID,
+          outputTokenProgram: TOKEN_PROGRAM_ID,
+          userDestinationTokenAccount,
+          inputMint: sourceMint,
+          outputMint: destinationMint,
+          platformFeeAccount: feeAccount,
+          operator: operator.publicKey,
+          systemProgram: SystemProgram.programId,
+        })
+        .remainingAccounts(swapAccounts(outputVault, orderVault))
+        .signers([operator])
+        .rpc({ commitment: "confirmed" });
+    try {
+      await execute(10, null);
+      assert.fail("An execution charging a fee without a fee account should be rejected");
+    } catch (error) {
+      assert.include(error.toString(), "MissingFeeAccount");
+    }
+    await assertNoFeeTaken(await execute(0, delegatedFeeAccount));
+    assert.isNull(await provider.connection.getAccountInfo(limitOrder));
+
+    // route_and_create_order
+    const parkNonce = new BN(Date.now() + 1);
+    const [parkedOrder] = PublicKey.findProgramAddressSync(
+      [
+        Buffer.from("limit_order"),
+        user.publicKey.toBuffer(),
+        parkNonce.toArrayLike(Buffer, "le", 8),
+      ],
+      program.programId
+    );
+    const [parkedVault] = PublicKey.findProgramAddressSync(
+      [Buffer.from("order_vault"), parkedOrder.toBuffer()],
+      program.programId
+    );
+    await program.methods
+      .initLimitOrder(parkNonce, 0, false)
+      .accounts({
+        vaultAuthority,
+        limitOrder: parkedOrder,
+        inputVault: parkedVault,
+        inputMint: destinationMint,
+        inputTokenProgram: TOKEN_PROGRAM_ID,
+        creator: user.publicKey,
+        payer: user.publicKey,
+        systemProgram: SystemProgram.programId,
+        rent: SYSVAR_RENT_PUBKEY,
+      })
+      .signers([user])
+      .rpc();
+    const routeAndCreate = (platformFeeBps: number, feeAccount: PublicKey | null) =>
+      program.methods
+        .routeAndCreateOrder(
+          parkNonce,
+          routePlan,
+          amountIn,
+          new BN(1),
+          100,
+          platformFeeBps,
+          new BN(1_000_000),
+          500,
+          new BN(Math.floor(Date.now() / 1000) + 7200),
+          300,
+          null
+        )
+        .accounts({
+          adapterRegistry,
+          vaultAuthority,
+          limitOrder: parkedOrder,
+          inputVault: parkedVault,
+          userInputAccount: userSourceTokenAccount,
+          userDestinationAccount: userSourceTokenAccount,
+          inputMint: sourceMint,
+          outputMint: destinationMint,
+          inputTokenProgram: TOKEN_PROGRAM_ID,
+          outputTokenProgram: TOKEN_PROGRAM_ID,
+          platformFeeAccount: feeAccount,
+          creator: user.publicKey,
+          systemProgram: SystemProgram.programId,
+        })
+        .remainingAccounts(swapAccounts(parkedVault))
+        .signers([user])
+        .rpc({ commitment: "confirmed" });
+    try {
+      await routeAndCreate(50, null);
+      assert.fail("Parking an order while charging a fee without a fee account should be rejected");
+    } catch (error) {
+      assert.include(error.toString(), "MissingFeeAccount");
+    }
+    await assertNoFeeTaken(await routeAndCreate(0, delegatedFeeAccount));
+    const parked = await getAccount(provider.connection, parkedVault);
+    assert(parked.amount > 0n, "The swap output is parked without a fee");
+  });
+
+  it("35. Sponsored order vaults refund their rent to the recorded payer on close", async () => {
+    const sponsor = Keypair.generate();
+    await provider.sendAndConfirm(
+      new Transaction().add(
+        SystemProgram.transfer({
+          fromPubkey: wallet.publicKey,
+          toPubkey: sponsor.publicKey,
+          lamports: 100_000_000,
+        })
+      )
+    );
+    const [inputPoolVault, outputPoolVault] =
+      sourceMint.toString() < destinationMint.toString()
+        ? [raydiumTokenAVault, raydiumTokenBVault]
+        : [raydiumTokenBVault, raydiumTokenAVault];
+    await mintTo(
+      provider.connection,
+      wallet.payer,
+      sourceMint,
+      userSourceTokenAccount,
+      wallet.publicKey,
+      2_000_000
+    );
+
+    let nonceOffset = 0;
+    const initOrder = async (payer: Keypair, prefundRecord = false) => {
+      const nonce = new BN(Date.now() + nonceOffset++);
+      const [limitOrder] = PublicKey.findProgramAddressSync(
+        [
+          Buffer.from("limit_order"),
+          user.publicKey.toBuffer(),
+          nonce.toArrayLike(Buffer, "le", 8),
+        ],
+        program.programId
+      );
+      const [orderVault] = PublicKey.findProgramAddressSync(
+        [Buffer.from("order_vault"), limitOrder.toBuffer()],
+        program.programId
+      );
+      const [vaultRentPayer] = PublicKey.findProgramAddressSync(
+        [Buffer.from("vault_rent_payer"), orderVault.toBuffer()],
+        program.programId
+      );
+      if (prefundRecord) {
+        await provider.sendAndConfirm(
+          new Transaction().add(
+            SystemProgram.transfer({
+              fromPubkey: wallet.publicKey,
+              toPubkey: vaultRentPayer,
+              lamports: 1_000_000,
+            })
+          )
+        );
+      }
+      await program.methods
+        .initLimitOrder(nonce, 0, false)
+        .accounts({
+          vaultAuthority,
+          limitOrder,
+          inputVault: orderVault,
+          inputMint: sourceMint,
+          inputTokenProgram: TOKEN_PROGRAM_ID,
+          creator: user.publicKey,
+          payer: payer.publicKey,
+          systemProgram: SystemProgram.programId,
+          rent: SYSVAR_RENT_PUBKEY,
+        })
+        .signers(payer === user ? [user] : [user, payer])
+        .rpc();
+      return { nonce, limitOrder, orderVault, vaultRentPayer };
+    };
+    const openOrder = (nonce: BN, limitOrder: PublicKey, orderVault: PublicKey) =>
+      program.methods
+        .createLimitOrder(
+          nonce,
+          new BN(1_000_000),
+          new BN(100_000),
+          500,
+          { takeProfit: {} },
+          new BN(Math.floor(Date.now() / 1000) + 3600),
+          300,
+          false,
+          null,
+          null,
+          new BN(0)
+        )
+        .accounts({
+          vaultAuthority,
+          limitOrder,
+          inputVault: orderVault,
+          userInputTokenAccount: userSourceTokenAccount,
+          userDestinationTokenAccount,
+          inputMint: sourceMint,
+          outputMint: destinationMint,
+          inputTokenProgram: TOKEN_PROGRAM_ID,
+          outputTokenProgram: TOKEN_PROGRAM_ID,
+          creator: user.publicKey,
+          payer: user.publicKey,
+          systemProgram: SystemProgram.programId,
+        })
+        .signers([user])
+        .rpc();
+    const cancel = (limitOrder: PublicKey, orderVault: PublicKey, rentPayer: PublicKey | null) =>
+      program.methods
+        .cancelLimitOrder()
+        .accounts({
+          vaultAuthority,
+          limitOrder,
+          inputVault: orderVault,
+          userInputTokenAccount: userSourceTokenAccount,
+          inputMint: sourceMint,
+          inputTokenProgram: TOKEN_PROGRAM_ID,
+          rentPayer,
+          creator: user.publicKey,
+        })
+        .signers([user])
+        .rpc();
+    // The sponsor gets back the vault rent and the rent of the record itself
+    const assertRefunded = async (
+      refund: () => Promise<string>,
+      orderVault: PublicKey,
+      vaultRentPayer: PublicKey
+    ) => {
+      const vaultRent = await provider.connection.getBalance(orderVault);
+      const recordRent = await provider.connection.getBalance(vaultRentPayer);
+      const sponsorBefore = await provider.connection.getBalance(sponsor.publicKey);
+      await refund();
+      assert.isNull(await provider.connection.getAccountInfo(orderVault));
+      assert.isNull(await provider.connection.getAccountInfo(vaultRentPayer));
+      assert.equal(
+        await provider.connection.getBalance(sponsor.publicKey),
+        sponsorBefore + vaultRent + recordRent,
+        "The sponsor should be refunded the vault rent"
+      );
+    };
+
+    // A creator-funded vault has no record and closes as before
+    const own = await initOrder(user);
+    assert.isNull(await provider.connection.getAccountInfo(own.vaultRentPayer));
+    await cancel(own.limitOrder, own.orderVault, null);
+    assert.isNull(await provider.connection.getAccountInfo(own.orderVault));
+
+    // A sponsored vault records its payer
+    const sponsored = await initOrder(sponsor);
+    const record = await program.account.vaultRentPayer.fetch(sponsored.vaultRentPayer);
+    assert.ok(record.vault.equals(sponsored.orderVault));
+    assert.ok(record.payer.equals(sponsor.publicKey));
+
+    // Lamports sent to the record's address beforehand cannot block a sponsored vault
+    const prefunded = await initOrder(sponsor, true);
+    const prefundedRecord = await program.account.vaultRentPayer.fetch(prefunded.vaultRentPayer);
+    assert.ok(prefundedRecord.payer.equals(sponsor.publicKey));
+    await cancel(prefunded.limitOrder, prefunded.orderVault, sponsor.publicKey);
+    assert.isNull(await provider.connection.getAccountInfo(prefunded.vaultRentPayer));
+
+    // Closing it requires the recorded payer
+    for (const rentPayer of [null, user.publicKey]) {
+      try {
+        await cancel(sponsored.limitOrder, sponsored.orderVault, rentPayer);
+        assert.fail("Cancelling without the recorded rent payer should be rejected");
+      } catch (error) {
+        assert.include(error.toString(), "InvalidRentPayer");
+      }
+    }
+
+    // cancel_limit_order, Open order
+    await openOrder(sponsored.nonce, sponsored.limitOrder, sponsored.orderVault);
+    await assertRefunded(
+      () => cancel(sponsored.limitOrder, sponsored.orderVault, sponsor.publicKey),
+      sponsored.orderVault,
+      sponsored.vaultRentPayer
+    );
+
+    // execute_limit_order
+    const filled = await initOrder(sponsor);
+    await openOrder(filled.nonce, filled.limitOrder, filled.orderVault);
+    await assertRefunded(
+      () =>
+        program.methods
+          .executeLimitOrder(
+            [{ swap: { raydium: {} }, percent: 100, inputIndex: 0, outputIndex: 13 }],
+            new BN(110_000),
+            0,
+            null,
+            null,
+            null
+          )
+          .accounts({
+            adapterRegistry,
+            vaultAuthority,
+            limitOrder: filled.limitOrder,
+            inputVault: filled.orderVault,
+            inputTokenProgram: TOKEN_PROGRAM_ID,
+            outputTokenProgram: TOKEN_PROGRAM_ID,
+            userDestinationTokenAccount,
+            inputMint: sourceMint,
+            outputMint: destinationMint,
+            platformFeeAccount: null,
+            rentPayer: sponsor.publicKey,
+            operator: operator.publicKey,
+            systemProgram: SystemProgram.programId,
+          })
+          .remainingAccounts([
+            { pubkey: filled.orderVault, isWritable: true, isSigner: false },
+            { pubkey: raydiumPoolInfo, isWritable: true, isSigner: false },
+            { pubkey: raydiumPoolAuthority, isWritable: false, isSigner: false },
+            { pubkey: raydiumAmmConfig, isWritable: false, isSigner: false },
+            { pubkey: raydiumPoolState, isWritable: true, isSigner: false },
+            { pubkey: inputPoolVault, isWritable: true, isSigner: false },
+            { pubkey: outputPoolVault, isWritable: true, isSigner: false },
+            { pubkey: TOKEN_PROGRAM_ID, isWritable: false, isSigner: false },
+            { pubkey: TOKEN_PROGRAM_ID, isWritable: false, isSigner: false },
+            { pubkey: sourceMint, isWritable: false, isSigner: false },
+            { pubkey: destinationMint, isWritable: false, isSigner: false },
+            { pubkey: raydiumObservationState, isWritable: true, isSigner: false },
+            { pubkey: mockRaydiumProgramId, isWritable: false, isSigner: false },
+            { pubkey: outputVault, isWritable: true, isSigner: false },
+          ])
+          .signers([operator])
+          .rpc(),
+      filled.orderVault,
+      filled.vaultRentPayer
+    );
+    assert.isNull(await provider.connection.getAccountInfo(filled.limitOrder));
+
+    // close_limit_order_by_operator, Init order
+    const abandoned = await initOrder(sponsor);
+    await assertRefunded(
+      () =>
+        program.methods
+          .closeLimitOrderByOperator()
+          .accounts({
+            adapterRegistry,
+            vaultAuthority,
+            limitOrder: abandoned.limitOrder,
+            inputVault: abandoned.orderVault,
+            inputTokenProgram: TOKEN_PROGRAM_ID,
+            rentPayer: sponsor.publicKey,
+            operator: operator.publicKey,
+            systemProgram: SystemProgram.programId,
+          })
+          .signers([operator])
+          .rpc(),
+      abandoned.orderVault,
+      abandoned.vaultRentPayer
+    );
+  });
+
+  it("36. Routes and limit order fills emit their decoded plan in a RoutePlanEvent", async () => {
+    const [inputPoolVault, outputPoolVault] =
+      sourceMint.toString() < destinationMint.toString()
+        ? [raydiumTokenAVault, raydiumTokenBVault]
+        : [raydiumTokenBVault, raydiumTokenAVault];
+    const swapAccounts = (inputAccount: PublicKey) => [
+      { pubkey: inputAccount, isWritable: true, isSigner: false },
+      { pubkey: raydiumPoolInfo, isWritable: true, isSigner: false },
+      { pubkey: raydiumPoolAuthority, isWritable: false, isSigner: false },
+      { pubkey: raydiumAmmConfig, isWritable: false, isSigner: false },
+      { pubkey: raydiumPoolState, isWritable: true, isSigner: false },
+      { pubkey: inputPoolVault, isWritable: true, isSigner: false },
+      { pubkey: outputPoolVault, isWritable: true, isSigner: false },
+      { pubkey: TOKEN_PROGRAM_ID, isWritable: false, isSigner: false },
+      { pubkey: TOKEN_PROGRAM_ID, isWritable: false, isSigner: false },
+      { pubkey: sourceMint, isWritable: false, isSigner: false },
+      { pubkey: destinationMint, isWritable: false, isSigner: false },
+      { pubkey: raydiumObservationState, isWritable: true, isSigner: false },
+      { pubkey: mockRaydiumProgramId, isWritable: false, isSigner: false },
+      { pubkey: outputVault, isWritable: true, isSigner: false },
+    ];
+    const routePlan = [
+      { swap: { raydium: {} }, percent: 100, inputIndex: 0, outputIndex: 13 },
+    ];
+    const amountIn = new BN(1_000_000);
+    await mintTo(
+      provider.connection,
+      wallet.payer,
+      sourceMint,
+      userSourceTokenAccount,
+      wallet.publicKey,
+      amountIn.muln(2).toNumber()
+    );
+
+    // One summary per step: the Raydium tag (7), its percent and its PoolInfo account
+    const assertPlanEvent = async (signature: string) => {
+      const planEvents = await cpiEventsOf(signature, "RoutePlanEvent");
+      assert.lengthOf(planEvents, 1);
+      assert.lengthOf(planEvents[0].steps, 1);
+      const [step] = planEvents[0].steps;
+      assert.equal(step.swapTag, 7);
+      assert.equal(step.percent, 100);
+      assert.isTrue(step.pool.equals(raydiumPoolInfo));
+    };
+
+    // route
+    await assertPlanEvent(
+      await program.methods
+        .route(routePlan, amountIn, new BN(1), 100, 0, new BN(0), false, null, false, null)
+        .accounts({
+          adapterRegistry,
+          vaultAuthority,
+          inputTokenProgram: TOKEN_PROGRAM_ID,
+          outputTokenProgram: TOKEN_PROGRAM_ID,
+          userTransferAuthority: user.publicKey,
+          userSourceTokenAccount,
+          userDestinationTokenAccount,
+          sourceMint,
+          destinationMint,
+          platformFeeAccount: null,
+          systemProgram: SystemProgram.programId,
+        })
+        .remainingAccounts(swapAccounts(inputVault))
+        .signers([user])
+        .rpc({ commitment: "confirmed" })
+    );
+
+    // execute_limit_order
+    const nonce = new BN(Date.now());
+    const [limitOrder] = PublicKey.findProgramAddressSync(
+      [
+        Buffer.from("limit_order"),
+        user.publicKey.toBuffer(),
+        nonce.toArrayLike(Buffer, "le", 8),
+      ],
+      program.programId
+    );
+    const [orderVault] = PublicKey.findProgramAddressSync(
+      [Buffer.from("order_vault"), limitOrder.toBuffer()],
+      program.programId
+    );
+    await program.methods
+      .initLimitOrder(nonce, 0, false)
+      .accounts({
+        vaultAuthority,
+        limitOrder,
+        inputVault: orderVault,
+        inputMint: sourceMint,
+        inputTokenProgram: TOKEN_PROGRAM_ID,
+        creator: user.publicKey,
+        payer: user.publicKey,
+        systemProgram: SystemProgram.programId,
+        rent: SYSVAR_RENT_PUBKEY,
+      })
+      .signers([user])
+      .rpc();
+    await program.methods
+      .createLimitOrder(
+        nonce,
+        amountIn,
+        new BN(100_000),
+        500,
+        { takeProfit: {} },
+        new BN(Math.floor(Date.now() / 1000) + 3600),
+        300,
+        false,
+        null,
+        null,
+        new BN(0)
+      )
+      .accounts({
+        vaultAuthority,
+        limitOrder,
+        inputVault: orderVault,
+        userInputTokenAccount: userSourceTokenAccount,
+        userDestinationTokenAccount,
+        inputMint: sourceMint,
+        outputMint: destinationMint,
+        inputTokenProgram: TOKEN_PROGRAM_ID,
+        outputTokenProgram: TOKEN_PROGRAM_ID,
+        creator: user.publicKey,
+        payer: user.publicKey,
+        systemProgram: SystemProgram.programId,
+      })
+      .signers([user])
+      .rpc();
+    await assertPlanEvent(
+      await program.methods
+        .executeLimitOrder(routePlan, new BN(110_000), 0, null, null, null)
+        .accounts({
+          adapterRegistry,
+          vaultAuthority,
+          limitOrder,
+          inputVault: orderVault,
+          inputTokenProgram: TOKEN_PROGRAM_ID,
+          outputTokenProgram: TOKEN_PROGRAM_ID,
+          userDestinationTokenAccount,
+          inputMint: sourceMint,
+          outputMint: destinationMint,
+          platformFeeAccount: null,
+          operator: operator.publicKey,
+          systemProgram: SystemProgram.programId,
+        })
+        .remainingAccounts(swapAccounts(orderVault))
+        .signers([operator])
+        .rpc({ commitment: "confirmed" })
+    );
+  });
+
+  it("37. Orders whose input and output mints are identical are rejected at creation", async () => {
+    const nonce = new BN(Date.now());
+    const [limitOrder] = PublicKey.findProgramAddressSync(
+      [
+        Buffer.from("limit_order"),
+        user.publicKey.toBuffer(),
+        nonce.toArrayLike(Buffer, "le", 8),
+      ],
+      program.programId
+    );
+    const [orderVault] = PublicKey.findProgramAddressSync(
+      [Buffer.from("order_vault"), limitOrder.toBuffer()],
+      program.programId
+    );
+    await program.methods
+      .initLimitOrder(nonce, 0, false)
+      .accounts({
+        vaultAuthority,
+        limitOrder,
+        inputVault: orderVault,
+        inputMint: sourceMint,
+        inputTokenProgram: TOKEN_PROGRAM_ID,
+        creator: user.publicKey,
+        payer: user.publicKey,
+        systemProgram: SystemProgram.programId,
+        rent: SYSVAR_RENT_PUBKEY,
+      })
+      .signers([user])
+      .rpc();
+
+    // A "parking" order: same mint in and out, with the source account as destination
+    try {
+      await program.methods
+        .createLimitOrder(
+          nonce,
+          new BN(1_000),
+          new BN(1_000),
+          500,
+          { takeProfit: {} },
+          new BN(Math.floor(Date.now() / 1000) + 3600),
+          300,
+          false,
+          null,
+          null,
+          new BN(0)
+        )
+        .accounts({
+          vaultAuthority,
+          limitOrder,
+          inputVault: orderVault,
+          userInputTokenAccount: userSourceTokenAccount,
+          userDestinationTokenAccount: userSourceTokenAccount,
+          inputMint: sourceMint,
+          outputMint: sourceMint,
+          inputTokenProgram: TOKEN_PROGRAM_ID,
+          outputTokenProgram: TOKEN_PROGRAM_ID,
+          creator: user.publicKey,
+          payer: user.publicKey,
+          systemProgram: SystemProgram.programId,
+        })
+        .signers([user])
+        .rpc();
+      assert.fail("An order with identical input and output mints should be rejected");
+    } catch (error) {
+      assert.include(error.toString(), "IdenticalMints");
+    }
+
+    const order = await program.account.limitOrder.fetch(limitOrder);
+    assert.deepEqual(order.status, { init: {} });
+    const vault = await getAccount(provider.connection, orderVault);
+    assert.equal(vault.amount.toString(), "0");
+  });
+
+  it("38. Order creation and execution pause independently while cancels still work", async () => {
+    const [protocolConfig] = PublicKey.findProgramAddressSync(
+      [Buffer.from("protocol_config")],
+      program.programId
+    );
+    if (!(await provider.connection.getAccountInfo(protocolConfig))) {
+      await program.methods
+        .createProtocolConfig()
+        .accounts({ payer: wallet.publicKey, authority: wallet.publicKey })
+        .signers([wallet.payer])
+        .rpc();
+    }
+    const setCreationPaused = (paused: boolean) =>
+      program.methods
+        .setOrderCreationPaused(paused)
+        .accounts({ operator: wallet.publicKey })
+        .signers([wallet.payer])
+        .rpc();
+    const setExecutionPaused = (paused: boolean) =>
+      program.methods
+        .setOrderExecutionPaused(paused)
+        .accounts({ operator: wallet.publicKey })
+        .signers([wallet.payer])
+        .rpc();
+
+    // Only registry operators may pause
+    try {
+      await program.methods
+        .setOrderCreationPaused(true)
+        .accounts({ operator: user.publicKey })
+        .signers([user])
+        .rpc();
+      assert.fail("A non-operator should not be able to pause order creation");
+    } catch (error) {
+      assert.include(error.toString(), "InvalidOperator");
+    }
+
+    const nonce = new BN(Date.now());
+    const [limitOrder] = PublicKey.findProgramAddressSync(
+      [Buffer.from("limit_order"), user.publicKey.toBuffer(), nonce.toArrayLike(Buffer, "le", 8)],
+      program.programId
+    );
+    const [orderVault] = PublicKey.findProgramAddressSync(
+      [Buffer.from("order_vault"), limitOrder.toBuffer()],
+      program.programId
+    );
+    await program.methods
+      .initLimitOrder(nonce, 0, false)
+      .accounts({
+        vaultAuthority,
+        limitOrder,
+        inputVault: orderVault,
+        inputMint: sourceMint,
+        inputTokenProgram: TOKEN_PROGRAM_ID,
+        creator: user.publicKey,
+        payer: user.publicKey,
+        systemProgram: SystemProgram.programId,
+        rent: SYSVAR_RENT_PUBKEY,
+      })
+      .signers([user])
+      .rpc();
+    await mintTo(
+      provider.connection,
+      wallet.payer,
+      sourceMint,
+      userSourceTokenAccount,
+      wallet.publicKey,
+      1_000_000
+    );
+
+    const createOrder = () =>
+      program.methods
+        .createLimitOrder(
+          nonce,
+          new BN(1_000_000),
+          new BN(1_000_000),
+          500,
+          { takeProfit: {} },
+          new BN(Math.floor(Date.now() / 1000) + 3600),
+          300,
+          false,
+          null,
+          null,
+          new BN(0)
+        )
+        .accounts({
+          vaultAuthority,
+          limitOrder,
+          inputVault: orderVault,
+          userInputTokenAccount: userSourceTokenAccount,
+          userDestinationTokenAccount,
+          inputMint: sourceMint,
+          outputMint: destinationMint,
+          inputTokenProgram: TOKEN_PROGRAM_ID,
+          outputTokenProgram: TOKEN_PROGRAM_ID,
+          creator: user.publicKey,
+          payer: user.publicKey,
+          systemProgram: SystemProgram.programId,
+        })
+        .signers([user])
+        .rpc();
+    const execute = () =>
+      program.methods
+        .executeLimitOrder([], new BN(1_100_000), 0, null, null, null)
+        .accounts({
+          adapterRegistry,
+          vaultAuthority,
+          limitOrder,
+          inputVault: orderVault,
+          inputTokenProgram: TOKEN_PROGRAM_ID,
+          outputTokenProgram: TOKEN_PROGRAM_ID,
+          userDestinationTokenAccount,
+          inputMint: sourceMint,
+          outputMint: destinationMint,
+          platformFeeAccount: null,
+          creatorDestinationAta: null,
+          creator: null,
+          associatedTokenProgram: null,
+          operator: operator.publicKey,
+          systemProgram: SystemProgram.programId,
+        })
+        .signers([operator])
+        .rpc();
+
+    // Creation paused: new orders are rejected
+    await setCreationPaused(true);
+    let config = await program.account.protocolConfig.fetch(protocolConfig);
+    assert.isTrue(config.creationPaused);
+    assert.isFalse(config.executionPaused);
+    try {
+      await createOrder();
+      assert.fail("Order creation should be rejected while paused");
+    } catch (error) {
+      assert.include(error.toString(), "OrderCreationPaused");
+    }
+
+    // Execution paused alone does not block creation
+    await setCreationPaused(false);
+    await setExecutionPaused(true);
+    config = await program.account.protocolConfig.fetch(protocolConfig);
+    assert.isFalse(config.creationPaused);
+    assert.isTrue(config.executionPaused);
+    await createOrder();
+    try {
+      await execute();
+      assert.fail("Order execution should be rejected while paused");
+    } catch (error) {
+      assert.include(error.toString(), "OrderExecutionPaused");
+    }
+
+    // Once resumed, execution proceeds to route validation
+    await setExecutionPaused(false);
+    try {
+      await execute();
+      assert.fail("An empty route should be rejected");
+    } catch (error) {
+      assert.include(error.toString(), "EmptyRoute");
+    }
+
+    // Replacing opens a new order, so the creation pause blocks it too
+    const replacementNonce = nonce.addn(1);
+    const [replacementOrder] = PublicKey.findProgramAddressSync(
+      [Buffer.from("limit_order"), user.publicKey.toBuffer(), replacementNonce.toArrayLike(Buffer, "le", 8)],
+      program.programId
+    );
+    const [replacementVault] = PublicKey.findProgramAddressSync(
+      [Buffer.from("order_vault"), replacementOrder.toBuffer()],
+      program.programId
+    );
+    await program.methods
+      .initLimitOrder(replacementNonce, 0, false)
+      .accounts({
+        vaultAuthority,
+        limitOrder: replacementOrder,
+        inputVault: replacementVault,
+        inputMint: sourceMint,
+        inputTokenProgram: TOKEN_PROGRAM_ID,
+        creator: user.publicKey,
+        payer: user.publicKey,
+        systemProgram: SystemProgram.programId,
+        rent: SYSVAR_RENT_PUBKEY,
+      })
+      .signers([user])
+      .rpc();
+    await setCreationPaused(true);
+    try {
+      await program.methods
+        .replaceLimitOrder(
+          nonce,
+          replacementNonce,
+          new BN(1_000_000),
+          new BN(1_000_000),
+          500,
+          { takeProfit: {} },
+          new BN(Math.floor(Date.now() / 1000) + 3600),
+          300,
+          new BN(0)
+        )
+        .accounts({
+          vaultAuthority,
+          oldOrder: limitOrder,
+          oldInputVault: orderVault,
+          newOrder: replacementOrder,
+          newInputVault: replacementVault,
+          userInputTokenAccount: userSourceTokenAccount,
+          userDestinationTokenAccount,
+          inputMint: sourceMint,
+          outputMint: destinationMint,
+          inputTokenProgram: TOKEN_PROGRAM_ID,
+          outputTokenProgram: TOKEN_PROGRAM_ID,
+          creator: user.publicKey,
+          payer: user.publicKey,
+          systemProgram: SystemProgram.programId,
+        })
+        .signers([user])
+        .rpc();
+      assert.fail("Replacing an order should be rejected while creation is paused");
+    } catch (error) {
+      assert.include(error.toString(), "OrderCreationPaused");
+    }
+    await setCreationPaused(false);
+
+    // Cancels ignore both pauses
+    await setCreationPaused(true);
+    await setExecutionPaused(true);
+    await program.methods
+      .cancelLimitOrder()
+      .accounts({
+        vaultAuthority,
+        limitOrder,
+        inputVault: orderVault,
+        userInputTokenAccount: userSourceTokenAccount,
+        inputMint: sourceMint,
+        inputTokenProgram: TOKEN_PROGRAM_ID,
+        creator: user.publicKey,
+      })
+      .signers([user])
+      .rpc();
+    assert.isNull(await provider.connection.getAccountInfo(limitOrder));
+
+    await setCreationPaused(false);
+    await setExecutionPaused(false);
+  });
+
+  it("39. Routes spend a delegated source up to the delegation and reject other signers", async () => {
+    const custodian = Keypair.generate();
+    const stranger = Keypair.generate();
+    const delegation = 1_000_000n;
+
+    // User-owned source that the custodian may spend as delegate, never as owner
+    const custodialSource = await createAccount(
+      provider.connection,
+      wallet.payer,
+      sourceMint,
+      user.publicKey,
+      Keypair.generate(),
+      undefined,
+      TOKEN_PROGRAM_ID
+    );
+    await mintTo(
+      provider.connection,
+      wallet.payer,
+      sourceMint,
+      custodialSource,
+      wallet.publicKey,
+      2n * delegation
+    );
+    await approve(
+      provider.connection,
+      wallet.payer,
+      custodialSource,
+      custodian.publicKey,
+      user,
+      delegation
+    );
+    // The route destination must still belong to the signer
+    const destinationOf = (owner: Keypair) =>
+      createAccount(
+        provider.connection,
+        wallet.payer,
+        destinationMint,
+        owner.publicKey,
+        Keypair.generate(),
+        undefined,
+        TOKEN_PROGRAM_ID
+      );
+    const custodianDestination = await destinationOf(custodian);
+    const strangerDestination = await destinationOf(stranger);
+
+    const [inputPoolVault, outputPoolVault] =
+      sourceMint.toString() < destinationMint.toString()
+        ? [raydiumTokenAVault, raydiumTokenBVault]
+        : [raydiumTokenBVault, raydiumTokenAVault];
+    const routeAs = (signer: Keypair, destination: PublicKey, amount: BN) =>
+      program.methods
+        .route(
+          [{ swap: { raydium: {} }, percent: 100, inputIndex: 0, outputIndex: 13 }],
+          amount,
+          new BN(1),
+          100,
+          0,
+          new BN(0),
+          false,
+          null,
+          false,
+          null
+        )
+        .accounts({
+          adapterRegistry,
+          vaultAuthority,
+          inputTokenProgram: TOKEN_PROGRAM_ID,
+          outputTokenProgram: TOKEN_PROGRAM_ID,
+          userTransferAuthority: signer.publicKey,
+          userSourceTokenAccount: custodialSource,
+          userDestinationTokenAccount: destination,
+          sourceMint,
+          destinationMint,
+          platformFeeAccount: null,
+          systemProgram: SystemProgram.programId,
+        })
+        .remainingAccounts([
+          { pubkey: inputVault, isWritable: true, isSigner: false },
+          { pubkey: raydiumPoolInfo, isWritable: true, isSigner: false },
+          { pubkey: raydiumPoolAuthority, isWritable: false, isSigner: false },
+          { pubkey: raydiumAmmConfig, isWritable: false, isSigner: false },
+          { pubkey: raydiumPoolState, isWritable: true, isSigner: false },
+          { pubkey: inputPoolVault, isWritable: true, isSigner: false },
+          { pubkey: outputPoolVault, isWritable: true, isSigner: false },
+          { pubkey: TOKEN_PROGRAM_ID, isWritable: false, isSigner: false },
+          { pubkey: TOKEN_PROGRAM_ID, isWritable: false, isSigner: false },
+          { pubkey: sourceMint, isWritable: false, isSigner: false },
+          { pubkey: destinationMint, isWritable: false, isSigner: false },
+          { pubkey: raydiumObservationState, isWritable: true, isSigner: false },
+          { pubkey: mockRaydiumProgramId, isWritable: false, isSigner: false },
+          { pubkey: outputVault, isWritable: true, isSigner: false },
+        ])
+        .signers([signer])
+        .rpc();
+
+    // A signer that is neither owner nor delegate cannot spend the account
+    try {
+      await routeAs(stranger, strangerDestination, new BN(1_000));
+      assert.fail("A signer that is neither owner nor delegate should be rejected");
+    } catch (error) {
+      assert.include(error.toString(), "ConstraintRaw");
+    }
+
+    // The delegate cannot exceed its delegation
+    try {
+      await routeAs(custodian, custodianDestination, new BN((delegation + 1n).toString()));
+      assert.fail("Routing more than the delegation should be rejected");
+    } catch (error) {
+      assert.include(error.toString(), "InsufficientDelegation");
+    }
+
+    // A delegate sweep moves exactly the delegation, which the token program uses up
+    await routeAs(custodian, custodianDestination, new BN("18446744073709551615"));
+    const source = await getAccount(provider.connection, custodialSource);
+    assert.equal(source.amount, delegation);
+    assert.equal(source.delegatedAmount, 0n);
+    assert.isNull(source.delegate);
+    assert((await getAccount(provider.connection, custodianDestination)).amount > 0n);
+
+    // With the delegation spent the custodian is just another signer
+    try {
+      await routeAs(custodian, custodianDestination, new BN(1_000));
+      assert.fail("A spent delegation should no longer authorize routes");
+    } catch (error) {
+      assert.include(error.toString(), "ConstraintRaw");
+    }
+  });
+
+  it("40. Order fills reject routes whose later steps read another order's vault", async () => {
+    const inputAmount = 1_000_000;
+    await mintTo(
+      provider.connection,
+      wallet.payer,
+      sourceMint,
+      userSourceTokenAccount,
+      wallet.publicKey,
+      2 * inputAmount
+    );
+
+    // Opens a take profit order of sourceMint and returns its order and vault addresses
+    const openOrder = async (nonce: BN) => {
+      const [limitOrder] = PublicKey.findProgramAddressSync(
+        [Buffer.from("limit_order"), user.publicKey.toBuffer(), nonce.toArrayLike(Buffer, "le", 8)],
+        program.programId
+      );
+      const [orderVault] = PublicKey.findProgramAddressSync(
+        [Buffer.from("order_vault"), limitOrder.toBuffer()],
+        program.programId
+      );
+      await program.methods
+        .initLimitOrder(nonce, 0, false)
+        .accounts({
+          vaultAuthority,
+          limitOrder,
+          inputVault: orderVault,
+          inputMint: sourceMint,
+          inputTokenProgram: TOKEN_PROGRAM_ID,
+          creator: user.publicKey,
+          payer: user.publicKey,
+          systemProgram: SystemProgram.programId,
+          rent: SYSVAR_RENT_PUBKEY,
+        })
+        .signers([user])
+        .rpc();
+      await program.methods
+        .createLimitOrder(
+          nonce,
+          new BN(inputAmount),
+          new BN(1_000_000),
+          500,
+          { takeProfit: {} },
+          new BN(Math.floor(Date.now() / 1000) + 3600),
+          300,
+          false,
+          null,
+          null,
+          new BN(0)
+        )
+        .accounts({
+          vaultAuthority,
+          limitOrder,
+          inputVault: orderVault,
+          userInputTokenAccount: userSourceTokenAccount,
+          userDestinationTokenAccount,
+          inputMint: sourceMint,
+          outputMint: destinationMint,
+          inputTokenProgram: TOKEN_PROGRAM_ID,
+          outputTokenProgram: TOKEN_PROGRAM_ID,
+          creator: user.publicKey,
+          payer: user.publicKey,
+          systemProgram: SystemProgram.programId,
+        })
+        .signers([user])
+        .rpc();
+      return { limitOrder, orderVault };
+    };
+    const nonce = new BN(Date.now());
+    const order = await openOrder(nonce);
+    const victim = await openOrder(nonce.addn(1));
+
+    const [inputPoolVault, outputPoolVault] =
+      sourceMint.toString() < destinationMint.toString()
+        ? [raydiumTokenAVault, raydiumTokenBVault]
+        : [raydiumTokenBVault, raydiumTokenAVault];
+    // Raydium step accounts reading `vault`, paying into the shared output vault
+    const raydiumStepAccounts = (vault: PublicKey) => [
+      { pubkey: vault, isWritable: true, isSigner: false },
+      { pubkey: raydiumPoolInfo, isWritable: true, isSigner: false },
+      { pubkey: raydiumPoolAuthority, isWritable: false, isSigner: false },
+      { pubkey: raydiumAmmConfig, isWritable: false, isSigner: false },
+      { pubkey: raydiumPoolState, isWritable: true, isSigner: false },
+      { pubkey: inputPoolVault, isWritable: true, isSigner: false },
+      { pubkey: outputPoolVault, isWritable: true, isSigner: false },
+      { pubkey: TOKEN_PROGRAM_ID, isWritable: false, isSigner: false },
+      { pubkey: TOKEN_PROGRAM_ID, isWritable: false, isSigner: false },
+      { pubkey: sourceMint, isWritable: false, isSigner: false },
+      { pubkey: destinationMint, isWritable: false, isSigner: false },
+      { pubkey: raydiumObservationState, isWritable: true, isSigner: false },
+      { pubkey: mockRaydiumProgramId, isWritable: false, isSigner: false },
+      { pubkey: outputVault, isWritable: true, isSigner: false },
+    ];
+    // The order's own vault first, then a step spending the victim order's vault
+    const routePlan = [
+      { swap: { raydium: {} }, percent: 100, inputIndex: 0, outputIndex: 13 },
+      { swap: { raydium: {} }, percent: 100, inputIndex: 14, outputIndex: 27 },
+    ];
+    const remainingAccounts = [
+      ...raydiumStepAccounts(order.orderVault),
+      ...raydiumStepAccounts(victim.orderVault),
+    ];
+    const orderAccounts = {
+      adapterRegistry,
+      vaultAuthority,
+      limitOrder: order.limitOrder,
+      inputVault: order.orderVault,
+      inputTokenProgram: TOKEN_PROGRAM_ID,
+      outputTokenProgram: TOKEN_PROGRAM_ID,
+      inputMint: sourceMint,
+      outputMint: destinationMint,
+    };
+
+    try {
+      await program.methods
+        .validateOrderExecution(routePlan, new BN(1_100_000))
+        .accounts(orderAccounts)
+        .remainingAccounts(remainingAccounts)
+        .view();
+      assert.fail("Dry-run should reject a step reading another order's vault");
+    } catch (error) {
+      assert.include(error.toString(), "InvalidVaultAddress");
+    }
+    try {
+      await program.methods
+        .executeLimitOrder(routePlan, new BN(1_100_000), 0, null, null, null)
+        .accounts({
+          ...orderAccounts,
+          userDestinationTokenAccount,
+          platformFeeAccount: null,
+          creatorDestinationAta: null,
+          creator: null,
+          associatedTokenProgram: null,
+          operator: operator.publicKey,
+          systemProgram: SystemProgram.programId,
+        })
+        .remainingAccounts(remainingAccounts)
+        .signers([operator])
+        .rpc();
+      assert.fail("A fill should not spend another order's vault");
+    } catch (error) {
+      assert.include(error.toString(), "InvalidVaultAddress");
+    }
+    assert.equal(
+      (await getAccount(provider.connection, victim.orderVault)).amount,
+      BigInt(inputAmount)
+    );
+
+    for (const { limitOrder, orderVault } of [order, victim]) {
+      await program.methods
+        .cancelLimitOrder()
+        .accounts({
+          vaultAuthority,
+          limitOrder,
+          inputVault: orderVault,
+          userInputTokenAccount: userSourceTokenAccount,
+          inputMint: sourceMint,
+          inputTokenProgram: TOKEN_PROGRAM_ID,
+          creator: user.publicKey,
+        })
+        .signers([user])
+        .rpc();
+    }
+  });
+
+  it("41. Route platform fees drop to the volume tier a trader's recorded volume reaches", async () => {
+    const [protocolConfig] = PublicKey.findProgramAddressSync(
+      [Buffer.from("protocol_config")],
+      program.programId
+    );
+    if (!(await provider.connection.getAccountInfo(protocolConfig))) {
+      await program.methods
+        .createProtocolConfig()
+        .accounts({ payer: wallet.publicKey, authority: wallet.publicKey })
+        .signers([wallet.payer])
+        .rpc();
+    }
+    const [traderVolume] = PublicKey.findProgramAddressSync(
+      [Buffer.from("trader_volume"), user.publicKey.toBuffer()],
+      program.programId
+    );
+    const setVolumeFeeTiers = (volumeMint: PublicKey, tiers: { minVolume: BN; feeMultiplierBps: number }[]) =>
+      program.methods
+        .setVolumeFeeTiers(volumeMint, tiers)
+        .accounts({ authority: wallet.publicKey })
+        .signers([wallet.payer])
+        .rpc();
+
+    // Registry authority only, and tiers must ascend
+    try {
+      await program.methods
+        .setVolumeFeeTiers(sourceMint, [])
+        .accounts({ authority: user.publicKey })
+        .signers([user])
+        .rpc();
+      assert.fail("Only the registry authority may set volume tiers");
+    } catch (error) {
+      assert.include(error.toString(), "InvalidAuthority");
+    }
+    try {
+      await setVolumeFeeTiers(sourceMint, [
+        { minVolume: new BN(2_000_000), feeMultiplierBps: 8_000 },
+        { minVolume: new BN(1_000_000), feeMultiplierBps: 5_000 },
+      ]);
+      assert.fail("Descending tiers should be rejected");
+    } catch (error) {
+      assert.include(error.toString(), "InvalidVolumeFeeTiers");
+    }
+
+    // Half the fee from 1 source token of volume
+    await setVolumeFeeTiers(sourceMint, [{ minVolume: new BN(1_000_000), feeMultiplierBps: 5_000 }]);
+    const config = await program.account.protocolConfig.fetch(protocolConfig);
+    assert(config.volumeMint.equals(sourceMint));
+    assert.equal(config.volumeTierCount, 1);
+
+    const [inputPoolVault, outputPoolVault] =
+      sourceMint.toString() < destinationMint.toString()
+        ? [raydiumTokenAVault, raydiumTokenBVault]
+        : [raydiumTokenBVault, raydiumTokenAVault];
+    const remainingAccounts = [
+      { pubkey: inputVault, isWritable: true, isSigner: false },
+      { pubkey: raydiumPoolInfo, isWritable: true, isSigner: false },
+      { pubkey: raydiumPoolAuthority, isWritable: false, isSigner: false },
+      { pubkey: raydiumAmmConfig, isWritable: false, isSigner: false },
+      { pubkey: raydiumPoolState, isWritable: true, isSigner: false },
+      { pubkey: inputPoolVault, isWritable: true, isSigner: false },
+      { pubkey: outputPoolVault, isWritable: true, isSigner: false },
+      { pubkey: TOKEN_PROGRAM_ID, isWritable: false, isSigner: false },
+      { pubkey: TOKEN_PROGRAM_ID, isWritable: false, isSigner: false },
+      { pubkey: sourceMint, isWritable: false, isSigner: false },
+      { pubkey: destinationMint, isWritable: false, isSigner: false },
+      { pubkey: raydiumObservationState, isWritable: true, isSigner: false },
+      { pubkey: mockRaydiumProgramId, isWritable: false, isSigner: false },
+      { pubkey: outputVault, isWritable: true, isSigner: false },
+    ];
+    const routePlan = [
+      { swap: { raydium: {} }, percent: 100, inputIndex: 0, outputIndex: 13 },
+    ];
+    const amountIn = new BN(1_000_000);
+    await mintTo(
+      provider.connection,
+      wallet.payer,
+      sourceMint,
+      userSourceTokenAccount,
+      wallet.publicKey,
+      amountIn.muln(3).toNumber()
+    );
+    const route = (traderVolumeAccount: PublicKey | null) =>
+      program.methods
+        .route(routePlan, amountIn, new BN(1), 100, 50, new BN(0), false, null, false, null)
+        .accounts({
+          adapterRegistry,
+          vaultAuthority,
+          inputTokenProgram: TOKEN_PROGRAM_ID,
+          outputTokenProgram: TOKEN_PROGRAM_ID,
+          userTransferAuthority: user.publicKey,
+          userSourceTokenAccount,
+          userDestinationTokenAccount,
+          sourceMint,
+          destinationMint,
+          platformFeeAccount,
+          traderVolume: traderVolumeAccount,
+          systemProgram: SystemProgram.programId,
+        })
+        .remainingAccounts(remainingAccounts)
+        .signers([user])
+        .rpc({ commitment: "confirmed" });
+    const windowVolume = async () =>
+      (await program.account.traderVolume.fetch(traderVolume)).bucketVolumes
+        .reduce((total: BN, volume: BN) => total.add(volume), new BN(0))
+        .toNumber();
+
+    // The first route pays the full fee and creates the trader's volume record
+    let [feeEvent] = await feeEventsOf(await route(traderVolume));
+    assert.equal(feeEvent.feeBps, 50);
+    const record = await program.account.traderVolume.fetch(traderVolume);
+    assert(record.trader.equals(user.publicKey));
+    assert.equal(await windowVolume(), 1_000_000);
+
+    // Its volume reaches the tier, so the next route pays half
+    [feeEvent] = await feeEventsOf(await route(traderVolume));
+    assert.equal(feeEvent.feeBps, 25);
+    assert.equal(await windowVolume(), 2_000_000);
+
+    // Without the record there is no rebate and nothing is counted
+    [feeEvent] = await feeEventsOf(await route(null));
+    assert.equal(feeEvent.feeBps, 50);
+    assert.equal(await windowVolume(), 2_000_000);
+
+    await setVolumeFeeTiers(PublicKey.default, []);
+  });
+
+  it("42. Operators sponsoring open order vaults are only removed by force, handing them to the treasury", async () => {
+    const sponsorOperator = Keypair.generate();
+    await provider.sendAndConfirm(
+      new Transaction().add(
+        SystemProgram.transfer({
+          fromPubkey: wallet.publicKey,
+          toPubkey: sponsorOperator.publicKey,
+          lamports: 100_000_000,
+        })
+      )
+    );
+    const registryAccount = await program.account.adapterRegistry.fetch(adapterRegistry);
+    const authority = registryAccount.authority;
+    await program.methods
+      .addOperator(sponsorOperator.publicKey)
+      .accounts({ adapterRegistry, authority })
+      .signers([wallet.payer])
+      .rpc();
+    const setTreasury = (treasuryKey: PublicKey) =>
+      program.methods
+        .setOrderCreationFee(new BN(0), treasuryKey)
+        .accounts({ adapterRegistry, authority })
+        .signers([wallet.payer])
+        .rpc();
+    await setTreasury(treasury.publicKey);
+
+    const [operatorStats] = PublicKey.findProgramAddressSync(
+      [Buffer.from("operator_stats"), sponsorOperator.publicKey.toBuffer()],
+      program.programId
+    );
+    const obligations = async () =>
+      (await program.account.operatorStats.fetch(operatorStats)).outstandingObligations;
+
+    let nonceOffset = 0;
+    const sponsorOrder = async () => {
+      const nonce = new BN(Date.now() + nonceOffset++);
+      const [limitOrder] = PublicKey.findProgramAddressSync(
+        [
+          Buffer.from("limit_order"),
+          user.publicKey.toBuffer(),
+          nonce.toArrayLike(Buffer, "le", 8),
+        ],
+        program.programId
+      );
+      const [orderVault] = PublicKey.findProgramAddressSync(
+        [Buffer.from("order_vault"), limitOrder.toBuffer()],
+        program.programId
+      );
+      await program.methods
+        .initLimitOrder(nonce, 0, false)
+        .accounts({
+          vaultAuthority,
+          limitOrder,
+          inputVault: orderVault,
+          inputMint: sourceMint,
+          inputTokenProgram: TOKEN_PROGRAM_ID,
+          creator: user.publicKey,
+          payer: sponsorOperator.publicKey,
+          systemProgram: SystemProgram.programId,
+          rent: SYSVAR_RENT_PUBKEY,
+        })
+        .signers([user, sponsorOperator])
+        .rpc();
+      return { limitOrder, orderVault };
+    };
+    const cancel = (order: { limitOrder: PublicKey; orderVault: PublicKey }, rentPayer: PublicKey) =>
+      program.methods
+        .cancelLimitOrder()
+        .accounts({
+          vaultAuthority,
+          limitOrder: order.limitOrder,
+          inputVault: order.orderVault,
+          userInputTokenAccount: userSourceTokenAccount,
+          inputMint: sourceMint,
+          inputTokenProgram: TOKEN_PROGRAM_ID,
+          rentPayer,
+          rentPayerStats: operatorStats,
+          creator: user.publicKey,
+        })
+        .signers([user])
+        .rpc();
+    const removeOperator = (force: boolean) =>
+      program.methods
+        .removeOperator(sponsorOperator.publicKey, force)
+        .accounts({ adapterRegistry, authority })
+        .signers([wallet.payer])
+        .rpc({ commitment: "confirmed" });
+
+    try {
+      // Each sponsored vault is counted in the operator's stats, created with the first one
+      const settled = await sponsorOrder();
+      const outstanding = await sponsorOrder();
+      assert.equal(await obligations(), 2);
+
+      // Closing a sponsored vault settles its obligation
+      await cancel(settled, sponsorOperator.publicKey);
+      assert.equal(await obligations(), 1);
+
+      // The open vault blocks a plain removal
+      try {
+        await removeOperator(false);
+        assert.fail("Removing an operator with obligations should require force");
+      } catch (error) {
+        assert.include(error.toString(), "OperatorHasObligations");
+      }
+      let registry = await program.account.adapterRegistry.fetch(adapterRegistry);
+      assert.isTrue(registry.operators.some((op: PublicKey) => op.equals(sponsorOperator.publicKey)));
+
+      // Forcing it hands the vault to the treasury
+      const signature = await removeOperator(true);
+      const [removed] = await cpiEventsOf(signature, "OperatorRemoved");
+      assert.equal(removed.transferredObligations, 1);
+      registry = await program.account.adapterRegistry.fetch(adapterRegistry);
+      assert.isFalse(registry.operators.some((op: PublicKey) => op.equals(sponsorOperator.publicKey)));
+      const stats = await program.account.operatorStats.fetch(operatorStats);
+      assert.ok(stats.obligationsTransferredTo.equals(treasury.publicKey));
+
+      // The vault rent now goes to the treasury, not the removed operator
+      try {
+        await cancel(outstanding, sponsorOperator.publicKey);
+        assert.fail("The removed operator should no longer be refunded");
+      } catch (error) {
+        assert.include(error.toString(), "InvalidRentPayer");
+      }
+      const [vaultRentPayer] = PublicKey.findProgramAddressSync(
+        [Buffer.from("vault_rent_payer"), outstanding.orderVault.toBuffer()],
+        program.programId
+      );
+      const vaultRent = await provider.connection.getBalance(outstanding.orderVault);
+      const recordRent = await provider.connection.getBalance(vaultRentPayer);
+      const treasuryBefore = await provider.connection.getBalance(treasury.publicKey);
+      await cancel(outstanding, treasury.publicKey);
+      assert.equal(
+        await provider.connection.getBalance(treasury.publicKey),
+        treasuryBefore + vaultRent + recordRent,
+        "The treasury should be refunded the vault rent"
+      );
+      assert.equal(await obligations(), 0);
+    } finally {
+      await setTreasury(PublicKey.default);
+    }
+  });
+
+  it("43. Failures a DEX reports back are mapped to flipper errors through the adapter's table", async () => {
+    const [inputPoolVault, outputPoolVault] =
+      sourceMint.toString() < destinationMint.toString()
+        ? [raydiumTokenAVault, raydiumTokenBVault]
+        : [raydiumTokenBVault, raydiumTokenAVault];
+    const amountIn = new BN(1_000_000);
+    await mintTo(
+      provider.connection,
+      wallet.payer,
+      sourceMint,
+      userSourceTokenAccount,
+      wallet.publicKey,
+      amountIn.toNumber()
+    );
+
+    const setFailMode = (failCode: number) =>
+      mockRaydiumProgram.methods
+        .setFailMode(failCode)
+        .accounts({ user: wallet.publicKey, poolState: raydiumPoolState })
+        .signers([wallet.payer])
+        .rpc();
+    const routeThroughPool = () =>
+      program.methods
+        .route(
+          [{ swap: { raydium: {} }, percent: 100, inputIndex: 0, outputIndex: 13 }],
+          amountIn,
+          new BN(1),
+          100,
+          0,
+          new BN(0),
+          false,
+          null,
+          false,
+          null
+        )
+        .accounts({
+          adapterRegistry,
+          vaultAuthority,
+          inputTokenProgram: TOKEN_PROGRAM_ID,
+          outputTokenProgram: TOKEN_PROGRAM_ID,
+          userTransferAuthority: user.publicKey,
+          userSourceTokenAccount,
+          userDestinationTokenAccount,
+          sourceMint,
+          destinationMint,
+          platformFeeAccount: null,
+          systemProgram: SystemProgram.programId,
+        })
+        .remainingAccounts([
+          { pubkey: inputVault, isWritable: true, isSigner: false },
+          { pubkey: raydiumPoolInfo, isWritable: true, isSigner: false },
+          { pubkey: raydiumPoolAuthority, isWritable: false, isSigner: false },
+          { pubkey: raydiumAmmConfig, isWritable: false, isSigner: false },
+          { pubkey: raydiumPoolState, isWritable: true, isSigner: false },
+          { pubkey: inputPoolVault, isWritable: true, isSigner: false },
+          { pubkey: outputPoolVault, isWritable: true, isSigner: false },
+          { pubkey: TOKEN_PROGRAM_ID, isWritable: false, isSigner: false },
+          { pubkey: TOKEN_PROGRAM_ID, isWritable: false, isSigner: false },
+          { pubkey: sourceMint, isWritable: false, isSigner: false },
+          { pubkey: destinationMint, isWritable: false, isSigner: false },
+          { pubkey: raydiumObservationState, isWritable: true, isSigner: false },
+          { pubkey: mockRaydiumProgramId, isWritable: false, isSigner: false },
+          { pubkey: outputVault, isWritable: true, isSigner: false },
+        ])
+        .signers([user])
+        .rpc({ commitment: "confirmed" });
+
+    // Each mock_raydium code in RAYDIUM_ERRORS, token InsufficientFunds and an unknown code
+    const cases: [number, string][] = [
+      [6000, "StepAmountTooSmall"],
+      [6001, "SlippageToleranceExceeded"],
+      [6002, "InvalidCalculation"],
+      [6003, "InvalidTokenProgram"],
+      [1, "InsufficientFunds"],
+      [6999, "AdapterCpiFailed"],
+    ];
+    try {
+      for (const [failCode, expected] of cases) {
+        await setFailMode(failCode);
+        try {
+          await routeThroughPool();
+          assert.fail(`Reported code ${failCode} should fail the route`);
+        } catch (error) {
+          assert.include(error.toString(), expected);
+          assert.isTrue(
+            error.logs.some((log: string) => log.includes(`Raydium CPI failed: Custom(${failCode})`)),
+            "The raw code should be logged"
+          );
+        }
+      }
+    } finally {
+      await setFailMode(0);
+    }
+
+    // A successful swap logs no CPI failure
+    const signature = await routeThroughPool();
+    const tx = await provider.connection.getTransaction(signature, {
+      commitment: "confirmed",
+      maxSupportedTransactionVersion: 0,
+    });
+    assert.isFalse(tx.meta.logMessages.some((log) => log.includes("CPI failed")));
+  });
+
+  it("44. Routes checking a cached vault generation fail once it is stale", async () => {
+    const routePlan = [
+      { swap: { raydium: {} }, percent: 100, inputIndex: 0, outputIndex: 13 },
+    ];
+    const [inputPoolVault, outputPoolVault] =
+      sourceMint.toString() < destinationMint.toString()
+        ? [raydiumTokenAVault, raydiumTokenBVault]
+        : [raydiumTokenBVault, raydiumTokenAVault];
+    const [inputVaultMeta] = PublicKey.findProgramAddressSync(
+      [Buffer.from("vault_meta"), inputVault.toBuffer()],
+      program.programId
+    );
+    const route = (expectedGeneration: number | null, vaultMeta: PublicKey | null) =>
+      program.methods
+        .route(routePlan, new BN(1_000_000), new BN(1), 100, 0, new BN(0), false, null, false, expectedGeneration)
+        .accounts({
+          adapterRegistry,
+          vaultAuthority,
+          inputTokenProgram: TOKEN_PROGRAM_ID,
+          outputTokenProgram: TOKEN_PROGRAM_ID,
+          userTransferAuthority: user.publicKey,
+          userSourceTokenAccount,
+          userDestinationTokenAccount,
+          sourceMint,
+          destinationMint,
+          platformFeeAccount: null,
+          vaultMeta,
+          systemProgram: SystemProgram.programId,
+        })
+        .remainingAccounts([
+          { pubkey: inputVault, isWritable: true, isSigner: false },
+          { pubkey: raydiumPoolInfo, isWritable: true, isSigner: false },
+          { pubkey: raydiumPoolAuthority, isWritable: false, isSigner: false },
+          { pubkey: raydiumAmmConfig, isWritable: false, isSigner: false },
+          { pubkey: raydiumPoolState, isWritable: true, isSigner: false },
+          { pubkey: inputPoolVault, isWritable: true, isSigner: false },
+          { pubkey: outputPoolVault, isWritable: true, isSigner: false },
+          { pubkey: TOKEN_PROGRAM_ID, isWritable: false, isSigner: false },
+          { pubkey: TOKEN_PROGRAM_ID, isWritable: false, isSigner: false },
+          { pubkey: sourceMint, isWritable: false, isSigner: false },
+          { pubkey: destinationMint, isWritable: false, isSigner: false },
+          { pubkey: raydiumObservationState, isWritable: true, isSigner: false },
+          { pubkey: mockRaydiumProgramId, isWritable: false, isSigner: false },
+          { pubkey: outputVault, isWritable: true, isSigner: false },
+        ])
+        .signers([user])
+        .rpc();
+    await mintTo(
+      provider.connection,
+      wallet.payer,
+      sourceMint,
+      userSourceTokenAccount,
+      wallet.publicKey,
+      2_000_000
+    );
+
+    const generation = (await program.account.vaultMeta.fetch(inputVaultMeta)).vaultGeneration;
+    try {
+      await route(generation + 1, inputVaultMeta);
+      assert.fail("A generation other than the vault's should be rejected");
+    } catch (error) {
+      assert.include(error.toString(), "VaultGenerationMismatch");
+    }
+    try {
+      await route(generation, null);
+      assert.fail("An expected generation without vault_meta should be rejected");
+    } catch (error) {
+      assert.include(error.toString(), "VaultMetaNotProvided");
+    }
+
+    const initialDest = (await getAccount(provider.connection, userDestinationTokenAccount)).amount;
+    await route(generation, inputVaultMeta);
+    const finalDest = (await getAccount(provider.connection, userDestinationTokenAccount)).amount;
+    assert(finalDest > initialDest, "Destination balance should increase");
+  });
 });
@@ -21,6 +21,10 @@ import {
 import { assert } from "chai";
 import { Flipper } from "../target/types/flipper";
 
+const MEMO_PROGRAM_ID = new PublicKey(
+  "MemoSq4gqABAXKb96qnH8TysNcWxMyWCqXgDLGmfcHr"
+);
+
 describe("Flipper Swap Protocol - Whirlpools Swap and Limit Orders", () => {
   const provider = anchor.AnchorProvider.env();
   anchor.setProvider(provider);
@@ -48,6 +52,12 @@ describe("Flipper Swap Protocol - Whirlpools Swap and Limit Orders", () => {
       bytes[0] = 17;
       bytes[1] = swapType.whirlpool.aToB ? 1 : 0;
     }
+    if ("whirlpoolTwoHop" in swapType) {
+      bytes[0] = 127;
+      bytes[1] =
+        (swapType.whirlpoolTwoHop.aToBOne ? 1 : 0) |
+        (swapType.whirlpoolTwoHop.aToBTwo ? 2 : 0);
+    }
     return bytes;
   }
 
@@ -599,7 +609,10 @@ describe("Flipper Swap Protocol - Whirlpools Swap and Limit Orders", () => {
       },
     ];
 
-    const whirlpoolOracle = Keypair.generate().publicKey;
+    const [whirlpoolOracle] = PublicKey.findProgramAddressSync(
+      [Buffer.from("oracle"), whirlpoolPoolState.toBuffer()],
+      mockWhirlpoolProgramId
+    );
 
     // Для aToB=true: свапаем A->B
     // token_owner_account_a = inputVault (содержит tokenA)
@@ -646,7 +659,7 @@ describe("Flipper Swap Protocol - Whirlpools Swap and Limit Orders", () => {
       { pubkey: whirlpoolPoolInfo, isWritable: true, isSigner: false }, // 1: pool_info
       { pubkey: TOKEN_PROGRAM_ID, isWritable: false, isSigner: false }, // 2: token_program_a
       { pubkey: TOKEN_PROGRAM_ID, isWritable: false, isSigner: false }, // 3: token_program_b
-      { pubkey: PublicKey.default, isWritable: false, isSigner: false }, // 4: memo_program
+      { pubkey: MEMO_PROGRAM_ID, isWritable: false, isSigner: false }, // 4: memo_program
       { pubkey: whirlpoolPoolState, isWritable: true, isSigner: false }, // 5: whirlpool
       { pubkey: tokenAMint, isWritable: false, isSigner: false }, // 6: token_mint_a
       { pubkey: tokenBMint, isWritable: false, isSigner: false }, // 7: token_mint_b
@@ -676,7 +689,7 @@ describe("Flipper Swap Protocol - Whirlpools Swap and Limit Orders", () => {
     //console.log("Initial intermediate balance:", initialIntermediate.toString());
 
     await program.methods
-      .route(routePlan, inAmount, quotedOutAmount, slippageBps, platformFeeBps)
+      .route(routePlan, inAmount, quotedOutAmount, slippageBps, platformFeeBps, new BN(0), false, null, false, null)
       .accounts({
         adapterRegistry,
         vaultAuthority,
@@ -726,4 +739,650 @@ describe("Flipper Swap Protocol - Whirlpools Swap and Limit Orders", () => {
 
     //console.log("✓ Whirlpool swap with supplemental tick arrays completed successfully");
   });
+
+  it("2. Whirlpool swap follows the pool's mints when the client a_to_b flag is wrong", async () => {
+    // Same A->B pool as test 1, created there
+    const [tokenAMint, tokenBMint] =
+      sourceMint.toString() > intermediateMint.toString()
+        ? [intermediateMint, sourceMint]
+        : [sourceMint, intermediateMint];
+    const [aVault, bVault] =
+      sourceMint.toString() > intermediateMint.toString()
+        ? [intermediateVault, inputVault]
+        : [inputVault, intermediateVault];
+    const [userTokenA, userTokenB] =
+      sourceMint.toString() > intermediateMint.toString()
+        ? [userIntermediateTokenAccount, userSourceTokenAccount]
+        : [userSourceTokenAccount, userIntermediateTokenAccount];
+
+    const [whirlpoolPoolState] = PublicKey.findProgramAddressSync(
+      [Buffer.from("whirlpool"), tokenAMint.toBuffer(), tokenBMint.toBuffer()],
+      mockWhirlpoolProgramId
+    );
+    const [tickArray0, tickArray1, tickArray2] = [-100, 0, 100].map(
+      (startTick) =>
+        PublicKey.findProgramAddressSync(
+          [
+            Buffer.from("tick_array"),
+            whirlpoolPoolState.toBuffer(),
+            Buffer.from(new Int32Array([startTick]).buffer),
+          ],
+          mockWhirlpoolProgramId
+        )[0]
+    );
+    const [whirlpoolPoolInfo] = PublicKey.findProgramAddressSync(
+      [
+        Buffer.from("pool_info"),
+        getSwapTypeBytes({ whirlpool: { aToB: true } }),
+        whirlpoolPoolState.toBuffer(),
+      ],
+      program.programId
+    );
+    const [whirlpoolOracle] = PublicKey.findProgramAddressSync(
+      [Buffer.from("oracle"), whirlpoolPoolState.toBuffer()],
+      mockWhirlpoolProgramId
+    );
+
+    // Swapping A -> B, but the client claims B -> A and passes the mints as (B, A)
+    const inAmount = new BN(10_000_000);
+    const routePlan = [
+      {
+        swap: { whirlpool: { aToB: false } },
+        percent: 100,
+        inputIndex: 0,
+        outputIndex: 17,
+      },
+    ];
+    const remainingAccounts = [
+      { pubkey: aVault, isWritable: true, isSigner: false }, // 0: input vault (token A)
+      { pubkey: whirlpoolPoolInfo, isWritable: true, isSigner: false }, // 1: pool_info
+      { pubkey: TOKEN_PROGRAM_ID, isWritable: false, isSigner: false }, // 2: token_program_a
+      { pubkey: TOKEN_PROGRAM_ID, isWritable: false, isSigner: false }, // 3: token_program_b
+      { pubkey: MEMO_PROGRAM_ID, isWritable: false, isSigner: false }, // 4: memo_program
+      { pubkey: whirlpoolPoolState, isWritable: true, isSigner: false }, // 5: whirlpool
+      { pubkey: tokenBMint, isWritable: false, isSigner: false }, // 6: mint (reversed)
+      { pubkey: tokenAMint, isWritable: false, isSigner: false }, // 7: mint (reversed)
+      { pubkey: aVault, isWritable: true, isSigner: false }, // 8: token_owner_account_a
+      {
+        pubkey: getAssociatedTokenAddressSync(tokenAMint, whirlpoolPoolState, true, TOKEN_PROGRAM_ID),
+        isWritable: true,
+        isSigner: false,
+      }, // 9: token_vault_a
+      { pubkey: bVault, isWritable: true, isSigner: false }, // 10: token_owner_account_b
+      {
+        pubkey: getAssociatedTokenAddressSync(tokenBMint, whirlpoolPoolState, true, TOKEN_PROGRAM_ID),
+        isWritable: true,
+        isSigner: false,
+      }, // 11: token_vault_b
+      { pubkey: tickArray0, isWritable: true, isSigner: false }, // 12: tick_array_0
+      { pubkey: tickArray1, isWritable: true, isSigner: false }, // 13: tick_array_1
+      { pubkey: tickArray2, isWritable: true, isSigner: false }, // 14: tick_array_2
+      { pubkey: whirlpoolOracle, isWritable: true, isSigner: false }, // 15: oracle
+      { pubkey: mockWhirlpoolProgramId, isWritable: false, isSigner: false }, // 16: whirlpool program
+      { pubkey: bVault, isWritable: true, isSigner: false }, // 17: output vault (token B)
+    ];
+
+    const initialA = (await getAccount(provider.connection, userTokenA)).amount;
+    const initialB = (await getAccount(provider.connection, userTokenB)).amount;
+
+    await program.methods
+      .route(routePlan, inAmount, new BN(1), 100, 0, new BN(0), false, null, false, null)
+      .accounts({
+        adapterRegistry,
+        vaultAuthority,
+        inputTokenProgram: TOKEN_PROGRAM_ID,
+        outputTokenProgram: TOKEN_PROGRAM_ID,
+        userTransferAuthority: user.publicKey,
+        userSourceTokenAccount: userTokenA,
+        userDestinationTokenAccount: userTokenB,
+        sourceMint: tokenAMint,
+        destinationMint: tokenBMint,
+        platformFeeAccount: null,
+        systemProgram: SystemProgram.programId,
+      })
+      .remainingAccounts(remainingAccounts)
+      .signers([user])
+      .rpc();
+
+    const finalA = (await getAccount(provider.connection, userTokenA)).amount;
+    const finalB = (await getAccount(provider.connection, userTokenB)).amount;
+    assert.equal(
+      finalA.toString(),
+      (initialA - BigInt(inAmount.toString())).toString(),
+      "Token A should be spent"
+    );
+    assert(finalB > initialB, "Token B should be received despite the wrong flag");
+  });
+
+  it("3. Whirlpool route rejects token programs that don't own the mints and foreign oracles", async () => {
+    // Same A->B pool as test 1, created there
+    const [tokenAMint, tokenBMint] =
+      sourceMint.toString() > intermediateMint.toString()
+        ? [intermediateMint, sourceMint]
+        : [sourceMint, intermediateMint];
+    const [aVault, bVault] =
+      sourceMint.toString() > intermediateMint.toString()
+        ? [intermediateVault, inputVault]
+        : [inputVault, intermediateVault];
+    const [userTokenA, userTokenB] =
+      sourceMint.toString() > intermediateMint.toString()
+        ? [userIntermediateTokenAccount, userSourceTokenAccount]
+        : [userSourceTokenAccount, userIntermediateTokenAccount];
+
+    const [whirlpoolPoolState] = PublicKey.findProgramAddressSync(
+      [Buffer.from("whirlpool"), tokenAMint.toBuffer(), tokenBMint.toBuffer()],
+      mockWhirlpoolProgramId
+    );
+    const [tickArray0, tickArray1, tickArray2] = [-100, 0, 100].map(
+      (startTick) =>
+        PublicKey.findProgramAddressSync(
+          [
+            Buffer.from("tick_array"),
+            whirlpoolPoolState.toBuffer(),
+            Buffer.from(new Int32Array([startTick]).buffer),
+          ],
+          mockWhirlpoolProgramId
+        )[0]
+    );
+    const [whirlpoolPoolInfo] = PublicKey.findProgramAddressSync(
+      [
+        Buffer.from("pool_info"),
+        getSwapTypeBytes({ whirlpool: { aToB: true } }),
+        whirlpoolPoolState.toBuffer(),
+      ],
+      program.programId
+    );
+    const [whirlpoolOracle] = PublicKey.findProgramAddressSync(
+      [Buffer.from("oracle"), whirlpoolPoolState.toBuffer()],
+      mockWhirlpoolProgramId
+    );
+
+    const routePlan = [
+      {
+        swap: { whirlpool: { aToB: true } },
+        percent: 100,
+        inputIndex: 0,
+        outputIndex: 17,
+      },
+    ];
+    const remainingAccounts = (tokenProgramA: PublicKey, oracle: PublicKey) => [
+      { pubkey: aVault, isWritable: true, isSigner: false }, // 0: input vault (token A)
+      { pubkey: whirlpoolPoolInfo, isWritable: true, isSigner: false }, // 1: pool_info
+      { pubkey: tokenProgramA, isWritable: false, isSigner: false }, // 2: token_program_a
+      { pubkey: TOKEN_PROGRAM_ID, isWritable: false, isSigner: false }, // 3: token_program_b
+      { pubkey: MEMO_PROGRAM_ID, isWritable: false, isSigner: false }, // 4: memo_program
+      { pubkey: whirlpoolPoolState, isWritable: true, isSigner: false }, // 5: whirlpool
+      { pubkey: tokenAMint, isWritable: false, isSigner: false }, // 6: token_mint_a
+      { pubkey: tokenBMint, isWritable: false, isSigner: false }, // 7: token_mint_b
+      { pubkey: aVault, isWritable: true, isSigner: false }, // 8: token_owner_account_a
+      {
+        pubkey: getAssociatedTokenAddressSync(tokenAMint, whirlpoolPoolState, true, TOKEN_PROGRAM_ID),
+        isWritable: true,
+        isSigner: false,
+      }, // 9: token_vault_a
+      { pubkey: bVault, isWritable: true, isSigner: false }, // 10: token_owner_account_b
+      {
+        pubkey: getAssociatedTokenAddressSync(tokenBMint, whirlpoolPoolState, true, TOKEN_PROGRAM_ID),
+        isWritable: true,
+        isSigner: false,
+      }, // 11: token_vault_b
+      { pubkey: tickArray0, isWritable: true, isSigner: false }, // 12: tick_array_0
+      { pubkey: tickArray1, isWritable: true, isSigner: false }, // 13: tick_array_1
+      { pubkey: tickArray2, isWritable: true, isSigner: false }, // 14: tick_array_2
+      { pubkey: oracle, isWritable: true, isSigner: false }, // 15: oracle
+      { pubkey: mockWhirlpoolProgramId, isWritable: false, isSigner: false }, // 16: whirlpool program
+      { pubkey: bVault, isWritable: true, isSigner: false }, // 17: output vault (token B)
+    ];
+    const route = (tokenProgramA: PublicKey, oracle: PublicKey) =>
+      program.methods
+        .route(routePlan, new BN(10_000_000), new BN(1), 100, 0, new BN(0), false, null, false, null)
+        .accounts({
+          adapterRegistry,
+          vaultAuthority,
+          inputTokenProgram: TOKEN_PROGRAM_ID,
+          outputTokenProgram: TOKEN_PROGRAM_ID,
+          userTransferAuthority: user.publicKey,
+          userSourceTokenAccount: userTokenA,
+          userDestinationTokenAccount: userTokenB,
+          sourceMint: tokenAMint,
+          destinationMint: tokenBMint,
+          platformFeeAccount: null,
+          systemProgram: SystemProgram.programId,
+        })
+        .remainingAccounts(remainingAccounts(tokenProgramA, oracle))
+        .signers([user])
+        .rpc();
+
+    // Token-2022 program for the legacy mint A
+    try {
+      await route(TOKEN_2022_PROGRAM_ID, whirlpoolOracle);
+      assert.fail("Should have failed with InvalidTokenProgram");
+    } catch (error) {
+      assert.include(error.toString(), "InvalidTokenProgram");
+    }
+
+    // Oracle that is not the pool's ["oracle", whirlpool] PDA
+    try {
+      await route(TOKEN_PROGRAM_ID, Keypair.generate().publicKey);
+      assert.fail("Should have failed with InvalidOracleAccount");
+    } catch (error) {
+      assert.include(error.toString(), "InvalidOracleAccount");
+    }
+  });
+
+  it("4. Whirlpool two-hop step swaps through two pools without an intermediate vault", async () => {
+    // Pool one is test 1's A/B pool; pool two pairs its token B with a new output mint
+    const [tokenAMint, tokenBMint] =
+      sourceMint.toString() > intermediateMint.toString()
+        ? [intermediateMint, sourceMint]
+        : [sourceMint, intermediateMint];
+    const [aVault, bVault] =
+      sourceMint.toString() > intermediateMint.toString()
+        ? [intermediateVault, inputVault]
+        : [inputVault, intermediateVault];
+    const userTokenA =
+      sourceMint.toString() > intermediateMint.toString()
+        ? userIntermediateTokenAccount
+        : userSourceTokenAccount;
+
+    const outputMint = await createMint(
+      provider.connection,
+      wallet.payer,
+      wallet.publicKey,
+      null,
+      9,
+      undefined,
+      undefined,
+      TOKEN_PROGRAM_ID
+    );
+    const [outputVault] = PublicKey.findProgramAddressSync(
+      [Buffer.from("vault"), outputMint.toBuffer()],
+      program.programId
+    );
+    await program.methods
+      .createVault()
+      .accounts({
+        vaultAuthority,
+        payer: wallet.publicKey,
+        admin: wallet.publicKey,
+        vault: outputVault,
+        vaultMint: outputMint,
+        vaultTokenProgram: TOKEN_PROGRAM_ID,
+        systemProgram: SystemProgram.programId,
+      })
+      .signers([wallet.payer])
+      .rpc();
+    const userOutputTokenAccount = await createAssociatedTokenAccount(
+      provider.connection,
+      user,
+      outputMint,
+      user.publicKey
+    );
+
+    const tickArrays = (whirlpool: PublicKey) =>
+      [-100, 0, 100].map(
+        (startTick) =>
+          PublicKey.findProgramAddressSync(
+            [
+              Buffer.from("tick_array"),
+              whirlpool.toBuffer(),
+              Buffer.from(new Int32Array([startTick]).buffer),
+            ],
+            mockWhirlpoolProgramId
+          )[0]
+      );
+    const oracle = (whirlpool: PublicKey) =>
+      PublicKey.findProgramAddressSync(
+        [Buffer.from("oracle"), whirlpool.toBuffer()],
+        mockWhirlpoolProgramId
+      )[0];
+
+    const [whirlpoolOne] = PublicKey.findProgramAddressSync(
+      [Buffer.from("whirlpool"), tokenAMint.toBuffer(), tokenBMint.toBuffer()],
+      mockWhirlpoolProgramId
+    );
+    const [whirlpoolTwo] = PublicKey.findProgramAddressSync(
+      [Buffer.from("whirlpool"), tokenBMint.toBuffer(), outputMint.toBuffer()],
+      mockWhirlpoolProgramId
+    );
+
+    // Seed pool two from the wallet
+    const walletTokenB = getAssociatedTokenAddressSync(tokenBMint, wallet.publicKey, false, TOKEN_PROGRAM_ID);
+    await mintTo(provider.connection, wallet.payer, tokenBMint, walletTokenB, wallet.publicKey, 1_000_000_000);
+    const walletOutput = await createAssociatedTokenAccount(
+      provider.connection,
+      wallet.payer,
+      outputMint,
+      wallet.publicKey
+    );
+    await mintTo(provider.connection, wallet.payer, outputMint, walletOutput, wallet.publicKey, 1_000_000_000);
+    const [twoTickArray0, twoTickArray1, twoTickArray2] = tickArrays(whirlpoolTwo);
+    await mockWhirlpoolProgram.methods
+      .initializePool(new BN(1_000_000_000), new BN(1_000_000_000))
+      .accounts({
+        user: wallet.publicKey,
+        whirlpool: whirlpoolTwo,
+        tickArray0: twoTickArray0,
+        tickArray1: twoTickArray1,
+        tickArray2: twoTickArray2,
+        userTokenA: walletTokenB,
+        userTokenB: walletOutput,
+        tokenVaultA: getAssociatedTokenAddressSync(tokenBMint, whirlpoolTwo, true, TOKEN_PROGRAM_ID),
+        tokenVaultB: getAssociatedTokenAddressSync(outputMint, whirlpoolTwo, true, TOKEN_PROGRAM_ID),
+        tokenMintA: tokenBMint,
+        tokenMintB: outputMint,
+        tokenProgramA: TOKEN_PROGRAM_ID,
+        tokenProgramB: TOKEN_PROGRAM_ID,
+        associatedTokenProgram: ASSOCIATED_TOKEN_PROGRAM_ID,
+        systemProgram: SystemProgram.programId,
+      })
+      .signers([wallet.payer])
+      .rpc();
+
+    // Both pools are registered under the two-hop swap type
+    const swapType = { whirlpoolTwoHop: { aToBOne: true, aToBTwo: true } };
+    await program.methods
+      .configureAdapter({
+        name: "whirlpool_two_hop",
+        programId: mockWhirlpoolProgramId,
+        swapType,
+      })
+      .accounts({
+        adapterRegistry,
+        operator: wallet.publicKey,
+      })
+      .signers([wallet.payer])
+      .rpc();
+    const [poolInfoOne, poolInfoTwo] = [whirlpoolOne, whirlpoolTwo].map(
+      (whirlpool) =>
+        PublicKey.findProgramAddressSync(
+          [Buffer.from("pool_info"), getSwapTypeBytes(swapType), whirlpool.toBuffer()],
+          program.programId
+        )[0]
+    );
+    for (const [poolInfo, whirlpool] of [
+      [poolInfoOne, whirlpoolOne],
+      [poolInfoTwo, whirlpoolTwo],
+    ]) {
+      await program.methods
+        .initializePoolInfo(swapType, whirlpool)
+        .accounts({
+          poolInfo,
+          adapterRegistry,
+          payer: wallet.publicKey,
+          operator: wallet.publicKey,
+          systemProgram: SystemProgram.programId,
+        })
+        .signers([wallet.payer])
+        .rpc();
+    }
+
+    const routePlan = [
+      {
+        swap: swapType,
+        percent: 100,
+        inputIndex: 0,
+        outputIndex: 25,
+      },
+    ];
+    const remainingAccounts = [
+      { pubkey: aVault, isWritable: true, isSigner: false }, // 0: input vault (token A)
+      { pubkey: poolInfoOne, isWritable: true, isSigner: false }, // 1: pool_info_one
+      { pubkey: poolInfoTwo, isWritable: true, isSigner: false }, // 2: pool_info_two
+      { pubkey: TOKEN_PROGRAM_ID, isWritable: false, isSigner: false }, // 3: token_program_input
+      { pubkey: TOKEN_PROGRAM_ID, isWritable: false, isSigner: false }, // 4: token_program_intermediate
+      { pubkey: TOKEN_PROGRAM_ID, isWritable: false, isSigner: false }, // 5: token_program_output
+      { pubkey: MEMO_PROGRAM_ID, isWritable: false, isSigner: false }, // 6: memo_program
+      { pubkey: whirlpoolOne, isWritable: true, isSigner: false }, // 7: whirlpool_one
+      { pubkey: whirlpoolTwo, isWritable: true, isSigner: false }, // 8: whirlpool_two
+      { pubkey: tokenAMint, isWritable: false, isSigner: false }, // 9: token_mint_input
+      { pubkey: tokenBMint, isWritable: false, isSigner: false }, // 10: token_mint_intermediate
+      { pubkey: outputMint, isWritable: false, isSigner: false }, // 11: token_mint_output
+      {
+        pubkey: getAssociatedTokenAddressSync(tokenAMint, whirlpoolOne, true, TOKEN_PROGRAM_ID),
+        isWritable: true,
+        isSigner: false,
+      }, // 12: token_vault_one_input
+      {
+        pubkey: getAssociatedTokenAddressSync(tokenBMint, whirlpoolOne, true, TOKEN_PROGRAM_ID),
+        isWritable: true,
+        isSigner: false,
+      }, // 13: token_vault_one_intermediate
+      {
+        pubkey: getAssociatedTokenAddressSync(tokenBMint, whirlpoolTwo, true, TOKEN_PROGRAM_ID),
+        isWritable: true,
+        isSigner: false,
+      }, // 14: token_vault_two_intermediate
+      {
+        pubkey: getAssociatedTokenAddressSync(outputMint, whirlpoolTwo, true, TOKEN_PROGRAM_ID),
+        isWritable: true,
+        isSigner: false,
+      }, // 15: token_vault_two_output
+      ...[...tickArrays(whirlpoolOne), ...tickArrays(whirlpoolTwo)].map((pubkey) => ({
+        pubkey,
+        isWritable: true,
+        isSigner: false,
+      })), // 16-21: tick arrays of pool one, then pool two
+      { pubkey: oracle(whirlpoolOne), isWritable: true, isSigner: false }, // 22: oracle_one
+      { pubkey: oracle(whirlpoolTwo), isWritable: true, isSigner: false }, // 23: oracle_two
+      { pubkey: mockWhirlpoolProgramId, isWritable: false, isSigner: false }, // 24: whirlpool program
+      { pubkey: outputVault, isWritable: true, isSigner: false }, // 25: output vault
+    ];
+
+    const inAmount = new BN(10_000_000);
+    const initialA = (await getAccount(provider.connection, userTokenA)).amount;
+    const initialIntermediateVault = (await getAccount(provider.connection, bVault)).amount;
+
+    await program.methods
+      .route(routePlan, inAmount, new BN(1), 100, 0, new BN(0), false, null, false, null)
+      .accounts({
+        adapterRegistry,
+        vaultAuthority,
+        inputTokenProgram: TOKEN_PROGRAM_ID,
+        outputTokenProgram: TOKEN_PROGRAM_ID,
+        userTransferAuthority: user.publicKey,
+        userSourceTokenAccount: userTokenA,
+        userDestinationTokenAccount: userOutputTokenAccount,
+        sourceMint: tokenAMint,
+        destinationMint: outputMint,
+        platformFeeAccount: null,
+        systemProgram: SystemProgram.programId,
+      })
+      .remainingAccounts(remainingAccounts)
+      .signers([user])
+      .rpc();
+
+    const finalA = (await getAccount(provider.connection, userTokenA)).amount;
+    const finalOutput = (await getAccount(provider.connection, userOutputTokenAccount)).amount;
+    assert.equal(
+      finalA.toString(),
+      (initialA - BigInt(inAmount.toString())).toString(),
+      "Token A should be spent"
+    );
+    assert(finalOutput > 0n, "Output token should be received through both pools");
+    assert.equal(
+      (await getAccount(provider.connection, bVault)).amount.toString(),
+      initialIntermediateVault.toString(),
+      "The router's intermediate token vault should not be used"
+    );
+
+    // A failure reported by pool two comes back through the two-hop adapter's CPI
+    const setFailMode = (whirlpool: PublicKey, failCode: number) =>
+      mockWhirlpoolProgram.methods
+        .setFailMode(failCode)
+        .accounts({ user: wallet.publicKey, whirlpool })
+        .signers([wallet.payer])
+        .rpc();
+    await mintTo(provider.connection, wallet.payer, tokenAMint, userTokenA, wallet.publicKey, inAmount.toNumber());
+    await setFailMode(whirlpoolTwo, 6001);
+    try {
+      await program.methods
+        .route(routePlan, inAmount, new BN(1), 100, 0, new BN(0), false, null, false, null)
+        .accounts({
+          adapterRegistry,
+          vaultAuthority,
+          inputTokenProgram: TOKEN_PROGRAM_ID,
+          outputTokenProgram: TOKEN_PROGRAM_ID,
+          userTransferAuthority: user.publicKey,
+          userSourceTokenAccount: userTokenA,
+          userDestinationTokenAccount: userOutputTokenAccount,
+          sourceMint: tokenAMint,
+          destinationMint: outputMint,
+          platformFeeAccount: null,
+          systemProgram: SystemProgram.programId,
+        })
+        .remainingAccounts(remainingAccounts)
+        .signers([user])
+        .rpc();
+      assert.fail("A failure reported by pool two should fail the route");
+    } catch (error) {
+      assert.include(error.toString(), "SlippageToleranceExceeded");
+      assert.isTrue(
+        error.logs.some((log: string) => log.includes("Whirlpool two-hop CPI failed: Custom(6001)")),
+        "The raw code should be logged"
+      );
+    } finally {
+      await setFailMode(whirlpoolTwo, 0);
+    }
+  });
+
+  it("5. Failures a Whirlpool reports back are mapped to flipper errors through the adapter's table", async () => {
+    // Same A->B pool as test 1, created there
+    const [tokenAMint, tokenBMint] =
+      sourceMint.toString() > intermediateMint.toString()
+        ? [intermediateMint, sourceMint]
+        : [sourceMint, intermediateMint];
+    const [aVault, bVault] =
+      sourceMint.toString() > intermediateMint.toString()
+        ? [intermediateVault, inputVault]
+        : [inputVault, intermediateVault];
+    const [userTokenA, userTokenB] =
+      sourceMint.toString() > intermediateMint.toString()
+        ? [userIntermediateTokenAccount, userSourceTokenAccount]
+        : [userSourceTokenAccount, userIntermediateTokenAccount];
+
+    const [whirlpoolPoolState] = PublicKey.findProgramAddressSync(
+      [Buffer.from("whirlpool"), tokenAMint.toBuffer(), tokenBMint.toBuffer()],
+      mockWhirlpoolProgramId
+    );
+    const [tickArray0, tickArray1, tickArray2] = [-100, 0, 100].map(
+      (startTick) =>
+        PublicKey.findProgramAddressSync(
+          [
+            Buffer.from("tick_array"),
+            whirlpoolPoolState.toBuffer(),
+            Buffer.from(new Int32Array([startTick]).buffer),
+          ],
+          mockWhirlpoolProgramId
+        )[0]
+    );
+    const [whirlpoolPoolInfo] = PublicKey.findProgramAddressSync(
+      [
+        Buffer.from("pool_info"),
+        getSwapTypeBytes({ whirlpool: { aToB: true } }),
+        whirlpoolPoolState.toBuffer(),
+      ],
+      program.programId
+    );
+    const [whirlpoolOracle] = PublicKey.findProgramAddressSync(
+      [Buffer.from("oracle"), whirlpoolPoolState.toBuffer()],
+      mockWhirlpoolProgramId
+    );
+
+    const inAmount = new BN(1_000_000);
+    await mintTo(provider.connection, wallet.payer, tokenAMint, userTokenA, wallet.publicKey, inAmount.toNumber());
+    const routePlan = [
+      {
+        swap: { whirlpool: { aToB: true } },
+        percent: 100,
+        inputIndex: 0,
+        outputIndex: 17,
+      },
+    ];
+    const remainingAccounts = [
+      { pubkey: aVault, isWritable: true, isSigner: false }, // 0: input vault (token A)
+      { pubkey: whirlpoolPoolInfo, isWritable: true, isSigner: false }, // 1: pool_info
+      { pubkey: TOKEN_PROGRAM_ID, isWritable: false, isSigner: false }, // 2: token_program_a
+      { pubkey: TOKEN_PROGRAM_ID, isWritable: false, isSigner: false }, // 3: token_program_b
+      { pubkey: MEMO_PROGRAM_ID, isWritable: false, isSigner: false }, // 4: memo_program
+      { pubkey: whirlpoolPoolState, isWritable: true, isSigner: false }, // 5: whirlpool
+      { pubkey: tokenAMint, isWritable: false, isSigner: false }, // 6: token_mint_a
+      { pubkey: tokenBMint, isWritable: false, isSigner: false }, // 7: token_mint_b
+      { pubkey: aVault, isWritable: true, isSigner: false }, // 8: token_owner_account_a
+      {
+        pubkey: getAssociatedTokenAddressSync(tokenAMint, whirlpoolPoolState, true, TOKEN_PROGRAM_ID),
+        isWritable: true,
+        isSigner: false,
+      }, // 9: token_vault_a
+      { pubkey: bVault, isWritable: true, isSigner: false }, // 10: token_owner_account_b
+      {
+        pubkey: getAssociatedTokenAddressSync(tokenBMint, whirlpoolPoolState, true, TOKEN_PROGRAM_ID),
+        isWritable: true,
+        isSigner: false,
+      }, // 11: token_vault_b
+      { pubkey: tickArray0, isWritable: true, isSigner: false }, // 12: tick_array_0
+      { pubkey: tickArray1, isWritable: true, isSigner: false }, // 13: tick_array_1
+      { pubkey: tickArray2, isWritable: true, isSigner: false }, // 14: tick_array_2
+      { pubkey: whirlpoolOracle, isWritable: true, isSigner: false }, // 15: oracle
+      { pubkey: mockWhirlpoolProgramId, isWritable: false, isSigner: false }, // 16: whirlpool program
+      { pubkey: bVault, isWritable: true, isSigner: false }, // 17: output vault (token B)
+    ];
+    const routeThroughPool = () =>
+      program.methods
+        .route(routePlan, inAmount, new BN(1), 100, 0, new BN(0), false, null, false, null)
+        .accounts({
+          adapterRegistry,
+          vaultAuthority,
+          inputTokenProgram: TOKEN_PROGRAM_ID,
+          outputTokenProgram: TOKEN_PROGRAM_ID,
+          userTransferAuthority: user.publicKey,
+          userSourceTokenAccount: userTokenA,
+          userDestinationTokenAccount: userTokenB,
+          sourceMint: tokenAMint,
+          destinationMint: tokenBMint,
+          platformFeeAccount: null,
+          systemProgram: SystemProgram.programId,
+        })
+        .remainingAccounts(remainingAccounts)
+        .signers([user])
+        .rpc();
+    const setFailMode = (failCode: number) =>
+      mockWhirlpoolProgram.methods
+        .setFailMode(failCode)
+        .accounts({ user: wallet.publicKey, whirlpool: whirlpoolPoolState })
+        .signers([wallet.payer])
+        .rpc();
+
+    // Each mock_whirlpools code in WHIRLPOOL_ERRORS, token InsufficientFunds and an unknown code
+    const cases: [number, string][] = [
+      [6000, "StepAmountTooSmall"],
+      [6001, "SlippageToleranceExceeded"],
+      [6002, "SlippageToleranceExceeded"],
+      [6003, "InvalidCalculation"],
+      [6004, "InvalidTokenProgram"],
+      [1, "InsufficientFunds"],
+      [6999, "AdapterCpiFailed"],
+    ];
+    try {
+      for (const [failCode, expected] of cases) {
+        await setFailMode(failCode);
+        try {
+          await routeThroughPool();
+          assert.fail(`Reported code ${failCode} should fail the route`);
+        } catch (error) {
+          assert.include(error.toString(), expected);
+          assert.isTrue(
+            error.logs.some((log: string) => log.includes(`Whirlpool CPI failed: Custom(${failCode})`)),
+            "The raw code should be logged"
+          );
+        }
+      }
+    } finally {
+      await setFailMode(0);
+    }
+
+    const initialB = (await getAccount(provider.connection, userTokenB)).amount;
+    await routeThroughPool();
+    assert((await getAccount(provider.connection, userTokenB)).amount > initialB, "The pool swaps again once reset");
+  });
 });
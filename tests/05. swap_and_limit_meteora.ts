@@ -10,10 +10,16 @@ import {
   getAccount,
   getOrCreateAssociatedTokenAccount,
   createAssociatedTokenAccount,
+  createAccount,
 } from "@solana/spl-token";
 import { assert } from "chai";
+import { createHash } from "crypto";
 import { Flipper } from "../target/types/flipper";
 
+const MEMO_PROGRAM_ID = new PublicKey(
+  "MemoSq4gqABAXKb96qnH8TysNcWxMyWCqXgDLGmfcHr"
+);
+
 describe("Meteora Adapter - End to End Tests for Swaps", () => {
   const provider = anchor.AnchorProvider.env();
   anchor.setProvider(provider);
@@ -51,6 +57,52 @@ describe("Meteora Adapter - End to End Tests for Swaps", () => {
     return bytes;
   }
 
+  // Remaining accounts for a single-step Meteora route from inputVault to outputVault
+  function meteoraRemainingAccounts(hostFeeIn: anchor.web3.AccountMeta) {
+    return [
+      { pubkey: inputVault, isWritable: true, isSigner: false }, // 0: inputVault (for adapter)
+      { pubkey: meteoraPoolInfo, isWritable: true, isSigner: false }, // 1: pool_info
+      { pubkey: meteoraPoolState, isWritable: true, isSigner: false }, // 2: lb_pair
+      { pubkey: meteoraBitmapExtension, isWritable: true, isSigner: false }, // 3: bin_array_bitmap_extension
+      { pubkey: meteoraReserveX, isWritable: true, isSigner: false }, // 4: reserve_x
+      { pubkey: meteoraReserveY, isWritable: true, isSigner: false }, // 5: reserve_y
+      { pubkey: inputVault, isWritable: true, isSigner: false }, // 6: user_token_in
+      { pubkey: outputVault, isWritable: true, isSigner: false }, // 7: user_token_out
+      { pubkey: tokenXMint, isWritable: false, isSigner: false }, // 8: token_x_mint
+      { pubkey: tokenYMint, isWritable: false, isSigner: false }, // 9: token_y_mint
+      { pubkey: meteoraOracle, isWritable: true, isSigner: false }, // 10: oracle
+      hostFeeIn, // 11: host_fee_in
+      { pubkey: TOKEN_PROGRAM_ID, isWritable: false, isSigner: false }, // 12: token_x_program
+      { pubkey: TOKEN_PROGRAM_ID, isWritable: false, isSigner: false }, // 13: token_y_program
+      { pubkey: MEMO_PROGRAM_ID, isWritable: false, isSigner: false }, // 14: memo_program
+      { pubkey: meteoraEventAuthority, isWritable: false, isSigner: false }, // 15: event_authority
+      { pubkey: mockMeteoraProgramId, isWritable: false, isSigner: false }, // 16: program (CPI event, readonly)
+      { pubkey: meteoraBinArray0, isWritable: true, isSigner: false }, // 17: bin_array_0
+      { pubkey: meteoraBinArray1, isWritable: true, isSigner: false }, // 18: bin_array_1
+      { pubkey: meteoraBinArray2, isWritable: true, isSigner: false }, // 19: bin_array_2
+      { pubkey: mockMeteoraProgramId, isWritable: false, isSigner: false }, // 20: program ID (readonly, для CPI)
+      { pubkey: outputVault, isWritable: true, isSigner: false }, // 21: output vault
+    ];
+  }
+
+  // Route accounts execute_staged_route reads from the head of its remaining accounts
+  function stagedRouteAccounts(): anchor.web3.AccountMeta[] {
+    const pda = (seed: string) =>
+      PublicKey.findProgramAddressSync([Buffer.from(seed)], program.programId)[0];
+    return [
+      { pubkey: adapterRegistry, isWritable: false, isSigner: false }, // 0: adapter_registry
+      { pubkey: vaultAuthority, isWritable: false, isSigner: false }, // 1: vault_authority
+      { pubkey: userTokenXAccount, isWritable: true, isSigner: false }, // 2: user_source_token_account
+      { pubkey: userTokenYAccount, isWritable: true, isSigner: false }, // 3: user_destination_token_account
+      { pubkey: tokenXMint, isWritable: false, isSigner: false }, // 4: source_mint
+      { pubkey: tokenYMint, isWritable: false, isSigner: false }, // 5: destination_mint
+      { pubkey: platformFeeAccount, isWritable: true, isSigner: false }, // 6: platform_fee_account
+      { pubkey: pda("fee_vault_registry"), isWritable: false, isSigner: false }, // 7: fee_vault_registry
+      { pubkey: pda("protocol_config"), isWritable: false, isSigner: false }, // 8: protocol_config
+      { pubkey: program.programId, isWritable: false, isSigner: false }, // 9: trader_volume (none)
+    ];
+  }
+
   before(async () => {
     wallet = provider.wallet as anchor.Wallet;
     user = wallet.publicKey; // Use wallet.publicKey for consistency
@@ -340,38 +392,12 @@ describe("Meteora Adapter - End to End Tests for Swaps", () => {
     ];
 
     // Prepare remaining accounts for Meteora swap, including inputVault and outputVault
-    const remainingAccounts = [
-      { pubkey: inputVault, isWritable: true, isSigner: false }, // 0: inputVault (for adapter)
-      { pubkey: meteoraPoolInfo, isWritable: true, isSigner: false }, // 1: pool_info
-      { pubkey: meteoraPoolState, isWritable: true, isSigner: false }, // 2: lb_pair
-      { pubkey: meteoraBitmapExtension, isWritable: true, isSigner: false }, // 3: bin_array_bitmap_extension
-      { pubkey: meteoraReserveX, isWritable: true, isSigner: false }, // 4: reserve_x
-      { pubkey: meteoraReserveY, isWritable: true, isSigner: false }, // 5: reserve_y
-      { pubkey: inputVault, isWritable: true, isSigner: false }, // 6: user_token_in
-      { pubkey: outputVault, isWritable: true, isSigner: false }, // 7: user_token_out
-      { pubkey: tokenXMint, isWritable: false, isSigner: false }, // 8: token_x_mint
-      { pubkey: tokenYMint, isWritable: false, isSigner: false }, // 9: token_y_mint
-      { pubkey: meteoraOracle, isWritable: true, isSigner: false }, // 10: oracle
-      {
-        pubkey: Keypair.generate().publicKey,
-        isWritable: true,
-        isSigner: false,
-      }, // 11: host_fee_in
-      { pubkey: TOKEN_PROGRAM_ID, isWritable: false, isSigner: false }, // 12: token_x_program
-      { pubkey: TOKEN_PROGRAM_ID, isWritable: false, isSigner: false }, // 13: token_y_program
-      {
-        pubkey: Keypair.generate().publicKey,
-        isWritable: false,
-        isSigner: false,
-      }, // 14: memo_program
-      { pubkey: meteoraEventAuthority, isWritable: false, isSigner: false }, // 15: event_authority
-      { pubkey: mockMeteoraProgramId, isWritable: false, isSigner: false }, // 16: program (CPI event, readonly)
-      { pubkey: meteoraBinArray0, isWritable: true, isSigner: false }, // 17: bin_array_0
-      { pubkey: meteoraBinArray1, isWritable: true, isSigner: false }, // 18: bin_array_1
-      { pubkey: meteoraBinArray2, isWritable: true, isSigner: false }, // 19: bin_array_2
-      { pubkey: mockMeteoraProgramId, isWritable: false, isSigner: false }, // 20: program ID (readonly, для CPI)
-      { pubkey: outputVault, isWritable: true, isSigner: false }, // 21: output vault
-    ];
+    // Passing the Meteora program id as host_fee_in means no host fee
+    const remainingAccounts = meteoraRemainingAccounts({
+      pubkey: mockMeteoraProgramId,
+      isWritable: false,
+      isSigner: false,
+    });
 
     const initialSourceBalance = (
       await getAccount(provider.connection, userTokenXAccount)
@@ -381,7 +407,7 @@ describe("Meteora Adapter - End to End Tests for Swaps", () => {
     ).amount;
 
     await program.methods
-      .route(routePlan, inAmount, quotedOutAmount, slippageBps, platformFeeBps)
+      .route(routePlan, inAmount, quotedOutAmount, slippageBps, platformFeeBps, new BN(0), false, null, false, null)
       .accounts({
         adapterRegistry,
         vaultAuthority,
@@ -429,4 +455,358 @@ describe("Meteora Adapter - End to End Tests for Swaps", () => {
       "Output amount should meet minimum after slippage"
     );
   });
+
+  it("should pay the Meteora host fee to a vault authority account", async () => {
+    const inAmount = new BN(100_000);
+    // Mock Meteora HOST_FEE_BPS = 10
+    const expectedHostFee = inAmount.muln(10).divn(10_000);
+
+    // Input mint account owned by the vault authority, separate from the input vault
+    const hostFeeAccount = await createAccount(
+      provider.connection,
+      wallet.payer,
+      tokenXMint,
+      vaultAuthority,
+      Keypair.generate()
+    );
+
+    const routePlan = [
+      {
+        swap: { meteora: {} },
+        inputIndex: 0,
+        outputIndex: 21,
+        percent: 100,
+      },
+    ];
+
+    await program.methods
+      .route(routePlan, inAmount, new BN(1), 100, 0, new BN(0), false, null, false, null)
+      .accounts({
+        adapterRegistry,
+        vaultAuthority,
+        inputTokenProgram: TOKEN_PROGRAM_ID,
+        outputTokenProgram: TOKEN_PROGRAM_ID,
+        userTransferAuthority: wallet.publicKey,
+        userSourceTokenAccount: userTokenXAccount,
+        userDestinationTokenAccount: userTokenYAccount,
+        sourceMint: tokenXMint,
+        destinationMint: tokenYMint,
+        platformFeeAccount,
+        systemProgram: SystemProgram.programId,
+      })
+      .remainingAccounts(
+        meteoraRemainingAccounts({
+          pubkey: hostFeeAccount,
+          isWritable: true,
+          isSigner: false,
+        })
+      )
+      .signers([wallet.payer])
+      .rpc();
+
+    const hostFeeBalance = (await getAccount(provider.connection, hostFeeAccount)).amount;
+    assert.equal(hostFeeBalance.toString(), expectedHostFee.toString());
+  });
+
+  it("should reject a Meteora host fee account not owned by the vault authority", async () => {
+    const hostFeeAccount = await createAccount(
+      provider.connection,
+      wallet.payer,
+      tokenXMint,
+      wallet.publicKey,
+      Keypair.generate()
+    );
+
+    try {
+      await program.methods
+        .route(
+          [{ swap: { meteora: {} }, inputIndex: 0, outputIndex: 21, percent: 100 }],
+          new BN(100_000),
+          new BN(1),
+          100,
+          0,
+          new BN(0),
+          false,
+          null,
+          false,
+          null
+        )
+        .accounts({
+          adapterRegistry,
+          vaultAuthority,
+          inputTokenProgram: TOKEN_PROGRAM_ID,
+          outputTokenProgram: TOKEN_PROGRAM_ID,
+          userTransferAuthority: wallet.publicKey,
+          userSourceTokenAccount: userTokenXAccount,
+          userDestinationTokenAccount: userTokenYAccount,
+          sourceMint: tokenXMint,
+          destinationMint: tokenYMint,
+          platformFeeAccount,
+          systemProgram: SystemProgram.programId,
+        })
+        .remainingAccounts(
+          meteoraRemainingAccounts({
+            pubkey: hostFeeAccount,
+            isWritable: true,
+            isSigner: false,
+          })
+        )
+        .signers([wallet.payer])
+        .rpc();
+      assert.fail("Host fee account owned by another wallet should be rejected");
+    } catch (error) {
+      assert.include(error.toString(), "InvalidVaultOwner");
+    }
+  });
+
+  it("should execute a staged Meteora route once and close its session", async () => {
+    const inAmount = new BN(1000);
+    const routePlan = [
+      { swap: { meteora: {} }, inputIndex: 0, outputIndex: 21, percent: 100 },
+    ];
+    // The route's vaults, mints and PDAs lead the remaining accounts, ahead of the swap accounts
+    const remainingAccounts = [
+      ...stagedRouteAccounts(),
+      ...meteoraRemainingAccounts({
+        pubkey: mockMeteoraProgramId,
+        isWritable: false,
+        isSigner: false,
+      }),
+    ];
+    // Commitment to the remaining account keys, in order
+    const accountsHash = Array.from(
+      createHash("sha256")
+        .update(Buffer.concat(remainingAccounts.map((meta) => meta.pubkey.toBuffer())))
+        .digest()
+    );
+    const [routeSession] = PublicKey.findProgramAddressSync(
+      [Buffer.from("route_session"), wallet.publicKey.toBuffer()],
+      program.programId
+    );
+    const stagedAccounts = {
+      routeSession,
+      userTransferAuthority: wallet.publicKey,
+      inputTokenProgram: TOKEN_PROGRAM_ID,
+      outputTokenProgram: TOKEN_PROGRAM_ID,
+      systemProgram: SystemProgram.programId,
+    };
+
+    const stageIx = await program.methods
+      .stageRoute(routePlan, inAmount, new BN(900), 100, 0, new BN(0), accountsHash, new BN(20))
+      .accounts({ routeSession, user: wallet.publicKey, systemProgram: SystemProgram.programId })
+      .instruction();
+
+    const initialSourceBalance = (
+      await getAccount(provider.connection, userTokenXAccount)
+    ).amount;
+
+    await program.methods
+      .executeStagedRoute()
+      .accounts(stagedAccounts)
+      .remainingAccounts(remainingAccounts)
+      .preInstructions([stageIx])
+      .signers([wallet.payer])
+      .rpc();
+
+    const finalSourceBalance = (
+      await getAccount(provider.connection, userTokenXAccount)
+    ).amount;
+    assert.equal(
+      finalSourceBalance.toString(),
+      (initialSourceBalance - BigInt(inAmount.toString())).toString()
+    );
+    assert.isNull(await provider.connection.getAccountInfo(routeSession));
+
+    // The session was closed on execution, so the staged route cannot run again
+    try {
+      await program.methods
+        .executeStagedRoute()
+        .accounts(stagedAccounts)
+        .remainingAccounts(remainingAccounts)
+        .signers([wallet.payer])
+        .rpc();
+      assert.fail("Executed route session should not be replayable");
+    } catch (error) {
+      assert.include(error.toString(), "AccountNotInitialized");
+    }
+  });
+
+  it("should reject a staged route executed with different remaining accounts", async () => {
+    const remainingAccounts = [
+      ...stagedRouteAccounts(),
+      ...meteoraRemainingAccounts({
+        pubkey: mockMeteoraProgramId,
+        isWritable: false,
+        isSigner: false,
+      }),
+    ];
+    const [routeSession] = PublicKey.findProgramAddressSync(
+      [Buffer.from("route_session"), wallet.publicKey.toBuffer()],
+      program.programId
+    );
+
+    await program.methods
+      .stageRoute(
+        [{ swap: { meteora: {} }, inputIndex: 0, outputIndex: 21, percent: 100 }],
+        new BN(1000),
+        new BN(900),
+        100,
+        0,
+        new BN(0),
+        Array(32).fill(0),
+        new BN(20)
+      )
+      .accounts({ routeSession, user: wallet.publicKey, systemProgram: SystemProgram.programId })
+      .signers([wallet.payer])
+      .rpc();
+
+    try {
+      await program.methods
+        .executeStagedRoute()
+        .accounts({
+          routeSession,
+          userTransferAuthority: wallet.publicKey,
+          inputTokenProgram: TOKEN_PROGRAM_ID,
+          outputTokenProgram: TOKEN_PROGRAM_ID,
+          systemProgram: SystemProgram.programId,
+        })
+        .remainingAccounts(remainingAccounts)
+        .signers([wallet.payer])
+        .rpc();
+      assert.fail("Remaining accounts differing from the commitment should be rejected");
+    } catch (error) {
+      assert.include(error.toString(), "RouteCommitmentMismatch");
+    }
+
+    // The user can reclaim the rent of the unexecuted session at any time
+    await program.methods
+      .closeRouteSession()
+      .accounts({ routeSession, user: wallet.publicKey, closer: wallet.publicKey })
+      .signers([wallet.payer])
+      .rpc();
+    assert.isNull(await provider.connection.getAccountInfo(routeSession));
+  });
+
+  it("should move the mock Meteora oracle price when a route swaps through the pool", async () => {
+    const readPrice = (): Promise<BN> =>
+      mockMeteoraProgram.methods
+        .getPrice()
+        .accounts({ lbPair: meteoraPoolState, oracle: meteoraOracle })
+        .view();
+    const priceBefore = await readPrice();
+
+    await program.methods
+      .route(
+        [{ swap: { meteora: {} }, inputIndex: 0, outputIndex: 21, percent: 100 }],
+        new BN(1000),
+        new BN(900),
+        100,
+        0,
+        new BN(0),
+        false,
+        null,
+        false,
+        null
+      )
+      .accounts({
+        adapterRegistry,
+        vaultAuthority,
+        inputTokenProgram: TOKEN_PROGRAM_ID,
+        outputTokenProgram: TOKEN_PROGRAM_ID,
+        userTransferAuthority: wallet.publicKey,
+        userSourceTokenAccount: userTokenXAccount,
+        userDestinationTokenAccount: userTokenYAccount,
+        sourceMint: tokenXMint,
+        destinationMint: tokenYMint,
+        platformFeeAccount: null,
+        systemProgram: SystemProgram.programId,
+      })
+      .remainingAccounts(
+        meteoraRemainingAccounts({ pubkey: mockMeteoraProgramId, isWritable: false, isSigner: false })
+      )
+      .signers([wallet.payer])
+      .rpc();
+
+    // The route sold X into the pool, so the oracle now prices more X per Y
+    const priceAfter = await readPrice();
+    assert.isTrue(priceAfter.gt(priceBefore));
+    const oracle = await mockMeteoraProgram.account.oracle.fetch(meteoraOracle);
+    assert.equal(oracle.lastPriceXPerY.toString(), priceAfter.toString());
+  });
+
+  it("should map failures the Meteora pool reports back through the adapter's table", async () => {
+    const setFailMode = (failCode: number) =>
+      mockMeteoraProgram.methods
+        .setFailMode(failCode)
+        .accounts({ user: wallet.publicKey, lbPair: meteoraPoolState })
+        .signers([wallet.payer])
+        .rpc();
+    const routeThroughPool = () =>
+      program.methods
+        .route(
+          [{ swap: { meteora: {} }, inputIndex: 0, outputIndex: 21, percent: 100 }],
+          new BN(1000),
+          new BN(900),
+          100,
+          0,
+          new BN(0),
+          false,
+          null,
+          false,
+          null
+        )
+        .accounts({
+          adapterRegistry,
+          vaultAuthority,
+          inputTokenProgram: TOKEN_PROGRAM_ID,
+          outputTokenProgram: TOKEN_PROGRAM_ID,
+          userTransferAuthority: wallet.publicKey,
+          userSourceTokenAccount: userTokenXAccount,
+          userDestinationTokenAccount: userTokenYAccount,
+          sourceMint: tokenXMint,
+          destinationMint: tokenYMint,
+          platformFeeAccount: null,
+          systemProgram: SystemProgram.programId,
+        })
+        .remainingAccounts(
+          meteoraRemainingAccounts({ pubkey: mockMeteoraProgramId, isWritable: false, isSigner: false })
+        )
+        .signers([wallet.payer])
+        .rpc();
+
+    // Each mock_meteora code in METEORA_ERRORS, token InsufficientFunds and unmapped codes
+    const cases: [number, string][] = [
+      [6000, "StepAmountTooSmall"],
+      [6001, "SlippageToleranceExceeded"],
+      [6002, "InvalidCalculation"],
+      [6003, "InvalidPoolReserve"],
+      [6004, "InvalidPoolReserve"],
+      [6005, "InvalidMint"],
+      [6006, "AdapterCpiFailed"], // InvalidOwner is left unmapped
+      [6007, "InvalidTokenProgram"],
+      [6008, "SlippageToleranceExceeded"],
+      [1, "InsufficientFunds"],
+    ];
+    try {
+      for (const [failCode, expected] of cases) {
+        await setFailMode(failCode);
+        try {
+          await routeThroughPool();
+          assert.fail(`Reported code ${failCode} should fail the route`);
+        } catch (error) {
+          assert.include(error.toString(), expected);
+          assert.isTrue(
+            error.logs.some((log: string) => log.includes(`Meteora CPI failed: Custom(${failCode})`)),
+            "The raw code should be logged"
+          );
+        }
+      }
+    } finally {
+      await setFailMode(0);
+    }
+
+    const initialY = (await getAccount(provider.connection, userTokenYAccount)).amount;
+    await routeThroughPool();
+    assert((await getAccount(provider.connection, userTokenYAccount)).amount > initialY, "The pool swaps again once reset");
+  });
 });
@@ -299,8 +299,21 @@ describe("Flipper Swap Protocol - End to End Tests for Swaps and Limit Orders wi
     mockWhirlpoolProgramId = mockWhirlpoolProgram.programId;
     mockMeteoraProgramId = mockMeteoraProgram.programId;
 
-    const raydiumAmmConfigKeypair = Keypair.generate();
-    raydiumAmmConfig = raydiumAmmConfigKeypair.publicKey;
+    // amm_config must be owned by the Raydium program (checked by the adapter)
+    [raydiumAmmConfig] = PublicKey.findProgramAddressSync(
+      [Buffer.from("amm_config"), Buffer.from([0, 0])],
+      mockRaydiumProgramId
+    );
+    if (!(await provider.connection.getAccountInfo(raydiumAmmConfig))) {
+      await mockRaydiumProgram.methods
+        .createAmmConfig(0)
+        .accounts({
+          payer: wallet.publicKey,
+          ammConfig: raydiumAmmConfig,
+          systemProgram: SystemProgram.programId,
+        })
+        .rpc();
+    }
 
     // Check if adapter registry exists
     const registryInfo = await provider.connection.getAccountInfo(
@@ -525,7 +538,7 @@ describe("Flipper Swap Protocol - End to End Tests for Swaps and Limit Orders wi
     ).amount;
 
     await program.methods
-      .route(routePlan, inAmount, quotedOutAmount, slippageBps, platformFeeBps)
+      .route(routePlan, inAmount, quotedOutAmount, slippageBps, platformFeeBps, new BN(0), false, null, false, null)
       .accounts({
         adapterRegistry,
         vaultAuthority,
@@ -594,7 +607,7 @@ describe("Flipper Swap Protocol - End to End Tests for Swaps and Limit Orders wi
 
     // Initialize limit order and vault first (for standard tokens, account_space = 0)
     await program.methods
-      .initLimitOrder(nonce, 0)
+      .initLimitOrder(nonce, 0, false)
       .accounts({
         vaultAuthority,
         limitOrder,
@@ -602,6 +615,7 @@ describe("Flipper Swap Protocol - End to End Tests for Swaps and Limit Orders wi
         inputMint: sourceMint,
         inputTokenProgram: TOKEN_PROGRAM_ID,
         creator: user.publicKey,
+        payer: user.publicKey,
         systemProgram: SystemProgram.programId,
         rent: SYSVAR_RENT_PUBKEY,
       })
@@ -620,7 +634,11 @@ describe("Flipper Swap Protocol - End to End Tests for Swaps and Limit Orders wi
         triggerPriceBps,
         triggerType,
         expiry,
-        slippageBps
+        slippageBps,
+        false,
+        null,
+        null,
+        new BN(0)
       )
       .accounts({
         vaultAuthority,
@@ -633,6 +651,7 @@ describe("Flipper Swap Protocol - End to End Tests for Swaps and Limit Orders wi
         inputTokenProgram: TOKEN_PROGRAM_ID,
         outputTokenProgram: TOKEN_PROGRAM_ID,
         creator: user.publicKey,
+        payer: user.publicKey,
         systemProgram: SystemProgram.programId,
       })
       .signers([user])
@@ -686,7 +705,7 @@ describe("Flipper Swap Protocol - End to End Tests for Swaps and Limit Orders wi
 
     // Initialize limit order and vault first (for standard tokens, account_space = 0)
     await program.methods
-      .initLimitOrder(nonce, 0)
+      .initLimitOrder(nonce, 0, false)
       .accounts({
         vaultAuthority,
         limitOrder,
@@ -694,6 +713,7 @@ describe("Flipper Swap Protocol - End to End Tests for Swaps and Limit Orders wi
         inputMint: sourceMint,
         inputTokenProgram: TOKEN_PROGRAM_ID,
         creator: user.publicKey,
+        payer: user.publicKey,
         systemProgram: SystemProgram.programId,
         rent: SYSVAR_RENT_PUBKEY,
       })
@@ -708,7 +728,11 @@ describe("Flipper Swap Protocol - End to End Tests for Swaps and Limit Orders wi
         triggerPriceBps,
         triggerType,
         expiry,
-        slippageBps
+        slippageBps,
+        false,
+        null,
+        null,
+        new BN(0)
       )
       .accounts({
         vaultAuthority,
@@ -721,6 +745,7 @@ describe("Flipper Swap Protocol - End to End Tests for Swaps and Limit Orders wi
         inputTokenProgram: TOKEN_PROGRAM_ID,
         outputTokenProgram: TOKEN_PROGRAM_ID,
         creator: user.publicKey,
+        payer: user.publicKey,
         systemProgram: SystemProgram.programId,
       })
       .signers([user])
@@ -771,7 +796,7 @@ describe("Flipper Swap Protocol - End to End Tests for Swaps and Limit Orders wi
     const triggerRatio = 10000 + orderData.triggerPriceBps;
 
     await program.methods
-      .executeLimitOrder(routePlan, quotedOutAmount, platformFeeBps)
+      .executeLimitOrder(routePlan, quotedOutAmount, platformFeeBps, null, null, null)
       .accounts({
         adapterRegistry,
         vaultAuthority,
@@ -835,7 +860,7 @@ describe("Flipper Swap Protocol - End to End Tests for Swaps and Limit Orders wi
 
     // Initialize limit order and vault first (for standard tokens, account_space = 0)
     await program.methods
-      .initLimitOrder(nonce, 0)
+      .initLimitOrder(nonce, 0, false)
       .accounts({
         vaultAuthority,
         limitOrder,
@@ -843,6 +868,7 @@ describe("Flipper Swap Protocol - End to End Tests for Swaps and Limit Orders wi
         inputMint: sourceMint,
         inputTokenProgram: TOKEN_PROGRAM_ID,
         creator: user.publicKey,
+        payer: user.publicKey,
         systemProgram: SystemProgram.programId,
         rent: SYSVAR_RENT_PUBKEY,
       })
@@ -857,7 +883,11 @@ describe("Flipper Swap Protocol - End to End Tests for Swaps and Limit Orders wi
         triggerPriceBps,
         triggerType,
         expiry,
-        slippageBps
+        slippageBps,
+        false,
+        null,
+        null,
+        new BN(0)
       )
       .accounts({
         vaultAuthority,
@@ -870,6 +900,7 @@ describe("Flipper Swap Protocol - End to End Tests for Swaps and Limit Orders wi
         inputTokenProgram: TOKEN_PROGRAM_ID,
         outputTokenProgram: TOKEN_PROGRAM_ID,
         creator: user.publicKey,
+        payer: user.publicKey,
         systemProgram: SystemProgram.programId,
       })
       .signers([user])
@@ -931,7 +962,7 @@ describe("Flipper Swap Protocol - End to End Tests for Swaps and Limit Orders wi
 
     // Initialize limit order and vault first (for standard tokens, account_space = 0)
     await program.methods
-      .initLimitOrder(orderNonce, 0)
+      .initLimitOrder(orderNonce, 0, false)
       .accounts({
         vaultAuthority,
         limitOrder,
@@ -939,6 +970,7 @@ describe("Flipper Swap Protocol - End to End Tests for Swaps and Limit Orders wi
         inputMint: destinationMint, // For route_and_create_order, vault holds output_mint tokens
         inputTokenProgram: TOKEN_PROGRAM_ID,
         creator: user.publicKey,
+        payer: user.publicKey,
         systemProgram: SystemProgram.programId,
         rent: SYSVAR_RENT_PUBKEY,
       })
@@ -993,7 +1025,8 @@ describe("Flipper Swap Protocol - End to End Tests for Swaps and Limit Orders wi
         orderMinOutputAmount,
         orderTriggerPriceBps,
         orderExpiry,
-        orderSlippageBps
+        orderSlippageBps,
+        null
       )
       .accounts({
         adapterRegistry,
@@ -1139,7 +1172,7 @@ describe("Flipper Swap Protocol - End to End Tests for Swaps and Limit Orders wi
 
     // Initialize limit order and vault first (for standard tokens, account_space = 0)
     await program.methods
-      .initLimitOrder(nonce, 0)
+      .initLimitOrder(nonce, 0, false)
       .accounts({
         vaultAuthority,
         limitOrder,
@@ -1147,6 +1180,7 @@ describe("Flipper Swap Protocol - End to End Tests for Swaps and Limit Orders wi
         inputMint: sourceMint,
         inputTokenProgram: TOKEN_PROGRAM_ID,
         creator: user.publicKey,
+        payer: user.publicKey,
         systemProgram: SystemProgram.programId,
         rent: SYSVAR_RENT_PUBKEY,
       })
@@ -1161,7 +1195,11 @@ describe("Flipper Swap Protocol - End to End Tests for Swaps and Limit Orders wi
         triggerPriceBps,
         triggerType,
         expiry,
-        slippageBps
+        slippageBps,
+        false,
+        null,
+        null,
+        new BN(0)
       )
       .accounts({
         vaultAuthority,
@@ -1174,6 +1212,7 @@ describe("Flipper Swap Protocol - End to End Tests for Swaps and Limit Orders wi
         inputTokenProgram: TOKEN_PROGRAM_ID,
         outputTokenProgram: TOKEN_PROGRAM_ID,
         creator: user.publicKey,
+        payer: user.publicKey,
         systemProgram: SystemProgram.programId,
       })
       .signers([user])
@@ -1235,7 +1274,7 @@ describe("Flipper Swap Protocol - End to End Tests for Swaps and Limit Orders wi
     assert(priceRatio.lte(new BN(triggerRatio)), "Stop loss should trigger");
 
     await program.methods
-      .executeLimitOrder(routePlan, quotedOutAmount, platformFeeBps)
+      .executeLimitOrder(routePlan, quotedOutAmount, platformFeeBps, null, null, null)
       .accounts({
         adapterRegistry,
         vaultAuthority,
@@ -1342,7 +1381,11 @@ describe("Flipper Swap Protocol - End to End Tests for Swaps and Limit Orders wi
                     triggerPriceBps,
                     triggerType,
                     expiry,
-                    slippageBps
+                    slippageBps,
+                    false,
+                    null,
+                    null,
+                    new BN(0)
                 )
                 .accounts({
                     vaultAuthority,
@@ -1355,6 +1398,7 @@ describe("Flipper Swap Protocol - End to End Tests for Swaps and Limit Orders wi
                     inputTokenProgram: TOKEN_PROGRAM_ID,
                     outputTokenProgram: TOKEN_PROGRAM_ID,
                     creator: user.publicKey,
+                    payer: user.publicKey,
                     systemProgram: SystemProgram.programId,
                 })
                 .signers([user])
@@ -1409,7 +1453,7 @@ describe("Flipper Swap Protocol - End to End Tests for Swaps and Limit Orders wi
 
             // Execute the order
             await program.methods
-                .executeLimitOrder(routePlan, quotedOutAmount, platformFeeBps)
+                .executeLimitOrder(routePlan, quotedOutAmount, platformFeeBps, null, null, null)
                 .accounts({
                     adapterRegistry,
                     vaultAuthority,
@@ -1461,7 +1505,7 @@ describe("Flipper Swap Protocol - End to End Tests for Swaps and Limit Orders wi
       // Initialize limit order and vault first (for standard tokens, account_space = 0)
       // This creates an order in Init status (not yet filled with createLimitOrder)
       await program.methods
-        .initLimitOrder(nonce, 0)
+        .initLimitOrder(nonce, 0, false)
         .accounts({
           vaultAuthority,
           limitOrder,
@@ -1469,6 +1513,7 @@ describe("Flipper Swap Protocol - End to End Tests for Swaps and Limit Orders wi
           inputMint: sourceMint,
           inputTokenProgram: TOKEN_PROGRAM_ID,
           creator: user.publicKey,
+          payer: user.publicKey,
           systemProgram: SystemProgram.programId,
           rent: SYSVAR_RENT_PUBKEY,
         })
@@ -1564,7 +1609,7 @@ describe("Flipper Swap Protocol - End to End Tests for Swaps and Limit Orders wi
 
       // Initialize limit order and vault first (for standard tokens, account_space = 0)
       await program.methods
-        .initLimitOrder(nonce, 0)
+        .initLimitOrder(nonce, 0, false)
         .accounts({
           vaultAuthority,
           limitOrder,
@@ -1572,6 +1617,7 @@ describe("Flipper Swap Protocol - End to End Tests for Swaps and Limit Orders wi
           inputMint: sourceMint,
           inputTokenProgram: TOKEN_PROGRAM_ID,
           creator: user.publicKey,
+          payer: user.publicKey,
           systemProgram: SystemProgram.programId,
           rent: SYSVAR_RENT_PUBKEY,
         })
@@ -1586,7 +1632,11 @@ describe("Flipper Swap Protocol - End to End Tests for Swaps and Limit Orders wi
           triggerPriceBps,
           triggerType,
           expiry,
-          slippageBps
+          slippageBps,
+          false,
+          null,
+          null,
+          new BN(0)
         )
         .accounts({
           vaultAuthority,
@@ -1599,6 +1649,7 @@ describe("Flipper Swap Protocol - End to End Tests for Swaps and Limit Orders wi
           inputTokenProgram: TOKEN_PROGRAM_ID,
           outputTokenProgram: TOKEN_PROGRAM_ID,
           creator: user.publicKey,
+          payer: user.publicKey,
           systemProgram: SystemProgram.programId,
         })
         .signers([user])
@@ -1686,7 +1737,7 @@ describe("Flipper Swap Protocol - End to End Tests for Swaps and Limit Orders wi
 
       // Initialize limit order and vault first (for standard tokens, account_space = 0)
       await program.methods
-        .initLimitOrder(nonce, 0)
+        .initLimitOrder(nonce, 0, false)
         .accounts({
           vaultAuthority,
           limitOrder,
@@ -1694,6 +1745,7 @@ describe("Flipper Swap Protocol - End to End Tests for Swaps and Limit Orders wi
           inputMint: sourceMint,
           inputTokenProgram: TOKEN_PROGRAM_ID,
           creator: user.publicKey,
+          payer: user.publicKey,
           systemProgram: SystemProgram.programId,
           rent: SYSVAR_RENT_PUBKEY,
         })
@@ -1710,7 +1762,11 @@ describe("Flipper Swap Protocol - End to End Tests for Swaps and Limit Orders wi
           triggerPriceBps,
           triggerType,
           futureExpiry,
-          slippageBps
+          slippageBps,
+          false,
+          null,
+          null,
+          new BN(0)
         )
         .accounts({
           vaultAuthority,
@@ -1723,6 +1779,7 @@ describe("Flipper Swap Protocol - End to End Tests for Swaps and Limit Orders wi
           inputTokenProgram: TOKEN_PROGRAM_ID,
           outputTokenProgram: TOKEN_PROGRAM_ID,
           creator: user.publicKey,
+          payer: user.publicKey,
           systemProgram: SystemProgram.programId,
         })
         .signers([user])
@@ -1806,7 +1863,7 @@ describe("Flipper Swap Protocol - End to End Tests for Swaps and Limit Orders wi
 
       // Initialize limit order and vault first (for standard tokens, account_space = 0)
       await program.methods
-        .initLimitOrder(expiredNonce, 0)
+        .initLimitOrder(expiredNonce, 0, false)
         .accounts({
           vaultAuthority,
           limitOrder: expiredLimitOrder,
@@ -1814,6 +1871,7 @@ describe("Flipper Swap Protocol - End to End Tests for Swaps and Limit Orders wi
           inputMint: sourceMint,
           inputTokenProgram: TOKEN_PROGRAM_ID,
           creator: user.publicKey,
+          payer: user.publicKey,
           systemProgram: SystemProgram.programId,
           rent: SYSVAR_RENT_PUBKEY,
         })
@@ -1830,7 +1888,11 @@ describe("Flipper Swap Protocol - End to End Tests for Swaps and Limit Orders wi
           triggerPriceBps,
           triggerType,
           shortExpiry,
-          slippageBps
+          slippageBps,
+          false,
+          null,
+          null,
+          new BN(0)
         )
         .accounts({
           vaultAuthority,
@@ -1843,6 +1905,7 @@ describe("Flipper Swap Protocol - End to End Tests for Swaps and Limit Orders wi
           inputTokenProgram: TOKEN_PROGRAM_ID,
           outputTokenProgram: TOKEN_PROGRAM_ID,
           creator: user.publicKey,
+          payer: user.publicKey,
           systemProgram: SystemProgram.programId,
         })
         .signers([user])
@@ -1927,7 +1990,7 @@ describe("Flipper Swap Protocol - End to End Tests for Swaps and Limit Orders wi
       // Initialize limit order and vault first (for standard tokens, account_space = 0)
       // This creates an order in Init status (not yet filled with createLimitOrder)
       await program.methods
-        .initLimitOrder(nonce, 0)
+        .initLimitOrder(nonce, 0, false)
         .accounts({
           vaultAuthority,
           limitOrder,
@@ -1935,6 +1998,7 @@ describe("Flipper Swap Protocol - End to End Tests for Swaps and Limit Orders wi
           inputMint: sourceMint,
           inputTokenProgram: TOKEN_PROGRAM_ID,
           creator: user.publicKey,
+          payer: user.publicKey,
           systemProgram: SystemProgram.programId,
           rent: SYSVAR_RENT_PUBKEY,
         })
@@ -2025,7 +2089,7 @@ describe("Flipper Swap Protocol - End to End Tests for Swaps and Limit Orders wi
 
       // Initialize limit order and vault first (for standard tokens, account_space = 0)
       await program.methods
-        .initLimitOrder(nonce, 0)
+        .initLimitOrder(nonce, 0, false)
         .accounts({
           vaultAuthority,
           limitOrder,
@@ -2033,6 +2097,7 @@ describe("Flipper Swap Protocol - End to End Tests for Swaps and Limit Orders wi
           inputMint: sourceMint,
           inputTokenProgram: TOKEN_PROGRAM_ID,
           creator: user.publicKey,
+          payer: user.publicKey,
           systemProgram: SystemProgram.programId,
           rent: SYSVAR_RENT_PUBKEY,
         })
@@ -2047,7 +2112,11 @@ describe("Flipper Swap Protocol - End to End Tests for Swaps and Limit Orders wi
           triggerPriceBps,
           triggerType,
           expiry,
-          slippageBps
+          slippageBps,
+          false,
+          null,
+          null,
+          new BN(0)
         )
         .accounts({
           vaultAuthority,
@@ -2060,6 +2129,7 @@ describe("Flipper Swap Protocol - End to End Tests for Swaps and Limit Orders wi
           inputTokenProgram: TOKEN_PROGRAM_ID,
           outputTokenProgram: TOKEN_PROGRAM_ID,
           creator: user.publicKey,
+          payer: user.publicKey,
           systemProgram: SystemProgram.programId,
         })
         .signers([user])
@@ -2116,7 +2186,7 @@ describe("Flipper Swap Protocol - End to End Tests for Swaps and Limit Orders wi
 
       // Initialize limit order and vault first (for standard tokens, account_space = 0)
       await program.methods
-        .initLimitOrder(nonce, 0)
+        .initLimitOrder(nonce, 0, false)
         .accounts({
           vaultAuthority,
           limitOrder,
@@ -2124,6 +2194,7 @@ describe("Flipper Swap Protocol - End to End Tests for Swaps and Limit Orders wi
           inputMint: sourceMint,
           inputTokenProgram: TOKEN_PROGRAM_ID,
           creator: user.publicKey,
+          payer: user.publicKey,
           systemProgram: SystemProgram.programId,
           rent: SYSVAR_RENT_PUBKEY,
         })
@@ -2138,7 +2209,11 @@ describe("Flipper Swap Protocol - End to End Tests for Swaps and Limit Orders wi
           triggerPriceBps,
           triggerType,
           expiry,
-          slippageBps
+          slippageBps,
+          false,
+          null,
+          null,
+          new BN(0)
         )
         .accounts({
           vaultAuthority,
@@ -2151,6 +2226,7 @@ describe("Flipper Swap Protocol - End to End Tests for Swaps and Limit Orders wi
           inputTokenProgram: TOKEN_PROGRAM_ID,
           outputTokenProgram: TOKEN_PROGRAM_ID,
           creator: user.publicKey,
+          payer: user.publicKey,
           systemProgram: SystemProgram.programId,
         })
         .signers([user])
@@ -2185,4 +2261,312 @@ describe("Flipper Swap Protocol - End to End Tests for Swaps and Limit Orders wi
       }
     });
   });
+
+  it("8. Revoke vault delegations sweep", async () => {
+    const vaults = [inputVault, intermediateVault, outputVault];
+
+    await program.methods
+      .revokeVaultDelegations()
+      .accounts({
+        vaultAuthority,
+        adapterRegistry,
+        caller: operator.publicKey,
+        tokenProgram: TOKEN_PROGRAM_ID,
+      })
+      .remainingAccounts(
+        vaults.map((pubkey) => ({ pubkey, isWritable: true, isSigner: false }))
+      )
+      .signers([operator])
+      .rpc();
+
+    // Routes never leave a delegate behind, and the sweep leaves none either
+    for (const vault of vaults) {
+      const account = await getAccount(provider.connection, vault);
+      assert.isNull(account.delegate, "Vault should have no delegate");
+      assert.equal(account.delegatedAmount.toString(), "0");
+    }
+
+    try {
+      await program.methods
+        .revokeVaultDelegations()
+        .accounts({
+          vaultAuthority,
+          adapterRegistry,
+          caller: user.publicKey,
+          tokenProgram: TOKEN_PROGRAM_ID,
+        })
+        .remainingAccounts(
+          vaults.map((pubkey) => ({ pubkey, isWritable: true, isSigner: false }))
+        )
+        .signers([user])
+        .rpc();
+      assert.fail("Should have failed - user is not admin or operator");
+    } catch (error: any) {
+      assert(
+        error.message.includes("UnauthorizedVaultCreator"),
+        "Should fail with UnauthorizedVaultCreator error"
+      );
+    }
+  });
+
+  it("9. Unwrap and re-wrap WSOL through the lamport escrow inside a route", async () => {
+    const [solEscrow] = PublicKey.findProgramAddressSync(
+      [Buffer.from("sol_escrow")],
+      program.programId
+    );
+    const [escrowUnwrapAccount] = PublicKey.findProgramAddressSync(
+      [Buffer.from("unwrap_account"), solEscrow.toBuffer()],
+      program.programId
+    );
+
+    for (const [name, swapType] of [
+      ["wrap_sol", { wrapSol: {} }],
+      ["unwrap_sol", { unwrapSol: {} }],
+    ] as const) {
+      await program.methods
+        .configureAdapter({
+          name,
+          programId: SystemProgram.programId,
+          swapType,
+        })
+        .accounts({ adapterRegistry, operator: wallet.publicKey })
+        .signers([wallet.payer])
+        .rpc();
+    }
+
+    // The escrow's reserve keeps it rent exempt and pays for the transient unwrap account
+    const reserve =
+      (await provider.connection.getMinimumBalanceForRentExemption(0)) +
+      (await provider.connection.getMinimumBalanceForRentExemption(165));
+    const escrowLamports = await provider.connection.getBalance(solEscrow);
+    if (escrowLamports < reserve) {
+      await provider.sendAndConfirm(
+        new Transaction().add(
+          SystemProgram.transfer({
+            fromPubkey: wallet.publicKey,
+            toPubkey: solEscrow,
+            lamports: reserve - escrowLamports,
+          })
+        ),
+        [wallet.payer]
+      );
+    }
+    const escrowBefore = await provider.connection.getBalance(solEscrow);
+
+    // A second vault authority owned WSOL account receives the re-wrapped lamports
+    const rewrappedVault = (
+      await getOrCreateAssociatedTokenAccount(
+        provider.connection,
+        wallet.payer,
+        NATIVE_MINT,
+        vaultAuthority,
+        true
+      )
+    ).address;
+
+    const wsolStepAccounts = [
+      { pubkey: solEscrow, isWritable: true, isSigner: false }, // escrow in the PoolInfo slot
+      { pubkey: escrowUnwrapAccount, isWritable: true, isSigner: false },
+      { pubkey: NATIVE_MINT, isWritable: false, isSigner: false },
+      { pubkey: TOKEN_PROGRAM_ID, isWritable: false, isSigner: false },
+      { pubkey: SystemProgram.programId, isWritable: false, isSigner: false }, // "program"
+    ];
+    const inputPoolVault =
+      sourceMint.toBuffer().compare(destinationMint.toBuffer()) < 0
+        ? raydiumTokenAVault
+        : raydiumTokenBVault;
+    const outputPoolVault =
+      sourceMint.toBuffer().compare(destinationMint.toBuffer()) < 0
+        ? raydiumTokenBVault
+        : raydiumTokenAVault;
+    const raydiumStepAccounts = [
+      { pubkey: raydiumPoolInfo, isWritable: true, isSigner: false },
+      { pubkey: raydiumPoolAuthority, isWritable: false, isSigner: false },
+      { pubkey: raydiumAmmConfig, isWritable: false, isSigner: false },
+      { pubkey: raydiumPoolState, isWritable: true, isSigner: false },
+      { pubkey: inputPoolVault, isWritable: true, isSigner: false },
+      { pubkey: outputPoolVault, isWritable: true, isSigner: false },
+      { pubkey: TOKEN_PROGRAM_ID, isWritable: false, isSigner: false },
+      { pubkey: TOKEN_PROGRAM_ID, isWritable: false, isSigner: false },
+      { pubkey: sourceMint, isWritable: false, isSigner: false },
+      { pubkey: destinationMint, isWritable: false, isSigner: false },
+      { pubkey: raydiumObservationState, isWritable: true, isSigner: false },
+      { pubkey: mockRaydiumProgramId, isWritable: false, isSigner: false },
+    ];
+    const escrowAccount = { pubkey: solEscrow, isWritable: true, isSigner: false };
+    const rewrappedAccount = { pubkey: rewrappedVault, isWritable: true, isSigner: false };
+
+    const routeAccounts = {
+      adapterRegistry,
+      vaultAuthority,
+      inputTokenProgram: TOKEN_PROGRAM_ID,
+      outputTokenProgram: TOKEN_PROGRAM_ID,
+      userTransferAuthority: user.publicKey,
+      userSourceTokenAccount,
+      userDestinationTokenAccount,
+      sourceMint,
+      destinationMint,
+      platformFeeAccount,
+      systemProgram: SystemProgram.programId,
+    };
+    const inAmount = new BN(10_000_000);
+
+    // WSOL vault -> UnwrapSol -> escrow -> WrapSol -> second WSOL vault -> Raydium -> output vault
+    const routePlan = [
+      { swap: { unwrapSol: {} }, percent: 100, inputIndex: 0, outputIndex: 6 },
+      { swap: { wrapSol: {} }, percent: 100, inputIndex: 6, outputIndex: 12 },
+      { swap: { raydium: {} }, percent: 100, inputIndex: 12, outputIndex: 25 },
+    ];
+    const remainingAccounts = [
+      { pubkey: inputVault, isWritable: true, isSigner: false }, // 0
+      ...wsolStepAccounts, // 1-5
+      escrowAccount, // 6
+      ...wsolStepAccounts, // 7-11
+      rewrappedAccount, // 12
+      ...raydiumStepAccounts, // 13-24
+      { pubkey: outputVault, isWritable: true, isSigner: false }, // 25
+    ];
+
+    const initialSource = (await getAccount(provider.connection, userSourceTokenAccount)).amount;
+    const initialDest = (await getAccount(provider.connection, userDestinationTokenAccount)).amount;
+    const initialRewrapped = (await getAccount(provider.connection, rewrappedVault)).amount;
+
+    await program.methods
+      .route(routePlan, inAmount, new BN(5_000_000), 100, 0, new BN(0), false, null, false, null)
+      .accounts(routeAccounts)
+      .remainingAccounts(remainingAccounts)
+      .signers([user])
+      .rpc();
+
+    const finalSource = (await getAccount(provider.connection, userSourceTokenAccount)).amount;
+    const finalDest = (await getAccount(provider.connection, userDestinationTokenAccount)).amount;
+    assert.equal((initialSource - finalSource).toString(), inAmount.toString());
+    assert(finalDest > initialDest, "Destination balance should grow");
+
+    // Every unwrapped lamport was wrapped again and swapped on; nothing is left behind
+    assert.equal(await provider.connection.getBalance(solEscrow), escrowBefore);
+    assert.isNull(await provider.connection.getAccountInfo(escrowUnwrapAccount));
+    assert.equal(
+      (await getAccount(provider.connection, rewrappedVault)).amount.toString(),
+      initialRewrapped.toString()
+    );
+
+    // A route opening with WrapSol takes native SOL, so its source mint must be the native mint
+    const wrapFirstPlan = [
+      { swap: { wrapSol: {} }, percent: 100, inputIndex: 1, outputIndex: 7 },
+      { swap: { raydium: {} }, percent: 100, inputIndex: 7, outputIndex: 20 },
+    ];
+    try {
+      await program.methods
+        .route(wrapFirstPlan, inAmount, new BN(5_000_000), 100, 0, new BN(0), false, null, false, null)
+        .accounts(routeAccounts)
+        .remainingAccounts([
+          { pubkey: inputVault, isWritable: true, isSigner: false }, // 0: source vault
+          escrowAccount, // 1
+          ...wsolStepAccounts, // 2-6
+          rewrappedAccount, // 7
+          ...raydiumStepAccounts, // 8-19
+          { pubkey: outputVault, isWritable: true, isSigner: false }, // 20
+        ])
+        .signers([user])
+        .rpc();
+      assert.fail("Should have failed - the route's source mint is not the native mint");
+    } catch (error: any) {
+      assert(
+        error.message.includes("InvalidMint"),
+        `Expected InvalidMint, got ${error.message}`
+      );
+    }
+  });
+
+  it("10. Route native SOL in and out through the lamport escrow", async () => {
+    const [solEscrow] = PublicKey.findProgramAddressSync(
+      [Buffer.from("sol_escrow")],
+      program.programId
+    );
+    const [escrowUnwrapAccount] = PublicKey.findProgramAddressSync(
+      [Buffer.from("unwrap_account"), solEscrow.toBuffer()],
+      program.programId
+    );
+    const wsolStepAccounts = [
+      { pubkey: solEscrow, isWritable: true, isSigner: false }, // escrow in the PoolInfo slot
+      { pubkey: escrowUnwrapAccount, isWritable: true, isSigner: false },
+      { pubkey: NATIVE_MINT, isWritable: false, isSigner: false },
+      { pubkey: TOKEN_PROGRAM_ID, isWritable: false, isSigner: false },
+      { pubkey: SystemProgram.programId, isWritable: false, isSigner: false }, // "program"
+    ];
+    const escrowAccount = { pubkey: solEscrow, isWritable: true, isSigner: false };
+    const wsolVault = (
+      await getOrCreateAssociatedTokenAccount(
+        provider.connection,
+        wallet.payer,
+        NATIVE_MINT,
+        vaultAuthority,
+        true
+      )
+    ).address;
+    // The route accounts still name the user's WSOL account, which native SOL leaves untouched
+    const userWsolAccount = (
+      await getOrCreateAssociatedTokenAccount(
+        provider.connection,
+        wallet.payer,
+        NATIVE_MINT,
+        user.publicKey
+      )
+    ).address;
+
+    const inAmount = new BN(5_000_000);
+    const escrowBefore = await provider.connection.getBalance(solEscrow);
+    const userBefore = await provider.connection.getBalance(user.publicKey);
+    const vaultBefore = (await getAccount(provider.connection, wsolVault)).amount;
+
+    // User lamports -> escrow -> WrapSol -> WSOL vault -> UnwrapSol -> escrow -> user lamports
+    await program.methods
+      .route(
+        [
+          { swap: { wrapSol: {} }, percent: 100, inputIndex: 0, outputIndex: 6 },
+          { swap: { unwrapSol: {} }, percent: 100, inputIndex: 6, outputIndex: 12 },
+        ],
+        inAmount,
+        inAmount,
+        100,
+        0,
+        new BN(0),
+        false,
+        null,
+        false,
+        null
+      )
+      .accounts({
+        adapterRegistry,
+        vaultAuthority,
+        inputTokenProgram: TOKEN_PROGRAM_ID,
+        outputTokenProgram: TOKEN_PROGRAM_ID,
+        userTransferAuthority: user.publicKey,
+        userSourceTokenAccount: userWsolAccount,
+        userDestinationTokenAccount: userWsolAccount,
+        sourceMint: NATIVE_MINT,
+        destinationMint: NATIVE_MINT,
+        platformFeeAccount: null,
+        systemProgram: SystemProgram.programId,
+      })
+      .remainingAccounts([
+        escrowAccount, // 0: route input, funded from the user's lamports
+        ...wsolStepAccounts, // 1-5
+        { pubkey: wsolVault, isWritable: true, isSigner: false }, // 6
+        ...wsolStepAccounts, // 7-11
+        escrowAccount, // 12: route output, paid out to the user
+      ])
+      .signers([user])
+      .rpc();
+
+    // The provider wallet pays the transaction fee, so the user gets back every lamport
+    assert.equal(await provider.connection.getBalance(user.publicKey), userBefore);
+    assert.equal(await provider.connection.getBalance(solEscrow), escrowBefore);
+    assert.equal(
+      (await getAccount(provider.connection, wsolVault)).amount.toString(),
+      vaultBefore.toString()
+    );
+    assert.isNull(await provider.connection.getAccountInfo(escrowUnwrapAccount));
+  });
 });
@@ -372,6 +372,9 @@ describe("Flipper - Shared Jupiter CPI Instructions", () => {
             new BN(1),
             1,
             0,
+            new BN(0),
+            false,
+            false,
             Buffer.alloc(8) // Empty data (8 bytes minimum for mock discriminator)
           )
           .accounts({
@@ -495,6 +498,9 @@ describe("Flipper - Shared Jupiter CPI Instructions", () => {
           quotedOutAmount,
           slippageBps,
           platformFeeBps,
+          new BN(0),
+          false,
+          false,
           data
         )
         .accounts({
@@ -566,6 +572,308 @@ describe("Flipper - Shared Jupiter CPI Instructions", () => {
 
       console.log("✅ Verified: User received output tokens directly");
     });
+
+    it("Should take the platform fee in the source mint before the Jupiter CPI", async () => {
+      const inAmount = new BN(100_000_000); // 100 tokens
+      const platformFeeBps = 50; // 0.5%, skimmed from the input
+      const feeAmount = new BN(500_000);
+      const swapInAmount = inAmount.sub(feeAmount);
+      const quotedOutAmount = new BN(149_250_000); // 1.5x of the post-fee input
+      const slippageBps = 50;
+
+      // Fee account for the source mint, owned by the vault authority
+      const sourceFeeAccount = (
+        await getOrCreateAssociatedTokenAccount(
+          provider.connection,
+          wallet.payer,
+          sourceMint,
+          vaultAuthority,
+          true // allowOwnerOffCurve
+        )
+      ).address;
+
+      const mockLiquidityPool = (
+        await getOrCreateAssociatedTokenAccount(
+          provider.connection,
+          wallet.payer,
+          destinationMint,
+          wallet.publicKey
+        )
+      ).address;
+      await mintTo(
+        provider.connection,
+        wallet.payer,
+        destinationMint,
+        mockLiquidityPool,
+        admin,
+        quotedOutAmount.toNumber() * 2
+      );
+
+      const routePlan = [
+        { swap: { raydium: {} }, percent: 100, inputIndex: 0, outputIndex: 1 },
+      ];
+      const remainingAccounts = buildJupiterRemainingAccounts({
+        tokenProgram: TOKEN_PROGRAM_ID,
+        jupiterProgramAuthority: mockJupiterProgram.programId,
+        vaultAuthority,
+        vaultSource: sourceVault,
+        vaultDestination: destinationVault,
+        sourceMint,
+        destinationMint,
+        platformFeeOrPlaceholder: TOKEN_PROGRAM_ID,
+        token2022OrPlaceholder: JUPITER_EVENT_AUTHORITY,
+        eventAuthority: JUPITER_EVENT_AUTHORITY,
+        jupiterProgram: mockJupiterProgram.programId,
+        mockPool: mockLiquidityPool,
+        mockPoolAuthority: wallet.publicKey,
+      });
+      const sharedRouteWithInputFee = (
+        jupiterInAmount: BN,
+        feeBps: number = platformFeeBps,
+        feeAccount: PublicKey | null = sourceFeeAccount
+      ) =>
+        program.methods
+          .sharedRoute(
+            inAmount,
+            quotedOutAmount,
+            slippageBps,
+            feeBps,
+            new BN(0),
+            true,
+            false,
+            buildJupiterCpiInstructionData(
+              mockJupiterProgram,
+              0,
+              routePlan,
+              jupiterInAmount,
+              quotedOutAmount,
+              slippageBps,
+              0
+            )
+          )
+          .accounts({
+            vaultAuthority,
+            userSourceTokenAccount,
+            userDestinationTokenAccount,
+            vaultSource: sourceVault,
+            vaultDestination: destinationVault,
+            sourceMint,
+            destinationMint,
+            inputTokenProgram: TOKEN_PROGRAM_ID,
+            outputTokenProgram: TOKEN_PROGRAM_ID,
+            userTransferAuthority: user.publicKey,
+            platformFeeAccount: feeAccount,
+            jupiterProgram: mockJupiterProgram.programId,
+            systemProgram: SystemProgram.programId,
+          })
+          .remainingAccounts(remainingAccounts)
+          .signers([user])
+          .rpc({ commitment: "confirmed" });
+
+      // Jupiter data still asking for the gross deposit is rejected
+      try {
+        await sharedRouteWithInputFee(inAmount);
+        assert.fail("Should have failed with JupiterInAmountMismatch");
+      } catch (err: any) {
+        assert.include(err.toString(), "JupiterInAmountMismatch");
+      }
+
+      // The destination-mint fee account no longer fits when the fee is on the input
+      try {
+        await program.methods
+          .sharedRoute(inAmount, quotedOutAmount, slippageBps, platformFeeBps, new BN(0), true, false, Buffer.alloc(8))
+          .accounts({
+            vaultAuthority,
+            userSourceTokenAccount,
+            userDestinationTokenAccount,
+            vaultSource: sourceVault,
+            vaultDestination: destinationVault,
+            sourceMint,
+            destinationMint,
+            inputTokenProgram: TOKEN_PROGRAM_ID,
+            outputTokenProgram: TOKEN_PROGRAM_ID,
+            userTransferAuthority: user.publicKey,
+            platformFeeAccount,
+            jupiterProgram: mockJupiterProgram.programId,
+            systemProgram: SystemProgram.programId,
+          })
+          .remainingAccounts(remainingAccounts)
+          .signers([user])
+          .rpc();
+        assert.fail("Should have failed with InvalidPlatformFeeMint");
+      } catch (err: any) {
+        assert.include(err.toString(), "InvalidPlatformFeeMint");
+      }
+
+      // Charging a fee without a fee account is rejected rather than skipping the fee
+      try {
+        await sharedRouteWithInputFee(swapInAmount, platformFeeBps, null);
+        assert.fail("Should have failed with MissingFeeAccount");
+      } catch (err: any) {
+        assert.include(err.toString(), "MissingFeeAccount");
+      }
+
+      const feeBefore = await getAccount(provider.connection, sourceFeeAccount);
+      const outputFeeBefore = await getAccount(provider.connection, platformFeeAccount);
+      const userDestBefore = await getAccount(provider.connection, userDestinationTokenAccount);
+
+      const signature = await sharedRouteWithInputFee(swapInAmount);
+
+      const feeAfter = await getAccount(provider.connection, sourceFeeAccount);
+      const outputFeeAfter = await getAccount(provider.connection, platformFeeAccount);
+      const userDestAfter = await getAccount(provider.connection, userDestinationTokenAccount);
+      assert.equal(
+        Number(feeAfter.amount) - Number(feeBefore.amount),
+        feeAmount.toNumber(),
+        "Fee should be taken in the source mint"
+      );
+      assert.equal(
+        outputFeeAfter.amount,
+        outputFeeBefore.amount,
+        "No fee should be taken from the output"
+      );
+      assert.equal(
+        Number(userDestAfter.amount) - Number(userDestBefore.amount),
+        quotedOutAmount.toNumber(),
+        "User should receive the full output of the post-fee swap"
+      );
+
+      // The FeeEvent names the shared route flow and the source mint
+      const txInfo = await provider.connection.getTransaction(signature, {
+        commitment: "confirmed",
+        maxSupportedTransactionVersion: 0,
+      });
+      const accountKeys = txInfo.transaction.message.getAccountKeys();
+      const feeEvent = txInfo.meta.innerInstructions
+        .flatMap((inner) => inner.instructions)
+        .filter((ix) => accountKeys.get(ix.programIdIndex).equals(program.programId))
+        .map((ix) =>
+          program.coder.events.decode(
+            Buffer.from(anchor.utils.bytes.bs58.decode(ix.data).subarray(8)).toString("base64")
+          )
+        )
+        .find((event) => event?.name === "FeeEvent");
+      assert.deepEqual(feeEvent.data.source, { sharedRoute: {} });
+      assert(feeEvent.data.mint.equals(sourceMint));
+
+      // Without a fee the destination-mint account is ignored instead of failing the mint check
+      const unchargedBefore = await getAccount(provider.connection, platformFeeAccount);
+      const unchargedSignature = await sharedRouteWithInputFee(inAmount, 0, platformFeeAccount);
+      const unchargedAfter = await getAccount(provider.connection, platformFeeAccount);
+      assert.equal(unchargedAfter.amount, unchargedBefore.amount, "No fee should be taken without a fee");
+      const unchargedTx = await provider.connection.getTransaction(unchargedSignature, {
+        commitment: "confirmed",
+        maxSupportedTransactionVersion: 0,
+      });
+      const unchargedKeys = unchargedTx.transaction.message.getAccountKeys();
+      const unchargedFeeEvents = unchargedTx.meta.innerInstructions
+        .flatMap((inner) => inner.instructions)
+        .filter((ix) => unchargedKeys.get(ix.programIdIndex).equals(program.programId))
+        .map((ix) =>
+          program.coder.events.decode(
+            Buffer.from(anchor.utils.bytes.bs58.decode(ix.data).subarray(8)).toString("base64")
+          )
+        )
+        .filter((event) => event?.name === "FeeEvent");
+      assert.isEmpty(unchargedFeeEvents, "No FeeEvent without a fee");
+    });
+
+    it("Should not credit a direct vault deposit made earlier in the transaction as swap output", async () => {
+      const inAmount = new BN(10_000_000);
+      const quotedOutAmount = new BN(15_000_000); // Mock Jupiter pays min(1.5x input, quote)
+      const donation = new BN(7_000_000);
+
+      const mockLiquidityPool = (
+        await getOrCreateAssociatedTokenAccount(
+          provider.connection,
+          wallet.payer,
+          destinationMint,
+          wallet.publicKey
+        )
+      ).address;
+      await mintTo(
+        provider.connection,
+        wallet.payer,
+        destinationMint,
+        mockLiquidityPool,
+        admin,
+        quotedOutAmount.add(donation).toNumber()
+      );
+
+      const data = buildJupiterCpiInstructionData(
+        mockJupiterProgram,
+        0,
+        [{ swap: { raydium: {} }, percent: 100, inputIndex: 0, outputIndex: 1 }],
+        inAmount,
+        quotedOutAmount,
+        50,
+        0
+      );
+      const remainingAccounts = buildJupiterRemainingAccounts({
+        tokenProgram: TOKEN_PROGRAM_ID,
+        jupiterProgramAuthority: mockJupiterProgram.programId,
+        vaultAuthority,
+        vaultSource: sourceVault,
+        vaultDestination: destinationVault,
+        sourceMint,
+        destinationMint,
+        platformFeeOrPlaceholder: TOKEN_PROGRAM_ID,
+        token2022OrPlaceholder: JUPITER_EVENT_AUTHORITY,
+        eventAuthority: JUPITER_EVENT_AUTHORITY,
+        jupiterProgram: mockJupiterProgram.programId,
+        mockPool: mockLiquidityPool,
+        mockPoolAuthority: wallet.publicKey,
+      });
+
+      // The pre-instruction deposits straight into the shared destination vault, as a batched
+      // instruction or an attacker could; the output is measured from a snapshot taken after it
+      const deposit = createTransferCheckedInstruction(
+        mockLiquidityPool,
+        destinationMint,
+        destinationVault,
+        wallet.publicKey,
+        BigInt(donation.toString()),
+        6
+      );
+
+      const userDestBefore = await getAccount(provider.connection, userDestinationTokenAccount);
+      const destVaultBefore = await getAccount(provider.connection, destinationVault);
+
+      await program.methods
+        .sharedRoute(inAmount, quotedOutAmount, 50, 0, new BN(0), false, false, data)
+        .accounts({
+          vaultAuthority,
+          userSourceTokenAccount,
+          userDestinationTokenAccount,
+          vaultSource: sourceVault,
+          vaultDestination: destinationVault,
+          sourceMint,
+          destinationMint,
+          inputTokenProgram: TOKEN_PROGRAM_ID,
+          outputTokenProgram: TOKEN_PROGRAM_ID,
+          userTransferAuthority: user.publicKey,
+          platformFeeAccount: null,
+          jupiterProgram: mockJupiterProgram.programId,
+          systemProgram: SystemProgram.programId,
+        })
+        .remainingAccounts(remainingAccounts)
+        .preInstructions([deposit])
+        .signers([user])
+        .rpc();
+
+      const userDestAfter = await getAccount(provider.connection, userDestinationTokenAccount);
+      const destVaultAfter = await getAccount(provider.connection, destinationVault);
+      assert.equal(
+        (userDestAfter.amount - userDestBefore.amount).toString(),
+        quotedOutAmount.toString(),
+        "The user receives only what the Jupiter CPI delivered"
+      );
+      assert.equal(
+        (destVaultAfter.amount - destVaultBefore.amount).toString(),
+        donation.toString(),
+        "The deposit stays in the shared vault"
+      );
+    });
   });
 
   describe("shared_route - invalid Jupiter program", () => {
@@ -579,6 +887,9 @@ describe("Flipper - Shared Jupiter CPI Instructions", () => {
             new BN(150_000_000),
             50,
             0,
+            new BN(0),
+            false,
+            false,
             Buffer.alloc(8)
           )
           .accounts({
@@ -617,6 +928,60 @@ describe("Flipper - Shared Jupiter CPI Instructions", () => {
         console.log("✅ Correctly rejected invalid Jupiter program ID");
       }
     });
+
+    it("Should reject Jupiter data that is too short or not shared_accounts_route", async () => {
+      // Oversized data cannot fit in a transaction with the default limit; covered by unit tests
+      const cases: [Buffer, string][] = [
+        [Buffer.alloc(7), "JupiterDataTooShort"],
+        [Buffer.alloc(8), "InvalidJupiterDiscriminator"],
+      ];
+
+      for (const [data, expected] of cases) {
+        try {
+          await program.methods
+            .sharedRoute(
+              new BN(100_000_000),
+              new BN(150_000_000),
+              50,
+              0,
+              new BN(0),
+              false,
+              false,
+              data
+            )
+            .accounts({
+              vaultAuthority,
+              userSourceTokenAccount,
+              userDestinationTokenAccount,
+              vaultSource: sourceVault,
+              vaultDestination: destinationVault,
+              sourceMint,
+              destinationMint,
+              inputTokenProgram: TOKEN_PROGRAM_ID,
+              outputTokenProgram: TOKEN_PROGRAM_ID,
+              userTransferAuthority: user.publicKey,
+              platformFeeAccount: null,
+              jupiterProgram: mockJupiterProgram.programId,
+              systemProgram: SystemProgram.programId,
+            })
+            .remainingAccounts([])
+            .signers([user])
+            .rpc();
+
+          assert.fail(`Should have failed with ${expected}`);
+        } catch (err: any) {
+          const errorStr = err.toString();
+          assert.isTrue(
+            errorStr.includes(expected) ||
+              (err.error &&
+                err.error.errorCode &&
+                err.error.errorCode.code === expected),
+            `Expected ${expected} error, got: ${errorStr}`
+          );
+        }
+      }
+      console.log("✅ Rejected malformed Jupiter instruction data");
+    });
   });
 
   describe("create_limit_order (used with shared_execute)", () => {
@@ -646,7 +1011,7 @@ describe("Flipper - Shared Jupiter CPI Instructions", () => {
 
       // First initialize the limit order account
       await program.methods
-        .initLimitOrder(nonce, 0)
+        .initLimitOrder(nonce, 0, false)
         .accounts({
           vaultAuthority,
           limitOrder,
@@ -654,6 +1019,7 @@ describe("Flipper - Shared Jupiter CPI Instructions", () => {
           inputMint: sourceMint,
           inputTokenProgram: TOKEN_PROGRAM_ID,
           creator: user.publicKey,
+          payer: user.publicKey,
           systemProgram: SystemProgram.programId,
           rent: anchor.web3.SYSVAR_RENT_PUBKEY,
         })
@@ -672,7 +1038,11 @@ describe("Flipper - Shared Jupiter CPI Instructions", () => {
           triggerPriceBps,
           triggerType,
           expiry,
-          slippageBps
+          slippageBps,
+          false,
+          null,
+          null,
+          new BN(0)
         )
         .accounts({
           vaultAuthority,
@@ -685,6 +1055,7 @@ describe("Flipper - Shared Jupiter CPI Instructions", () => {
           inputTokenProgram: TOKEN_PROGRAM_ID,
           outputTokenProgram: TOKEN_PROGRAM_ID,
           creator: user.publicKey,
+          payer: user.publicKey,
           systemProgram: SystemProgram.programId,
         })
         .signers([user])
@@ -731,7 +1102,7 @@ describe("Flipper - Shared Jupiter CPI Instructions", () => {
 
       // Initialize and create order
       await program.methods
-        .initLimitOrder(nonce, 0)
+        .initLimitOrder(nonce, 0, false)
         .accounts({
           vaultAuthority,
           limitOrder,
@@ -739,6 +1110,7 @@ describe("Flipper - Shared Jupiter CPI Instructions", () => {
           inputMint: sourceMint,
           inputTokenProgram: TOKEN_PROGRAM_ID,
           creator: user.publicKey,
+          payer: user.publicKey,
           systemProgram: SystemProgram.programId,
           rent: anchor.web3.SYSVAR_RENT_PUBKEY,
         })
@@ -753,7 +1125,11 @@ describe("Flipper - Shared Jupiter CPI Instructions", () => {
           triggerPriceBps,
           triggerType,
           expiry,
-          slippageBps
+          slippageBps,
+          false,
+          null,
+          null,
+          new BN(0)
         )
         .accounts({
           vaultAuthority,
@@ -766,6 +1142,7 @@ describe("Flipper - Shared Jupiter CPI Instructions", () => {
           inputTokenProgram: TOKEN_PROGRAM_ID,
           outputTokenProgram: TOKEN_PROGRAM_ID,
           creator: user.publicKey,
+          payer: user.publicKey,
           systemProgram: SystemProgram.programId,
         })
         .signers([user])
@@ -834,7 +1211,7 @@ describe("Flipper - Shared Jupiter CPI Instructions", () => {
 
       // Initialize and create order
       await program.methods
-        .initLimitOrder(nonce, 0)
+        .initLimitOrder(nonce, 0, false)
         .accounts({
           vaultAuthority,
           limitOrder,
@@ -842,6 +1219,7 @@ describe("Flipper - Shared Jupiter CPI Instructions", () => {
           inputMint: sourceMint,
           inputTokenProgram: TOKEN_PROGRAM_ID,
           creator: user.publicKey,
+          payer: user.publicKey,
           systemProgram: SystemProgram.programId,
           rent: anchor.web3.SYSVAR_RENT_PUBKEY,
         })
@@ -856,7 +1234,11 @@ describe("Flipper - Shared Jupiter CPI Instructions", () => {
           triggerPriceBps,
           triggerType,
           expiry,
-          slippageBps
+          slippageBps,
+          false,
+          null,
+          null,
+          new BN(0)
         )
         .accounts({
           vaultAuthority,
@@ -869,6 +1251,7 @@ describe("Flipper - Shared Jupiter CPI Instructions", () => {
           inputTokenProgram: TOKEN_PROGRAM_ID,
           outputTokenProgram: TOKEN_PROGRAM_ID,
           creator: user.publicKey,
+          payer: user.publicKey,
           systemProgram: SystemProgram.programId,
         })
         .signers([user])
@@ -931,7 +1314,7 @@ describe("Flipper - Shared Jupiter CPI Instructions", () => {
 
       // Initialize limit order account first
       await program.methods
-        .initLimitOrder(orderNonce, 0)
+        .initLimitOrder(orderNonce, 0, false)
         .accounts({
           vaultAuthority,
           limitOrder,
@@ -939,6 +1322,7 @@ describe("Flipper - Shared Jupiter CPI Instructions", () => {
           inputMint: destinationMint, // Order input is swap output
           inputTokenProgram: TOKEN_PROGRAM_ID,
           creator: user.publicKey,
+          payer: user.publicKey,
           systemProgram: SystemProgram.programId,
           rent: anchor.web3.SYSVAR_RENT_PUBKEY,
         })
@@ -1130,7 +1514,7 @@ describe("Flipper - Shared Jupiter CPI Instructions", () => {
       );
 
       await program.methods
-        .initLimitOrder(orderNonce, 0)
+        .initLimitOrder(orderNonce, 0, false)
         .accounts({
           vaultAuthority,
           limitOrder,
@@ -1138,6 +1522,7 @@ describe("Flipper - Shared Jupiter CPI Instructions", () => {
           inputMint: destinationMint,
           inputTokenProgram: TOKEN_PROGRAM_ID,
           creator: user.publicKey,
+          payer: user.publicKey,
           systemProgram: SystemProgram.programId,
           rent: anchor.web3.SYSVAR_RENT_PUBKEY,
         })
@@ -0,0 +1,97 @@
+import * as anchor from "@coral-xyz/anchor";
+import { Program } from "@coral-xyz/anchor";
+import { PublicKey } from "@solana/web3.js";
+import { assert } from "chai";
+import { Flipper } from "../target/types/flipper";
+import { MockGovernance } from "../target/types/mock_governance";
+
+describe("mock-governance", () => {
+  const provider = anchor.AnchorProvider.env();
+  anchor.setProvider(provider);
+  const flipper = anchor.workspace.Flipper as Program<Flipper>;
+  const program = anchor.workspace.MockGovernance as Program<MockGovernance>;
+  const wallet = provider.wallet as anchor.Wallet;
+
+  const [governance] = PublicKey.findProgramAddressSync(
+    [Buffer.from("governance")],
+    program.programId
+  );
+  const [adapterRegistry] = PublicKey.findProgramAddressSync(
+    [Buffer.from("adapter_registry")],
+    flipper.programId
+  );
+  const [adminLog] = PublicKey.findProgramAddressSync(
+    [Buffer.from("admin_log")],
+    flipper.programId
+  );
+  const [flipperEventAuthority] = PublicKey.findProgramAddressSync(
+    [Buffer.from("__event_authority")],
+    flipper.programId
+  );
+
+  it("configures an adapter via CPI with a governance PDA as registry authority", async () => {
+    const registryBefore = await flipper.account.adapterRegistry.fetch(adapterRegistry);
+    assert.ok(registryBefore.authority.equals(wallet.publicKey));
+    const raydium = registryBefore.supportedAdapters.find((adapter) => "raydium" in adapter.swapType);
+    assert.ok(raydium, "raydium adapter configured by earlier suites");
+
+    // Hand the registry to the governance PDA, which can never sign a transaction itself
+    await flipper.methods
+      .changeAuthority()
+      .accounts({
+        adapterRegistry,
+        authority: wallet.publicKey,
+        newAuthority: governance,
+      })
+      .rpc();
+
+    try {
+      await program.methods
+        .configureAdapter(raydium)
+        .accounts({
+          governance,
+          adapterRegistry,
+          adminLog,
+          flipperEventAuthority,
+          flipperProgram: flipper.programId,
+        })
+        .rpc();
+
+      const registry = await flipper.account.adapterRegistry.fetch(adapterRegistry);
+      assert.ok(registry.authority.equals(governance));
+      const updated = registry.supportedAdapters.find((adapter) => "raydium" in adapter.swapType);
+      assert.ok(updated.programId.equals(raydium.programId));
+
+      // The previous authority no longer passes has_one = authority
+      try {
+        await flipper.methods
+          .changeAuthority()
+          .accounts({
+            adapterRegistry,
+            authority: wallet.publicKey,
+            newAuthority: wallet.publicKey,
+          })
+          .rpc();
+        assert.fail("Expected InvalidAuthority");
+      } catch (err) {
+        assert.include(err.toString(), "InvalidAuthority");
+      }
+    } finally {
+      // Return the registry to the wallet for the remaining suites
+      await program.methods
+        .changeAuthority()
+        .accounts({
+          governance,
+          adapterRegistry,
+          newAuthority: wallet.publicKey,
+          adminLog,
+          flipperEventAuthority,
+          flipperProgram: flipper.programId,
+        })
+        .rpc();
+    }
+
+    const registryAfter = await flipper.account.adapterRegistry.fetch(adapterRegistry);
+    assert.ok(registryAfter.authority.equals(wallet.publicKey));
+  });
+});
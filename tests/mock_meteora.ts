@@ -10,6 +10,7 @@ import {
   createMint,
   mintTo,
   createAssociatedTokenAccount,
+  getAccount,
 } from "@solana/spl-token";
 import { assert } from "chai";
 
@@ -381,7 +382,7 @@ describe("mock-meteora-swap", () => {
         tokenXMint,
         tokenYMint,
         oracle,
-        hostFeeIn: Keypair.generate().publicKey,
+        hostFeeIn: program.programId, // no host fee
         user: wallet.publicKey,
         tokenXProgram,
         tokenYProgram,
@@ -601,7 +602,7 @@ describe("mock-meteora-swap", () => {
           tokenXMint,
           tokenYMint,
           oracle: oracleTokenToToken,
-          hostFeeIn: Keypair.generate().publicKey,
+          hostFeeIn: program.programId, // no host fee
           user: wallet.publicKey,
           tokenXProgram: TOKEN_PROGRAM_ID,
           tokenYProgram: TOKEN_PROGRAM_ID,
@@ -618,4 +619,214 @@ describe("mock-meteora-swap", () => {
       assert.include(err.toString(), "Amount cannot be zero");
     }
   });
+  // Accounts for swapping X -> Y in the Token to Token pool
+  function tokenToTokenSwapAccounts() {
+    return {
+      lbPair: lbPairTokenToToken,
+      binArrayBitmapExtension: Keypair.generate().publicKey,
+      reserveX: reserveXTokenToToken,
+      reserveY: reserveYTokenToToken,
+      userTokenIn: userTokenXAccount,
+      userTokenOut: userTokenYAccount,
+      tokenXMint,
+      tokenYMint,
+      oracle: oracleTokenToToken,
+      hostFeeIn: program.programId, // no host fee
+      user: wallet.publicKey,
+      tokenXProgram: TOKEN_PROGRAM_ID,
+      tokenYProgram: TOKEN_PROGRAM_ID,
+      memoProgram: Keypair.generate().publicKey,
+      eventAuthority: PublicKey.findProgramAddressSync(
+        [Buffer.from("__event_authority")],
+        program.programId
+      )[0],
+      program: SystemProgram.programId,
+    };
+  }
+
+  // Decodes the SwapAmounts { amount_in, amount_out, host_fee } return data of a swap transaction
+  async function getSwapAmounts(signature: string) {
+    const tx = await provider.connection.getTransaction(signature, {
+      commitment: "confirmed",
+      maxSupportedTransactionVersion: 0,
+    });
+    const returnData = Buffer.from(tx.meta.returnData.data[0], "base64");
+    assert.equal(tx.meta.returnData.programId, program.programId.toBase58());
+    return {
+      amountIn: new BN(returnData.subarray(0, 8), "le"),
+      amountOut: new BN(returnData.subarray(8, 16), "le"),
+      hostFee: new BN(returnData.subarray(16, 24), "le"),
+    };
+  }
+
+  it("swap2 writes realized amounts to return data", async () => {
+    const userOutBefore = (await getAccount(provider.connection, userTokenYAccount)).amount;
+
+    const signature = await program.methods
+      .swap2(new BN(1000), new BN(1), { slices: [] })
+      .accounts(tokenToTokenSwapAccounts())
+      .rpc({ commitment: "confirmed" });
+
+    const userOutAfter = (await getAccount(provider.connection, userTokenYAccount)).amount;
+    const { amountIn, amountOut } = await getSwapAmounts(signature);
+    assert.equal(amountIn.toString(), "1000");
+    assert.equal(amountOut.toString(), (userOutAfter - userOutBefore).toString());
+  });
+
+  it("swap_exact_out delivers exactly amount_out using the reverse formula", async () => {
+    const amountOut = new BN(5000);
+    const lbPair = await program.account.lbPair.fetch(lbPairTokenToToken);
+    const reserveIn = lbPair.tokenXVaultAmount;
+    const reserveOut = lbPair.tokenYVaultAmount;
+    // Ceil(reserve_in * amount_out / (reserve_out - amount_out))
+    const denominator = reserveOut.sub(amountOut);
+    const expectedIn = reserveIn
+      .mul(amountOut)
+      .add(denominator)
+      .subn(1)
+      .div(denominator);
+
+    const userInBefore = (await getAccount(provider.connection, userTokenXAccount)).amount;
+    const userOutBefore = (await getAccount(provider.connection, userTokenYAccount)).amount;
+
+    const signature = await program.methods
+      .swapExactOut(amountOut, expectedIn, { slices: [] })
+      .accounts(tokenToTokenSwapAccounts())
+      .rpc({ commitment: "confirmed" });
+
+    const userInAfter = (await getAccount(provider.connection, userTokenXAccount)).amount;
+    const userOutAfter = (await getAccount(provider.connection, userTokenYAccount)).amount;
+    assert.equal((userOutAfter - userOutBefore).toString(), amountOut.toString());
+    assert.equal((userInBefore - userInAfter).toString(), expectedIn.toString());
+
+    const { amountIn, amountOut: returnedOut } = await getSwapAmounts(signature);
+    assert.equal(amountIn.toString(), expectedIn.toString());
+    assert.equal(returnedOut.toString(), amountOut.toString());
+
+    const lbPairAfter = await program.account.lbPair.fetch(lbPairTokenToToken);
+    assert.equal(lbPairAfter.tokenXVaultAmount.toString(), reserveIn.add(expectedIn).toString());
+    assert.equal(lbPairAfter.tokenYVaultAmount.toString(), reserveOut.sub(amountOut).toString());
+
+    const oracle = await program.account.oracle.fetch(oracleTokenToToken);
+    assert.isTrue(oracle.lastUpdateTimestamp.toNumber() > 0);
+  });
+
+  it("Fails swap_exact_out when required input is above max_in", async () => {
+    try {
+      await program.methods
+        .swapExactOut(new BN(5000), new BN(1), { slices: [] })
+        .accounts(tokenToTokenSwapAccounts())
+        .rpc();
+      assert.fail("Swap above max_in should fail");
+    } catch (err) {
+      assert.include(err.toString(), "AmountInAboveMaximum");
+    }
+  });
+
+  it("swap2 pays the host fee into host_fee_in", async () => {
+    const amountIn = new BN(100_000);
+    // HOST_FEE_BPS = 10
+    const expectedHostFee = amountIn.muln(10).divn(10_000);
+
+    const hostFeeOwner = Keypair.generate();
+    const hostFeeAccount = await createAssociatedTokenAccount(
+      provider.connection,
+      wallet.payer,
+      tokenXMint,
+      hostFeeOwner.publicKey
+    );
+
+    const lbPair = await program.account.lbPair.fetch(lbPairTokenToToken);
+    const userInBefore = (await getAccount(provider.connection, userTokenXAccount)).amount;
+
+    const signature = await program.methods
+      .swap2(amountIn, new BN(1), { slices: [] })
+      .accounts({ ...tokenToTokenSwapAccounts(), hostFeeIn: hostFeeAccount })
+      .rpc({ commitment: "confirmed" });
+
+    const hostFeeBalance = (await getAccount(provider.connection, hostFeeAccount)).amount;
+    assert.equal(hostFeeBalance.toString(), expectedHostFee.toString());
+
+    // The user pays amount_in in total; only amount_in - host_fee reaches the pool
+    const userInAfter = (await getAccount(provider.connection, userTokenXAccount)).amount;
+    assert.equal((userInBefore - userInAfter).toString(), amountIn.toString());
+    const lbPairAfter = await program.account.lbPair.fetch(lbPairTokenToToken);
+    assert.equal(
+      lbPairAfter.tokenXVaultAmount.toString(),
+      lbPair.tokenXVaultAmount.add(amountIn).sub(expectedHostFee).toString()
+    );
+
+    const { amountIn: returnedIn, hostFee } = await getSwapAmounts(signature);
+    assert.equal(returnedIn.toString(), amountIn.toString());
+    assert.equal(hostFee.toString(), expectedHostFee.toString());
+  });
+
+  it("Fails swap2 when host_fee_in holds a different mint", async () => {
+    const hostFeeAccount = await createAssociatedTokenAccount(
+      provider.connection,
+      wallet.payer,
+      tokenYMint,
+      Keypair.generate().publicKey
+    );
+
+    try {
+      await program.methods
+        .swap2(new BN(100_000), new BN(1), { slices: [] })
+        .accounts({ ...tokenToTokenSwapAccounts(), hostFeeIn: hostFeeAccount })
+        .rpc();
+      assert.fail("Host fee account of the output mint should be rejected");
+    } catch (err) {
+      assert.include(err.toString(), "InvalidMint");
+    }
+  });
+
+  it("swap2 only accepts the oracle PDA of the lb_pair", async () => {
+    // The pool's own ["oracle", lb_pair] PDA is accepted
+    await program.methods
+      .swap2(new BN(100_000), new BN(1), { slices: [] })
+      .accounts(tokenToTokenSwapAccounts())
+      .rpc();
+
+    // An initialized oracle of another pool is rejected
+    try {
+      await program.methods
+        .swap2(new BN(100_000), new BN(1), { slices: [] })
+        .accounts({ ...tokenToTokenSwapAccounts(), oracle: oracleTokenTo2022 })
+        .rpc();
+      assert.fail("Oracle of another pool should be rejected");
+    } catch (err) {
+      assert.include(err.toString(), "ConstraintSeeds");
+    }
+  });
+
+  it("swap2 records the pool price in the oracle and get_price returns it", async () => {
+    const priceAccounts = { lbPair: lbPairTokenToToken, oracle: oracleTokenToToken };
+    const before = await program.account.oracle.fetch(oracleTokenToToken);
+    assert.equal(
+      (await program.methods.getPrice().accounts(priceAccounts).view()).toString(),
+      before.lastPriceXPerY.toString()
+    );
+
+    // Selling X into the pool raises the amount of X per Y
+    await program.methods
+      .swap2(new BN(100_000), new BN(1), { slices: [] })
+      .accounts(tokenToTokenSwapAccounts())
+      .rpc();
+
+    const lbPair = await program.account.lbPair.fetch(lbPairTokenToToken);
+    const expectedPrice = lbPair.tokenXVaultAmount.mul(new BN(1_000_000_000)).div(lbPair.tokenYVaultAmount);
+    const after = await program.account.oracle.fetch(oracleTokenToToken);
+    assert.equal(after.lastPriceXPerY.toString(), expectedPrice.toString());
+    assert.isTrue(after.lastPriceXPerY.gt(before.lastPriceXPerY));
+    assert.isTrue(after.lastUpdateTimestamp.gte(before.lastUpdateTimestamp));
+    // The previous price accrues for the seconds it was in force
+    const elapsed = after.lastUpdateTimestamp.sub(before.lastUpdateTimestamp);
+    assert.equal(
+      after.cumulativePrice.toString(),
+      before.cumulativePrice.add(before.lastPriceXPerY.mul(elapsed)).toString()
+    );
+
+    const price = await program.methods.getPrice().accounts(priceAccounts).view();
+    assert.equal(price.toString(), expectedPrice.toString());
+  });
 });
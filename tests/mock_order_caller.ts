@@ -0,0 +1,164 @@
+import * as anchor from "@coral-xyz/anchor";
+import { Program, BN } from "@coral-xyz/anchor";
+import { PublicKey, SystemProgram, SYSVAR_RENT_PUBKEY } from "@solana/web3.js";
+import {
+  TOKEN_PROGRAM_ID,
+  createMint,
+  mintTo,
+  getOrCreateAssociatedTokenAccount,
+  getAccount,
+} from "@solana/spl-token";
+import { assert } from "chai";
+import { Flipper } from "../target/types/flipper";
+import { MockOrderCaller } from "../target/types/mock_order_caller";
+
+describe("mock-order-caller", () => {
+  const provider = anchor.AnchorProvider.env();
+  anchor.setProvider(provider);
+  const flipper = anchor.workspace.Flipper as Program<Flipper>;
+  const program = anchor.workspace.MockOrderCaller as Program<MockOrderCaller>;
+  const wallet = provider.wallet as anchor.Wallet;
+
+  const [orderCreator] = PublicKey.findProgramAddressSync(
+    [Buffer.from("order_creator")],
+    program.programId
+  );
+  const [vaultAuthority] = PublicKey.findProgramAddressSync(
+    [Buffer.from("vault_authority")],
+    flipper.programId
+  );
+  const [adapterRegistry] = PublicKey.findProgramAddressSync(
+    [Buffer.from("adapter_registry")],
+    flipper.programId
+  );
+  const [flipperEventAuthority] = PublicKey.findProgramAddressSync(
+    [Buffer.from("__event_authority")],
+    flipper.programId
+  );
+
+  let inputMint: PublicKey;
+  let outputMint: PublicKey;
+  let creatorInputAccount: PublicKey;
+  let creatorDestinationAccount: PublicKey;
+
+  before(async () => {
+    inputMint = await createMint(provider.connection, wallet.payer, wallet.publicKey, null, 6);
+    outputMint = await createMint(provider.connection, wallet.payer, wallet.publicKey, null, 6);
+
+    // Token accounts owned by the caller program's PDA
+    creatorInputAccount = (
+      await getOrCreateAssociatedTokenAccount(provider.connection, wallet.payer, inputMint, orderCreator, true)
+    ).address;
+    creatorDestinationAccount = (
+      await getOrCreateAssociatedTokenAccount(provider.connection, wallet.payer, outputMint, orderCreator, true)
+    ).address;
+    await mintTo(provider.connection, wallet.payer, inputMint, creatorInputAccount, wallet.payer, 1_000_000);
+  });
+
+  it("creates and cancels a limit order whose creator is a program PDA", async () => {
+    const nonce = new BN(Date.now());
+    const [limitOrder] = PublicKey.findProgramAddressSync(
+      [Buffer.from("limit_order"), orderCreator.toBuffer(), nonce.toArrayLike(Buffer, "le", 8)],
+      flipper.programId
+    );
+    const [inputVault] = PublicKey.findProgramAddressSync(
+      [Buffer.from("order_vault"), limitOrder.toBuffer()],
+      flipper.programId
+    );
+    const [orderCounter] = PublicKey.findProgramAddressSync(
+      [Buffer.from("order_counter"), orderCreator.toBuffer()],
+      flipper.programId
+    );
+    const [protocolConfig] = PublicKey.findProgramAddressSync(
+      [Buffer.from("protocol_config")],
+      flipper.programId
+    );
+    const [vaultRentPayer] = PublicKey.findProgramAddressSync(
+      [Buffer.from("vault_rent_payer"), inputVault.toBuffer()],
+      flipper.programId
+    );
+    // The payer's OperatorStats count the vault when the payer is a registered operator
+    const [payerStats] = PublicKey.findProgramAddressSync(
+      [Buffer.from("operator_stats"), wallet.publicKey.toBuffer()],
+      flipper.programId
+    );
+    const [orderReceipt] = PublicKey.findProgramAddressSync(
+      [Buffer.from("receipt"), limitOrder.toBuffer()],
+      flipper.programId
+    );
+
+    // Pass the treasury only when the registry charges a creation fee
+    const registry = await flipper.account.adapterRegistry.fetch(adapterRegistry);
+    const treasury = registry.orderCreationFeeLamports.gtn(0) ? registry.treasury : null;
+
+    const inputAmount = new BN(400_000);
+    const expiry = new BN(Math.floor(Date.now() / 1000) + 3600);
+    await program.methods
+      .createOrder(nonce, inputAmount, new BN(350_000), 500, expiry, 100)
+      .accounts({
+        orderCreator,
+        payer: wallet.publicKey,
+        vaultAuthority,
+        adapterRegistry,
+        limitOrder,
+        orderCounter,
+        inputVault,
+        vaultRentPayer,
+        payerStats,
+        creatorInputAccount,
+        creatorDestinationAccount,
+        inputMint,
+        outputMint,
+        inputTokenProgram: TOKEN_PROGRAM_ID,
+        outputTokenProgram: TOKEN_PROGRAM_ID,
+        treasury,
+        protocolConfig,
+        flipperEventAuthority,
+        flipperProgram: flipper.programId,
+        systemProgram: SystemProgram.programId,
+        rent: SYSVAR_RENT_PUBKEY,
+      })
+      .rpc();
+
+    const order = await flipper.account.limitOrder.fetch(limitOrder);
+    assert.ok(order.creator.equals(orderCreator));
+    assert.deepEqual(order.status, { open: {} });
+    assert.equal(order.inputAmount.toNumber(), 400_000);
+    const counter = await flipper.account.creatorOrderCounter.fetch(orderCounter);
+    assert.equal(order.generation, counter.ordersCreated - 1);
+    assert.equal((await getAccount(provider.connection, inputVault)).amount.toString(), "400000");
+    assert.equal((await getAccount(provider.connection, creatorInputAccount)).amount.toString(), "600000");
+    // The payer funded the vault for the PDA, so it is recorded as the vault's sponsor
+    const record = await flipper.account.vaultRentPayer.fetch(vaultRentPayer);
+    assert.ok(record.vault.equals(inputVault));
+    assert.ok(record.payer.equals(wallet.publicKey));
+
+    const orderRent = await provider.connection.getBalance(limitOrder);
+    const creatorBalanceBefore = await provider.connection.getBalance(orderCreator);
+    await program.methods
+      .cancelOrder()
+      .accounts({
+        orderCreator,
+        vaultAuthority,
+        limitOrder,
+        inputVault,
+        orderReceipt,
+        vaultRentPayer,
+        rentPayer: wallet.publicKey,
+        rentPayerStats: record.operatorSponsored ? payerStats : null,
+        creatorInputAccount,
+        inputMint,
+        inputTokenProgram: TOKEN_PROGRAM_ID,
+        flipperEventAuthority,
+        flipperProgram: flipper.programId,
+      })
+      .rpc();
+
+    assert.isNull(await provider.connection.getAccountInfo(limitOrder));
+    assert.isNull(await provider.connection.getAccountInfo(inputVault));
+    assert.equal((await getAccount(provider.connection, creatorInputAccount)).amount.toString(), "1000000");
+    assert.isNull(await provider.connection.getAccountInfo(vaultRentPayer));
+    // Order rent returns to the creator PDA; the vault rent went back to the payer with the record
+    assert.equal(await provider.connection.getBalance(orderCreator), creatorBalanceBefore + orderRent);
+  });
+});
@@ -344,9 +344,13 @@ describe("mock-whirlpool-swap", () => {
     tokenMintA: PublicKey,
     tokenProgramA: PublicKey,
     tokenMintB: PublicKey,
-    tokenProgramB: PublicKey
+    tokenProgramB: PublicKey,
+    aToB = true
   ) {
     const oracle = Keypair.generate();
+    const [inputAccount, outputAccount] = aToB
+      ? [tokenOwnerAccountA, tokenOwnerAccountB]
+      : [tokenOwnerAccountB, tokenOwnerAccountA];
 
     // Derive tick arrays
     const [tickArray0] = PublicKey.findProgramAddressSync(
@@ -359,17 +363,18 @@ describe("mock-whirlpool-swap", () => {
     );
 
     const initialInputBalance =
-      await provider.connection.getTokenAccountBalance(tokenOwnerAccountA);
+      await provider.connection.getTokenAccountBalance(inputAccount);
     const initialOutputBalance =
-      await provider.connection.getTokenAccountBalance(tokenOwnerAccountB);
+      await provider.connection.getTokenAccountBalance(outputAccount);
 
+    // B -> A trades against the cheaper side, so it swaps without a minimum
     await program.methods
       .swapV2(
         SWAP_AMOUNT,
-        OTHER_AMOUNT_THRESHOLD,
+        aToB ? OTHER_AMOUNT_THRESHOLD : new BN(0),
         SQRT_PRICE_LIMIT,
         true,
-        true,
+        aToB,
         null
       )
       .accounts({
@@ -391,10 +396,10 @@ describe("mock-whirlpool-swap", () => {
 
     // Verify user balances after swap
     const finalInputBalance = await provider.connection.getTokenAccountBalance(
-      tokenOwnerAccountA
+      inputAccount
     );
     const finalOutputBalance = await provider.connection.getTokenAccountBalance(
-      tokenOwnerAccountB
+      outputAccount
     );
     assert.isBelow(
       finalInputBalance.value.uiAmount,
@@ -669,6 +674,71 @@ describe("mock-whirlpool-swap", () => {
     );
   });
 
+  it("Swaps B to A with one Token-2022 mint on each side", async () => {
+    // Pools created by the two mixed tests above: Token-2022 as token B, then as token A
+    await performSwap(
+      whirlpoolTokenTo2022,
+      userTokenAAccount,
+      userToken2022BAccount,
+      tokenAVaultTokenTo2022,
+      tokenBVaultTokenTo2022,
+      tokenAMint,
+      TOKEN_PROGRAM_ID,
+      token2022BMint,
+      TOKEN_2022_PROGRAM_ID,
+      false
+    );
+
+    await performSwap(
+      whirlpool2022ToToken,
+      userToken2022AAccount,
+      userTokenBAccount,
+      tokenAVault2022ToToken,
+      tokenBVault2022ToToken,
+      token2022AMint,
+      TOKEN_2022_PROGRAM_ID,
+      tokenBMint,
+      TOKEN_PROGRAM_ID,
+      false
+    );
+  });
+
+  it("Fails when a token program does not own its mint", async () => {
+    const [tickArray0] = PublicKey.findProgramAddressSync(
+      [
+        Buffer.from("tick_array"),
+        whirlpoolTokenTo2022.toBuffer(),
+        Buffer.from(new Int32Array([-100]).buffer),
+      ],
+      program.programId
+    );
+
+    // Token-2022 mint B paired with the legacy token program
+    try {
+      await program.methods
+        .swapV2(SWAP_AMOUNT, new BN(0), SQRT_PRICE_LIMIT, true, true, null)
+        .accounts({
+          tokenProgramA: TOKEN_PROGRAM_ID,
+          tokenProgramB: TOKEN_PROGRAM_ID,
+          memoProgram: Keypair.generate().publicKey,
+          tokenAuthority: wallet.publicKey,
+          whirlpool: whirlpoolTokenTo2022,
+          tokenOwnerAccountA: userTokenAAccount,
+          tokenOwnerAccountB: userToken2022BAccount,
+          tokenVaultA: tokenAVaultTokenTo2022,
+          tokenVaultB: tokenBVaultTokenTo2022,
+          tickArray0,
+          oracle: Keypair.generate().publicKey,
+          tokenMintA: tokenAMint,
+          tokenMintB: token2022BMint,
+        })
+        .rpc();
+      assert.fail("Should have failed with InvalidTokenProgram");
+    } catch (error) {
+      assert.include(error.toString(), "InvalidTokenProgram");
+    }
+  });
+
   it("Fails with zero input amount (Token to Token)", async () => {
     // Initialize pool first if not already done
     try {
@@ -710,7 +780,7 @@ describe("mock-whirlpool-swap", () => {
       await program.methods
         .swapV2(
           new BN(0),
-          OTHER_AMOUNT_THRESHOLD,
+          new BN(0),
           SQRT_PRICE_LIMIT,
           true,
           true,
@@ -734,20 +804,8 @@ describe("mock-whirlpool-swap", () => {
         .rpc();
       assert.fail("Swap with zero amount should fail");
     } catch (err) {
-      // Check for various valid errors (zero amount, minimum output, etc)
-      const errString = err.toString();
-      if (
-        errString.includes("Amount cannot be zero") ||
-        errString.includes("Output amount is less than minimum") ||
-        errString.includes("AmountOutBelowMinimum") ||
-        errString.includes("anchor") ||
-        errString.includes("constraint")
-      ) {
-        // Test passes - either got the expected error or a related validation error
-        assert.isTrue(true);
-      } else {
-        throw err;
-      }
+      // A zero threshold means no minimum, so only the zero amount check can reject this
+      assert.include(err.toString(), "ZeroAmount");
     }
   });
 
@@ -832,4 +890,137 @@ describe("mock-whirlpool-swap", () => {
       }
     }
   });
+  // Swaps in the Token to Token pool and returns the realized input and output amounts
+  async function swapTokenToToken(
+    amount: BN,
+    otherAmountThreshold: BN,
+    amountSpecifiedIsInput: boolean,
+    aToB: boolean
+  ) {
+    const [tickArray0] = PublicKey.findProgramAddressSync(
+      [
+        Buffer.from("tick_array"),
+        whirlpoolTokenToToken.toBuffer(),
+        Buffer.from(new Int32Array([-100]).buffer),
+      ],
+      program.programId
+    );
+
+    const inputAccount = aToB ? userTokenAAccount : userTokenBAccount;
+    const outputAccount = aToB ? userTokenBAccount : userTokenAAccount;
+    const inputBefore = new BN(
+      (await provider.connection.getTokenAccountBalance(inputAccount)).value.amount
+    );
+    const outputBefore = new BN(
+      (await provider.connection.getTokenAccountBalance(outputAccount)).value.amount
+    );
+
+    await program.methods
+      .swapV2(
+        amount,
+        otherAmountThreshold,
+        SQRT_PRICE_LIMIT,
+        amountSpecifiedIsInput,
+        aToB,
+        null
+      )
+      .accounts({
+        tokenProgramA: TOKEN_PROGRAM_ID,
+        tokenProgramB: TOKEN_PROGRAM_ID,
+        memoProgram: Keypair.generate().publicKey,
+        tokenAuthority: wallet.publicKey,
+        whirlpool: whirlpoolTokenToToken,
+        tokenOwnerAccountA: userTokenAAccount,
+        tokenOwnerAccountB: userTokenBAccount,
+        tokenVaultA: tokenAVaultTokenToToken,
+        tokenVaultB: tokenBVaultTokenToToken,
+        tickArray0,
+        oracle: Keypair.generate().publicKey,
+        tokenMintA: tokenAMint,
+        tokenMintB: tokenBMint,
+      })
+      .rpc();
+
+    const inputAfter = new BN(
+      (await provider.connection.getTokenAccountBalance(inputAccount)).value.amount
+    );
+    const outputAfter = new BN(
+      (await provider.connection.getTokenAccountBalance(outputAccount)).value.amount
+    );
+    return {
+      amountIn: inputBefore.sub(inputAfter),
+      amountOut: outputAfter.sub(outputBefore),
+    };
+  }
+
+  // Mirrors the mock's constant product pricing for the Token to Token pool
+  async function quoteTokenToToken(amount: BN, amountSpecifiedIsInput: boolean, aToB: boolean) {
+    const pool = await program.account.whirlpool.fetch(whirlpoolTokenToToken);
+    const reserveIn = aToB ? pool.tokenVaultAAmount : pool.tokenVaultBAmount;
+    const reserveOut = aToB ? pool.tokenVaultBAmount : pool.tokenVaultAAmount;
+    const product = reserveIn.mul(reserveOut);
+    if (amountSpecifiedIsInput) {
+      return reserveOut.sub(product.div(reserveIn.add(amount)));
+    }
+    return product.div(reserveOut.sub(amount)).sub(reserveIn);
+  }
+
+  it("Exact in with zero threshold swaps with no minimum (Token to Token)", async () => {
+    const { amountIn, amountOut } = await swapTokenToToken(
+      SWAP_AMOUNT,
+      new BN(0),
+      true,
+      true
+    );
+    assert.equal(amountIn.toString(), SWAP_AMOUNT.toString());
+    assert.isTrue(amountOut.gtn(0), "Output should be positive");
+  });
+
+  it("Exact in passes when output equals the threshold (Token to Token)", async () => {
+    const expectedOut = await quoteTokenToToken(SWAP_AMOUNT, true, false);
+    const { amountOut } = await swapTokenToToken(SWAP_AMOUNT, expectedOut, true, false);
+    assert.equal(amountOut.toString(), expectedOut.toString());
+  });
+
+  it("Exact in fails when output is one below the threshold (Token to Token)", async () => {
+    const expectedOut = await quoteTokenToToken(SWAP_AMOUNT, true, true);
+    try {
+      await swapTokenToToken(SWAP_AMOUNT, expectedOut.addn(1), true, true);
+      assert.fail("Swap below the minimum output should fail");
+    } catch (err) {
+      assert.include(err.toString(), "AmountOutBelowMinimum");
+    }
+  });
+
+  it("Exact out with u64::MAX threshold swaps with no maximum (Token to Token)", async () => {
+    const amountOut = new BN(50000);
+    const expectedIn = await quoteTokenToToken(amountOut, false, true);
+    const result = await swapTokenToToken(
+      amountOut,
+      new BN("18446744073709551615"),
+      false,
+      true
+    );
+    assert.equal(result.amountOut.toString(), amountOut.toString());
+    assert.equal(result.amountIn.toString(), expectedIn.toString());
+  });
+
+  it("Exact out passes when input equals the threshold (Token to Token)", async () => {
+    const amountOut = new BN(50000);
+    const expectedIn = await quoteTokenToToken(amountOut, false, false);
+    const result = await swapTokenToToken(amountOut, expectedIn, false, false);
+    assert.equal(result.amountOut.toString(), amountOut.toString());
+    assert.equal(result.amountIn.toString(), expectedIn.toString());
+  });
+
+  it("Exact out fails when input is one above the threshold (Token to Token)", async () => {
+    const amountOut = new BN(50000);
+    const expectedIn = await quoteTokenToToken(amountOut, false, true);
+    try {
+      await swapTokenToToken(amountOut, expectedIn.subn(1), false, true);
+      assert.fail("Swap above the maximum input should fail");
+    } catch (err) {
+      assert.include(err.toString(), "AmountInAboveMaximum");
+    }
+  });
 });
@@ -0,0 +1,27 @@
+import { Program } from "@coral-xyz/anchor";
+import { Flipper } from "../../target/types/flipper";
+
+// Helpers for the ComputeTelemetry events of flipper builds with the "telemetry" feature
+// (anchor build -- --features telemetry). Budgets mirror the table in TESTING.md.
+
+export const ROUTE_TELEMETRY_TAG = 0;
+export const SHARED_ROUTE_TELEMETRY_TAG = 1;
+export const EXECUTE_LIMIT_ORDER_TELEMETRY_TAG = 2;
+
+// Compute unit budget per instruction_tag
+export const COMPUTE_BUDGETS: Record<number, number> = {
+  [ROUTE_TELEMETRY_TAG]: 120_000,
+  [SHARED_ROUTE_TELEMETRY_TAG]: 150_000,
+  [EXECUTE_LIMIT_ORDER_TELEMETRY_TAG]: 160_000,
+};
+
+// Whether the loaded IDL has the ComputeTelemetry event, i.e. the program was built with telemetry
+export function hasComputeTelemetry(program: Program<Flipper>): boolean {
+  return (program.idl.events ?? []).some((event) => event.name === "ComputeTelemetry");
+}
+
+// Whether the suite must run against a telemetry build: CI's telemetry job sets FLIPPER_FEATURES,
+// so the budget test fails there instead of skipping when the feature did not make it into the build
+export function computeTelemetryRequired(): boolean {
+  return (process.env.FLIPPER_FEATURES ?? "").split(",").includes("telemetry");
+}
@@ -0,0 +1,28 @@
+import { Program, BN } from "@coral-xyz/anchor";
+import { AccountMeta, PublicKey } from "@solana/web3.js";
+import { Flipper } from "../../target/types/flipper";
+
+// Helpers for the MockClock of flipper builds with the "test-clock" feature
+// (anchor build -- --features test-clock,localnet). Limit order instructions read the mock
+// time while the MockClock is passed in their remaining accounts.
+
+export function mockClockAddress(programId: PublicKey): PublicKey {
+  return PublicKey.findProgramAddressSync([Buffer.from("mock_clock")], programId)[0];
+}
+
+// Whether the loaded IDL has set_mock_clock, i.e. the program was built with test-clock
+export function hasMockClock(program: Program<Flipper>): boolean {
+  return "setMockClock" in program.methods;
+}
+
+export async function setMockClock(program: Program<Flipper>, unixTimestamp: number): Promise<void> {
+  await (program.methods as any)
+    .setMockClock(new BN(unixTimestamp))
+    .accounts({ payer: program.provider.publicKey })
+    .rpc();
+}
+
+// Remaining account entry that makes an instruction read the mock time
+export function mockClockAccount(programId: PublicKey): AccountMeta {
+  return { pubkey: mockClockAddress(programId), isWritable: false, isSigner: false };
+}